@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Second-level labels that sit under a two-letter country TLD and act as
+/// part of the suffix (`example.co.uk`, `example.com.au`).
+const COUNTRY_SECOND_LEVELS: &[&str] = &["ac", "co", "com", "edu", "gov", "net", "or", "org"];
+
+/// The "family" a domain's provider history is filed under: its registrable
+/// domain, so `api.example.com` and `www.example.com` share what was learned
+/// about `example.com`. A heuristic rather than a public-suffix lookup;
+/// IP addresses and single-label hosts are their own family.
+pub fn domain_family(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+    if domain.parse::<std::net::IpAddr>().is_ok() {
+        return domain;
+    }
+    let labels: Vec<&str> = domain.split('.').filter(|l| !l.is_empty()).collect();
+    let keep = match labels.as_slice() {
+        [.., second, tld] if tld.len() == 2 && COUNTRY_SECOND_LEVELS.contains(second) => 3,
+        _ => 2,
+    };
+    labels[labels.len().saturating_sub(keep)..].join(".")
+}
+
+/// Accumulated yield of one provider for one domain family: how many fetches
+/// it has served, how many URLs they returned, and how long they took.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProviderYield {
+    pub fetches: u64,
+    pub urls: u64,
+    pub elapsed_ms: u64,
+}
+
+impl ProviderYield {
+    /// A single fetch's worth of history.
+    pub fn from_fetch(urls: usize, elapsed: Duration) -> Self {
+        Self {
+            fetches: 1,
+            urls: urls as u64,
+            elapsed_ms: elapsed.as_millis() as u64,
+        }
+    }
+
+    /// Fold another record into this one.
+    pub fn merge(&mut self, other: &ProviderYield) {
+        self.fetches += other.fetches;
+        self.urls += other.urls;
+        self.elapsed_ms += other.elapsed_ms;
+    }
+
+    /// Mean URLs returned per fetch.
+    pub fn urls_per_fetch(&self) -> f64 {
+        if self.fetches == 0 {
+            0.0
+        } else {
+            self.urls as f64 / self.fetches as f64
+        }
+    }
+
+    /// Mean fetch latency in milliseconds.
+    pub fn latency_ms(&self) -> f64 {
+        if self.fetches == 0 {
+            0.0
+        } else {
+            self.elapsed_ms as f64 / self.fetches as f64
+        }
+    }
+}
+
+/// Order providers for `--smart-order`: historically productive providers
+/// first (most URLs per fetch, faster first on a tie), then providers with
+/// no history yet, then those that have only ever come back empty. Returns
+/// indices into `provider_names`; the sort is stable, so providers that
+/// compare equal keep their configured order.
+pub fn rank_providers(
+    provider_names: &[String],
+    history: &HashMap<String, ProviderYield>,
+) -> Vec<usize> {
+    let tier = |name: &String| match history.get(name) {
+        Some(y) if y.urls > 0 => 0,
+        None => 1,
+        Some(_) => 2,
+    };
+    let mut order: Vec<usize> = (0..provider_names.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (name_a, name_b) = (&provider_names[a], &provider_names[b]);
+        tier(name_a).cmp(&tier(name_b)).then_with(|| {
+            match (history.get(name_a), history.get(name_b)) {
+                (Some(ya), Some(yb)) => yb
+                    .urls_per_fetch()
+                    .total_cmp(&ya.urls_per_fetch())
+                    .then_with(|| ya.latency_ms().total_cmp(&yb.latency_ms())),
+                _ => std::cmp::Ordering::Equal,
+            }
+        })
+    });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_family() {
+        assert_eq!(domain_family("example.com"), "example.com");
+        assert_eq!(domain_family("api.Example.com."), "example.com");
+        assert_eq!(domain_family("a.b.example.co.uk"), "example.co.uk");
+        assert_eq!(domain_family("shop.example.de"), "example.de");
+        assert_eq!(domain_family("localhost"), "localhost");
+        assert_eq!(domain_family("192.168.0.1"), "192.168.0.1");
+    }
+
+    #[test]
+    fn test_rank_providers() {
+        let names: Vec<String> = ["empty", "unknown", "slow", "fast", "best"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut history = HashMap::new();
+        history.insert(
+            "empty".to_string(),
+            ProviderYield::from_fetch(0, Duration::from_millis(10)),
+        );
+        history.insert(
+            "slow".to_string(),
+            ProviderYield::from_fetch(100, Duration::from_secs(9)),
+        );
+        history.insert(
+            "fast".to_string(),
+            ProviderYield::from_fetch(100, Duration::from_secs(1)),
+        );
+        history.insert(
+            "best".to_string(),
+            ProviderYield::from_fetch(500, Duration::from_secs(30)),
+        );
+
+        let ranked: Vec<&str> = rank_providers(&names, &history)
+            .into_iter()
+            .map(|i| names[i].as_str())
+            .collect();
+        assert_eq!(ranked, vec!["best", "fast", "slow", "unknown", "empty"]);
+    }
+
+    #[test]
+    fn test_merge_averages() {
+        let mut y = ProviderYield::from_fetch(10, Duration::from_millis(100));
+        y.merge(&ProviderYield::from_fetch(30, Duration::from_millis(300)));
+        assert_eq!(y.fetches, 2);
+        assert_eq!(y.urls_per_fetch(), 20.0);
+        assert_eq!(y.latency_ms(), 200.0);
+    }
+}
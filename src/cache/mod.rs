@@ -1,17 +1,19 @@
+mod history;
 mod sqlite;
 mod types;
 
 #[cfg(feature = "redis-cache")]
 mod redis_impl;
 
+pub use history::{domain_family, rank_providers, ProviderYield};
 pub use sqlite::SqliteCache;
-pub use types::{CacheBackend, CacheEntry, CacheFilters, CacheKey};
+pub use types::{CacheBackend, CacheEntry, CacheFilters, CacheKey, DEFAULT_NAMESPACE};
 
 #[cfg(feature = "redis-cache")]
 pub use redis_impl::RedisCache;
 
-use anyhow::Result;
-use std::collections::HashSet;
+use crate::error::{UrxError, UrxResult};
+use std::collections::{HashMap, HashSet};
 
 /// Cache manager that provides a unified interface for different cache backends
 pub struct CacheManager {
@@ -20,31 +22,31 @@ pub struct CacheManager {
 
 impl CacheManager {
     /// Create a new cache manager with SQLite backend
-    pub async fn new_sqlite<P: AsRef<std::path::Path>>(db_path: P) -> Result<Self> {
-        let backend = Box::new(SqliteCache::new(db_path).await?);
+    pub async fn new_sqlite<P: AsRef<std::path::Path>>(db_path: P) -> UrxResult<Self> {
+        let backend = Box::new(SqliteCache::new(db_path).await.map_err(UrxError::cache)?);
         Ok(Self { backend })
     }
 
     /// Create a new cache manager with Redis backend (if feature is enabled)
     #[cfg(feature = "redis-cache")]
-    pub async fn new_redis(redis_url: &str) -> Result<Self> {
-        let backend = Box::new(RedisCache::new(redis_url).await?);
+    pub async fn new_redis(redis_url: &str) -> UrxResult<Self> {
+        let backend = Box::new(RedisCache::new(redis_url).await.map_err(UrxError::cache)?);
         Ok(Self { backend })
     }
 
     /// Get cached URLs for a domain and configuration
-    pub async fn get_cached_urls(&self, key: &CacheKey) -> Result<Option<CacheEntry>> {
-        self.backend.get(key).await
+    pub async fn get_cached_urls(&self, key: &CacheKey) -> UrxResult<Option<CacheEntry>> {
+        self.backend.get(key).await.map_err(UrxError::cache)
     }
 
     /// Store URLs in cache
-    pub async fn store_urls(&self, key: &CacheKey, entry: &CacheEntry) -> Result<()> {
-        self.backend.set(key, entry).await
+    pub async fn store_urls(&self, key: &CacheKey, entry: &CacheEntry) -> UrxResult<()> {
+        self.backend.set(key, entry).await.map_err(UrxError::cache)
     }
 
     /// Check if cache entry is still valid based on TTL
-    pub async fn is_valid(&self, key: &CacheKey, ttl_seconds: u64) -> Result<bool> {
-        if let Some(entry) = self.backend.get(key).await? {
+    pub async fn is_valid(&self, key: &CacheKey, ttl_seconds: u64) -> UrxResult<bool> {
+        if let Some(entry) = self.backend.get(key).await.map_err(UrxError::cache)? {
             if entry.is_expired(ttl_seconds) {
                 // Remove expired entry proactively
                 let _ = self.backend.delete(key).await;
@@ -62,11 +64,11 @@ impl CacheManager {
         &self,
         key: &CacheKey,
         new_urls: &HashSet<String>,
-    ) -> Result<HashSet<String>> {
-        if !self.backend.exists(key).await? {
+    ) -> UrxResult<HashSet<String>> {
+        if !self.backend.exists(key).await.map_err(UrxError::cache)? {
             return Ok(new_urls.clone());
         }
-        if let Some(cached_entry) = self.backend.get(key).await? {
+        if let Some(cached_entry) = self.backend.get(key).await.map_err(UrxError::cache)? {
             let cached_urls: HashSet<String> = cached_entry.urls.into_iter().collect();
             Ok(new_urls.difference(&cached_urls).cloned().collect())
         } else {
@@ -75,9 +77,84 @@ impl CacheManager {
         }
     }
 
+    /// Provider yield history for the domain family `family`
+    /// (see [`domain_family`]), keyed by provider name
+    pub async fn provider_history(
+        &self,
+        family: &str,
+    ) -> UrxResult<HashMap<String, ProviderYield>> {
+        self.backend
+            .provider_history(family)
+            .await
+            .map_err(UrxError::cache)
+    }
+
+    /// Add one run's yield for `provider` to the family's history
+    pub async fn record_provider_yield(
+        &self,
+        family: &str,
+        provider: &str,
+        provider_yield: &ProviderYield,
+    ) -> UrxResult<()> {
+        self.backend
+            .record_provider_yield(family, provider, provider_yield)
+            .await
+            .map_err(UrxError::cache)
+    }
+
+    /// Namespaces holding cached entries, with their entry counts. The
+    /// un-namespaced cache is reported as [`DEFAULT_NAMESPACE`].
+    pub async fn list_namespaces(&self) -> UrxResult<Vec<(String, usize)>> {
+        let mut namespaces = self
+            .backend
+            .list_namespaces()
+            .await
+            .map_err(UrxError::cache)?;
+        for (name, _) in &mut namespaces {
+            if name.is_empty() {
+                *name = DEFAULT_NAMESPACE.to_string();
+            }
+        }
+        Ok(namespaces)
+    }
+
+    /// Delete every entry in `namespace`, returning how many were removed
+    pub async fn delete_namespace(&self, namespace: &str) -> UrxResult<usize> {
+        let namespace = if namespace == DEFAULT_NAMESPACE {
+            ""
+        } else {
+            namespace
+        };
+        self.backend
+            .delete_namespace(namespace)
+            .await
+            .map_err(UrxError::cache)
+    }
+
+    /// Requests sent today (UTC) to each metered provider, by provider id
+    pub async fn quota_usage(&self, day: &str) -> UrxResult<HashMap<String, u64>> {
+        self.backend.quota_usage(day).await.map_err(UrxError::cache)
+    }
+
+    /// Add one run's requests to `provider`'s count for `day`
+    pub async fn record_quota_usage(
+        &self,
+        day: &str,
+        provider: &str,
+        requests: u64,
+    ) -> UrxResult<()> {
+        self.backend
+            .record_quota_usage(day, provider, requests)
+            .await
+            .map_err(UrxError::cache)
+    }
+
     /// Clear expired cache entries
-    pub async fn cleanup_expired(&self, ttl_seconds: u64) -> Result<()> {
-        self.backend.cleanup_expired(ttl_seconds).await
+    pub async fn cleanup_expired(&self, ttl_seconds: u64) -> UrxResult<()> {
+        self.backend
+            .cleanup_expired(ttl_seconds)
+            .await
+            .map_err(UrxError::cache)
     }
 
     #[cfg(test)]
@@ -89,6 +166,7 @@ impl CacheManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
     use std::collections::HashSet;
     use tempfile::tempdir;
 
@@ -103,6 +181,7 @@ mod tests {
             domain: "example.com".to_string(),
             providers: vec!["wayback".to_string()],
             filters_hash: "test_hash".to_string(),
+            namespace: String::new(),
         };
 
         let entry = CacheEntry {
@@ -141,6 +220,7 @@ mod tests {
             domain: "example.com".to_string(),
             providers: vec!["wayback".to_string()],
             filters_hash: "test_hash".to_string(),
+            namespace: String::new(),
         };
 
         // Store initial URLs
@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
+use super::history::ProviderYield;
 use super::types::{CacheBackend, CacheEntry, CacheKey};
 
 /// Redis-based cache implementation
@@ -40,6 +42,47 @@ impl RedisCache {
     fn redis_meta_key(&self, key: &CacheKey) -> String {
         format!("urx:meta:{}", key)
     }
+
+    /// Generate the Redis key of a domain family's provider history. A hash
+    /// with `<provider>:fetches`, `<provider>:urls` and
+    /// `<provider>:elapsed_ms` counters.
+    fn redis_history_key(&self, family: &str) -> String {
+        format!("urx:history:{}", family)
+    }
+
+    /// Generate the Redis key of a day's provider quota counters: a hash of
+    /// provider id -> requests sent.
+    fn redis_quota_key(&self, day: &str) -> String {
+        format!("urx:quota:{}", day)
+    }
+
+    /// Every metadata key paired with the namespace recorded in it. Entries
+    /// written before namespaces existed belong to the default ('') one.
+    async fn namespaced_meta_keys(
+        &self,
+        conn: &mut redis::aio::MultiplexedConnection,
+    ) -> Result<Vec<(String, String)>> {
+        let meta_keys: Vec<String> = redis::cmd("KEYS")
+            .arg("urx:meta:*")
+            .query_async(conn)
+            .await
+            .context("Failed to get metadata keys from Redis")?;
+
+        let mut keys = Vec::with_capacity(meta_keys.len());
+        for meta_key in meta_keys {
+            let meta_value: Option<String> = redis::cmd("GET")
+                .arg(&meta_key)
+                .query_async(conn)
+                .await
+                .context("Failed to get metadata from Redis")?;
+            let namespace = meta_value
+                .and_then(|meta| serde_json::from_str::<serde_json::Value>(&meta).ok())
+                .and_then(|meta| meta["namespace"].as_str().map(str::to_string))
+                .unwrap_or_default();
+            keys.push((meta_key, namespace));
+        }
+        Ok(keys)
+    }
 }
 
 #[cfg(feature = "redis-cache")]
@@ -91,6 +134,7 @@ impl CacheBackend for RedisCache {
         let meta_data = serde_json::json!({
             "domain": key.domain,
             "providers": key.providers,
+            "namespace": key.namespace,
             "timestamp": entry.timestamp.to_rfc3339()
         });
 
@@ -186,6 +230,145 @@ impl CacheBackend for RedisCache {
 
         Ok(exists)
     }
+
+    async fn provider_history(&self, family: &str) -> Result<HashMap<String, ProviderYield>> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let fields: HashMap<String, u64> = redis::cmd("HGETALL")
+            .arg(self.redis_history_key(family))
+            .query_async(&mut conn)
+            .await
+            .context("Failed to get provider history from Redis")?;
+
+        let mut history: HashMap<String, ProviderYield> = HashMap::new();
+        for (field, value) in fields {
+            let Some((provider, counter)) = field.rsplit_once(':') else {
+                continue;
+            };
+            let entry = history.entry(provider.to_string()).or_default();
+            match counter {
+                "fetches" => entry.fetches = value,
+                "urls" => entry.urls = value,
+                "elapsed_ms" => entry.elapsed_ms = value,
+                _ => {}
+            }
+        }
+        Ok(history)
+    }
+
+    async fn record_provider_yield(
+        &self,
+        family: &str,
+        provider: &str,
+        provider_yield: &ProviderYield,
+    ) -> Result<()> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let history_key = self.redis_history_key(family);
+        for (counter, value) in [
+            ("fetches", provider_yield.fetches),
+            ("urls", provider_yield.urls),
+            ("elapsed_ms", provider_yield.elapsed_ms),
+        ] {
+            redis::cmd("HINCRBY")
+                .arg(&history_key)
+                .arg(format!("{provider}:{counter}"))
+                .arg(value)
+                .query_async::<()>(&mut conn)
+                .await
+                .context("Failed to record provider history in Redis")?;
+        }
+
+        Ok(())
+    }
+
+    async fn quota_usage(&self, day: &str) -> Result<HashMap<String, u64>> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let usage: HashMap<String, u64> = redis::cmd("HGETALL")
+            .arg(self.redis_quota_key(day))
+            .query_async(&mut conn)
+            .await
+            .context("Failed to get provider quota usage from Redis")?;
+        Ok(usage)
+    }
+
+    async fn record_quota_usage(&self, day: &str, provider: &str, requests: u64) -> Result<()> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let quota_key = self.redis_quota_key(day);
+        redis::cmd("HINCRBY")
+            .arg(&quota_key)
+            .arg(provider)
+            .arg(requests)
+            .query_async::<()>(&mut conn)
+            .await
+            .context("Failed to record provider quota usage in Redis")?;
+        // A day's counters only matter until the next UTC day; let Redis
+        // drop them instead of pruning by hand.
+        redis::cmd("EXPIRE")
+            .arg(&quota_key)
+            .arg(2 * 24 * 60 * 60)
+            .query_async::<()>(&mut conn)
+            .await
+            .context("Failed to set provider quota expiry in Redis")?;
+
+        Ok(())
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<(String, usize)>> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let mut counts: std::collections::BTreeMap<String, usize> = Default::default();
+        for (_, namespace) in self.namespaced_meta_keys(&mut conn).await? {
+            *counts.entry(namespace).or_default() += 1;
+        }
+        Ok(counts.into_iter().collect())
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize> {
+        let mut conn = self
+            .client
+            .get_multiplexed_async_connection()
+            .await
+            .context("Failed to connect to Redis")?;
+
+        let mut deleted = 0;
+        for (meta_key, entry_namespace) in self.namespaced_meta_keys(&mut conn).await? {
+            if entry_namespace != namespace {
+                continue;
+            }
+            let cache_key = meta_key.replace("urx:meta:", "urx:cache:");
+            redis::cmd("DEL")
+                .arg(&cache_key)
+                .arg(&meta_key)
+                .query_async::<()>(&mut conn)
+                .await
+                .context("Failed to delete namespace entry from Redis")?;
+            deleted += 1;
+        }
+        Ok(deleted)
+    }
 }
 
 #[cfg(test)]
@@ -223,6 +406,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new("example.com", &["wayback".to_string()], &filters);
@@ -276,6 +463,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new("example.com", &["wayback".to_string()], &filters);
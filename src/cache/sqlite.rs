@@ -2,9 +2,11 @@ use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::path::Path;
 use tokio::task;
 
+use super::history::ProviderYield;
 use super::types::{CacheBackend, CacheEntry, CacheKey};
 
 /// SQLite-based cache implementation
@@ -53,6 +55,19 @@ impl SqliteCache {
             )
             .context("Failed to create cache table")?;
 
+            // Databases created before --cache-namespace lack the column;
+            // their entries land in the default ('') namespace.
+            let has_namespace = conn
+                .prepare("SELECT 1 FROM pragma_table_info('url_cache') WHERE name = 'namespace'")?
+                .exists([])?;
+            if !has_namespace {
+                conn.execute(
+                    "ALTER TABLE url_cache ADD COLUMN namespace TEXT NOT NULL DEFAULT ''",
+                    [],
+                )
+                .context("Failed to add namespace column")?;
+            }
+
             // Create index for better performance
             conn.execute(
                 "CREATE INDEX IF NOT EXISTS idx_cache_key ON url_cache(cache_key)",
@@ -72,6 +87,38 @@ impl SqliteCache {
             )
             .context("Failed to create timestamp index")?;
 
+            // Running totals per (domain family, provider) for --smart-order.
+            // Not subject to the URL cache TTL: older runs are still signal.
+            conn.execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS provider_yield (
+                    family TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    fetches INTEGER NOT NULL,
+                    urls INTEGER NOT NULL,
+                    elapsed_ms INTEGER NOT NULL,
+                    updated_at TEXT NOT NULL,
+                    PRIMARY KEY (family, provider)
+                )
+                "#,
+                [],
+            )
+            .context("Failed to create provider yield table")?;
+
+            // Requests per (UTC day, keyed provider) for --provider-quota.
+            conn.execute(
+                r#"
+                CREATE TABLE IF NOT EXISTS provider_quota (
+                    day TEXT NOT NULL,
+                    provider TEXT NOT NULL,
+                    requests INTEGER NOT NULL,
+                    PRIMARY KEY (day, provider)
+                )
+                "#,
+                [],
+            )
+            .context("Failed to create provider quota table")?;
+
             Ok::<(), anyhow::Error>(())
         })
         .await??;
@@ -138,6 +185,7 @@ impl CacheBackend for SqliteCache {
         let domain = key.domain.clone();
         let providers = serde_json::to_string(&key.providers)?;
         let filters_hash = key.filters_hash.clone();
+        let namespace = key.namespace.clone();
         let urls = serde_json::to_string(&entry.urls)?;
         let timestamp = entry.timestamp.to_rfc3339();
 
@@ -145,10 +193,18 @@ impl CacheBackend for SqliteCache {
             conn.execute(
                 r#"
                 INSERT OR REPLACE INTO url_cache
-                (cache_key, domain, providers, filters_hash, urls, timestamp)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                (cache_key, domain, providers, filters_hash, urls, timestamp, namespace)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
                 "#,
-                params![cache_key, domain, providers, filters_hash, urls, timestamp],
+                params![
+                    cache_key,
+                    domain,
+                    providers,
+                    filters_hash,
+                    urls,
+                    timestamp,
+                    namespace
+                ],
             )?;
             Ok(())
         })
@@ -201,6 +257,123 @@ impl CacheBackend for SqliteCache {
         })
         .await
     }
+
+    async fn provider_history(&self, family: &str) -> Result<HashMap<String, ProviderYield>> {
+        let family = family.to_string();
+
+        self.with_connection(move |conn| {
+            let mut stmt = conn.prepare(
+                "SELECT provider, fetches, urls, elapsed_ms FROM provider_yield WHERE family = ?1",
+            )?;
+            let rows = stmt.query_map(params![family], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    ProviderYield {
+                        fetches: row.get::<_, i64>(1)? as u64,
+                        urls: row.get::<_, i64>(2)? as u64,
+                        elapsed_ms: row.get::<_, i64>(3)? as u64,
+                    },
+                ))
+            })?;
+            Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+        })
+        .await
+    }
+
+    async fn record_provider_yield(
+        &self,
+        family: &str,
+        provider: &str,
+        provider_yield: &ProviderYield,
+    ) -> Result<()> {
+        let family = family.to_string();
+        let provider = provider.to_string();
+        let y = provider_yield.clone();
+        let updated_at = Utc::now().to_rfc3339();
+
+        self.with_connection(move |conn| {
+            conn.execute(
+                r#"
+                INSERT INTO provider_yield (family, provider, fetches, urls, elapsed_ms, updated_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT(family, provider) DO UPDATE SET
+                    fetches = fetches + excluded.fetches,
+                    urls = urls + excluded.urls,
+                    elapsed_ms = elapsed_ms + excluded.elapsed_ms,
+                    updated_at = excluded.updated_at
+                "#,
+                params![
+                    family,
+                    provider,
+                    y.fetches as i64,
+                    y.urls as i64,
+                    y.elapsed_ms as i64,
+                    updated_at
+                ],
+            )?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<(String, usize)>> {
+        self.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT namespace, COUNT(*) FROM url_cache GROUP BY namespace ORDER BY namespace",
+            )?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+            })?;
+            Ok(rows.collect::<rusqlite::Result<Vec<_>>>()?)
+        })
+        .await
+    }
+
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize> {
+        let namespace = namespace.to_string();
+
+        self.with_connection(move |conn| {
+            Ok(conn.execute(
+                "DELETE FROM url_cache WHERE namespace = ?1",
+                params![namespace],
+            )?)
+        })
+        .await
+    }
+
+    async fn quota_usage(&self, day: &str) -> Result<HashMap<String, u64>> {
+        let day = day.to_string();
+
+        self.with_connection(move |conn| {
+            let mut stmt =
+                conn.prepare("SELECT provider, requests FROM provider_quota WHERE day = ?1")?;
+            let rows = stmt.query_map(params![day], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            })?;
+            Ok(rows.collect::<rusqlite::Result<HashMap<_, _>>>()?)
+        })
+        .await
+    }
+
+    async fn record_quota_usage(&self, day: &str, provider: &str, requests: u64) -> Result<()> {
+        let day = day.to_string();
+        let provider = provider.to_string();
+
+        self.with_connection(move |conn| {
+            conn.execute("DELETE FROM provider_quota WHERE day < ?1", params![day])?;
+            conn.execute(
+                r#"
+                INSERT INTO provider_quota (day, provider, requests)
+                VALUES (?1, ?2, ?3)
+                ON CONFLICT(day, provider) DO UPDATE SET
+                    requests = requests + excluded.requests
+                "#,
+                params![day, provider, requests as i64],
+            )?;
+            Ok(())
+        })
+        .await
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +401,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new("example.com", &["wayback".to_string()], &filters);
@@ -274,6 +451,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new("example.com", &["wayback".to_string()], &filters);
@@ -313,6 +494,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key1 = CacheKey::new("example.com", &["wayback".to_string()], &filters);
@@ -337,4 +522,141 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_sqlite_provider_history_accumulates() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let cache = SqliteCache::new(temp_dir.path().join("test.db")).await?;
+
+        assert!(cache.provider_history("example.com").await?.is_empty());
+
+        let fetch = |urls| ProviderYield::from_fetch(urls, std::time::Duration::from_millis(200));
+        cache
+            .record_provider_yield("example.com", "Wayback Machine", &fetch(40))
+            .await?;
+        cache
+            .record_provider_yield("example.com", "Wayback Machine", &fetch(20))
+            .await?;
+        cache
+            .record_provider_yield("example.com", "OTX", &fetch(0))
+            .await?;
+        cache
+            .record_provider_yield("other.org", "OTX", &fetch(7))
+            .await?;
+
+        let history = cache.provider_history("example.com").await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history["Wayback Machine"],
+            ProviderYield {
+                fetches: 2,
+                urls: 60,
+                elapsed_ms: 400
+            }
+        );
+        assert_eq!(history["OTX"].urls, 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_quota_usage_is_per_day() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let cache = SqliteCache::new(temp_dir.path().join("test.db")).await?;
+
+        cache.record_quota_usage("2026-01-01", "vt", 100).await?;
+        cache.record_quota_usage("2026-01-02", "vt", 3).await?;
+        cache.record_quota_usage("2026-01-02", "vt", 4).await?;
+        cache.record_quota_usage("2026-01-02", "urlscan", 9).await?;
+
+        let today = cache.quota_usage("2026-01-02").await?;
+        assert_eq!(today.get("vt"), Some(&7));
+        assert_eq!(today.get("urlscan"), Some(&9));
+        // Recording a newer day prunes the older ones.
+        assert!(cache.quota_usage("2026-01-01").await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_namespaces_isolated() -> Result<()> {
+        let temp_dir = tempdir()?;
+        let db_path = temp_dir.path().join("test.db");
+
+        // A database from before namespaces existed, with one entry.
+        {
+            let conn = Connection::open(&db_path)?;
+            conn.execute_batch(
+                r#"
+                CREATE TABLE url_cache (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    cache_key TEXT UNIQUE NOT NULL,
+                    domain TEXT NOT NULL,
+                    providers TEXT NOT NULL,
+                    filters_hash TEXT NOT NULL,
+                    urls TEXT NOT NULL,
+                    timestamp TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+                INSERT INTO url_cache (cache_key, domain, providers, filters_hash, urls, timestamp)
+                VALUES ('legacy', 'old.com', '[]', '', '[]', '2024-01-01T00:00:00Z');
+                "#,
+            )?;
+        }
+        let cache = SqliteCache::new(&db_path).await?;
+
+        let filters = CacheFilters {
+            subs: false,
+            extensions: vec![],
+            exclude_extensions: vec![],
+            patterns: vec![],
+            exclude_patterns: vec![],
+            presets: vec![],
+            min_length: None,
+            max_length: None,
+            strict: true,
+            normalize_url: false,
+            merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
+        };
+        let key = CacheKey::new("example.com", &["wayback".to_string()], &filters);
+        let acme = key.clone().with_namespace("acme");
+        let globex = key.clone().with_namespace("globex");
+        cache
+            .set(
+                &acme,
+                &CacheEntry::new(vec!["https://example.com/a".into()]),
+            )
+            .await?;
+        cache
+            .set(
+                &globex,
+                &CacheEntry::new(vec!["https://example.com/g".into()]),
+            )
+            .await?;
+
+        assert!(!cache.exists(&key).await?);
+        assert_eq!(
+            cache.get(&acme).await?.unwrap().urls,
+            vec!["https://example.com/a"]
+        );
+        assert_eq!(
+            cache.list_namespaces().await?,
+            vec![
+                (String::new(), 1),
+                ("acme".to_string(), 1),
+                ("globex".to_string(), 1)
+            ]
+        );
+
+        assert_eq!(cache.delete_namespace("acme").await?, 1);
+        assert!(!cache.exists(&acme).await?);
+        assert!(cache.exists(&globex).await?);
+        assert_eq!(cache.delete_namespace("acme").await?, 0);
+
+        Ok(())
+    }
 }
@@ -3,6 +3,9 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use super::history::ProviderYield;
 
 /// Cache key that uniquely identifies a scan configuration
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -10,6 +13,9 @@ pub struct CacheKey {
     pub domain: String,
     pub providers: Vec<String>,
     pub filters_hash: String,
+    /// `--cache-namespace` the entry belongs to; empty for the default one
+    #[serde(default)]
+    pub namespace: String,
 }
 
 impl CacheKey {
@@ -24,10 +30,25 @@ impl CacheKey {
             domain: domain.to_string(),
             providers,
             filters_hash,
+            namespace: String::new(),
         }
     }
+
+    /// File the key under `namespace`, so the same scan in another namespace
+    /// gets a distinct entry. `default` (or empty) is the shared namespace.
+    pub fn with_namespace(mut self, namespace: &str) -> Self {
+        self.namespace = if namespace == DEFAULT_NAMESPACE {
+            String::new()
+        } else {
+            namespace.to_string()
+        };
+        self
+    }
 }
 
+/// Name under which entries written without `--cache-namespace` are listed.
+pub const DEFAULT_NAMESPACE: &str = "default";
+
 /// Feed one field into the hasher length-prefixed, so that adjacent fields can
 /// never be confused for one another. Without this, concatenating raw bytes
 /// lets distinct configs collide (e.g. domain `"ab"`+provider `"c"` hashes the
@@ -52,6 +73,10 @@ impl std::fmt::Display for CacheKey {
         feed(&mut hasher, self.domain.as_bytes());
         feed_list(&mut hasher, &self.providers);
         feed(&mut hasher, self.filters_hash.as_bytes());
+        // Only fed when set so keys written before namespaces existed stay valid
+        if !self.namespace.is_empty() {
+            feed(&mut hasher, self.namespace.as_bytes());
+        }
         let result = hasher.finalize();
         for byte in result {
             write!(f, "{:02x}", byte)?;
@@ -74,6 +99,14 @@ pub struct CacheFilters {
     pub strict: bool,
     pub normalize_url: bool,
     pub merge_endpoint: bool,
+    #[serde(default)]
+    pub ignore_port: bool,
+    #[serde(default)]
+    pub provider_status: Vec<String>,
+    #[serde(default)]
+    pub min_params: Option<usize>,
+    #[serde(default)]
+    pub max_params: Option<usize>,
 }
 
 impl CacheFilters {
@@ -109,6 +142,23 @@ impl CacheFilters {
         hasher.update([self.strict as u8]);
         hasher.update([self.normalize_url as u8]);
         hasher.update([self.merge_endpoint as u8]);
+        // Only fed when set so keys written before --ignore-port existed stay valid
+        if self.ignore_port {
+            hasher.update([self.ignore_port as u8]);
+        }
+        // Likewise only fed when --provider-status-filter is set
+        if !self.provider_status.is_empty() {
+            feed_list(&mut hasher, &self.provider_status);
+        }
+        // And the query parameter limits only when set
+        if self.min_params.is_some() || self.max_params.is_some() {
+            for limit in [self.min_params, self.max_params] {
+                feed(
+                    &mut hasher,
+                    limit.map(|l| l.to_string()).unwrap_or_default().as_bytes(),
+                );
+            }
+        }
 
         hasher
             .finalize()
@@ -159,6 +209,32 @@ pub trait CacheBackend: Send + Sync {
 
     /// Check if a key exists in the cache
     async fn exists(&self, key: &CacheKey) -> Result<bool>;
+
+    /// Accumulated provider yield for a domain family, keyed by provider name
+    async fn provider_history(&self, family: &str) -> Result<HashMap<String, ProviderYield>>;
+
+    /// Fold one run's yield for `provider` into the family's history
+    async fn record_provider_yield(
+        &self,
+        family: &str,
+        provider: &str,
+        provider_yield: &ProviderYield,
+    ) -> Result<()>;
+
+    /// Namespaces holding at least one entry, with their entry counts.
+    /// The default namespace is reported as an empty name.
+    async fn list_namespaces(&self) -> Result<Vec<(String, usize)>>;
+
+    /// Remove every entry in `namespace`, returning how many were deleted
+    async fn delete_namespace(&self, namespace: &str) -> Result<usize>;
+
+    /// Requests sent to each metered provider on `day` (UTC `YYYY-MM-DD`),
+    /// keyed by provider id
+    async fn quota_usage(&self, day: &str) -> Result<HashMap<String, u64>>;
+
+    /// Add `requests` to `provider`'s count for `day`. Earlier days are
+    /// dropped: only the current day's count matters for a daily quota.
+    async fn record_quota_usage(&self, day: &str, provider: &str, requests: u64) -> Result<()>;
 }
 
 #[cfg(test)]
@@ -179,6 +255,10 @@ mod tests {
             strict: true,
             normalize_url: true,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new(
@@ -206,6 +286,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -220,6 +304,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_eq!(filters1.compute_hash(), filters2.compute_hash());
@@ -239,6 +327,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -253,6 +345,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -284,6 +380,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key1 = CacheKey::new("example.com", &["wayback".to_string()], &filters);
@@ -345,6 +445,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -359,6 +463,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -378,6 +486,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -392,6 +504,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -411,6 +527,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -425,6 +545,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -444,6 +568,10 @@ mod tests {
             strict: true,
             normalize_url: true,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -458,6 +586,10 @@ mod tests {
             strict: true,
             normalize_url: false, // Different
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -477,6 +609,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: true,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let filters2 = CacheFilters {
@@ -491,6 +627,66 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false, // Different
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
+        };
+
+        assert_ne!(filters1.compute_hash(), filters2.compute_hash());
+    }
+
+    #[test]
+    fn test_cache_filters_hash_with_ignore_port() {
+        let filters1 = CacheFilters {
+            subs: true,
+            extensions: vec![],
+            exclude_extensions: vec![],
+            patterns: vec![],
+            exclude_patterns: vec![],
+            presets: vec![],
+            min_length: None,
+            max_length: None,
+            strict: true,
+            normalize_url: false,
+            merge_endpoint: false,
+            ignore_port: true,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
+        };
+
+        let filters2 = CacheFilters {
+            ignore_port: false, // Different
+            ..filters1.clone()
+        };
+
+        assert_ne!(filters1.compute_hash(), filters2.compute_hash());
+    }
+
+    #[test]
+    fn test_cache_filters_hash_with_provider_status() {
+        let filters1 = CacheFilters {
+            subs: false,
+            extensions: vec![],
+            exclude_extensions: vec![],
+            patterns: vec![],
+            exclude_patterns: vec![],
+            presets: vec![],
+            min_length: None,
+            max_length: None,
+            strict: true,
+            normalize_url: false,
+            merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
+        };
+
+        let filters2 = CacheFilters {
+            provider_status: vec!["200".to_string(), "3xx".to_string()],
+            ..filters1.clone()
         };
 
         assert_ne!(filters1.compute_hash(), filters2.compute_hash());
@@ -510,6 +706,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         // Providers in different order should result in same sorted list
@@ -544,6 +744,10 @@ mod tests {
             strict: false,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
         let a = CacheFilters {
             presets: vec!["a".to_string()],
@@ -572,6 +776,10 @@ mod tests {
             strict: false,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
         // domain "ab" + provider "c" vs domain "a" + provider "bc".
         let k1 = CacheKey::new("ab", &["c".to_string()], &filters);
@@ -579,6 +787,39 @@ mod tests {
         assert_ne!(format!("{}", k1), format!("{}", k2));
     }
 
+    #[test]
+    fn test_cache_key_namespace() {
+        let filters = CacheFilters {
+            subs: false,
+            extensions: vec![],
+            exclude_extensions: vec![],
+            patterns: vec![],
+            exclude_patterns: vec![],
+            presets: vec![],
+            min_length: None,
+            max_length: None,
+            strict: true,
+            normalize_url: false,
+            merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
+        };
+        let providers = vec!["wayback".to_string()];
+        let plain = CacheKey::new("example.com", &providers, &filters);
+        let acme = plain.clone().with_namespace("acme");
+        let other = plain.clone().with_namespace("globex");
+
+        assert_ne!(plain.to_string(), acme.to_string());
+        assert_ne!(acme.to_string(), other.to_string());
+        // "default" is the un-namespaced cache, so existing entries stay reachable.
+        assert_eq!(
+            plain.to_string(),
+            plain.clone().with_namespace(DEFAULT_NAMESPACE).to_string()
+        );
+    }
+
     #[test]
     fn test_cache_key_empty_providers() {
         let filters = CacheFilters {
@@ -593,6 +834,10 @@ mod tests {
             strict: true,
             normalize_url: false,
             merge_endpoint: false,
+            ignore_port: false,
+            provider_status: vec![],
+            min_params: None,
+            max_params: None,
         };
 
         let key = CacheKey::new("example.com", &[], &filters);
@@ -1,10 +1,11 @@
-use anyhow::{Context, Result};
+use anyhow::Context;
 use serde::Deserialize;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use crate::cli::Args;
+use crate::error::{UrxError, UrxResult};
 
 /// Represents the application configuration loaded from a file
 #[derive(Debug, Deserialize, Default)]
@@ -26,18 +27,37 @@ pub struct Config {
 
     #[serde(default)]
     pub cache: CacheConfig,
+
+    /// `[preset.<name>]` tables: filter presets usable with `--preset`.
+    #[serde(default)]
+    pub preset: std::collections::BTreeMap<String, crate::filters::CustomPreset>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 pub struct OutputConfig {
     pub output: Option<String>,
     pub format: Option<String>,
+    pub format_template: Option<String>,
+    pub csv_columns: Option<Vec<String>>,
+    pub compress: Option<String>,
+    pub append: Option<bool>,
+    pub es_url: Option<String>,
+    pub es_index: Option<String>,
     pub merge_endpoint: Option<bool>,
+    pub strip_params: Option<Vec<String>>,
+    pub clean_params: Option<bool>,
+    pub dedup_similar: Option<bool>,
+    pub sample_per_path: Option<usize>,
+    pub fuzz_params: Option<bool>,
+    pub fuzz_path_last: Option<bool>,
+    pub replace_values: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Default)]
 pub struct ProviderConfig {
     pub providers: Option<Vec<String>>,
+    pub exclude_providers: Option<Vec<String>>,
+    pub provider_quota: Option<Vec<String>>,
     pub subs: Option<bool>,
     pub cc_index: Option<String>,
     pub vt_api_key: Option<String>,
@@ -47,6 +67,19 @@ pub struct ProviderConfig {
     pub include_sitemap: Option<bool>,
     pub exclude_robots: Option<bool>,
     pub exclude_sitemap: Option<bool>,
+    pub robots_wildcards: Option<bool>,
+    /// `[[provider.custom]]` entries: HTTP sources urx doesn't ship, run by
+    /// the generic provider.
+    #[serde(default)]
+    pub custom: Vec<crate::providers::CustomProviderDef>,
+    /// `[[provider.plugin]]` entries: external programs run as providers.
+    #[serde(default)]
+    pub plugin: Vec<crate::providers::PluginProviderDef>,
+    /// `[provider.<id>]` sub-tables holding per-provider network overrides
+    /// (e.g. `[provider.wayback] timeout = 300`). Other stray keys under
+    /// `[provider]` land here too and are ignored.
+    #[serde(flatten)]
+    pub sections: std::collections::BTreeMap<String, toml::Value>,
 }
 
 /// Provider-config file: a small TOML that holds only API keys so the main
@@ -62,19 +95,23 @@ pub struct ProviderKeysConfig {
 impl ProviderKeysConfig {
     /// Parse a provider-config TOML file from `path`. Returns the parsed
     /// struct or an error.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let content = fs::read_to_string(&path).with_context(|| {
-            format!(
-                "Failed to read provider-config file: {}",
-                path.as_ref().display()
-            )
-        })?;
-        let parsed: ProviderKeysConfig = toml::from_str(&content).with_context(|| {
-            format!(
-                "Failed to parse provider-config file: {}",
-                path.as_ref().display()
-            )
-        })?;
+    pub fn from_file<P: AsRef<Path>>(path: P) -> UrxResult<Self> {
+        let content = fs::read_to_string(&path)
+            .with_context(|| {
+                format!(
+                    "Failed to read provider-config file: {}",
+                    path.as_ref().display()
+                )
+            })
+            .map_err(UrxError::config)?;
+        let parsed: ProviderKeysConfig = toml::from_str(&content)
+            .with_context(|| {
+                format!(
+                    "Failed to parse provider-config file: {}",
+                    path.as_ref().display()
+                )
+            })
+            .map_err(UrxError::config)?;
         Ok(parsed)
     }
 
@@ -110,7 +147,7 @@ impl ProviderKeysConfig {
     /// Load using the same precedence as the main config: --provider-config
     /// flag wins, then the default path. Returns an empty config when no file
     /// is found so callers can chain it freely.
-    pub fn load(args: &Args) -> Result<Self> {
+    pub fn load(args: &Args) -> UrxResult<Self> {
         if let Some(path) = &args.provider_config {
             return Self::from_file(path);
         }
@@ -166,11 +203,18 @@ pub struct FilterConfig {
     pub exclude_extensions: Option<Vec<String>>,
     pub patterns: Option<Vec<String>>,
     pub exclude_patterns: Option<Vec<String>>,
+    pub exclude_hosts: Option<Vec<String>>,
+    pub exclude_file: Option<PathBuf>,
+    pub case_sensitive: Option<bool>,
     pub show_only_host: Option<bool>,
     pub show_only_path: Option<bool>,
     pub show_only_param: Option<bool>,
+    pub show_only_param_keys: Option<bool>,
+    pub param_key_counts: Option<bool>,
     pub min_length: Option<usize>,
     pub max_length: Option<usize>,
+    pub min_params: Option<usize>,
+    pub max_params: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -184,6 +228,12 @@ pub struct NetworkConfig {
     pub retries: Option<u32>,
     pub parallel: Option<u32>,
     pub rate_limit: Option<f32>,
+    pub max_bandwidth: Option<String>,
+    pub http_version: Option<String>,
+    pub global_rate_limit: Option<f32>,
+    pub max_requests: Option<u64>,
+    pub max_urls: Option<usize>,
+    pub max_urls_per_domain: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -192,6 +242,18 @@ pub struct TestingConfig {
     pub include_status: Option<Vec<String>>,
     pub exclude_status: Option<Vec<String>>,
     pub extract_links: Option<bool>,
+    pub body_stats: Option<bool>,
+    pub include_headers: Option<Vec<String>>,
+    pub match_body: Option<String>,
+    pub filter_body: Option<String>,
+    pub extract_title: Option<bool>,
+    pub extract_js_endpoints: Option<bool>,
+    pub tls_info: Option<bool>,
+    pub check_reflection: Option<bool>,
+    pub check_open_redirect: Option<bool>,
+    pub scan_secrets: Option<bool>,
+    pub respect_robots: Option<bool>,
+    pub per_category_limit: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -201,7 +263,14 @@ pub struct CacheConfig {
     pub cache_path: Option<String>,
     pub redis_url: Option<String>,
     pub cache_ttl: Option<u64>,
+    /// `[cache.ttl]` table of per-provider TTLs (e.g. `wayback = "7d"`,
+    /// `robots = 3600`), folded in as `--cache-ttl-by` entries.
+    pub ttl: Option<std::collections::BTreeMap<String, toml::Value>>,
     pub no_cache: Option<bool>,
+    pub http_cache: Option<bool>,
+    pub http_cache_path: Option<String>,
+    pub smart_order: Option<bool>,
+    pub namespace: Option<String>,
 }
 
 fn normalize_output_format(format: &str) -> Option<String> {
@@ -209,6 +278,12 @@ fn normalize_output_format(format: &str) -> Option<String> {
         "plain" => Some("plain".to_string()),
         "json" => Some("json".to_string()),
         "csv" => Some("csv".to_string()),
+        "html" => Some("html".to_string()),
+        "sitemap" => Some("sitemap".to_string()),
+        "burp" => Some("burp".to_string()),
+        "tree" => Some("tree".to_string()),
+        "dot" => Some("dot".to_string()),
+        "parquet" => Some("parquet".to_string()),
         _ => None,
     }
 }
@@ -225,12 +300,14 @@ fn normalize_network_scope(scope: &str) -> Option<String> {
 
 impl Config {
     /// Load configuration from a specific file path
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> UrxResult<Self> {
         let content = fs::read_to_string(&path)
-            .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))?;
+            .with_context(|| format!("Failed to read config file: {}", path.as_ref().display()))
+            .map_err(UrxError::config)?;
 
         let config: Config = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.as_ref().display()))?;
+            .with_context(|| format!("Failed to parse config file: {}", path.as_ref().display()))
+            .map_err(UrxError::config)?;
 
         Ok(config)
     }
@@ -287,7 +364,7 @@ impl Config {
 
     /// Load configuration based on command line arguments
     /// Priority: --config flag > default path > default values
-    pub fn load(args: &Args) -> Result<Self> {
+    pub fn load(args: &Args) -> UrxResult<Self> {
         // Try to load from --config flag first
         if let Some(path) = &args.config {
             return Self::from_file(path);
@@ -317,7 +394,7 @@ impl Config {
         // Output options
         if args.output.is_none() {
             if let Some(output) = &self.output.output {
-                args.output = Some(PathBuf::from(output));
+                args.output = Some(expand_tilde(output));
             }
         }
 
@@ -327,15 +404,93 @@ impl Config {
                     args.format = format;
                 } else if !args.silent {
                     eprintln!(
-                        "Ignoring [output].format={format:?} in config: expected plain, json, or csv"
+                        "Ignoring [output].format={format:?} in config: expected plain, json, csv, html, sitemap, burp, tree, dot, or parquet"
                     );
                 }
             }
         }
 
+        if args.format_template.is_none() {
+            args.format_template = self.output.format_template.clone();
+        }
+
+        if args.csv_columns.is_none() {
+            if let Some(columns) = &self.output.csv_columns {
+                match columns.join(",").parse() {
+                    Ok(columns) => args.csv_columns = Some(columns),
+                    Err(e) if !args.silent => {
+                        eprintln!("Ignoring [output].csv_columns in config: {e}")
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if args.compress.is_none() {
+            if let Some(raw) = &self.output.compress {
+                match raw.parse() {
+                    Ok(compression) => args.compress = Some(compression),
+                    Err(e) if !args.silent => {
+                        eprintln!("Ignoring [output].compress in config: {e}")
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if args.es_url.is_none() {
+            args.es_url = self.output.es_url.clone();
+        }
+
+        if args.es_index == "urx" {
+            if let Some(index) = &self.output.es_index {
+                match crate::output::validate_es_index(index) {
+                    Ok(()) => args.es_index = index.clone(),
+                    Err(e) if !args.silent => {
+                        eprintln!("Ignoring [output].es_index in config: {e}")
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if !args.append && self.output.append.unwrap_or(false) {
+            args.append = true;
+        }
+
         if !args.merge_endpoint && self.output.merge_endpoint.unwrap_or(false) {
             args.merge_endpoint = true;
         }
+
+        if args.strip_params.is_empty() {
+            if let Some(strip_params) = &self.output.strip_params {
+                args.strip_params = strip_params.clone();
+            }
+        }
+
+        if !args.clean_params && self.output.clean_params.unwrap_or(false) {
+            args.clean_params = true;
+        }
+
+        if !args.dedup_similar && self.output.dedup_similar.unwrap_or(false) {
+            args.dedup_similar = true;
+        }
+
+        if args.sample_per_path.is_none() {
+            args.sample_per_path = self.output.sample_per_path.filter(|&limit| limit > 0);
+        }
+
+        if !args.fuzz_params && self.output.fuzz_params.unwrap_or(false) {
+            args.fuzz_params = true;
+        }
+
+        if !args.fuzz_path_last && self.output.fuzz_path_last.unwrap_or(false) {
+            args.fuzz_path_last = true;
+        }
+
+        if args.replace_values.is_none() && !args.fuzz_params {
+            args.replace_values = self.output.replace_values.clone();
+        }
     }
 
     fn apply_provider_config(&self, args: &mut Args) {
@@ -345,10 +500,46 @@ impl Config {
                 args.providers = providers.clone();
             }
         }
+        if args.exclude_providers.is_empty() {
+            if let Some(excluded) = &self.provider.exclude_providers {
+                args.exclude_providers = excluded.clone();
+            }
+        }
+        if args.provider_quota.is_empty() {
+            if let Some(quotas) = &self.provider.provider_quota {
+                args.provider_quota = quotas.clone();
+            }
+        }
 
         if !args.subs && self.provider.subs.unwrap_or(false) {
             args.subs = true;
         }
+        if args.custom_providers.is_empty() {
+            args.custom_providers = self.provider.custom.clone();
+        }
+        if args.plugin_providers.is_empty() {
+            args.plugin_providers = self.provider.plugin.clone();
+        }
+
+        // Fold `[provider.<id>]` sections in as --provider-opt entries ahead
+        // of the CLI ones, so a flag still overrides the same key.
+        let mut section_opts: Vec<String> = Vec::new();
+        for (id, value) in &self.provider.sections {
+            let Some(table) = value.as_table() else {
+                continue;
+            };
+            for (key, value) in table {
+                let value = match value {
+                    toml::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                section_opts.push(format!("{id}:{key}={value}"));
+            }
+        }
+        if !section_opts.is_empty() {
+            section_opts.append(&mut args.provider_opt);
+            args.provider_opt = section_opts;
+        }
 
         // Treat the default singleton list as "not user-supplied" so the file
         // value wins. Config file still accepts a single string; we split it
@@ -394,6 +585,10 @@ impl Config {
             args.exclude_sitemap = true;
         }
 
+        if !args.robots_wildcards && self.provider.robots_wildcards.unwrap_or(false) {
+            args.robots_wildcards = true;
+        }
+
         // Only apply include_* if exclude_* is not set (exclude takes precedence)
         if !args.exclude_robots && args.include_robots {
             if let Some(include_robots) = self.provider.include_robots {
@@ -415,6 +610,9 @@ impl Config {
                 args.preset = preset.clone();
             }
         }
+        if args.custom_presets.is_empty() {
+            args.custom_presets = self.preset.clone();
+        }
 
         if args.extensions.is_empty() {
             if let Some(extensions) = &self.filter.extensions {
@@ -440,6 +638,20 @@ impl Config {
             }
         }
 
+        if args.exclude_file.is_none() {
+            args.exclude_file = self.filter.exclude_file.as_ref().map(expand_tilde);
+        }
+
+        if !args.case_sensitive && self.filter.case_sensitive.unwrap_or(false) {
+            args.case_sensitive = true;
+        }
+
+        if args.exclude_hosts.is_empty() {
+            if let Some(exclude_hosts) = &self.filter.exclude_hosts {
+                args.exclude_hosts = exclude_hosts.clone();
+            }
+        }
+
         if !args.show_only_host && self.filter.show_only_host.unwrap_or(false) {
             args.show_only_host = true;
         }
@@ -452,6 +664,14 @@ impl Config {
             args.show_only_param = true;
         }
 
+        if !args.show_only_param_keys && self.filter.show_only_param_keys.unwrap_or(false) {
+            args.show_only_param_keys = true;
+        }
+
+        if !args.param_key_counts && self.filter.param_key_counts.unwrap_or(false) {
+            args.param_key_counts = true;
+        }
+
         if args.min_length.is_none() && self.filter.min_length.is_some() {
             args.min_length = self.filter.min_length;
         }
@@ -459,6 +679,14 @@ impl Config {
         if args.max_length.is_none() && self.filter.max_length.is_some() {
             args.max_length = self.filter.max_length;
         }
+
+        if args.min_params.is_none() && self.filter.min_params.is_some() {
+            args.min_params = self.filter.min_params;
+        }
+
+        if args.max_params.is_none() && self.filter.max_params.is_some() {
+            args.max_params = self.filter.max_params;
+        }
     }
 
     fn apply_network_config(&self, args: &mut Args) {
@@ -522,6 +750,46 @@ impl Config {
         if args.rate_limit.is_none() && self.network.rate_limit.is_some() {
             args.rate_limit = self.network.rate_limit;
         }
+
+        if args.max_bandwidth.is_none() {
+            if let Some(raw) = &self.network.max_bandwidth {
+                match crate::network::parse_bandwidth(raw) {
+                    Some(rate) => args.max_bandwidth = Some(rate),
+                    None if !args.silent => eprintln!(
+                        "Ignoring [network].max_bandwidth={raw:?} in config: expected a rate such as \"10MBps\""
+                    ),
+                    None => {}
+                }
+            }
+        }
+
+        if args.http_version.is_none() {
+            if let Some(raw) = &self.network.http_version {
+                match raw.parse() {
+                    Ok(version) => args.http_version = Some(version),
+                    Err(e) if !args.silent => {
+                        eprintln!("Ignoring [network].http_version in config: {e}")
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if args.global_rate_limit.is_none() {
+            args.global_rate_limit = self.network.global_rate_limit.filter(|r| *r > 0.0);
+        }
+
+        if args.max_requests.is_none() {
+            args.max_requests = self.network.max_requests.filter(|n| *n > 0);
+        }
+
+        if args.max_urls.is_none() {
+            args.max_urls = self.network.max_urls.filter(|n| *n > 0);
+        }
+
+        if args.max_urls_per_domain.is_none() {
+            args.max_urls_per_domain = self.network.max_urls_per_domain.filter(|n| *n > 0);
+        }
     }
 
     fn apply_testing_config(&self, args: &mut Args) {
@@ -545,6 +813,56 @@ impl Config {
         if !args.extract_links && self.testing.extract_links.unwrap_or(false) {
             args.extract_links = true;
         }
+
+        if !args.body_stats && self.testing.body_stats.unwrap_or(false) {
+            args.body_stats = true;
+        }
+
+        if args.include_headers.is_empty() {
+            if let Some(include_headers) = &self.testing.include_headers {
+                args.include_headers = include_headers.clone();
+            }
+        }
+
+        if args.match_body.is_none() && self.testing.match_body.is_some() {
+            args.match_body = self.testing.match_body.clone();
+        }
+
+        if args.filter_body.is_none() && self.testing.filter_body.is_some() {
+            args.filter_body = self.testing.filter_body.clone();
+        }
+
+        if !args.extract_title && self.testing.extract_title.unwrap_or(false) {
+            args.extract_title = true;
+        }
+
+        if !args.extract_js_endpoints && self.testing.extract_js_endpoints.unwrap_or(false) {
+            args.extract_js_endpoints = true;
+        }
+
+        if !args.tls_info && self.testing.tls_info.unwrap_or(false) {
+            args.tls_info = true;
+        }
+
+        if !args.check_reflection && self.testing.check_reflection.unwrap_or(false) {
+            args.check_reflection = true;
+        }
+
+        if !args.check_open_redirect && self.testing.check_open_redirect.unwrap_or(false) {
+            args.check_open_redirect = true;
+        }
+
+        if !args.scan_secrets && self.testing.scan_secrets.unwrap_or(false) {
+            args.scan_secrets = true;
+        }
+
+        if !args.respect_robots && self.testing.respect_robots.unwrap_or(false) {
+            args.respect_robots = true;
+        }
+
+        if args.per_category_limit.is_none() {
+            args.per_category_limit = self.testing.per_category_limit.filter(|n| *n > 0);
+        }
     }
 
     fn apply_cache_config(&self, args: &mut Args) {
@@ -561,7 +879,7 @@ impl Config {
 
         if args.cache_path.is_none() {
             if let Some(cache_path) = &self.cache.cache_path {
-                args.cache_path = Some(PathBuf::from(cache_path));
+                args.cache_path = Some(expand_tilde(cache_path));
             }
         }
 
@@ -575,13 +893,58 @@ impl Config {
             }
         }
 
+        // `[cache.ttl]` entries go ahead of the CLI ones, so a flag still
+        // overrides the same provider.
+        if let Some(ttls) = &self.cache.ttl {
+            let mut entries: Vec<String> = ttls
+                .iter()
+                .map(|(id, value)| match value {
+                    toml::Value::String(s) => format!("{id}={s}"),
+                    other => format!("{id}={other}"),
+                })
+                .collect();
+            entries.append(&mut args.cache_ttl_by);
+            args.cache_ttl_by = entries;
+        }
+
         if !args.no_cache && self.cache.no_cache.unwrap_or(false) {
             args.no_cache = true;
         }
+
+        if !args.http_cache && self.cache.http_cache.unwrap_or(false) {
+            args.http_cache = true;
+        }
+
+        if args.http_cache_path.is_none() {
+            if let Some(path) = &self.cache.http_cache_path {
+                args.http_cache_path = Some(expand_tilde(path));
+            }
+        }
+
+        if !args.smart_order && self.cache.smart_order.unwrap_or(false) {
+            args.smart_order = true;
+        }
+
+        if args.cache_namespace.is_none() {
+            args.cache_namespace = self.cache.namespace.clone();
+        }
+    }
+}
+
+/// Expand a leading `~` in a config-file path to the home directory. The
+/// shell does this for paths given on the command line, but not for values
+/// read from TOML, which would otherwise name a literal `./~` directory.
+fn expand_tilde(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    match path.strip_prefix("~") {
+        Ok(rest) => match home_dir() {
+            Some(home) => home.join(rest),
+            None => path.to_path_buf(),
+        },
+        Err(_) => path.to_path_buf(),
     }
 }
 
-#[cfg_attr(windows, allow(dead_code))]
 /// Helper function to get the home directory
 fn home_dir() -> Option<PathBuf> {
     env::var_os("HOME").map(PathBuf::from).or({
@@ -610,6 +973,7 @@ fn home_dir() -> Option<PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
     use clap::Parser;
     use std::io::Write;
     use tempfile::NamedTempFile;
@@ -758,6 +1122,102 @@ mod tests {
             wayback_from: None,
             wayback_to: None,
             github_api_key: vec![],
+            watch_ct: false,
+            ct_stream_url: crate::providers::DEFAULT_CERTSTREAM_URL.to_string(),
+            urlscan_max_results: crate::providers::DEFAULT_URLSCAN_MAX_RESULTS,
+            since: None,
+            urlscan_submit: false,
+            urlscan_submit_max: crate::providers::DEFAULT_URLSCAN_SUBMIT_MAX,
+            ignore_port: false,
+            stream: false,
+            bloom_fp_rate: 0.001,
+            bloom_capacity: 10_000_000,
+            stream_exact: false,
+            retry_failed_domains: 0,
+            sign_output: None,
+            list_presets: false,
+            wayback_collapse: None,
+            wayback_filter: vec![],
+            wayback_match_type: None,
+            fail_on_new: false,
+            provider_opt: vec![],
+            check_reflection: false,
+            max_bandwidth: None,
+            explain_scope: false,
+            global_rate_limit: None,
+            max_requests: None,
+            max_urls: None,
+            smart_order: false,
+            circuit_breaker: 5,
+            cache_namespace: None,
+            list_cache_namespaces: false,
+            delete_cache_namespace: None,
+            provider_status_filter: vec![],
+            schema: false,
+            check_providers: false,
+            dry_run: false,
+            extract_sourcemaps: false,
+            max_body_bytes: 5_000_000,
+            max_urls_per_domain: None,
+            hosts_output: None,
+            provider_quota: vec![],
+            cache_ttl_by: vec![],
+            http_cache: false,
+            http_cache_path: None,
+            split_output_by_tag: false,
+            from: None,
+            to: None,
+            force_progress: false,
+            custom_providers: vec![],
+            per_category_limit: None,
+            plugin_providers: vec![],
+            metrics_addr: None,
+            robots_wildcards: false,
+            deterministic: false,
+            seed: 0,
+            extract_title: false,
+            tls_info: false,
+            check_open_redirect: false,
+            scan_secrets: false,
+            secrets_output: None,
+            extract_js_endpoints: false,
+            body_stats: false,
+            include_external: false,
+            respect_robots: false,
+            include_headers: vec![],
+            match_body: None,
+            filter_body: None,
+            http_version: None,
+            format_template: None,
+            compress: None,
+            append: false,
+            diff: None,
+            stats_json: None,
+            es_url: None,
+            es_index: "urx".to_string(),
+            csv_columns: None,
+            count: false,
+            count_by: None,
+            exclude_hosts: vec![],
+            custom_presets: Default::default(),
+            strip_params: vec![],
+            clean_params: false,
+            dedup_similar: false,
+            min_params: None,
+            max_params: None,
+            exclude_file: None,
+            exclude_regexes: vec![],
+            case_sensitive: false,
+            seen_after: None,
+            seen_before: None,
+            sample_per_path: None,
+            show_only_param_keys: false,
+            param_key_counts: false,
+            fuzz_params: false,
+            fuzz_path_last: false,
+            replace_values: None,
+            stats_markdown: None,
+            fail_on_unchanged: false,
         };
         assert_eq!(args.output, None);
         assert_eq!(args.format, "plain");
@@ -772,6 +1232,25 @@ mod tests {
         assert_eq!(args.providers, vec!["cc"]);
     }
 
+    #[test]
+    fn test_apply_to_args_expands_tilde_in_paths() {
+        let Some(home) = home_dir() else {
+            return;
+        };
+        let mut config = Config::default();
+        config.cache.cache_path = Some("~/.urx/cache.db".to_string());
+        config.cache.http_cache_path = Some("~/.urx/http-cache".to_string());
+        config.output.output = Some("out/~/urls.txt".to_string());
+
+        let mut args = Args::parse_from(["urx", "example.com"]);
+        config.apply_to_args(&mut args);
+
+        assert_eq!(args.cache_path, Some(home.join(".urx/cache.db")));
+        assert_eq!(args.http_cache_path, Some(home.join(".urx/http-cache")));
+        // Only a leading `~` is the home directory
+        assert_eq!(args.output, Some(PathBuf::from("out/~/urls.txt")));
+    }
+
     #[test]
     fn test_apply_to_args_ignores_invalid_network_values() {
         let mut config = Config::default();
@@ -811,6 +1290,103 @@ mod tests {
         assert_eq!(args.network_scope, "providers,testers");
     }
 
+    #[test]
+    fn test_provider_sections_become_provider_opts() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [provider]
+            providers = ["wayback", "vt"]
+            verbose = false
+
+            [provider.wayback]
+            timeout = 300
+            retries = 5
+
+            [provider.vt]
+            rate_limit = 0.5
+            "#,
+        )?;
+        assert_eq!(
+            config.provider.providers,
+            Some(vec!["wayback".to_string(), "vt".to_string()])
+        );
+
+        // The CLI entry for the same key is applied last, so it wins
+        let mut args =
+            Args::parse_from(["urx", "example.com", "--provider-opt", "wayback:timeout=60"]);
+        config.apply_to_args(&mut args);
+        let overrides = args.provider_overrides()?;
+        assert_eq!(overrides["wayback"].timeout, Some(60));
+        assert_eq!(overrides["wayback"].retries, Some(5));
+        assert_eq!(overrides["vt"].rate_limit, Some(0.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_cache_ttl_table_becomes_cache_ttl_by() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [cache]
+            cache_ttl = 43200
+
+            [cache.ttl]
+            wayback = "7d"
+            robots = 3600
+            "#,
+        )?;
+
+        let mut args = Args::parse_from(["urx", "example.com", "--cache-ttl-by", "robots=5m"]);
+        config.apply_to_args(&mut args);
+        assert_eq!(args.cache_ttl_for("wayback"), 604_800);
+        assert_eq!(args.cache_ttl_for("robots"), 300);
+        assert_eq!(args.cache_ttl_for("otx"), 43_200);
+        Ok(())
+    }
+
+    #[test]
+    fn test_exclude_providers_from_config() -> Result<()> {
+        let toml = r#"
+            [provider]
+            exclude_providers = ["cc", "sitemap"]
+        "#;
+        let mut args = Args::parse_from(["urx", "example.com"]);
+        toml::from_str::<Config>(toml)?.apply_to_args(&mut args);
+        assert_eq!(args.exclude_providers, vec!["cc", "sitemap"]);
+
+        // A CLI list replaces the config one
+        let mut args = Args::parse_from(["urx", "example.com", "--exclude-providers", "otx"]);
+        toml::from_str::<Config>(toml)?.apply_to_args(&mut args);
+        assert_eq!(args.exclude_providers, vec!["otx"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_preset_tables_become_custom_presets() -> Result<()> {
+        let config: Config = toml::from_str(
+            r#"
+            [filter]
+            preset = ["my-api"]
+
+            [preset.my-api]
+            description = "API routes without images"
+            patterns = ["/api/"]
+            exclude_extensions = ["png"]
+            "#,
+        )?;
+        let mut args = Args::parse_from(["urx", "example.com"]);
+        config.apply_to_args(&mut args);
+        assert_eq!(args.preset, vec!["my-api"]);
+        assert_eq!(args.custom_presets["my-api"].patterns, vec!["/api/"]);
+        assert_eq!(
+            args.custom_presets["my-api"].exclude_extensions,
+            vec!["png"]
+        );
+
+        // Unknown keys in a preset are a config error, not silently ignored
+        assert!(toml::from_str::<Config>("[preset.bad]\nextension = [\"js\"]").is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_provider_keys_config_parses_csv() -> Result<()> {
         let content = r#"
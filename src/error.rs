@@ -0,0 +1,91 @@
+/// Typed errors for the boundaries between urx's modules.
+///
+/// Modules keep using `anyhow` internally; what crosses into the rest of the
+/// program (a config load, a cache call, a provider fetch, …) is wrapped in
+/// the [`UrxError`] variant for that kind of failure, so callers can match on
+/// it and `main` can pick an exit code without parsing messages.
+use std::error::Error as StdError;
+
+type Source = Box<dyn StdError + Send + Sync + 'static>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum UrxError {
+    /// A provider could not be set up or its fetch failed.
+    #[error(transparent)]
+    Provider(Source),
+    /// The result cache could not be opened, read or written.
+    #[error(transparent)]
+    Cache(Source),
+    /// An input file could not be read.
+    #[error(transparent)]
+    Io(Source),
+    /// The config file or a configured value is invalid.
+    #[error(transparent)]
+    Config(Source),
+    /// A tester (status check, link extraction, …) failed on a URL.
+    #[error(transparent)]
+    Tester(Source),
+}
+
+pub type UrxResult<T> = std::result::Result<T, UrxError>;
+
+impl UrxError {
+    pub fn provider(err: impl Into<anyhow::Error>) -> Self {
+        UrxError::Provider(err.into().into())
+    }
+
+    pub fn cache(err: impl Into<anyhow::Error>) -> Self {
+        UrxError::Cache(err.into().into())
+    }
+
+    pub fn io(err: impl Into<anyhow::Error>) -> Self {
+        UrxError::Io(err.into().into())
+    }
+
+    pub fn config(err: impl Into<anyhow::Error>) -> Self {
+        UrxError::Config(err.into().into())
+    }
+
+    pub fn tester(err: impl Into<anyhow::Error>) -> Self {
+        UrxError::Tester(err.into().into())
+    }
+
+    /// Process exit code for a run that ends with this error. 1 stays the
+    /// code for untyped failures and 3 belongs to incremental mode, so the
+    /// typed kinds start at 4; config errors share clap's usage code 2.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            UrxError::Config(_) => 2,
+            UrxError::Provider(_) => 4,
+            UrxError::Cache(_) => 5,
+            UrxError::Io(_) => 6,
+            UrxError::Tester(_) => 7,
+        }
+    }
+}
+
+/// Exit code for `err`: the code of the first [`UrxError`] in its chain, or 1.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    err.chain()
+        .find_map(|e| e.downcast_ref::<UrxError>())
+        .map_or(1, UrxError::exit_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code_follows_the_typed_error_through_context() {
+        let err: anyhow::Error = UrxError::cache(anyhow::anyhow!("database is locked")).into();
+        assert_eq!(err.to_string(), "database is locked");
+        assert_eq!(exit_code(&err), 5);
+
+        let wrapped = Err::<(), _>(UrxError::config(anyhow::anyhow!("bad toml")))
+            .context("loading config")
+            .unwrap_err();
+        assert_eq!(exit_code(&wrapped), 2);
+        assert_eq!(exit_code(&anyhow::anyhow!("untyped")), 1);
+    }
+}
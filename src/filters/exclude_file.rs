@@ -0,0 +1,72 @@
+/// `--exclude-file`: a deny-list of URL patterns, one per line.
+use anyhow::{Context, Result};
+use regex::{Regex, RegexBuilder};
+use std::path::Path;
+
+/// Prefix marking a line as a regular expression rather than a substring.
+const REGEX_PREFIX: &str = "re:";
+
+/// Patterns read from an `--exclude-file`.
+#[derive(Debug, Default)]
+pub struct ExcludeList {
+    /// Plain lines, matched as substrings like `--exclude-patterns`
+    pub substrings: Vec<String>,
+    /// `re:` lines, compiled case-insensitive by default
+    pub regexes: Vec<Regex>,
+}
+
+/// Parse deny-list text. Blank lines and `#` comments are skipped; a line
+/// starting with `re:` is a regex, anything else a substring. Regexes ignore
+/// case unless `case_sensitive` (`--case-sensitive`).
+pub fn parse_exclude_list(text: &str, case_sensitive: bool) -> Result<ExcludeList> {
+    let mut list = ExcludeList::default();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.strip_prefix(REGEX_PREFIX) {
+            Some(pattern) => list.regexes.push(
+                RegexBuilder::new(pattern.trim())
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .with_context(|| format!("line {}: invalid regex", number + 1))?,
+            ),
+            None => list.substrings.push(line.to_string()),
+        }
+    }
+    Ok(list)
+}
+
+/// Read and parse an `--exclude-file`.
+pub fn read_exclude_file(path: &Path, case_sensitive: bool) -> Result<ExcludeList> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read exclude file {}", path.display()))?;
+    parse_exclude_list(&text, case_sensitive)
+        .with_context(|| format!("In exclude file {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exclude_list() {
+        let list = parse_exclude_list(
+            "# known noise\n/static/\n\n  logout  \nre:/wp-content/.*\\.(png|jpg)$\nre: ^https://cdn\\.\n",
+            false,
+        )
+        .unwrap();
+        assert_eq!(list.substrings, vec!["/static/", "logout"]);
+        assert_eq!(list.regexes.len(), 2);
+        assert!(list.regexes[0].is_match("https://example.com/wp-content/uploads/A.PNG"));
+        assert!(list.regexes[1].is_match("https://CDN.example.com/x"));
+
+        let strict = parse_exclude_list("re:/Token/", true).unwrap();
+        assert!(strict.regexes[0].is_match("https://example.com/Token/1"));
+        assert!(!strict.regexes[0].is_match("https://example.com/token/1"));
+
+        let err = parse_exclude_list("ok\nre:(unclosed\n", false).unwrap_err();
+        assert!(format!("{err:#}").starts_with("line 2: invalid regex"));
+    }
+}
@@ -0,0 +1,110 @@
+/// `--since` filtering on provider-reported modification dates (currently the
+/// sitemap `<lastmod>` field), and `--seen-after`/`--seen-before` on archive
+/// snapshot timestamps.
+use serde_json::{Map, Value};
+
+/// Validate a `--since` value: `YYYY`, `YYYY-MM`, or `YYYY-MM-DD`. Returns the
+/// trimmed value, which compares correctly against W3C datetimes as a plain
+/// string because both are big-endian and zero-padded.
+pub fn parse_since(value: &str) -> Option<String> {
+    let value = value.trim();
+    let parts: Vec<&str> = value.split('-').collect();
+    let widths = [4, 2, 2];
+    if parts.is_empty() || parts.len() > widths.len() {
+        return None;
+    }
+    let well_formed = parts
+        .iter()
+        .zip(widths)
+        .all(|(p, w)| p.len() == w && p.bytes().all(|b| b.is_ascii_digit()));
+    well_formed.then(|| value.to_string())
+}
+
+/// Whether a URL passes `--since`. URLs without a `lastmod` are kept: most
+/// providers don't report one, and unknown freshness isn't evidence of staleness.
+pub fn is_modified_since(metadata: Option<&Map<String, Value>>, since: &str) -> bool {
+    let Some(lastmod) = metadata
+        .and_then(|m| m.get("lastmod"))
+        .and_then(Value::as_str)
+    else {
+        return true;
+    };
+    // Compare only the date portion so `2024-05-01T10:00:00+09:00` against
+    // `2024-05-01` counts as the same day.
+    let date = lastmod.get(..10).unwrap_or(lastmod);
+    date >= since
+}
+
+/// Whether a URL's snapshot `timestamp` (Wayback, Common Crawl, urlscan) falls
+/// in the `--seen-after`/`--seen-before` window: on or after `after`, strictly
+/// before `before`. Both take [`parse_since`] values, so `--seen-before 2024`
+/// keeps captures up to the end of 2023. URLs without a timestamp are kept,
+/// as with `--since`.
+pub fn is_seen_within(
+    metadata: Option<&Map<String, Value>>,
+    after: Option<&str>,
+    before: Option<&str>,
+) -> bool {
+    let Some(timestamp) = metadata
+        .and_then(|m| m.get("timestamp"))
+        .and_then(Value::as_str)
+    else {
+        return true;
+    };
+    let date = timestamp.get(..10).unwrap_or(timestamp);
+    after.is_none_or(|after| date >= after) && before.is_none_or(|before| date < before)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(lastmod: &str) -> Map<String, Value> {
+        let mut m = Map::new();
+        m.insert("lastmod".to_string(), Value::from(lastmod));
+        m
+    }
+
+    #[test]
+    fn test_parse_since() {
+        assert_eq!(parse_since("2024").as_deref(), Some("2024"));
+        assert_eq!(parse_since(" 2024-03 ").as_deref(), Some("2024-03"));
+        assert_eq!(parse_since("2024-03-09").as_deref(), Some("2024-03-09"));
+        assert!(parse_since("24-03").is_none());
+        assert!(parse_since("2024/03/09").is_none());
+        assert!(parse_since("2024-03-09-01").is_none());
+    }
+
+    #[test]
+    fn test_is_modified_since() {
+        assert!(is_modified_since(Some(&meta("2024-05-01")), "2024-05"));
+        assert!(is_modified_since(
+            Some(&meta("2024-05-01T10:00:00+09:00")),
+            "2024-05-01"
+        ));
+        assert!(!is_modified_since(Some(&meta("2023-12-31")), "2024"));
+        // No lastmod means unknown freshness — keep it.
+        assert!(is_modified_since(None, "2024"));
+        assert!(is_modified_since(Some(&Map::new()), "2024"));
+    }
+
+    #[test]
+    fn test_is_seen_within() {
+        let mut captured = Map::new();
+        captured.insert("timestamp".to_string(), Value::from("2023-06-15T08:00:00Z"));
+        let captured = Some(&captured);
+
+        assert!(is_seen_within(captured, Some("2023-01-01"), None));
+        assert!(is_seen_within(captured, Some("2023-06"), Some("2024")));
+        assert!(is_seen_within(
+            captured,
+            Some("2023-06-15"),
+            Some("2023-06-16")
+        ));
+        assert!(!is_seen_within(captured, Some("2023-07"), None));
+        assert!(!is_seen_within(captured, None, Some("2023-06-15")));
+        assert!(!is_seen_within(captured, None, Some("2023")));
+        // No snapshot timestamp — keep it.
+        assert!(is_seen_within(None, Some("2030"), Some("2000")));
+    }
+}
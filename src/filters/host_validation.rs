@@ -1,68 +1,264 @@
-use std::collections::HashSet;
+use crate::utils::split_host_port;
 use url::Url;
 
 /// Validates whether URLs have the same host as the provided domains
 pub struct HostValidator {
-    domains: HashSet<String>,
+    /// Normalized target hosts, each with the explicit port it was given as
+    /// (`example.com:8080`), if any
+    targets: Vec<(String, Option<u16>)>,
+    /// Accept any host not excluded, instead of checking `targets`
+    any_host: bool,
+    /// `--exclude-hosts` patterns: exact hosts, or `*.suffix` for any
+    /// subdomain of `suffix`
+    excluded: Vec<String>,
     include_subdomains: bool,
+    ignore_port: bool,
 }
 
 impl HostValidator {
     /// Create a new host validator with the given domains that can include subdomains
     pub fn new(domains: &[String], include_subdomains: bool) -> Self {
-        let normalized_domains: HashSet<String> = domains
+        let mut targets: Vec<(String, Option<u16>)> = domains
             .iter()
             .map(|domain| {
-                domain
-                    .trim()
-                    .to_lowercase()
-                    .trim_end_matches('.')
-                    .to_string()
+                let normalized = domain.trim().to_lowercase();
+                let (host, port) = split_host_port(&normalized);
+                (host.trim_end_matches('.').to_string(), port)
             })
             .collect();
+        targets.sort();
+        targets.dedup();
 
         HostValidator {
-            domains: normalized_domains,
+            targets,
+            any_host: false,
+            excluded: Vec::new(),
             include_subdomains,
+            ignore_port: false,
         }
     }
 
+    /// A validator that accepts every URL except those on excluded hosts,
+    /// for `--exclude-hosts` without strict validation
+    pub fn any_host() -> Self {
+        HostValidator {
+            any_host: true,
+            ..HostValidator::new(&[], false)
+        }
+    }
+
+    /// Reject hosts matching any of `patterns`, even when they are targets
+    /// (`--exclude-hosts`). `*.cdn.example.com` matches the subdomains of
+    /// `cdn.example.com`; other patterns match the host exactly.
+    pub fn with_excluded_hosts(&mut self, patterns: &[String]) -> &mut Self {
+        self.excluded = patterns
+            .iter()
+            .map(|pattern| pattern.trim().trim_end_matches('.').to_lowercase())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+        self
+    }
+
+    /// When enabled, a target given as `host:port` matches that host on any
+    /// port (`--ignore-port`)
+    pub fn with_ignore_port(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_port = ignore;
+        self
+    }
+
     /// Validate that the URL's host matches one of the provided domains
     pub fn is_valid_host(&self, url_str: &str) -> bool {
-        if let Ok(url) = Url::parse(url_str) {
-            if let Some(host) = url.host_str() {
-                // Normalize the host for comparison (lowercase and strip trailing dot)
-                let normalized_host = host.to_lowercase();
-                let host_stripped = normalized_host.trim_end_matches('.');
-
-                // Check if the host exactly matches any of our domains
-                if self.domains.contains(host_stripped) {
-                    return true;
-                }
+        self.explain(url_str).is_none()
+    }
+
+    /// Why `url_str` is out of scope, or `None` if it is accepted. Backs
+    /// [`is_valid_host`](Self::is_valid_host) and `--explain-scope`.
+    pub fn explain(&self, url_str: &str) -> Option<ScopeRejection> {
+        // If we can't parse the URL or it has no host, consider it invalid
+        let Ok(url) = Url::parse(url_str) else {
+            return (!self.any_host).then_some(ScopeRejection::Unparseable);
+        };
+        let Some(host) = url.host_str() else {
+            return (!self.any_host).then_some(ScopeRejection::NoHost);
+        };
+
+        // Normalize the host for comparison (lowercase and strip trailing dot)
+        let normalized_host = host.to_lowercase();
+        let host_stripped = normalized_host.trim_end_matches('.');
+        let url_port = url.port_or_known_default();
 
-                if self.include_subdomains {
-                    // If subdomains are allowed, accept any subdomain of a target.
-                    for domain in &self.domains {
-                        if host_stripped.ends_with(&format!(".{domain}")) {
-                            return true;
-                        }
-                    }
-                } else {
-                    // Even in strict (apex-only) mode, treat the conventional
-                    // `www.` host as the apex itself: a site served entirely on
-                    // www.<domain> must not return zero results for a bare
-                    // `<domain>` query. Other subdomains still require --subs.
-                    for domain in &self.domains {
-                        if host_stripped == format!("www.{domain}") {
-                            return true;
-                        }
-                    }
+        if let Some(pattern) = self.excluded_by(host_stripped) {
+            return Some(ScopeRejection::Excluded {
+                host: host_stripped.to_string(),
+                pattern: pattern.to_string(),
+            });
+        }
+        if self.any_host {
+            return None;
+        }
+
+        let mut rejection = ScopeRejection::HostMismatch(host_stripped.to_string());
+        for (domain, port) in &self.targets {
+            let host_match = self.host_matches(host_stripped, domain);
+            // A target without a port accepts any port; one with a port only
+            // accepts URLs served on it (default ports included, so
+            // `example.com:443` matches `https://example.com`).
+            let port_matches = self.ignore_port || port.is_none() || *port == url_port;
+            match host_match {
+                HostMatch::Yes if port_matches => return None,
+                // Keep the most specific reason across targets: a port
+                // mismatch beats a subdomain miss beats an unrelated host.
+                HostMatch::Yes => {
+                    rejection = ScopeRejection::PortMismatch {
+                        host: host_stripped.to_string(),
+                        port: url_port,
+                        expected: port.unwrap_or_default(),
+                    };
+                }
+                HostMatch::SubdomainNotAllowed
+                    if matches!(rejection, ScopeRejection::HostMismatch(_)) =>
+                {
+                    rejection = ScopeRejection::SubdomainNotAllowed {
+                        host: host_stripped.to_string(),
+                        target: domain.clone(),
+                    };
                 }
+                HostMatch::PublicSuffix
+                    if matches!(rejection, ScopeRejection::HostMismatch(_))
+                        && host_stripped.ends_with(&format!(".{domain}")) =>
+                {
+                    rejection = ScopeRejection::PublicSuffix {
+                        host: host_stripped.to_string(),
+                        target: domain.clone(),
+                    };
+                }
+                _ => {}
             }
         }
+        Some(rejection)
+    }
 
-        // If we can't parse the URL or it has no host, consider it invalid
-        false
+    /// The first `--exclude-hosts` pattern matching `host`, if any.
+    fn excluded_by(&self, host: &str) -> Option<&str> {
+        self.excluded
+            .iter()
+            .find(|pattern| match pattern.strip_prefix("*.") {
+                Some(suffix) => host
+                    .strip_suffix(suffix)
+                    .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('.')),
+                None => host == pattern.as_str(),
+            })
+            .map(String::as_str)
+    }
+
+    fn host_matches(&self, host: &str, domain: &str) -> HostMatch {
+        // Check if the host exactly matches the domain
+        if host == domain {
+            return HostMatch::Yes;
+        }
+
+        // Names under a public suffix (`co.uk`, `github.io`) belong to
+        // unrelated owners, so such a target only ever matches itself
+        if is_public_suffix(domain) {
+            return HostMatch::PublicSuffix;
+        }
+
+        match host.strip_suffix(domain).and_then(|p| p.strip_suffix('.')) {
+            // If subdomains are allowed, accept any subdomain of a target.
+            Some(_) if self.include_subdomains => HostMatch::Yes,
+            // Even in strict (apex-only) mode, treat the conventional
+            // `www.` host as the apex itself: a site served entirely on
+            // www.<domain> must not return zero results for a bare
+            // `<domain>` query. Other subdomains still require --subs.
+            Some("www") => HostMatch::Yes,
+            Some(_) => HostMatch::SubdomainNotAllowed,
+            None => HostMatch::No,
+        }
+    }
+}
+
+enum HostMatch {
+    Yes,
+    SubdomainNotAllowed,
+    PublicSuffix,
+    No,
+}
+
+/// Whether `domain` is itself an entry of the Public Suffix List (ICANN or
+/// private, e.g. `co.uk` or `github.io`). Names the list doesn't know, such
+/// as `localhost` or internal TLDs, are not.
+fn is_public_suffix(domain: &str) -> bool {
+    psl::suffix(domain.as_bytes())
+        .is_some_and(|suffix| suffix.is_known() && suffix.as_bytes() == domain.as_bytes())
+}
+
+/// Why [`HostValidator`] rejected a URL, for `--explain-scope`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScopeRejection {
+    /// Not a parseable absolute URL
+    Unparseable,
+    /// Parsed, but has no host (`mailto:`, `data:`, `file:`)
+    NoHost,
+    /// Host is not any target or a subdomain of one
+    HostMismatch(String),
+    /// Host is a subdomain of `target`, but `--subs` is off
+    SubdomainNotAllowed { host: String, target: String },
+    /// Host matches, but the target pins a different port
+    PortMismatch {
+        host: String,
+        port: Option<u16>,
+        expected: u16,
+    },
+    /// Host is under `target`, but `target` is a public suffix (`co.uk`), so
+    /// the host is someone else's domain
+    PublicSuffix { host: String, target: String },
+    /// Host matches an `--exclude-hosts` pattern
+    Excluded { host: String, pattern: String },
+}
+
+impl ScopeRejection {
+    /// Short category name used to group decisions in reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ScopeRejection::Unparseable | ScopeRejection::NoHost => "invalid url",
+            ScopeRejection::HostMismatch(_) => "host mismatch",
+            ScopeRejection::SubdomainNotAllowed { .. } => "subdomain policy",
+            ScopeRejection::PortMismatch { .. } => "port mismatch",
+            ScopeRejection::PublicSuffix { .. } => "public suffix",
+            ScopeRejection::Excluded { .. } => "excluded host",
+        }
+    }
+}
+
+impl std::fmt::Display for ScopeRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScopeRejection::Unparseable => write!(f, "not a valid absolute URL"),
+            ScopeRejection::NoHost => write!(f, "URL has no host"),
+            ScopeRejection::HostMismatch(host) => write!(f, "host {host} is not a target"),
+            ScopeRejection::SubdomainNotAllowed { host, target } => write!(
+                f,
+                "{host} is a subdomain of {target}; pass --subs to keep it"
+            ),
+            ScopeRejection::PortMismatch {
+                host,
+                port,
+                expected,
+            } => match port {
+                Some(port) => write!(
+                    f,
+                    "{host} served on port {port}, target expects {expected}; pass --ignore-port to keep it"
+                ),
+                None => write!(f, "{host} has no port, target expects {expected}"),
+            },
+            ScopeRejection::PublicSuffix { host, target } => write!(
+                f,
+                "{target} is a public suffix; {host} is a separate registrable domain"
+            ),
+            ScopeRejection::Excluded { host, pattern } => {
+                write!(f, "{host} matches --exclude-hosts {pattern}")
+            }
+        }
     }
 }
 
@@ -163,4 +359,127 @@ mod tests {
         assert!(validator.is_valid_host("https://sub.test.org"));
         assert!(validator.is_valid_host("https://sub.test.org."));
     }
+
+    #[test]
+    fn test_host_validation_with_port() {
+        let domains = vec!["example.com:8080".to_string(), "test.org".to_string()];
+        let validator = HostValidator::new(&domains, false);
+
+        assert!(validator.is_valid_host("http://example.com:8080/admin"));
+        assert!(validator.is_valid_host("https://www.example.com:8080/"));
+        assert!(!validator.is_valid_host("https://example.com/admin"));
+        assert!(!validator.is_valid_host("http://example.com:9090/admin"));
+        // No port on the target means any port
+        assert!(validator.is_valid_host("https://test.org:8443/"));
+
+        // Default ports are compared through the scheme
+        let validator = HostValidator::new(&["example.com:443".to_string()], false);
+        assert!(validator.is_valid_host("https://example.com/"));
+        assert!(!validator.is_valid_host("http://example.com/"));
+
+        let mut validator = HostValidator::new(&domains, false);
+        validator.with_ignore_port(true);
+        assert!(validator.is_valid_host("https://example.com/admin"));
+        assert!(validator.is_valid_host("http://example.com:9090/admin"));
+    }
+
+    #[test]
+    fn test_explain_reports_rejection_reason() {
+        let domains = vec!["example.com:8080".to_string(), "test.org".to_string()];
+        let validator = HostValidator::new(&domains, false);
+
+        assert_eq!(validator.explain("https://test.org/"), None);
+        assert_eq!(
+            validator.explain("https://other.net/"),
+            Some(ScopeRejection::HostMismatch("other.net".to_string()))
+        );
+        assert_eq!(
+            validator.explain("https://api.test.org/"),
+            Some(ScopeRejection::SubdomainNotAllowed {
+                host: "api.test.org".to_string(),
+                target: "test.org".to_string(),
+            })
+        );
+        assert_eq!(
+            validator.explain("http://example.com:9090/"),
+            Some(ScopeRejection::PortMismatch {
+                host: "example.com".to_string(),
+                port: Some(9090),
+                expected: 8080,
+            })
+        );
+        assert_eq!(
+            validator.explain("mailto:user@example.com"),
+            Some(ScopeRejection::NoHost)
+        );
+        assert_eq!(
+            validator.explain("not-a-url"),
+            Some(ScopeRejection::Unparseable)
+        );
+        assert_eq!(
+            validator.explain("https://api.test.org/").map(|r| r.kind()),
+            Some("subdomain policy")
+        );
+    }
+
+    #[test]
+    fn test_public_suffix_targets() {
+        let domains = vec!["example.co.uk".to_string(), "github.io".to_string()];
+        let validator = HostValidator::new(&domains, true);
+
+        // Multi-part TLDs: subdomains of the registrable domain only
+        assert!(validator.is_valid_host("https://shop.example.co.uk/"));
+        assert!(validator.is_valid_host("https://a.b.example.co.uk/"));
+        assert!(!validator.is_valid_host("https://example.co.uk.evil.com/"));
+        assert!(!validator.is_valid_host("https://notexample.co.uk/"));
+        assert!(!validator.is_valid_host("https://other.co.uk/"));
+
+        // A public suffix target covers itself, never other people's sites
+        assert!(validator.is_valid_host("https://github.io/"));
+        assert_eq!(
+            validator.explain("https://someone.github.io/"),
+            Some(ScopeRejection::PublicSuffix {
+                host: "someone.github.io".to_string(),
+                target: "github.io".to_string(),
+            })
+        );
+
+        // Names the list doesn't know still take subdomains
+        let validator = HostValidator::new(&["intranet.corp".to_string()], true);
+        assert!(validator.is_valid_host("http://wiki.intranet.corp/"));
+        let validator = HostValidator::new(&["localhost".to_string()], true);
+        assert!(validator.is_valid_host("http://api.localhost:3000/"));
+        assert!(is_public_suffix("co.uk"));
+        assert!(!is_public_suffix("example.co.uk"));
+    }
+
+    #[test]
+    fn test_excluded_hosts() {
+        let mut validator = HostValidator::new(&["example.com".to_string()], true);
+        validator.with_excluded_hosts(&[
+            "static.example.com".to_string(),
+            "*.CDN.example.com.".to_string(),
+        ]);
+
+        assert!(validator.is_valid_host("https://api.example.com/"));
+        assert!(validator.is_valid_host("https://cdn.example.com/"));
+        assert!(!validator.is_valid_host("https://static.example.com/app.js"));
+        assert!(!validator.is_valid_host("https://eu.cdn.example.com/img.png"));
+        assert!(!validator.is_valid_host("https://a.b.cdn.example.com/"));
+        // A wildcard needs a label boundary
+        assert!(validator.is_valid_host("https://mycdn.example.com/"));
+        assert_eq!(
+            validator.explain("https://eu.cdn.example.com/"),
+            Some(ScopeRejection::Excluded {
+                host: "eu.cdn.example.com".to_string(),
+                pattern: "*.cdn.example.com".to_string(),
+            })
+        );
+
+        let mut validator = HostValidator::any_host();
+        validator.with_excluded_hosts(&["static.example.com".to_string()]);
+        assert!(validator.is_valid_host("https://other.net/"));
+        assert!(!validator.is_valid_host("https://static.example.com/"));
+        assert!(validator.is_valid_host("not-a-url"));
+    }
 }
@@ -1,6 +1,15 @@
+mod exclude_file;
+mod freshness;
 mod host_validation;
 mod preset;
+mod scope_report;
+mod status;
 mod url_filter;
 
-pub use host_validation::HostValidator;
+pub use exclude_file::read_exclude_file;
+pub use freshness::{is_modified_since, is_seen_within, parse_since};
+pub use host_validation::{HostValidator, ScopeRejection};
+pub use preset::{CustomPreset, PRESET_CATALOG};
+pub use scope_report::ScopeReport;
+pub use status::{status_matches_pattern, StatusFilter};
 pub use url_filter::UrlFilter;
@@ -1,3 +1,31 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A preset defined in config.toml as `[preset.<name>]`, usable with
+/// `--preset <name>` like the built-in ones:
+///
+/// ```toml
+/// [preset.my-api]
+/// description = "API routes without images"
+/// patterns = ["/api/"]
+/// exclude_extensions = ["png"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomPreset {
+    /// Line shown by `--list-presets`.
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    #[serde(default)]
+    pub exclude_extensions: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub exclude_patterns: Vec<String>,
+}
+
 /// Standard filter presets for common URL filtering scenarios
 pub enum FilterPreset {
     /// Excludes common web resource files (js, css, ico, ttf, etc.)
@@ -26,8 +54,110 @@ pub enum FilterPreset {
     OnlyAudio,
     /// Only includes image files
     OnlyImages,
+    /// Only includes server-side dynamic pages (php, asp, jsp, cgi, etc.)
+    OnlyDynamic,
+    /// Only includes API-looking paths (/api/, /v1/, graphql, etc.)
+    OnlyApi,
+    /// Only includes single-page-app fragment routes (`#/...`, `#!/...`)
+    OnlySpa,
+    /// Only includes files worth a look in triage: backups, dumps, configs,
+    /// archives, keys and logs
+    SensitiveFiles,
+    /// Only includes API routes and their descriptions (swagger, wsdl, ...)
+    ApiEndpoints,
+    /// Defined in config.toml
+    Custom(CustomPreset),
+}
+
+/// Name and one-line description of a preset, as shown by `--list-presets`.
+pub struct PresetInfo {
+    /// Canonical name accepted by `--preset`.
+    pub name: &'static str,
+    /// One-line description.
+    pub summary: &'static str,
 }
 
+/// Every preset urx knows about, in `--list-presets` order.
+pub const PRESET_CATALOG: &[PresetInfo] = &[
+    PresetInfo {
+        name: "no-resources",
+        summary: "Drop static resources (images, fonts, documents, media, js, css)",
+    },
+    PresetInfo {
+        name: "no-images",
+        summary: "Drop image files",
+    },
+    PresetInfo {
+        name: "no-fonts",
+        summary: "Drop font files",
+    },
+    PresetInfo {
+        name: "no-documents",
+        summary: "Drop document files (pdf, doc, xls, ...)",
+    },
+    PresetInfo {
+        name: "no-videos",
+        summary: "Drop video files",
+    },
+    PresetInfo {
+        name: "no-audio",
+        summary: "Drop audio files",
+    },
+    PresetInfo {
+        name: "only-js",
+        summary: "Keep JavaScript and related files (js, ts, jsx, map, ...)",
+    },
+    PresetInfo {
+        name: "only-style",
+        summary: "Keep stylesheets (css, scss, sass, less, ...)",
+    },
+    PresetInfo {
+        name: "only-fonts",
+        summary: "Keep font files",
+    },
+    PresetInfo {
+        name: "only-documents",
+        summary: "Keep document files",
+    },
+    PresetInfo {
+        name: "only-videos",
+        summary: "Keep video files",
+    },
+    PresetInfo {
+        name: "only-audio",
+        summary: "Keep audio files",
+    },
+    PresetInfo {
+        name: "only-images",
+        summary: "Keep image files",
+    },
+    PresetInfo {
+        name: "only-dynamic",
+        summary: "Keep server-side pages (php, asp(x), jsp, cgi, cfm, do, ...)",
+    },
+    PresetInfo {
+        name: "only-api",
+        summary: "Keep API paths (/api/, /v1/../v3/, /rest/, graphql)",
+    },
+    PresetInfo {
+        name: "only-spa",
+        summary: "Keep SPA fragment routes (#/..., #!/...)",
+    },
+    PresetInfo {
+        name: "sensitive-files",
+        summary:
+            "Keep backups, dumps, configs, archives, keys and logs (bak, sql, env, zip, pem, ...)",
+    },
+    PresetInfo {
+        name: "api-endpoints",
+        summary: "Keep API routes and specs (only-api plus swagger, openapi, wsdl, wp-json, ...)",
+    },
+    PresetInfo {
+        name: "documents",
+        summary: "Keep document files (same as only-documents)",
+    },
+];
+
 /// Common file extensions for various resource types
 const IMAGE_EXTENSIONS: &[&str] = &[
     "png", "jpg", "jpeg", "gif", "svg", "webp", "bmp", "ico", "tiff", "tif", "heic", "heif", "raw",
@@ -62,6 +192,86 @@ const JS_EXTENSIONS: &[&str] = &[
     "astro", "njk", "map",
 ];
 
+const DYNAMIC_EXTENSIONS: &[&str] = &[
+    "php", "php3", "php4", "php5", "php7", "phtml", "asp", "aspx", "ashx", "asmx", "axd", "jsp",
+    "jspx", "jspa", "do", "action", "cgi", "pl", "cfm", "cfml", "shtml",
+];
+
+/// Substrings that mark an API endpoint. Version segments are matched with
+/// both slashes so `/v1/` doesn't hit paths like `/blog/v1-launch`.
+const API_PATTERNS: &[&str] = &["/api/", "/v1/", "/v2/", "/v3/", "/rest/", "graphql"];
+
+/// Extensions of files that commonly leak data or credentials when left on a
+/// web server. `tar.gz` and friends are caught by their last extension.
+const SENSITIVE_EXTENSIONS: &[&str] = &[
+    "bak",
+    "backup",
+    "old",
+    "orig",
+    "save",
+    "swp",
+    "tmp",
+    "sql",
+    "db",
+    "sqlite",
+    "sqlite3",
+    "mdb",
+    "dump",
+    "env",
+    "config",
+    "conf",
+    "cfg",
+    "ini",
+    "properties",
+    "yml",
+    "yaml",
+    "toml",
+    "zip",
+    "tar",
+    "gz",
+    "tgz",
+    "bz2",
+    "xz",
+    "7z",
+    "rar",
+    "war",
+    "jar",
+    "pem",
+    "key",
+    "crt",
+    "cer",
+    "p12",
+    "pfx",
+    "jks",
+    "keystore",
+    "ppk",
+    "kdbx",
+    "ovpn",
+    "htpasswd",
+    "npmrc",
+    "log",
+];
+
+/// [`API_PATTERNS`] plus the usual homes of API descriptions and RPC routes.
+const API_ENDPOINT_PATTERNS: &[&str] = &[
+    "/api/",
+    "/v1/",
+    "/v2/",
+    "/v3/",
+    "/rest/",
+    "graphql",
+    "swagger",
+    "openapi",
+    "api-docs",
+    "wsdl",
+    "/wp-json/",
+    "/odata/",
+    "jsonrpc",
+];
+
+/// Fragment prefixes used by hash-based SPA routers (Angular, Vue, Backbone).
+const SPA_PATTERNS: &[&str] = &["#/", "#!/"];
+
 const STYLE_EXTENSIONS: &[&str] = &[
     "css", "scss", "sass", "less", "stylus", "postcss", "pcss", "cssm", "cssx", "cssb",
 ];
@@ -79,14 +289,30 @@ impl FilterPreset {
             "only-js" => Some(FilterPreset::OnlyJs),
             "only-style" | "only-styles" => Some(FilterPreset::OnlyStyle),
             "only-fonts" => Some(FilterPreset::OnlyFonts),
-            "only-documents" => Some(FilterPreset::OnlyDocuments),
+            "only-documents" | "documents" => Some(FilterPreset::OnlyDocuments),
             "only-videos" => Some(FilterPreset::OnlyVideos),
             "only-audio" | "only-audios" => Some(FilterPreset::OnlyAudio),
             "only-images" => Some(FilterPreset::OnlyImages),
+            "only-dynamic" => Some(FilterPreset::OnlyDynamic),
+            "only-api" => Some(FilterPreset::OnlyApi),
+            "only-spa" => Some(FilterPreset::OnlySpa),
+            "sensitive-files" | "sensitive-file" => Some(FilterPreset::SensitiveFiles),
+            "api-endpoints" | "api-endpoint" => Some(FilterPreset::ApiEndpoints),
             _ => None,
         }
     }
 
+    /// Look up `s` among the config-defined presets first (so they can
+    /// redefine a built-in name), then the built-in ones.
+    pub fn resolve(s: &str, custom: &BTreeMap<String, CustomPreset>) -> Option<Self> {
+        let name = s.to_lowercase();
+        custom
+            .iter()
+            .find(|(key, _)| key.to_lowercase() == name)
+            .map(|(_, preset)| FilterPreset::Custom(preset.clone()))
+            .or_else(|| Self::from_str(&name))
+    }
+
     /// Get excluded extensions for this preset
     pub fn get_exclude_extensions(&self) -> Vec<String> {
         match self {
@@ -116,6 +342,12 @@ impl FilterPreset {
             FilterPreset::OnlyVideos => VIDEO_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
             FilterPreset::OnlyAudio => AUDIO_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
             FilterPreset::OnlyImages => IMAGE_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
+            FilterPreset::OnlyDynamic
+            | FilterPreset::OnlyApi
+            | FilterPreset::OnlySpa
+            | FilterPreset::SensitiveFiles
+            | FilterPreset::ApiEndpoints => vec![],
+            FilterPreset::Custom(preset) => preset.exclude_extensions.clone(),
         }
     }
 
@@ -124,18 +356,38 @@ impl FilterPreset {
         match self {
             FilterPreset::OnlyJs => JS_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
             FilterPreset::OnlyStyle => STYLE_EXTENSIONS.iter().map(|&s| s.to_string()).collect(),
+            FilterPreset::OnlyDynamic => {
+                DYNAMIC_EXTENSIONS.iter().map(|&s| s.to_string()).collect()
+            }
+            FilterPreset::SensitiveFiles => SENSITIVE_EXTENSIONS
+                .iter()
+                .map(|&s| s.to_string())
+                .collect(),
+            FilterPreset::Custom(preset) => preset.extensions.clone(),
             _ => vec![],
         }
     }
 
     /// Get excluded patterns for this preset
     pub fn get_exclude_patterns(&self) -> Vec<String> {
-        vec![]
+        match self {
+            FilterPreset::Custom(preset) => preset.exclude_patterns.clone(),
+            _ => vec![],
+        }
     }
 
     /// Get included patterns for this preset
     pub fn get_patterns(&self) -> Vec<String> {
-        vec![]
+        match self {
+            FilterPreset::OnlyApi => API_PATTERNS.iter().map(|&s| s.to_string()).collect(),
+            FilterPreset::OnlySpa => SPA_PATTERNS.iter().map(|&s| s.to_string()).collect(),
+            FilterPreset::ApiEndpoints => API_ENDPOINT_PATTERNS
+                .iter()
+                .map(|&s| s.to_string())
+                .collect(),
+            FilterPreset::Custom(preset) => preset.patterns.clone(),
+            _ => vec![],
+        }
     }
 }
 
@@ -198,6 +450,42 @@ mod tests {
         assert!(FilterPreset::from_str("").is_none());
     }
 
+    #[test]
+    fn test_resolve_custom_preset() {
+        let custom = BTreeMap::from([
+            (
+                "my-api".to_string(),
+                CustomPreset {
+                    patterns: vec!["/api/".to_string()],
+                    exclude_extensions: vec!["png".to_string()],
+                    ..Default::default()
+                },
+            ),
+            (
+                "only-js".to_string(),
+                CustomPreset {
+                    extensions: vec!["js".to_string()],
+                    ..Default::default()
+                },
+            ),
+        ]);
+
+        let preset = FilterPreset::resolve("My-API", &custom).unwrap();
+        assert_eq!(preset.get_patterns(), vec!["/api/".to_string()]);
+        assert_eq!(preset.get_exclude_extensions(), vec!["png".to_string()]);
+        assert!(preset.get_extensions().is_empty());
+
+        // A config entry replaces the built-in of the same name
+        let preset = FilterPreset::resolve("only-js", &custom).unwrap();
+        assert_eq!(preset.get_extensions(), vec!["js".to_string()]);
+
+        assert!(matches!(
+            FilterPreset::resolve("no-images", &custom),
+            Some(FilterPreset::NoImages)
+        ));
+        assert!(FilterPreset::resolve("missing", &custom).is_none());
+    }
+
     #[test]
     fn test_no_resources_preset() {
         let preset = FilterPreset::NoResources;
@@ -493,6 +781,34 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_stack_presets() {
+        let dynamic = FilterPreset::from_str("only-dynamic").unwrap();
+        for ext in ["php", "aspx", "jsp", "cgi"] {
+            assert!(dynamic.get_extensions().contains(&ext.to_string()));
+        }
+        assert!(dynamic.get_patterns().is_empty());
+
+        let api = FilterPreset::from_str("Only-API").unwrap();
+        assert!(api.get_patterns().contains(&"/api/".to_string()));
+        assert!(api.get_patterns().contains(&"graphql".to_string()));
+        assert!(api.get_extensions().is_empty());
+
+        let spa = FilterPreset::from_str("only-spa").unwrap();
+        assert!(spa.get_patterns().contains(&"#/".to_string()));
+    }
+
+    #[test]
+    fn test_preset_catalog_names_parse() {
+        for info in PRESET_CATALOG {
+            assert!(
+                FilterPreset::from_str(info.name).is_some(),
+                "{} is listed but not parsed",
+                info.name
+            );
+        }
+    }
+
     #[test]
     fn test_filter_preset_from_str_only_images() {
         assert!(matches!(
@@ -0,0 +1,96 @@
+/// Sampled account of the URLs dropped by filtering and host validation,
+/// printed by `--explain-scope`.
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+/// Drop decisions grouped by reason kind. Every drop is counted, but only the
+/// first `sample` URLs of each kind keep their detailed reason, so the report
+/// stays short on runs that discard millions of URLs.
+pub struct ScopeReport {
+    sample: usize,
+    groups: BTreeMap<&'static str, ScopeGroup>,
+}
+
+#[derive(Default)]
+struct ScopeGroup {
+    count: usize,
+    samples: Vec<(String, String)>,
+}
+
+impl ScopeReport {
+    pub fn new(sample: usize) -> Self {
+        ScopeReport {
+            sample,
+            groups: BTreeMap::new(),
+        }
+    }
+
+    /// Record that `url` was dropped for `reason`, grouped under `kind`.
+    pub fn record(&mut self, kind: &'static str, url: &str, reason: impl Display) {
+        let group = self.groups.entry(kind).or_default();
+        group.count += 1;
+        if group.samples.len() < self.sample {
+            group.samples.push((url.to_string(), reason.to_string()));
+        }
+    }
+
+    /// Total number of dropped URLs recorded.
+    pub fn total(&self) -> usize {
+        self.groups.values().map(|g| g.count).sum()
+    }
+
+    /// Human-readable report: one header line per kind (most frequent first)
+    /// followed by its sampled URLs and reasons.
+    pub fn render(&self) -> String {
+        let mut groups: Vec<(&&str, &ScopeGroup)> = self.groups.iter().collect();
+        groups.sort_by(|a, b| b.1.count.cmp(&a.1.count).then(a.0.cmp(b.0)));
+
+        let mut out = format!("[urx] scope: {} URL(s) dropped\n", self.total());
+        for (kind, group) in groups {
+            out.push_str(&format!("  {kind}: {}\n", group.count));
+            for (url, reason) in &group.samples {
+                out.push_str(&format!("    {url} ({reason})\n"));
+            }
+            if group.count > group.samples.len() {
+                out.push_str(&format!(
+                    "    … and {} more\n",
+                    group.count - group.samples.len()
+                ));
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_counts_all_and_samples_some() {
+        let mut report = ScopeReport::new(2);
+        for i in 0..5 {
+            report.record(
+                "host mismatch",
+                &format!("https://other{i}.net/"),
+                "not a target",
+            );
+        }
+        report.record(
+            "deny pattern",
+            "https://example.com/logout",
+            "matches \"logout\"",
+        );
+
+        assert_eq!(report.total(), 6);
+        let rendered = report.render();
+        assert!(rendered.starts_with("[urx] scope: 6 URL(s) dropped\n"));
+        // Most frequent kind first, capped at the sample size
+        let host = rendered.find("host mismatch: 5").unwrap();
+        let deny = rendered.find("deny pattern: 1").unwrap();
+        assert!(host < deny);
+        assert!(rendered.contains("https://other1.net/ (not a target)"));
+        assert!(!rendered.contains("https://other2.net/"));
+        assert!(rendered.contains("… and 3 more"));
+    }
+}
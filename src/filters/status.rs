@@ -0,0 +1,83 @@
+/// Whether `code` matches a status pattern: an exact code (`200`) or a
+/// wildcard where `x` stands for any digit (`20x`, `3xx`).
+pub fn status_matches_pattern(code: u16, pattern: &str) -> bool {
+    if pattern.contains(['x', 'X']) {
+        let code = code.to_string();
+        code.len() == pattern.len()
+            && code
+                .chars()
+                .zip(pattern.chars())
+                .all(|(c, p)| p.eq_ignore_ascii_case(&'x') || p == c)
+    } else {
+        pattern.parse::<u16>() == Ok(code)
+    }
+}
+
+/// `--provider-status-filter`: the archived HTTP statuses a provider keeps
+/// while parsing its results, so dead URLs never enter the pipeline.
+#[derive(Debug, Clone, Default)]
+pub struct StatusFilter {
+    patterns: Vec<String>,
+}
+
+impl StatusFilter {
+    /// Build from patterns as given on the command line; each entry may hold
+    /// several comma-separated ones (`200,3xx`).
+    pub fn new(patterns: &[String]) -> Self {
+        StatusFilter {
+            patterns: patterns
+                .iter()
+                .flat_map(|p| p.split(','))
+                .map(|p| p.trim().to_string())
+                .filter(|p| !p.is_empty())
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether a record with archived status `status` is kept. Records without
+    /// a usable status are kept: an unknown status isn't evidence the URL is
+    /// dead.
+    pub fn allows(&self, status: Option<u16>) -> bool {
+        match status {
+            Some(code) if code > 0 && !self.is_empty() => self
+                .patterns
+                .iter()
+                .any(|p| status_matches_pattern(code, p)),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_matches_pattern() {
+        assert!(status_matches_pattern(200, "200"));
+        assert!(status_matches_pattern(301, "3xx"));
+        assert!(status_matches_pattern(302, "30X"));
+        assert!(!status_matches_pattern(404, "3xx"));
+        assert!(!status_matches_pattern(200, "2xxx"));
+        assert!(!status_matches_pattern(200, "abc"));
+    }
+
+    #[test]
+    fn test_status_filter_allows() {
+        let filter = StatusFilter::new(&["200, 3xx".to_string()]);
+        assert!(filter.allows(Some(200)));
+        assert!(filter.allows(Some(302)));
+        assert!(!filter.allows(Some(404)));
+        // Unknown status: keep.
+        assert!(filter.allows(None));
+        assert!(filter.allows(Some(0)));
+
+        let none = StatusFilter::new(&[]);
+        assert!(none.is_empty());
+        assert!(none.allows(Some(500)));
+    }
+}
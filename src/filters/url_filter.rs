@@ -1,18 +1,35 @@
-use std::collections::HashSet;
+use rayon::prelude::*;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 use url::Url;
 
-use super::preset::FilterPreset;
+use super::preset::{CustomPreset, FilterPreset};
 
 /// URL Filter for filtering URLs based on extensions, patterns, length, etc.
 #[derive(Default)]
 pub struct UrlFilter {
-    extensions: Vec<String>,
-    exclude_extensions: Vec<String>,
+    /// Lowercased; sets so checking a URL's extension is one lookup even
+    /// with a preset's long lists
+    extensions: HashSet<String>,
+    exclude_extensions: HashSet<String>,
+    /// As given; matched as-is when `case_sensitive`
     patterns: Vec<String>,
     exclude_patterns: Vec<String>,
+    /// Lowercased copies of the above, matched against the lowercased URL
+    /// otherwise. Both forms are kept so `with_case_sensitive` can be set
+    /// before or after the patterns are added.
+    folded_patterns: Vec<String>,
+    folded_exclude_patterns: Vec<String>,
+    /// Match patterns against the URL as-is instead of lowercasing both
+    case_sensitive: bool,
+    /// `re:` lines of `--exclude-file`
+    exclude_regexes: Vec<Regex>,
     min_length: Option<usize>,
     max_length: Option<usize>,
+    min_params: Option<usize>,
+    max_params: Option<usize>,
 }
 
 impl UrlFilter {
@@ -21,10 +38,15 @@ impl UrlFilter {
         Self::default()
     }
 
-    /// Apply filter presets to this URL filter
-    pub fn apply_presets(&mut self, presets: &[String]) -> &mut Self {
+    /// Apply filter presets to this URL filter, looking names up in the
+    /// config-defined `custom` presets before the built-in ones
+    pub fn apply_presets(
+        &mut self,
+        presets: &[String],
+        custom: &BTreeMap<String, CustomPreset>,
+    ) -> &mut Self {
         for preset_str in presets {
-            if let Some(preset) = FilterPreset::from_str(preset_str) {
+            if let Some(preset) = FilterPreset::resolve(preset_str, custom) {
                 // Merge preset extensions/patterns with existing ones
                 self.extensions.extend(
                     preset
@@ -38,14 +60,8 @@ impl UrlFilter {
                         .into_iter()
                         .map(|s| s.to_lowercase()),
                 );
-                self.patterns
-                    .extend(preset.get_patterns().into_iter().map(|s| s.to_lowercase()));
-                self.exclude_patterns.extend(
-                    preset
-                        .get_exclude_patterns()
-                        .into_iter()
-                        .map(|s| s.to_lowercase()),
-                );
+                self.with_patterns(preset.get_patterns());
+                self.with_exclude_patterns(preset.get_exclude_patterns());
             }
         }
         self
@@ -69,16 +85,30 @@ impl UrlFilter {
     /// Set patterns to include
     pub fn with_patterns(&mut self, patterns: Vec<String>) -> &mut Self {
         // Merge with existing patterns instead of replacing
-        self.patterns
-            .extend(patterns.into_iter().map(|s| s.to_lowercase()));
+        self.folded_patterns
+            .extend(patterns.iter().map(|s| s.to_lowercase()));
+        self.patterns.extend(patterns);
         self
     }
 
     /// Set patterns to exclude
     pub fn with_exclude_patterns(&mut self, exclude_patterns: Vec<String>) -> &mut Self {
         // Merge with existing exclude_patterns instead of replacing
-        self.exclude_patterns
-            .extend(exclude_patterns.into_iter().map(|s| s.to_lowercase()));
+        self.folded_exclude_patterns
+            .extend(exclude_patterns.iter().map(|s| s.to_lowercase()));
+        self.exclude_patterns.extend(exclude_patterns);
+        self
+    }
+
+    /// Match patterns case-sensitively
+    pub fn with_case_sensitive(&mut self, case_sensitive: bool) -> &mut Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Set regexes to exclude, on top of the exclude patterns
+    pub fn with_exclude_regexes(&mut self, exclude_regexes: Vec<Regex>) -> &mut Self {
+        self.exclude_regexes.extend(exclude_regexes);
         self
     }
 
@@ -94,122 +124,265 @@ impl UrlFilter {
         self
     }
 
-    /// Apply filters to a set of URLs
+    /// Set minimum number of query parameters
+    pub fn with_min_params(&mut self, min_params: Option<usize>) -> &mut Self {
+        self.min_params = min_params;
+        self
+    }
+
+    /// Set maximum number of query parameters
+    pub fn with_max_params(&mut self, max_params: Option<usize>) -> &mut Self {
+        self.max_params = max_params;
+        self
+    }
+
+    /// Apply filters to a set of URLs, spread across all cores so
+    /// multi-million-URL runs don't wait on one thread
     pub fn apply_filters(&self, urls: &HashSet<String>) -> Vec<String> {
-        let mut result = Vec::new();
+        let mut result: Vec<String> = urls
+            .par_iter()
+            .filter(|url| self.explain(url).is_none())
+            .cloned()
+            .collect();
 
-        for url in urls {
-            // Skip if URL doesn't match the length criteria
-            if let Some(min) = self.min_length {
-                if url.len() < min {
-                    continue;
-                }
+        // Sort the results for consistent output
+        result.par_sort_unstable();
+        result
+    }
+
+    /// Why `url` would be dropped by [`apply_filters`](Self::apply_filters),
+    /// or `None` if it is kept.
+    pub fn explain(&self, url: &str) -> Option<FilterRejection> {
+        // Skip if URL doesn't match the length criteria
+        if let Some(min) = self.min_length {
+            if url.len() < min {
+                return Some(FilterRejection::TooShort(min));
             }
+        }
 
-            if let Some(max) = self.max_length {
-                if url.len() > max {
-                    continue;
-                }
+        if let Some(max) = self.max_length {
+            if url.len() > max {
+                return Some(FilterRejection::TooLong(max));
+            }
+        }
+
+        // Parse once, and only when a filter looks at the URL's structure
+        let parsed = (!self.extensions.is_empty()
+            || !self.exclude_extensions.is_empty()
+            || self.min_params.is_some()
+            || self.max_params.is_some())
+        .then(|| Url::parse(url).ok())
+        .flatten();
+
+        if self.min_params.is_some() || self.max_params.is_some() {
+            let count = param_count(parsed.as_ref(), url);
+            if let Some(min) = self.min_params.filter(|&min| count < min) {
+                return Some(FilterRejection::TooFewParams(min));
             }
+            if let Some(max) = self.max_params.filter(|&max| count > max) {
+                return Some(FilterRejection::TooManyParams(max));
+            }
+        }
 
-            // Parse the URL to extract the path for better extension handling
-            let extension = match Url::parse(url) {
-                Ok(parsed_url) => {
-                    // Get the path from the URL
-                    if let Some(path) = parsed_url
-                        .path_segments()
-                        .and_then(|mut segments| segments.next_back())
-                    {
-                        // Extract extension from the last path segment
-                        Path::new(path)
-                            .extension()
-                            .and_then(|ext| ext.to_str())
-                            .map(|s| s.to_lowercase())
-                    } else {
-                        None
-                    }
-                }
-                Err(_) => {
-                    // Fallback for invalid URLs - try to extract extension from the whole string
-                    let parts: Vec<&str> = url.split('/').collect();
-                    if let Some(last) = parts.last() {
-                        let filename_parts: Vec<&str> = last.split('.').collect();
-                        if filename_parts.len() > 1 {
-                            Some(
-                                filename_parts
-                                    .last()
-                                    .unwrap()
-                                    .split('?')
-                                    .next()
-                                    .unwrap_or("")
-                                    .to_lowercase(),
-                            )
-                        } else {
-                            None
-                        }
-                    } else {
-                        None
-                    }
-                }
-            };
+        let extension = if self.extensions.is_empty() && self.exclude_extensions.is_empty() {
+            None
+        } else {
+            url_extension(parsed.as_ref(), url)
+        };
 
-            // Compute url_lower once per URL iteration if needed
-            let mut url_lower = None;
-
-            // Check exclusions first
-            if !self.exclude_extensions.is_empty() {
-                if let Some(ext) = &extension {
-                    if self
-                        .exclude_extensions
-                        .iter()
-                        .any(|excluded_ext| excluded_ext == ext)
-                    {
-                        continue;
-                    }
-                }
+        // Compute the pattern haystack once per URL if needed
+        let mut haystack: Option<Cow<str>> = None;
+
+        // Check exclusions first
+        if let Some(ext) = &extension {
+            if self.exclude_extensions.contains(ext) {
+                return Some(FilterRejection::ExcludedExtension(ext.clone()));
             }
+        }
 
-            if !self.exclude_patterns.is_empty() {
-                let url_lower_str = url_lower.get_or_insert_with(|| url.to_lowercase());
-                if self
-                    .exclude_patterns
-                    .iter()
-                    .any(|pattern| url_lower_str.contains(pattern))
-                {
-                    continue;
-                }
+        if !self.exclude_patterns.is_empty() {
+            let haystack = haystack.get_or_insert_with(|| self.pattern_haystack(url));
+            let candidates = if self.case_sensitive {
+                &self.exclude_patterns
+            } else {
+                &self.folded_exclude_patterns
+            };
+            if let Some(index) = candidates
+                .iter()
+                .position(|pattern| haystack.contains(pattern.as_str()))
+            {
+                return Some(FilterRejection::DenyPattern(
+                    self.exclude_patterns[index].clone(),
+                ));
             }
+        }
 
-            // Then check inclusions
-            let mut include = true;
-
-            if !self.extensions.is_empty() {
-                if let Some(ext) = &extension {
-                    include = self
-                        .extensions
-                        .iter()
-                        .any(|included_ext| included_ext == ext);
-                } else {
-                    include = false; // No extension found but extensions filter is set
-                }
+        if let Some(regex) = self
+            .exclude_regexes
+            .iter()
+            .find(|regex| regex.is_match(url))
+        {
+            return Some(FilterRejection::DenyPattern(format!(
+                "re:{}",
+                regex.as_str()
+            )));
+        }
+
+        // Then check inclusions
+        if !self.extensions.is_empty() {
+            // No extension found but extensions filter is set
+            let allowed = extension
+                .as_ref()
+                .is_some_and(|ext| self.extensions.contains(ext));
+            if !allowed {
+                return Some(FilterRejection::ExtensionNotAllowed(extension));
             }
+        }
 
-            if include && !self.patterns.is_empty() {
-                let url_lower_str = url_lower.get_or_insert_with(|| url.to_lowercase());
-                include = self
-                    .patterns
-                    .iter()
-                    .any(|pattern| url_lower_str.contains(pattern));
+        if !self.patterns.is_empty() {
+            let haystack = haystack.get_or_insert_with(|| self.pattern_haystack(url));
+            let candidates = if self.case_sensitive {
+                &self.patterns
+            } else {
+                &self.folded_patterns
+            };
+            if !candidates
+                .iter()
+                .any(|pattern| haystack.contains(pattern.as_str()))
+            {
+                return Some(FilterRejection::NoPatternMatch);
             }
+        }
+
+        None
+    }
 
-            if include {
-                result.push(url.clone());
+    fn pattern_haystack<'a>(&self, url: &'a str) -> Cow<'a, str> {
+        if self.case_sensitive {
+            Cow::Borrowed(url)
+        } else {
+            Cow::Owned(url.to_lowercase())
+        }
+    }
+}
+
+/// Why [`UrlFilter`] dropped a URL, for `--explain-scope`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterRejection {
+    /// Shorter than `--min-length`
+    TooShort(usize),
+    /// Longer than `--max-length`
+    TooLong(usize),
+    /// Fewer query parameters than `--min-params`
+    TooFewParams(usize),
+    /// More query parameters than `--max-params`
+    TooManyParams(usize),
+    /// Extension listed in `--exclude-extensions` (or a `no-*` preset)
+    ExcludedExtension(String),
+    /// Contains an `--exclude-patterns` entry (or matches an `--exclude-file`
+    /// line)
+    DenyPattern(String),
+    /// `--extensions` is set and the URL's extension (if any) isn't in it
+    ExtensionNotAllowed(Option<String>),
+    /// `--patterns` is set and the URL contains none of them
+    NoPatternMatch,
+}
+
+impl FilterRejection {
+    /// Short category name used to group decisions in reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FilterRejection::TooShort(_) | FilterRejection::TooLong(_) => "length limit",
+            FilterRejection::TooFewParams(_) | FilterRejection::TooManyParams(_) => {
+                "parameter count"
             }
+            FilterRejection::ExcludedExtension(_) => "excluded extension",
+            FilterRejection::DenyPattern(_) => "deny pattern",
+            FilterRejection::ExtensionNotAllowed(_) => "extension not allowed",
+            FilterRejection::NoPatternMatch => "no pattern match",
         }
+    }
+}
 
-        // Sort the results for consistent output
-        result.sort();
-        result
+impl std::fmt::Display for FilterRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FilterRejection::TooShort(min) => write!(f, "shorter than --min-length {min}"),
+            FilterRejection::TooLong(max) => write!(f, "longer than --max-length {max}"),
+            FilterRejection::TooFewParams(min) => {
+                write!(f, "fewer query parameters than --min-params {min}")
+            }
+            FilterRejection::TooManyParams(max) => {
+                write!(f, "more query parameters than --max-params {max}")
+            }
+            FilterRejection::ExcludedExtension(ext) => write!(f, "extension .{ext} is excluded"),
+            FilterRejection::DenyPattern(pattern) => {
+                write!(f, "matches exclude pattern {pattern:?}")
+            }
+            FilterRejection::ExtensionNotAllowed(Some(ext)) => {
+                write!(f, "extension .{ext} is not in --extensions")
+            }
+            FilterRejection::ExtensionNotAllowed(None) => {
+                write!(f, "no extension, but --extensions is set")
+            }
+            FilterRejection::NoPatternMatch => write!(f, "matches none of --patterns"),
+        }
+    }
+}
+
+/// Number of `key[=value]` pairs in the URL's query string (fragment
+/// excluded), repeated names counted each time. `parsed` is `url` parsed, or
+/// `None` when it doesn't parse.
+fn param_count(parsed: Option<&Url>, url: &str) -> usize {
+    let query = match parsed {
+        Some(parsed) => parsed.query().unwrap_or("").to_string(),
+        None => url
+            .split_once('?')
+            .map(|(_, query)| query.split('#').next().unwrap_or("").to_string())
+            .unwrap_or_default(),
+    };
+    query.split('&').filter(|pair| !pair.is_empty()).count()
+}
+
+/// Lowercased extension of the URL's last path segment, if any. A dotfile
+/// such as `/.env` counts as having the extension `env`. `parsed` is `url`
+/// parsed, or `None` when it doesn't parse.
+fn url_extension(parsed: Option<&Url>, url: &str) -> Option<String> {
+    match parsed {
+        Some(parsed_url) => {
+            // Extract extension from the last path segment
+            let last = parsed_url
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())?;
+            let last = match last.strip_prefix('.') {
+                Some(name) if !name.is_empty() && !name.contains('.') => {
+                    return Some(name.to_lowercase())
+                }
+                _ => last,
+            };
+            Path::new(last)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .map(|s| s.to_lowercase())
+        }
+        None => {
+            // Fallback for invalid URLs - try to extract extension from the whole string
+            let last = url.split('/').next_back()?;
+            let filename_parts: Vec<&str> = last.split('.').collect();
+            if filename_parts.len() > 1 {
+                Some(
+                    filename_parts
+                        .last()
+                        .unwrap()
+                        .split('?')
+                        .next()
+                        .unwrap_or("")
+                        .to_lowercase(),
+                )
+            } else {
+                None
+            }
+        }
     }
 }
 
@@ -259,6 +432,35 @@ mod tests {
         assert!(filtered.contains(&"https://example.com/admin/login.php".to_string()));
     }
 
+    #[test]
+    fn test_explain_names_the_rule() {
+        let mut filter = UrlFilter::new();
+        filter
+            .with_exclude_patterns(vec!["logout".to_string()])
+            .with_extensions(vec!["php".to_string()])
+            .with_min_length(Some(20));
+
+        assert_eq!(filter.explain("https://example.com/a.php"), None);
+        assert_eq!(
+            filter.explain("https://example.com/logout.php"),
+            Some(FilterRejection::DenyPattern("logout".to_string()))
+        );
+        assert_eq!(
+            filter.explain("https://example.com/app.js"),
+            Some(FilterRejection::ExtensionNotAllowed(Some("js".to_string())))
+        );
+        assert_eq!(
+            filter.explain("https://a.io/x.php"),
+            Some(FilterRejection::TooShort(20))
+        );
+        assert_eq!(
+            filter
+                .explain("https://example.com/logout.php")
+                .map(|r| r.kind()),
+            Some("deny pattern")
+        );
+    }
+
     #[test]
     fn test_with_exclude_extensions() {
         let mut filter = UrlFilter::new();
@@ -303,6 +505,47 @@ mod tests {
         assert!(!filtered.contains(&"https://example.com/.git/config".to_string()));
     }
 
+    #[test]
+    fn test_case_sensitive_patterns() {
+        let urls: HashSet<String> = [
+            "https://example.com/t/aGVsbG8",
+            "https://example.com/t/AGVSBG8",
+            "https://example.com/t/other",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let mut filter = UrlFilter::new();
+        filter.with_patterns(vec!["aGVsbG8".to_string()]);
+        assert_eq!(filter.apply_filters(&urls).len(), 2);
+
+        let mut filter = UrlFilter::new();
+        filter
+            .with_case_sensitive(true)
+            .with_patterns(vec!["aGVsbG8".to_string()]);
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec!["https://example.com/t/aGVsbG8".to_string()]
+        );
+
+        let mut filter = UrlFilter::new();
+        filter
+            .with_case_sensitive(true)
+            .with_exclude_patterns(vec!["AGVSBG8".to_string()]);
+        assert_eq!(filter.apply_filters(&urls).len(), 2);
+
+        // Order doesn't matter: patterns added first still match as given
+        let mut filter = UrlFilter::new();
+        filter
+            .with_patterns(vec!["aGVsbG8".to_string()])
+            .with_case_sensitive(true);
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec!["https://example.com/t/aGVsbG8".to_string()]
+        );
+    }
+
     #[test]
     fn test_with_length_filters() {
         let mut filter = UrlFilter::new();
@@ -321,7 +564,10 @@ mod tests {
     #[test]
     fn test_apply_presets() {
         let mut filter = UrlFilter::new();
-        filter.apply_presets(&["no-images".to_string(), "only-js".to_string()]);
+        filter.apply_presets(
+            &["no-images".to_string(), "only-js".to_string()],
+            &BTreeMap::new(),
+        );
 
         let urls = create_test_urls();
         let filtered = filter.apply_filters(&urls);
@@ -330,6 +576,148 @@ mod tests {
         assert!(!filtered.contains(&"https://example.com/image.png".to_string()));
     }
 
+    #[test]
+    fn test_stack_presets_combine_with_user_filters() {
+        let mut urls = create_test_urls();
+        urls.insert("https://example.com/app/#/settings/profile".to_string());
+        urls.insert("https://example.com/graphql?query=x".to_string());
+
+        let mut filter = UrlFilter::new();
+        filter.apply_presets(&["only-api".to_string()], &BTreeMap::new());
+        let mut filtered = filter.apply_filters(&urls);
+        filtered.sort();
+        assert_eq!(
+            filtered,
+            vec![
+                "https://example.com/api/v1/users?id=123".to_string(),
+                "https://example.com/graphql?query=x".to_string(),
+            ]
+        );
+
+        // User exclusions still apply on top of the preset
+        filter.with_exclude_patterns(vec!["graphql".to_string()]);
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec!["https://example.com/api/v1/users?id=123".to_string()]
+        );
+
+        let mut filter = UrlFilter::new();
+        filter.apply_presets(&["only-spa".to_string()], &BTreeMap::new());
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec!["https://example.com/app/#/settings/profile".to_string()]
+        );
+
+        let mut filter = UrlFilter::new();
+        filter.apply_presets(&["only-dynamic".to_string()], &BTreeMap::new());
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec!["https://example.com/admin/login.php".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_exclude_regexes() {
+        let mut filter = UrlFilter::new();
+        filter.with_exclude_regexes(vec![Regex::new(r"/item/\d+$").unwrap()]);
+        assert_eq!(
+            filter.explain("https://example.com/item/42"),
+            Some(FilterRejection::DenyPattern(r"re:/item/\d+$".to_string()))
+        );
+        assert_eq!(filter.explain("https://example.com/item/42/edit"), None);
+    }
+
+    #[test]
+    fn test_param_count_limits() {
+        let urls: HashSet<String> = [
+            "https://example.com/",
+            "https://example.com/?utm_source=x",
+            "https://example.com/search?q=a&page=2",
+            "https://example.com/item?id=1&id=2&sort=asc#x=1&y=2",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let mut filter = UrlFilter::new();
+        filter.with_min_params(Some(2));
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec![
+                "https://example.com/item?id=1&id=2&sort=asc#x=1&y=2".to_string(),
+                "https://example.com/search?q=a&page=2".to_string(),
+            ]
+        );
+        assert_eq!(
+            filter.explain("https://example.com/?utm_source=x"),
+            Some(FilterRejection::TooFewParams(2))
+        );
+
+        let mut filter = UrlFilter::new();
+        filter.with_min_params(Some(1)).with_max_params(Some(2));
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec![
+                "https://example.com/?utm_source=x".to_string(),
+                "https://example.com/search?q=a&page=2".to_string(),
+            ]
+        );
+        assert_eq!(param_count(None, "not a url?a=1&b"), 2);
+    }
+
+    #[test]
+    fn test_triage_presets() {
+        let urls: HashSet<String> = [
+            "https://example.com/.env",
+            "https://example.com/backup/site.tar.gz",
+            "https://example.com/db/dump.sql",
+            "https://example.com/index.php.bak",
+            "https://example.com/.well-known/security.txt",
+            "https://example.com/swagger/index.html",
+            "https://example.com/wp-json/wp/v2/users",
+            "https://example.com/about",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let mut filter = UrlFilter::new();
+        filter.apply_presets(&["sensitive-files".to_string()], &BTreeMap::new());
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec![
+                "https://example.com/.env".to_string(),
+                "https://example.com/backup/site.tar.gz".to_string(),
+                "https://example.com/db/dump.sql".to_string(),
+                "https://example.com/index.php.bak".to_string(),
+            ]
+        );
+
+        let mut filter = UrlFilter::new();
+        filter.apply_presets(&["api-endpoints".to_string()], &BTreeMap::new());
+        assert_eq!(
+            filter.apply_filters(&urls),
+            vec![
+                "https://example.com/swagger/index.html".to_string(),
+                "https://example.com/wp-json/wp/v2/users".to_string(),
+            ]
+        );
+        assert_eq!(
+            url_extension(
+                Url::parse("https://example.com/.htaccess").ok().as_ref(),
+                "https://example.com/.htaccess"
+            ),
+            Some("htaccess".to_string())
+        );
+        assert_eq!(
+            url_extension(
+                Url::parse("https://example.com/.config.bak").ok().as_ref(),
+                "https://example.com/.config.bak"
+            ),
+            Some("bak".to_string())
+        );
+    }
+
     #[test]
     fn test_fallback_invalid_urls() {
         let mut filter = UrlFilter::new();
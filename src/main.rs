@@ -4,7 +4,9 @@ use clap::Parser;
 mod cache;
 mod cli;
 mod config;
+mod error;
 mod filters;
+mod metrics;
 mod network;
 mod output;
 mod progress;
@@ -19,24 +21,28 @@ mod utils;
 use cache::{CacheEntry, CacheFilters, CacheKey, CacheManager};
 use cli::{read_domains_from_file, read_domains_from_stdin, Args};
 use config::Config;
+use error::UrxError;
 use filters::{HostValidator, UrlFilter};
-use network::NetworkSettings;
+use network::{NetworkSettings, RequestGate};
 use output::create_outputter;
-use progress::ProgressManager;
+use progress::{ProgressManager, ProgressMode};
 use providers::{
-    ArquivoProvider, CommonCrawlProvider, GitHubProvider, OTXProvider, Provider, RobotsProvider,
-    SitemapProvider, UrlscanProvider, VirusTotalProvider, WaybackMachineProvider, ZoomEyeProvider,
+    ArquivoProvider, CertstreamProvider, CommonCrawlProvider, GenericProvider, GitHubProvider,
+    OTXProvider, PluginProvider, Provider, RobotsProvider, SitemapProvider, StreamingProvider,
+    UrlscanProvider, VirusTotalProvider, WaybackMachineProvider, ZoomEyeProvider,
 };
 use readers::read_urls_from_file;
-use runner::{add_provider, process_domains, ProviderRunResult};
-use tester_manager::{apply_network_settings_to_tester, process_urls_with_testers};
-use testers::{LinkExtractor, StatusChecker, Tester};
+use runner::{add_provider, process_domains, FetchRecord, ProviderList, ProviderRunResult};
+use tester_manager::{
+    apply_network_settings_to_tester, process_urls_with_testers, DiscoveryScope, TesterRole,
+};
+use testers::{
+    JsEndpointExtractor, LinkExtractor, OpenRedirectTester, ReflectionTester, SecretFinding,
+    SecretScanner, SourceMapExtractor, StatusChecker, Tester, TitleExtractor, TlsInspector,
+};
 use utils::verbose_print;
 use utils::UrlTransformer;
 
-/// Type alias for provider initialization result
-type ProviderList = (Vec<Box<dyn Provider>>, Vec<String>);
-
 /// Static metadata for one of urx's URL providers.
 struct ProviderInfo {
     /// Short identifier accepted on the command line (e.g. "wayback").
@@ -139,10 +145,40 @@ fn print_provider_list() {
     println!("--exclude-providers wins on conflict.");
 }
 
+/// Print the filter presets accepted by `--preset`, for `--list-presets`.
+fn print_preset_list(args: &Args) {
+    println!("Available presets:");
+    for p in filters::PRESET_CATALOG {
+        if !args.custom_presets.contains_key(p.name) {
+            println!("  {:<15}  {}", p.name, p.summary);
+        }
+    }
+    if !args.custom_presets.is_empty() {
+        println!();
+        println!("From config:");
+        for (name, preset) in &args.custom_presets {
+            println!(
+                "  {:<15}  {}",
+                name,
+                preset.description.as_deref().unwrap_or("(no description)")
+            );
+        }
+    }
+    println!();
+    println!("Use --preset name1,name2 to apply. Presets combine with each other and with");
+    println!("-e/--exclude-extensions/--patterns/--exclude-patterns; exclusions always win.");
+}
+
 /// Collect the effective domain list from CLI positional args, `--domain-list`
 /// files, and (when both are empty) stdin. Duplicates are removed while
 /// preserving first-seen order so the run order is predictable.
 fn collect_domains(args: &Args) -> Result<Vec<String>> {
+    Ok(collect_tagged_domains(args)?.0)
+}
+
+/// [`collect_domains`], plus the tags targets were listed with
+/// (`example.com#prod`, or a tag column in a CSV domain list).
+fn collect_tagged_domains(args: &Args) -> Result<(Vec<String>, output::TargetTags)> {
     let mut domains: Vec<String> = args.domains.clone();
 
     for path in &args.domain_list {
@@ -164,15 +200,35 @@ fn collect_domains(args: &Args) -> Result<Vec<String>> {
     }
 
     // Reduce each target to a bare host so a pasted full URL or trailing path
-    // doesn't silently corrupt provider queries (a common copy/paste footgun).
+    // doesn't silently corrupt provider queries (a common copy/paste footgun),
+    // then encode it for provider queries. Hosts no provider could match are
+    // skipped with a reason instead of quietly returning nothing.
+    let mut tags = output::TargetTags::default();
     let mut normalized: Vec<String> = domains
         .iter()
-        .filter_map(|d| cli::normalize_domain(d))
+        .filter_map(|d| {
+            let (target, tag) = output::split_target_tag(d);
+            let host = cli::normalize_domain(target)?;
+            match utils::encode_query_host(&host) {
+                Ok(encoded) => {
+                    if let Some(tag) = tag {
+                        tags.add(&encoded, tag);
+                    }
+                    Some(encoded)
+                }
+                Err(e) => {
+                    if !args.silent {
+                        eprintln!("Skipping invalid target {d:?}: {e}");
+                    }
+                    None
+                }
+            }
+        })
         .collect();
 
     let mut seen = std::collections::HashSet::new();
     normalized.retain(|d| seen.insert(d.clone()));
-    Ok(normalized)
+    Ok((normalized, tags))
 }
 
 /// Parse API keys from environment variable (comma-separated) and combine with CLI keys
@@ -242,12 +298,26 @@ pub fn auto_enable_provider(
     }
 }
 
-fn valid_provider_ids() -> std::collections::HashSet<&'static str> {
-    provider_catalog().iter().map(|p| p.id).collect()
+/// Ids of the providers declared in config.toml, custom HTTP ones first.
+fn declared_provider_ids(args: &Args) -> impl Iterator<Item = &str> {
+    args.custom_providers
+        .iter()
+        .map(|p| p.id.as_str())
+        .chain(args.plugin_providers.iter().map(|p| p.id.as_str()))
+}
+
+/// Built-in provider ids plus the ids of config-declared custom and plugin
+/// providers.
+fn valid_provider_ids(args: &Args) -> std::collections::HashSet<&str> {
+    provider_catalog()
+        .iter()
+        .map(|p| p.id)
+        .chain(declared_provider_ids(args))
+        .collect()
 }
 
-fn validate_provider_ids(ids: &[String], flag_name: &str) -> Result<()> {
-    let valid_ids = valid_provider_ids();
+fn validate_provider_ids(args: &Args, ids: &[String], flag_name: &str) -> Result<()> {
+    let valid_ids = valid_provider_ids(args);
 
     let unknown: Vec<&str> = ids
         .iter()
@@ -268,9 +338,30 @@ fn validate_provider_ids(ids: &[String], flag_name: &str) -> Result<()> {
     ))
 }
 
+/// Reject `[[provider.custom]]` and `[[provider.plugin]]` entries that are
+/// malformed, reuse a built-in id, or repeat another entry's id.
+fn validate_custom_providers(args: &Args) -> Result<()> {
+    for def in &args.custom_providers {
+        GenericProvider::new(def.clone())?;
+    }
+    for def in &args.plugin_providers {
+        PluginProvider::new(def.clone())?;
+    }
+    let mut seen = std::collections::HashSet::new();
+    for id in declared_provider_ids(args) {
+        if provider_catalog().iter().any(|p| p.id == id) {
+            anyhow::bail!("Custom provider id {id:?} is already a built-in provider");
+        }
+        if !seen.insert(id) {
+            anyhow::bail!("Custom provider id {id:?} is declared twice");
+        }
+    }
+    Ok(())
+}
+
 fn validate_rate_limit_override_ids(args: &Args) -> Result<()> {
     let override_ids: Vec<String> = args.rate_limit_overrides().into_keys().collect();
-    validate_provider_ids(&override_ids, "--rate-limit-by")
+    validate_provider_ids(args, &override_ids, "--rate-limit-by")
 }
 
 fn effective_provider_ids(args: &Args) -> Vec<String> {
@@ -295,6 +386,7 @@ fn effective_provider_ids(args: &Args) -> Vec<String> {
             })
             .filter(|p| p.id != "robots" && p.id != "sitemap")
             .map(|p| p.id.to_string())
+            .chain(declared_provider_ids(args).map(str::to_string))
             .collect()
     } else {
         args.providers.clone()
@@ -341,11 +433,10 @@ fn effective_provider_ids(args: &Args) -> Vec<String> {
 
 /// Initialize all providers based on args and API keys
 fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Result<ProviderList> {
-    let mut providers: Vec<Box<dyn Provider>> = Vec::new();
-    let mut provider_names: Vec<String> = Vec::new();
+    let mut list = ProviderList::default();
 
-    validate_provider_ids(&args.providers, "--providers")?;
-    validate_provider_ids(&args.exclude_providers, "--exclude-providers")?;
+    validate_provider_ids(args, &args.providers, "--providers")?;
+    validate_provider_ids(args, &args.exclude_providers, "--exclude-providers")?;
     validate_rate_limit_override_ids(args)?;
 
     // Get API keys (from CLI and env vars)
@@ -353,6 +444,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
     let urlscan_api_keys = parse_api_keys(args.urlscan_api_key.clone(), "URX_URLSCAN_API_KEY");
     let zoomeye_api_keys = parse_api_keys(args.zoomeye_api_key.clone(), "URX_ZOOMEYE_API_KEY");
     let github_api_keys = parse_api_keys(args.github_api_key.clone(), "URX_GITHUB_API_KEY");
+    let provider_status_filter = filters::StatusFilter::new(&args.provider_status_filter);
 
     let providers_list = effective_provider_ids(args);
 
@@ -360,10 +452,30 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
     // so suppress the per-provider "needs API key" messages in that mode.
     let suppress_key_errors = args.all_providers;
 
+    // --from / --to scope every archive that takes a date window. clap has
+    // already validated them.
+    let date_from = args
+        .from
+        .as_deref()
+        .and_then(|s| providers::wayback::normalize_cdx_timestamp(s, false));
+    let date_to = args
+        .to
+        .as_deref()
+        .and_then(|s| providers::wayback::normalize_cdx_timestamp(s, true));
+    if let (Some(from), Some(to)) = (&date_from, &date_to) {
+        if from > to {
+            return Err(anyhow::anyhow!(
+                "--from {} is after --to {}",
+                args.from.as_deref().unwrap_or_default(),
+                args.to.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+
     if providers_list.iter().any(|p| p == "wayback") {
         // Normalise --wayback-from/--wayback-to up front so a malformed value
         // produces a single warning instead of one per domain. CDX wants
-        // YYYYMMDDhhmmss.
+        // YYYYMMDDhhmmss. The generic --from/--to fill in for them.
         let wayback_from = args.wayback_from.as_deref().and_then(|s| {
             let parsed = providers::wayback::normalize_cdx_timestamp(s, false);
             if parsed.is_none() && !args.silent {
@@ -371,6 +483,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             }
             parsed
         });
+        let wayback_from = wayback_from.or_else(|| date_from.clone());
         let wayback_to = args.wayback_to.as_deref().and_then(|s| {
             let parsed = providers::wayback::normalize_cdx_timestamp(s, true);
             if parsed.is_none() && !args.silent {
@@ -378,24 +491,60 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             }
             parsed
         });
+        let wayback_to = wayback_to.or_else(|| date_to.clone());
+        let wayback_collapse = match args.wayback_collapse.as_deref() {
+            None => Some("urlkey".to_string()),
+            Some(raw) => providers::wayback::parse_cdx_collapse(raw).unwrap_or_else(|| {
+                if !args.silent {
+                    eprintln!("Ignoring --wayback-collapse={raw:?}: expected a CDX field (e.g. urlkey, digest, timestamp:8) or none");
+                }
+                Some("urlkey".to_string())
+            }),
+        };
+        let wayback_filters: Vec<String> = args
+            .wayback_filter
+            .iter()
+            .filter_map(|raw| {
+                let parsed = providers::wayback::parse_cdx_filter(raw);
+                if parsed.is_none() && !args.silent {
+                    eprintln!("Ignoring --wayback-filter={raw:?}: expected [!]field:regex (e.g. statuscode:200)");
+                }
+                parsed
+            })
+            .collect();
         let wb_from = wayback_from.clone();
         let wb_to = wayback_to.clone();
+        let wb_match_type = args.wayback_match_type.clone();
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "wayback",
             "Wayback Machine".to_string(),
             move || {
                 let mut p = WaybackMachineProvider::new();
-                p.with_from(wb_from).with_to(wb_to);
+                p.with_from(wb_from)
+                    .with_to(wb_to)
+                    .with_collapse(wayback_collapse)
+                    .with_filters(wayback_filters)
+                    .with_match_type(wb_match_type);
                 p
             },
         );
     }
 
     if providers_list.iter().any(|p| p == "cc") {
+        // Remember the `latest` resolution next to the URL cache so repeat
+        // runs skip collinfo.json; --no-cache opts out of that too.
+        let collinfo_cache = (!args.no_cache)
+            .then(|| std::env::var_os("HOME"))
+            .flatten()
+            .map(|home| {
+                std::path::PathBuf::from(home)
+                    .join(".urx")
+                    .join("cc-latest-index.json")
+            });
+
         // Each --cc-index entry becomes its own provider instance so they
         // run in parallel and the per-provider stats stay distinct.
         for index in &args.cc_index {
@@ -403,11 +552,17 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             add_provider(
                 args,
                 network_settings,
-                &mut providers,
-                &mut provider_names,
+                &mut list,
                 "cc",
                 index.clone(),
-                || CommonCrawlProvider::with_index(index.clone()),
+                || {
+                    let mut provider = CommonCrawlProvider::with_index(index.clone());
+                    if let Some(path) = &collinfo_cache {
+                        provider.with_collinfo_cache(path.clone());
+                    }
+                    provider.with_date_range(date_from.clone(), date_to.clone());
+                    provider
+                },
             );
         }
     }
@@ -416,11 +571,14 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "robots",
             "Robots.txt".to_string(),
-            RobotsProvider::new,
+            || {
+                let mut provider = RobotsProvider::new();
+                provider.with_wildcard_expansion(args.robots_wildcards);
+                provider
+            },
         );
     }
 
@@ -428,8 +586,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "sitemap",
             "Sitemap".to_string(),
             SitemapProvider::new,
@@ -440,11 +597,14 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "otx",
             "OTX".to_string(),
-            OTXProvider::new,
+            || {
+                let mut p = OTXProvider::new();
+                p.with_status_filter(provider_status_filter.clone());
+                p
+            },
         );
     }
 
@@ -452,8 +612,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "arquivo",
             "Arquivo.pt".to_string(),
             ArquivoProvider::new,
@@ -465,8 +624,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             add_provider(
                 args,
                 network_settings,
-                &mut providers,
-                &mut provider_names,
+                &mut list,
                 "vt",
                 "VirusTotal".to_string(),
                 || VirusTotalProvider::new_with_keys(vt_api_keys.clone()),
@@ -484,11 +642,16 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         add_provider(
             args,
             network_settings,
-            &mut providers,
-            &mut provider_names,
+            &mut list,
             "urlscan",
             "Urlscan".to_string(),
-            || UrlscanProvider::new_with_keys(urlscan_api_keys.clone()),
+            || {
+                let mut p = UrlscanProvider::new_with_keys(urlscan_api_keys.clone());
+                p.with_max_results(args.urlscan_max_results);
+                p.with_status_filter(provider_status_filter.clone());
+                p.with_date_range(date_from.clone(), date_to.clone());
+                p
+            },
         );
     }
 
@@ -497,8 +660,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             add_provider(
                 args,
                 network_settings,
-                &mut providers,
-                &mut provider_names,
+                &mut list,
                 "zoomeye",
                 "ZoomEye".to_string(),
                 || ZoomEyeProvider::new_with_keys(zoomeye_api_keys.clone()),
@@ -513,8 +675,7 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
             add_provider(
                 args,
                 network_settings,
-                &mut providers,
-                &mut provider_names,
+                &mut list,
                 "github",
                 "GitHub".to_string(),
                 || GitHubProvider::new_with_keys(github_api_keys.clone()),
@@ -524,14 +685,45 @@ fn initialize_providers(args: &Args, network_settings: &NetworkSettings) -> Resu
         }
     }
 
-    if providers.is_empty() {
+    for def in &args.custom_providers {
+        if providers_list.contains(&def.id) {
+            // validate_custom_providers has already vetted every definition.
+            let provider = GenericProvider::new(def.clone())?;
+            add_provider(
+                args,
+                network_settings,
+                &mut list,
+                &def.id,
+                def.display_name(),
+                || provider,
+            );
+        }
+    }
+
+    for def in &args.plugin_providers {
+        if providers_list.contains(&def.id) {
+            let provider = PluginProvider::new(def.clone())?;
+            add_provider(
+                args,
+                network_settings,
+                &mut list,
+                &def.id,
+                def.display_name(),
+                || provider,
+            );
+        }
+    }
+
+    if list.providers.is_empty() {
         if !args.silent {
             eprintln!("Error: No valid providers specified. Please use --providers with valid provider names (wayback, cc, otx, arquivo, vt, urlscan, zoomeye)");
         }
-        return Err(anyhow::anyhow!("No valid providers specified"));
+        return Err(
+            error::UrxError::provider(anyhow::anyhow!("No valid providers specified")).into(),
+        );
     }
 
-    Ok((providers, provider_names))
+    Ok(list)
 }
 
 /// Read URLs from multiple files
@@ -558,7 +750,7 @@ fn read_urls_from_files(args: &Args) -> Result<Option<Vec<String>>> {
                 if !args.silent {
                     eprintln!("Error reading file {}: {}", file_path.display(), e);
                 }
-                return Err(e);
+                return Err(e.into());
             }
         }
     }
@@ -574,7 +766,92 @@ fn read_urls_from_files(args: &Args) -> Result<Option<Vec<String>>> {
     Ok(Some(all_file_urls))
 }
 
-/// Apply URL filtering and host validation
+/// Build the URL filter from presets plus the explicit extension, pattern,
+/// and length flags (explicit flags combine with preset filters).
+fn build_url_filter(args: &Args) -> UrlFilter {
+    let mut url_filter = UrlFilter::new();
+    url_filter.with_case_sensitive(args.case_sensitive);
+
+    // Apply presets if specified
+    if !args.preset.is_empty() {
+        url_filter.apply_presets(&args.preset, &args.custom_presets);
+    }
+
+    // Apply additional filters (will be combined with preset filters)
+    url_filter
+        .with_extensions(args.extensions.clone())
+        .with_exclude_extensions(args.exclude_extensions.clone())
+        .with_patterns(args.patterns.clone())
+        .with_exclude_patterns(args.exclude_patterns.clone())
+        .with_exclude_regexes(args.exclude_regexes.clone())
+        .with_min_length(args.min_length)
+        .with_max_length(args.max_length)
+        .with_min_params(args.min_params)
+        .with_max_params(args.max_params);
+
+    url_filter
+}
+
+/// Reject a malformed `--since`/`--seen-after`/`--seen-before` before any
+/// provider runs; the filters themselves only apply once collection is done.
+fn validate_date_filters(args: &Args) -> Result<()> {
+    for (flag, raw) in [
+        ("--since", &args.since),
+        ("--seen-after", &args.seen_after),
+        ("--seen-before", &args.seen_before),
+    ] {
+        if let Some(raw) = raw {
+            if filters::parse_since(raw).is_none() {
+                return Err(anyhow::anyhow!(
+                    "Invalid {flag}={raw:?}: expected YYYY, YYYY-MM, or YYYY-MM-DD"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `--count`/`--count-by` print counts in place of the URL list, so clap
+/// rejects them with `--output`; an `output` from config.toml gets past that
+/// and would otherwise be ignored without a word.
+fn validate_count_output(args: &Args) -> Result<()> {
+    if (args.count || args.count_by.is_some()) && args.output.is_some() {
+        return Err(anyhow::anyhow!(
+            "--count and --count-by can't write the output file set in the config; remove [output].output or drop the flag"
+        ));
+    }
+    Ok(())
+}
+
+/// The `--seen-after`/`--seen-before` dates, validated up front by
+/// [`validate_date_filters`].
+fn seen_window(args: &Args) -> (Option<String>, Option<String>) {
+    (
+        args.seen_after.as_deref().and_then(filters::parse_since),
+        args.seen_before.as_deref().and_then(filters::parse_since),
+    )
+}
+
+/// Sample URLs shown per drop reason by `--explain-scope`.
+const EXPLAIN_SCOPE_SAMPLES: usize = 5;
+
+/// The target domains strict host validation checks against. Re-resolves
+/// the original domain list, normalized the same way as the fetch targets so
+/// the validator's hosts line up with what was queried. We can't read stdin a
+/// second time, so this falls back to whatever positional args and
+/// --domain-list files supplied.
+fn strict_scope_domains(args: &Args) -> Result<Vec<String>> {
+    let mut domains: Vec<String> = args.domains.clone();
+    for path in &args.domain_list {
+        domains.extend(read_domains_from_file(path)?);
+    }
+    Ok(domains
+        .iter()
+        .filter_map(|d| cli::normalize_domain(output::split_target_tag(d).0))
+        .filter_map(|d| utils::encode_query_host(&d).ok())
+        .collect())
+}
+
 fn apply_url_filters(
     args: &Args,
     urls: &std::collections::HashSet<String>,
@@ -585,8 +862,11 @@ fn apply_url_filters(
         || !args.patterns.is_empty()
         || !args.exclude_extensions.is_empty()
         || !args.exclude_patterns.is_empty()
+        || !args.exclude_regexes.is_empty()
         || args.min_length.is_some()
         || args.max_length.is_some()
+        || args.min_params.is_some()
+        || args.max_params.is_some()
     {
         let bar = progress_manager.create_filter_bar();
         bar.set_message("Applying filters to URLs...");
@@ -595,48 +875,60 @@ fn apply_url_filters(
         None
     };
 
-    // Apply URL filtering
-    let mut url_filter = UrlFilter::new();
-
-    // Apply presets if specified
-    if !args.preset.is_empty() {
-        url_filter.apply_presets(&args.preset);
-    }
-
-    // Apply additional filters (will be combined with preset filters)
-    url_filter
-        .with_extensions(args.extensions.clone())
-        .with_exclude_extensions(args.exclude_extensions.clone())
-        .with_patterns(args.patterns.clone())
-        .with_exclude_patterns(args.exclude_patterns.clone())
-        .with_min_length(args.min_length)
-        .with_max_length(args.max_length);
-
     // Apply URL filters
+    let url_filter = build_url_filter(args);
     let mut sorted_urls = url_filter.apply_filters(urls);
 
-    // Apply host validation if strict mode is enabled and we have domains (not from file)
-    if args.strict_enabled() && args.files.is_empty() {
+    let mut scope_report = args
+        .explain_scope
+        .then(|| filters::ScopeReport::new(EXPLAIN_SCOPE_SAMPLES));
+    if let Some(report) = scope_report.as_mut() {
+        let mut dropped: Vec<&String> = urls.iter().collect();
+        dropped.sort();
+        for url in dropped {
+            if let Some(reason) = url_filter.explain(url) {
+                report.record(reason.kind(), url, reason);
+            }
+        }
+    }
+
+    // Apply host validation if strict mode is enabled and we have domains (not
+    // from file), and --exclude-hosts either way
+    let domains = if args.strict_enabled() && args.files.is_empty() {
         if args.verbose && !args.silent {
             println!("Enforcing strict host validation...");
         }
-        // Re-resolve the original domain list, normalized the same way as the
-        // fetch targets so the validator's hosts line up with what was queried.
-        // We can't read stdin a second time, so this falls back to whatever
-        // positional args and --domain-list files supplied.
-        let mut domains: Vec<String> = args.domains.clone();
-        for path in &args.domain_list {
-            domains.extend(read_domains_from_file(path)?);
-        }
-        let domains: Vec<String> = domains
-            .iter()
-            .filter_map(|d| cli::normalize_domain(d))
-            .collect();
+        strict_scope_domains(args)?
+    } else {
+        Vec::new()
+    };
+    if !domains.is_empty() || !args.exclude_hosts.is_empty() {
+        let before = sorted_urls.len();
+        let mut host_validator = if domains.is_empty() {
+            HostValidator::any_host()
+        } else {
+            HostValidator::new(&domains, args.subs)
+        };
+        host_validator
+            .with_ignore_port(args.ignore_port)
+            .with_excluded_hosts(&args.exclude_hosts);
+        let mut excluded = 0usize;
+        sorted_urls.retain(|url| match host_validator.explain(url) {
+            None => true,
+            Some(reason) => {
+                if matches!(reason, filters::ScopeRejection::Excluded { .. }) {
+                    excluded += 1;
+                }
+                if let Some(report) = scope_report.as_mut() {
+                    report.record(reason.kind(), url, reason);
+                }
+                false
+            }
+        });
 
         if !domains.is_empty() {
-            let before = sorted_urls.len();
-            let host_validator = HostValidator::new(&domains, args.subs);
-            sorted_urls.retain(|url| host_validator.is_valid_host(url));
+            // Hosts the user excluded aren't a surprise; only count the rest
+            let before = before - excluded;
             let removed = before - sorted_urls.len();
 
             // When validation discards most (or all) of what providers returned,
@@ -665,6 +957,12 @@ fn apply_url_filters(
         bar.finish_with_message(format!("Filtered to {} URLs", sorted_urls.len()));
     }
 
+    if let Some(report) = scope_report.filter(|r| r.total() > 0) {
+        if !args.silent {
+            progress_manager.note(report.render().trim_end());
+        }
+    }
+
     if args.verbose && !args.silent {
         println!("Total unique URLs after filtering: {}", sorted_urls.len());
     }
@@ -680,9 +978,18 @@ fn apply_url_transformations(
 ) -> Vec<String> {
     // Apply URL transformation based on display options
     let transform_bar = if args.merge_endpoint
+        || !args.strip_params.is_empty()
+        || args.clean_params
+        || args.dedup_similar
+        || args.sample_per_path.is_some()
+        || args.fuzz_params
+        || args.fuzz_path_last
+        || args.replace_values.is_some()
         || args.show_only_host
         || args.show_only_path
         || args.show_only_param
+        || args.show_only_param_keys
+        || args.param_key_counts
     {
         let bar = progress_manager.create_transform_bar();
         bar.set_message("Applying URL transformations...");
@@ -695,10 +1002,19 @@ fn apply_url_transformations(
     let mut url_transformer = UrlTransformer::new();
     url_transformer
         .with_normalize_url(args.normalize_url)
+        .with_ignore_port(args.ignore_port)
+        .with_strip_params(args.stripped_params())
+        .with_dedup_similar(args.dedup_similar)
+        .with_sample_per_path(args.sample_per_path)
         .with_merge_endpoint(args.merge_endpoint)
+        .with_fuzz_params(args.fuzz_params)
+        .with_fuzz_path_last(args.fuzz_path_last)
+        .with_replace_values(args.replace_values.clone())
         .with_show_only_host(args.show_only_host)
         .with_show_only_path(args.show_only_path)
-        .with_show_only_param(args.show_only_param);
+        .with_show_only_param(args.show_only_param)
+        .with_show_only_param_keys(args.show_only_param_keys || args.param_key_counts)
+        .with_param_key_counts(args.param_key_counts);
 
     let transformed_urls = url_transformer.transform(urls);
 
@@ -739,7 +1055,10 @@ async fn create_cache_manager(args: &Args) -> Result<Option<CacheManager>> {
                 if !args.silent {
                     eprintln!("Error: Redis cache type selected but no --redis-url provided");
                 }
-                Err(anyhow::anyhow!("Redis URL required for Redis cache type"))
+                Err(
+                    UrxError::config(anyhow::anyhow!("Redis URL required for Redis cache type"))
+                        .into(),
+                )
             }
         }
         #[cfg(not(feature = "redis-cache"))]
@@ -747,7 +1066,7 @@ async fn create_cache_manager(args: &Args) -> Result<Option<CacheManager>> {
             if !args.silent {
                 eprintln!("Error: Redis cache support not compiled in. Use 'sqlite' or compile with --features redis-cache");
             }
-            Err(anyhow::anyhow!("Redis cache not supported"))
+            Err(UrxError::config(anyhow::anyhow!("Redis cache not supported")).into())
         }
         _ => {
             if !args.silent {
@@ -756,13 +1075,14 @@ async fn create_cache_manager(args: &Args) -> Result<Option<CacheManager>> {
                     args.cache_type
                 );
             }
-            Err(anyhow::anyhow!("Invalid cache type"))
+            Err(UrxError::config(anyhow::anyhow!("Invalid cache type")).into())
         }
     }
 }
 
-/// Create cache key from arguments and domains
-fn create_cache_key(domain: &str, args: &Args) -> CacheKey {
+/// Create the cache key for `domain` as fetched by `providers` under the
+/// filters in `args`
+fn create_cache_key(domain: &str, providers: &[String], args: &Args) -> CacheKey {
     let filters = CacheFilters {
         subs: args.subs,
         extensions: args.extensions.clone(),
@@ -775,9 +1095,161 @@ fn create_cache_key(domain: &str, args: &Args) -> CacheKey {
         strict: args.strict_enabled(),
         normalize_url: args.normalize_url,
         merge_endpoint: args.merge_endpoint,
+        ignore_port: args.ignore_port,
+        provider_status: args.provider_status_filter.clone(),
+        min_params: args.min_params,
+        max_params: args.max_params,
+    };
+
+    let key = CacheKey::new(domain, providers, &filters);
+    match &args.cache_namespace {
+        Some(namespace) => key.with_namespace(namespace),
+        None => key,
+    }
+}
+
+/// Handle `--list-cache-namespaces` / `--delete-cache-namespace`: report or
+/// purge namespaces in the configured cache backend.
+async fn manage_cache_namespaces(args: &Args) -> Result<()> {
+    let Some(cache) = create_cache_manager(args).await? else {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "Cache namespaces can't be managed with --no-cache"
+        ))
+        .into());
     };
 
-    CacheKey::new(domain, &effective_provider_ids(args), &filters)
+    if let Some(namespace) = &args.delete_cache_namespace {
+        let deleted = cache.delete_namespace(namespace).await?;
+        println!("Deleted {deleted} cached entries from namespace '{namespace}'");
+    }
+    if args.list_cache_namespaces {
+        let namespaces = cache.list_namespaces().await?;
+        if namespaces.is_empty() {
+            println!("The cache is empty.");
+            return Ok(());
+        }
+        println!("Cache namespaces:");
+        println!("  {:<24}  entries", "namespace");
+        println!("  {:<24}  -------", "------------------------");
+        for (name, entries) in namespaces {
+            println!("  {name:<24}  {entries}");
+        }
+    }
+    Ok(())
+}
+
+/// Probe each configured provider once for `--check-providers` and print a
+/// status table. Keyed providers without a key are reported rather than
+/// skipped; any unusable provider makes the command fail.
+async fn check_provider_health(args: &Args, network_settings: &NetworkSettings) -> Result<()> {
+    use runner::health::{check_providers, ProviderCheck, ProviderHealth};
+
+    let domain = args
+        .domains
+        .first()
+        .map(String::as_str)
+        .unwrap_or("example.com");
+
+    let mut checks: Vec<ProviderCheck> = Vec::new();
+    for id in effective_provider_ids(args) {
+        let (flag_keys, env_var) = match id.as_str() {
+            "vt" => (&args.vt_api_key, "URX_VT_API_KEY"),
+            "zoomeye" => (&args.zoomeye_api_key, "URX_ZOOMEYE_API_KEY"),
+            "github" => (&args.github_api_key, "URX_GITHUB_API_KEY"),
+            _ => continue,
+        };
+        if parse_api_keys(flag_keys.clone(), env_var).is_empty() {
+            let info = provider_catalog().iter().find(|p| p.id == id);
+            checks.push(ProviderCheck {
+                name: info.map_or(id.clone(), |p| p.display_name.to_string()),
+                health: ProviderHealth::MissingKey,
+                latency: None,
+            });
+        }
+    }
+
+    // The missing-key rows above replace initialize_providers' own errors.
+    let mut quiet = args.clone();
+    quiet.silent = true;
+    match initialize_providers(&quiet, network_settings) {
+        Ok(list) => {
+            if !args.silent {
+                eprintln!(
+                    "Checking {} provider(s) against {domain}...",
+                    list.providers.len()
+                );
+            }
+            checks.extend(check_providers(&list.providers, &list.names, domain).await);
+        }
+        Err(e) if checks.is_empty() => return Err(e),
+        Err(_) => {}
+    }
+
+    // Every probe above went through this backend, so a clean report also
+    // confirms the TLS stack the binary was built with works here.
+    println!("TLS backend: {}", network::client::TLS_BACKEND);
+    println!(
+        "  {:<28}  {:<8}  {:>8}  detail",
+        "provider", "status", "latency"
+    );
+    println!(
+        "  {:<28}  {:<8}  {:>8}  ------",
+        "----------------------------", "--------", "--------"
+    );
+    for check in &checks {
+        let latency = check
+            .latency
+            .map_or("-".to_string(), |d| format!("{}ms", d.as_millis()));
+        println!(
+            "  {:<28}  {:<8}  {:>8}  {}",
+            check.name,
+            check.health.label(),
+            latency,
+            check.health.detail()
+        );
+    }
+
+    let unusable = checks.iter().filter(|c| !c.health.is_usable()).count();
+    if unusable > 0 {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "{unusable} of {} provider(s) unusable",
+            checks.len()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+/// Print, for `--dry-run`, the requests every configured provider would send
+/// for each domain. Nothing goes over the network.
+fn print_request_plan(args: &Args, network_settings: &NetworkSettings) -> Result<()> {
+    let domains = collect_domains(args)?;
+    if domains.is_empty() {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "--dry-run needs domains: pass them positionally, use --domain-list FILE, or pipe them through stdin"
+        ))
+        .into());
+    }
+    let ProviderList {
+        providers,
+        names: provider_names,
+        ..
+    } = initialize_providers(args, network_settings)?;
+
+    for domain in &domains {
+        println!("{domain}");
+        for (provider, name) in providers.iter().zip(&provider_names) {
+            println!("  [{name}]");
+            let plan = provider.plan_requests(domain);
+            if plan.is_empty() {
+                println!("    (no request plan available)");
+            }
+            for step in plan {
+                println!("    {step}");
+            }
+        }
+    }
+    Ok(())
 }
 
 /// Collect URLs that truly belong to `domain`, using host validation instead of
@@ -787,164 +1259,708 @@ fn collect_domain_urls(
     urls: &std::collections::HashMap<String, std::collections::HashSet<String>>,
     domain: &str,
     include_subdomains: bool,
+    ignore_port: bool,
 ) -> std::collections::HashSet<String> {
-    let validator = HostValidator::new(&[domain.to_string()], include_subdomains);
+    let mut validator = HostValidator::new(&[domain.to_string()], include_subdomains);
+    validator.with_ignore_port(ignore_port);
     urls.keys()
         .filter(|url| validator.is_valid_host(url))
         .cloned()
         .collect()
 }
 
-/// Process domains with cache support
-async fn process_domains_with_cache(
-    domains: Vec<String>,
-    args: &Args,
-    progress_manager: &ProgressManager,
-    providers: &[Box<dyn Provider>],
-    provider_names: &[String],
-    cache_manager: Option<&CacheManager>,
-) -> Result<ProviderRunResult> {
-    use std::collections::{HashMap, HashSet};
-
-    let mut final_result = ProviderRunResult::default();
+/// Provider name recorded as the source of links harvested by --urlscan-submit.
+const URLSCAN_SUBMIT_SOURCE: &str = "Urlscan (live scan)";
 
-    // If caching is disabled, use normal processing
-    if cache_manager.is_none() {
-        return Ok(
-            process_domains(domains, args, progress_manager, providers, provider_names).await,
-        );
+/// `--urlscan-submit`: submit each target's root plus a few discovered pages
+/// to urlscan.io for fresh scans and merge the links found in the rendered DOM
+/// into `result`. Runs after the provider pass so it can pick from what the
+/// archives already know about, and stops with the links harvested so far
+/// once the run is cancelled: `--max-time` (counted from `started`), a spent
+/// `--max-requests` budget, or Ctrl-C.
+async fn run_urlscan_submit(
+    args: &Args,
+    network_settings: &NetworkSettings,
+    domains: &[String],
+    result: &mut ProviderRunResult,
+    started: std::time::Instant,
+) {
+    let keys = parse_api_keys(args.urlscan_api_key.clone(), "URX_URLSCAN_API_KEY");
+    if keys.is_empty() {
+        if !args.silent {
+            eprintln!("--urlscan-submit requires a urlscan API key (--urlscan-api-key or URX_URLSCAN_API_KEY); skipping live scans");
+        }
+        return;
     }
-
-    let cache = cache_manager.unwrap();
-    let mut domains_to_process = Vec::new();
-    let mut cached_urls: HashMap<String, HashSet<String>> = HashMap::new();
-
-    // Check cache for each domain
-    for domain in &domains {
-        let cache_key = create_cache_key(domain, args);
-
-        if cache.is_valid(&cache_key, args.cache_ttl).await? {
-            if let Some(cached_entry) = cache.get_cached_urls(&cache_key).await? {
-                verbose_print(args, format!("Using cached results for domain: {}", domain));
-
-                if args.incremental {
-                    // For incremental mode, we still need to fetch fresh URLs to compare
-                    domains_to_process.push(domain.clone());
-                } else {
-                    // Use cached results directly. Source attribution isn't
-                    // persisted in the cache, so cached URLs surface with an
-                    // empty provider set.
-                    for url in cached_entry.urls {
-                        cached_urls.entry(url).or_default();
-                    }
-                    continue;
+    let mut submitter = UrlscanProvider::new_with_keys(keys);
+    runner::apply_network_settings_to_provider(&mut submitter, network_settings);
+
+    let cancel = tokio_util::sync::CancellationToken::new();
+    let watch = {
+        let cancel = cancel.clone();
+        let deadline = (args.max_time > 0).then(|| {
+            std::time::Duration::from_secs(args.max_time).saturating_sub(started.elapsed())
+        });
+        let gate = network_settings.request_gate.clone();
+        tokio::spawn(async move {
+            let timeout = async {
+                match deadline {
+                    Some(d) => tokio::time::sleep(d).await,
+                    None => std::future::pending::<()>().await,
                 }
+            };
+            let exhausted = async {
+                match &gate {
+                    Some(gate) => gate.exhausted().await,
+                    None => std::future::pending::<()>().await,
+                }
+            };
+            tokio::select! {
+                _ = timeout => {}
+                _ = exhausted => {}
+                _ = utils::interrupt::interrupted() => {}
             }
-        }
-
-        // Domain not in cache or cache expired, needs processing
-        domains_to_process.push(domain.clone());
-    }
-
-    // Add cached URLs to final result
-    for (url, sources) in cached_urls {
-        final_result.urls.entry(url).or_default().extend(sources);
-    }
+            cancel.cancel();
+        })
+    };
+    submitter.with_cancellation(cancel.clone());
 
-    // Process domains that need fresh data
-    if !domains_to_process.is_empty() {
+    let discovered: Vec<String> = result.urls.keys().cloned().collect();
+    for domain in domains {
+        if cancel.is_cancelled() {
+            if !args.silent {
+                eprintln!("[urx] run cancelled; no further urlscan.io scans will be submitted");
+            }
+            break;
+        }
+        let targets = providers::select_scan_targets(domain, &discovered, args.urlscan_submit_max);
+        if targets.is_empty() {
+            continue;
+        }
         verbose_print(
             args,
             format!(
-                "Processing {} domains (cache miss/expired)",
-                domains_to_process.len()
+                "Submitting {} URL(s) to urlscan.io for {domain}",
+                targets.len()
             ),
         );
-
-        let fresh_run = process_domains(
-            domains_to_process.clone(),
-            args,
-            progress_manager,
-            providers,
-            provider_names,
-        )
-        .await;
-
-        // Carry the provider stats from the fresh run through to the caller.
-        final_result.stats = fresh_run.stats;
-
-        // Handle incremental scanning and cache updates
-        if args.incremental {
-            for domain in &domains_to_process {
-                let cache_key = create_cache_key(domain, args);
-
-                let domain_fresh_urls = collect_domain_urls(&fresh_run.urls, domain, args.subs);
-
-                let new_urls = cache.get_new_urls(&cache_key, &domain_fresh_urls).await?;
-
-                if !new_urls.is_empty() {
-                    verbose_print(
-                        args,
-                        format!("Found {} new URLs for domain: {}", new_urls.len(), domain),
-                    );
-                    for url in new_urls {
-                        if let Some(sources) = fresh_run.urls.get(&url) {
-                            final_result
-                                .urls
-                                .entry(url)
-                                .or_default()
-                                .extend(sources.iter().cloned());
-                        } else {
-                            final_result.urls.entry(url).or_default();
-                        }
+        match submitter.submit_and_harvest(&targets).await {
+            Ok(harvest) => {
+                verbose_print(
+                    args,
+                    format!(
+                        "urlscan.io: {} scan(s) for {domain} yielded {} link(s)",
+                        harvest.submitted,
+                        harvest.links.len()
+                    ),
+                );
+                if !args.silent {
+                    for e in &harvest.errors {
+                        eprintln!("urlscan.io live scan failed for {domain}: {e}");
                     }
                 }
-
-                // Update cache with all fresh URLs for this domain
-                let entry = CacheEntry::new(domain_fresh_urls.into_iter().collect());
-                cache.store_urls(&cache_key, &entry).await?;
+                for link in harvest.links {
+                    result
+                        .urls
+                        .entry(link)
+                        .or_default()
+                        .insert(URLSCAN_SUBMIT_SOURCE.to_string());
+                }
+                if harvest.quota_exhausted {
+                    if !args.silent {
+                        eprintln!("urlscan.io scan quota exhausted; no further scans will be submitted this run");
+                    }
+                    break;
+                }
             }
-        } else {
-            // Normal mode: merge all fresh URLs (and their providers) into the result.
-            for (url, sources) in &fresh_run.urls {
-                final_result
-                    .urls
-                    .entry(url.clone())
-                    .or_default()
-                    .extend(sources.iter().cloned());
-            }
-
-            // For simplicity, store all URLs for each domain (this could be optimized)
-            for domain in &domains_to_process {
-                let cache_key = create_cache_key(domain, args);
-                let domain_urls: Vec<String> =
-                    collect_domain_urls(&fresh_run.urls, domain, args.subs)
-                        .into_iter()
-                        .collect();
-
-                if !domain_urls.is_empty() {
-                    let entry = CacheEntry::new(domain_urls);
-                    cache.store_urls(&cache_key, &entry).await?;
+            Err(e) => {
+                if !args.silent {
+                    eprintln!("urlscan.io live scan failed for {domain}: {e}");
                 }
             }
         }
     }
-
-    // Clean up expired cache entries
-    cache.cleanup_expired(args.cache_ttl * 2).await?;
-
-    Ok(final_result)
+    watch.abort();
+}
+
+/// Reorder providers for `--smart-order`, using the yield history recorded for
+/// the domain families being scanned.
+async fn rank_providers_by_history(
+    args: &Args,
+    cache: &CacheManager,
+    domains: &[String],
+    providers: &[Box<dyn Provider>],
+    provider_names: &[String],
+) -> Result<(Vec<Box<dyn Provider>>, Vec<String>)> {
+    use std::collections::{BTreeSet, HashMap};
+
+    let families: BTreeSet<String> = domains.iter().map(|d| cache::domain_family(d)).collect();
+    let mut history: HashMap<String, cache::ProviderYield> = HashMap::new();
+    for family in &families {
+        for (provider, provider_yield) in cache.provider_history(family).await? {
+            history.entry(provider).or_default().merge(&provider_yield);
+        }
+    }
+
+    let order = cache::rank_providers(provider_names, &history);
+    let names: Vec<String> = order.iter().map(|&i| provider_names[i].clone()).collect();
+    verbose_print(args, format!("Smart provider order: {}", names.join(", ")));
+    Ok((
+        order.iter().map(|&i| providers[i].clone_box()).collect(),
+        names,
+    ))
+}
+
+/// Fold a run's completed fetches into the per-family provider history.
+async fn record_provider_history(cache: &CacheManager, fetches: &[FetchRecord]) -> Result<()> {
+    use std::collections::HashMap;
+
+    let mut totals: HashMap<(String, &str), cache::ProviderYield> = HashMap::new();
+    for fetch in fetches {
+        totals
+            .entry((cache::domain_family(&fetch.domain), fetch.provider.as_str()))
+            .or_default()
+            .merge(&cache::ProviderYield::from_fetch(fetch.urls, fetch.elapsed));
+    }
+    for ((family, provider), provider_yield) in totals {
+        cache
+            .record_provider_yield(&family, provider, &provider_yield)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Build a `--provider-quota` meter for every metered provider, starting
+/// from the requests already recorded for `day` (UTC `YYYY-MM-DD`).
+async fn load_provider_quotas(
+    args: &Args,
+    cache: &CacheManager,
+    day: &str,
+) -> Result<network::QuotaBook> {
+    let limits = network::parse_provider_quotas(&args.provider_quota)?;
+    let usage = cache.quota_usage(day).await?;
+    Ok(network::METERED_PROVIDERS
+        .iter()
+        .map(|id| {
+            let used = usage.get(*id).copied().unwrap_or(0);
+            let meter = network::QuotaMeter::new(id, limits.get(*id).copied(), used);
+            (id.to_string(), meter)
+        })
+        .collect())
+}
+
+/// Warn before the run about providers whose daily quota is nearly or
+/// entirely spent; spent ones skip every domain.
+fn warn_about_provider_quotas(args: &Args, providers: &[Box<dyn Provider>], names: &[String]) {
+    if args.silent {
+        return;
+    }
+    for (provider, name) in providers.iter().zip(names) {
+        let Some(meter) = provider.quota() else {
+            continue;
+        };
+        let Some(limit) = meter.limit() else {
+            continue;
+        };
+        let used = meter.used();
+        if meter.is_spent() {
+            eprintln!(
+                "[urx] warning: {name} has spent its daily quota ({used}/{limit} requests today, UTC) and will be skipped; raise it with --provider-quota {}=N",
+                meter.provider()
+            );
+        } else if meter.is_near_limit() {
+            eprintln!(
+                "[urx] warning: {name} has used {used}/{limit} requests of its daily quota (UTC)"
+            );
+        }
+    }
+}
+
+/// Add each metered provider's requests from this run to `day`'s counts.
+async fn record_provider_quotas(
+    args: &Args,
+    cache: &CacheManager,
+    day: &str,
+    quotas: &network::QuotaBook,
+) -> Result<()> {
+    for meter in quotas.values().filter(|m| m.sent() > 0) {
+        cache
+            .record_quota_usage(day, meter.provider(), meter.sent())
+            .await?;
+        let limit = meter
+            .limit()
+            .map_or_else(|| "no limit".to_string(), |l| l.to_string());
+        verbose_print(
+            args,
+            format!(
+                "Quota: {} sent {} request(s), {}/{limit} today (UTC)",
+                meter.provider(),
+                meter.sent(),
+                meter.used()
+            ),
+        );
+    }
+    Ok(())
+}
+
+/// Run the providers over `domains`, serving what the cache still holds.
+///
+/// Entries are cached per (domain, provider), and each provider's slice is
+/// checked against its own TTL (`--cache-ttl-by`, falling back to
+/// `--cache-ttl`), so only the providers whose slice went stale are queried.
+/// `--incremental` always fetches, and diffs against one entry per domain
+/// covering all providers.
+async fn process_domains_with_cache(
+    domains: Vec<String>,
+    args: &Args,
+    progress_manager: &ProgressManager,
+    provider_list: &ProviderList,
+    cache_manager: Option<&CacheManager>,
+    request_gate: Option<&RequestGate>,
+) -> Result<ProviderRunResult> {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    let Some(cache) = cache_manager else {
+        if args.smart_order && !args.silent {
+            eprintln!("--smart-order needs the cache for provider history; using the configured provider order");
+        }
+        return Ok(process_domains(
+            domains,
+            args,
+            progress_manager,
+            &provider_list.providers,
+            &provider_list.names,
+            request_gate,
+        )
+        .await);
+    };
+
+    let final_result = if args.incremental {
+        process_domains_incremental(
+            domains,
+            args,
+            progress_manager,
+            provider_list,
+            cache,
+            request_gate,
+        )
+        .await?
+    } else {
+        let mut final_result = ProviderRunResult::default();
+
+        // Serve every fresh slice from the cache, and group the domains by
+        // the set of providers that have to be queried for them.
+        let mut stale_groups: BTreeMap<BTreeSet<usize>, Vec<String>> = BTreeMap::new();
+        let mut cache_hits = 0usize;
+        for domain in &domains {
+            let mut stale = BTreeSet::new();
+            for (i, (id, name)) in provider_list
+                .ids
+                .iter()
+                .zip(&provider_list.names)
+                .enumerate()
+            {
+                let cache_key = create_cache_key(domain, &[id.clone(), name.clone()], args);
+                let cached = if cache.is_valid(&cache_key, args.cache_ttl_for(id)).await? {
+                    cache.get_cached_urls(&cache_key).await?
+                } else {
+                    None
+                };
+                metrics::record_cache_lookup(cached.is_some());
+                match cached {
+                    Some(entry) => {
+                        cache_hits += 1;
+                        verbose_print(
+                            args,
+                            format!("Using cached {name} results for domain: {domain}"),
+                        );
+                        for url in entry.urls {
+                            final_result
+                                .urls
+                                .entry(url)
+                                .or_default()
+                                .insert(name.clone());
+                        }
+                    }
+                    None => {
+                        stale.insert(i);
+                    }
+                }
+            }
+            if !stale.is_empty() {
+                stale_groups.entry(stale).or_default().push(domain.clone());
+            }
+        }
+
+        // Cache entries keep URLs only, not the provider metadata the date
+        // filters read, so cached URLs can't be dated and all pass them.
+        let date_flag = [
+            ("--since", &args.since),
+            ("--seen-after", &args.seen_after),
+            ("--seen-before", &args.seen_before),
+        ]
+        .into_iter()
+        .find_map(|(flag, raw)| raw.is_some().then_some(flag));
+        if let Some(flag) = date_flag.filter(|_| cache_hits > 0 && !args.silent) {
+            eprintln!(
+                "{flag} can't filter {cache_hits} cached result(s), which carry no dates; use --no-cache to filter everything"
+            );
+        }
+
+        for (stale, group) in stale_groups {
+            verbose_print(
+                args,
+                format!(
+                    "Processing {} domains with {} provider(s) (cache miss/expired)",
+                    group.len(),
+                    stale.len()
+                ),
+            );
+            let providers: Vec<Box<dyn Provider>> = stale
+                .iter()
+                .map(|&i| provider_list.providers[i].clone_box())
+                .collect();
+            let names: Vec<String> = stale
+                .iter()
+                .map(|&i| provider_list.names[i].clone())
+                .collect();
+            let fresh_run = fetch_uncached(
+                group.clone(),
+                args,
+                progress_manager,
+                &providers,
+                &names,
+                cache,
+                request_gate,
+            )
+            .await?;
+
+            // Store each provider's share of the domain's URLs in its slice.
+            for domain in &group {
+                let domain_urls =
+                    collect_domain_urls(&fresh_run.urls, domain, args.subs, args.ignore_port);
+                for &i in &stale {
+                    let (id, name) = (&provider_list.ids[i], &provider_list.names[i]);
+                    let provider_urls: Vec<String> = domain_urls
+                        .iter()
+                        .filter(|url| fresh_run.urls[*url].contains(name))
+                        .cloned()
+                        .collect();
+                    if !provider_urls.is_empty() {
+                        let cache_key = create_cache_key(domain, &[id.clone(), name.clone()], args);
+                        cache
+                            .store_urls(&cache_key, &CacheEntry::new(provider_urls))
+                            .await?;
+                    }
+                }
+            }
+            final_result.merge(fresh_run);
+        }
+        final_result
+    };
+
+    // Clean up expired cache entries. Keep anything the longest TTL in play
+    // might still want.
+    let longest_ttl = provider_list
+        .ids
+        .iter()
+        .map(|id| args.cache_ttl_for(id))
+        .chain([args.cache_ttl])
+        .max()
+        .unwrap_or(args.cache_ttl);
+    cache.cleanup_expired(longest_ttl.saturating_mul(2)).await?;
+
+    Ok(final_result)
+}
+
+/// `--incremental`: fetch every domain, report only the URLs the domain's
+/// cached entry didn't already have, then replace the entry with this run.
+async fn process_domains_incremental(
+    domains: Vec<String>,
+    args: &Args,
+    progress_manager: &ProgressManager,
+    provider_list: &ProviderList,
+    cache: &CacheManager,
+    request_gate: Option<&RequestGate>,
+) -> Result<ProviderRunResult> {
+    let mut final_result = ProviderRunResult::default();
+    let provider_ids = effective_provider_ids(args);
+
+    for domain in &domains {
+        let cache_key = create_cache_key(domain, &provider_ids, args);
+        if cache.is_valid(&cache_key, args.cache_ttl).await?
+            && cache.get_cached_urls(&cache_key).await?.is_some()
+        {
+            verbose_print(args, format!("Using cached results for domain: {}", domain));
+        }
+    }
+
+    if domains.is_empty() {
+        return Ok(final_result);
+    }
+    verbose_print(
+        args,
+        format!("Processing {} domains (incremental)", domains.len()),
+    );
+
+    let fresh_run = fetch_uncached(
+        domains.clone(),
+        args,
+        progress_manager,
+        &provider_list.providers,
+        &provider_list.names,
+        cache,
+        request_gate,
+    )
+    .await?;
+
+    for domain in &domains {
+        let cache_key = create_cache_key(domain, &provider_ids, args);
+
+        let domain_fresh_urls =
+            collect_domain_urls(&fresh_run.urls, domain, args.subs, args.ignore_port);
+
+        let new_urls = cache.get_new_urls(&cache_key, &domain_fresh_urls).await?;
+
+        if !new_urls.is_empty() {
+            verbose_print(
+                args,
+                format!("Found {} new URLs for domain: {}", new_urls.len(), domain),
+            );
+            for url in new_urls {
+                if let Some(sources) = fresh_run.urls.get(&url) {
+                    final_result
+                        .urls
+                        .entry(url)
+                        .or_default()
+                        .extend(sources.iter().cloned());
+                } else {
+                    final_result.urls.entry(url).or_default();
+                }
+            }
+        }
+
+        // Update cache with all fresh URLs for this domain
+        let entry = CacheEntry::new(domain_fresh_urls.into_iter().collect());
+        cache.store_urls(&cache_key, &entry).await?;
+    }
+
+    // Carry the provider stats and per-URL metadata from the fresh run
+    // through to the caller.
+    final_result.stats = fresh_run.stats;
+    final_result.metadata = fresh_run.metadata;
+    final_result.fetches = fresh_run.fetches;
+    Ok(final_result)
+}
+
+/// Query `providers` for `domains` (in `--smart-order` when asked) and fold
+/// the completed fetches into the provider yield history.
+async fn fetch_uncached(
+    domains: Vec<String>,
+    args: &Args,
+    progress_manager: &ProgressManager,
+    providers: &[Box<dyn Provider>],
+    provider_names: &[String],
+    cache: &CacheManager,
+    request_gate: Option<&RequestGate>,
+) -> Result<ProviderRunResult> {
+    let ranked = if args.smart_order {
+        Some(rank_providers_by_history(args, cache, &domains, providers, provider_names).await?)
+    } else {
+        None
+    };
+    let (providers, provider_names) = match &ranked {
+        Some((providers, names)) => (providers.as_slice(), names.as_slice()),
+        None => (providers, provider_names),
+    };
+
+    let fresh_run = process_domains(
+        domains,
+        args,
+        progress_manager,
+        providers,
+        provider_names,
+        request_gate,
+    )
+    .await;
+
+    record_provider_history(cache, &fresh_run.fetches).await?;
+    Ok(fresh_run)
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() {
+    if let Err(err) = run().await {
+        // The report anyhow prints for a `main` returning `Result`, with the
+        // exit code picked by the error's kind.
+        eprintln!("Error: {err:?}");
+        std::process::exit(error::exit_code(&err));
+    }
+}
+
+/// Start the `--metrics-addr` endpoint. Binding happens before any work, so
+/// a taken port fails the run instead of leaving it unobservable.
+#[cfg(feature = "metrics")]
+async fn start_metrics_server(args: &Args, addr: std::net::SocketAddr) -> Result<()> {
+    let bound = metrics::serve(addr).await.map_err(UrxError::config)?;
+    verbose_print(args, format!("Serving metrics at http://{bound}/metrics"));
+    Ok(())
+}
+
+#[cfg(not(feature = "metrics"))]
+async fn start_metrics_server(args: &Args, _addr: std::net::SocketAddr) -> Result<()> {
+    if !args.silent {
+        eprintln!("Error: Metrics support not compiled in. Rebuild with --features metrics to use --metrics-addr");
+    }
+    Err(UrxError::config(anyhow::anyhow!("Metrics endpoint not supported")).into())
+}
+
+/// `--format parquet` writes a binary file that compresses its own columns,
+/// so it needs `--output` and no `--compress` on top.
+#[cfg(feature = "parquet")]
+fn check_parquet_output(args: &Args) -> Result<()> {
+    let Some(path) = &args.output else {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "--format parquet is binary and needs --output FILE"
+        ))
+        .into());
+    };
+    if args.compress.is_some() || output::Compression::from_path(path).is_some() {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "Parquet files are compressed internally; drop --compress and the .gz/.zst extension"
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet"))]
+fn check_parquet_output(args: &Args) -> Result<()> {
+    if !args.silent {
+        eprintln!("Error: Parquet support not compiled in. Rebuild with --features parquet to use --format parquet");
+    }
+    Err(UrxError::config(anyhow::anyhow!("Parquet output not supported")).into())
+}
+
+#[cfg(feature = "s3")]
+type S3Output = output::S3Uploader;
+#[cfg(not(feature = "s3"))]
+type S3Output = std::convert::Infallible;
+
+/// Whether `--output` names an object-storage target rather than a file.
+fn is_s3_output(args: &Args) -> bool {
+    args.output
+        .as_ref()
+        .and_then(|path| path.to_str())
+        .is_some_and(|path| path.starts_with("s3://"))
+}
+
+/// Set up the upload for `--output s3://bucket/key` and point `--output` at
+/// the local file it's written to first. Credentials are checked here, so a
+/// missing one fails before the scan.
+#[cfg(feature = "s3")]
+fn prepare_s3_output(
+    args: &mut Args,
+    client_config: &network::client::HttpClientConfig,
+) -> Result<Option<S3Output>> {
+    if !is_s3_output(args) {
+        return Ok(None);
+    }
+    let target = args
+        .output
+        .as_ref()
+        .and_then(|path| path.to_str())
+        .unwrap_or("");
+    let location = output::S3Location::parse(target).ok_or_else(|| {
+        UrxError::config(anyhow::anyhow!(
+            "Invalid S3 output {target}: expected s3://bucket/key"
+        ))
+    })?;
+    if args.append || args.stream || args.watch_ct || args.split_output_by_tag {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "s3:// output can't be used with --append, --stream, --watch-ct or --split-output-by-tag"
+        ))
+        .into());
+    }
+    if args.sign_output.is_some() {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "s3:// output can't be signed; write a local file with --sign-output instead"
+        ))
+        .into());
+    }
+    let local = std::env::temp_dir().join(format!(
+        "urx-{}-{}",
+        std::process::id(),
+        location.file_name()
+    ));
+    let credentials = output::S3Credentials::from_env().map_err(UrxError::config)?;
+    let uploader =
+        output::S3Uploader::new(location, credentials, client_config).map_err(UrxError::config)?;
+    args.output = Some(local);
+    Ok(Some(uploader))
+}
+
+#[cfg(not(feature = "s3"))]
+fn prepare_s3_output(
+    args: &mut Args,
+    _client_config: &network::client::HttpClientConfig,
+) -> Result<Option<S3Output>> {
+    if !is_s3_output(args) {
+        return Ok(None);
+    }
+    if !args.silent {
+        eprintln!("Error: S3 support not compiled in. Rebuild with --features s3 to use --output s3://...");
+    }
+    Err(UrxError::config(anyhow::anyhow!("S3 output not supported")).into())
+}
+
+/// Upload the output file written for an s3:// `--output`, then remove it.
+/// A failed upload keeps the local file and says where it is.
+#[cfg(feature = "s3")]
+async fn upload_s3_output(args: &Args, uploader: &S3Output) {
+    let Some(local) = &args.output else {
+        return;
+    };
+    let result = match std::fs::read(local) {
+        Ok(body) => uploader.upload(body).await,
+        Err(e) => Err(e.into()),
+    };
+    match result {
+        Ok(()) => {
+            let _ = std::fs::remove_file(local);
+            verbose_print(args, format!("Results uploaded to: {}", uploader.target()));
+        }
+        Err(e) => {
+            if !args.silent {
+                eprintln!(
+                    "Error uploading output: {e:#} (results kept at {})",
+                    local.display()
+                );
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+async fn upload_s3_output(_args: &Args, uploader: &S3Output) {
+    match *uploader {}
+}
+
+async fn run() -> Result<()> {
+    let started = std::time::Instant::now();
     let mut args = Args::parse();
+    // From here on Ctrl-C stops the run gracefully and keeps what it found
+    utils::interrupt::install();
 
     // Short-circuit: list providers and exit without doing any I/O.
     if args.list_providers {
         print_provider_list();
         return Ok(());
     }
+    if args.schema {
+        print!("{}", output::OUTPUT_SCHEMA);
+        return Ok(());
+    }
 
     // Load configuration and apply it to args
     // This ensures command line options take precedence over config file
@@ -971,13 +1987,201 @@ async fn main() -> Result<()> {
         cli_supplied_zoomeye || env_supplied_zoomeye,
     );
 
+    // Fold --exclude-file into the exclude filters
+    if let Some(path) = &args.exclude_file {
+        let list =
+            filters::read_exclude_file(path, args.case_sensitive).map_err(UrxError::config)?;
+        verbose_print(
+            &args,
+            format!(
+                "Loaded {} exclude patterns from {}",
+                list.substrings.len() + list.regexes.len(),
+                path.display()
+            ),
+        );
+        args.exclude_patterns.extend(list.substrings);
+        args.exclude_regexes.extend(list.regexes);
+    }
+
+    // After the config so its [preset.<name>] entries are listed too
+    if args.list_presets {
+        print_preset_list(&args);
+        return Ok(());
+    }
+
     // Honor --no-color / NO_COLOR before any styled output is produced.
     configure_colors(&args);
 
+    // Writers compress by extension, so --compress only has to name the file.
+    if let (Some(compression), Some(path)) = (args.compress, &args.output) {
+        args.output = Some(compression.apply_to(path));
+    }
+
+    // Cache maintenance runs against the configured backend and exits.
+    if args.list_cache_namespaces || args.delete_cache_namespace.is_some() {
+        return manage_cache_namespaces(&args).await;
+    }
+
+    // Reject malformed --provider-opt / [provider.<id>] overrides before any
+    // provider is built.
+    args.provider_overrides().map_err(UrxError::config)?;
+    validate_custom_providers(&args).map_err(UrxError::config)?;
+    network::parse_provider_quotas(&args.provider_quota).map_err(UrxError::config)?;
+    let ttl_ids: Vec<String> = args
+        .cache_ttl_overrides()
+        .map_err(UrxError::config)?
+        .into_keys()
+        .collect();
+    validate_provider_ids(&args, &ttl_ids, "--cache-ttl-by").map_err(UrxError::config)?;
+    validate_date_filters(&args).map_err(UrxError::config)?;
+    validate_count_output(&args).map_err(UrxError::config)?;
+
+    if args.deterministic {
+        utils::determinism::set_seed(args.seed);
+        verbose_print(&args, format!("Deterministic mode, seed {}", args.seed));
+    }
+
+    // Throttle every response body read from here on (providers and testers).
+    network::set_max_bandwidth(args.max_bandwidth);
+    if let Some(rate) = args.max_bandwidth {
+        verbose_print(&args, format!("Max bandwidth: {rate} bytes/second"));
+    }
+    if let Some(rate) = args.global_rate_limit {
+        verbose_print(&args, format!("Global rate limit: {rate} requests/second"));
+    }
+    // --no-cache turns off every cache, this one included.
+    if args.http_cache && !args.no_cache {
+        let dir = args.http_cache_path.clone().unwrap_or_else(|| {
+            let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
+            std::path::PathBuf::from(home)
+                .join(".urx")
+                .join("http-cache")
+        });
+        verbose_print(&args, format!("Using HTTP cache at: {}", dir.display()));
+        network::set_http_cache(dir);
+    }
+    if let Some(budget) = args.max_requests {
+        verbose_print(&args, format!("Request budget: {budget} requests"));
+    }
+    if let Some(addr) = args.metrics_addr {
+        start_metrics_server(&args, addr).await?;
+    }
+    // The summary reads its cache hit counts from the metrics registry
+    if wants_run_summary(&args) {
+        metrics::enable();
+    }
+
+    if args.append {
+        if args.output.is_none() {
+            return Err(UrxError::config(anyhow::anyhow!(
+                "--append needs --output FILE to add to"
+            ))
+            .into());
+        }
+        if args.format_template.is_some()
+            || !output::APPENDABLE_FORMATS.contains(&args.format.to_lowercase().as_str())
+        {
+            return Err(UrxError::config(anyhow::anyhow!(
+                "--append reads the existing output back, so it needs --format {} without --format-template",
+                output::APPENDABLE_FORMATS.join(", ")
+            ))
+            .into());
+        }
+        if let Some(columns) = &args.csv_columns {
+            if !columns.names().iter().any(|name| name == "url") {
+                return Err(UrxError::config(anyhow::anyhow!(
+                    "--append matches rows by URL, so --csv-columns needs the url column"
+                ))
+                .into());
+            }
+        }
+    }
+
+    // Read the --diff baseline before scanning, too: an unreadable file fails
+    // early, and an --output of the same name can't overwrite it first.
+    let diff_baseline = match &args.diff {
+        Some(path) => Some(output::read_entries(path).map_err(UrxError::io)?),
+        None => None,
+    };
+
+    // Load the signing key up front so a bad key fails before a long scan
+    // rather than after it.
+    let signing_key = match &args.sign_output {
+        Some(key_path) => {
+            if args.output.is_none() {
+                return Err(UrxError::config(anyhow::anyhow!(
+                    "--sign-output needs --output FILE to sign"
+                ))
+                .into());
+            }
+            Some(output::load_signing_key(key_path)?)
+        }
+        None => None,
+    };
+
     // Create common network settings and progress manager once
     let network_settings = NetworkSettings::from_args(&args);
+    // Like the signing key, a bad --es-url fails before the scan
+    let client_config = network::client::HttpClientConfig {
+        timeout: network_settings.timeout,
+        insecure: network_settings.insecure,
+        random_agent: network_settings.random_agent,
+        proxy: network_settings.proxy.clone(),
+        proxy_auth: network_settings.proxy_auth.clone(),
+        ..Default::default()
+    };
+    let es_sink = match &args.es_url {
+        Some(es_url) => {
+            let mut sink = output::ElasticsearchSink::new(es_url, &args.es_index, &client_config)
+                .map_err(UrxError::config)?;
+            sink.with_api_key(std::env::var("URX_ES_API_KEY").ok());
+            Some(sink)
+        }
+        None => None,
+    };
+    // An s3:// --output is written to a local file and uploaded once done
+    let s3_output = prepare_s3_output(&mut args, &client_config)?;
     let progress_check = args.no_progress || args.silent;
-    let progress_manager = ProgressManager::new(progress_check);
+    let progress_manager =
+        ProgressManager::with_mode(ProgressMode::detect(progress_check, args.force_progress));
+
+    if args.check_providers {
+        return check_provider_health(&args, &network_settings).await;
+    }
+    if args.dry_run {
+        return print_request_plan(&args, &network_settings);
+    }
+
+    let whole_run_format = matches!(
+        args.format.to_lowercase().as_str(),
+        "html" | "sitemap" | "burp" | "tree" | "dot" | "parquet"
+    );
+    if (args.watch_ct || args.stream) && whole_run_format {
+        return Err(UrxError::config(anyhow::anyhow!(
+            "--format {} builds one document at the end of a run; use json or plain with --stream and --watch-ct",
+            args.format
+        ))
+        .into());
+    }
+    if args.format.eq_ignore_ascii_case("parquet") {
+        check_parquet_output(&args)?;
+    }
+
+    // Watch mode streams results until interrupted and never reaches the
+    // one-shot filter/test/output pipeline below.
+    if args.watch_ct {
+        return run_watch_ct(&args).await;
+    }
+
+    // Stream mode prints as providers finish and keeps only a fixed-size
+    // dedup filter, so it can't feed the sort/test/output pipeline either.
+    if args.stream {
+        run_stream(&args, &network_settings).await?;
+        if let Some(key) = &signing_key {
+            sign_output_file(&args, key);
+        }
+        return Ok(());
+    }
 
     // Check if file input is provided
     let urls_from_file = read_urls_from_files(&args)?;
@@ -986,6 +2190,7 @@ async fn main() -> Result<()> {
     // outlives the provider branch where it's created and is cleared together
     // with the bars when the scan finishes.
     let mut _header_line = None;
+    let mut target_tags = output::TargetTags::default();
     let run_result = if let Some(urls) = urls_from_file {
         // URLs read from file(s) - skip provider processing. Mark every URL
         // as coming from "file" so downstream `--show-sources` is consistent.
@@ -997,10 +2202,13 @@ async fn main() -> Result<()> {
         ProviderRunResult {
             urls: url_map,
             stats: Vec::new(),
+            metadata: std::collections::HashMap::new(),
+            fetches: Vec::new(),
         }
     } else {
         // No file input - use traditional domain-based approach
-        let domains = collect_domains(&args)?;
+        let (domains, tags) = collect_tagged_domains(&args)?;
+        target_tags = tags;
 
         if domains.is_empty() {
             if !args.silent {
@@ -1010,48 +2218,114 @@ async fn main() -> Result<()> {
             }
             return Ok(());
         }
+        // Initialize cache manager if caching is enabled
+        let cache_manager = create_cache_manager(&args).await?;
+
+        // Daily quota meters start from today's persisted counts, and have
+        // to be in the settings before the providers are built.
+        let mut network_settings = network_settings.clone();
+        let quota_day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        if let Some(cache) = &cache_manager {
+            network_settings.quotas = load_provider_quotas(&args, cache, &quota_day).await?;
+        }
+
         // Initialize providers based on command-line flags and API keys
-        let (providers, provider_names) = initialize_providers(&args, &network_settings)?;
+        let provider_list = initialize_providers(&args, &network_settings)?;
+        warn_about_provider_quotas(&args, &provider_list.providers, &provider_list.names);
 
         // Header at the top of the live region — transient, cleared with the
         // bars when the scan finishes so only the URL list remains.
         _header_line = Some(
-            progress_manager.create_header_line(render_header(domains.len(), provider_names.len())),
+            progress_manager
+                .create_header_line(render_header(domains.len(), provider_list.names.len())),
         );
 
-        // Initialize cache manager if caching is enabled
-        let cache_manager = create_cache_manager(&args).await?;
-
         // Process each domain with caching support
-        process_domains_with_cache(
+        let mut result = process_domains_with_cache(
             domains.clone(),
             &args,
             &progress_manager,
-            &providers,
-            &provider_names,
+            &provider_list,
             cache_manager.as_ref(),
+            network_settings.request_gate.as_ref(),
         )
-        .await?
+        .await?;
+
+        if let Some(cache) = &cache_manager {
+            record_provider_quotas(&args, cache, &quota_day, &network_settings.quotas).await?;
+        }
+        if let Some(http_cache) = network::http_cache() {
+            let (revalidated, stored) = http_cache.counts();
+            verbose_print(
+                &args,
+                format!("HTTP cache: {revalidated} response(s) unchanged (304), {stored} stored"),
+            );
+        }
+
+        if args.urlscan_submit {
+            run_urlscan_submit(&args, &network_settings, &domains, &mut result, started).await;
+        }
+        result
     };
 
     // URL-only view for filters (they don't care about sources).
-    let all_urls: std::collections::HashSet<String> = run_result.urls.keys().cloned().collect();
+    let mut all_urls: std::collections::HashSet<String> = run_result.urls.keys().cloned().collect();
+
+    // --since needs provider metadata, so it runs here rather than in the
+    // string-only UrlFilter. The date was validated up front.
+    if let Some(since) = args.since.as_deref().and_then(filters::parse_since) {
+        all_urls.retain(|url| filters::is_modified_since(run_result.metadata.get(url), &since));
+    }
+    let (seen_after, seen_before) = seen_window(&args);
+    if seen_after.is_some() || seen_before.is_some() {
+        all_urls.retain(|url| {
+            filters::is_seen_within(
+                run_result.metadata.get(url),
+                seen_after.as_deref(),
+                seen_before.as_deref(),
+            )
+        });
+    }
 
     // Apply URL filtering
     let sorted_urls = apply_url_filters(&args, &all_urls, &progress_manager)?;
 
     // Apply URL transformations
     let transformed_urls = apply_url_transformations(&args, sorted_urls, &progress_manager);
+    let after_filters = transformed_urls.len();
 
-    let outputter = create_outputter(&args.format);
+    let outputter = build_outputter(&args)?;
 
     // Determine if we need to do status checking (either explicitly requested or needed for filters)
-    let should_check_status =
-        args.check_status || !args.include_status.is_empty() || !args.exclude_status.is_empty();
+    let should_check_status = args.check_status
+        || args.body_stats
+        || !args.include_headers.is_empty()
+        || args.match_body.is_some()
+        || args.filter_body.is_some()
+        || !args.include_status.is_empty()
+        || !args.exclude_status.is_empty();
+
+    // One collector shared by every tester that reads response bodies
+    let secret_scanner =
+        (args.scan_secrets || args.secrets_output.is_some()).then(SecretScanner::new);
+    if secret_scanner.is_some() && !should_check_status && !args.extract_links && !args.silent {
+        eprintln!(
+            "Warning: --scan-secrets only scans bodies fetched by --check-status or --extract-links; nothing will be scanned"
+        );
+    }
 
-    let mut final_urls = if should_check_status || args.extract_links {
-        // Initialize appropriate testers
-        let mut testers: Vec<Box<dyn Tester>> = Vec::new();
+    let mut final_urls = if should_check_status
+        || args.extract_links
+        || args.extract_title
+        || args.tls_info
+        || args.extract_sourcemaps
+        || args.extract_js_endpoints
+        || args.check_open_redirect
+        || args.check_reflection
+    {
+        // Initialize appropriate testers, in the order process_urls_with_testers
+        // expects them
+        let mut testers: Vec<(TesterRole, Box<dyn Tester>)> = Vec::new();
 
         // Initialize StatusChecker if any status check or filtering is needed
         if should_check_status {
@@ -1077,13 +2351,48 @@ async fn main() -> Result<()> {
                 verbose_print(
                     &args,
                     format!(
-                        "Excluding status codes that match: {}",
-                        args.exclude_status.join(", ")
+                        "Excluding status codes that match: {}",
+                        args.exclude_status.join(", ")
+                    ),
+                );
+            }
+
+            if args.body_stats {
+                status_checker.with_body_stats(true);
+                verbose_print(&args, "Recording body hashes and word/line counts");
+            }
+
+            if args.http_version.is_some() {
+                status_checker.with_record_protocol(true);
+            }
+
+            if !args.include_headers.is_empty() {
+                status_checker.with_include_headers(args.include_headers.clone());
+                verbose_print(
+                    &args,
+                    format!(
+                        "Capturing response headers: {}",
+                        args.include_headers.join(", ")
                     ),
                 );
             }
 
-            testers.push(Box::new(status_checker));
+            // Config values skip the CLI's validation, so compile here
+            let body_regex = |flag: &str, pattern: &Option<String>| {
+                pattern
+                    .as_deref()
+                    .map(regex::Regex::new)
+                    .transpose()
+                    .map_err(|e| anyhow::anyhow!("Invalid {flag} pattern: {e}"))
+            };
+            let match_body = body_regex("--match-body", &args.match_body)?;
+            let filter_body = body_regex("--filter-body", &args.filter_body)?;
+            if match_body.is_some() || filter_body.is_some() {
+                status_checker.with_body_match(match_body, filter_body);
+                verbose_print(&args, "Filtering URLs by response body");
+            }
+
+            testers.push((TesterRole::Status, Box::new(status_checker)));
         }
 
         if args.extract_links {
@@ -1093,9 +2402,76 @@ async fn main() -> Result<()> {
 
             let mut link_extractor = LinkExtractor::new();
             apply_network_settings_to_tester(&mut link_extractor, &network_settings);
-            testers.push(Box::new(link_extractor));
+            testers.push((TesterRole::Links, Box::new(link_extractor)));
+        }
+
+        if args.extract_title {
+            verbose_print(&args, "Extracting page titles");
+
+            let mut title_extractor = TitleExtractor::new();
+            apply_network_settings_to_tester(&mut title_extractor, &network_settings);
+            testers.push((TesterRole::Title, Box::new(title_extractor)));
+        }
+
+        if args.tls_info {
+            verbose_print(&args, "Inspecting TLS certificates of HTTPS hosts");
+
+            let mut tls_inspector = TlsInspector::new();
+            apply_network_settings_to_tester(&mut tls_inspector, &network_settings);
+            testers.push((TesterRole::Tls, Box::new(tls_inspector)));
+        }
+
+        if args.extract_sourcemaps {
+            verbose_print(&args, "Mining JavaScript source maps");
+
+            let mut sourcemap_extractor = SourceMapExtractor::new();
+            apply_network_settings_to_tester(&mut sourcemap_extractor, &network_settings);
+            testers.push((TesterRole::SourceMap, Box::new(sourcemap_extractor)));
+        }
+
+        if args.extract_js_endpoints {
+            verbose_print(&args, "Extracting endpoints from JavaScript files");
+
+            let mut js_endpoint_extractor = JsEndpointExtractor::new();
+            apply_network_settings_to_tester(&mut js_endpoint_extractor, &network_settings);
+            testers.push((TesterRole::JsEndpoints, Box::new(js_endpoint_extractor)));
         }
 
+        if args.check_open_redirect {
+            verbose_print(&args, "Checking redirect parameters for open redirects");
+
+            let mut open_redirect_tester = OpenRedirectTester::new();
+            apply_network_settings_to_tester(&mut open_redirect_tester, &network_settings);
+            testers.push((TesterRole::OpenRedirect, Box::new(open_redirect_tester)));
+        }
+
+        if args.check_reflection {
+            verbose_print(&args, "Checking query parameters for reflection");
+
+            let mut reflection_tester = ReflectionTester::new();
+            apply_network_settings_to_tester(&mut reflection_tester, &network_settings);
+            testers.push((TesterRole::Reflection, Box::new(reflection_tester)));
+        }
+
+        if let Some(scanner) = &secret_scanner {
+            for (_, tester) in testers.iter_mut() {
+                tester.with_secret_scanner(scanner.clone());
+            }
+        }
+
+        // URLs the testers discover pass the same filters as provider URLs,
+        // and the same host validation unless --include-external
+        let mut discovery_scope = DiscoveryScope::new(build_url_filter(&args));
+        if args.strict_enabled() && !args.include_external {
+            let domains = if args.files.is_empty() {
+                strict_scope_domains(&args)?
+            } else {
+                Vec::new()
+            };
+            discovery_scope.with_strict_hosts(&domains, args.subs, args.ignore_port);
+        }
+        discovery_scope.with_excluded_hosts(&args.exclude_hosts);
+
         // Process URLs with testers
         process_urls_with_testers(
             transformed_urls,
@@ -1103,6 +2479,8 @@ async fn main() -> Result<()> {
             &progress_manager,
             testers,
             should_check_status,
+            &network_settings,
+            Some(discovery_scope),
         )
         .await
     } else {
@@ -1127,18 +2505,92 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Provider metadata (e.g. VirusTotal analysis stats) is cheap to carry
+    // and only rendered by the JSON formatter, so attach it unconditionally.
+    // URLs rewritten by --normalize-url miss the exact lookup, so fall back to
+    // metadata keyed by the normalized form (built on the first miss).
+    if !run_result.metadata.is_empty() {
+        let mut by_normalized: Option<std::collections::HashMap<String, &runner::UrlMetadata>> =
+            None;
+        for entry in final_urls.iter_mut() {
+            let metadata = run_result.metadata.get(&entry.url).or_else(|| {
+                by_normalized
+                    .get_or_insert_with(|| {
+                        let mut map = std::collections::HashMap::new();
+                        for (url, metadata) in &run_result.metadata {
+                            map.entry(utils::normalize_url(url)).or_insert(metadata);
+                        }
+                        map
+                    })
+                    .get(&utils::normalize_url(&entry.url))
+                    .copied()
+            });
+            if let Some(metadata) = metadata {
+                // Keep fields testers already set (e.g. reflection tags)
+                for (key, value) in metadata {
+                    entry
+                        .metadata
+                        .entry(key.clone())
+                        .or_insert_with(|| value.clone());
+                }
+            }
+        }
+    }
+
+    if !target_tags.is_empty() {
+        target_tags.apply(&mut final_urls, args.subs, args.ignore_port);
+    }
+
+    if let Some(previous) = diff_baseline {
+        let (changed, summary) = output::diff_entries(final_urls, previous);
+        final_urls = changed;
+        verbose_print(
+            &args,
+            format!(
+                "Diff: {} new, {} removed, {} unchanged",
+                summary.new, summary.removed, summary.unchanged
+            ),
+        );
+    }
+
     // Progress is transient: tear down the live region (header + all bars) now
     // that scanning is done, so the only thing left on screen is the result —
     // the URL list printed below.
     progress_manager.clear();
 
-    match outputter.output(&final_urls, args.output.clone(), args.silent) {
+    let written = match args.output.as_deref().filter(|_| args.append) {
+        // --count replaces the URL list; validate_count_output rejects an
+        // output file alongside it
+        _ if args.count || args.count_by.is_some() => {
+            if !args.silent {
+                print!(
+                    "{}",
+                    output::render_counts(&final_urls, args.count_by.as_deref())
+                );
+            }
+            Ok(())
+        }
+        Some(path) => output::append_output(outputter.as_ref(), &args.format, &final_urls, path)
+            .map(|added| {
+                verbose_print(
+                    &args,
+                    format!("Appended {added} new URL(s) to {}", path.display()),
+                )
+            }),
+        None => outputter.output(&final_urls, args.output.clone(), args.silent),
+    };
+    match written {
         Ok(_) => {
-            if args.verbose && !args.silent {
+            if let Some(uploader) = &s3_output {
+                upload_s3_output(&args, uploader).await;
+            } else if args.verbose && !args.silent {
                 if let Some(path) = &args.output {
                     println!("Results written to: {}", path.display());
                 }
             }
+            if let Some(key) = &signing_key {
+                sign_output_file(&args, key);
+            }
         }
         Err(e) => {
             if !args.silent {
@@ -1148,7 +2600,17 @@ async fn main() -> Result<()> {
     }
 
     if let Some(dir) = args.output_dir.clone() {
-        if let Err(e) = write_per_domain_output(&final_urls, &dir, &args.format, args.silent) {
+        let ext = match args.format_template {
+            Some(_) => "txt",
+            None => output_dir_extension(&args.format),
+        };
+        let ext = match args.compress {
+            Some(compression) => format!("{ext}.{}", compression.extension()),
+            None => ext.to_string(),
+        };
+        if let Err(e) =
+            write_per_domain_output(&final_urls, &dir, outputter.as_ref(), &ext, args.silent)
+        {
             if !args.silent {
                 eprintln!("Error writing per-domain output to {}: {e}", dir.display());
             }
@@ -1157,20 +2619,551 @@ async fn main() -> Result<()> {
         }
     }
 
-    if args.stats && !args.silent {
-        print_provider_stats(&run_result.stats);
+    if let Some(path) = args.output.as_ref().filter(|_| args.split_output_by_tag) {
+        match output::write_split_by_tag(&final_urls, path, outputter.as_ref(), args.silent) {
+            Ok(written) => {
+                if args.verbose && !args.silent {
+                    for file in &written {
+                        println!("Tagged results written to: {}", file.display());
+                    }
+                }
+            }
+            Err(e) => {
+                if !args.silent {
+                    eprintln!("Error writing per-tag output: {e}");
+                }
+            }
+        }
+    }
+
+    if let Some(sink) = &es_sink {
+        match sink.index(&final_urls).await {
+            Ok(report) => {
+                verbose_print(
+                    &args,
+                    format!(
+                        "Indexed {} URL(s) into Elasticsearch index {}",
+                        report.indexed, args.es_index
+                    ),
+                );
+                if report.failed > 0 && !args.silent {
+                    eprintln!(
+                        "Warning: Elasticsearch rejected {} of {} document(s)",
+                        report.failed,
+                        final_urls.len()
+                    );
+                }
+            }
+            Err(e) => {
+                if !args.silent {
+                    eprintln!("Error indexing into Elasticsearch: {e:#}");
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &args.hosts_output {
+        if let Err(e) = output::write_hosts_csv(&final_urls, path) {
+            if !args.silent {
+                eprintln!("Error writing host summary to {}: {e}", path.display());
+            }
+        } else if args.verbose && !args.silent {
+            println!("Host summary written to: {}", path.display());
+        }
+    }
+
+    if let Some(scanner) = &secret_scanner {
+        let findings = scanner.findings();
+        if !args.silent {
+            print_secret_findings(&findings);
+        }
+        if let Some(path) = &args.secrets_output {
+            if let Err(e) = output::write_secrets_json(&findings, path) {
+                if !args.silent {
+                    eprintln!("Error writing secret findings to {}: {e}", path.display());
+                }
+            } else if args.verbose && !args.silent {
+                println!("Secret findings written to: {}", path.display());
+            }
+        }
+    }
+
+    if wants_run_summary(&args) {
+        let cache = metrics::cache_lookups()
+            .filter(|&(hits, misses)| hits + misses > 0)
+            .map(|(hits, misses)| runner::CacheSummary { hits, misses });
+        let summary = runner::RunSummary::new(
+            &run_result.stats,
+            run_result.urls.len(),
+            after_filters,
+            &final_urls,
+            cache,
+            started.elapsed(),
+        );
+        if let Some(path) = &args.stats_json {
+            let written = serde_json::to_string_pretty(&summary)
+                .map_err(anyhow::Error::from)
+                .and_then(|json| Ok(std::fs::write(path, json + "\n")?));
+            if let Err(e) = written {
+                if !args.silent {
+                    eprintln!("Error writing run summary to {}: {e}", path.display());
+                }
+            }
+        }
+        if let Some(path) = &args.stats_markdown {
+            let mut markdown = summary.render_markdown();
+            if should_check_status {
+                markdown.push_str(&output::protected_endpoints_markdown(
+                    &output::protected_prefixes(&final_urls),
+                ));
+            }
+            if let Err(e) = std::fs::write(path, markdown) {
+                if !args.silent {
+                    eprintln!("Error writing run summary to {}: {e}", path.display());
+                }
+            }
+        }
+        if args.stats && !args.silent {
+            eprint!("{}", summary.render_text());
+        }
+    }
+
+    if args.stats && !args.silent && should_check_status {
+        print_protected_endpoints(&output::protected_prefixes(&final_urls));
+    }
+
+    if args.incremental {
+        let code =
+            incremental_exit_code(final_urls.len(), args.fail_on_new, args.fail_on_unchanged);
+        verbose_print(
+            &args,
+            format!(
+                "Incremental run found {} new URL(s); exiting with status {code}",
+                final_urls.len()
+            ),
+        );
+        if code != 0 {
+            // process::exit skips destructors, so flush buffered output first
+            std::io::Write::flush(&mut std::io::stdout())?;
+            std::process::exit(code);
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether any of `--stats`, `--stats-json` or `--stats-markdown` asks for the
+/// end-of-run summary.
+fn wants_run_summary(args: &Args) -> bool {
+    args.stats || args.stats_json.is_some() || args.stats_markdown.is_some()
+}
+
+/// Exit status an `--incremental` run uses to signal whether the attack
+/// surface changed. Distinct from 1, which anyhow uses for errors.
+const EXIT_INCREMENTAL_SIGNAL: i32 = 3;
+
+/// Exit status for an `--incremental` run that output `new_urls` URLs after
+/// filtering: [`EXIT_INCREMENTAL_SIGNAL`] when something is new under
+/// `--fail-on-new` or nothing is under `--fail-on-unchanged`, 0 otherwise.
+fn incremental_exit_code(new_urls: usize, fail_on_new: bool, fail_on_unchanged: bool) -> i32 {
+    let signal = if new_urls > 0 {
+        fail_on_new
+    } else {
+        fail_on_unchanged
+    };
+    if signal {
+        EXIT_INCREMENTAL_SIGNAL
+    } else {
+        0
+    }
+}
+
+/// Follow the certificate-transparency stream for the target domains, printing
+/// (and optionally appending to `--output`) each newly seen URL as it arrives.
+/// Each record is formatted on its own line — JSON becomes one object per line —
+/// since there is no "end" at which to close a document. Runs until Ctrl-C.
+async fn run_watch_ct(args: &Args) -> Result<()> {
+    use std::io::Write;
+
+    let domains = collect_domains(args)?;
+    if domains.is_empty() {
+        if !args.silent {
+            eprintln!("No domains provided for --watch-ct. Pass DOMAINS positionally, use --domain-list FILE, or pipe them through stdin.");
+        }
+        return Ok(());
+    }
+
+    let mut file = match &args.output {
+        Some(path) => Some(output::OutputFile::append(path)?),
+        None => None,
+    };
+
+    if !args.silent {
+        eprintln!(
+            "[urx] watching {} for certificates covering {} domain(s); press Ctrl-C to stop",
+            args.ct_stream_url,
+            domains.len()
+        );
+    }
+
+    let mut watcher = CertstreamProvider::new(args.ct_stream_url.clone());
+    if !args.silent {
+        watcher.with_retry_notifier(std::sync::Arc::new(|error, attempt, wait| {
+            eprintln!(
+                "[urx] certstream: {error:#}; reconnect attempt {attempt} in {}s",
+                wait.as_secs()
+            );
+        }));
+    }
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let watch = watcher.watch(&domains, tx);
+    tokio::pin!(watch);
+
+    let outputter = build_outputter(args)?;
+    let result = loop {
+        tokio::select! {
+            Some(url) = rx.recv() => {
+                metrics::record_urls("Certstream", 1);
+                metrics::record_emitted(1);
+                let line = outputter.format(&output::UrlData::new(url), true);
+                if let Some(f) = file.as_mut() {
+                    f.write_all(console::strip_ansi_codes(&line).as_bytes())?;
+                    f.flush()?;
+                }
+                if !args.silent {
+                    print!("{line}");
+                    std::io::stdout().flush()?;
+                }
+            }
+            result = &mut watch => break result,
+            _ = utils::interrupt::interrupted() => break Ok(()),
+        }
+    };
+    if let Some(f) = file {
+        f.finish()?;
+    }
+    result
+}
+
+/// Write the `--sign-output` manifest and signature for the finished
+/// `--output` file. Failures are reported but don't fail the run, matching
+/// how output write errors are handled.
+fn sign_output_file(args: &Args, key: &ring::signature::Ed25519KeyPair) {
+    let Some(path) = &args.output else {
+        return;
+    };
+    match output::sign_output(path, key) {
+        Ok(artifacts) => verbose_print(
+            args,
+            format!(
+                "Signed output: {} ({})",
+                artifacts.manifest.display(),
+                artifacts.signature.display()
+            ),
+        ),
+        Err(e) => {
+            if !args.silent {
+                eprintln!("Error signing output: {e}");
+            }
+        }
+    }
+}
+
+/// `--stream`: fetch every (domain, provider) pair, printing each batch's new
+/// URLs as soon as it lands. Duplicates are dropped with a bloom filter sized
+/// by `--bloom-capacity` / `--bloom-fp-rate`, so memory stays constant no
+/// matter how many URLs flow through. Like watch mode, JSON is one object per
+/// line. Filters, `--since`, and strict host validation still apply per URL.
+async fn run_stream(args: &Args, network_settings: &NetworkSettings) -> Result<()> {
+    use futures::StreamExt;
+    use std::io::Write;
+
+    let domains = collect_domains(args)?;
+    if domains.is_empty() {
+        if !args.silent {
+            eprintln!("No domains provided for --stream. Pass DOMAINS positionally, use --domain-list FILE, or pipe them through stdin.");
+        }
+        return Ok(());
+    }
+    let ProviderList {
+        providers,
+        names: provider_names,
+        ..
+    } = initialize_providers(args, network_settings)?;
+
+    let mut file = match &args.output {
+        Some(path) => Some(output::OutputFile::create(path)?),
+        None => None,
+    };
+
+    let url_filter = build_url_filter(args);
+    let since = args.since.as_deref().and_then(filters::parse_since);
+    let (seen_after, seen_before) = seen_window(args);
+    let strict = args.strict_enabled();
+    let mut seen = utils::BloomFilter::with_rate(args.bloom_capacity, args.bloom_fp_rate);
+    // Only populated for --stream-exact: URLs the filter called duplicates,
+    // to be checked against what was actually written once the run ends.
+    let mut suspects: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut suspects_capped = false;
+
+    verbose_print(
+        args,
+        format!(
+            "Streaming {} domain(s) × {} provider(s); bloom filter {} KiB for {} URLs at {} false-positive rate",
+            domains.len(),
+            provider_names.len(),
+            seen.size_bytes() / 1024,
+            args.bloom_capacity,
+            args.bloom_fp_rate
+        ),
+    );
+
+    // Each (domain, provider) fetch is a stream of pages, so paginating
+    // providers feed the filter and output below page by page instead of
+    // after their whole result set has downloaded.
+    let jobs = domains.iter().flat_map(|domain| {
+        providers
+            .iter()
+            .zip(&provider_names)
+            .map(move |(provider, name)| {
+                provider
+                    .fetch_records_stream(domain, None)
+                    .map(move |page| (domain, name, page))
+            })
+    });
+    let mut batches =
+        futures::stream::iter(jobs).flatten_unordered(args.parallel.unwrap_or(5) as usize);
+
+    let deadline = async {
+        if args.max_time > 0 {
+            tokio::time::sleep(std::time::Duration::from_secs(args.max_time)).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+    tokio::pin!(deadline);
+    let budget_spent = async {
+        match &network_settings.request_gate {
+            Some(gate) => gate.exhausted().await,
+            None => std::future::pending::<()>().await,
+        }
+    };
+    tokio::pin!(budget_spent);
+
+    let outputter = build_outputter(args)?;
+    let mut emitted = 0usize;
+    loop {
+        let (domain, name, result) = tokio::select! {
+            next = batches.next() => match next {
+                Some(batch) => batch,
+                None => break,
+            },
+            _ = &mut deadline => {
+                if !args.silent {
+                    eprintln!("[urx] --max-time reached; stopping stream");
+                }
+                break;
+            }
+            _ = &mut budget_spent => {
+                if !args.silent {
+                    eprintln!("[urx] --max-requests budget spent; stopping stream");
+                }
+                break;
+            }
+            _ = utils::interrupt::interrupted() => {
+                if !args.silent {
+                    eprintln!("[urx] interrupted (Ctrl-C); stopping stream — press Ctrl-C again to force quit");
+                }
+                break;
+            }
+        };
+        let records = match result {
+            Ok(records) => records,
+            Err(e) => {
+                metrics::record_provider_error(name);
+                if !args.silent {
+                    eprintln!("[urx] {name} failed for {domain}: {e}");
+                }
+                continue;
+            }
+        };
+        metrics::record_urls(name, records.len());
+
+        let mut validator = if strict {
+            HostValidator::new(std::slice::from_ref(domain), args.subs)
+        } else {
+            HostValidator::any_host()
+        };
+        validator
+            .with_ignore_port(args.ignore_port)
+            .with_excluded_hosts(&args.exclude_hosts);
+        let mut batch = std::collections::HashSet::new();
+        let mut batch_metadata = std::collections::HashMap::new();
+        for record in records {
+            let fresh = since
+                .as_deref()
+                .is_none_or(|since| filters::is_modified_since(Some(&record.metadata), since))
+                && filters::is_seen_within(
+                    Some(&record.metadata),
+                    seen_after.as_deref(),
+                    seen_before.as_deref(),
+                );
+            if fresh && validator.is_valid_host(&record.url) {
+                if !record.metadata.is_empty() {
+                    batch_metadata.insert(record.url.clone(), record.metadata);
+                }
+                batch.insert(record.url);
+            }
+        }
+
+        for url in url_filter.apply_filters(&batch) {
+            if !seen.insert(&url) {
+                if args.stream_exact {
+                    if suspects.len() < STREAM_EXACT_MAX_SUSPECTS {
+                        suspects.insert(url);
+                    } else if !suspects_capped {
+                        suspects_capped = true;
+                        if !args.silent {
+                            eprintln!("[urx] --stream-exact is tracking {STREAM_EXACT_MAX_SUSPECTS} suspected duplicates; later ones won't be rechecked");
+                        }
+                    }
+                }
+                continue;
+            }
+            // Only the first provider to report a URL is known here; later
+            // sightings are dropped by the dedup filter.
+            let mut entry = output::UrlData::new(url);
+            if args.show_sources {
+                entry.sources = vec![name.to_string()];
+            }
+            if let Some(metadata) = batch_metadata.remove(&entry.url) {
+                entry.metadata = metadata;
+            }
+            let line = outputter.format(&entry, true);
+            if let Some(f) = file.as_mut() {
+                f.write_all(console::strip_ansi_codes(&line).as_bytes())?;
+            }
+            if !args.silent {
+                print!("{line}");
+            }
+            emitted += 1;
+            metrics::record_emitted(1);
+        }
+        if let Some(f) = file.as_mut() {
+            f.flush()?;
+        }
+        std::io::stdout().flush()?;
+        if args.max_urls.is_some_and(|max| emitted >= max) {
+            if !args.silent {
+                eprintln!("[urx] --max-urls reached; stopping stream");
+            }
+            break;
+        }
+    }
+    drop(batches);
+
+    if args.stream_exact {
+        let recovered = recover_bloom_false_positives(args, suspects)?;
+        for url in &recovered {
+            let line = outputter.format(&output::UrlData::new(url.clone()), true);
+            if let Some(f) = file.as_mut() {
+                f.write_all(console::strip_ansi_codes(&line).as_bytes())?;
+            }
+            if !args.silent {
+                print!("{line}");
+            }
+        }
+        emitted += recovered.len();
+    }
+    if let Some(f) = file {
+        f.finish()?;
     }
 
+    verbose_print(args, format!("Streamed {emitted} unique URL(s)"));
     Ok(())
 }
 
+/// Most URLs `--stream-exact` holds for its post-pass, so the exact pass can't
+/// undo the bloom filter's memory bound on a duplicate-heavy stream.
+const STREAM_EXACT_MAX_SUSPECTS: usize = 1_000_000;
+
+/// `--stream-exact` post-pass: every URL the bloom filter rejected is either a
+/// real duplicate (already in the output file) or a false positive (never
+/// written). Re-read the file line by line and return the false positives,
+/// sorted. Needs plain `--output` to have something to read back.
+fn recover_bloom_false_positives(
+    args: &Args,
+    suspects: std::collections::HashSet<String>,
+) -> Result<Vec<String>> {
+    use std::io::BufRead;
+
+    let plain = args.format_template.is_none()
+        && !matches!(
+            args.format.to_lowercase().as_str(),
+            "json" | "csv" | "html" | "sitemap" | "burp" | "tree" | "dot" | "parquet"
+        );
+    let Some(path) = args
+        .output
+        .as_ref()
+        .filter(|path| plain && output::Compression::from_path(path).is_none())
+    else {
+        if !args.silent {
+            eprintln!("[urx] --stream-exact needs an uncompressed --output with the plain format; skipping the exact pass");
+        }
+        return Ok(Vec::new());
+    };
+
+    // Lines carry the escaped URL plus any tags (`[200]`, `[sources]`), so
+    // match on that URL form and keep the bare URL alongside to return.
+    let mut pending: std::collections::HashMap<String, String> = suspects
+        .into_iter()
+        .map(|url| (output::plain_escape(&url).into_owned(), url))
+        .collect();
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    for line in reader.lines() {
+        let line = line?;
+        pending.remove(output::plain_line_url(&line));
+        if pending.is_empty() {
+            break;
+        }
+    }
+    let mut recovered: Vec<String> = pending.into_values().collect();
+    recovered.sort();
+    verbose_print(
+        args,
+        format!(
+            "Exact pass recovered {} URL(s) dropped by the bloom filter",
+            recovered.len()
+        ),
+    );
+    Ok(recovered)
+}
+
+/// The outputter for `--format`, or a plain one shaped by
+/// `--format-template` when that is set.
+fn build_outputter(args: &Args) -> Result<Box<dyn output::Outputter>> {
+    match &args.format_template {
+        Some(template) => output::create_template_outputter(template),
+        None => match &args.csv_columns {
+            Some(columns) if args.format.eq_ignore_ascii_case("csv") => {
+                Ok(output::create_csv_outputter(columns.clone()))
+            }
+            _ => Ok(create_outputter(&args.format)),
+        },
+    }
+}
+
 /// Best-effort filename extension matching `--format`. Anything other than
-/// json/csv falls back to `.txt`, mirroring how `create_outputter` treats
-/// unknown formats as plain text.
+/// json/csv/html/sitemap/burp/dot/parquet falls back to `.txt`, mirroring how
+/// `create_outputter` treats unknown formats as plain text.
 fn output_dir_extension(format: &str) -> &'static str {
     match format.to_lowercase().as_str() {
         "json" => "json",
         "csv" => "csv",
+        "html" => "html",
+        "sitemap" | "burp" => "xml",
+        "dot" => "dot",
+        "parquet" => "parquet",
         _ => "txt",
     }
 }
@@ -1181,7 +3174,8 @@ fn output_dir_extension(format: &str) -> &'static str {
 fn write_per_domain_output(
     urls: &[output::UrlData],
     dir: &std::path::Path,
-    format: &str,
+    outputter: &dyn output::Outputter,
+    ext: &str,
     silent: bool,
 ) -> anyhow::Result<()> {
     if !dir.exists() {
@@ -1198,9 +3192,6 @@ fn write_per_domain_output(
         grouped.entry(host).or_default().push(entry.clone());
     }
 
-    let outputter = output::create_outputter(format);
-    let ext = output_dir_extension(format);
-
     for (host, entries) in &grouped {
         let file_name = format!("{host}.{ext}");
         let path = dir.join(file_name);
@@ -1253,33 +3244,40 @@ fn render_header(n_domains: usize, n_providers: usize) -> String {
     )
 }
 
-/// Render the per-provider summary table to stderr (so it doesn't pollute
-/// stdout when callers pipe URL results into other tools).
-fn print_provider_stats(stats: &[runner::ProviderStats]) {
-    if stats.is_empty() {
+/// Render the "protected endpoints" section of `--stats`: path prefixes whose
+/// status-checked URLs all answered 401/403, i.e. likely auth boundaries.
+/// Goes to stderr alongside the provider table.
+fn print_protected_endpoints(prefixes: &[output::ProtectedPrefix]) {
+    if prefixes.is_empty() {
+        return;
+    }
+    eprintln!();
+    eprintln!("Protected endpoints (consistently 401/403):");
+    eprintln!("  {:>5}  {:>5}  {:>5}  prefix", "urls", "401", "403");
+    eprintln!("  {:>5}  {:>5}  {:>5}  ------", "-----", "-----", "-----");
+    for p in prefixes {
+        eprintln!(
+            "  {:>5}  {:>5}  {:>5}  {}",
+            p.total(),
+            p.unauthorized,
+            p.forbidden,
+            p.prefix
+        );
+    }
+}
+
+/// Render the `--scan-secrets` section: one line per finding with the rule,
+/// the masked secret and the URL it came from. Goes to stderr so stdout stays
+/// a clean URL list.
+fn print_secret_findings(findings: &[SecretFinding]) {
+    if findings.is_empty() {
         return;
     }
     eprintln!();
-    eprintln!("Provider stats:");
-    eprintln!(
-        "  {:<18}  {:>8}  {:>8}  {:>7}  {:>10}",
-        "provider", "urls", "partial", "errors", "elapsed"
-    );
-    eprintln!(
-        "  {:<18}  {:>8}  {:>8}  {:>7}  {:>10}",
-        "------------------", "--------", "--------", "-------", "----------"
-    );
-    for s in stats {
-        let elapsed_ms = s.elapsed.as_millis();
-        let elapsed_label = if elapsed_ms >= 1000 {
-            format!("{:.2}s", s.elapsed.as_secs_f64())
-        } else {
-            format!("{}ms", elapsed_ms)
-        };
-        eprintln!(
-            "  {:<18}  {:>8}  {:>8}  {:>7}  {:>10}",
-            s.name, s.url_count, s.partial_count, s.error_count, elapsed_label
-        );
+    eprintln!("Secrets found in responses:");
+    let width = findings.iter().map(|f| f.rule.len()).max().unwrap_or(0);
+    for f in findings {
+        eprintln!("  {:<width$}  {:<20}  {}", f.rule, f.masked(), f.url);
     }
 }
 
@@ -1450,12 +3448,81 @@ mod tests {
             None => env::remove_var("URX_URLSCAN_API_KEY"),
         }
 
-        let (providers, names) = result.expect("urlscan should initialize without an API key");
+        let list = result.expect("urlscan should initialize without an API key");
         assert!(
-            !providers.is_empty(),
+            !list.providers.is_empty(),
             "urlscan must be instantiated even without a key"
         );
-        assert!(names.iter().any(|n| n == "Urlscan"));
+        assert!(list.names.iter().any(|n| n == "Urlscan"));
+        assert_eq!(list.ids, vec!["urlscan".to_string()]);
+    }
+
+    #[test]
+    fn test_custom_providers_from_config_join_the_provider_list() {
+        let config: Config = toml::from_str(
+            r#"
+[[provider.custom]]
+id = "intel"
+name = "Intel API"
+url = "https://intel.example.com/api?host={domain}"
+json_path = "results.url"
+"#,
+        )
+        .unwrap();
+        let mut args = build_test_args();
+        args.providers = vec!["intel".to_string()];
+        config.apply_to_args(&mut args);
+        validate_custom_providers(&args).unwrap();
+
+        let list = initialize_providers(&args, &NetworkSettings::default()).unwrap();
+        assert_eq!(list.ids, vec!["intel".to_string()]);
+        assert_eq!(list.names, vec!["Intel API".to_string()]);
+
+        args.custom_providers[0].id = "wayback".to_string();
+        assert!(validate_custom_providers(&args).is_err());
+
+        let config: Config = toml::from_str(
+            r#"
+[[provider.plugin]]
+id = "intel"
+command = "intel-urls"
+args = ["{domain}"]
+"#,
+        )
+        .unwrap();
+        args.custom_providers[0].id = "intel".to_string();
+        args.plugin_providers.clear();
+        config.apply_to_args(&mut args);
+        assert_eq!(args.plugin_providers[0].command, "intel-urls");
+        assert!(validate_custom_providers(&args).is_err());
+        args.plugin_providers[0].id = "gau".to_string();
+        args.providers = vec!["gau".to_string()];
+        validate_custom_providers(&args).unwrap();
+        let list = initialize_providers(&args, &NetworkSettings::default()).unwrap();
+        assert_eq!(list.ids, vec!["gau".to_string()]);
+    }
+
+    #[test]
+    fn test_exclude_providers_composes_with_auto_enabled_keys() {
+        let _env_lock = env_mutex().lock().unwrap();
+        let saved = env::var("URX_VT_API_KEY").ok();
+        env::remove_var("URX_VT_API_KEY");
+
+        let mut args = build_test_args();
+        args.providers = vec!["wayback".into(), "cc".into(), "otx".into()];
+        args.vt_api_key = vec!["key".to_string()];
+        args.zoomeye_api_key = vec!["key".to_string()];
+        args.exclude_providers = vec!["cc".to_string(), "vt".to_string()];
+        let ids = effective_provider_ids(&args);
+
+        if let Some(val) = saved {
+            env::set_var("URX_VT_API_KEY", val);
+        }
+
+        // The keyed zoomeye is still auto-enabled; the excluded vt is not.
+        assert!(ids.iter().any(|p| p == "zoomeye"));
+        assert!(ids.iter().any(|p| p == "wayback"));
+        assert!(!ids.iter().any(|p| p == "vt" || p == "cc"));
     }
 
     #[test]
@@ -1676,7 +3743,7 @@ mod tests {
         args.include_sitemap = false;
         args.exclude_sitemap = true;
 
-        let key = create_cache_key("example.com", &args);
+        let key = create_cache_key("example.com", &effective_provider_ids(&args), &args);
 
         assert_eq!(key.providers, vec!["robots", "vt", "wayback"]);
 
@@ -1693,6 +3760,13 @@ mod tests {
         should_fail: bool,
         delay_ms: u64,
         calls: Arc<Mutex<Vec<String>>>,
+        /// When set, a cancelled run cuts the delay short and still returns
+        /// `urls`, like a paginating provider handing back its pages so far.
+        honours_cancel: bool,
+        cancel: tokio_util::sync::CancellationToken,
+        /// Fail this many calls before succeeding, like a flaky upstream.
+        fail_first: usize,
+        quota: Option<network::QuotaMeter>,
     }
 
     impl MockProvider {
@@ -1702,13 +3776,27 @@ mod tests {
                 should_fail,
                 delay_ms: 0,
                 calls: Arc::new(Mutex::new(vec![])),
+                honours_cancel: false,
+                cancel: tokio_util::sync::CancellationToken::new(),
+                fail_first: 0,
+                quota: None,
             }
         }
 
+        fn failing_first(mut self, calls: usize) -> Self {
+            self.fail_first = calls;
+            self
+        }
+
         fn with_delay_ms(mut self, ms: u64) -> Self {
             self.delay_ms = ms;
             self
         }
+
+        fn honouring_cancel(mut self) -> Self {
+            self.honours_cancel = true;
+            self
+        }
     }
 
     impl Provider for MockProvider {
@@ -1725,15 +3813,27 @@ mod tests {
             let calls = self.calls.clone();
 
             let delay = self.delay_ms;
+            let cancel = self.honours_cancel.then(|| self.cancel.clone());
+            let fail_first = self.fail_first;
             Box::pin(async move {
                 // Record the call
-                calls.lock().unwrap().push(domain.to_string());
+                let call_no = {
+                    let mut calls = calls.lock().unwrap();
+                    calls.push(domain.to_string());
+                    calls.len()
+                };
 
                 if delay > 0 {
-                    tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                    let sleep = tokio::time::sleep(std::time::Duration::from_millis(delay));
+                    match cancel {
+                        Some(token) => {
+                            token.run_until_cancelled(sleep).await;
+                        }
+                        None => sleep.await,
+                    }
                 }
 
-                if should_fail {
+                if should_fail || call_no <= fail_first {
                     Err(anyhow::anyhow!("Mock provider failure"))
                 } else {
                     Ok(urls)
@@ -1749,6 +3849,17 @@ mod tests {
         fn with_random_agent(&mut self, _enabled: bool) {}
         fn with_insecure(&mut self, _enabled: bool) {}
         fn with_rate_limit(&mut self, _rate_limit: Option<f32>) {}
+        fn with_cancellation(&mut self, token: tokio_util::sync::CancellationToken) {
+            self.cancel = token;
+        }
+
+        fn with_quota(&mut self, meter: network::QuotaMeter) {
+            self.quota = Some(meter);
+        }
+
+        fn quota(&self) -> Option<&network::QuotaMeter> {
+            self.quota.as_ref()
+        }
     }
 
     // Mock StatusChecker for testing
@@ -1807,6 +3918,43 @@ mod tests {
         async fn exists(&self, _key: &CacheKey) -> Result<bool> {
             Err(anyhow::anyhow!("cache exists failed"))
         }
+
+        async fn provider_history(
+            &self,
+            _family: &str,
+        ) -> Result<std::collections::HashMap<String, cache::ProviderYield>> {
+            Err(anyhow::anyhow!("cache history failed"))
+        }
+
+        async fn record_provider_yield(
+            &self,
+            _family: &str,
+            _provider: &str,
+            _provider_yield: &cache::ProviderYield,
+        ) -> Result<()> {
+            Err(anyhow::anyhow!("cache history failed"))
+        }
+
+        async fn list_namespaces(&self) -> Result<Vec<(String, usize)>> {
+            Err(anyhow::anyhow!("cache listing failed"))
+        }
+
+        async fn delete_namespace(&self, _namespace: &str) -> Result<usize> {
+            Err(anyhow::anyhow!("cache deletion failed"))
+        }
+
+        async fn quota_usage(&self, _day: &str) -> Result<std::collections::HashMap<String, u64>> {
+            Err(anyhow::anyhow!("cache quota failed"))
+        }
+
+        async fn record_quota_usage(
+            &self,
+            _day: &str,
+            _provider: &str,
+            _requests: u64,
+        ) -> Result<()> {
+            Err(anyhow::anyhow!("cache quota failed"))
+        }
     }
 
     #[tokio::test]
@@ -1890,6 +4038,102 @@ mod tests {
             wayback_from: None,
             wayback_to: None,
             github_api_key: vec![],
+            watch_ct: false,
+            ct_stream_url: crate::providers::DEFAULT_CERTSTREAM_URL.to_string(),
+            urlscan_max_results: crate::providers::DEFAULT_URLSCAN_MAX_RESULTS,
+            since: None,
+            urlscan_submit: false,
+            urlscan_submit_max: crate::providers::DEFAULT_URLSCAN_SUBMIT_MAX,
+            ignore_port: false,
+            stream: false,
+            bloom_fp_rate: 0.001,
+            bloom_capacity: 10_000_000,
+            stream_exact: false,
+            retry_failed_domains: 0,
+            sign_output: None,
+            list_presets: false,
+            wayback_collapse: None,
+            wayback_filter: vec![],
+            wayback_match_type: None,
+            fail_on_new: false,
+            provider_opt: vec![],
+            check_reflection: false,
+            max_bandwidth: None,
+            explain_scope: false,
+            global_rate_limit: None,
+            max_requests: None,
+            max_urls: None,
+            smart_order: false,
+            circuit_breaker: 5,
+            cache_namespace: None,
+            list_cache_namespaces: false,
+            delete_cache_namespace: None,
+            provider_status_filter: vec![],
+            schema: false,
+            check_providers: false,
+            dry_run: false,
+            extract_sourcemaps: false,
+            max_body_bytes: 5_000_000,
+            max_urls_per_domain: None,
+            hosts_output: None,
+            provider_quota: vec![],
+            cache_ttl_by: vec![],
+            http_cache: false,
+            http_cache_path: None,
+            split_output_by_tag: false,
+            from: None,
+            to: None,
+            force_progress: false,
+            custom_providers: vec![],
+            per_category_limit: None,
+            plugin_providers: vec![],
+            metrics_addr: None,
+            robots_wildcards: false,
+            deterministic: false,
+            seed: 0,
+            extract_title: false,
+            tls_info: false,
+            check_open_redirect: false,
+            scan_secrets: false,
+            secrets_output: None,
+            extract_js_endpoints: false,
+            body_stats: false,
+            include_external: false,
+            respect_robots: false,
+            include_headers: vec![],
+            match_body: None,
+            filter_body: None,
+            http_version: None,
+            format_template: None,
+            compress: None,
+            append: false,
+            diff: None,
+            stats_json: None,
+            es_url: None,
+            es_index: "urx".to_string(),
+            csv_columns: None,
+            count: false,
+            count_by: None,
+            exclude_hosts: vec![],
+            custom_presets: Default::default(),
+            strip_params: vec![],
+            clean_params: false,
+            dedup_similar: false,
+            min_params: None,
+            max_params: None,
+            exclude_file: None,
+            exclude_regexes: vec![],
+            case_sensitive: false,
+            seen_after: None,
+            seen_before: None,
+            sample_per_path: None,
+            show_only_param_keys: false,
+            param_key_counts: false,
+            fuzz_params: false,
+            fuzz_path_last: false,
+            replace_values: None,
+            stats_markdown: None,
+            fail_on_unchanged: false,
         };
 
         let progress_manager = ProgressManager::new(true);
@@ -1901,6 +4145,7 @@ mod tests {
             &progress_manager,
             &providers,
             &provider_names,
+            None,
         )
         .await;
 
@@ -1950,6 +4195,7 @@ mod tests {
             &progress_manager,
             &providers,
             &provider_names,
+            None,
         )
         .await;
         let elapsed = start.elapsed();
@@ -1990,6 +4236,7 @@ mod tests {
             &progress_manager,
             &providers,
             &provider_names,
+            None,
         )
         .await;
         let elapsed = start.elapsed();
@@ -2021,6 +4268,7 @@ mod tests {
             &progress_manager,
             &providers,
             &provider_names,
+            None,
         )
         .await;
         let elapsed = started.elapsed();
@@ -2039,6 +4287,213 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_smart_order_with_max_urls_skips_later_providers() {
+        // Providers run one at a time under --smart-order + --max-urls, so
+        // once the first meets the limit the second is never queried.
+        let productive = MockProvider::new(
+            vec![
+                "https://example.com/a".to_string(),
+                "https://example.com/b".to_string(),
+            ],
+            false,
+        );
+        let unneeded = MockProvider::new(vec!["https://example.com/c".to_string()], false);
+        let unneeded_calls = unneeded.calls.clone();
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(productive), Box::new(unneeded)];
+        let provider_names = vec!["Productive".to_string(), "Unneeded".to_string()];
+
+        let mut args = build_test_args();
+        args.smart_order = true;
+        args.max_urls = Some(2);
+        let progress_manager = ProgressManager::new(true);
+
+        let result = process_domains(
+            vec!["example.com".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+
+        assert_eq!(result.urls.len(), 2);
+        assert!(unneeded_calls.lock().unwrap().is_empty());
+        assert_eq!(result.fetches.len(), 1);
+        assert_eq!(result.fetches[0].provider, "Productive");
+        assert_eq!(result.fetches[0].urls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_urls_per_domain_stops_slower_providers() {
+        // The fast provider fills the domain's quota, which ends the slow
+        // provider's fetch early instead of waiting out its delay.
+        let fast = MockProvider::new(
+            vec![
+                "https://example.com/a".to_string(),
+                "https://example.com/b".to_string(),
+            ],
+            false,
+        );
+        let slow = MockProvider::new(vec!["https://example.com/c".to_string()], false)
+            .with_delay_ms(10_000)
+            .honouring_cancel();
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(fast), Box::new(slow)];
+        let provider_names = vec!["Fast".to_string(), "Slow".to_string()];
+
+        let mut args = build_test_args();
+        args.max_urls_per_domain = Some(2);
+        let progress_manager = ProgressManager::new(true);
+
+        let started = std::time::Instant::now();
+        let result = process_domains(
+            vec!["example.com".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        assert!(result.urls.len() >= 2);
+        // The cut-short fetch stays out of the yield history.
+        assert_eq!(result.fetches.len(), 1);
+        assert_eq!(result.fetches[0].provider, "Fast");
+    }
+
+    #[tokio::test]
+    async fn test_spent_provider_quota_skips_its_domains() {
+        let mut keyed = MockProvider::new(vec!["https://example.com/a".to_string()], false);
+        let calls = keyed.calls.clone();
+        // Earlier runs today already used the whole quota.
+        keyed.with_quota(network::QuotaMeter::new("vt", Some(10), 10));
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(keyed)];
+        let provider_names = vec!["VirusTotal".to_string()];
+
+        let args = build_test_args();
+        let progress_manager = ProgressManager::new(true);
+        let result = process_domains(
+            vec!["example.com".to_string(), "example.org".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+
+        assert!(calls.lock().unwrap().is_empty());
+        assert!(result.urls.is_empty());
+        assert_eq!(result.stats[0].quota_skipped_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_circuit_breaker_skips_provider_after_consecutive_failures() {
+        let dead = MockProvider::new(vec![], true);
+        let calls = dead.calls.clone();
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(dead)];
+        let provider_names = vec!["DeadProvider".to_string()];
+
+        let mut args = build_test_args();
+        args.circuit_breaker = 2;
+        args.parallel = Some(1);
+        let progress_manager = ProgressManager::new(true);
+
+        let domains: Vec<String> = ["a.com", "b.com", "c.com", "d.com"]
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        let result = process_domains(
+            domains,
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+
+        assert_eq!(calls.lock().unwrap().len(), 2);
+        let stats = &result.stats[0];
+        assert!(stats.circuit_open);
+        assert_eq!(stats.error_count, 2);
+        assert_eq!(stats.skipped_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_failed_domains_requeues_failed_pairs() {
+        let flaky =
+            MockProvider::new(vec!["https://example.com/a".to_string()], false).failing_first(1);
+        let calls = flaky.calls.clone();
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(flaky)];
+        let provider_names = vec!["FlakyProvider".to_string()];
+        let progress_manager = ProgressManager::new(true);
+
+        // Without the flag the failure is final.
+        let args = build_test_args();
+        let result = process_domains(
+            vec!["example.com".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+        assert!(result.urls.is_empty());
+        assert_eq!(result.stats[0].error_count, 1);
+
+        // With it, the pair is re-queued and the second try succeeds.
+        calls.lock().unwrap().clear();
+        let mut args = build_test_args();
+        args.retry_failed_domains = 2;
+        let result = process_domains(
+            vec!["example.com".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+        assert!(result.urls.contains_key("https://example.com/a"));
+        assert_eq!(result.stats[0].error_count, 0);
+        assert_eq!(calls.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_max_time_keeps_partial_results_from_cancellable_provider() {
+        // A provider that listens to the cancellation token returns what it has
+        // once --max-time fires, instead of being aborted empty-handed.
+        let slow = MockProvider::new(vec!["https://example.com/partial".to_string()], false)
+            .with_delay_ms(5_000)
+            .honouring_cancel();
+
+        let providers: Vec<Box<dyn Provider>> = vec![Box::new(slow)];
+        let provider_names = vec!["SlowProvider".to_string()];
+
+        let mut args = build_test_args();
+        args.max_time = 1;
+        let progress_manager = ProgressManager::new(true);
+
+        let started = std::time::Instant::now();
+        let result = process_domains(
+            vec!["example.com".to_string()],
+            &args,
+            &progress_manager,
+            &providers,
+            &provider_names,
+            None,
+        )
+        .await;
+
+        assert!(started.elapsed().as_secs() < 3);
+        assert!(result.urls.contains_key("https://example.com/partial"));
+    }
+
     #[tokio::test]
     async fn test_zero_timeout_does_not_panic() {
         let provider = MockProvider::new(vec!["https://example.com/page1".to_string()], false)
@@ -2056,6 +4511,7 @@ mod tests {
             &progress_manager,
             &providers,
             &provider_names,
+            None,
         )
         .await;
 
@@ -2084,13 +4540,19 @@ mod tests {
         let args = build_test_args();
         let progress_manager = ProgressManager::new(true);
 
+        let provider_list = ProviderList {
+            providers,
+            names: provider_names,
+            ids: vec!["wayback".to_string()],
+        };
+
         let err = process_domains_with_cache(
             vec!["example.com".to_string()],
             &args,
             &progress_manager,
-            &providers,
-            &provider_names,
+            &provider_list,
             Some(&cache),
+            None,
         )
         .await
         .unwrap_err();
@@ -2098,11 +4560,74 @@ mod tests {
         assert!(err.to_string().contains("cache get failed"));
     }
 
+    #[tokio::test]
+    async fn test_cache_ttl_by_refetches_only_stale_providers() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let cache = CacheManager::new_sqlite(temp_dir.path().join("cache.db")).await?;
+        let archive = MockProvider::new(vec!["https://example.com/old".to_string()], false);
+        let robots = MockProvider::new(vec!["https://example.com/admin".to_string()], false);
+        let archive_calls = archive.calls.clone();
+        let robots_calls = robots.calls.clone();
+        let provider_list = ProviderList {
+            providers: vec![Box::new(archive), Box::new(robots)],
+            names: vec!["Wayback Machine".to_string(), "Robots.txt".to_string()],
+            ids: vec!["wayback".to_string(), "robots".to_string()],
+        };
+        let mut args = build_test_args();
+        args.cache_ttl_by = vec!["robots=1h".to_string()];
+        let progress_manager = ProgressManager::new(true);
+        let domains = vec!["example.com".to_string()];
+
+        let first = process_domains_with_cache(
+            domains.clone(),
+            &args,
+            &progress_manager,
+            &provider_list,
+            Some(&cache),
+            None,
+        )
+        .await?;
+        assert_eq!(first.urls.len(), 2);
+
+        // Age the robots slice past its 1h TTL; the archive slice is still
+        // inside the default 24h.
+        let robots_key = create_cache_key(
+            "example.com",
+            &["robots".to_string(), "Robots.txt".to_string()],
+            &args,
+        );
+        let mut entry = cache.get_cached_urls(&robots_key).await?.unwrap();
+        entry.timestamp = chrono::Utc::now() - chrono::Duration::hours(2);
+        cache.store_urls(&robots_key, &entry).await?;
+
+        let second = process_domains_with_cache(
+            domains,
+            &args,
+            &progress_manager,
+            &provider_list,
+            Some(&cache),
+            None,
+        )
+        .await?;
+
+        assert_eq!(archive_calls.lock().unwrap().len(), 1);
+        assert_eq!(robots_calls.lock().unwrap().len(), 2);
+        assert_eq!(second.urls.len(), 2);
+        assert!(second.urls["https://example.com/old"].contains("Wayback Machine"));
+        assert_eq!(second.stats.len(), 1);
+        assert_eq!(second.stats[0].name, "Robots.txt");
+        Ok(())
+    }
+
     #[test]
     fn test_output_dir_extension() {
         assert_eq!(output_dir_extension("json"), "json");
         assert_eq!(output_dir_extension("JSON"), "json");
         assert_eq!(output_dir_extension("csv"), "csv");
+        assert_eq!(output_dir_extension("HTML"), "html");
+        assert_eq!(output_dir_extension("sitemap"), "xml");
+        assert_eq!(output_dir_extension("burp"), "xml");
+        assert_eq!(output_dir_extension("dot"), "dot");
         assert_eq!(output_dir_extension("plain"), "txt");
         assert_eq!(output_dir_extension("anything-else"), "txt");
     }
@@ -2117,7 +4642,13 @@ mod tests {
             output::UrlData::new("not-a-url".to_string()),
         ];
 
-        write_per_domain_output(&urls, dir.path(), "plain", true)?;
+        write_per_domain_output(
+            &urls,
+            dir.path(),
+            &output::PlainOutputter::new(),
+            "txt",
+            true,
+        )?;
 
         let example = std::fs::read_to_string(dir.path().join("example.com.txt"))?;
         assert!(example.contains("https://example.com/a"));
@@ -2138,7 +4669,7 @@ mod tests {
         let nested = base.path().join("nested/output/dir");
         let urls = vec![output::UrlData::new("https://example.com/a".to_string())];
 
-        write_per_domain_output(&urls, &nested, "json", true)?;
+        write_per_domain_output(&urls, &nested, &output::JsonOutputter::new(), "json", true)?;
 
         assert!(nested.is_dir());
         let example = std::fs::read_to_string(nested.join("example.com.json"))?;
@@ -2166,6 +4697,144 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_incremental_exit_code() {
+        // Opt-in: a plain --incremental run always succeeds
+        assert_eq!(incremental_exit_code(5, false, false), 0);
+        assert_eq!(incremental_exit_code(0, false, false), 0);
+        assert_eq!(
+            incremental_exit_code(5, true, false),
+            EXIT_INCREMENTAL_SIGNAL
+        );
+        assert_eq!(incremental_exit_code(0, true, false), 0);
+        assert_eq!(incremental_exit_code(5, false, true), 0);
+        assert_eq!(
+            incremental_exit_code(0, false, true),
+            EXIT_INCREMENTAL_SIGNAL
+        );
+    }
+
+    #[test]
+    fn test_wants_run_summary_for_every_stats_flag() {
+        let mut args = build_test_args();
+        assert!(!wants_run_summary(&args));
+        // --stats-markdown alone still needs the metrics registry for its
+        // cache line
+        args.stats_markdown = Some(std::path::PathBuf::from("stats.md"));
+        assert!(wants_run_summary(&args));
+    }
+
+    #[test]
+    fn test_validate_date_filters_rejects_malformed_since() {
+        let mut args = build_test_args();
+        assert!(validate_date_filters(&args).is_ok());
+        args.since = Some("2024-06".to_string());
+        assert!(validate_date_filters(&args).is_ok());
+        args.since = Some("last week".to_string());
+        let err = validate_date_filters(&args).unwrap_err();
+        assert!(err.to_string().contains("--since"));
+
+        args.since = None;
+        args.seen_before = Some("yesterday".to_string());
+        let err = validate_date_filters(&args).unwrap_err();
+        assert!(err.to_string().contains("--seen-before"));
+    }
+
+    #[test]
+    fn test_validate_count_output_rejects_config_output() {
+        let mut args = build_test_args();
+        args.count = true;
+        assert!(validate_count_output(&args).is_ok());
+        args.output = Some(std::path::PathBuf::from("urls.txt"));
+        let err = validate_count_output(&args).unwrap_err();
+        assert!(err.to_string().contains("[output].output"));
+
+        args.count = false;
+        assert!(validate_count_output(&args).is_ok());
+        args.count_by = Some("host".to_string());
+        assert!(validate_count_output(&args).is_err());
+    }
+
+    #[test]
+    fn test_collect_tagged_domains_reads_hash_and_csv_tags() -> anyhow::Result<()> {
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(
+            file,
+            "domain,tag\nstaging.example.com,staging\nexample.com,eu"
+        )?;
+
+        let mut args = build_test_args();
+        args.domains = vec![
+            "Example.com#prod".to_string(),
+            "https://example.com/#section".to_string(),
+        ];
+        args.domain_list = vec![file.path().to_path_buf()];
+
+        let (domains, tags) = collect_tagged_domains(&args)?;
+        assert_eq!(domains, vec!["example.com", "staging.example.com"]);
+
+        let mut urls = vec![
+            output::UrlData::new("https://example.com/a".to_string()),
+            output::UrlData::new("https://staging.example.com/b".to_string()),
+        ];
+        tags.apply(&mut urls, false, false);
+        assert_eq!(
+            urls[0].metadata["target_tags"],
+            serde_json::json!(["eu", "prod"])
+        );
+        assert_eq!(
+            urls[1].metadata["target_tags"],
+            serde_json::json!(["staging"])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_collect_domains_encodes_and_skips_invalid_hosts() -> anyhow::Result<()> {
+        let mut args = build_test_args();
+        args.silent = true;
+        args.domains = vec![
+            "https://Bücher.de/shop".to_string(),
+            "bad host.com".to_string(),
+            "a..b.com".to_string(),
+            "my_host.example.com.".to_string(),
+        ];
+
+        let domains = collect_domains(&args)?;
+        assert_eq!(domains, vec!["xn--bcher-kva.de", "my_host.example.com"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recover_bloom_false_positives_reads_back_output() -> anyhow::Result<()> {
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new()?;
+        writeln!(
+            file,
+            "https://example.com/a\nhttps://example.com/b [wayback]\nhttps://example.com/c%0A"
+        )?;
+
+        let mut args = build_test_args();
+        args.silent = true;
+        args.output = Some(file.path().to_path_buf());
+        let suspects = std::collections::HashSet::from([
+            "https://example.com/b".to_string(), // real duplicate, tagged line
+            "https://example.com/c\n".to_string(), // real duplicate, escaped
+            "https://example.com/z".to_string(), // bloom false positive
+        ]);
+
+        assert_eq!(
+            recover_bloom_false_positives(&args, suspects.clone())?,
+            vec!["https://example.com/z".to_string()]
+        );
+
+        // Nothing to read back without a plain --output file
+        args.format = "json".to_string();
+        assert!(recover_bloom_false_positives(&args, suspects)?.is_empty());
+        Ok(())
+    }
+
     /// Helper to build a fully-defaulted Args for tests that only care about
     /// a couple of fields. Keep this in sync with the `Args` struct.
     fn build_test_args() -> Args {
@@ -2235,6 +4904,102 @@ mod tests {
             wayback_from: None,
             wayback_to: None,
             github_api_key: vec![],
+            watch_ct: false,
+            ct_stream_url: crate::providers::DEFAULT_CERTSTREAM_URL.to_string(),
+            urlscan_max_results: crate::providers::DEFAULT_URLSCAN_MAX_RESULTS,
+            since: None,
+            urlscan_submit: false,
+            urlscan_submit_max: crate::providers::DEFAULT_URLSCAN_SUBMIT_MAX,
+            ignore_port: false,
+            stream: false,
+            bloom_fp_rate: 0.001,
+            bloom_capacity: 10_000_000,
+            stream_exact: false,
+            retry_failed_domains: 0,
+            sign_output: None,
+            list_presets: false,
+            wayback_collapse: None,
+            wayback_filter: vec![],
+            wayback_match_type: None,
+            fail_on_new: false,
+            provider_opt: vec![],
+            check_reflection: false,
+            max_bandwidth: None,
+            explain_scope: false,
+            global_rate_limit: None,
+            max_requests: None,
+            max_urls: None,
+            smart_order: false,
+            circuit_breaker: 5,
+            cache_namespace: None,
+            list_cache_namespaces: false,
+            delete_cache_namespace: None,
+            provider_status_filter: vec![],
+            schema: false,
+            check_providers: false,
+            dry_run: false,
+            extract_sourcemaps: false,
+            max_body_bytes: 5_000_000,
+            max_urls_per_domain: None,
+            hosts_output: None,
+            provider_quota: vec![],
+            cache_ttl_by: vec![],
+            http_cache: false,
+            http_cache_path: None,
+            split_output_by_tag: false,
+            from: None,
+            to: None,
+            force_progress: false,
+            custom_providers: vec![],
+            per_category_limit: None,
+            plugin_providers: vec![],
+            metrics_addr: None,
+            robots_wildcards: false,
+            deterministic: false,
+            seed: 0,
+            extract_title: false,
+            tls_info: false,
+            check_open_redirect: false,
+            scan_secrets: false,
+            secrets_output: None,
+            extract_js_endpoints: false,
+            body_stats: false,
+            include_external: false,
+            respect_robots: false,
+            include_headers: vec![],
+            match_body: None,
+            filter_body: None,
+            http_version: None,
+            format_template: None,
+            compress: None,
+            append: false,
+            diff: None,
+            stats_json: None,
+            es_url: None,
+            es_index: "urx".to_string(),
+            csv_columns: None,
+            count: false,
+            count_by: None,
+            exclude_hosts: vec![],
+            custom_presets: Default::default(),
+            strip_params: vec![],
+            clean_params: false,
+            dedup_similar: false,
+            min_params: None,
+            max_params: None,
+            exclude_file: None,
+            exclude_regexes: vec![],
+            case_sensitive: false,
+            seen_after: None,
+            seen_before: None,
+            sample_per_path: None,
+            show_only_param_keys: false,
+            param_key_counts: false,
+            fuzz_params: false,
+            fuzz_path_last: false,
+            replace_values: None,
+            stats_markdown: None,
+            fail_on_unchanged: false,
         }
     }
 
@@ -2259,13 +5024,13 @@ mod tests {
             ),
         ]);
 
-        let exact = collect_domain_urls(&urls, "example.com", false);
+        let exact = collect_domain_urls(&urls, "example.com", false, false);
         assert_eq!(
             exact,
             std::collections::HashSet::from(["https://example.com/path".to_string()])
         );
 
-        let with_subdomains = collect_domain_urls(&urls, "example.com", true);
+        let with_subdomains = collect_domain_urls(&urls, "example.com", true, false);
         assert_eq!(
             with_subdomains,
             std::collections::HashSet::from([
@@ -2283,7 +5048,8 @@ mod tests {
             "https://example.com/result2".to_string(),
         ];
         let mock_tester = MockStatusChecker::new(mock_results.clone());
-        let testers: Vec<Box<dyn Tester>> = vec![Box::new(mock_tester)];
+        let testers: Vec<(TesterRole, Box<dyn Tester>)> =
+            vec![(TesterRole::Title, Box::new(mock_tester))];
 
         // Create test input
         let input_urls = vec![
@@ -2358,6 +5124,102 @@ mod tests {
             wayback_from: None,
             wayback_to: None,
             github_api_key: vec![],
+            watch_ct: false,
+            ct_stream_url: crate::providers::DEFAULT_CERTSTREAM_URL.to_string(),
+            urlscan_max_results: crate::providers::DEFAULT_URLSCAN_MAX_RESULTS,
+            since: None,
+            urlscan_submit: false,
+            urlscan_submit_max: crate::providers::DEFAULT_URLSCAN_SUBMIT_MAX,
+            ignore_port: false,
+            stream: false,
+            bloom_fp_rate: 0.001,
+            bloom_capacity: 10_000_000,
+            stream_exact: false,
+            retry_failed_domains: 0,
+            sign_output: None,
+            list_presets: false,
+            wayback_collapse: None,
+            wayback_filter: vec![],
+            wayback_match_type: None,
+            fail_on_new: false,
+            provider_opt: vec![],
+            check_reflection: false,
+            max_bandwidth: None,
+            explain_scope: false,
+            global_rate_limit: None,
+            max_requests: None,
+            max_urls: None,
+            smart_order: false,
+            circuit_breaker: 5,
+            cache_namespace: None,
+            list_cache_namespaces: false,
+            delete_cache_namespace: None,
+            provider_status_filter: vec![],
+            schema: false,
+            check_providers: false,
+            dry_run: false,
+            extract_sourcemaps: false,
+            max_body_bytes: 5_000_000,
+            max_urls_per_domain: None,
+            hosts_output: None,
+            provider_quota: vec![],
+            cache_ttl_by: vec![],
+            http_cache: false,
+            http_cache_path: None,
+            split_output_by_tag: false,
+            from: None,
+            to: None,
+            force_progress: false,
+            custom_providers: vec![],
+            per_category_limit: None,
+            plugin_providers: vec![],
+            metrics_addr: None,
+            robots_wildcards: false,
+            deterministic: false,
+            seed: 0,
+            extract_title: false,
+            tls_info: false,
+            check_open_redirect: false,
+            scan_secrets: false,
+            secrets_output: None,
+            extract_js_endpoints: false,
+            body_stats: false,
+            include_external: false,
+            respect_robots: false,
+            include_headers: vec![],
+            match_body: None,
+            filter_body: None,
+            http_version: None,
+            format_template: None,
+            compress: None,
+            append: false,
+            diff: None,
+            stats_json: None,
+            es_url: None,
+            es_index: "urx".to_string(),
+            csv_columns: None,
+            count: false,
+            count_by: None,
+            exclude_hosts: vec![],
+            custom_presets: Default::default(),
+            strip_params: vec![],
+            clean_params: false,
+            dedup_similar: false,
+            min_params: None,
+            max_params: None,
+            exclude_file: None,
+            exclude_regexes: vec![],
+            case_sensitive: false,
+            seen_after: None,
+            seen_before: None,
+            sample_per_path: None,
+            show_only_param_keys: false,
+            param_key_counts: false,
+            fuzz_params: false,
+            fuzz_path_last: false,
+            replace_values: None,
+            stats_markdown: None,
+            fail_on_unchanged: false,
         };
 
         let progress_manager = ProgressManager::new(true);
@@ -2369,6 +5231,8 @@ mod tests {
             &progress_manager,
             testers,
             false, // 여기를 false로 변경 (should_check_status)
+            &NetworkSettings::default(),
+            None,
         )
         .await;
 
@@ -0,0 +1,237 @@
+/// Live counters for `--metrics-addr`, served in the Prometheus text format.
+///
+/// The registry is process-wide, like the `--max-bandwidth` limiter: the
+/// runner, the cache lookups, the response readers and watch mode all feed
+/// it without any of them owning it. Every `record_*` call is a no-op until
+/// [`enable`] installs the registry, so runs without `--metrics-addr` pay
+/// nothing. The HTTP listener itself is behind the `metrics` feature.
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+#[derive(Debug)]
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+struct Metrics {
+    started: Instant,
+    /// Per provider name: (URLs returned, failed fetches).
+    providers: Mutex<BTreeMap<String, [u64; 2]>>,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    http_responses: AtomicU64,
+    http_response_bytes: AtomicU64,
+    urls_emitted: AtomicU64,
+}
+
+/// Start collecting. Only the first call has any effect.
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+pub fn enable() {
+    let _ = METRICS.set(Metrics {
+        started: Instant::now(),
+        providers: Mutex::new(BTreeMap::new()),
+        cache_hits: AtomicU64::new(0),
+        cache_misses: AtomicU64::new(0),
+        http_responses: AtomicU64::new(0),
+        http_response_bytes: AtomicU64::new(0),
+        urls_emitted: AtomicU64::new(0),
+    });
+}
+
+fn bump_provider(provider: &str, slot: usize, n: u64) {
+    if let Some(m) = METRICS.get() {
+        let mut providers = m.providers.lock().unwrap_or_else(|e| e.into_inner());
+        providers.entry(provider.to_string()).or_default()[slot] += n;
+    }
+}
+
+/// `provider` returned `n` URLs (one fetch, or one page of a stream).
+pub fn record_urls(provider: &str, n: usize) {
+    bump_provider(provider, 0, n as u64);
+}
+
+/// A (domain, provider) fetch failed for good.
+pub fn record_provider_error(provider: &str) {
+    bump_provider(provider, 1, 1);
+}
+
+/// A result-cache lookup for one domain and provider.
+pub fn record_cache_lookup(hit: bool) {
+    if let Some(m) = METRICS.get() {
+        let counter = if hit { &m.cache_hits } else { &m.cache_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Result-cache (hits, misses) so far, or `None` before [`enable`].
+pub fn cache_lookups() -> Option<(u64, u64)> {
+    let m = METRICS.get()?;
+    Some((
+        m.cache_hits.load(Ordering::Relaxed),
+        m.cache_misses.load(Ordering::Relaxed),
+    ))
+}
+
+/// A response body of `bytes` was read (providers and testers).
+pub fn record_response(bytes: usize) {
+    if let Some(m) = METRICS.get() {
+        m.http_responses.fetch_add(1, Ordering::Relaxed);
+        m.http_response_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+}
+
+/// `n` URLs were written out by `--stream` or `--watch-ct`.
+pub fn record_emitted(n: usize) {
+    if let Some(m) = METRICS.get() {
+        m.urls_emitted.fetch_add(n as u64, Ordering::Relaxed);
+    }
+}
+
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// The current counters as a Prometheus text exposition, or `None` before
+/// [`enable`].
+#[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+pub fn render() -> Option<String> {
+    let m = METRICS.get()?;
+    let mut out = String::new();
+    let mut counter = |name: &str, help: &str, samples: &[(Option<&str>, u64)]| {
+        let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} counter");
+        for (provider, value) in samples {
+            match provider {
+                Some(p) => {
+                    let _ = writeln!(out, "{name}{{provider=\"{}\"}} {value}", escape_label(p));
+                }
+                None => {
+                    let _ = writeln!(out, "{name} {value}");
+                }
+            }
+        }
+    };
+
+    let providers = m
+        .providers
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let per_provider = |i: usize| -> Vec<(Option<&str>, u64)> {
+        providers
+            .iter()
+            .map(|(name, v)| (Some(name.as_str()), v[i]))
+            .collect()
+    };
+    counter(
+        "urx_urls_collected_total",
+        "URLs returned by providers, before deduplication.",
+        &per_provider(0),
+    );
+    counter(
+        "urx_provider_errors_total",
+        "(domain, provider) fetches that failed.",
+        &per_provider(1),
+    );
+    counter(
+        "urx_cache_hits_total",
+        "Result-cache lookups answered from the cache.",
+        &[(None, m.cache_hits.load(Ordering::Relaxed))],
+    );
+    counter(
+        "urx_cache_misses_total",
+        "Result-cache lookups that needed a fetch.",
+        &[(None, m.cache_misses.load(Ordering::Relaxed))],
+    );
+    counter(
+        "urx_http_responses_total",
+        "HTTP response bodies read by providers and testers.",
+        &[(None, m.http_responses.load(Ordering::Relaxed))],
+    );
+    counter(
+        "urx_http_response_bytes_total",
+        "Bytes of HTTP response bodies read.",
+        &[(None, m.http_response_bytes.load(Ordering::Relaxed))],
+    );
+    counter(
+        "urx_urls_emitted_total",
+        "URLs written out by --stream and --watch-ct.",
+        &[(None, m.urls_emitted.load(Ordering::Relaxed))],
+    );
+    let _ = writeln!(
+        out,
+        "# HELP urx_uptime_seconds Seconds since the run started.\n# TYPE urx_uptime_seconds gauge\nurx_uptime_seconds {:.3}",
+        m.started.elapsed().as_secs_f64()
+    );
+    Some(out)
+}
+
+/// Bind `addr` and answer `GET /metrics` with [`render`] for the rest of the
+/// process. Returns once the socket is bound, so a port already in use fails
+/// the run up front.
+#[cfg(feature = "metrics")]
+pub async fn serve(addr: std::net::SocketAddr) -> anyhow::Result<std::net::SocketAddr> {
+    use anyhow::Context;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    enable();
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to bind --metrics-addr {addr}"))?;
+    let bound = listener.local_addr()?;
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                // Only the request line matters; anything past the first
+                // read is ignored.
+                let mut buf = [0u8; 1024];
+                let n = socket.read(&mut buf).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]);
+                let target = request
+                    .strip_prefix("GET ")
+                    .and_then(|rest| rest.split_whitespace().next());
+                let (status, body) = match target {
+                    Some("/metrics") => ("200 OK", render().unwrap_or_default()),
+                    _ => ("404 Not Found", "Not Found\n".to_string()),
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+                let _ = socket.shutdown().await;
+            });
+        }
+    });
+    Ok(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_exposes_recorded_counters() {
+        record_urls("ignored before enable", 1);
+        enable();
+        record_urls("Metrics Test", 40);
+        record_provider_error("Metrics Test");
+        record_urls("Say \"hi\"", 2);
+        record_cache_lookup(true);
+        record_response(512);
+
+        let text = render().unwrap();
+        assert!(!text.contains("ignored before enable"));
+        assert!(text.contains("urx_urls_collected_total{provider=\"Metrics Test\"} 40"));
+        assert!(text.contains("urx_provider_errors_total{provider=\"Metrics Test\"} 1"));
+        assert!(text.contains("{provider=\"Say \\\"hi\\\"\"} 2"));
+        assert!(text.contains("# TYPE urx_cache_hits_total counter"));
+        assert!(text.contains("urx_uptime_seconds "));
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Default `--max-body-bytes`: large enough for any real page or script,
+/// small enough that an archived multi-gigabyte file can't exhaust memory.
+pub const DEFAULT_MAX_BODY_BYTES: u64 = 5_000_000;
+
+/// Process-wide cap set from `--max-bandwidth`. Global rather than threaded
+/// through every provider and tester because the limit is on the whole run's
+/// downstream traffic, not on any one component.
+static MAX_BANDWIDTH: OnceLock<BandwidthLimiter> = OnceLock::new();
+
+/// Token bucket over response-body bytes. Holds at most one second's worth of
+/// tokens, so idle time doesn't bank an unbounded burst. Reads may overdraw
+/// the bucket; the reader then sleeps until the debt is repaid, which keeps
+/// the long-run average at the configured rate regardless of chunk size.
+#[derive(Debug)]
+struct BandwidthLimiter {
+    bytes_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl BandwidthLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        let bytes_per_sec = bytes_per_sec.max(1) as f64;
+        BandwidthLimiter {
+            bytes_per_sec,
+            bucket: Mutex::new(Bucket {
+                tokens: bytes_per_sec,
+                last: Instant::now(),
+            }),
+        }
+    }
+
+    /// Charge `bytes` against the bucket, returning how long the caller must
+    /// wait before reading more.
+    fn charge(&self, bytes: usize) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.last).as_secs_f64() * self.bytes_per_sec;
+        bucket.tokens = (bucket.tokens + refill).min(self.bytes_per_sec);
+        bucket.last = now;
+        bucket.tokens -= bytes as f64;
+        if bucket.tokens < 0.0 {
+            Duration::from_secs_f64(-bucket.tokens / self.bytes_per_sec)
+        } else {
+            Duration::ZERO
+        }
+    }
+
+    /// Account for `bytes` just read, sleeping if the run is over its budget.
+    async fn consume(&self, bytes: usize) {
+        let wait = self.charge(bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Install the `--max-bandwidth` cap for this process. Only the first call
+/// takes effect; `None` leaves reads unthrottled.
+pub fn set_max_bandwidth(bytes_per_sec: Option<u64>) {
+    if let Some(rate) = bytes_per_sec.filter(|r| *r > 0) {
+        let _ = MAX_BANDWIDTH.set(BandwidthLimiter::new(rate));
+    }
+}
+
+/// Charge `bytes` of body already read against the `--max-bandwidth` cap, for
+/// callers that stream a body themselves. A no-op when no cap is set.
+pub async fn consume_bandwidth(bytes: usize) {
+    if let Some(limiter) = MAX_BANDWIDTH.get() {
+        limiter.consume(bytes).await;
+    }
+}
+
+/// Parse a `--max-bandwidth` value into bytes per second: a number with an
+/// optional unit (`B`, `KB`, `MB`, `GB` in powers of 1000; `KiB`, `MiB`, `GiB`
+/// in powers of 1024; a bare `K`/`M`/`G` means the decimal unit) and an
+/// optional `ps` or `/s` suffix, e.g. `10MBps`, `512KiB/s`, `2000000`.
+pub fn parse_bandwidth(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let lower = value.to_ascii_lowercase();
+    let unit_part = lower
+        .strip_suffix("/s")
+        .or_else(|| lower.strip_suffix("ps"))
+        .unwrap_or(&lower);
+    let split = unit_part
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(unit_part.len());
+    let (number, unit) = unit_part.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier = match unit.trim() {
+        "" | "b" => 1.0,
+        "k" | "kb" => 1e3,
+        "m" | "mb" => 1e6,
+        "g" | "gb" => 1e9,
+        "kib" => 1024.0,
+        "mib" => 1024.0 * 1024.0,
+        "gib" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    let bytes = (number * multiplier).round();
+    (bytes >= 1.0 && bytes.is_finite()).then_some(bytes as u64)
+}
+
+/// Read a response body into memory, pacing the read against the
+/// `--max-bandwidth` cap when one is set.
+pub async fn read_bytes(response: reqwest::Response) -> Result<Vec<u8>> {
+    read_bytes_capped(response, 0).await
+}
+
+/// [`read_bytes`] that gives up on bodies over `max_bytes` (0 = no cap), for
+/// testers fetching arbitrary collected URLs (`--max-body-bytes`). A declared
+/// `Content-Length` over the cap fails before any of the body is read;
+/// otherwise the read stops at the chunk that crosses it.
+pub async fn read_bytes_capped(mut response: reqwest::Response, max_bytes: u64) -> Result<Vec<u8>> {
+    let too_large =
+        || anyhow::anyhow!("Response body exceeds --max-body-bytes ({max_bytes} bytes)");
+    if max_bytes > 0 && response.content_length().is_some_and(|len| len > max_bytes) {
+        return Err(too_large());
+    }
+    let limiter = MAX_BANDWIDTH.get();
+    if limiter.is_none() && max_bytes == 0 {
+        let body = response.bytes().await?.to_vec();
+        crate::metrics::record_response(body.len());
+        return Ok(body);
+    }
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if max_bytes > 0 && (body.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
+        }
+        if let Some(limiter) = limiter {
+            limiter.consume(chunk.len()).await;
+        }
+        body.extend_from_slice(&chunk);
+    }
+    crate::metrics::record_response(body.len());
+    Ok(body)
+}
+
+/// [`read_bytes`] decoded as (lossy) UTF-8, for callers that used
+/// `Response::text`.
+pub async fn read_text(response: reqwest::Response) -> Result<String> {
+    read_text_capped(response, 0).await
+}
+
+/// [`read_bytes_capped`] decoded as (lossy) UTF-8.
+pub async fn read_text_capped(response: reqwest::Response, max_bytes: u64) -> Result<String> {
+    let body = read_bytes_capped(response, max_bytes).await?;
+    Ok(match String::from_utf8(body) {
+        Ok(text) => text,
+        Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+    })
+}
+
+/// [`read_bytes`] deserialized as JSON, for callers that used `Response::json`.
+pub async fn read_json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let body = read_bytes(response).await?;
+    Ok(serde_json::from_slice(&body)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bandwidth() {
+        assert_eq!(parse_bandwidth("10MBps"), Some(10_000_000));
+        assert_eq!(parse_bandwidth("10mb/s"), Some(10_000_000));
+        assert_eq!(parse_bandwidth("512KiB"), Some(524_288));
+        assert_eq!(parse_bandwidth("1.5M"), Some(1_500_000));
+        assert_eq!(parse_bandwidth("2000"), Some(2000));
+        assert_eq!(parse_bandwidth(" 1 GiB/s "), Some(1_073_741_824));
+        assert_eq!(parse_bandwidth("0"), None);
+        assert_eq!(parse_bandwidth("fast"), None);
+        assert_eq!(parse_bandwidth("10Mbit"), None);
+        assert_eq!(parse_bandwidth(""), None);
+    }
+
+    #[test]
+    fn test_charge_allows_one_second_burst_then_waits() {
+        let limiter = BandwidthLimiter::new(1000);
+        assert!(limiter.charge(1000).is_zero());
+        // The bucket is empty now: 500 more bytes cost ~0.5s.
+        let wait = limiter.charge(500);
+        assert!(wait > Duration::from_millis(450) && wait <= Duration::from_millis(500));
+    }
+
+    #[tokio::test]
+    async fn test_read_text_capped_rejects_oversized_bodies() {
+        let mut server = mockito::Server::new_async().await;
+        let _big = server
+            .mock("GET", "/big")
+            .with_status(200)
+            .with_body("x".repeat(1000))
+            .create_async()
+            .await;
+        let url = format!("{}/big", server.url());
+        let client = reqwest::Client::new();
+
+        let err = read_text_capped(client.get(&url).send().await.unwrap(), 100)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("--max-body-bytes"));
+
+        let body = read_text_capped(client.get(&url).send().await.unwrap(), 1000)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), 1000);
+        let body = read_text_capped(client.get(&url).send().await.unwrap(), 0)
+            .await
+            .unwrap();
+        assert_eq!(body.len(), 1000);
+    }
+
+    #[tokio::test]
+    async fn test_consume_paces_reads() {
+        let limiter = BandwidthLimiter::new(10_000);
+        let start = Instant::now();
+        limiter.consume(10_000).await; // burst allowance
+        limiter.consume(2_000).await; // ~200ms of debt
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+}
@@ -2,6 +2,46 @@ use anyhow::Result;
 use reqwest::Client;
 use std::time::Duration;
 
+use super::{http_cache, read_text, server_retry_delay, wait_before_retry, HttpCache, RateLimiter};
+
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+compile_error!("urx needs a TLS backend: enable the `rustls` (default) or `native-tls` feature");
+
+/// TLS backend every client from [`HttpClientConfig::build_client`] uses.
+/// `rustls` (pure Rust, no OpenSSL) wins when both features are enabled.
+pub const TLS_BACKEND: &str = if cfg!(feature = "rustls") {
+    "rustls"
+} else {
+    "native-tls"
+};
+
+/// Which HTTP version testers speak (`--http-version`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum HttpVersion {
+    /// Negotiate with ALPN over TLS (HTTP/2 when the server offers it) and
+    /// use HTTP/1.1 over plain HTTP.
+    #[default]
+    Auto,
+    /// Only ever speak HTTP/1.1.
+    Http1,
+    /// Speak HTTP/2 from the first byte (prior knowledge), over plain HTTP
+    /// too.
+    Http2,
+}
+
+impl std::str::FromStr for HttpVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(HttpVersion::Auto),
+            "1.1" | "1" | "http1" | "http/1.1" => Ok(HttpVersion::Http1),
+            "2" | "http2" | "http/2" => Ok(HttpVersion::Http2),
+            _ => Err(format!("Invalid HTTP version: {s}. Use auto, 1.1 or 2")),
+        }
+    }
+}
+
 /// Common HTTP client configuration shared across providers and testers.
 ///
 /// This struct centralizes the logic for building a `reqwest::Client` with
@@ -19,6 +59,8 @@ pub struct HttpClientConfig {
     pub proxy: Option<String>,
     /// Optional proxy authentication in "username:password" format
     pub proxy_auth: Option<String>,
+    /// HTTP version to speak; see [`HttpVersion`]
+    pub http_version: HttpVersion,
 }
 
 impl Default for HttpClientConfig {
@@ -29,6 +71,7 @@ impl Default for HttpClientConfig {
             random_agent: false,
             proxy: None,
             proxy_auth: None,
+            http_version: HttpVersion::Auto,
         }
     }
 }
@@ -40,12 +83,33 @@ impl HttpClientConfig {
     ///
     /// Returns an error if the proxy URL is invalid or the client fails to build.
     pub fn build_client(&self) -> Result<Client> {
+        Ok(self.client_builder()?.build()?)
+    }
+
+    /// The builder behind [`build_client`](Self::build_client), for callers
+    /// that need to add to it (e.g. default headers) before building.
+    pub fn client_builder(&self) -> Result<reqwest::ClientBuilder> {
         let mut builder = Client::builder().timeout(Duration::from_secs(self.timeout));
 
+        #[cfg(feature = "rustls")]
+        {
+            builder = builder.tls_backend_rustls();
+        }
+        #[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+        {
+            builder = builder.tls_backend_native();
+        }
+
         if self.insecure {
             builder = builder.danger_accept_invalid_certs(true);
         }
 
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+
         // Always send a User-Agent. reqwest sends none by default, and several
         // upstreams — notably the Wayback CDX API — answer a UA-less request
         // with `400 Bad Request`, so an unset header was a silent, blanket
@@ -70,54 +134,90 @@ impl HttpClientConfig {
             builder = builder.proxy(proxy);
         }
 
-        Ok(builder.build()?)
+        Ok(builder)
     }
 }
 
-/// Parse a `Retry-After` response header into a sleep duration so a throttled
-/// request waits as long as the server asked before retrying. Only the
-/// delta-seconds form (the common API case, e.g. `Retry-After: 30`) is honored;
-/// the HTTP-date form returns `None` and the caller falls back to its normal
-/// back-off. The value is capped so a hostile or absurd header can't stall a run.
-pub fn retry_after_delay(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
-    const MAX_RETRY_AFTER_SECS: u64 = 60;
-    let raw = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
-    let secs: u64 = raw.trim().parse().ok()?;
-    Some(Duration::from_secs(secs.min(MAX_RETRY_AFTER_SECS)))
-}
-
-/// Execute an HTTP GET request with retry and linear back-off.
+/// Execute an HTTP GET request with retry and exponential back-off (see
+/// [`wait_before_retry`]), honoring `Retry-After` on 429/503 responses.
 ///
 /// `max_retries` is the number of **additional** attempts after the first
 /// failure (i.e. total attempts = 1 + max_retries).
 ///
+/// Every attempt, retries included, first waits on `limiter` (when set), so
+/// a flaky endpoint can't be hit faster than `--rate-limit` allows.
+///
+/// With `--http-cache` on, a response stored for `url` is revalidated with a
+/// conditional request and served from disk on `304 Not Modified`.
+///
 /// On success the response body is returned as a `String`.
 ///
 /// # Errors
 ///
 /// Returns the last encountered error if all attempts are exhausted.
-pub async fn get_with_retry(client: &Client, url: &str, max_retries: u32) -> Result<String> {
+pub async fn get_with_retry(
+    client: &Client,
+    url: &str,
+    max_retries: u32,
+    limiter: Option<&RateLimiter>,
+) -> Result<String> {
+    get_with_retry_cached(client, url, max_retries, limiter, http_cache()).await
+}
+
+/// [`get_with_retry`] against an explicit HTTP cache.
+async fn get_with_retry_cached(
+    client: &Client,
+    url: &str,
+    max_retries: u32,
+    limiter: Option<&RateLimiter>,
+    cache: Option<&HttpCache>,
+) -> Result<String> {
+    let cached = match cache {
+        Some(cache) => cache.lookup(url).await,
+        None => None,
+    };
     let mut last_error: Option<anyhow::Error> = None;
     let mut attempt: u32 = 0;
+    let mut server_delay = None;
 
     while attempt <= max_retries {
         if attempt > 0 {
-            // Linear back-off: 500ms, 1000ms, 1500ms, …
-            tokio::time::sleep(Duration::from_millis(500 * attempt as u64)).await;
+            wait_before_retry(attempt, server_delay.take()).await;
+        }
+        if let Some(rl) = limiter {
+            rl.acquire().await;
+        }
+
+        let mut request = client.get(url);
+        if let Some(entry) = &cached {
+            request = entry.conditional(request);
         }
 
-        match client.get(url).send().await {
+        match request.send().await {
             Ok(response) => {
+                if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                    if let (Some(cache), Some(entry)) = (cache, &cached) {
+                        cache.record_revalidated();
+                        return Ok(entry.body.clone());
+                    }
+                }
                 if !response.status().is_success() {
+                    server_delay = server_retry_delay(response.status(), response.headers());
                     last_error = Some(anyhow::anyhow!("HTTP error: {}", response.status()));
                     attempt += 1;
                     continue;
                 }
 
-                match response.text().await {
-                    Ok(text) => return Ok(text),
+                let headers = response.headers().clone();
+                match read_text(response).await {
+                    Ok(text) => {
+                        if let Some(cache) = cache {
+                            cache.store(url, &headers, &text).await;
+                        }
+                        return Ok(text);
+                    }
                     Err(e) => {
-                        last_error = Some(e.into());
+                        last_error = Some(e);
                         attempt += 1;
                         continue;
                     }
@@ -146,36 +246,6 @@ pub async fn get_with_retry(client: &Client, url: &str, max_retries: u32) -> Res
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_retry_after_delay_parses_seconds() {
-        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
-        let mut headers = HeaderMap::new();
-        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
-        assert_eq!(retry_after_delay(&headers), Some(Duration::from_secs(30)));
-    }
-
-    #[test]
-    fn test_retry_after_delay_caps_large_values() {
-        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
-        let mut headers = HeaderMap::new();
-        headers.insert(RETRY_AFTER, HeaderValue::from_static("100000"));
-        assert_eq!(retry_after_delay(&headers), Some(Duration::from_secs(60)));
-    }
-
-    #[test]
-    fn test_retry_after_delay_ignores_http_date_and_missing() {
-        use reqwest::header::{HeaderMap, HeaderValue, RETRY_AFTER};
-        let empty = HeaderMap::new();
-        assert_eq!(retry_after_delay(&empty), None);
-
-        let mut headers = HeaderMap::new();
-        headers.insert(
-            RETRY_AFTER,
-            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
-        );
-        assert_eq!(retry_after_delay(&headers), None);
-    }
-
     #[test]
     fn test_default_config() {
         let config = HttpClientConfig::default();
@@ -252,11 +322,20 @@ mod tests {
             random_agent: true,
             proxy: Some("http://127.0.0.1:8080".to_string()),
             proxy_auth: Some("admin:secret".to_string()),
+            http_version: HttpVersion::Http2,
         };
         let client = config.build_client();
         assert!(client.is_ok());
     }
 
+    #[test]
+    fn test_http_version_parses() {
+        assert_eq!("auto".parse(), Ok(HttpVersion::Auto));
+        assert_eq!("1.1".parse(), Ok(HttpVersion::Http1));
+        assert_eq!("HTTP/2".parse(), Ok(HttpVersion::Http2));
+        assert!("3".parse::<HttpVersion>().is_err());
+    }
+
     #[tokio::test]
     async fn test_get_with_retry_success_first_try() {
         let mut mock_server = mockito::Server::new_async().await;
@@ -269,7 +348,7 @@ mod tests {
 
         let client = Client::new();
         let url = format!("{}/test", mock_server.url());
-        let result = get_with_retry(&client, &url, 3).await;
+        let result = get_with_retry(&client, &url, 3, None).await;
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "success");
@@ -300,7 +379,7 @@ mod tests {
         let url = format!("{}/test", mock_server.url());
 
         // We expect it to succeed eventually
-        let result = get_with_retry(&client, &url, 3).await;
+        let result = get_with_retry(&client, &url, 3, None).await;
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "success");
@@ -322,23 +401,81 @@ mod tests {
         let url = format!("{}/test", mock_server.url());
 
         // Max retries = 1. Total attempts = 2.
-        let result = get_with_retry(&client, &url, 1).await;
+        let result = get_with_retry(&client, &url, 1, None).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Failed after 2 attempts"));
     }
 
+    #[tokio::test]
+    async fn test_get_with_retry_waits_on_limiter() {
+        let mut mock_server = mockito::Server::new_async().await;
+        let _m = mock_server
+            .mock("GET", "/test")
+            .with_status(200)
+            .with_body("success")
+            .create_async()
+            .await;
+
+        let client = Client::new();
+        let url = format!("{}/test", mock_server.url());
+        // 4 req/s: a request right after another one must wait ~250ms.
+        let limiter = RateLimiter::new(4.0).unwrap();
+        limiter.acquire().await;
+
+        let start = std::time::Instant::now();
+        let result = get_with_retry(&client, &url, 0, Some(&limiter)).await;
+
+        assert_eq!(result.unwrap(), "success");
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
     #[tokio::test]
     async fn test_get_with_retry_connection_error() {
         // Use a reserved port (0) which typically causes a connection error immediately
         let client = Client::new();
         let url = "http://127.0.0.1:0";
 
-        let result = get_with_retry(&client, url, 1).await;
+        let result = get_with_retry(&client, url, 1, None).await;
 
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Failed after 2 attempts"));
     }
+
+    #[tokio::test]
+    async fn test_get_with_retry_revalidates_cached_responses() {
+        let mut mock_server = mockito::Server::new_async().await;
+        let fresh = mock_server
+            .mock("GET", "/cdx")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_body("archived urls")
+            .expect(1)
+            .create_async()
+            .await;
+        let not_modified = mock_server
+            .mock("GET", "/cdx")
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path());
+        let client = Client::new();
+        let url = format!("{}/cdx", mock_server.url());
+
+        let first = get_with_retry_cached(&client, &url, 0, None, Some(&cache)).await;
+        let second = get_with_retry_cached(&client, &url, 0, None, Some(&cache)).await;
+
+        assert_eq!(first.unwrap(), "archived urls");
+        assert_eq!(second.unwrap(), "archived urls");
+        assert_eq!(cache.counts(), (1, 1));
+        fresh.assert_async().await;
+        not_modified.assert_async().await;
+    }
 }
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+/// Process-wide HTTP cache set from `--http-cache`. Global for the same
+/// reason as `--max-bandwidth`: it sits under every provider request rather
+/// than belonging to any one provider.
+static HTTP_CACHE: OnceLock<HttpCache> = OnceLock::new();
+
+/// On-disk store of provider responses keyed by request URL, revalidated
+/// with conditional requests (`If-None-Match` / `If-Modified-Since`).
+///
+/// Unlike the result cache this works below the providers: a repeat scan
+/// still asks the upstream, but an unchanged page comes back as a bodiless
+/// `304 Not Modified` and is served from disk. Only responses carrying an
+/// `ETag` or `Last-Modified` validator are stored, since nothing else can be
+/// revalidated.
+#[derive(Debug)]
+pub struct HttpCache {
+    dir: PathBuf,
+    revalidated: AtomicU64,
+    stored: AtomicU64,
+}
+
+/// A stored response body and the validators it came with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// Add the conditional headers for this entry to `request`.
+    pub fn conditional(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+impl HttpCache {
+    /// A cache storing its entries under `dir`, created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        HttpCache {
+            dir: dir.into(),
+            revalidated: AtomicU64::new(0),
+            stored: AtomicU64::new(0),
+        }
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let digest = Sha256::digest(url.as_bytes());
+        let name: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+        self.dir.join(format!("{name}.json"))
+    }
+
+    /// The stored response for `url`, if any. An unreadable or corrupt entry
+    /// counts as a miss.
+    pub async fn lookup(&self, url: &str) -> Option<CachedResponse> {
+        let raw = tokio::fs::read(self.entry_path(url)).await.ok()?;
+        serde_json::from_slice::<CachedResponse>(&raw)
+            .ok()
+            .filter(|entry| entry.url == url)
+    }
+
+    /// Store `body` for `url` when `headers` carry a validator. Write errors
+    /// are ignored: the cache only ever saves work.
+    pub async fn store(&self, url: &str, headers: &reqwest::header::HeaderMap, body: &str) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|v: &reqwest::header::HeaderValue| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        if etag.is_none() && last_modified.is_none() {
+            return;
+        }
+        let entry = CachedResponse {
+            url: url.to_string(),
+            etag,
+            last_modified,
+            body: body.to_string(),
+        };
+        let Ok(serialized) = serde_json::to_vec(&entry) else {
+            return;
+        };
+        if tokio::fs::create_dir_all(&self.dir).await.is_err() {
+            return;
+        }
+        // Write then rename, so a concurrent reader never sees half an entry.
+        let path = self.entry_path(url);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        if tokio::fs::write(&tmp, serialized).await.is_ok()
+            && tokio::fs::rename(&tmp, &path).await.is_ok()
+        {
+            self.stored.fetch_add(1, Ordering::Relaxed);
+        } else {
+            let _ = tokio::fs::remove_file(&tmp).await;
+        }
+    }
+
+    /// Record a `304 Not Modified` served from the cache.
+    pub fn record_revalidated(&self) {
+        self.revalidated.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Responses served from the cache after a 304, and responses stored.
+    pub fn counts(&self) -> (u64, u64) {
+        (
+            self.revalidated.load(Ordering::Relaxed),
+            self.stored.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// Install the `--http-cache` store for this process. Only the first call
+/// takes effect.
+pub fn set_http_cache(dir: PathBuf) {
+    let _ = HTTP_CACHE.set(HttpCache::new(dir));
+}
+
+/// The process-wide HTTP cache, when `--http-cache` enabled one.
+pub fn http_cache() -> Option<&'static HttpCache> {
+    HTTP_CACHE.get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderMap, HeaderValue, ETAG};
+
+    #[tokio::test]
+    async fn test_store_needs_a_validator() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HttpCache::new(dir.path().join("http"));
+        let url = "https://web.archive.org/cdx/search/cdx?url=example.com";
+
+        cache.store(url, &HeaderMap::new(), "body").await;
+        assert!(cache.lookup(url).await.is_none());
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ETAG, HeaderValue::from_static("\"v1\""));
+        cache.store(url, &headers, "body").await;
+        let entry = cache.lookup(url).await.unwrap();
+        assert_eq!(entry.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(entry.body, "body");
+        assert!(cache.lookup("https://other.example/").await.is_none());
+        assert_eq!(cache.counts(), (0, 1));
+    }
+}
@@ -3,11 +3,24 @@
 // This module provides shared network configuration functionality for HTTP requests
 // across different parts of the application, such as providers and testers.
 
+mod bandwidth;
 pub mod client;
+mod http_cache;
+mod quota;
 mod rate_limiter;
+mod request_gate;
+mod retry;
 mod settings;
 pub mod user_agent;
 
+pub use bandwidth::{
+    consume_bandwidth, parse_bandwidth, read_bytes_capped, read_json, read_text, read_text_capped,
+    set_max_bandwidth, DEFAULT_MAX_BODY_BYTES,
+};
+pub use http_cache::{http_cache, set_http_cache, HttpCache};
+pub use quota::{parse_provider_quotas, QuotaBook, QuotaMeter, METERED_PROVIDERS};
 pub use rate_limiter::RateLimiter;
-pub use settings::{NetworkScope, NetworkSettings};
+pub use request_gate::RequestGate;
+pub use retry::{retry_after_delay, server_retry_delay, wait_before_retry};
+pub use settings::{NetworkScope, NetworkSettings, ProviderNetworkOverride};
 pub use user_agent::{default_user_agent, random_user_agent};
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Keyed providers whose requests are counted against a daily quota and
+/// persisted in the cache between runs.
+pub const METERED_PROVIDERS: [&str; 4] = ["vt", "urlscan", "zoomeye", "github"];
+
+/// Daily limits applied when `--provider-quota` doesn't mention a provider:
+/// the VirusTotal public API allows 500 lookups a day. The other metered
+/// providers are tracked but unlimited unless configured, since their quotas
+/// depend on the plan.
+pub const DEFAULT_PROVIDER_QUOTAS: [(&str, u64); 1] = [("vt", 500)];
+
+/// Share of the daily limit past which urx warns before starting the run.
+const WARN_RATIO: f64 = 0.8;
+
+/// Counts one provider's requests for the day against its daily limit
+/// (`--provider-quota`).
+///
+/// `used_before` is what earlier runs today already spent, loaded from the
+/// cache. Every request the provider sends (pages and retries alike) goes
+/// through its [`super::RateLimiter`], which ticks the meter. Clones share the
+/// count, so the per-domain provider copies the runner makes all add to it.
+#[derive(Clone, Debug)]
+pub struct QuotaMeter {
+    inner: Arc<MeterInner>,
+}
+
+#[derive(Debug)]
+struct MeterInner {
+    provider: String,
+    limit: Option<u64>,
+    used_before: u64,
+    sent: AtomicU64,
+}
+
+/// Quota meters by provider id (`vt`, `urlscan`, …).
+pub type QuotaBook = HashMap<String, QuotaMeter>;
+
+impl QuotaMeter {
+    /// A meter for `provider` with `used_before` requests already spent
+    /// today. `None` (or a limit of 0) tracks usage without limiting it.
+    pub fn new(provider: &str, limit: Option<u64>, used_before: u64) -> Self {
+        QuotaMeter {
+            inner: Arc::new(MeterInner {
+                provider: provider.to_string(),
+                limit: limit.filter(|l| *l > 0),
+                used_before,
+                sent: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Record one outbound request.
+    pub(super) fn hit(&self) {
+        self.inner.sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn provider(&self) -> &str {
+        &self.inner.provider
+    }
+
+    pub fn limit(&self) -> Option<u64> {
+        self.inner.limit
+    }
+
+    /// Requests sent by this run.
+    pub fn sent(&self) -> u64 {
+        self.inner.sent.load(Ordering::Relaxed)
+    }
+
+    /// Requests spent today, this run included.
+    pub fn used(&self) -> u64 {
+        self.inner.used_before + self.sent()
+    }
+
+    /// Whether today's quota is used up.
+    pub fn is_spent(&self) -> bool {
+        self.inner.limit.is_some_and(|limit| self.used() >= limit)
+    }
+
+    /// Whether today's usage is close enough to the limit to warn about.
+    pub fn is_near_limit(&self) -> bool {
+        self.inner
+            .limit
+            .is_some_and(|limit| self.used() as f64 >= limit as f64 * WARN_RATIO)
+    }
+}
+
+/// Parse `--provider-quota` entries (`id=N`, comma-separated) on top of
+/// [`DEFAULT_PROVIDER_QUOTAS`]. `0` removes a limit. Malformed entries and
+/// providers that aren't metered are an error naming the entry.
+pub fn parse_provider_quotas(entries: &[String]) -> anyhow::Result<HashMap<String, u64>> {
+    let mut limits: HashMap<String, u64> = DEFAULT_PROVIDER_QUOTAS
+        .iter()
+        .map(|(id, limit)| (id.to_string(), *limit))
+        .collect();
+    for raw in entries {
+        let entry = raw.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let parsed = entry
+            .split_once('=')
+            .and_then(|(id, n)| Some((id.trim(), n.trim().parse::<u64>().ok()?)));
+        let Some((id, limit)) = parsed else {
+            anyhow::bail!("Invalid --provider-quota entry {entry:?}: expected id=N (e.g. vt=500)");
+        };
+        if !METERED_PROVIDERS.contains(&id) {
+            anyhow::bail!(
+                "Invalid --provider-quota entry {entry:?}: quotas apply to {}",
+                METERED_PROVIDERS.join(", ")
+            );
+        }
+        limits.insert(id.to_string(), limit);
+    }
+    Ok(limits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_counts_against_the_daily_limit() {
+        let meter = QuotaMeter::new("vt", Some(10), 7);
+        let clone = meter.clone();
+        assert!(!meter.is_near_limit());
+        clone.hit();
+        assert!(meter.is_near_limit());
+        assert!(!meter.is_spent());
+        clone.hit();
+        meter.hit();
+        assert_eq!(meter.sent(), 3);
+        assert_eq!(meter.used(), 10);
+        assert!(meter.is_spent());
+
+        let unlimited = QuotaMeter::new("urlscan", Some(0), 1_000_000);
+        assert!(unlimited.limit().is_none());
+        assert!(!unlimited.is_spent());
+        assert!(!unlimited.is_near_limit());
+    }
+
+    #[test]
+    fn test_parse_provider_quotas() {
+        let limits = parse_provider_quotas(&[]).unwrap();
+        assert_eq!(limits.get("vt"), Some(&500));
+
+        let limits =
+            parse_provider_quotas(&["urlscan=1000".to_string(), " vt = 0 ".to_string()]).unwrap();
+        assert_eq!(limits.get("urlscan"), Some(&1000));
+        assert_eq!(limits.get("vt"), Some(&0));
+
+        assert!(parse_provider_quotas(&["vt".to_string()]).is_err());
+        assert!(parse_provider_quotas(&["vt=many".to_string()]).is_err());
+        assert!(parse_provider_quotas(&["wayback=10".to_string()]).is_err());
+    }
+}
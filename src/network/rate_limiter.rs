@@ -2,6 +2,8 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+use super::{QuotaMeter, RequestGate};
+
 /// Minimum-interval rate limiter that paces a provider's HTTP requests.
 ///
 /// Built from a requests-per-second rate; each [`RateLimiter::acquire`] blocks
@@ -9,14 +11,19 @@ use tokio::sync::Mutex;
 /// requests go out no faster than the configured rate. Cloning shares the same
 /// timestamp, so every request paced by a given limiter is throttled together.
 ///
-/// Providers build one per `fetch_urls` call and `acquire()` before each
-/// request, which paces a domain's (often paginated) requests. This is what
-/// makes `--rate-limit` / `--rate-limit-by` actually take effect — previously
-/// the configured rate was stored but never enforced.
+/// Providers build one in `with_rate_limit` and `acquire()` before every
+/// outbound request — each page and each retry attempt. Clones made for
+/// other domains share it, so the rate holds per provider rather than per
+/// domain.
 #[derive(Clone, Debug)]
 pub struct RateLimiter {
     last: Arc<Mutex<Option<Instant>>>,
     min_interval: Duration,
+    /// Run-wide `--global-rate-limit` / `--max-requests` gate, checked before
+    /// this limiter's own pacing.
+    gate: Option<RequestGate>,
+    /// Daily quota meter (`--provider-quota`), ticked for every request.
+    meter: Option<QuotaMeter>,
 }
 
 impl RateLimiter {
@@ -29,9 +36,37 @@ impl RateLimiter {
         Some(Self {
             last: Arc::new(Mutex::new(None)),
             min_interval: Duration::from_secs_f32(1.0 / requests_per_sec),
+            gate: None,
+            meter: None,
         })
     }
 
+    /// Route `limiter` (or, without one, an unpaced limiter) through the
+    /// run-wide `gate`. Providers call this from `with_request_gate`, after
+    /// `with_rate_limit` has built their own limiter.
+    pub fn gated(limiter: Option<Self>, gate: RequestGate) -> Self {
+        let mut limiter = limiter.unwrap_or_else(Self::unpaced);
+        limiter.gate = Some(gate);
+        limiter
+    }
+
+    /// Count every request through `limiter` (or an unpaced one) on `meter`.
+    /// Providers call this from `with_quota`, after the rate limit and gate.
+    pub fn metered(limiter: Option<Self>, meter: QuotaMeter) -> Self {
+        let mut limiter = limiter.unwrap_or_else(Self::unpaced);
+        limiter.meter = Some(meter);
+        limiter
+    }
+
+    fn unpaced() -> Self {
+        Self {
+            last: Arc::new(Mutex::new(None)),
+            min_interval: Duration::ZERO,
+            gate: None,
+            meter: None,
+        }
+    }
+
     /// Convenience constructor from an `Option<f32>` rate, so callers can write
     /// `RateLimiter::from_rate(self.rate_limit)`.
     pub fn from_rate(requests_per_sec: Option<f32>) -> Option<Self> {
@@ -42,6 +77,20 @@ impl RateLimiter {
     /// lock is held across the sleep so concurrent callers queue rather than
     /// all firing at once.
     pub async fn acquire(&self) {
+        if let Some(gate) = &self.gate {
+            gate.acquire().await;
+        }
+        self.pace().await;
+        if let Some(meter) = &self.meter {
+            meter.hit();
+        }
+    }
+
+    /// The interval wait alone, without the gate.
+    pub(super) async fn pace(&self) {
+        if self.min_interval.is_zero() {
+            return;
+        }
         let mut guard = self.last.lock().await;
         if let Some(prev) = *guard {
             let elapsed = prev.elapsed();
@@ -101,6 +150,31 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_gated_limiter_spends_the_shared_budget() {
+        let gate = RequestGate::new(None, Some(1)).unwrap();
+        let limiter = RateLimiter::gated(None, gate.clone());
+        limiter.acquire().await;
+        let blocked = tokio::time::timeout(Duration::from_millis(50), limiter.acquire()).await;
+        assert!(
+            blocked.is_err(),
+            "second request must wait on the spent budget"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_metered_limiter_counts_every_request() {
+        let meter = QuotaMeter::new("vt", Some(500), 0);
+        let gate = RequestGate::new(None, Some(10)).unwrap();
+        let limiter = RateLimiter::gated(None, gate);
+        let limiter = RateLimiter::metered(Some(limiter), meter.clone());
+        let clone = limiter.clone();
+        limiter.acquire().await;
+        clone.acquire().await;
+        assert_eq!(meter.sent(), 2);
+        assert!(limiter.gate.is_some(), "metering keeps the gate");
+    }
+
     #[tokio::test]
     async fn test_first_acquire_does_not_block() {
         let limiter = RateLimiter::new(1.0).unwrap(); // 1s interval
@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+
+use super::RateLimiter;
+
+/// Run-wide request gate for `--global-rate-limit` and `--max-requests`.
+///
+/// One gate is built per run and handed (via [`NetworkSettings`]) to every
+/// provider and tester, which pass through it before each outbound request,
+/// retries included. Clones share the same pacing and budget, so the limits
+/// hold across all providers and domains together rather than per component.
+///
+/// When the budget runs out, the gate signals [`exhausted`](Self::exhausted)
+/// and any further [`acquire`](Self::acquire) never completes: callers run
+/// requests under their cancellation token, so the runner cancels the run and
+/// each component returns what it has, just as with `--max-time`.
+///
+/// [`NetworkSettings`]: super::NetworkSettings
+#[derive(Clone, Debug)]
+pub struct RequestGate {
+    inner: Arc<GateInner>,
+}
+
+#[derive(Debug)]
+struct GateInner {
+    pacer: Option<RateLimiter>,
+    budget: Option<u64>,
+    remaining: AtomicU64,
+    exhausted: CancellationToken,
+}
+
+impl RequestGate {
+    /// Build a gate from a global rate (requests per second) and a total
+    /// request budget. Returns `None` when neither limit is set.
+    pub fn new(requests_per_sec: Option<f32>, max_requests: Option<u64>) -> Option<Self> {
+        let pacer = RateLimiter::from_rate(requests_per_sec);
+        if pacer.is_none() && max_requests.is_none() {
+            return None;
+        }
+        Some(RequestGate {
+            inner: Arc::new(GateInner {
+                pacer,
+                budget: max_requests,
+                remaining: AtomicU64::new(max_requests.unwrap_or(0)),
+                exhausted: CancellationToken::new(),
+            }),
+        })
+    }
+
+    /// Take one request from the budget and wait for the global rate. Never
+    /// completes once the budget is spent.
+    pub async fn acquire(&self) {
+        if self.inner.budget.is_some() {
+            let took = self
+                .inner
+                .remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+                .is_ok();
+            if !took {
+                self.inner.exhausted.cancel();
+                std::future::pending::<()>().await;
+            }
+        }
+        if let Some(pacer) = &self.inner.pacer {
+            pacer.pace().await;
+        }
+    }
+
+    /// Resolves once a request has been refused for lack of budget.
+    pub async fn exhausted(&self) {
+        self.inner.exhausted.cancelled().await;
+    }
+
+    /// Whether a request has been refused for lack of budget.
+    pub fn is_exhausted(&self) -> bool {
+        self.inner.exhausted.is_cancelled()
+    }
+
+    /// The `--max-requests` budget, if one was set.
+    pub fn budget(&self) -> Option<u64> {
+        self.inner.budget
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_no_limits_means_no_gate() {
+        assert!(RequestGate::new(None, None).is_none());
+        assert!(RequestGate::new(Some(0.0), None).is_none());
+        assert!(RequestGate::new(None, Some(10)).is_some());
+        assert!(RequestGate::new(Some(5.0), None).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_budget_is_shared_and_then_blocks() {
+        let gate = RequestGate::new(None, Some(2)).unwrap();
+        let clone = gate.clone();
+        gate.acquire().await;
+        clone.acquire().await;
+
+        // Third request: refused, signalled, and never completes.
+        let blocked = tokio::time::timeout(Duration::from_millis(50), gate.acquire()).await;
+        assert!(blocked.is_err());
+        tokio::time::timeout(Duration::from_millis(50), clone.exhausted())
+            .await
+            .expect("exhaustion should be signalled");
+        assert!(gate.is_exhausted());
+    }
+
+    #[tokio::test]
+    async fn test_global_rate_paces_clones() {
+        let gate = RequestGate::new(Some(20.0), None).unwrap();
+        let clone = gate.clone();
+        let start = Instant::now();
+        gate.acquire().await;
+        clone.acquire().await; // ~50ms after the first
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
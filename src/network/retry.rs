@@ -0,0 +1,142 @@
+use rand::RngExt;
+use reqwest::header::HeaderMap;
+use reqwest::StatusCode;
+use std::time::Duration;
+
+use crate::utils::determinism;
+
+/// Delay before the first retry; each further retry doubles it.
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Ceiling on the computed back-off, before jitter.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Ceiling on a server-requested `Retry-After`, so a hostile or absurd header
+/// can't stall a run.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Exponential back-off with jitter for retry number `attempt` (1-based):
+/// 500ms, 1s, 2s, … capped at 30s, of which a random half is shaved off so
+/// concurrent domains retrying the same upstream don't hit it in lockstep.
+pub fn backoff_delay(attempt: u32) -> Duration {
+    let exp = BASE_BACKOFF.saturating_mul(1u32 << attempt.saturating_sub(1).min(16));
+    let delay = exp.min(MAX_BACKOFF);
+    let jitter = match determinism::rng(determinism::stream::RETRY_JITTER, attempt.into()) {
+        Some(mut rng) => rng.random::<f64>(),
+        None => rand::random::<f64>(),
+    };
+    delay / 2 + delay.mul_f64(jitter / 2.0)
+}
+
+/// Parse a `Retry-After` response header into a sleep duration so a throttled
+/// request waits as long as the server asked before retrying. Both the
+/// delta-seconds form (`Retry-After: 30`) and the HTTP-date form are honored;
+/// a date already in the past yields `None` and the caller falls back to its
+/// normal back-off. The value is capped at 60 seconds.
+pub fn retry_after_delay(headers: &HeaderMap) -> Option<Duration> {
+    let raw = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    let delay = match raw.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(raw).ok()?;
+            (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .ok()
+                .filter(|d| !d.is_zero())?
+        }
+    };
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+/// The `Retry-After` delay to honor before retrying a failed response. Only
+/// 429 and 503 carry a meaningful one.
+pub fn server_retry_delay(status: StatusCode, headers: &HeaderMap) -> Option<Duration> {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE
+    )
+    .then(|| retry_after_delay(headers))
+    .flatten()
+}
+
+/// Sleep before retry number `attempt`: as long as the server asked when it
+/// sent a `Retry-After` (see [`server_retry_delay`]), otherwise the jittered
+/// exponential [`backoff_delay`].
+pub async fn wait_before_retry(attempt: u32, server_delay: Option<Duration>) {
+    tokio::time::sleep(server_delay.unwrap_or_else(|| backoff_delay(attempt))).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, RETRY_AFTER};
+
+    #[test]
+    fn test_backoff_delay_grows_with_jitter() {
+        for (attempt, full) in [(1, 500), (2, 1000), (3, 2000), (8, 30_000), (40, 30_000)] {
+            let full = Duration::from_millis(full);
+            for _ in 0..20 {
+                let d = backoff_delay(attempt);
+                assert!(d >= full / 2 && d <= full, "attempt {attempt}: {d:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_after_delay_parses_seconds() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(retry_after_delay(&headers), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_retry_after_delay_caps_large_values() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("100000"));
+        assert_eq!(retry_after_delay(&headers), Some(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_retry_after_delay_http_date() {
+        let empty = HeaderMap::new();
+        assert_eq!(retry_after_delay(&empty), None);
+
+        // In the past: nothing left to wait for.
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after_delay(&headers), None);
+
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(20))
+            .format("%a, %d %b %Y %H:%M:%S GMT")
+            .to_string();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(&soon).unwrap());
+        let delay = retry_after_delay(&headers).unwrap();
+        assert!(delay > Duration::from_secs(15) && delay <= Duration::from_secs(20));
+    }
+
+    #[test]
+    fn test_server_retry_delay_only_for_throttling_statuses() {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("5"));
+        let five = Some(Duration::from_secs(5));
+        assert_eq!(
+            server_retry_delay(StatusCode::TOO_MANY_REQUESTS, &headers),
+            five
+        );
+        assert_eq!(
+            server_retry_delay(StatusCode::SERVICE_UNAVAILABLE, &headers),
+            five
+        );
+        assert_eq!(
+            server_retry_delay(StatusCode::INTERNAL_SERVER_ERROR, &headers),
+            None
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use super::RequestGate;
+
 /// Network scope specifying which components should use the network settings
 #[derive(Clone, Debug, PartialEq, Default)]
 pub enum NetworkScope {
@@ -45,6 +47,23 @@ pub struct NetworkSettings {
 
     /// Which components should use these network settings
     pub scope: NetworkScope,
+
+    /// Run-wide pacing and request budget (`--global-rate-limit`,
+    /// `--max-requests`). Shared by every provider and tester regardless of
+    /// `scope`.
+    pub request_gate: Option<RequestGate>,
+
+    /// Largest response body testers will download (`--max-body-bytes`;
+    /// 0 = no cap). Applied regardless of `scope`.
+    pub max_body_bytes: u64,
+
+    /// HTTP version testers speak (`--http-version`). Applied regardless of
+    /// `scope`, since no other option covers it.
+    pub http_version: super::client::HttpVersion,
+
+    /// Daily quota meters for keyed providers, by provider id. Filled from
+    /// the cache in main before providers are built; empty without a cache.
+    pub quotas: super::QuotaBook,
 }
 
 impl Default for NetworkSettings {
@@ -60,6 +79,10 @@ impl Default for NetworkSettings {
             rate_limit: None,
             include_subdomains: false,
             scope: NetworkScope::All,
+            request_gate: None,
+            max_body_bytes: super::DEFAULT_MAX_BODY_BYTES,
+            http_version: super::client::HttpVersion::Auto,
+            quotas: super::QuotaBook::new(),
         }
     }
 }
@@ -148,6 +171,10 @@ impl NetworkSettings {
             settings = settings.with_rate_limit(Some(rate));
         }
 
+        settings.request_gate = RequestGate::new(args.global_rate_limit, args.max_requests);
+        settings.max_body_bytes = args.max_body_bytes;
+        settings.http_version = args.http_version.unwrap_or_default();
+
         if let Some(proxy) = &args.proxy {
             settings = settings.with_proxy(Some(proxy.clone()));
 
@@ -160,10 +187,108 @@ impl NetworkSettings {
     }
 }
 
+/// Network settings for a single provider, layered over the global
+/// [`NetworkSettings`] (`--provider-opt wayback:timeout=300` or a
+/// `[provider.wayback]` config section). Unset fields keep the global value.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProviderNetworkOverride {
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub rate_limit: Option<f32>,
+    pub proxy: Option<String>,
+    pub insecure: Option<bool>,
+    pub random_agent: Option<bool>,
+}
+
+impl ProviderNetworkOverride {
+    /// Keys accepted by [`ProviderNetworkOverride::set`].
+    pub const KEYS: &'static [&'static str] = &[
+        "timeout",
+        "retries",
+        "rate_limit",
+        "proxy",
+        "insecure",
+        "random_agent",
+    ];
+
+    /// Set one field from its textual `key=value` form, validating the value.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        let invalid = |expected: &str| format!("invalid {key} {value:?}: expected {expected}");
+        match key.trim().replace('-', "_").as_str() {
+            "timeout" => match value.parse::<u64>() {
+                Ok(secs) if secs > 0 => self.timeout = Some(secs),
+                _ => return Err(invalid("a positive number of seconds")),
+            },
+            "retries" => self.retries = Some(value.parse().map_err(|_| invalid("a whole number"))?),
+            "rate_limit" => match value.parse::<f32>() {
+                Ok(rate) if rate > 0.0 => self.rate_limit = Some(rate),
+                _ => return Err(invalid("a positive requests-per-second value")),
+            },
+            "proxy" => self.proxy = Some(value.to_string()),
+            "insecure" => {
+                self.insecure = Some(value.parse().map_err(|_| invalid("true or false"))?)
+            }
+            "random_agent" => {
+                self.random_agent = Some(value.parse().map_err(|_| invalid("true or false"))?)
+            }
+            other => {
+                return Err(format!(
+                    "unknown provider option {other:?} (expected one of {})",
+                    Self::KEYS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Layer the fields that are set onto `settings`.
+    pub fn apply_to(&self, settings: &mut NetworkSettings) {
+        if let Some(timeout) = self.timeout {
+            settings.timeout = timeout;
+        }
+        if let Some(retries) = self.retries {
+            settings.retries = retries;
+        }
+        if let Some(rate) = self.rate_limit {
+            settings.rate_limit = Some(rate);
+        }
+        if let Some(proxy) = &self.proxy {
+            settings.proxy = Some(proxy.clone());
+        }
+        if let Some(insecure) = self.insecure {
+            settings.insecure = insecure;
+        }
+        if let Some(random_agent) = self.random_agent {
+            settings.random_agent = random_agent;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_provider_override_set_and_apply() {
+        let mut o = ProviderNetworkOverride::default();
+        o.set("timeout", "300").unwrap();
+        o.set("retries", " 5 ").unwrap();
+        o.set("rate-limit", "0.5").unwrap();
+        assert!(o.set("timeout", "0").is_err());
+        assert!(o.set("insecure", "yes").is_err());
+        assert!(o.set("colour", "blue").is_err());
+
+        let mut settings = NetworkSettings::new().with_insecure(true);
+        o.apply_to(&mut settings);
+        assert_eq!(settings.timeout, 300);
+        assert_eq!(settings.retries, 5);
+        assert_eq!(settings.rate_limit, Some(0.5));
+        // Unset fields keep the global value
+        assert!(settings.insecure);
+        assert_eq!(settings.proxy, None);
+    }
+
     #[test]
     fn test_network_scope_default() {
         let scope = NetworkScope::default();
@@ -1,5 +1,8 @@
 use rand::prelude::IndexedRandom;
-use rand::RngExt;
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
+
+use crate::utils::determinism;
 
 /// Centralized random User-Agent generator
 ///
@@ -17,7 +20,7 @@ impl UserAgent {
     /// Returns a random realistic User-Agent with desktop/mobile weighting.
     /// Roughly 65% desktop, 35% mobile.
     pub fn random() -> String {
-        let mut rng = rand::rng();
+        let mut rng = Self::rng();
         let pick_mobile = rng.random_bool(0.35);
         if pick_mobile {
             Self::random_mobile()
@@ -28,8 +31,8 @@ impl UserAgent {
 
     /// Returns a random realistic desktop User-Agent.
     pub fn random_desktop() -> String {
-        let mut rng = rand::rng();
-        let desktop_generators: &[fn(&mut rand::rngs::ThreadRng) -> String] = &[
+        let mut rng = Self::rng();
+        let desktop_generators: &[fn(&mut StdRng) -> String] = &[
             Self::ua_win_chrome,
             Self::ua_win_edge,
             Self::ua_win_firefox,
@@ -46,8 +49,8 @@ impl UserAgent {
 
     /// Returns a random realistic mobile User-Agent (phones and tablets).
     pub fn random_mobile() -> String {
-        let mut rng = rand::rng();
-        let mobile_generators: &[fn(&mut rand::rngs::ThreadRng) -> String] = &[
+        let mut rng = Self::rng();
+        let mobile_generators: &[fn(&mut StdRng) -> String] = &[
             Self::ua_ios_iphone_safari,
             Self::ua_ios_ipad_safari,
             Self::ua_android_phone_chrome,
@@ -61,13 +64,13 @@ impl UserAgent {
 
     // ----- Generators: Desktop -----
 
-    fn ua_win_chrome(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_win_chrome(rng: &mut StdRng) -> String {
         let win_nt = Self::pick(rng, &["10.0", "10.0", "10.0", "11.0"]); // Win11 still often reports 10.0; bias toward 10.0
         let (chrome, build, patch) = Self::chrome_ver(rng);
         format!("Mozilla/5.0 (Windows NT {win_nt}; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{chrome}.{patch}.{build} Safari/537.36")
     }
 
-    fn ua_win_edge(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_win_edge(rng: &mut StdRng) -> String {
         let win_nt = Self::pick(rng, &["10.0", "10.0", "11.0"]);
         let (chrome, build, patch) = Self::chrome_ver(rng);
         // Edge uses Edg/ with usually same Chrome major; keep builds close
@@ -75,13 +78,13 @@ impl UserAgent {
         format!("Mozilla/5.0 (Windows NT {win_nt}; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{chrome}.{patch}.{build} Safari/537.36 Edg/{edge_major}.{edge_patch}.{edge_build}")
     }
 
-    fn ua_win_firefox(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_win_firefox(rng: &mut StdRng) -> String {
         let win_nt = Self::pick(rng, &["10.0", "10.0", "11.0"]);
         let ff = Self::firefox_major(rng);
         format!("Mozilla/5.0 (Windows NT {win_nt}; Win64; x64; rv:{ff}.0) Gecko/20100101 Firefox/{ff}.0")
     }
 
-    fn ua_macos_chrome(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_macos_chrome(rng: &mut StdRng) -> String {
         let mac = Self::pick(
             rng,
             &[
@@ -92,26 +95,26 @@ impl UserAgent {
         format!("Mozilla/5.0 (Macintosh; Intel Mac OS X {mac}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{chrome}.{patch}.{build} Safari/537.36")
     }
 
-    fn ua_macos_safari(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_macos_safari(rng: &mut StdRng) -> String {
         let mac = Self::pick(rng, &["12_7_6", "13_6_7", "14_6", "14_5", "14_4_1"]);
         let safari_ver = Self::pick(rng, &["16.6", "17.0", "17.3", "17.4", "17.5", "17.6"]);
         // Safari WebKit build remains commonly 605.1.15 in UA
         format!("Mozilla/5.0 (Macintosh; Intel Mac OS X {mac}) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{safari_ver} Safari/605.1.15")
     }
 
-    fn ua_linux_chrome(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_linux_chrome(rng: &mut StdRng) -> String {
         let (chrome, build, patch) = Self::chrome_ver(rng);
         format!("Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{chrome}.{patch}.{build} Safari/537.36")
     }
 
-    fn ua_linux_firefox(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_linux_firefox(rng: &mut StdRng) -> String {
         let ff = Self::firefox_major(rng);
         format!("Mozilla/5.0 (X11; Linux x86_64; rv:{ff}.0) Gecko/20100101 Firefox/{ff}.0")
     }
 
     // ----- Generators: Mobile -----
 
-    fn ua_ios_iphone_safari(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_ios_iphone_safari(rng: &mut StdRng) -> String {
         let ios = Self::pick(
             rng,
             &[
@@ -124,7 +127,7 @@ impl UserAgent {
         format!("Mozilla/5.0 (iPhone; CPU iPhone OS {ios} like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{version} Mobile/{mobile_build} Safari/604.1")
     }
 
-    fn ua_ios_ipad_safari(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_ios_ipad_safari(rng: &mut StdRng) -> String {
         let ios = Self::pick(
             rng,
             &["16_6", "17_0", "17_1", "17_3", "17_4", "17_5", "17_6"],
@@ -134,7 +137,7 @@ impl UserAgent {
         format!("Mozilla/5.0 (iPad; CPU OS {ios} like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/{version} Mobile/{mobile_build} Safari/604.1")
     }
 
-    fn ua_android_phone_chrome(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_android_phone_chrome(rng: &mut StdRng) -> String {
         let android = Self::pick(rng, &["10", "11", "12", "13", "14"]);
         let device = Self::pick(
             rng,
@@ -159,7 +162,7 @@ impl UserAgent {
         format!("Mozilla/5.0 (Linux; Android {android}; {device}) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/{chrome}.{patch}.{build} Mobile Safari/537.36")
     }
 
-    fn ua_android_tablet_chrome(rng: &mut rand::rngs::ThreadRng) -> String {
+    fn ua_android_tablet_chrome(rng: &mut StdRng) -> String {
         let android = Self::pick(rng, &["10", "11", "12", "13", "14"]);
         let device = Self::pick(
             rng,
@@ -177,8 +180,15 @@ impl UserAgent {
 
     // ----- Helpers -----
 
+    /// The run's seeded generator under `--deterministic` (so every request
+    /// carries the same User-Agent), a fresh random one otherwise.
+    fn rng() -> StdRng {
+        determinism::rng(determinism::stream::USER_AGENT, 0)
+            .unwrap_or_else(|| StdRng::from_rng(&mut rand::rng()))
+    }
+
     /// Picks a random element from slice.
-    fn pick<T: Clone>(rng: &mut rand::rngs::ThreadRng, vals: &[T]) -> T {
+    fn pick<T: Clone>(rng: &mut StdRng, vals: &[T]) -> T {
         vals.choose(rng).expect("slice not empty").clone()
     }
 
@@ -187,7 +197,7 @@ impl UserAgent {
     /// - minor: always 0 in UA (Chrome/<major>.0.<build>.<patch>)
     /// - build: 6000..=7100
     /// - patch: 10..=200
-    fn chrome_ver(rng: &mut rand::rngs::ThreadRng) -> (u32, u32, u32) {
+    fn chrome_ver(rng: &mut StdRng) -> (u32, u32, u32) {
         let major = rng.random_range(120..=128);
         let build = rng.random_range(6000..=7100);
         let patch = rng.random_range(10..=200);
@@ -195,7 +205,7 @@ impl UserAgent {
     }
 
     /// Generates a realistic Firefox major version: 115..=130
-    fn firefox_major(rng: &mut rand::rngs::ThreadRng) -> u32 {
+    fn firefox_major(rng: &mut StdRng) -> u32 {
         rng.random_range(115..=130)
     }
 }
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+use super::compress::read_output_file;
+use super::readback::parse_entries;
+use super::{OutputFile, Outputter, UrlData};
+
+/// Formats `--append` can read back from an existing output file.
+pub const APPENDABLE_FORMATS: &[&str] = &["plain", "json", "csv"];
+
+/// `--append`: add `urls` to the output file a previous run left at `path`,
+/// skipping URLs it already lists. Plain output gets the unseen lines
+/// appended; JSON and CSV can't simply grow, so they're read back and
+/// rewritten with the unseen entries after the existing ones. A missing file
+/// is created. Returns how many URLs were added.
+pub fn append_output(
+    outputter: &dyn Outputter,
+    format: &str,
+    urls: &[UrlData],
+    path: &Path,
+) -> Result<usize> {
+    let existing_text = if path.exists() {
+        read_output_file(path)?
+    } else {
+        String::new()
+    };
+    let format = format.to_lowercase();
+    let existing =
+        parse_entries(&existing_text, &format).with_context(|| path.display().to_string())?;
+
+    let seen: HashSet<&str> = existing.iter().map(|entry| entry.url.as_str()).collect();
+    let mut added = HashSet::new();
+    let unseen: Vec<UrlData> = urls
+        .iter()
+        .filter(|entry| !seen.contains(entry.url.as_str()) && added.insert(entry.url.as_str()))
+        .cloned()
+        .collect();
+
+    match format.as_str() {
+        "json" | "csv" => {
+            let mut merged = existing;
+            merged.extend(unseen.iter().cloned());
+            outputter.output(&merged, Some(path.to_path_buf()), false)?;
+        }
+        _ => {
+            let mut file = OutputFile::append(path)?;
+            for (i, url_data) in unseen.iter().enumerate() {
+                let line = outputter.format(url_data, i == unseen.len() - 1);
+                file.write_all(console::strip_ansi_codes(&line).as_bytes())
+                    .context("Failed to write to output file")?;
+            }
+            file.finish()?;
+        }
+    }
+    Ok(unseen.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{CsvOutputter, JsonOutputter, PlainOutputter};
+
+    #[test]
+    fn test_append_output_skips_known_urls() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let first = vec![
+            UrlData::with_status("https://example.com/a".into(), "200 OK".into()),
+            UrlData::new("https://example.com/b".into()),
+        ];
+        let mut title = UrlData::new("https://example.com/c".into());
+        title.title = Some("Hello, \"world\"\nagain".into());
+        let second = vec![
+            UrlData::new("https://example.com/b".into()),
+            title,
+            UrlData::new("https://example.com/c".into()),
+        ];
+
+        let plain = dir.path().join("urls.txt.gz");
+        assert_eq!(
+            append_output(&PlainOutputter::new(), "plain", &first, &plain)?,
+            2
+        );
+        assert_eq!(
+            append_output(&PlainOutputter::new(), "plain", &second, &plain)?,
+            1
+        );
+        assert_eq!(
+            read_output_file(&plain)?,
+            "https://example.com/a [200 OK]\nhttps://example.com/b\nhttps://example.com/c [Hello, \"world\"%0Aagain]\n"
+        );
+
+        for (format, outputter) in [
+            ("json", Box::new(JsonOutputter::new()) as Box<dyn Outputter>),
+            ("csv", Box::new(CsvOutputter::new())),
+        ] {
+            let path = dir.path().join(format!("urls.{format}"));
+            append_output(outputter.as_ref(), format, &first, &path)?;
+            assert_eq!(
+                append_output(outputter.as_ref(), format, &second, &path)?,
+                1
+            );
+            let entries = parse_entries(&read_output_file(&path)?, format)?;
+            let urls: Vec<&str> = entries.iter().map(|e| e.url.as_str()).collect();
+            assert_eq!(
+                urls,
+                [
+                    "https://example.com/a",
+                    "https://example.com/b",
+                    "https://example.com/c"
+                ],
+                "{format}"
+            );
+            assert_eq!(entries[0].status.as_deref(), Some("200 OK"), "{format}");
+            assert_eq!(
+                entries[2].title.as_deref(),
+                Some("Hello, \"world\"\nagain"),
+                "{format}"
+            );
+        }
+        Ok(())
+    }
+}
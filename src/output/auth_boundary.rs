@@ -0,0 +1,217 @@
+/// Detects authentication boundaries from `--check-status` results by grouping
+/// URLs under a shared path prefix that consistently answer 401/403.
+use super::UrlData;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+
+/// A path prefix whose every status-checked URL came back 401 or 403.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedPrefix {
+    /// `scheme://host/dir/` — always ends with a slash.
+    pub prefix: String,
+    /// Number of URLs under the prefix that returned 401.
+    pub unauthorized: usize,
+    /// Number of URLs under the prefix that returned 403.
+    pub forbidden: usize,
+}
+
+impl ProtectedPrefix {
+    /// Total number of URLs grouped under this prefix.
+    pub fn total(&self) -> usize {
+        self.unauthorized + self.forbidden
+    }
+}
+
+/// Minimum number of URLs a prefix needs before it is reported. A single 403
+/// is usually a one-off (a stray `.htaccess`), not an auth boundary.
+const MIN_GROUP_SIZE: usize = 2;
+
+#[derive(Default)]
+struct Tally {
+    unauthorized: usize,
+    forbidden: usize,
+    other: usize,
+}
+
+/// Directory prefixes of `url`, shortest first: `https://h/a/b/c.php` yields
+/// `https://h/a/` and `https://h/a/b/`. The root is skipped — "the whole site
+/// is protected" is better read from the status column itself.
+fn directory_prefixes(url: &str) -> Vec<String> {
+    let Ok(parsed) = url::Url::parse(url) else {
+        return Vec::new();
+    };
+    let Some(host) = parsed.host_str() else {
+        return Vec::new();
+    };
+    let origin = match parsed.port() {
+        Some(port) => format!("{}://{host}:{port}", parsed.scheme()),
+        None => format!("{}://{host}", parsed.scheme()),
+    };
+
+    let segments: Vec<&str> = parsed.path().split('/').filter(|s| !s.is_empty()).collect();
+    // The last segment is the resource itself unless the path ends with `/`.
+    let dir_count = if parsed.path().ends_with('/') {
+        segments.len()
+    } else {
+        segments.len().saturating_sub(1)
+    };
+
+    let mut prefixes = Vec::with_capacity(dir_count);
+    let mut current = origin;
+    for seg in &segments[..dir_count] {
+        current.push('/');
+        current.push_str(seg);
+        prefixes.push(format!("{current}/"));
+    }
+    prefixes
+}
+
+/// Leading numeric status code of a `"401 Unauthorized"`-style status string.
+fn status_code(status: &str) -> Option<u16> {
+    status.split_whitespace().next()?.parse().ok()
+}
+
+/// Group status-checked URLs by directory prefix and return the prefixes where
+/// every URL answered 401/403. Nested prefixes under an already-reported
+/// ancestor are folded into it so each boundary is listed once.
+pub fn protected_prefixes(urls: &[UrlData]) -> Vec<ProtectedPrefix> {
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    for entry in urls {
+        let Some(code) = entry.status.as_deref().and_then(status_code) else {
+            continue;
+        };
+        for prefix in directory_prefixes(&entry.url) {
+            let tally = tallies.entry(prefix).or_default();
+            match code {
+                401 => tally.unauthorized += 1,
+                403 => tally.forbidden += 1,
+                _ => tally.other += 1,
+            }
+        }
+    }
+
+    // BTreeMap order puts an ancestor directly before its descendants, so a
+    // single pass with the last reported prefix is enough to fold them.
+    let mut result: Vec<ProtectedPrefix> = Vec::new();
+    for (prefix, tally) in tallies {
+        let protected = tally.unauthorized + tally.forbidden;
+        if tally.other > 0 || protected < MIN_GROUP_SIZE {
+            continue;
+        }
+        if result
+            .last()
+            .is_some_and(|p: &ProtectedPrefix| prefix.starts_with(&p.prefix))
+        {
+            continue;
+        }
+        result.push(ProtectedPrefix {
+            prefix,
+            unauthorized: tally.unauthorized,
+            forbidden: tally.forbidden,
+        });
+    }
+    result
+}
+
+/// The "protected endpoints" section of `--stats-markdown`: a heading and one
+/// table row per prefix. Empty when nothing was found.
+pub fn protected_endpoints_markdown(prefixes: &[ProtectedPrefix]) -> String {
+    if prefixes.is_empty() {
+        return String::new();
+    }
+    let mut out = String::from("\n## Protected endpoints\n\n");
+    out.push_str("Path prefixes whose status-checked URLs all answered 401/403.\n\n");
+    out.push_str("| URLs | 401 | 403 | Prefix |\n");
+    out.push_str("|---:|---:|---:|---|\n");
+    for p in prefixes {
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            p.total(),
+            p.unauthorized,
+            p.forbidden,
+            p.prefix.replace('|', "\\|")
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(url: &str, status: &str) -> UrlData {
+        UrlData::with_status(url.to_string(), status.to_string())
+    }
+
+    #[test]
+    fn test_directory_prefixes() {
+        assert_eq!(
+            directory_prefixes("https://example.com/a/b/c.php?x=1"),
+            vec!["https://example.com/a/", "https://example.com/a/b/"]
+        );
+        assert_eq!(
+            directory_prefixes("http://example.com:8080/admin/"),
+            vec!["http://example.com:8080/admin/"]
+        );
+        assert!(directory_prefixes("https://example.com/index.html").is_empty());
+        assert!(directory_prefixes("not a url").is_empty());
+    }
+
+    #[test]
+    fn test_protected_prefixes_groups_consistent_denials() {
+        let urls = vec![
+            data("https://example.com/admin/users", "401 Unauthorized"),
+            data("https://example.com/admin/settings", "403 Forbidden"),
+            data("https://example.com/admin/reports/q1", "401 Unauthorized"),
+            data("https://example.com/public/a", "200 OK"),
+            data("https://example.com/public/b", "403 Forbidden"),
+        ];
+        let found = protected_prefixes(&urls);
+        assert_eq!(
+            found,
+            vec![ProtectedPrefix {
+                prefix: "https://example.com/admin/".to_string(),
+                unauthorized: 2,
+                forbidden: 1,
+            }]
+        );
+        assert_eq!(found[0].total(), 3);
+    }
+
+    #[test]
+    fn test_protected_prefixes_requires_minimum_group_and_status() {
+        let urls = vec![
+            data("https://example.com/secret/one", "403 Forbidden"),
+            UrlData::new("https://example.com/secret/two".to_string()),
+            data("https://example.com/x/y", "Status check failed"),
+        ];
+        assert!(protected_prefixes(&urls).is_empty());
+    }
+
+    #[test]
+    fn test_protected_endpoints_markdown() {
+        let prefixes = vec![
+            ProtectedPrefix {
+                prefix: "https://example.com/admin/".to_string(),
+                unauthorized: 2,
+                forbidden: 1,
+            },
+            ProtectedPrefix {
+                prefix: "https://example.com/a|b/".to_string(),
+                unauthorized: 0,
+                forbidden: 2,
+            },
+        ];
+        assert_eq!(
+            protected_endpoints_markdown(&prefixes),
+            "\n## Protected endpoints\n\n\
+             Path prefixes whose status-checked URLs all answered 401/403.\n\n\
+             | URLs | 401 | 403 | Prefix |\n\
+             |---:|---:|---:|---|\n\
+             | 3 | 2 | 1 | https://example.com/admin/ |\n\
+             | 2 | 0 | 2 | https://example.com/a\\|b/ |\n"
+        );
+        assert_eq!(protected_endpoints_markdown(&[]), "");
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::io::Write;
+use std::path::PathBuf;
+use url::Url;
+
+use super::sitemap::xml_escape;
+use super::{OutputFile, Outputter, UrlData};
+
+/// Wrap `value` in CDATA, splitting any `]]>` it contains across sections.
+fn cdata(value: &str) -> String {
+    format!("<![CDATA[{}]]>", value.replace("]]>", "]]]]><![CDATA[>"))
+}
+
+/// Burp's coarse MIME type label for a `Content-Type`, as shown in its
+/// site map. Empty when unknown.
+fn burp_mime_type(content_type: Option<&str>) -> &'static str {
+    let Some(content_type) = content_type.map(str::to_ascii_lowercase) else {
+        return "";
+    };
+    [
+        ("html", "HTML"),
+        ("json", "JSON"),
+        ("javascript", "script"),
+        ("ecmascript", "script"),
+        ("css", "CSS"),
+        ("xml", "XML"),
+        ("image/", "image"),
+        ("text/plain", "text"),
+    ]
+    .iter()
+    .find(|(needle, _)| content_type.contains(needle))
+    .map_or("", |(_, label)| label)
+}
+
+/// The `<item>` Burp would save for a GET of `url_data`. `None` for URLs
+/// Burp can't request (not HTTP(S), or no host).
+fn burp_item(url_data: &UrlData, time: &str) -> Option<String> {
+    let url = Url::parse(&url_data.url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?;
+    let port = url.port_or_known_default()?;
+    let mut path = url.path().to_string();
+    if let Some(query) = url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+    let extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|last| last.rsplit_once('.'))
+        .map_or("null", |(_, ext)| ext);
+    let host_header = match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host_header}\r\nUser-Agent: {}\r\nAccept: */*\r\nConnection: close\r\n\r\n",
+        crate::network::default_user_agent()
+    );
+    let status = url_data
+        .status
+        .as_deref()
+        .and_then(|s| s.split_whitespace().next())
+        .filter(|code| code.parse::<u16>().is_ok())
+        .unwrap_or("");
+
+    Some(format!(
+        concat!(
+            "  <item>\n",
+            "    <time>{time}</time>\n",
+            "    <url>{url}</url>\n",
+            "    <host ip=\"\">{host}</host>\n",
+            "    <port>{port}</port>\n",
+            "    <protocol>{protocol}</protocol>\n",
+            "    <method>{method}</method>\n",
+            "    <path>{path}</path>\n",
+            "    <extension>{extension}</extension>\n",
+            "    <request base64=\"true\">{request}</request>\n",
+            "    <status>{status}</status>\n",
+            "    <responselength>{length}</responselength>\n",
+            "    <mimetype>{mime}</mimetype>\n",
+            "    <response base64=\"true\"></response>\n",
+            "    <comment>{comment}</comment>\n",
+            "  </item>\n",
+        ),
+        time = time,
+        url = cdata(url.as_str()),
+        host = xml_escape(host),
+        port = port,
+        protocol = url.scheme(),
+        method = cdata("GET"),
+        path = cdata(&path),
+        extension = xml_escape(extension),
+        request = cdata(&STANDARD.encode(request)),
+        status = status,
+        length = url_data
+            .content_length
+            .map(|len| len.to_string())
+            .unwrap_or_default(),
+        mime = burp_mime_type(url_data.content_type.as_deref()),
+        comment = xml_escape(url_data.title.as_deref().unwrap_or("")),
+    ))
+}
+
+/// Outputter for `--format burp`: the XML Burp Suite writes for "Save
+/// items", one GET request per URL (status, length, MIME type and title
+/// filled in when tested), ready to load into the site map with an import
+/// extension. URLs that aren't HTTP(S) are left out.
+#[derive(Debug, Clone)]
+pub struct BurpOutputter;
+
+impl BurpOutputter {
+    pub fn new() -> Self {
+        BurpOutputter
+    }
+
+    fn write_items(&self, urls: &[UrlData], mut out: impl Write) -> std::io::Result<()> {
+        let now = chrono::Utc::now();
+        writeln!(out, r#"<?xml version="1.0"?>"#)?;
+        writeln!(
+            out,
+            r#"<items burpVersion="" exportTime="{}">"#,
+            now.format("%a %b %d %H:%M:%S UTC %Y")
+        )?;
+        for url_data in urls {
+            out.write_all(self.format(url_data, false).as_bytes())?;
+        }
+        writeln!(out, "</items>")?;
+        out.flush()
+    }
+}
+
+impl Outputter for BurpOutputter {
+    /// One `<item>` element, or nothing for a URL Burp can't request
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        let time = chrono::Utc::now()
+            .format("%a %b %d %H:%M:%S UTC %Y")
+            .to_string();
+        burp_item(url_data, &time).unwrap_or_default()
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
+        match output_path {
+            Some(path) => {
+                let mut file = OutputFile::create(&path)?;
+                self.write_items(urls, &mut file)
+                    .context("Failed to write to output file")?;
+                file.finish()
+            }
+            None if silent => Ok(()),
+            None => self
+                .write_items(urls, std::io::stdout().lock())
+                .context("Failed to write Burp items"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burp_item_describes_a_get_request() {
+        let mut tested = UrlData::with_status(
+            "https://example.com:8443/app/login.php?next=/a".into(),
+            "200 OK".into(),
+        );
+        tested.content_type = Some("text/html; charset=utf-8".into());
+        tested.content_length = Some(1024);
+        tested.title = Some("Sign in & go".into());
+        let item = burp_item(&tested, "Mon Jan 01 00:00:00 UTC 2024").unwrap();
+
+        assert!(item.contains("<host ip=\"\">example.com</host>"));
+        assert!(item.contains("<port>8443</port>"));
+        assert!(item.contains("<protocol>https</protocol>"));
+        assert!(item.contains("<path><![CDATA[/app/login.php?next=/a]]></path>"));
+        assert!(item.contains("<extension>php</extension>"));
+        assert!(item.contains("<status>200</status>"));
+        assert!(item.contains("<responselength>1024</responselength>"));
+        assert!(item.contains("<mimetype>HTML</mimetype>"));
+        assert!(item.contains("<comment>Sign in &amp; go</comment>"));
+
+        let start = item.find("<request base64=\"true\"><![CDATA[").unwrap() + 32;
+        let end = start + item[start..].find("]]>").unwrap();
+        let request = String::from_utf8(STANDARD.decode(&item[start..end]).unwrap()).unwrap();
+        assert!(request
+            .starts_with("GET /app/login.php?next=/a HTTP/1.1\r\nHost: example.com:8443\r\n"));
+        assert_eq!(cdata("a]]>b"), "<![CDATA[a]]]]><![CDATA[>b]]>");
+
+        let untested = burp_item(&UrlData::new("http://example.com/".into()), "t").unwrap();
+        assert!(untested.contains("<port>80</port>"));
+        assert!(untested.contains("<extension>null</extension>"));
+        assert!(untested.contains("<status></status>"));
+        assert!(burp_item(&UrlData::new("ftp://example.com/f".into()), "t").is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Compression applied to output files, picked from the `--output`
+/// extension (`.gz`, `.zst`) or forced with `--compress`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Gzip,
+    Zstd,
+}
+
+impl Compression {
+    /// The compression a path's extension asks for, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "gz" | "gzip" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Filename extension for this compression, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Compression::Gzip => "gz",
+            Compression::Zstd => "zst",
+        }
+    }
+
+    /// `path` with this compression's extension appended, unless its
+    /// extension already asks for this compression.
+    pub fn apply_to(self, path: &Path) -> PathBuf {
+        if Compression::from_path(path) == Some(self) {
+            return path.to_path_buf();
+        }
+        let mut name = path.as_os_str().to_owned();
+        name.push(".");
+        name.push(self.extension());
+        PathBuf::from(name)
+    }
+}
+
+impl std::str::FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            _ => Err(format!("Invalid compression: {s}. Use gzip or zstd")),
+        }
+    }
+}
+
+/// A buffered output file, compressed when its extension says so. Call
+/// [`finish`](OutputFile::finish) once done: compressed streams only get
+/// their trailer there.
+pub enum OutputFile {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl OutputFile {
+    /// Create (or truncate) `path`.
+    pub fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create output file {}", path.display()))?;
+        Self::wrap(file, Compression::from_path(path))
+    }
+
+    /// Open `path` for appending. Compressed output gets a new gzip member or
+    /// zstd frame, which decompressors read as one continuous stream.
+    pub fn append(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open output file {}", path.display()))?;
+        Self::wrap(file, Compression::from_path(path))
+    }
+
+    fn wrap(file: File, compression: Option<Compression>) -> Result<Self> {
+        let out = BufWriter::new(file);
+        Ok(match compression {
+            None => OutputFile::Plain(out),
+            Some(Compression::Gzip) => {
+                OutputFile::Gzip(GzEncoder::new(out, flate2::Compression::default()))
+            }
+            Some(Compression::Zstd) => {
+                OutputFile::Zstd(zstd::Encoder::new(out, 0).context("Failed to start zstd output")?)
+            }
+        })
+    }
+
+    /// Write any compression trailer and flush everything to disk.
+    pub fn finish(self) -> Result<()> {
+        let out = match self {
+            OutputFile::Plain(out) => out,
+            OutputFile::Gzip(encoder) => encoder.finish()?,
+            OutputFile::Zstd(encoder) => encoder.finish()?,
+        };
+        out.into_inner()
+            .map_err(|e| e.into_error())
+            .context("Failed to write to output file")?;
+        Ok(())
+    }
+}
+
+/// Read a file [`OutputFile`] wrote, decompressing by extension.
+pub fn read_output_file(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut reader: Box<dyn Read> = match Compression::from_path(path) {
+        None => Box::new(file),
+        Some(Compression::Gzip) => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        Some(Compression::Zstd) => Box::new(zstd::Decoder::new(file)?),
+    };
+    let mut text = String::new();
+    reader
+        .read_to_string(&mut text)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(text)
+}
+
+impl Write for OutputFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            OutputFile::Plain(out) => out.write(buf),
+            OutputFile::Gzip(out) => out.write(buf),
+            OutputFile::Zstd(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            OutputFile::Plain(out) => out.flush(),
+            OutputFile::Gzip(out) => out.flush(),
+            OutputFile::Zstd(out) => out.flush(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_file_compresses_by_extension() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        for name in ["urls.txt", "urls.txt.gz", "urls.txt.zst"] {
+            let path = dir.path().join(name);
+            let mut file = OutputFile::create(&path)?;
+            file.write_all(b"https://example.com/a\n")?;
+            file.finish()?;
+            let mut file = OutputFile::append(&path)?;
+            file.write_all(b"https://example.com/b\n")?;
+            file.finish()?;
+
+            assert_eq!(
+                read_output_file(&path)?,
+                "https://example.com/a\nhttps://example.com/b\n",
+                "{name}"
+            );
+        }
+
+        assert_eq!(
+            Compression::Gzip.apply_to(Path::new("out/urls.txt")),
+            PathBuf::from("out/urls.txt.gz")
+        );
+        assert_eq!(
+            Compression::Zstd.apply_to(Path::new("urls.zst")),
+            PathBuf::from("urls.zst")
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use super::UrlData;
+
+/// Host bucket for URLs that don't parse, as in `--output-dir`.
+const UNKNOWN_HOST: &str = "_unknown";
+
+/// `--count-by host`: how many of `urls` each host has, most first, ties by
+/// host name.
+pub fn count_by_host(urls: &[UrlData]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in urls {
+        let host = url::Url::parse(&entry.url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| UNKNOWN_HOST.to_string());
+        *counts.entry(host).or_insert(0) += 1;
+    }
+    let mut counts: Vec<(String, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+/// What `--count` prints: the number of URLs, or with `--count-by host` one
+/// `host<TAB>count` line per host.
+pub fn render_counts(urls: &[UrlData], count_by: Option<&str>) -> String {
+    match count_by {
+        Some("host") => count_by_host(urls)
+            .into_iter()
+            .map(|(host, n)| format!("{host}\t{n}\n"))
+            .collect(),
+        _ => format!("{}\n", urls.len()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts() {
+        let urls = [
+            UrlData::new("https://b.example.com/1".into()),
+            UrlData::new("https://a.example.com/1".into()),
+            UrlData::new("https://b.example.com/2".into()),
+            UrlData::new("https://a.example.com/2".into()),
+            UrlData::new("https://c.example.com/".into()),
+            UrlData::new("not a url".into()),
+        ];
+        assert_eq!(render_counts(&urls, None), "6\n");
+        assert_eq!(
+            render_counts(&urls, Some("host")),
+            "a.example.com\t2\nb.example.com\t2\n_unknown\t1\nc.example.com\t1\n"
+        );
+        assert_eq!(render_counts(&[], Some("host")), "");
+    }
+}
@@ -0,0 +1,78 @@
+use std::collections::HashSet;
+
+use super::UrlData;
+
+/// Metadata key `--diff` labels entries with: `"new"` or `"removed"`.
+pub const DIFF_KEY: &str = "diff";
+
+/// How a `--diff` run compares to the previous result file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DiffSummary {
+    pub new: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+/// `--diff`: keep only what changed since `previous`. URLs of `current` the
+/// previous file didn't list come first, labelled `new`; then the previous
+/// entries that are gone, labelled `removed` and carrying the data they had
+/// then. URLs in both are dropped and only counted.
+pub fn diff_entries(current: Vec<UrlData>, previous: Vec<UrlData>) -> (Vec<UrlData>, DiffSummary) {
+    let before: HashSet<&str> = previous.iter().map(|entry| entry.url.as_str()).collect();
+    let now: HashSet<&str> = current.iter().map(|entry| entry.url.as_str()).collect();
+    let mut summary = DiffSummary::default();
+    let mut changed = Vec::new();
+    for mut entry in current.iter().cloned() {
+        if before.contains(entry.url.as_str()) {
+            summary.unchanged += 1;
+            continue;
+        }
+        entry.metadata.insert(DIFF_KEY.to_string(), "new".into());
+        changed.push(entry);
+        summary.new += 1;
+    }
+    let mut listed = HashSet::new();
+    for mut entry in previous.iter().cloned() {
+        if now.contains(entry.url.as_str()) || !listed.insert(entry.url.clone()) {
+            continue;
+        }
+        entry
+            .metadata
+            .insert(DIFF_KEY.to_string(), "removed".into());
+        changed.push(entry);
+        summary.removed += 1;
+    }
+    (changed, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_entries_labels_new_and_removed() {
+        let previous = vec![
+            UrlData::with_status("https://example.com/a".into(), "200 OK".into()),
+            UrlData::with_status("https://example.com/gone".into(), "200 OK".into()),
+        ];
+        let current = vec![
+            UrlData::new("https://example.com/a".into()),
+            UrlData::new("https://example.com/fresh".into()),
+        ];
+        let (changed, summary) = diff_entries(current, previous);
+
+        assert_eq!(
+            summary,
+            DiffSummary {
+                new: 1,
+                removed: 1,
+                unchanged: 1
+            }
+        );
+        assert_eq!(changed[0].url, "https://example.com/fresh");
+        assert_eq!(changed[0].metadata[DIFF_KEY], "new");
+        assert_eq!(changed[1].url, "https://example.com/gone");
+        assert_eq!(changed[1].metadata[DIFF_KEY], "removed");
+        assert_eq!(changed[1].status.as_deref(), Some("200 OK"));
+    }
+}
@@ -1,19 +1,64 @@
 /// Implements different URL output formatters
-use super::UrlData;
+use super::{UrlData, SCHEMA_VERSION};
+use crate::providers::SNAPSHOT_FIELDS;
 use colored::*;
 use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt;
 
 /// Helper struct for JSON serialization with guaranteed field order
-/// (url, status, sources). `sources` is omitted when empty so the output
-/// stays backward-compatible with callers that don't ask for attribution.
+/// (url, status, protocol, content_type, content_length, body_sha256,
+/// word_count, line_count, headers, title, sources, metadata,
+/// schema_version). `sources` is omitted when empty so the output stays
+/// backward-compatible with callers that don't ask for attribution. The
+/// layout is described by [`OUTPUT_SCHEMA`](super::OUTPUT_SCHEMA).
 #[derive(Serialize)]
-struct JsonUrlEntry<'a> {
+pub(crate) struct JsonUrlEntry<'a> {
     url: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     status: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_type: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_length: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_sha256: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    word_count: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_count: Option<u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    headers: &'a BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<&'a str>,
     #[serde(skip_serializing_if = "<[String]>::is_empty")]
     sources: &'a [String],
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    metadata: &'a serde_json::Map<String, serde_json::Value>,
+    schema_version: u32,
+}
+
+impl<'a> From<&'a UrlData> for JsonUrlEntry<'a> {
+    fn from(url_data: &'a UrlData) -> Self {
+        JsonUrlEntry {
+            url: &url_data.url,
+            status: url_data.status.as_deref(),
+            protocol: url_data.protocol.as_deref(),
+            content_type: url_data.content_type.as_deref(),
+            content_length: url_data.content_length,
+            body_sha256: url_data.body_sha256.as_deref(),
+            word_count: url_data.word_count,
+            line_count: url_data.line_count,
+            headers: &url_data.headers,
+            title: url_data.title.as_deref(),
+            sources: &url_data.sources,
+            metadata: &url_data.metadata,
+            schema_version: SCHEMA_VERSION,
+        }
+    }
 }
 
 /// Formatter trait for converting URL data to different output formats
@@ -49,23 +94,55 @@ impl Formatter for PlainFormatter {
     fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
         let mut line = match &url_data.status {
             Some(status) => {
-                let status_code_str = status.split_whitespace().next().unwrap_or("");
-                let colored_status = match status_code_str.parse::<u16>() {
-                    Ok(code) => match code {
-                        200..=299 => status.green(),
-                        300..=399 => status.yellow(),
-                        400..=499 => status.red(),
-                        500..=599 => status.magenta(),
-                        _ => status.normal(),
-                    },
-                    Err(_) => status.normal(),
+                let status = plain_escape(status);
+                let status = status.as_ref();
+                let colored_status = match status_color(status) {
+                    Some(color) => status.color(color),
+                    None => status.normal(),
                 };
-                format!("{} [{}]", url_data.url, colored_status)
+                format!("{} [{}]", plain_escape(&url_data.url), colored_status)
             }
-            None => url_data.url.clone(),
+            None => plain_escape(&url_data.url).into_owned(),
         };
+        if let Some(title) = &url_data.title {
+            line.push_str(&format!(" [{}]", plain_escape(title)));
+        }
         if !url_data.sources.is_empty() {
-            line.push_str(&format!(" [{}]", url_data.sources.join(",").cyan()));
+            let sources = plain_escape(&url_data.sources.join(",")).into_owned();
+            line.push_str(&format!(" [{}]", sources.cyan()));
+        }
+        if let Some(params) = url_data
+            .metadata
+            .get("reflected_params")
+            .and_then(|v| v.as_array())
+        {
+            let names: Vec<&str> = params.iter().filter_map(|p| p.as_str()).collect();
+            let tag = format!("xss-candidate: {}", plain_escape(&names.join(",")));
+            line.push_str(&format!(" [{}]", tag.red()));
+        }
+        if let Some(params) = url_data
+            .metadata
+            .get("open_redirect_params")
+            .and_then(|v| v.as_array())
+        {
+            let names: Vec<&str> = params.iter().filter_map(|p| p.as_str()).collect();
+            let tag = format!("open-redirect: {}", plain_escape(&names.join(",")));
+            line.push_str(&format!(" [{}]", tag.red()));
+        }
+        if url_data.metadata.contains_key("sourcemap_script") {
+            line.push_str(&format!(" [{}]", "sourcemap".blue()));
+        }
+        if url_data.metadata.contains_key("js_source") {
+            line.push_str(&format!(" [{}]", "js-endpoint".blue()));
+        }
+        match url_data
+            .metadata
+            .get(super::DIFF_KEY)
+            .and_then(|v| v.as_str())
+        {
+            Some("new") => line.push_str(&format!(" [{}]", "NEW".green())),
+            Some("removed") => line.push_str(&format!(" [{}]", "REMOVED".red())),
+            _ => {}
         }
         line.push('\n');
         line
@@ -76,6 +153,20 @@ impl Formatter for PlainFormatter {
     }
 }
 
+/// Terminal color of a status by class: 2xx green, 3xx blue, 4xx yellow,
+/// 5xx red. Anything else (errors like `timeout`) stays uncolored. `colored`
+/// drops the codes when stdout isn't a TTY or with `--no-color`.
+pub(crate) fn status_color(status: &str) -> Option<Color> {
+    let code = status.split_whitespace().next()?.parse::<u16>().ok()?;
+    match code {
+        200..=299 => Some(Color::Green),
+        300..=399 => Some(Color::Blue),
+        400..=499 => Some(Color::Yellow),
+        500..=599 => Some(Color::Red),
+        _ => None,
+    }
+}
+
 /// JSON formatter that outputs URLs as JSON objects
 #[derive(Debug, Clone)]
 pub struct JsonFormatter;
@@ -89,12 +180,7 @@ impl JsonFormatter {
 
 impl Formatter for JsonFormatter {
     fn format(&self, url_data: &UrlData, is_last: bool) -> String {
-        let entry = JsonUrlEntry {
-            url: &url_data.url,
-            status: url_data.status.as_deref(),
-            sources: &url_data.sources,
-        };
-        let json = serde_json::to_string(&entry).unwrap_or_default();
+        let json = serde_json::to_string(&JsonUrlEntry::from(url_data)).unwrap_or_default();
 
         if is_last {
             format!("{json}\n")
@@ -110,24 +196,33 @@ impl Formatter for JsonFormatter {
 
 /// CSV formatter that outputs URLs in comma-separated format
 #[derive(Debug, Clone)]
-pub struct CsvFormatter;
+pub struct CsvFormatter {
+    columns: Option<CsvColumns>,
+}
 
 impl CsvFormatter {
     /// Create a new CSV formatter
     pub fn new() -> Self {
-        CsvFormatter
+        CsvFormatter { columns: None }
+    }
+
+    /// A CSV formatter with a fixed `--csv-columns` selection
+    pub fn with_columns(columns: CsvColumns) -> Self {
+        CsvFormatter {
+            columns: Some(columns),
+        }
     }
 }
 
 impl Formatter for CsvFormatter {
     fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
-        // Standalone row: include only the columns this entry actually has,
-        // so a single formatted row is self-consistent (no dangling commas).
-        csv_row(
-            url_data,
-            url_data.status.is_some(),
-            !url_data.sources.is_empty(),
-        )
+        match &self.columns {
+            Some(columns) => csv_line(url_data, columns.names()),
+            // Standalone row: include only the columns this entry actually
+            // has, so a single formatted row is self-consistent (no dangling
+            // commas).
+            None => csv_row(url_data, &CsvLayout::of(url_data)),
+        }
     }
 
     fn clone_box(&self) -> Box<dyn Formatter> {
@@ -135,53 +230,229 @@ impl Formatter for CsvFormatter {
     }
 }
 
-/// Build the CSV header line for the given column layout. The `url` column is
-/// always present; `status` / `sources` are included only when the run carries
-/// that data, and the row formatter mirrors exactly the same layout so every
-/// line has an identical column count.
-pub(crate) fn csv_header(has_status: bool, has_sources: bool) -> String {
-    let mut cols = vec!["url"];
-    if has_status {
-        cols.push("status");
+/// Columns `--csv-columns` accepts, besides `header_<name>` for a captured
+/// response header.
+pub const CSV_COLUMNS: &[&str] = &[
+    "url",
+    "status",
+    "protocol",
+    "host",
+    "content_type",
+    "content_length",
+    "body_sha256",
+    "word_count",
+    "line_count",
+    "title",
+    "sources",
+    "timestamp",
+    "archived_status",
+    "mime",
+    "diff",
+];
+
+/// `--csv-columns`: the CSV columns to write, in order, whether or not the
+/// run has data for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvColumns(Vec<String>);
+
+impl CsvColumns {
+    pub fn names(&self) -> &[String] {
+        &self.0
     }
-    if has_sources {
-        cols.push("sources");
+}
+
+impl std::str::FromStr for CsvColumns {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut columns: Vec<String> = Vec::new();
+        for name in s.split(',').map(|name| name.trim().to_ascii_lowercase()) {
+            if name.is_empty() {
+                continue;
+            }
+            let known = CSV_COLUMNS.contains(&name.as_str())
+                || name.strip_prefix("header_").is_some_and(|h| !h.is_empty());
+            if !known {
+                return Err(format!(
+                    "Unknown CSV column: {name}. Use {} or header_<name>",
+                    CSV_COLUMNS.join(", ")
+                ));
+            }
+            if !columns.contains(&name) {
+                columns.push(name);
+            }
+        }
+        if columns.is_empty() {
+            return Err("--csv-columns needs at least one column".to_string());
+        }
+        Ok(CsvColumns(columns))
     }
-    let mut line = cols.join(",");
+}
+
+/// Whether an entry carries any archive snapshot field (see
+/// [`SNAPSHOT_FIELDS`]), which adds the snapshot columns to CSV output.
+pub(crate) fn has_snapshot(url_data: &UrlData) -> bool {
+    SNAPSHOT_FIELDS
+        .iter()
+        .any(|key| url_data.metadata.contains_key(*key))
+}
+
+/// Which optional CSV columns are emitted. The `url` column is always
+/// present; the others only when the run carries that data.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct CsvLayout {
+    pub status: bool,
+    /// `protocol`.
+    pub protocol: bool,
+    /// `content_type` and `content_length`.
+    pub content: bool,
+    /// `body_sha256`, `word_count` and `line_count`.
+    pub body: bool,
+    /// Names of the `--include-headers` headers, one `header_<name>`
+    /// column each.
+    pub headers: Vec<String>,
+    pub title: bool,
+    pub sources: bool,
+    /// The [`SNAPSHOT_FIELDS`] columns.
+    pub snapshot: bool,
+    /// `diff`, the `--diff` label.
+    pub diff: bool,
+}
+
+impl CsvLayout {
+    /// The columns `url_data` has data for.
+    pub(crate) fn of(url_data: &UrlData) -> Self {
+        CsvLayout {
+            status: url_data.status.is_some(),
+            protocol: url_data.protocol.is_some(),
+            content: url_data.has_content_info(),
+            body: url_data.has_body_stats(),
+            headers: url_data.headers.keys().cloned().collect(),
+            title: url_data.title.is_some(),
+            sources: !url_data.sources.is_empty(),
+            snapshot: has_snapshot(url_data),
+            diff: url_data.metadata.contains_key(super::DIFF_KEY),
+        }
+    }
+
+    /// The columns any of `urls` has data for, so a whole run shares one
+    /// header.
+    pub(crate) fn of_all(urls: &[UrlData]) -> Self {
+        urls.iter()
+            .map(Self::of)
+            .fold(Self::default(), |a, b| CsvLayout {
+                status: a.status || b.status,
+                protocol: a.protocol || b.protocol,
+                content: a.content || b.content,
+                body: a.body || b.body,
+                headers: a
+                    .headers
+                    .into_iter()
+                    .chain(b.headers)
+                    .collect::<std::collections::BTreeSet<_>>()
+                    .into_iter()
+                    .collect(),
+                title: a.title || b.title,
+                sources: a.sources || b.sources,
+                snapshot: a.snapshot || b.snapshot,
+                diff: a.diff || b.diff,
+            })
+    }
+
+    /// Column names of this layout, in output order.
+    pub(crate) fn columns(&self) -> Vec<String> {
+        let mut cols = vec!["url"];
+        if self.status {
+            cols.push("status");
+        }
+        if self.protocol {
+            cols.push("protocol");
+        }
+        if self.content {
+            cols.extend(["content_type", "content_length"]);
+        }
+        if self.body {
+            cols.extend(["body_sha256", "word_count", "line_count"]);
+        }
+        let mut names: Vec<String> = cols.into_iter().map(String::from).collect();
+        names.extend(self.headers.iter().map(|name| format!("header_{name}")));
+        if self.title {
+            names.push("title".to_string());
+        }
+        if self.sources {
+            names.push("sources".to_string());
+        }
+        if self.snapshot {
+            names.extend(SNAPSHOT_FIELDS.map(String::from));
+        }
+        if self.diff {
+            names.push(super::DIFF_KEY.to_string());
+        }
+        names
+    }
+}
+
+/// Build the CSV header line naming `columns`. The row formatter emits
+/// exactly the same columns so every line has an identical column count.
+pub(crate) fn csv_header(columns: &[String]) -> String {
+    let mut line = columns.join(",");
     line.push('\n');
     line
 }
 
 /// Format one CSV data row for the given column layout. Must agree with
 /// [`csv_header`] on which columns are emitted so header and body stay aligned.
-pub(crate) fn csv_row(url_data: &UrlData, has_status: bool, has_sources: bool) -> String {
-    let mut fields = vec![csv_escape(&url_data.url)];
-    if has_status {
-        fields.push(
-            url_data
-                .status
-                .as_deref()
-                .map(csv_escape)
-                .unwrap_or_default(),
-        );
-    }
-    if has_sources {
-        fields.push(if url_data.sources.is_empty() {
-            String::new()
-        } else {
-            csv_escape(&url_data.sources.join("|"))
-        });
-    }
+pub(crate) fn csv_row(url_data: &UrlData, layout: &CsvLayout) -> String {
+    csv_line(url_data, &layout.columns())
+}
+
+/// One CSV data row with `columns`, empty where the entry has no value.
+pub(crate) fn csv_line(url_data: &UrlData, columns: &[String]) -> String {
+    let fields: Vec<String> = columns
+        .iter()
+        .map(|column| csv_field(url_data, column))
+        .collect();
     let mut line = fields.join(",");
     line.push('\n');
     line
 }
 
+/// The escaped value of one CSV column of `url_data`.
+fn csv_field(url_data: &UrlData, column: &str) -> String {
+    let optional = |value: Option<&str>| value.map(csv_escape).unwrap_or_default();
+    let count = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+    match column {
+        "url" => csv_escape(&url_data.url),
+        "status" => optional(url_data.status.as_deref()),
+        "protocol" => optional(url_data.protocol.as_deref()),
+        "host" => url::Url::parse(&url_data.url)
+            .ok()
+            .and_then(|u| u.host_str().map(csv_escape))
+            .unwrap_or_default(),
+        "content_type" => optional(url_data.content_type.as_deref()),
+        "content_length" => count(url_data.content_length),
+        "body_sha256" => optional(url_data.body_sha256.as_deref()),
+        "word_count" => count(url_data.word_count),
+        "line_count" => count(url_data.line_count),
+        "title" => optional(url_data.title.as_deref()),
+        "sources" => csv_escape(&url_data.sources.join("|")),
+        name => match name.strip_prefix("header_") {
+            Some(header) => optional(url_data.headers.get(header).map(String::as_str)),
+            None => match url_data.metadata.get(name) {
+                Some(serde_json::Value::String(s)) => csv_escape(s),
+                Some(value) => csv_escape(&value.to_string()),
+                None => String::new(),
+            },
+        },
+    }
+}
+
 /// Escape a field value for CSV output per RFC 4180.
-/// If the value contains a comma, double-quote, or newline, wrap it in
-/// double-quotes and escape any internal double-quotes by doubling them.
+/// If the value contains a comma, double-quote, or line break (`\n` or `\r`),
+/// wrap it in double-quotes and escape any internal double-quotes by doubling
+/// them.
 pub(crate) fn csv_escape(value: &str) -> String {
-    if value.contains(',') || value.contains('"') || value.contains('\n') {
+    if value.contains([',', '"', '\n', '\r']) {
         let escaped = value.replace('"', "\"\"");
         format!("\"{escaped}\"")
     } else {
@@ -189,6 +460,36 @@ pub(crate) fn csv_escape(value: &str) -> String {
     }
 }
 
+/// Characters plain output can't carry as-is: ASCII and C1 control
+/// characters (line breaks would split a URL over two lines, escape sequences
+/// would drive the terminal) and the Unicode line and paragraph separators.
+/// Tab is kept: it stays on the line, and tab-separated lines such as
+/// `--param-key-counts` rely on it.
+fn breaks_plain_line(c: char) -> bool {
+    (c.is_control() && c != '\t') || matches!(c, '\u{2028}' | '\u{2029}')
+}
+
+/// Percent-encode the characters [`breaks_plain_line`] rejects, so every
+/// plain-format entry stays on one line and prints as inert text. Values
+/// without such characters are returned as-is.
+pub(crate) fn plain_escape(value: &str) -> Cow<'_, str> {
+    if !value.contains(breaks_plain_line) {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len() + 8);
+    for c in value.chars() {
+        if breaks_plain_line(c) {
+            let mut buf = [0u8; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                out.push_str(&format!("%{byte:02X}"));
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Cow::Owned(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,11 +633,11 @@ mod tests {
         let url_data = UrlData::new("https://example.com".to_string());
         assert_eq!(
             formatter.format(&url_data, false),
-            "{\"url\":\"https://example.com\"},"
+            "{\"url\":\"https://example.com\",\"schema_version\":1},"
         );
         assert_eq!(
             formatter.format(&url_data, true),
-            "{\"url\":\"https://example.com\"}\n"
+            "{\"url\":\"https://example.com\",\"schema_version\":1}\n"
         );
 
         // Test URL with status
@@ -344,7 +645,7 @@ mod tests {
             UrlData::with_status("https://example.com".to_string(), "200 OK".to_string());
         assert_eq!(
             formatter.format(&url_data_status, false),
-            "{\"url\":\"https://example.com\",\"status\":\"200 OK\"},"
+            "{\"url\":\"https://example.com\",\"status\":\"200 OK\",\"schema_version\":1},"
         );
     }
 
@@ -404,7 +705,7 @@ mod tests {
         // Sources are sorted and deduped; field appears after url/status.
         assert_eq!(
             formatter.format(&url_data, true),
-            "{\"url\":\"https://example.com\",\"sources\":[\"otx\",\"wayback\"]}\n"
+            "{\"url\":\"https://example.com\",\"sources\":[\"otx\",\"wayback\"],\"schema_version\":1}\n"
         );
     }
 
@@ -421,6 +722,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_content_info_in_json_and_csv() {
+        let mut url_data =
+            UrlData::with_status("https://example.com".to_string(), "200 OK".to_string());
+        url_data.content_type = Some("text/html; charset=utf-8".into());
+        url_data.content_length = Some(1256);
+        assert_eq!(
+            JsonFormatter::new().format(&url_data, true),
+            "{\"url\":\"https://example.com\",\"status\":\"200 OK\",\"content_type\":\"text/html; charset=utf-8\",\"content_length\":1256,\"schema_version\":1}\n"
+        );
+        assert_eq!(
+            csv_header(
+                &CsvLayout {
+                    status: true,
+                    content: true,
+                    ..Default::default()
+                }
+                .columns()
+            ),
+            "url,status,content_type,content_length\n"
+        );
+        assert_eq!(
+            CsvFormatter::new().format(&url_data, true),
+            "https://example.com,200 OK,text/html; charset=utf-8,1256\n"
+        );
+        // A missing header leaves its column empty
+        url_data.content_type = None;
+        assert_eq!(
+            csv_row(&url_data, &CsvLayout::of(&url_data)),
+            "https://example.com,200 OK,,1256\n"
+        );
+    }
+
+    #[test]
+    fn test_title_in_every_format() {
+        let mut url_data = UrlData::new("https://example.com".to_string());
+        url_data.title = Some("Sign in, \"admin\"".into());
+        assert_eq!(
+            console::strip_ansi_codes(&PlainFormatter::new().format(&url_data, true)),
+            "https://example.com [Sign in, \"admin\"]\n"
+        );
+        assert_eq!(
+            JsonFormatter::new().format(&url_data, true),
+            "{\"url\":\"https://example.com\",\"title\":\"Sign in, \\\"admin\\\"\",\"schema_version\":1}\n"
+        );
+        let layout = CsvLayout::of(&url_data);
+        assert_eq!(csv_header(&layout.columns()), "url,title\n");
+        assert_eq!(
+            csv_row(&url_data, &layout),
+            "https://example.com,\"Sign in, \"\"admin\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_captured_headers_in_json_and_csv() {
+        let mut with_headers =
+            UrlData::with_status("https://example.com/a".into(), "200 OK".into());
+        with_headers
+            .headers
+            .insert("x-powered-by".into(), "PHP/8.2".into());
+        with_headers.headers.insert("server".into(), "nginx".into());
+        assert_eq!(
+            JsonFormatter::new().format(&with_headers, true),
+            "{\"url\":\"https://example.com/a\",\"status\":\"200 OK\",\"headers\":{\"server\":\"nginx\",\"x-powered-by\":\"PHP/8.2\"},\"schema_version\":1}\n"
+        );
+
+        let mut redirect = UrlData::with_status("https://example.com/b".into(), "302 Found".into());
+        redirect.headers.insert("location".into(), "/login".into());
+        let urls = [with_headers, redirect];
+        let layout = CsvLayout::of_all(&urls);
+        assert_eq!(
+            csv_header(&layout.columns()),
+            "url,status,header_location,header_server,header_x-powered-by\n"
+        );
+        assert_eq!(
+            csv_row(&urls[0], &layout),
+            "https://example.com/a,200 OK,,nginx,PHP/8.2\n"
+        );
+        assert_eq!(
+            csv_row(&urls[1], &layout),
+            "https://example.com/b,302 Found,/login,,\n"
+        );
+    }
+
+    #[test]
+    fn test_status_color_by_class() {
+        assert_eq!(status_color("200 OK"), Some(Color::Green));
+        assert_eq!(status_color("301 Moved Permanently"), Some(Color::Blue));
+        assert_eq!(status_color("404 Not Found"), Some(Color::Yellow));
+        assert_eq!(status_color("503 Service Unavailable"), Some(Color::Red));
+        assert_eq!(status_color("101 Switching Protocols"), None);
+        assert_eq!(status_color("timeout"), None);
+    }
+
+    #[test]
+    fn test_csv_columns_selection() {
+        let columns: CsvColumns = "url, Status,host,sources,header_Server,url"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            columns.names(),
+            ["url", "status", "host", "sources", "header_server"]
+        );
+        assert!("url,bogus".parse::<CsvColumns>().is_err());
+        assert!(" , ".parse::<CsvColumns>().is_err());
+
+        let mut url_data = UrlData::new("https://example.com:8443/a?b=1,2".into())
+            .with_sources(vec!["wayback".into(), "otx".into()]);
+        url_data.headers.insert("server".into(), "nginx".into());
+        assert_eq!(
+            csv_header(columns.names()),
+            "url,status,host,sources,header_server
+"
+        );
+        assert_eq!(
+            CsvFormatter::with_columns(columns).format(&url_data, true),
+            "\"https://example.com:8443/a?b=1,2\",,example.com,otx|wayback,nginx\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_snapshot_columns() {
+        let mut url_data = UrlData::new("https://example.com/a".to_string());
+        url_data
+            .metadata
+            .insert("timestamp".into(), "2020-01-02T03:04:05Z".into());
+        url_data
+            .metadata
+            .insert("archived_status".into(), 200.into());
+        assert_eq!(
+            csv_header(
+                &CsvLayout {
+                    snapshot: true,
+                    ..Default::default()
+                }
+                .columns()
+            ),
+            "url,timestamp,archived_status,mime\n"
+        );
+        assert_eq!(
+            CsvFormatter::new().format(&url_data, true),
+            "https://example.com/a,2020-01-02T03:04:05Z,200,\n"
+        );
+        // Entries without snapshot data keep the plain layout
+        assert_eq!(
+            CsvFormatter::new().format(&UrlData::new("https://example.com/b".into()), true),
+            "https://example.com/b\n"
+        );
+    }
+
     #[test]
     fn test_plain_formatter_with_sources() {
         let formatter = PlainFormatter::new();
@@ -434,6 +885,29 @@ mod tests {
         assert!(out.ends_with('\n'));
     }
 
+    #[test]
+    fn test_plain_formatter_with_reflection() {
+        let formatter = PlainFormatter::new();
+        let mut url_data = UrlData::new("https://example.com/?q=1&id=2".to_string());
+        crate::testers::annotate_reflection(
+            &mut url_data.metadata,
+            &["q".to_string(), "id".to_string()],
+        );
+        let out = formatter.format(&url_data, true);
+        assert!(out.starts_with("https://example.com/?q=1&id=2 "));
+        assert!(out.contains("xss-candidate: q,id"));
+    }
+
+    #[test]
+    fn test_plain_formatter_with_sourcemap_tag() {
+        let formatter = PlainFormatter::new();
+        let mut url_data = UrlData::new("https://example.com/api/users".to_string());
+        crate::testers::annotate_sourcemap(&mut url_data.metadata, "https://example.com/app.js");
+        let out = formatter.format(&url_data, true);
+        assert!(out.starts_with("https://example.com/api/users "));
+        assert!(out.contains("sourcemap"));
+    }
+
     #[test]
     fn test_formatter_clone() {
         let plain_formatter: Box<dyn Formatter> = Box::new(PlainFormatter::new());
@@ -445,4 +919,160 @@ mod tests {
             cloned_formatter.format(&url_data, false)
         );
     }
+
+    #[test]
+    fn test_csv_escape_quotes_carriage_returns() {
+        assert_eq!(csv_escape("a\rb"), "\"a\rb\"");
+    }
+
+    #[test]
+    fn test_plain_escape_percent_encodes_line_breaks_and_controls() {
+        assert!(matches!(
+            plain_escape("https://example.com/é"),
+            Cow::Borrowed(_)
+        ));
+        assert_eq!(
+            plain_escape("https://example.com/a\r\nb\x1b[31m\u{85}\u{2028}"),
+            "https://example.com/a%0D%0Ab%1B[31m%C2%85%E2%80%A8"
+        );
+        assert!(matches!(plain_escape("page\t2"), Cow::Borrowed("page\t2")));
+    }
+
+    /// Split CSV text into records per RFC 4180, failing on anything a strict
+    /// parser would reject (a stray quote, an unterminated field).
+    fn parse_csv(text: &str) -> Vec<Vec<String>> {
+        let mut records = Vec::new();
+        let mut record = Vec::new();
+        let mut field = String::new();
+        let mut chars = text.chars().peekable();
+        let mut at_field_start = true;
+        while let Some(c) = chars.next() {
+            if at_field_start && c == '"' {
+                loop {
+                    match chars.next().expect("unterminated quoted field") {
+                        '"' if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            field.push('"');
+                        }
+                        '"' => break,
+                        c => field.push(c),
+                    }
+                }
+                at_field_start = false;
+                continue;
+            }
+            match c {
+                ',' => {
+                    record.push(std::mem::take(&mut field));
+                    at_field_start = true;
+                }
+                '\n' => {
+                    record.push(std::mem::take(&mut field));
+                    records.push(std::mem::take(&mut record));
+                    at_field_start = true;
+                }
+                '"' => panic!("stray quote in unquoted field: {text:?}"),
+                c => {
+                    assert!(c != '\r', "bare CR in unquoted field: {text:?}");
+                    field.push(c);
+                    at_field_start = false;
+                }
+            }
+        }
+        assert!(
+            field.is_empty() && record.is_empty(),
+            "missing final newline"
+        );
+        records
+    }
+
+    /// Randomised round trip through every formatter with strings built from
+    /// the characters that break naive escaping. Seeded, so a failure
+    /// reproduces; the seed and input are in the panic message.
+    #[test]
+    fn test_formatters_fuzz_round_trip() {
+        const PIECES: &[&str] = &[
+            "https://example.com/",
+            "a",
+            ",",
+            "\"",
+            "\n",
+            "\r",
+            "\r\n",
+            "\t",
+            "\0",
+            "\x1b[2J",
+            "\\",
+            "'",
+            ";",
+            "|",
+            "%0A",
+            "é",
+            "😀",
+            "\u{85}",
+            "\u{2028}",
+            "\u{7f}",
+            " ",
+            "[",
+            "]",
+            "{",
+            "}",
+        ];
+        let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut next = move |bound: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        };
+        let random_string = |next: &mut dyn FnMut(usize) -> usize| {
+            (0..next(8))
+                .map(|_| PIECES[next(PIECES.len())])
+                .collect::<String>()
+        };
+
+        for case in 0..2000 {
+            let url = random_string(&mut next);
+            let status = random_string(&mut next);
+            let source = random_string(&mut next);
+            let mut entry = UrlData::with_status(url.clone(), status.clone())
+                .with_sources(vec![source.clone()]);
+            entry
+                .metadata
+                .insert("timestamp".to_string(), random_string(&mut next).into());
+            entry
+                .metadata
+                .insert("reflected_params".to_string(), vec![source.clone()].into());
+            let context = format!("case {case}: {url:?} / {status:?} / {source:?}");
+
+            let plain = PlainFormatter::new().format(&entry, true);
+            let plain = console::strip_ansi_codes(&plain);
+            assert!(plain.ends_with('\n'), "{context}");
+            assert!(
+                !plain[..plain.len() - 1].contains(breaks_plain_line),
+                "{context}: {plain:?}"
+            );
+
+            let layout = CsvLayout::of(&entry);
+            let records = parse_csv(&(csv_header(&layout.columns()) + &csv_row(&entry, &layout)));
+            assert_eq!(records.len(), 2, "{context}");
+            assert_eq!(records[0].len(), records[1].len(), "{context}");
+            assert_eq!(records[1][0], url, "{context}");
+            assert_eq!(records[1][1], status, "{context}");
+            assert_eq!(records[1][2], source, "{context}");
+
+            let json = JsonFormatter::new();
+            let document = format!(
+                "[{}{}]",
+                json.format(&entry, false),
+                json.format(&entry, true)
+            );
+            let parsed: Vec<serde_json::Value> =
+                serde_json::from_str(&document).unwrap_or_else(|e| panic!("{context}: {e}"));
+            assert_eq!(parsed.len(), 2, "{context}");
+            assert_eq!(parsed[1]["url"], url.as_str(), "{context}");
+            assert_eq!(parsed[1]["status"], status.as_str(), "{context}");
+            assert_eq!(parsed[1]["sources"][0], source.as_str(), "{context}");
+        }
+    }
 }
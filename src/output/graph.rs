@@ -0,0 +1,259 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::{OutputFile, Outputter, UrlData};
+
+/// One path segment of a host's URL tree. `status` is set when a URL ends
+/// exactly here and was tested.
+#[derive(Debug, Default)]
+struct PathNode {
+    children: BTreeMap<String, PathNode>,
+    status: Option<String>,
+}
+
+impl PathNode {
+    fn insert(&mut self, segments: &[&str], status: Option<&str>) {
+        match segments.split_first() {
+            Some((first, rest)) => self
+                .children
+                .entry((*first).to_string())
+                .or_default()
+                .insert(rest, status),
+            None => {
+                if self.status.is_none() {
+                    self.status = status.map(str::to_string);
+                }
+            }
+        }
+    }
+}
+
+/// `host[:port]` → path tree of `urls`, both sorted. Query strings and
+/// fragments are dropped; URLs that don't parse are skipped.
+fn build_hosts(urls: &[UrlData]) -> BTreeMap<String, PathNode> {
+    let mut hosts: BTreeMap<String, PathNode> = BTreeMap::new();
+    for entry in urls {
+        let Ok(parsed) = url::Url::parse(&entry.url) else {
+            continue;
+        };
+        let Some(host) = parsed.host_str() else {
+            continue;
+        };
+        let host = match parsed.port() {
+            Some(port) => format!("{host}:{port}"),
+            None => host.to_string(),
+        };
+        let segments: Vec<&str> = parsed
+            .path()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        hosts
+            .entry(host)
+            .or_default()
+            .insert(&segments, entry.status.as_deref());
+    }
+    hosts
+}
+
+/// ` [status]` for a tested node, empty otherwise.
+fn status_suffix(node: &PathNode) -> String {
+    node.status
+        .as_deref()
+        .map(|status| format!(" [{status}]"))
+        .unwrap_or_default()
+}
+
+/// Write one whole-run document to `--output` or stdout.
+fn write_document(
+    urls: &[UrlData],
+    output_path: Option<PathBuf>,
+    silent: bool,
+    render: fn(&[UrlData], &mut dyn Write) -> std::io::Result<()>,
+) -> Result<()> {
+    match output_path {
+        Some(path) => {
+            let mut file = OutputFile::create(&path)?;
+            render(urls, &mut file).context("Failed to write to output file")?;
+            file.finish()
+        }
+        None if silent => Ok(()),
+        None => {
+            let mut out = std::io::stdout().lock();
+            render(urls, &mut out).context("Failed to write output")?;
+            Ok(out.flush()?)
+        }
+    }
+}
+
+/// Outputter for `--format tree`: each host with its path segments drawn as
+/// an indented tree, `[status]` on the tested URLs.
+#[derive(Debug, Clone)]
+pub struct TreeOutputter;
+
+impl TreeOutputter {
+    pub fn new() -> Self {
+        TreeOutputter
+    }
+
+    fn write_tree(urls: &[UrlData], out: &mut dyn Write) -> std::io::Result<()> {
+        fn write_children(
+            node: &PathNode,
+            prefix: &str,
+            out: &mut dyn Write,
+        ) -> std::io::Result<()> {
+            let count = node.children.len();
+            for (i, (segment, child)) in node.children.iter().enumerate() {
+                let last = i + 1 == count;
+                let (branch, indent) = if last {
+                    ("└── ", "    ")
+                } else {
+                    ("├── ", "│   ")
+                };
+                writeln!(out, "{prefix}{branch}{segment}{}", status_suffix(child))?;
+                write_children(child, &format!("{prefix}{indent}"), out)?;
+            }
+            Ok(())
+        }
+
+        for (host, root) in build_hosts(urls) {
+            writeln!(out, "{host}{}", status_suffix(&root))?;
+            write_children(&root, "", out)?;
+        }
+        Ok(())
+    }
+}
+
+impl Outputter for TreeOutputter {
+    /// The URL alone; the tree needs the whole run
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        format!("{}\n", url_data.url)
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
+        write_document(urls, output_path, silent, Self::write_tree)
+    }
+}
+
+/// Quote a DOT identifier or label.
+fn dot_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Outputter for `--format dot`: the same host → path tree as a Graphviz
+/// digraph, e.g. for `dot -Tsvg`. Node ids are the host plus path so far,
+/// labels the segment.
+#[derive(Debug, Clone)]
+pub struct DotOutputter;
+
+impl DotOutputter {
+    pub fn new() -> Self {
+        DotOutputter
+    }
+
+    fn write_dot(urls: &[UrlData], out: &mut dyn Write) -> std::io::Result<()> {
+        fn write_children(id: &str, node: &PathNode, out: &mut dyn Write) -> std::io::Result<()> {
+            for (segment, child) in &node.children {
+                let child_id = format!("{id}/{segment}");
+                let label = format!("{segment}{}", status_suffix(child));
+                writeln!(
+                    out,
+                    "  {} [label={}];",
+                    dot_quote(&child_id),
+                    dot_quote(&label)
+                )?;
+                writeln!(out, "  {} -> {};", dot_quote(id), dot_quote(&child_id))?;
+                write_children(&child_id, child, out)?;
+            }
+            Ok(())
+        }
+
+        writeln!(out, "digraph urx {{")?;
+        writeln!(out, "  rankdir=LR;")?;
+        writeln!(out, "  node [shape=box, fontname=\"monospace\"];")?;
+        for (host, root) in build_hosts(urls) {
+            let label = format!("{host}{}", status_suffix(&root));
+            writeln!(
+                out,
+                "  {} [label={}, shape=ellipse];",
+                dot_quote(&host),
+                dot_quote(&label)
+            )?;
+            write_children(&host, &root, out)?;
+        }
+        writeln!(out, "}}")
+    }
+}
+
+impl Outputter for DotOutputter {
+    /// The URL alone; the graph needs the whole run
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        format!("{}\n", url_data.url)
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
+        write_document(urls, output_path, silent, Self::write_dot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<UrlData> {
+        vec![
+            UrlData::with_status("https://example.com/".into(), "200 OK".into()),
+            UrlData::new("https://example.com/api/v1/users?id=1".into()),
+            UrlData::with_status("https://example.com/api/v2".into(), "404 Not Found".into()),
+            UrlData::new("https://example.com/login".into()),
+            UrlData::new("http://dev.example.com:8080/a\"b".into()),
+            UrlData::new("not a url".into()),
+        ]
+    }
+
+    #[test]
+    fn test_tree_output() -> Result<()> {
+        let temp_file = tempfile::NamedTempFile::new()?;
+        TreeOutputter::new().output(&sample(), Some(temp_file.path().to_path_buf()), false)?;
+        assert_eq!(
+            std::fs::read_to_string(temp_file.path())?,
+            "dev.example.com:8080
+└── a%22b
+example.com [200 OK]
+├── api
+│   ├── v1
+│   │   └── users
+│   └── v2 [404 Not Found]
+└── login
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_dot_output() -> Result<()> {
+        let temp_file = tempfile::NamedTempFile::new()?;
+        DotOutputter::new().output(&sample()[..3], Some(temp_file.path().to_path_buf()), false)?;
+        assert_eq!(
+            std::fs::read_to_string(temp_file.path())?,
+            r#"digraph urx {
+  rankdir=LR;
+  node [shape=box, fontname="monospace"];
+  "example.com" [label="example.com [200 OK]", shape=ellipse];
+  "example.com/api" [label="api"];
+  "example.com" -> "example.com/api";
+  "example.com/api/v1" [label="v1"];
+  "example.com/api" -> "example.com/api/v1";
+  "example.com/api/v1/users" [label="users"];
+  "example.com/api/v1" -> "example.com/api/v1/users";
+  "example.com/api/v2" [label="v2 [404 Not Found]"];
+  "example.com/api" -> "example.com/api/v2";
+}
+"#
+        );
+        assert_eq!(dot_quote(r#"a"b\c"#), r#""a\"b\\c""#);
+        Ok(())
+    }
+}
@@ -0,0 +1,269 @@
+/// Per-host asset inventory for `--hosts-output`: one CSV row per unique host
+/// summarizing the final URL set.
+use super::formatter::csv_escape;
+use super::UrlData;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Column header of the hosts CSV.
+const HOSTS_CSV_HEADER: &str = "host,urls,parameterized_urls,statuses,technologies,ips,cdn";
+
+/// Everything the hosts CSV records about one host.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostSummary {
+    pub host: String,
+    /// URLs collected for the host.
+    pub urls: usize,
+    /// URLs carrying a query string.
+    pub parameterized: usize,
+    /// Live status code (`--check-status`) -> URL count.
+    pub statuses: BTreeMap<String, usize>,
+    /// Technologies inferred from paths and extensions (`.php`, `/wp-content/`, …).
+    pub technologies: BTreeSet<&'static str>,
+    /// The host itself when it is an IP literal, plus any IP a provider
+    /// reported for its URLs (ZoomEye's `ip` field).
+    pub ips: BTreeSet<String>,
+    /// CDN recognised from the hostname or a CDN-specific path.
+    pub cdn: Option<&'static str>,
+}
+
+/// Path fragments that give a framework away. Matched case-insensitively.
+const PATH_SIGNATURES: &[(&str, &str)] = &[
+    ("/wp-content/", "WordPress"),
+    ("/wp-includes/", "WordPress"),
+    ("/wp-json/", "WordPress"),
+    ("/sites/default/files/", "Drupal"),
+    ("/components/com_", "Joomla"),
+    ("/skin/frontend/", "Magento"),
+    ("/_next/", "Next.js"),
+    ("/_nuxt/", "Nuxt"),
+    ("/rails/", "Ruby on Rails"),
+    ("/actuator/", "Spring Boot"),
+];
+
+/// File extensions tied to a server-side stack.
+const EXTENSION_SIGNATURES: &[(&str, &str)] = &[
+    ("php", "PHP"),
+    ("asp", "Classic ASP"),
+    ("aspx", "ASP.NET"),
+    ("ashx", "ASP.NET"),
+    ("asmx", "ASP.NET"),
+    ("jsp", "Java"),
+    ("do", "Java"),
+    ("action", "Java"),
+    ("cfm", "ColdFusion"),
+    ("cgi", "CGI"),
+    ("pl", "Perl"),
+];
+
+/// Hostname suffixes of CDN edges.
+const CDN_HOST_SUFFIXES: &[(&str, &str)] = &[
+    (".cloudfront.net", "CloudFront"),
+    (".akamaized.net", "Akamai"),
+    (".akamaihd.net", "Akamai"),
+    (".edgesuite.net", "Akamai"),
+    (".edgekey.net", "Akamai"),
+    (".fastly.net", "Fastly"),
+    (".fastlylb.net", "Fastly"),
+    (".azureedge.net", "Azure CDN"),
+    (".b-cdn.net", "Bunny CDN"),
+    (".cdn.cloudflare.net", "Cloudflare"),
+    (".netlify.app", "Netlify"),
+    (".vercel.app", "Vercel"),
+];
+
+fn detect_technologies(url: &url::Url, found: &mut BTreeSet<&'static str>) {
+    let path = url.path().to_ascii_lowercase();
+    for (fragment, tech) in PATH_SIGNATURES {
+        if path.contains(fragment) {
+            found.insert(tech);
+        }
+    }
+    let file = path.rsplit('/').next().unwrap_or_default();
+    if let Some((_, ext)) = file.rsplit_once('.') {
+        if let Some((_, tech)) = EXTENSION_SIGNATURES.iter().find(|(e, _)| *e == ext) {
+            found.insert(tech);
+        }
+    }
+}
+
+fn detect_cdn(host: &str, path: &str) -> Option<&'static str> {
+    let host = host.to_ascii_lowercase();
+    CDN_HOST_SUFFIXES
+        .iter()
+        .find(|(suffix, _)| host.ends_with(suffix))
+        .map(|(_, cdn)| *cdn)
+        // Cloudflare serves /cdn-cgi/ on every proxied zone.
+        .or_else(|| path.starts_with("/cdn-cgi/").then_some("Cloudflare"))
+}
+
+/// Leading three-digit code of a status string such as `200 OK`.
+fn status_code(status: &str) -> Option<&str> {
+    let code = status.split_whitespace().next()?;
+    (code.len() == 3 && code.bytes().all(|b| b.is_ascii_digit())).then_some(code)
+}
+
+/// Group `urls` by host, sorted by host. URLs that don't parse or have no
+/// host are left out.
+pub fn summarize_hosts(urls: &[UrlData]) -> Vec<HostSummary> {
+    let mut hosts: BTreeMap<String, HostSummary> = BTreeMap::new();
+    for entry in urls {
+        let Ok(parsed) = url::Url::parse(&entry.url) else {
+            continue;
+        };
+        let Some(host) = parsed.host_str() else {
+            continue;
+        };
+        let summary = hosts
+            .entry(host.to_string())
+            .or_insert_with(|| HostSummary {
+                host: host.to_string(),
+                ..Default::default()
+            });
+
+        summary.urls += 1;
+        if parsed.query().is_some_and(|q| !q.is_empty()) {
+            summary.parameterized += 1;
+        }
+        if let Some(code) = entry.status.as_deref().and_then(status_code) {
+            *summary.statuses.entry(code.to_string()).or_default() += 1;
+        }
+        detect_technologies(&parsed, &mut summary.technologies);
+        if matches!(parsed.host(), Some(url::Host::Ipv4(_) | url::Host::Ipv6(_))) {
+            summary
+                .ips
+                .insert(host.trim_matches(['[', ']']).to_string());
+        }
+        if let Some(ip) = entry.metadata.get("ip").and_then(|v| v.as_str()) {
+            if !ip.is_empty() {
+                summary.ips.insert(ip.to_string());
+            }
+        }
+        if summary.cdn.is_none() {
+            summary.cdn = detect_cdn(host, parsed.path());
+        }
+    }
+    hosts.into_values().collect()
+}
+
+/// One CSV line (no trailing newline). Multi-valued columns are joined with
+/// `;`, and the status distribution reads `200:12;404:3`.
+fn hosts_csv_row(summary: &HostSummary) -> String {
+    let statuses = summary
+        .statuses
+        .iter()
+        .map(|(code, n)| format!("{code}:{n}"))
+        .collect::<Vec<_>>()
+        .join(";");
+    let technologies = summary
+        .technologies
+        .iter()
+        .copied()
+        .collect::<Vec<_>>()
+        .join(";");
+    let ips = summary.ips.iter().cloned().collect::<Vec<_>>().join(";");
+    [
+        csv_escape(&summary.host),
+        summary.urls.to_string(),
+        summary.parameterized.to_string(),
+        statuses,
+        csv_escape(&technologies),
+        ips,
+        summary.cdn.unwrap_or_default().to_string(),
+    ]
+    .join(",")
+}
+
+/// Write the per-host summary of `urls` to `path` as CSV.
+pub fn write_hosts_csv(urls: &[UrlData], path: &Path) -> Result<()> {
+    let file = File::create(path).context("Failed to create hosts output file")?;
+    let mut out = BufWriter::new(file);
+    writeln!(out, "{HOSTS_CSV_HEADER}").context("Failed to write hosts output file")?;
+    for summary in summarize_hosts(urls) {
+        writeln!(out, "{}", hosts_csv_row(&summary))
+            .context("Failed to write hosts output file")?;
+    }
+    out.flush().context("Failed to write hosts output file")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_hosts_groups_and_counts() {
+        let mut ip_tagged = UrlData::new("https://shop.example.com/cart".to_string());
+        ip_tagged
+            .metadata
+            .insert("ip".to_string(), "203.0.113.7".into());
+        let urls = vec![
+            UrlData::with_status(
+                "https://example.com/index.php?id=1".to_string(),
+                "200 OK".to_string(),
+            ),
+            UrlData::with_status(
+                "https://example.com/wp-content/uploads/a.png".to_string(),
+                "404 Not Found".to_string(),
+            ),
+            UrlData::with_status(
+                "https://example.com/about".to_string(),
+                "200 OK".to_string(),
+            ),
+            ip_tagged,
+            UrlData::new("https://d111111abcdef8.cloudfront.net/app.js".to_string()),
+            UrlData::new("http://192.0.2.1:8080/login.jsp".to_string()),
+            UrlData::new("not a url".to_string()),
+        ];
+
+        let summaries = summarize_hosts(&urls);
+        let hosts: Vec<&str> = summaries.iter().map(|s| s.host.as_str()).collect();
+        assert_eq!(
+            hosts,
+            vec![
+                "192.0.2.1",
+                "d111111abcdef8.cloudfront.net",
+                "example.com",
+                "shop.example.com"
+            ]
+        );
+
+        let example = &summaries[2];
+        assert_eq!(example.urls, 3);
+        assert_eq!(example.parameterized, 1);
+        assert_eq!(example.statuses.get("200"), Some(&2));
+        assert_eq!(example.statuses.get("404"), Some(&1));
+        assert!(example.technologies.contains("PHP"));
+        assert!(example.technologies.contains("WordPress"));
+        assert_eq!(
+            hosts_csv_row(example),
+            "example.com,3,1,200:2;404:1,PHP;WordPress,,"
+        );
+
+        assert_eq!(summaries[0].ips.iter().next().unwrap(), "192.0.2.1");
+        assert!(summaries[0].technologies.contains("Java"));
+        assert_eq!(summaries[1].cdn, Some("CloudFront"));
+        assert!(summaries[3].ips.contains("203.0.113.7"));
+    }
+
+    #[test]
+    fn test_write_hosts_csv_has_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hosts.csv");
+        write_hosts_csv(
+            &[UrlData::new(
+                "https://example.com/cdn-cgi/trace".to_string(),
+            )],
+            &path,
+        )
+        .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            written,
+            format!("{HOSTS_CSV_HEADER}\nexample.com,1,0,,,,Cloudflare\n")
+        );
+    }
+}
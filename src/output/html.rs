@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::formatter::JsonUrlEntry;
+use super::{Formatter, OutputFile, Outputter, UrlData};
+
+/// The report page. Its script reads the entries from the `urx-data` block,
+/// where [`render_report`] puts them in place of [`DATA_PLACEHOLDER`].
+const TEMPLATE: &str = include_str!("report.html");
+const DATA_PLACEHOLDER: &str = "/*URX_DATA*/";
+
+/// Build the `--format html` report: one self-contained page with search,
+/// sortable columns and per-host grouping, carrying the same entries as
+/// JSON output.
+pub fn render_report(urls: &[UrlData]) -> Result<String> {
+    let entries: Vec<JsonUrlEntry> = urls.iter().map(Into::into).collect();
+    // `<` only occurs inside strings, where `\u003c` means the same, so a
+    // crawled `</script>` can't close the data block early
+    let data = serde_json::to_string(&entries)
+        .context("Failed to serialize report data")?
+        .replace('<', "\\u003c");
+    Ok(TEMPLATE.replacen(DATA_PLACEHOLDER, &data, 1))
+}
+
+/// Outputter for `--format html`. The report needs every entry at once, so
+/// [`format`](Outputter::format) only yields an entry's JSON record, as
+/// embedded in the page.
+#[derive(Debug, Clone)]
+pub struct HtmlOutputter {
+    formatter: Box<dyn Formatter>,
+}
+
+impl HtmlOutputter {
+    pub fn new() -> Self {
+        HtmlOutputter {
+            formatter: Box::new(super::JsonFormatter::new()),
+        }
+    }
+}
+
+impl Outputter for HtmlOutputter {
+    fn format(&self, url_data: &UrlData, is_last: bool) -> String {
+        self.formatter.format(url_data, is_last)
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
+        let report = render_report(urls)?;
+        match output_path {
+            Some(path) => {
+                let mut file = OutputFile::create(&path)?;
+                file.write_all(report.as_bytes())
+                    .context("Failed to write to output file")?;
+                file.finish()
+            }
+            None => {
+                if !silent {
+                    print!("{report}");
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_embeds_entries_safely() {
+        let mut page = UrlData::with_status("https://example.com/a".into(), "200 OK".into())
+            .with_sources(vec!["wayback".into()]);
+        page.title = Some("</script><script>alert(1)</script>".into());
+        page.content_length = Some(512);
+        let report =
+            render_report(&[page, UrlData::new("https://api.example.com/".into())]).unwrap();
+
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(!report.contains(DATA_PLACEHOLDER));
+        assert!(!report.contains("</script><script>alert"));
+
+        let start = report.find(r#"type="application/json">"#).unwrap() + 24;
+        let end = start + report[start..].find("</script>").unwrap();
+        let data: serde_json::Value = serde_json::from_str(&report[start..end]).unwrap();
+        assert_eq!(data[0]["title"], "</script><script>alert(1)</script>");
+        assert_eq!(data[0]["content_length"], 512);
+        assert_eq!(data[0]["sources"], serde_json::json!(["wayback"]));
+        assert_eq!(data[1]["url"], "https://api.example.com/");
+    }
+}
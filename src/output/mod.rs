@@ -1,10 +1,54 @@
 use anyhow::Result;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+mod append;
+mod auth_boundary;
+mod burp;
+mod compress;
+mod count;
+mod diff;
+mod elasticsearch;
 mod formatter;
+mod graph;
+mod host_summary;
+mod html;
+#[cfg(feature = "parquet")]
+mod parquet;
+mod readback;
+#[cfg(feature = "s3")]
+mod s3;
+mod schema;
+mod secrets;
+mod signing;
+mod sitemap;
+mod tags;
+mod template;
 mod writer;
 
+pub use append::{append_output, APPENDABLE_FORMATS};
+pub use auth_boundary::{protected_endpoints_markdown, protected_prefixes, ProtectedPrefix};
+pub use burp::BurpOutputter;
+pub use compress::{Compression, OutputFile};
+pub use count::render_counts;
+pub use diff::{diff_entries, DIFF_KEY};
+pub use elasticsearch::{validate_es_index, ElasticsearchSink};
 pub use formatter::*;
+pub use graph::{DotOutputter, TreeOutputter};
+pub use host_summary::write_hosts_csv;
+pub use html::HtmlOutputter;
+#[cfg(feature = "parquet")]
+pub use parquet::ParquetOutputter;
+pub(crate) use readback::plain_line_url;
+pub use readback::read_entries;
+#[cfg(feature = "s3")]
+pub use s3::{S3Credentials, S3Location, S3Uploader};
+pub use schema::{OUTPUT_SCHEMA, SCHEMA_VERSION};
+pub use secrets::write_secrets_json;
+pub use signing::{load_signing_key, sign_output};
+pub use sitemap::SitemapOutputter;
+pub use tags::{split_target_tag, write_split_by_tag, TargetTags};
+pub use template::TemplateFormatter;
 pub use writer::*;
 
 /// A structure to hold URL data with optional status information
@@ -14,8 +58,28 @@ pub struct UrlData {
     pub url: String,
     /// Optional status information (e.g., HTTP status code)
     pub status: Option<String>,
+    /// HTTP version of the `--check-status` response (e.g. `HTTP/2`), with
+    /// `--http-version`.
+    pub protocol: Option<String>,
+    /// `Content-Type` of the `--check-status` response, when it had one.
+    pub content_type: Option<String>,
+    /// `Content-Length` of the `--check-status` response, when it had one.
+    pub content_length: Option<u64>,
+    /// SHA-256 (hex) of the `--check-status` response body (`--body-stats`).
+    pub body_sha256: Option<String>,
+    /// Whitespace-separated words in that body (`--body-stats`).
+    pub word_count: Option<u64>,
+    /// Lines in that body (`--body-stats`).
+    pub line_count: Option<u64>,
+    /// Response headers captured by `--include-headers`, by lowercase name.
+    pub headers: BTreeMap<String, String>,
+    /// Page `<title>` found by `--extract-title`.
+    pub title: Option<String>,
     /// Providers that reported this URL (sorted, deduped). Empty when unknown.
     pub sources: Vec<String>,
+    /// Extra per-URL fields reported by providers (e.g. VirusTotal analysis
+    /// stats). Only rendered in JSON output; empty for most providers.
+    pub metadata: serde_json::Map<String, serde_json::Value>,
 }
 
 impl UrlData {
@@ -24,7 +88,16 @@ impl UrlData {
         UrlData {
             url,
             status: None,
+            protocol: None,
+            content_type: None,
+            content_length: None,
+            body_sha256: None,
+            word_count: None,
+            line_count: None,
+            headers: BTreeMap::new(),
+            title: None,
             sources: Vec::new(),
+            metadata: serde_json::Map::new(),
         }
     }
 
@@ -33,7 +106,16 @@ impl UrlData {
         UrlData {
             url,
             status: Some(status),
+            protocol: None,
+            content_type: None,
+            content_length: None,
+            body_sha256: None,
+            word_count: None,
+            line_count: None,
+            headers: BTreeMap::new(),
+            title: None,
             sources: Vec::new(),
+            metadata: serde_json::Map::new(),
         }
     }
 
@@ -46,6 +128,18 @@ impl UrlData {
         self
     }
 
+    /// Whether the entry carries a content type or length, which adds the
+    /// `content_type` / `content_length` columns to CSV output.
+    pub fn has_content_info(&self) -> bool {
+        self.content_type.is_some() || self.content_length.is_some()
+    }
+
+    /// Whether the entry carries `--body-stats` data, which adds the
+    /// `body_sha256` / `word_count` / `line_count` columns to CSV output.
+    pub fn has_body_stats(&self) -> bool {
+        self.body_sha256.is_some() || self.word_count.is_some() || self.line_count.is_some()
+    }
+
     /// Parse a URL data entry from a string
     ///
     /// Can handle strings in the format "{url} - {status}" or plain URLs
@@ -55,14 +149,32 @@ impl UrlData {
             UrlData {
                 url: url.to_string(),
                 status: Some(status.to_string()),
+                protocol: None,
+                content_type: None,
+                content_length: None,
+                body_sha256: None,
+                word_count: None,
+                line_count: None,
+                headers: BTreeMap::new(),
+                title: None,
                 sources: Vec::new(),
+                metadata: serde_json::Map::new(),
             }
         } else {
             // No status information found
             UrlData {
                 url: data,
                 status: None,
+                protocol: None,
+                content_type: None,
+                content_length: None,
+                body_sha256: None,
+                word_count: None,
+                line_count: None,
+                headers: BTreeMap::new(),
+                title: None,
                 sources: Vec::new(),
+                metadata: serde_json::Map::new(),
             }
         }
     }
@@ -82,15 +194,42 @@ pub trait Outputter: Send + Sync {
 /// Supported formats:
 /// - "json": JSON format with URL and optional status
 /// - "csv": CSV format with URL and optional status
+/// - "html": a self-contained, searchable HTML report
+/// - "sitemap": a sitemap.xml of the live URLs
+/// - "burp": Burp Suite saved-items XML, one GET request per URL
+/// - "tree": an ASCII tree of each host's path segments
+/// - "dot": the same tree as a Graphviz digraph
+/// - "parquet": typed columns in a Parquet file (with the `parquet` feature)
 /// - any other value: Plain text format with one URL per line
 pub fn create_outputter(format: &str) -> Box<dyn Outputter> {
     match format.to_lowercase().as_str() {
         "json" => Box::new(JsonOutputter::new()),
         "csv" => Box::new(CsvOutputter::new()),
+        "html" => Box::new(HtmlOutputter::new()),
+        "sitemap" => Box::new(SitemapOutputter::new()),
+        "burp" => Box::new(BurpOutputter::new()),
+        "tree" => Box::new(TreeOutputter::new()),
+        "dot" => Box::new(DotOutputter::new()),
+        #[cfg(feature = "parquet")]
+        "parquet" => Box::new(ParquetOutputter::new()),
         _ => Box::new(PlainOutputter::new()),
     }
 }
 
+/// Create a CSV outputter that writes the `--csv-columns` selection
+pub fn create_csv_outputter(columns: CsvColumns) -> Box<dyn Outputter> {
+    Box::new(CsvOutputter::with_columns(columns))
+}
+
+/// Create a plain-text outputter that shapes each line with a
+/// `--format-template` (see [`TemplateFormatter`])
+pub fn create_template_outputter(template: &str) -> Result<Box<dyn Outputter>> {
+    let formatter = TemplateFormatter::parse(template)?;
+    Ok(Box::new(PlainOutputter::with_formatter(Box::new(
+        formatter,
+    ))))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,7 +241,7 @@ mod tests {
         let url_data = UrlData::new("https://example.com".to_string());
         assert_eq!(
             outputter.format(&url_data, false),
-            "{\"url\":\"https://example.com\"},"
+            "{\"url\":\"https://example.com\",\"schema_version\":1},"
         );
     }
 
@@ -133,7 +272,7 @@ mod tests {
         let url_data = UrlData::new("https://example.com".to_string());
         assert_eq!(
             json_outputter.format(&url_data, false),
-            "{\"url\":\"https://example.com\"},"
+            "{\"url\":\"https://example.com\",\"schema_version\":1},"
         );
 
         let csv_outputter = create_outputter("CSV");
@@ -238,7 +377,7 @@ mod tests {
         let url_data = UrlData::new("https://example.com".to_string());
         assert_eq!(
             outputter.format(&url_data, false),
-            "{\"url\":\"https://example.com\"},"
+            "{\"url\":\"https://example.com\",\"schema_version\":1},"
         );
     }
 }
@@ -0,0 +1,199 @@
+use anyhow::{Context, Result};
+use arrow_array::builder::{ListBuilder, MapBuilder, StringBuilder, UInt16Builder, UInt64Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{Field, Schema};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use super::{Outputter, UrlData};
+
+/// Rows per record batch (and so at most per row group).
+const BATCH_ROWS: usize = 65_536;
+
+/// The leading status code of `status`, e.g. 404 for `404 Not Found`.
+fn status_code(status: &str) -> Option<u16> {
+    status.split_whitespace().next()?.parse().ok()
+}
+
+/// The columns of `urls`, in schema order.
+fn columns(urls: &[UrlData]) -> Vec<(&'static str, ArrayRef)> {
+    let mut url = StringBuilder::new();
+    let mut host = StringBuilder::new();
+    let mut status = StringBuilder::new();
+    let mut code = UInt16Builder::new();
+    let mut protocol = StringBuilder::new();
+    let mut content_type = StringBuilder::new();
+    let mut content_length = UInt64Builder::new();
+    let mut body_sha256 = StringBuilder::new();
+    let mut word_count = UInt64Builder::new();
+    let mut line_count = UInt64Builder::new();
+    let mut title = StringBuilder::new();
+    let mut sources = ListBuilder::new(StringBuilder::new());
+    let mut headers = MapBuilder::new(None, StringBuilder::new(), StringBuilder::new());
+    let mut metadata = StringBuilder::new();
+
+    for entry in urls {
+        url.append_value(&entry.url);
+        host.append_option(
+            url::Url::parse(&entry.url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string)),
+        );
+        status.append_option(entry.status.as_deref());
+        code.append_option(entry.status.as_deref().and_then(status_code));
+        protocol.append_option(entry.protocol.as_deref());
+        content_type.append_option(entry.content_type.as_deref());
+        content_length.append_option(entry.content_length);
+        body_sha256.append_option(entry.body_sha256.as_deref());
+        word_count.append_option(entry.word_count);
+        line_count.append_option(entry.line_count);
+        title.append_option(entry.title.as_deref());
+        for source in &entry.sources {
+            sources.values().append_value(source);
+        }
+        sources.append(true);
+        for (name, value) in &entry.headers {
+            headers.keys().append_value(name);
+            headers.values().append_value(value);
+        }
+        // Only fails when keys and values are out of step, which they can't be
+        let _ = headers.append(true);
+        metadata.append_option(
+            (!entry.metadata.is_empty())
+                .then(|| serde_json::Value::Object(entry.metadata.clone()).to_string()),
+        );
+    }
+
+    vec![
+        ("url", Arc::new(url.finish()) as ArrayRef),
+        ("host", Arc::new(host.finish())),
+        ("status", Arc::new(status.finish())),
+        ("status_code", Arc::new(code.finish())),
+        ("protocol", Arc::new(protocol.finish())),
+        ("content_type", Arc::new(content_type.finish())),
+        ("content_length", Arc::new(content_length.finish())),
+        ("body_sha256", Arc::new(body_sha256.finish())),
+        ("word_count", Arc::new(word_count.finish())),
+        ("line_count", Arc::new(line_count.finish())),
+        ("title", Arc::new(title.finish())),
+        ("sources", Arc::new(sources.finish())),
+        ("headers", Arc::new(headers.finish())),
+        ("metadata", Arc::new(metadata.finish())),
+    ]
+}
+
+/// Outputter for `--format parquet` (with the `parquet` feature): one row per
+/// URL with typed columns — `status_code` as a number, `sources` as a list,
+/// `headers` as a map and `metadata` as a JSON string — zstd-compressed, for
+/// loading into DuckDB, Spark or pandas. Needs `--output`.
+#[derive(Debug, Clone)]
+pub struct ParquetOutputter;
+
+impl ParquetOutputter {
+    pub fn new() -> Self {
+        ParquetOutputter
+    }
+
+    fn write_parquet(urls: &[UrlData], file: std::fs::File) -> Result<()> {
+        let schema_columns = columns(&[]);
+        let schema = Arc::new(Schema::new(
+            schema_columns
+                .iter()
+                .map(|(name, array)| Field::new(*name, array.data_type().clone(), *name != "url"))
+                .collect::<Vec<_>>(),
+        ));
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(properties))?;
+        for chunk in urls.chunks(BATCH_ROWS) {
+            let arrays = columns(chunk).into_iter().map(|(_, array)| array).collect();
+            writer.write(&RecordBatch::try_new(schema.clone(), arrays)?)?;
+        }
+        writer.close()?;
+        Ok(())
+    }
+}
+
+impl Outputter for ParquetOutputter {
+    /// The URL alone; Parquet is written for the whole run
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        format!("{}\n", url_data.url)
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, _silent: bool) -> Result<()> {
+        let path = output_path
+            .ok_or_else(|| anyhow::anyhow!("--format parquet is binary and needs --output FILE"))?;
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create output file {}", path.display()))?;
+        Self::write_parquet(urls, file)
+            .with_context(|| format!("Failed to write Parquet to {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{UInt16Type, UInt64Type};
+    use arrow_array::Array;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    #[test]
+    fn test_parquet_round_trip() -> Result<()> {
+        let mut tested =
+            UrlData::with_status("https://example.com/a".into(), "404 Not Found".into())
+                .with_sources(vec!["wayback".into(), "otx".into()]);
+        tested.content_length = Some(512);
+        tested.headers.insert("server".into(), "nginx".into());
+        tested
+            .metadata
+            .insert("timestamp".into(), "2020-01-02T03:04:05Z".into());
+        let urls = vec![tested, UrlData::new("https://b.example.com/".into())];
+
+        let temp_file = tempfile::NamedTempFile::new()?;
+        ParquetOutputter::new().output(&urls, Some(temp_file.path().to_path_buf()), false)?;
+
+        let reader =
+            ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(temp_file.path())?)?
+                .build()?;
+        let batches: Vec<RecordBatch> = reader.collect::<std::result::Result<_, _>>()?;
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 2);
+        assert!(!batch.schema().field_with_name("url")?.is_nullable());
+
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        assert_eq!(
+            column("url").as_string::<i32>().value(1),
+            "https://b.example.com/"
+        );
+        assert_eq!(column("host").as_string::<i32>().value(1), "b.example.com");
+        let codes = column("status_code");
+        assert_eq!(codes.as_primitive::<UInt16Type>().value(0), 404);
+        assert!(codes.is_null(1));
+        assert_eq!(
+            column("content_length")
+                .as_primitive::<UInt64Type>()
+                .value(0),
+            512
+        );
+        let sources = column("sources");
+        let first = sources.as_list::<i32>().value(0);
+        assert_eq!(first.as_string::<i32>().value(0), "otx");
+        assert_eq!(first.as_string::<i32>().value(1), "wayback");
+        let headers = column("headers");
+        let headers = headers.as_map();
+        assert_eq!(headers.keys().as_string::<i32>().value(0), "server");
+        assert_eq!(headers.values().as_string::<i32>().value(0), "nginx");
+        assert_eq!(
+            column("metadata").as_string::<i32>().value(0),
+            r#"{"timestamp":"2020-01-02T03:04:05Z"}"#
+        );
+        assert!(column("metadata").is_null(1));
+        Ok(())
+    }
+}
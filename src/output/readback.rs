@@ -0,0 +1,210 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use super::compress::read_output_file;
+use super::UrlData;
+use crate::providers::SNAPSHOT_FIELDS;
+
+/// An entry of a previous `--format json` run, as [`JsonUrlEntry`]
+/// serializes it.
+///
+/// [`JsonUrlEntry`]: super::formatter::JsonUrlEntry
+#[derive(Deserialize)]
+struct StoredEntry {
+    url: String,
+    status: Option<String>,
+    protocol: Option<String>,
+    content_type: Option<String>,
+    content_length: Option<u64>,
+    body_sha256: Option<String>,
+    word_count: Option<u64>,
+    line_count: Option<u64>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    title: Option<String>,
+    #[serde(default)]
+    sources: Vec<String>,
+    #[serde(default)]
+    metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl From<StoredEntry> for UrlData {
+    fn from(stored: StoredEntry) -> Self {
+        UrlData {
+            url: stored.url,
+            status: stored.status,
+            protocol: stored.protocol,
+            content_type: stored.content_type,
+            content_length: stored.content_length,
+            body_sha256: stored.body_sha256,
+            word_count: stored.word_count,
+            line_count: stored.line_count,
+            headers: stored.headers,
+            title: stored.title,
+            sources: stored.sources,
+            metadata: stored.metadata,
+        }
+    }
+}
+
+/// Split CSV text into records, honoring quoted fields (which may hold
+/// commas, doubled quotes and line breaks).
+fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+/// Rebuild entries from a previous `--format csv` run, by column name.
+fn read_csv(text: &str) -> Vec<UrlData> {
+    let mut records = parse_csv(text).into_iter();
+    let Some(columns) = records.next() else {
+        return Vec::new();
+    };
+    records
+        .filter_map(|record| {
+            let mut entry = UrlData::new(String::new());
+            for (column, value) in columns.iter().zip(record) {
+                if value.is_empty() {
+                    continue;
+                }
+                let number = value.parse().ok();
+                match column.as_str() {
+                    "url" => entry.url = value,
+                    "status" => entry.status = Some(value),
+                    "protocol" => entry.protocol = Some(value),
+                    "content_type" => entry.content_type = Some(value),
+                    "content_length" => entry.content_length = number,
+                    "body_sha256" => entry.body_sha256 = Some(value),
+                    "word_count" => entry.word_count = number,
+                    "line_count" => entry.line_count = number,
+                    "title" => entry.title = Some(value),
+                    "sources" => entry.sources = value.split('|').map(String::from).collect(),
+                    name if name == super::DIFF_KEY || SNAPSHOT_FIELDS.contains(&name) => {
+                        entry.metadata.insert(name.to_string(), value.into());
+                    }
+                    name => {
+                        if let Some(header) = name.strip_prefix("header_") {
+                            entry.headers.insert(header.to_string(), value);
+                        }
+                    }
+                }
+            }
+            (!entry.url.is_empty()).then_some(entry)
+        })
+        .collect()
+}
+
+/// The URL of a plain-format line: everything before the first ` [` tag.
+pub(crate) fn plain_line_url(line: &str) -> &str {
+    line.split_once(" [").map_or(line, |(url, _)| url).trim()
+}
+
+/// Entries of an output file's text in `format` (`json`, `csv`, anything
+/// else read as plain lines). Plain lines only give back their URL.
+pub(crate) fn parse_entries(text: &str, format: &str) -> Result<Vec<UrlData>> {
+    Ok(match format {
+        "json" if text.trim().is_empty() => Vec::new(),
+        "json" => serde_json::from_str::<Vec<StoredEntry>>(text)
+            .context("not a urx JSON output file")?
+            .into_iter()
+            .map(UrlData::from)
+            .collect(),
+        "csv" => read_csv(text),
+        _ => text
+            .lines()
+            .map(plain_line_url)
+            .filter(|url| !url.is_empty())
+            .map(|url| UrlData::new(url.to_string()))
+            .collect(),
+    })
+}
+
+/// The format an output file's text looks like: a JSON array, a CSV file
+/// with urx's `url` header, or plain lines.
+fn sniff_format(text: &str) -> &'static str {
+    let first_line = text.trim_start().lines().next().unwrap_or("");
+    if first_line.starts_with('[') {
+        "json"
+    } else if first_line == "url" || first_line.starts_with("url,") {
+        "csv"
+    } else {
+        "plain"
+    }
+}
+
+/// Read back the entries of a urx output file in any of the formats
+/// [`parse_entries`] knows, compressed or not.
+pub fn read_entries(path: &Path) -> Result<Vec<UrlData>> {
+    let text = read_output_file(path)?;
+    parse_entries(&text, sniff_format(&text)).with_context(|| path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_entries_sniffs_the_format() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let cases = [
+            (
+                "a.json",
+                r#"[{"url":"https://example.com/a","status":"200 OK","metadata":{"diff":"new"},"schema_version":1}]"#,
+            ),
+            (
+                "a.csv",
+                "url,status,title,diff\nhttps://example.com/a,200 OK,\"Hi, \"\"you\"\"\nthere\",new\n",
+            ),
+            ("a.txt", "https://example.com/a [200 OK] [NEW]\n\n"),
+        ];
+        for (name, text) in cases {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text)?;
+            let entries = read_entries(&path)?;
+            assert_eq!(entries.len(), 1, "{name}");
+            assert_eq!(entries[0].url, "https://example.com/a", "{name}");
+            if name != "a.txt" {
+                assert_eq!(entries[0].status.as_deref(), Some("200 OK"), "{name}");
+                assert_eq!(entries[0].metadata["diff"], "new", "{name}");
+            }
+        }
+        assert_eq!(
+            parse_entries("url\nhttps://example.com/a\n", "csv")?[0].title,
+            None
+        );
+        assert_eq!(
+            read_csv("url,title\nhttps://example.com/b,\"Hi, \"\"you\"\"\nthere\"\n")[0]
+                .title
+                .as_deref(),
+            Some("Hi, \"you\"\nthere")
+        );
+        Ok(())
+    }
+}
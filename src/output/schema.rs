@@ -0,0 +1,102 @@
+/// Version of the JSON output layout, emitted as `schema_version` on every
+/// entry. Adding a field keeps the version; renaming or removing one, or
+/// changing its type, bumps it.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// JSON Schema for `--format json` output, printed by `--schema` and published
+/// on the docs site under `/schema/`.
+pub const OUTPUT_SCHEMA: &str = include_str!("../../docs/static/schema/output-v1.json");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::{Formatter, JsonFormatter, UrlData};
+    use serde_json::Value;
+
+    fn schema() -> Value {
+        serde_json::from_str(OUTPUT_SCHEMA).expect("schema is valid JSON")
+    }
+
+    #[test]
+    fn test_schema_matches_version() {
+        let schema = schema();
+        let entry = &schema["$defs"]["entry"];
+        assert_eq!(
+            entry["properties"]["schema_version"]["const"],
+            SCHEMA_VERSION
+        );
+        assert!(schema["$id"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("output-v{SCHEMA_VERSION}.json")));
+    }
+
+    /// Frozen field names for schema version 1. If this fails, a field was
+    /// renamed or removed: bump SCHEMA_VERSION and publish a new schema file
+    /// instead of editing this list.
+    #[test]
+    fn test_v1_fields_are_stable() {
+        let schema = schema();
+        let entry = &schema["$defs"]["entry"];
+        let mut fields: Vec<&str> = entry["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        fields.sort();
+        assert_eq!(
+            fields,
+            [
+                "body_sha256",
+                "content_length",
+                "content_type",
+                "headers",
+                "line_count",
+                "metadata",
+                "protocol",
+                "schema_version",
+                "sources",
+                "status",
+                "title",
+                "url",
+                "word_count"
+            ]
+        );
+        assert_eq!(
+            entry["required"],
+            serde_json::json!(["url", "schema_version"])
+        );
+    }
+
+    /// Every key the JSON formatter emits must be declared by the schema.
+    #[test]
+    fn test_json_output_conforms_to_schema() {
+        let schema = schema();
+        let properties = schema["$defs"]["entry"]["properties"]
+            .as_object()
+            .unwrap()
+            .clone();
+
+        let mut full = UrlData::with_status("https://example.com/a".into(), "200 OK".into())
+            .with_sources(vec!["wayback".into()]);
+        full.metadata.insert("mime".into(), "text/html".into());
+        full.protocol = Some("HTTP/2".into());
+        full.content_type = Some("text/html".into());
+        full.content_length = Some(1024);
+        full.body_sha256 = Some("e3b0c442".into());
+        full.word_count = Some(120);
+        full.line_count = Some(30);
+        full.headers.insert("server".into(), "nginx".into());
+        full.title = Some("Home".into());
+        for data in [UrlData::new("https://example.com".into()), full] {
+            let line = JsonFormatter::new().format(&data, true);
+            let entry: Value = serde_json::from_str(line.trim()).unwrap();
+            let entry = entry.as_object().unwrap();
+            assert_eq!(entry["schema_version"], SCHEMA_VERSION);
+            for key in entry.keys() {
+                assert!(properties.contains_key(key), "undeclared field {key}");
+            }
+        }
+    }
+}
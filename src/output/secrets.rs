@@ -0,0 +1,18 @@
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::testers::SecretFinding;
+
+/// Write `--scan-secrets` findings to `path` as a JSON array of
+/// `{url, rule, secret}` objects.
+pub fn write_secrets_json(findings: &[SecretFinding], path: &Path) -> Result<()> {
+    let file = File::create(path).context("Failed to create secrets output file")?;
+    let mut out = BufWriter::new(file);
+    serde_json::to_writer_pretty(&mut out, findings)
+        .context("Failed to write secrets output file")?;
+    writeln!(out).context("Failed to write secrets output file")?;
+    out.flush().context("Failed to write secrets output file")?;
+    Ok(())
+}
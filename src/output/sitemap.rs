@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::{OutputFile, Outputter, UrlData};
+
+/// Most URLs one sitemap may list (sitemaps.org protocol).
+const MAX_SITEMAP_URLS: usize = 50_000;
+
+/// Escape the characters XML reserves, plus quotes, as the sitemap protocol
+/// requires for `<loc>` values.
+pub(crate) fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Whether an entry belongs in the sitemap: an HTTP(S) URL that, when
+/// tested, answered 2xx. Untested URLs are assumed live.
+fn is_listed(url_data: &UrlData) -> bool {
+    let url = url_data.url.to_ascii_lowercase();
+    let http = url.starts_with("http://") || url.starts_with("https://");
+    let live = url_data
+        .status
+        .as_deref()
+        .is_none_or(|status| status.starts_with('2'));
+    http && live
+}
+
+/// Outputter for `--format sitemap`: a sitemaps.org `urlset` of the live
+/// URLs, for SEO audits or seeding another crawler. URLs answering anything
+/// but 2xx under `--check-status` are left out.
+#[derive(Debug, Clone)]
+pub struct SitemapOutputter;
+
+impl SitemapOutputter {
+    pub fn new() -> Self {
+        SitemapOutputter
+    }
+
+    fn write_sitemap(&self, urls: &[UrlData], mut out: impl Write) -> std::io::Result<usize> {
+        writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            out,
+            r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#
+        )?;
+        let mut listed = 0;
+        for url_data in urls.iter().filter(|u| is_listed(u)) {
+            out.write_all(self.format(url_data, false).as_bytes())?;
+            listed += 1;
+        }
+        writeln!(out, "</urlset>")?;
+        out.flush()?;
+        Ok(listed)
+    }
+}
+
+impl Outputter for SitemapOutputter {
+    /// One `<url>` element, whether or not the entry would be listed
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        format!("  <url><loc>{}</loc></url>\n", xml_escape(&url_data.url))
+    }
+
+    fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
+        let listed = match output_path {
+            Some(path) => {
+                let mut file = OutputFile::create(&path)?;
+                let listed = self
+                    .write_sitemap(urls, &mut file)
+                    .context("Failed to write to output file")?;
+                file.finish()?;
+                listed
+            }
+            None if silent => return Ok(()),
+            None => self
+                .write_sitemap(urls, std::io::stdout().lock())
+                .context("Failed to write sitemap")?,
+        };
+        if listed > MAX_SITEMAP_URLS && !silent {
+            eprintln!(
+                "Warning: the sitemap lists {listed} URLs; search engines read at most {MAX_SITEMAP_URLS} per file"
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sitemap_lists_live_http_urls() -> Result<()> {
+        let urls = vec![
+            UrlData::new("https://example.com/search?q=a&lang=<en>".into()),
+            UrlData::with_status("https://example.com/ok".into(), "200 OK".into()),
+            UrlData::with_status("https://example.com/gone".into(), "404 Not Found".into()),
+            UrlData::with_status(
+                "https://example.com/moved".into(),
+                "301 Moved Permanently".into(),
+            ),
+            UrlData::new("ftp://example.com/file".into()),
+        ];
+        let temp_file = tempfile::NamedTempFile::new()?;
+        SitemapOutputter::new().output(&urls, Some(temp_file.path().to_path_buf()), false)?;
+
+        assert_eq!(
+            std::fs::read_to_string(temp_file.path())?,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url><loc>https://example.com/search?q=a&amp;lang=&lt;en&gt;</loc></url>
+  <url><loc>https://example.com/ok</loc></url>
+</urlset>
+"#
+        );
+        Ok(())
+    }
+}
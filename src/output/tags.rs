@@ -0,0 +1,209 @@
+/// Input target tags (`example.com#prod`, or a `tag` column in a CSV domain
+/// list) carried onto the URLs collected for each target, and the
+/// `--split-output-by-tag` writer built on them.
+use super::{Outputter, UrlData};
+use crate::filters::HostValidator;
+use anyhow::Result;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Metadata key holding a URL's target tags. Kept apart from `tags`, which
+/// testers use for finding labels such as `xss-candidate`.
+pub const TARGET_TAGS_KEY: &str = "target_tags";
+
+/// Tags by input target, in the normalized form the providers were queried
+/// with.
+#[derive(Debug, Default, Clone)]
+pub struct TargetTags {
+    targets: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl TargetTags {
+    pub fn add(&mut self, domain: &str, tag: &str) {
+        self.targets
+            .entry(domain.to_string())
+            .or_default()
+            .insert(tag.to_string());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    /// Record on each URL the tags of every target whose scope it falls in,
+    /// judged the way `--strict` scopes hosts.
+    pub fn apply(&self, urls: &mut [UrlData], include_subdomains: bool, ignore_port: bool) {
+        let scopes: Vec<(HostValidator, &BTreeSet<String>)> = self
+            .targets
+            .iter()
+            .map(|(domain, tags)| {
+                let mut validator =
+                    HostValidator::new(std::slice::from_ref(domain), include_subdomains);
+                validator.with_ignore_port(ignore_port);
+                (validator, tags)
+            })
+            .collect();
+        for entry in urls {
+            let tags: BTreeSet<&String> = scopes
+                .iter()
+                .filter(|(validator, _)| validator.is_valid_host(&entry.url))
+                .flat_map(|(_, tags)| tags.iter())
+                .collect();
+            if !tags.is_empty() {
+                let tags: Vec<String> = tags.into_iter().cloned().collect();
+                entry
+                    .metadata
+                    .insert(TARGET_TAGS_KEY.to_string(), tags.into());
+            }
+        }
+    }
+}
+
+/// Split a target tag off an input line: `example.com#prod`, or the CSV form
+/// `example.com,prod`. Full URLs keep their `#fragment`, and a suffix that
+/// isn't a valid tag (letters, digits, `.`, `_`, `-`) is left on the target.
+pub fn split_target_tag(line: &str) -> (&str, Option<&str>) {
+    let split = match line.split_once(',') {
+        Some(pair) => Some(pair),
+        None if !line.contains("://") => line.rsplit_once('#'),
+        None => None,
+    };
+    match split {
+        Some((target, tag)) if is_valid_tag(tag.trim()) => (target.trim(), Some(tag.trim())),
+        Some((target, tag)) if tag.trim().is_empty() => (target.trim(), None),
+        _ => (line.trim(), None),
+    }
+}
+
+fn is_valid_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+/// `results.json` + `prod` -> `results.prod.json`, and
+/// `results.json.gz` -> `results.prod.json.gz`.
+fn tag_output_path(output: &Path, tag: &str) -> PathBuf {
+    if let Some(compression) = super::Compression::from_path(output) {
+        return compression.apply_to(&tag_output_path(&output.with_extension(""), tag));
+    }
+    let stem = output
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match output.extension() {
+        Some(ext) => format!("{stem}.{tag}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{tag}"),
+    };
+    output.with_file_name(name)
+}
+
+/// `--split-output-by-tag`: write each tag's URLs to its own file next to
+/// `output` (see [`tag_output_path`]). A URL whose target carries several
+/// tags lands in each of their files; untagged URLs only stay in `output`.
+/// Returns the files written.
+pub fn write_split_by_tag(
+    urls: &[UrlData],
+    output: &Path,
+    outputter: &dyn Outputter,
+    silent: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut by_tag: BTreeMap<&str, Vec<UrlData>> = BTreeMap::new();
+    for entry in urls {
+        let Some(tags) = entry
+            .metadata
+            .get(TARGET_TAGS_KEY)
+            .and_then(|v| v.as_array())
+        else {
+            continue;
+        };
+        for tag in tags.iter().filter_map(|t| t.as_str()) {
+            by_tag.entry(tag).or_default().push(entry.clone());
+        }
+    }
+
+    let mut written = Vec::new();
+    for (tag, entries) in by_tag {
+        let path = tag_output_path(output, tag);
+        outputter.output(&entries, Some(path.clone()), silent)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_target_tag() {
+        assert_eq!(
+            split_target_tag("example.com#prod"),
+            ("example.com", Some("prod"))
+        );
+        assert_eq!(
+            split_target_tag("staging.example.com, staging"),
+            ("staging.example.com", Some("staging"))
+        );
+        assert_eq!(split_target_tag("example.com,"), ("example.com", None));
+        assert_eq!(
+            split_target_tag("https://example.com/#section"),
+            ("https://example.com/#section", None)
+        );
+        assert_eq!(
+            split_target_tag("example.com#no way"),
+            ("example.com#no way", None)
+        );
+        assert_eq!(split_target_tag("example.com"), ("example.com", None));
+    }
+
+    #[test]
+    fn test_tags_apply_and_split_output() {
+        let mut tags = TargetTags::default();
+        tags.add("example.com", "prod");
+        tags.add("staging.example.net", "staging");
+        let mut urls = vec![
+            UrlData::new("https://example.com/login".to_string()),
+            UrlData::new("https://api.example.com/v1".to_string()),
+            UrlData::new("https://staging.example.net/debug".to_string()),
+            UrlData::new("https://other.org/".to_string()),
+        ];
+
+        tags.apply(&mut urls, true, false);
+        assert_eq!(
+            urls[0].metadata[TARGET_TAGS_KEY],
+            serde_json::json!(["prod"])
+        );
+        assert_eq!(
+            urls[1].metadata[TARGET_TAGS_KEY],
+            serde_json::json!(["prod"])
+        );
+        assert_eq!(
+            urls[2].metadata[TARGET_TAGS_KEY],
+            serde_json::json!(["staging"])
+        );
+        assert!(!urls[3].metadata.contains_key(TARGET_TAGS_KEY));
+
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("results.txt");
+        let written =
+            write_split_by_tag(&urls, &output, &crate::output::PlainOutputter::new(), true)
+                .unwrap();
+        assert_eq!(
+            written,
+            vec![
+                dir.path().join("results.prod.txt"),
+                dir.path().join("results.staging.txt")
+            ]
+        );
+        let prod = std::fs::read_to_string(&written[0]).unwrap();
+        assert!(prod.contains("https://example.com/login"));
+        assert!(prod.contains("https://api.example.com/v1"));
+        assert!(!prod.contains("staging"));
+        assert_eq!(
+            tag_output_path(Path::new("out/results.json.gz"), "prod"),
+            PathBuf::from("out/results.prod.json.gz")
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use url::Url;
+
+use super::{Formatter, UrlData};
+
+/// Placeholders `--format-template` accepts besides `{headers.NAME}` and
+/// `{metadata.KEY}`.
+const FIELDS: &[&str] = &[
+    "url",
+    "scheme",
+    "host",
+    "port",
+    "path",
+    "query",
+    "status",
+    "protocol",
+    "content_type",
+    "content_length",
+    "body_sha256",
+    "word_count",
+    "line_count",
+    "title",
+    "sources",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(String),
+    Header(String),
+    Metadata(String),
+}
+
+/// Formatter for `--format-template`: one line per URL, with `{field}`
+/// placeholders replaced by the entry's values (empty when it has none).
+/// `{{` and `}}` stand for literal braces, `\t` and `\n` for a tab and a
+/// newline.
+#[derive(Debug, Clone)]
+pub struct TemplateFormatter {
+    parts: Vec<Part>,
+}
+
+impl TemplateFormatter {
+    /// Parse a template, rejecting unknown placeholders and unbalanced braces.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in chars.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        return Err(anyhow::anyhow!(
+                            "Unclosed '{{{name}' in --format-template; write '{{{{' for a literal brace"
+                        ));
+                    }
+                    let name = name.trim();
+                    let part = if let Some(header) = name.strip_prefix("headers.") {
+                        Part::Header(header.to_ascii_lowercase())
+                    } else if let Some(key) = name.strip_prefix("metadata.") {
+                        Part::Metadata(key.to_string())
+                    } else if FIELDS.contains(&name) {
+                        Part::Field(name.to_string())
+                    } else {
+                        return Err(anyhow::anyhow!(
+                            "Unknown placeholder {{{name}}} in --format-template. Use one of {}, {{headers.NAME}} or {{metadata.KEY}}",
+                            FIELDS
+                                .iter()
+                                .map(|f| format!("{{{f}}}"))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    };
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(part);
+                }
+                '}' => {
+                    return Err(anyhow::anyhow!(
+                        "Unmatched '}}' in --format-template; write '}}}}' for a literal brace"
+                    ))
+                }
+                '\\' if chars.peek() == Some(&'t') => {
+                    chars.next();
+                    text.push('\t');
+                }
+                '\\' if chars.peek() == Some(&'n') => {
+                    chars.next();
+                    text.push('\n');
+                }
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(TemplateFormatter { parts })
+    }
+
+    fn field(url_data: &UrlData, parsed: Option<&Url>, name: &str) -> String {
+        let count = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+        match name {
+            "url" => url_data.url.clone(),
+            "scheme" => parsed.map(|u| u.scheme().to_string()).unwrap_or_default(),
+            "host" => parsed
+                .and_then(|u| u.host_str())
+                .unwrap_or_default()
+                .to_string(),
+            "port" => count(
+                parsed
+                    .and_then(|u| u.port_or_known_default())
+                    .map(u64::from),
+            ),
+            "path" => parsed.map(|u| u.path().to_string()).unwrap_or_default(),
+            "query" => parsed
+                .and_then(|u| u.query())
+                .unwrap_or_default()
+                .to_string(),
+            "status" => url_data.status.clone().unwrap_or_default(),
+            "protocol" => url_data.protocol.clone().unwrap_or_default(),
+            "content_type" => url_data.content_type.clone().unwrap_or_default(),
+            "content_length" => count(url_data.content_length),
+            "body_sha256" => url_data.body_sha256.clone().unwrap_or_default(),
+            "word_count" => count(url_data.word_count),
+            "line_count" => count(url_data.line_count),
+            "title" => url_data.title.clone().unwrap_or_default(),
+            "sources" => url_data.sources.join(","),
+            _ => String::new(),
+        }
+    }
+}
+
+impl Formatter for TemplateFormatter {
+    fn format(&self, url_data: &UrlData, _is_last: bool) -> String {
+        let parsed = Url::parse(&url_data.url).ok();
+        let mut line = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(name) => line.push_str(&Self::field(url_data, parsed.as_ref(), name)),
+                Part::Header(name) => {
+                    line.push_str(url_data.headers.get(name).map_or("", String::as_str))
+                }
+                Part::Metadata(key) => match url_data.metadata.get(key) {
+                    Some(serde_json::Value::String(s)) => line.push_str(s),
+                    Some(value) => line.push_str(&value.to_string()),
+                    None => {}
+                },
+            }
+        }
+        line.push('\n');
+        line
+    }
+
+    fn clone_box(&self) -> Box<dyn Formatter> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_fills_placeholders() {
+        let mut entry = UrlData::with_status(
+            "https://api.example.com:8443/v1/users?id=1".into(),
+            "200 OK".into(),
+        )
+        .with_sources(vec!["cc".into(), "wayback".into()]);
+        entry.headers.insert("server".into(), "nginx".into());
+        entry.metadata.insert("archived_status".into(), 301.into());
+
+        let formatter = TemplateFormatter::parse(
+            r"{host}:{port} {status}\t{path}?{query} [{sources}] {headers.Server} {metadata.archived_status} {title}{{x}}",
+        )
+        .unwrap();
+        assert_eq!(
+            formatter.format(&entry, true),
+            "api.example.com:8443 200 OK\t/v1/users?id=1 [cc,wayback] nginx 301 {x}\n"
+        );
+
+        assert!(TemplateFormatter::parse("{hostname}").is_err());
+        assert!(TemplateFormatter::parse("{url} }").is_err());
+        assert!(TemplateFormatter::parse("{url").is_err());
+    }
+}
@@ -1,11 +1,10 @@
 use crate::output::Formatter;
 use anyhow::{Context, Result};
-use std::fs::File;
-use std::io::Write;
+use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 
 // Outputter implementations for different formats
-use super::{Outputter, UrlData};
+use super::{OutputFile, Outputter, UrlData};
 
 #[derive(Debug, Clone)]
 pub struct PlainOutputter {
@@ -18,6 +17,11 @@ impl PlainOutputter {
             formatter: Box::new(super::PlainFormatter::new()),
         }
     }
+
+    /// A plain outputter whose lines come from `formatter`
+    pub fn with_formatter(formatter: Box<dyn Formatter>) -> Self {
+        PlainOutputter { formatter }
+    }
 }
 
 impl Outputter for PlainOutputter {
@@ -37,15 +41,14 @@ impl Outputter for PlainOutputter {
                 // NO_COLOR run stays colourless instead of being re-enabled.
                 let prev_colorize = colored::control::SHOULD_COLORIZE.should_colorize();
                 colored::control::set_override(false);
-                let mut file = File::create(&path).context("Failed to create output file")?;
-
                 let result = (|| {
+                    let mut file = OutputFile::create(&path)?;
                     for (i, url_data) in urls.iter().enumerate() {
                         let formatted = self.format(url_data, i == urls.len() - 1);
                         file.write_all(formatted.as_bytes())
                             .context("Failed to write to output file")?;
                     }
-                    Ok(())
+                    file.finish()
                 })();
                 colored::control::set_override(prev_colorize);
                 result
@@ -76,6 +79,16 @@ impl JsonOutputter {
             formatter: Box::new(super::JsonFormatter::new()),
         }
     }
+
+    /// Serialize every entry as one JSON array, followed by a newline.
+    fn write_array(urls: &[UrlData], out: impl Write) -> Result<()> {
+        let entries: Vec<super::formatter::JsonUrlEntry> = urls.iter().map(Into::into).collect();
+        let mut out = BufWriter::new(out);
+        serde_json::to_writer(&mut out, &entries).context("Failed to write JSON output")?;
+        out.write_all(b"\n")
+            .and_then(|_| out.flush())
+            .context("Failed to write JSON output")
+    }
 }
 
 impl Outputter for JsonOutputter {
@@ -86,35 +99,15 @@ impl Outputter for JsonOutputter {
     fn output(&self, urls: &[UrlData], output_path: Option<PathBuf>, silent: bool) -> Result<()> {
         match output_path {
             Some(path) => {
-                let mut file = File::create(&path).context("Failed to create output file")?;
-
-                file.write_all(b"[")
-                    .context("Failed to write JSON opening bracket")?;
-
-                for (i, url_data) in urls.iter().enumerate() {
-                    let formatted = self.format(url_data, i == urls.len() - 1);
-                    file.write_all(formatted.as_bytes())
-                        .context("Failed to write to output file")?;
-                }
-
-                file.write_all(b"]")
-                    .context("Failed to write JSON closing bracket")?;
-                Ok(())
+                let mut file = OutputFile::create(&path)?;
+                Self::write_array(urls, &mut file)?;
+                file.finish()
             }
             None => {
                 if silent {
                     return Ok(());
                 };
-
-                print!("[");
-
-                for (i, url_data) in urls.iter().enumerate() {
-                    let formatted = self.format(url_data, i == urls.len() - 1);
-                    print!("{formatted}");
-                }
-
-                println!("]");
-                Ok(())
+                Self::write_array(urls, std::io::stdout().lock())
             }
         }
     }
@@ -123,12 +116,22 @@ impl Outputter for JsonOutputter {
 #[derive(Debug, Clone)]
 pub struct CsvOutputter {
     formatter: Box<dyn Formatter>,
+    columns: Option<super::CsvColumns>,
 }
 
 impl CsvOutputter {
     pub fn new() -> Self {
         CsvOutputter {
             formatter: Box::new(super::CsvFormatter::new()),
+            columns: None,
+        }
+    }
+
+    /// A CSV outputter that writes exactly `columns`, in that order
+    pub fn with_columns(columns: super::CsvColumns) -> Self {
+        CsvOutputter {
+            formatter: Box::new(super::CsvFormatter::with_columns(columns.clone())),
+            columns: Some(columns),
         }
     }
 }
@@ -142,22 +145,24 @@ impl Outputter for CsvOutputter {
         // Decide the column layout once for the whole run so the header and
         // every row emit exactly the same columns (otherwise rows could carry a
         // trailing/extra comma the header doesn't, breaking strict CSV parsers).
-        let has_status = urls.iter().any(|url| url.status.is_some());
-        let has_sources = urls.iter().any(|url| !url.sources.is_empty());
-        let header = super::formatter::csv_header(has_status, has_sources);
+        let columns = match &self.columns {
+            Some(columns) => columns.names().to_vec(),
+            None => super::formatter::CsvLayout::of_all(urls).columns(),
+        };
+        let header = super::formatter::csv_header(&columns);
         match output_path {
             Some(path) => {
-                let mut file = File::create(&path).context("Failed to create output file")?;
+                let mut file = OutputFile::create(&path)?;
                 file.write_all(header.as_bytes())
                     .context("Failed to write CSV header")?;
 
                 for url_data in urls {
-                    let formatted = super::formatter::csv_row(url_data, has_status, has_sources);
+                    let formatted = super::formatter::csv_line(url_data, &columns);
                     file.write_all(formatted.as_bytes())
                         .context("Failed to write to output file")?;
                 }
 
-                Ok(())
+                file.finish()
             }
             None => {
                 if silent {
@@ -167,7 +172,7 @@ impl Outputter for CsvOutputter {
                 print!("{header}");
 
                 for url_data in urls {
-                    let formatted = super::formatter::csv_row(url_data, has_status, has_sources);
+                    let formatted = super::formatter::csv_line(url_data, &columns);
                     print!("{formatted}");
                 }
 
@@ -180,6 +185,7 @@ impl Outputter for CsvOutputter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fs::File;
     use std::io::Read;
     use tempfile::NamedTempFile;
 
@@ -204,14 +210,14 @@ mod tests {
         let url_data = UrlData::new("https://example.com".to_string());
         assert_eq!(
             outputter.format(&url_data, false),
-            "{\"url\":\"https://example.com\"},"
+            "{\"url\":\"https://example.com\",\"schema_version\":1},"
         );
 
         let url_data_status =
             UrlData::with_status("https://example.com".to_string(), "200 OK".to_string());
         assert_eq!(
             outputter.format(&url_data_status, true),
-            "{\"url\":\"https://example.com\",\"status\":\"200 OK\"}\n"
+            "{\"url\":\"https://example.com\",\"status\":\"200 OK\",\"schema_version\":1}\n"
         );
     }
 
@@ -301,12 +307,38 @@ mod tests {
 
         assert_eq!(
             content,
-            "[{\"url\":\"https://example.com/page1\"},{\"url\":\"https://example.com/page2\",\"status\":\"200 OK\"}\n]"
+            "[{\"url\":\"https://example.com/page1\",\"schema_version\":1},{\"url\":\"https://example.com/page2\",\"status\":\"200 OK\",\"schema_version\":1}]\n"
         );
 
         Ok(())
     }
 
+    #[test]
+    fn test_json_outputter_writes_valid_array_with_all_fields() -> Result<()> {
+        let outputter = JsonOutputter::new();
+        let mut full = UrlData::with_status("https://example.com/a".into(), "200 OK".into())
+            .with_sources(vec!["cc".into(), "wayback".into()]);
+        full.content_type = Some("text/html".into());
+        full.metadata
+            .insert("timestamp".into(), "2020-01-02T03:04:05Z".into());
+
+        for (urls, expected_len) in [(vec![], 0), (vec![full], 1)] {
+            let temp_file = NamedTempFile::new()?;
+            outputter.output(&urls, Some(temp_file.path().to_path_buf()), false)?;
+            let content = std::fs::read_to_string(temp_file.path())?;
+            let parsed: serde_json::Value = serde_json::from_str(&content)?;
+            let entries = parsed.as_array().unwrap();
+            assert_eq!(entries.len(), expected_len);
+            if let Some(entry) = entries.first() {
+                assert_eq!(entry["status"], "200 OK");
+                assert_eq!(entry["content_type"], "text/html");
+                assert_eq!(entry["sources"], serde_json::json!(["cc", "wayback"]));
+                assert_eq!(entry["metadata"]["timestamp"], "2020-01-02T03:04:05Z");
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_csv_outputter_file_output() -> Result<()> {
         let outputter = CsvOutputter::new();
@@ -335,6 +367,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_csv_outputter_with_columns() -> Result<()> {
+        let outputter = CsvOutputter::with_columns("host,url,title".parse().unwrap());
+        let mut titled = UrlData::new("https://b.example.com/x".to_string());
+        titled.title = Some("Say \"hi\"".into());
+        let urls = vec![UrlData::new("https://a.example.com/".to_string()), titled];
+
+        let temp_file = NamedTempFile::new()?;
+        outputter.output(&urls, Some(temp_file.path().to_path_buf()), false)?;
+
+        let mut content = String::new();
+        File::open(temp_file.path())?.read_to_string(&mut content)?;
+        assert_eq!(
+            content,
+            "host,url,title\na.example.com,https://a.example.com/,\nb.example.com,https://b.example.com/x,\"Say \"\"hi\"\"\"\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_csv_outputter_with_sources_header() -> Result<()> {
         let outputter = CsvOutputter::new();
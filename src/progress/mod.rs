@@ -1,4 +1,5 @@
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use console::Term;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
@@ -34,6 +35,51 @@ const SPINNER_TICK_MS: u64 = 80;
 /// Steady-tick interval for determinate bars (ms).
 const BAR_TICK_MS: u64 = 80;
 
+/// Terminals narrower than this get the compact layout: the full one needs
+/// about this many columns before the elastic message gets any room.
+pub const COMPACT_WIDTH: u16 = 72;
+
+/// How much of the live progress region to draw.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressMode {
+    /// Nothing is drawn (`--no-progress`, `--silent`, or stderr isn't a terminal).
+    Hidden,
+    /// A single phase line with a short rail, for narrow terminals. The header
+    /// and the per-provider spinners are skipped.
+    Compact,
+    /// Header, per-provider spinners and phase rails.
+    Full,
+}
+
+impl ProgressMode {
+    /// Pick a mode for the current stderr. `force` (`--force-progress`) draws
+    /// even when stderr is redirected.
+    pub fn detect(disabled: bool, force: bool) -> Self {
+        let term = Term::stderr();
+        Self::for_terminal(
+            disabled,
+            force,
+            term.is_term(),
+            term.size_checked().map(|(_, cols)| cols),
+        )
+    }
+
+    /// The decision behind [`detect`], given what the terminal reports.
+    /// Redirected stderr has no width, so a forced run there uses the full
+    /// layout.
+    ///
+    /// [`detect`]: ProgressMode::detect
+    pub fn for_terminal(disabled: bool, force: bool, is_term: bool, width: Option<u16>) -> Self {
+        if disabled || (!is_term && !force) {
+            ProgressMode::Hidden
+        } else if width.is_some_and(|cols| cols < COMPACT_WIDTH) {
+            ProgressMode::Compact
+        } else {
+            ProgressMode::Full
+        }
+    }
+}
+
 /// Style for a provider line while a fetch is in flight: the spinner is the
 /// gutter glyph (col 3), the bold provider name follows, a dimmed elapsed timer
 /// in a fixed column, then the free-form status message. Elapsed sits *before*
@@ -133,6 +179,7 @@ impl ProgressReporter {
 pub struct ProgressManager {
     multi_progress: MultiProgress,
     no_progress: bool,
+    compact: bool,
 }
 
 impl ProgressManager {
@@ -140,9 +187,58 @@ impl ProgressManager {
         ProgressManager {
             multi_progress: MultiProgress::new(),
             no_progress,
+            compact: false,
         }
     }
 
+    /// A manager drawing in `mode`, as picked by [`ProgressMode::detect`]. A
+    /// visible mode off a terminal can only come from `--force-progress`, so it
+    /// swaps indicatif's default target (which hides itself there) for one that
+    /// always draws to stderr.
+    pub fn with_mode(mode: ProgressMode) -> Self {
+        let mut manager = ProgressManager::new(mode == ProgressMode::Hidden);
+        manager.compact = mode == ProgressMode::Compact;
+        if mode != ProgressMode::Hidden && !Term::stderr().is_term() {
+            manager
+                .multi_progress
+                .set_draw_target(ProgressDrawTarget::term_like_with_hz(
+                    Box::new(Term::stderr()),
+                    20,
+                ));
+        }
+        manager
+    }
+
+    /// Add a determinate phase rail (`◇ Domains ━━━╸──`). `counter` is the
+    /// `pos/len` template fragment, empty for percentage-only phases. The
+    /// compact layout shortens the rail and drops the label padding.
+    fn add_phase_bar(&self, label: &str, total: u64, color: &str, counter: &str) -> ProgressBar {
+        let (template, prefix) = if self.compact {
+            (
+                format!(
+                    "{{prefix:.#a7b6c2}} {{bar:10.{color}/#3b424d}} {counter}{{wide_msg:.#8b949e}}"
+                ),
+                label.to_string(),
+            )
+        } else {
+            // "◇ <label>" — the ◇ sits in the same gutter column as the provider
+            // status glyphs; the 12-wide label keeps every rail starting at one column.
+            (
+                format!("  {{prefix:.#a7b6c2}} {{bar:26.{color}/#3b424d}}  {counter}{{wide_msg:.#8b949e}}"),
+                format!("◇ {label:<12}"),
+            )
+        };
+        let style = ProgressStyle::with_template(&template)
+            .expect("phase bar template is valid")
+            .progress_chars(BAR_FILL);
+
+        let bar = self.multi_progress.add(ProgressBar::new(total));
+        bar.set_style(style);
+        bar.set_prefix(prefix);
+        bar.enable_steady_tick(std::time::Duration::from_millis(BAR_TICK_MS));
+        bar
+    }
+
     pub fn create_domain_bar(&self, total: usize) -> ProgressBar {
         if self.no_progress {
             // Return a hidden progress bar when progress is disabled
@@ -151,24 +247,11 @@ impl ProgressManager {
             return bar;
         }
 
-        let style = ProgressStyle::with_template(
-            "  {prefix:.#a7b6c2} {bar:26.#5ad1cd/#3b424d}  {pos:>3}/{len:<3}  {wide_msg:.#8b949e}",
-        )
-        .unwrap()
-        .progress_chars(BAR_FILL);
-
-        let bar = self.multi_progress.add(ProgressBar::new(total as u64));
-        bar.set_style(style);
-        // "◇ <label>" — the ◇ sits in the same gutter column as the provider
-        // status glyphs; the 12-wide label keeps every rail starting at one column.
-        bar.set_prefix(format!("◇ {:<12}", "Domains"));
-        bar.enable_steady_tick(std::time::Duration::from_millis(BAR_TICK_MS));
-
-        bar
+        self.add_phase_bar("Domains", total as u64, "#5ad1cd", "{pos:>3}/{len:<3}  ")
     }
 
     pub fn create_provider_bars(&self, provider_names: &[String]) -> Vec<ProgressBar> {
-        if self.no_progress {
+        if self.no_progress || self.compact {
             // Hidden spinners still accept set_message/set_style calls, so the
             // runner can drive them unconditionally without branching.
             return provider_names
@@ -214,18 +297,7 @@ impl ProgressManager {
             return bar;
         }
 
-        let style = ProgressStyle::with_template(
-            "  {prefix:.#a7b6c2} {bar:26.#5ad1cd/#3b424d}  {wide_msg:.#8b949e}",
-        )
-        .unwrap()
-        .progress_chars(BAR_FILL);
-
-        let bar = self.multi_progress.add(ProgressBar::new(100));
-        bar.set_style(style);
-        bar.set_prefix(format!("◇ {:<12}", "Filtering"));
-        bar.enable_steady_tick(std::time::Duration::from_millis(BAR_TICK_MS));
-
-        bar
+        self.add_phase_bar("Filtering", 100, "#5ad1cd", "")
     }
 
     pub fn create_transform_bar(&self) -> ProgressBar {
@@ -236,18 +308,7 @@ impl ProgressManager {
             return bar;
         }
 
-        let style = ProgressStyle::with_template(
-            "  {prefix:.#a7b6c2} {bar:26.#c29bf5/#3b424d}  {wide_msg:.#8b949e}",
-        )
-        .unwrap()
-        .progress_chars(BAR_FILL);
-
-        let bar = self.multi_progress.add(ProgressBar::new(100));
-        bar.set_style(style);
-        bar.set_prefix(format!("◇ {:<12}", "Transform"));
-        bar.enable_steady_tick(std::time::Duration::from_millis(BAR_TICK_MS));
-
-        bar
+        self.add_phase_bar("Transform", 100, "#c29bf5", "")
     }
 
     pub fn create_test_bar(&self, total: usize) -> ProgressBar {
@@ -258,18 +319,7 @@ impl ProgressManager {
             return bar;
         }
 
-        let style = ProgressStyle::with_template(
-            "  {prefix:.#a7b6c2} {bar:26.#56b6f6/#3b424d}  {pos:>5}/{len:<5}  {wide_msg:.#8b949e}",
-        )
-        .unwrap()
-        .progress_chars(BAR_FILL);
-
-        let bar = self.multi_progress.add(ProgressBar::new(total as u64));
-        bar.set_style(style);
-        bar.set_prefix(format!("◇ {:<12}", "Testing"));
-        bar.enable_steady_tick(std::time::Duration::from_millis(BAR_TICK_MS));
-
-        bar
+        self.add_phase_bar("Testing", total as u64, "#56b6f6", "{pos:>5}/{len:<5}  ")
     }
 
     /// Add the run header as a static (non-animated) line at the top of the
@@ -279,7 +329,7 @@ impl ProgressManager {
     ///
     /// [`clear`]: ProgressManager::clear
     pub fn create_header_line(&self, text: impl Into<String>) -> ProgressBar {
-        if self.no_progress {
+        if self.no_progress || self.compact {
             return ProgressBar::hidden();
         }
         let bar = self.multi_progress.add(ProgressBar::new_spinner());
@@ -408,6 +458,34 @@ mod tests {
         assert!(reporter.is_partial());
     }
 
+    #[test]
+    fn test_progress_mode_degrades_for_redirects_and_narrow_terminals() {
+        use ProgressMode::*;
+        assert_eq!(
+            ProgressMode::for_terminal(false, false, true, Some(120)),
+            Full
+        );
+        assert_eq!(
+            ProgressMode::for_terminal(false, false, true, Some(60)),
+            Compact
+        );
+        assert_eq!(
+            ProgressMode::for_terminal(false, false, false, None),
+            Hidden
+        );
+        assert_eq!(ProgressMode::for_terminal(false, true, false, None), Full);
+        assert_eq!(
+            ProgressMode::for_terminal(true, true, true, Some(120)),
+            Hidden
+        );
+
+        let compact = ProgressManager::with_mode(Compact);
+        let bars = compact.create_provider_bars(&["wayback".to_string()]);
+        assert!(bars[0].is_hidden());
+        assert!(compact.create_header_line("urx").is_hidden());
+        assert_eq!(compact.create_domain_bar(3).length(), Some(3));
+    }
+
     #[test]
     fn test_create_provider_bars_empty() {
         let manager = ProgressManager::new(false);
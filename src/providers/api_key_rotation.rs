@@ -1,11 +1,32 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a key that hit a 429 sits out when the response carried no
+/// usable `Retry-After`.
+pub const DEFAULT_KEY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Why a key is out of rotation.
+#[derive(Clone, Copy, Debug)]
+enum KeyState {
+    /// Rate-limited; usable again at this instant.
+    CoolingUntil(Instant),
+    /// Rejected (401/403); skipped for the rest of the run.
+    Rejected,
+}
 
 /// Thread-safe API key rotation manager
+///
+/// Keys rotate round-robin, but providers can report a key as rate-limited or
+/// rejected so the rotation skips it (for a cooldown, or for the rest of the
+/// run) and the failed request can be retried straight away with another key.
+/// Clones share rotation and cooldown state.
 #[derive(Clone, Debug)]
 pub struct ApiKeyRotator {
     keys: Vec<String>,
     counter: Arc<AtomicUsize>,
+    benched: Arc<Mutex<HashMap<String, KeyState>>>,
 }
 
 impl ApiKeyRotator {
@@ -14,17 +35,30 @@ impl ApiKeyRotator {
         ApiKeyRotator {
             keys,
             counter: Arc::new(AtomicUsize::new(0)),
+            benched: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    /// Get the next API key in rotation
+    /// Get the next API key in rotation, skipping keys that are cooling down
+    /// or were rejected. When every key is benched the plain round-robin key
+    /// is returned anyway, so callers still make (and back off on) the request.
     pub fn next_key(&self) -> Option<String> {
         if self.keys.is_empty() {
             return None;
         }
 
-        let index = self.counter.fetch_add(1, Ordering::Relaxed) % self.keys.len();
-        Some(self.keys[index].clone())
+        let now = Instant::now();
+        let benched = self.benched();
+        let mut fallback = None;
+        for _ in 0..self.keys.len() {
+            let index = self.counter.fetch_add(1, Ordering::Relaxed) % self.keys.len();
+            let key = &self.keys[index];
+            if is_usable(benched.get(key), now) {
+                return Some(key.clone());
+            }
+            fallback.get_or_insert(index);
+        }
+        fallback.map(|index| self.keys[index].clone())
     }
 
     /// Get the current key without advancing the rotation
@@ -48,6 +82,66 @@ impl ApiKeyRotator {
     pub fn key_count(&self) -> usize {
         self.keys.len()
     }
+
+    /// Whether at least one key is neither cooling down nor rejected.
+    pub fn has_usable_key(&self) -> bool {
+        let now = Instant::now();
+        let benched = self.benched();
+        self.keys.iter().any(|key| is_usable(benched.get(key), now))
+    }
+
+    /// Bench `key` after a rate-limit response for `cooldown` (typically the
+    /// server's `Retry-After`), or [`DEFAULT_KEY_COOLDOWN`] when `None`.
+    /// Returns whether another key is usable right now, i.e. whether the
+    /// caller should retry immediately instead of backing off.
+    pub fn mark_rate_limited(&self, key: &str, cooldown: Option<Duration>) -> bool {
+        let until = Instant::now() + cooldown.unwrap_or(DEFAULT_KEY_COOLDOWN);
+        {
+            let mut benched = self.benched();
+            // Never downgrade a rejection to a cooldown
+            if !matches!(benched.get(key), Some(KeyState::Rejected)) {
+                benched.insert(key.to_string(), KeyState::CoolingUntil(until));
+            }
+        }
+        self.has_usable_key()
+    }
+
+    /// Drop `key` from rotation for the rest of the run (revoked, banned, or
+    /// out of quota). Returns whether another key is usable right now.
+    pub fn mark_rejected(&self, key: &str) -> bool {
+        self.benched().insert(key.to_string(), KeyState::Rejected);
+        self.has_usable_key()
+    }
+
+    /// Classify an HTTP failure for `key`: 429 benches it for `retry_after`,
+    /// 401/403 rejects it. Returns `true` when the status was a key problem and
+    /// another key is usable, so the request should be retried right away.
+    /// Providers whose API uses 403 for throttling call
+    /// [`mark_rate_limited`](Self::mark_rate_limited) directly instead.
+    pub fn report_status(&self, key: &str, status: u16, retry_after: Option<Duration>) -> bool {
+        if key.is_empty() {
+            return false;
+        }
+        match status {
+            429 => self.mark_rate_limited(key, retry_after),
+            401 | 403 => self.mark_rejected(key),
+            _ => false,
+        }
+    }
+
+    fn benched(&self) -> std::sync::MutexGuard<'_, HashMap<String, KeyState>> {
+        self.benched
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn is_usable(state: Option<&KeyState>, now: Instant) -> bool {
+    match state {
+        None => true,
+        Some(KeyState::CoolingUntil(until)) => *until <= now,
+        Some(KeyState::Rejected) => false,
+    }
 }
 
 #[cfg(test)]
@@ -158,4 +252,49 @@ mod tests {
         assert!(unique_keys.is_subset(&expected_keys));
         assert_eq!(unique_keys, expected_keys);
     }
+
+    #[test]
+    fn test_rate_limited_key_is_skipped_until_cooldown_ends() {
+        let keys = vec!["key1".to_string(), "key2".to_string()];
+        let rotator = ApiKeyRotator::new(keys);
+
+        assert!(rotator.mark_rate_limited("key1", Some(Duration::from_millis(50))));
+        for _ in 0..4 {
+            assert_eq!(rotator.next_key(), Some("key2".to_string()));
+        }
+
+        std::thread::sleep(Duration::from_millis(60));
+        let seen: HashSet<String> = (0..4).filter_map(|_| rotator.next_key()).collect();
+        assert!(seen.contains("key1"));
+    }
+
+    #[test]
+    fn test_rejected_key_never_returns() {
+        let keys = vec!["key1".to_string(), "key2".to_string()];
+        let rotator = ApiKeyRotator::new(keys);
+
+        assert!(rotator.report_status("key2", 403, None));
+        for _ in 0..4 {
+            assert_eq!(rotator.next_key(), Some("key1".to_string()));
+        }
+        // A later 429 doesn't bring a rejected key back after its cooldown
+        rotator.mark_rate_limited("key2", Some(Duration::ZERO));
+        assert_eq!(rotator.next_key(), Some("key1".to_string()));
+    }
+
+    #[test]
+    fn test_all_keys_benched_falls_back_to_round_robin() {
+        let keys = vec!["key1".to_string(), "key2".to_string()];
+        let rotator = ApiKeyRotator::new(keys);
+
+        assert!(rotator.report_status("key1", 429, None));
+        // Last usable key gone: caller should back off rather than retry
+        assert!(!rotator.report_status("key2", 429, None));
+        assert!(!rotator.has_usable_key());
+        // Still hands out a key so the request can be attempted
+        assert!(rotator.next_key().is_some());
+        // Non-key failures and keyless requests don't bench anything
+        assert!(!rotator.report_status("key1", 500, None));
+        assert!(!rotator.report_status("", 429, None));
+    }
 }
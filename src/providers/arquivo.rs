@@ -3,10 +3,11 @@ use serde::Deserialize;
 use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
 use super::Provider;
 use crate::network::client::{get_with_retry, HttpClientConfig};
-use crate::network::RateLimiter;
+use crate::network::{RateLimiter, RequestGate};
 use crate::progress::ProgressReporter;
 
 /// Hard ceiling on the number of CDX pages walked for one domain. Arquivo.pt's
@@ -54,6 +55,7 @@ pub struct ArquivoProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
     #[cfg(test)]
     base_url: String,
 }
@@ -70,6 +72,7 @@ impl ArquivoProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
             #[cfg(test)]
             base_url: "https://arquivo.pt".to_string(),
         }
@@ -89,6 +92,7 @@ impl ArquivoProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -172,10 +176,16 @@ impl Provider for ArquivoProvider {
 
                 let url = format!("{query_base}&page={page}");
 
-                if let Some(rl) = &limiter {
-                    rl.acquire().await;
-                }
-                let text = match get_with_retry(&client, &url, self.retries).await {
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    get_with_retry(&client, &url, self.retries, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                let text = match fetched {
                     Ok(text) => text,
                     Err(e) => {
                         // Best effort: a mid-walk failure shouldn't discard the
@@ -217,6 +227,15 @@ impl Provider for ArquivoProvider {
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!("GET {}&page=0", self.query_base(domain)),
+            format!(
+                "  then page=1, 2, … until a page adds no new URLs (at most {MAX_PAGES} pages)"
+            ),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -248,6 +267,14 @@ impl Provider for ArquivoProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::StreamingProvider;
+
+/// Public certstream relay. Any server speaking the certstream JSON protocol
+/// (e.g. a self-hosted `certstream-server-go`) can be swapped in via
+/// `--ct-stream-url`.
+pub const DEFAULT_CERTSTREAM_URL: &str = "wss://certstream.calidog.io/";
+
+/// Upper bound on the reconnect back-off. The public relay drops idle or slow
+/// consumers fairly often, so we never wait longer than this between attempts.
+const MAX_RECONNECT_DELAY_SECS: u64 = 60;
+
+/// Consecutive failed reconnects after which the watcher gives up; each
+/// successful connection starts the count again.
+const MAX_RECONNECT_ATTEMPTS: u32 = 10;
+
+/// Called before each reconnect with the error that ended the last session,
+/// the attempt number and the delay before it.
+pub type RetryNotifier = Arc<dyn Fn(&anyhow::Error, u32, Duration) + Send + Sync>;
+
+type CertstreamSocket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Deserialize)]
+struct CertstreamMessage {
+    message_type: String,
+    #[serde(default)]
+    data: Option<CertstreamData>,
+}
+
+#[derive(Deserialize)]
+struct CertstreamData {
+    leaf_cert: LeafCert,
+}
+
+#[derive(Deserialize)]
+struct LeafCert {
+    #[serde(default)]
+    all_domains: Vec<String>,
+}
+
+/// Pull every host in a `certificate_update` message that belongs to one of
+/// `domains` (the apex itself or any subdomain). Wildcard SANs (`*.a.example.com`)
+/// are reduced to the host they cover (`a.example.com`). Heartbeats and
+/// unparseable frames yield nothing.
+fn matching_hosts(message: &str, domains: &[String]) -> Vec<String> {
+    let parsed: CertstreamMessage = match serde_json::from_str(message) {
+        Ok(m) => m,
+        Err(_) => return Vec::new(),
+    };
+    if parsed.message_type != "certificate_update" {
+        return Vec::new();
+    }
+    let Some(data) = parsed.data else {
+        return Vec::new();
+    };
+
+    let mut hosts = Vec::new();
+    for name in data.leaf_cert.all_domains {
+        let host = name
+            .trim()
+            .trim_start_matches("*.")
+            .trim_end_matches('.')
+            .to_lowercase();
+        if host.is_empty() || hosts.contains(&host) {
+            continue;
+        }
+        let in_scope = domains
+            .iter()
+            .any(|d| host == *d || host.ends_with(&format!(".{d}")));
+        if in_scope {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// Certificate-transparency watcher backed by a certstream websocket.
+///
+/// Unlike the archive providers this never finishes on its own: it forwards a
+/// URL for every newly logged certificate whose SANs fall under a target domain
+/// and reconnects with back-off whenever the stream drops. Certificates are
+/// nearly always issued per host, so subdomains are always matched — without
+/// them the feed would be close to empty.
+///
+/// The first connection must succeed, so a wrong `--ct-stream-url` fails
+/// straight away; after a drop, reconnecting is tried up to
+/// [`MAX_RECONNECT_ATTEMPTS`] times before the error is returned.
+#[derive(Clone)]
+pub struct CertstreamProvider {
+    url: String,
+    on_retry: Option<RetryNotifier>,
+}
+
+impl CertstreamProvider {
+    /// Create a watcher for the given certstream websocket endpoint.
+    pub fn new(url: impl Into<String>) -> Self {
+        CertstreamProvider {
+            url: url.into(),
+            on_retry: None,
+        }
+    }
+
+    /// Report each reconnect (e.g. to stderr) instead of retrying silently.
+    pub fn with_retry_notifier(&mut self, on_retry: RetryNotifier) -> &mut Self {
+        self.on_retry = Some(on_retry);
+        self
+    }
+
+    async fn connect(&self) -> Result<CertstreamSocket> {
+        let (ws, _) = tokio_tungstenite::connect_async(self.url.as_str())
+            .await
+            .with_context(|| format!("Failed to connect to certstream at {}", self.url))?;
+        Ok(ws)
+    }
+
+    /// Consume one websocket session until it closes or errors, forwarding
+    /// matches to `sink`. Returns `Ok(false)` when the receiver has gone away
+    /// (the caller is shutting down) and the watcher should stop for good.
+    async fn run_session(
+        &self,
+        mut ws: CertstreamSocket,
+        domains: &[String],
+        sink: &UnboundedSender<String>,
+        seen: &mut HashSet<String>,
+    ) -> Result<bool> {
+        while let Some(frame) = ws.next().await {
+            let text = match frame.context("certstream connection error")? {
+                Message::Text(t) => t,
+                Message::Close(_) => break,
+                // tungstenite answers pings itself; binary frames aren't part
+                // of the certstream protocol.
+                _ => continue,
+            };
+            for host in matching_hosts(text.as_str(), domains) {
+                if !seen.insert(host.clone()) {
+                    continue;
+                }
+                if sink.send(format!("https://{host}/")).is_err() {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl StreamingProvider for CertstreamProvider {
+    fn watch<'a>(
+        &'a self,
+        domains: &'a [String],
+        sink: UnboundedSender<String>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
+        Box::pin(async move {
+            let domains: Vec<String> = domains
+                .iter()
+                .map(|d| d.trim().trim_end_matches('.').to_lowercase())
+                .filter(|d| !d.is_empty())
+                .collect();
+            let mut seen = HashSet::new();
+            // Nothing to retry towards if the endpoint never answered
+            let mut ws = self.connect().await?;
+            let mut delay = 1u64;
+
+            loop {
+                let mut error = match self.run_session(ws, &domains, &sink, &mut seen).await {
+                    Ok(false) => return Ok(()),
+                    // A clean close resets the back-off: the relay was healthy.
+                    Ok(true) => {
+                        delay = 1;
+                        anyhow::anyhow!("certstream closed the connection")
+                    }
+                    Err(_) if sink.is_closed() => return Ok(()),
+                    Err(e) => e,
+                };
+
+                let mut attempt = 0;
+                ws = loop {
+                    attempt += 1;
+                    if attempt > MAX_RECONNECT_ATTEMPTS {
+                        return Err(error.context(format!(
+                            "Giving up on certstream after {MAX_RECONNECT_ATTEMPTS} reconnect attempts"
+                        )));
+                    }
+                    let wait = Duration::from_secs(delay);
+                    if let Some(on_retry) = &self.on_retry {
+                        on_retry(&error, attempt, wait);
+                    }
+                    tokio::time::sleep(wait).await;
+                    delay = (delay * 2).min(MAX_RECONNECT_DELAY_SECS);
+                    if sink.is_closed() {
+                        return Ok(());
+                    }
+                    match self.connect().await {
+                        Ok(socket) => break socket,
+                        Err(e) => error = e,
+                    }
+                };
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update(domains: &[&str]) -> String {
+        serde_json::json!({
+            "message_type": "certificate_update",
+            "data": { "leaf_cert": { "all_domains": domains } }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_matching_hosts_filters_to_targets() {
+        let targets = vec!["example.com".to_string()];
+        let msg = update(&[
+            "example.com",
+            "api.example.com",
+            "notexample.com",
+            "other.org",
+        ]);
+        assert_eq!(
+            matching_hosts(&msg, &targets),
+            vec!["example.com", "api.example.com"]
+        );
+    }
+
+    #[test]
+    fn test_matching_hosts_strips_wildcards_and_dedupes() {
+        let targets = vec!["example.com".to_string()];
+        let msg = update(&["*.Dev.Example.com", "dev.example.com."]);
+        assert_eq!(matching_hosts(&msg, &targets), vec!["dev.example.com"]);
+    }
+
+    #[tokio::test]
+    async fn test_watch_fails_when_first_connect_fails() {
+        // Bind then drop a listener to get a local port nothing answers on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let watcher = CertstreamProvider::new(format!("ws://127.0.0.1:{port}/"));
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let targets = vec!["example.com".to_string()];
+
+        let result =
+            tokio::time::timeout(Duration::from_secs(5), watcher.watch(&targets, tx)).await;
+        let err = result.expect("watch should not retry").unwrap_err();
+        assert!(format!("{err:#}").contains("Failed to connect to certstream"));
+    }
+
+    #[test]
+    fn test_matching_hosts_ignores_heartbeats_and_garbage() {
+        let targets = vec!["example.com".to_string()];
+        assert!(matching_hosts(r#"{"message_type":"heartbeat"}"#, &targets).is_empty());
+        assert!(matching_hosts("not json", &targets).is_empty());
+    }
+}
@@ -1,19 +1,28 @@
 use anyhow::Result;
 use serde::Deserialize;
+use serde::Serialize;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
 
-use super::Provider;
+use super::{collect_pages, page_stream, Provider, RecordStream, UrlRecord};
 use crate::network::client::{get_with_retry, HttpClientConfig};
-use crate::network::RateLimiter;
+use crate::network::{RateLimiter, RequestGate};
 use crate::progress::ProgressReporter;
 
 /// Sentinel value that asks the provider to resolve the most recent Common
 /// Crawl index at runtime via `collinfo.json`.
 pub(crate) const LATEST_INDEX_ALIAS: &str = "latest";
 
+/// How long a resolved `latest` index persisted to disk is trusted before
+/// `collinfo.json` is asked again. Crawls are published roughly monthly, so a
+/// day keeps runs from re-fetching it without letting the answer go stale.
+const COLLINFO_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
 /// Hard ceiling on the number of CDX index pages we will fetch for one domain,
 /// mirroring the Wayback guard. At the index server's default block size this
 /// covers far more captures than any real domain has.
@@ -43,6 +52,11 @@ pub struct CommonCrawlProvider {
     /// Cached resolution of `LATEST_INDEX_ALIAS`. Shared across clones so the
     /// `collinfo.json` lookup happens at most once per run.
     resolved_index: Arc<OnceCell<String>>,
+    /// Where the `latest` resolution is persisted between runs, if anywhere.
+    collinfo_cache: Option<PathBuf>,
+    /// `--from` / `--to` as 14-digit CDX timestamps.
+    from: Option<String>,
+    to: Option<String>,
     include_subdomains: bool,
     proxy: Option<String>,
     proxy_auth: Option<String>,
@@ -51,13 +65,19 @@ pub struct CommonCrawlProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
     #[cfg(test)]
     base_url: String,
 }
 
+/// One line of the index's `output=json` response. Snapshot fields are
+/// optional so a trimmed record (or a mock) still yields its URL.
 #[derive(Deserialize)]
 struct CCRecord {
     url: String,
+    timestamp: Option<String>,
+    status: Option<String>,
+    mime: Option<String>,
 }
 
 /// Response shape of a `&showNumPages=true` probe — the index server reports
@@ -73,12 +93,52 @@ struct CollInfoEntry {
     id: String,
 }
 
+/// On-disk record of the last `latest` resolution.
+#[derive(Serialize, Deserialize)]
+struct CachedLatestIndex {
+    id: String,
+    /// Unix seconds when `collinfo.json` returned `id`.
+    fetched_at: u64,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Read a persisted `latest` resolution, ignoring anything unreadable or with
+/// an id that doesn't look like a crawl index.
+fn read_cached_latest(path: &Path) -> Option<CachedLatestIndex> {
+    let raw = std::fs::read_to_string(path).ok()?;
+    let cached: CachedLatestIndex = serde_json::from_str(&raw).ok()?;
+    is_valid_cc_index_id(&cached.id).then_some(cached)
+}
+
+/// Best-effort write; a read-only home directory just means no persistence.
+fn write_cached_latest(path: &Path, id: &str) {
+    let record = CachedLatestIndex {
+        id: id.to_string(),
+        fetched_at: unix_now(),
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(&record) {
+        let _ = std::fs::write(path, json);
+    }
+}
+
 impl CommonCrawlProvider {
     #[allow(dead_code)]
     pub fn new() -> Self {
         CommonCrawlProvider {
             index: "CC-MAIN-2026-17".to_string(),
             resolved_index: Arc::new(OnceCell::new()),
+            collinfo_cache: None,
+            from: None,
+            to: None,
             include_subdomains: false,
             proxy: None,
             proxy_auth: None,
@@ -87,6 +147,7 @@ impl CommonCrawlProvider {
             random_agent: true,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
             #[cfg(test)]
             base_url: "https://index.commoncrawl.org".to_string(),
         }
@@ -101,6 +162,9 @@ impl CommonCrawlProvider {
         CommonCrawlProvider {
             index,
             resolved_index: Arc::new(OnceCell::new()),
+            collinfo_cache: None,
+            from: None,
+            to: None,
             include_subdomains: false,
             proxy: None,
             proxy_auth: None,
@@ -109,11 +173,56 @@ impl CommonCrawlProvider {
             random_agent: true,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
             #[cfg(test)]
             base_url: "https://index.commoncrawl.org".to_string(),
         }
     }
 
+    /// Persist the `latest` resolution at `path` so later runs within
+    /// [`COLLINFO_CACHE_TTL_SECS`] skip the `collinfo.json` request. A stale
+    /// entry is still used if `collinfo.json` can't be fetched.
+    pub fn with_collinfo_cache(&mut self, path: PathBuf) -> &mut Self {
+        self.collinfo_cache = Some(path);
+        self
+    }
+
+    /// Scope the index query to captures between `from` and `to` (14-digit
+    /// CDX timestamps, either end open). With the `latest` alias, the newest
+    /// crawl inside the window is used instead of the newest overall.
+    pub fn with_date_range(&mut self, from: Option<String>, to: Option<String>) -> &mut Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    fn has_date_range(&self) -> bool {
+        self.from.is_some() || self.to.is_some()
+    }
+
+    /// Whether the crawl `id` (`CC-MAIN-YYYY-WW`, an ISO year and week) was
+    /// published inside the `--from` / `--to` window.
+    fn index_in_range(&self, id: &str) -> bool {
+        let Some(rest) = id.strip_prefix("CC-MAIN-") else {
+            return false;
+        };
+        let Some((year, week)) = rest.split_once('-') else {
+            return false;
+        };
+        let (Ok(year), Ok(week)) = (year.parse::<i32>(), week.parse::<u32>()) else {
+            return false;
+        };
+        let Some(monday) = chrono::NaiveDate::from_isoywd_opt(year, week, chrono::Weekday::Mon)
+        else {
+            return false;
+        };
+        let day = monday.format("%Y%m%d").to_string();
+        self.from
+            .as_ref()
+            .is_none_or(|from| day.as_str() >= &from[..8])
+            && self.to.as_ref().is_none_or(|to| day.as_str() <= &to[..8])
+    }
+
     /// Build an `HttpClientConfig` from the current provider settings.
     fn client_config(&self) -> HttpClientConfig {
         HttpClientConfig {
@@ -122,6 +231,7 @@ impl CommonCrawlProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -137,7 +247,8 @@ impl CommonCrawlProvider {
     }
 
     /// Resolve `self.index`, fetching `collinfo.json` once if the user passed
-    /// the `latest` alias. The resolved value is memoised across all clones.
+    /// the `latest` alias. The resolved value is memoised across all clones
+    /// and, with [`with_collinfo_cache`](Self::with_collinfo_cache), across runs.
     async fn effective_index(&self) -> Result<String> {
         if !self.index.eq_ignore_ascii_case(LATEST_INDEX_ALIAS) {
             return Ok(self.index.clone());
@@ -146,37 +257,85 @@ impl CommonCrawlProvider {
         let cached = self
             .resolved_index
             .get_or_try_init(|| async {
-                let url = format!("{}/collinfo.json", self.index_base_url());
-                let client = self.client_config().build_client()?;
-                let body = get_with_retry(&client, &url, self.retries).await?;
-                let entries: Vec<CollInfoEntry> = serde_json::from_str(&body)?;
-                let id = entries
-                    .into_iter()
-                    .next()
-                    .map(|e| e.id)
-                    .ok_or_else(|| anyhow::anyhow!("collinfo.json returned no entries"))?;
-                if !is_valid_cc_index_id(&id) {
-                    return Err(anyhow::anyhow!(
-                        "collinfo.json returned an unexpected index id: {id:?}"
-                    ));
+                // The remembered answer is the newest crawl overall, which a
+                // date window may exclude.
+                let persisted = self
+                    .collinfo_cache
+                    .as_deref()
+                    .filter(|_| !self.has_date_range())
+                    .and_then(read_cached_latest);
+                if let Some(entry) = &persisted {
+                    if unix_now().saturating_sub(entry.fetched_at) < COLLINFO_CACHE_TTL_SECS {
+                        return Ok(entry.id.clone());
+                    }
+                }
+
+                match self.fetch_latest_index().await {
+                    Ok(id) => {
+                        if let Some(path) = self
+                            .collinfo_cache
+                            .as_ref()
+                            .filter(|_| !self.has_date_range())
+                        {
+                            write_cached_latest(path, &id);
+                        }
+                        Ok(id)
+                    }
+                    // An expired answer beats failing the provider outright.
+                    Err(e) => persisted.map(|entry| entry.id).ok_or(e),
                 }
-                Ok(id)
             })
             .await?;
 
         Ok(cached.clone())
     }
 
+    /// Ask `collinfo.json` for the most recent index id (it lists newest
+    /// first), within the `--from` / `--to` window when one is set.
+    async fn fetch_latest_index(&self) -> Result<String> {
+        let url = format!("{}/collinfo.json", self.index_base_url());
+        let client = self.client_config().build_client()?;
+        let body = get_with_retry(&client, &url, self.retries, self.rate_limit.as_ref()).await?;
+        let entries: Vec<CollInfoEntry> = serde_json::from_str(&body)?;
+        if entries.is_empty() {
+            return Err(anyhow::anyhow!("collinfo.json returned no entries"));
+        }
+        let id = entries
+            .into_iter()
+            .map(|e| e.id)
+            .find(|id| !self.has_date_range() || self.index_in_range(id))
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "collinfo.json lists no crawl between {} and {}",
+                    self.from.as_deref().unwrap_or("the first crawl"),
+                    self.to.as_deref().unwrap_or("now")
+                )
+            })?;
+        if !is_valid_cc_index_id(&id) {
+            return Err(anyhow::anyhow!(
+                "collinfo.json returned an unexpected index id: {id:?}"
+            ));
+        }
+        Ok(id)
+    }
+
     /// Build the index query without pagination params. `output=json` streams
     /// one JSON record per line; `&page=N` / `&showNumPages=true` are appended
     /// per request.
     fn query_base(&self, index: &str, domain: &str) -> String {
         let base_url = self.index_base_url();
-        if self.include_subdomains {
+        let mut query = if self.include_subdomains {
             format!("{base_url}/{index}-index?url=*.{domain}/*&output=json")
         } else {
             format!("{base_url}/{index}-index?url={domain}/*&output=json")
+        };
+        if let Some(from) = &self.from {
+            query.push_str(&format!("&from={from}"));
+        }
+        if let Some(to) = &self.to {
+            query.push_str(&format!("&to={to}"));
         }
+        query
     }
 }
 
@@ -198,6 +357,33 @@ impl Provider for CommonCrawlProvider {
         reporter: Option<ProgressReporter>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
         Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, reporter).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut urls = collect_pages(self.fetch_records_stream(domain, reporter)).await?;
+
+            // Remove duplicates, keeping the first capture of each URL
+            urls.sort_by(|a, b| a.url.cmp(&b.url));
+            urls.dedup_by(|a, b| a.url == b.url);
+
+            Ok(urls)
+        })
+    }
+
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> RecordStream<'a> {
+        page_stream(move |mut sink| async move {
             let index = self.effective_index().await?;
             let query_base = self.query_base(&index, domain);
             let client = self.client_config().build_client()?;
@@ -212,11 +398,8 @@ impl Provider for CommonCrawlProvider {
             // ask how many pages the query spans via `&showNumPages=true` and
             // then walk every page, or large domains are silently truncated to
             // their first block.
-            if let Some(rl) = &limiter {
-                rl.acquire().await;
-            }
             let count_url = format!("{query_base}&showNumPages=true");
-            let pages = match get_with_retry(&client, &count_url, self.retries).await {
+            let pages = match get_with_retry(&client, &count_url, self.retries, limiter).await {
                 Ok(body) => serde_json::from_str::<CCPageInfo>(body.trim())
                     .map(|info| info.pages)
                     // A 200 that isn't a page-count document: fall back to a
@@ -230,26 +413,42 @@ impl Provider for CommonCrawlProvider {
             };
 
             if pages == 0 {
-                return Ok(Vec::new());
+                return Ok(());
             }
             let pages = pages.min(CC_MAX_PAGES);
 
-            let mut urls = Vec::new();
             for page in 0..pages {
-                if let Some(rl) = &limiter {
-                    rl.acquire().await;
-                }
                 let page_url = format!("{query_base}&page={page}");
-                match get_with_retry(&client, &page_url, self.retries).await {
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    get_with_retry(&client, &page_url, self.retries, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                match fetched {
                     Ok(text) => {
                         // Common Crawl returns one JSON object per line.
-                        for line in text.lines() {
-                            if let Ok(record) = serde_json::from_str::<CCRecord>(line) {
-                                urls.push(record.url);
-                            }
+                        let mut records: Vec<UrlRecord> = text
+                            .lines()
+                            .filter_map(|line| serde_json::from_str::<CCRecord>(line).ok())
+                            .map(|record| {
+                                UrlRecord::new(record.url).with_snapshot(
+                                    record.timestamp.as_deref(),
+                                    record.status.as_deref(),
+                                    record.mime.as_deref(),
+                                )
+                            })
+                            .collect();
+                        records.sort_by(|a, b| a.url.cmp(&b.url));
+                        records.dedup_by(|a, b| a.url == b.url);
+                        if !sink.send(records).await {
+                            break;
                         }
                         if let Some(r) = &reporter {
-                            r.detail(format!("{} URLs…", urls.len()));
+                            r.detail(format!("{} URLs…", sink.sent()));
                         }
                     }
                     Err(e) => {
@@ -257,7 +456,7 @@ impl Provider for CommonCrawlProvider {
                         // first page 404s) is a hard failure, matching the old
                         // single-request behaviour. A mid-pagination failure
                         // keeps what we have and flags the result as partial.
-                        if urls.is_empty() {
+                        if sink.sent() == 0 {
                             return Err(e);
                         }
                         if let Some(r) = &reporter {
@@ -268,15 +467,52 @@ impl Provider for CommonCrawlProvider {
                 }
             }
 
-            // Remove duplicates
-            urls.sort();
-            urls.dedup();
-
-            Ok(urls)
+            Ok(())
         })
     }
 
     // Implement new trait methods
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        let mut plan = Vec::new();
+        let index = if self.index.eq_ignore_ascii_case(LATEST_INDEX_ALIAS) {
+            let persisted = self
+                .collinfo_cache
+                .as_deref()
+                .filter(|_| !self.has_date_range())
+                .and_then(read_cached_latest)
+                .filter(|e| unix_now().saturating_sub(e.fetched_at) < COLLINFO_CACHE_TTL_SECS);
+            match persisted {
+                Some(entry) => {
+                    plan.push(format!(
+                        "index: latest = {} (remembered, no collinfo.json request)",
+                        entry.id
+                    ));
+                    entry.id
+                }
+                None => {
+                    let scope = if self.has_date_range() {
+                        "the newest index in the --from/--to window"
+                    } else {
+                        "the newest index"
+                    };
+                    plan.push(format!(
+                        "GET {}/collinfo.json (resolves `latest` to {scope})",
+                        self.index_base_url()
+                    ));
+                    "<latest>".to_string()
+                }
+            }
+        } else {
+            self.index.clone()
+        };
+        let query_base = self.query_base(&index, domain);
+        plan.push(format!("GET {query_base}&showNumPages=true"));
+        plan.push(format!(
+            "GET {query_base}&page=N for N in 0..pages (at most {CC_MAX_PAGES} pages)"
+        ));
+        plan
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -309,6 +545,14 @@ impl Provider for CommonCrawlProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -555,6 +799,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_plan_requests_shows_index_selection() {
+        let mut provider = CommonCrawlProvider::new();
+        provider.base_url = "https://index.commoncrawl.org".to_string();
+        let plan = provider.plan_requests("example.com");
+        assert_eq!(
+            plan[0],
+            "GET https://index.commoncrawl.org/CC-MAIN-2026-17-index?url=example.com/*&output=json&showNumPages=true"
+        );
+        assert_eq!(plan.len(), 2);
+
+        let mut latest = CommonCrawlProvider::with_index(LATEST_INDEX_ALIAS.to_string());
+        latest.base_url = "https://index.commoncrawl.org".to_string();
+        let plan = latest.plan_requests("example.com");
+        assert!(plan[0].starts_with("GET https://index.commoncrawl.org/collinfo.json"));
+        assert!(plan[1].contains("/<latest>-index?"));
+    }
+
     #[tokio::test]
     async fn test_fetch_urls_integration() {
         let mut server = mockito::Server::new_async().await;
@@ -583,6 +845,33 @@ mod tests {
         assert_eq!(urls[1], "https://example.com/page2");
     }
 
+    #[tokio::test]
+    async fn test_fetch_records_carries_snapshot_metadata() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server
+            .mock("GET", "/CC-MAIN-2026-17-index")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"url": "https://example.com/a", "timestamp": "20240102030405", "status": "301", "mime": "text/html"}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut provider = CommonCrawlProvider::new();
+        provider.base_url = server.url();
+
+        let records = provider
+            .fetch_records_with_progress("example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        let meta = &records[0].metadata;
+        assert_eq!(meta["timestamp"], "2024-01-02T03:04:05Z");
+        assert_eq!(meta["archived_status"], 301);
+        assert_eq!(meta["mime"], "text/html");
+    }
+
     #[tokio::test]
     async fn test_fetch_urls_paginates_all_pages() {
         let mut server = mockito::Server::new_async().await;
@@ -679,6 +968,128 @@ mod tests {
         index_mock.assert();
     }
 
+    #[tokio::test]
+    async fn test_date_range_picks_newest_crawl_in_window() {
+        let mut server = mockito::Server::new_async().await;
+        let collinfo = server
+            .mock("GET", "/collinfo.json")
+            .with_status(200)
+            .with_body(
+                r#"[
+                    {"id": "CC-MAIN-2025-05"},
+                    {"id": "CC-MAIN-2024-51"},
+                    {"id": "CC-MAIN-2024-10"}
+                ]"#,
+            )
+            .create_async()
+            .await;
+        let index_mock = server
+            .mock("GET", "/CC-MAIN-2024-51-index")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("from".into(), "20240101000000".into()),
+                mockito::Matcher::UrlEncoded("to".into(), "20241231235959".into()),
+            ]))
+            .with_status(200)
+            .with_body("{\"url\": \"https://example.com/a\"}")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut provider = CommonCrawlProvider::with_index(LATEST_INDEX_ALIAS.to_string());
+        provider.base_url = server.url();
+        provider.with_date_range(
+            Some("20240101000000".to_string()),
+            Some("20241231235959".to_string()),
+        );
+        assert!(provider.index_in_range("CC-MAIN-2024-10"));
+        assert!(!provider.index_in_range("CC-MAIN-2023-50"));
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/a".to_string()]);
+        collinfo.assert();
+        index_mock.assert();
+
+        provider.resolved_index = Arc::new(OnceCell::new());
+        provider.with_date_range(Some("20260101000000".to_string()), None);
+        assert!(provider.fetch_urls("example.com").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_latest_alias_uses_fresh_persisted_index() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cc-latest-index.json");
+        write_cached_latest(&cache, "CC-MAIN-2099-01");
+
+        let collinfo = server
+            .mock("GET", "/collinfo.json")
+            .expect(0)
+            .create_async()
+            .await;
+        let _index = server
+            .mock("GET", "/CC-MAIN-2099-01-index")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body("{\"url\": \"https://example.com/a\"}")
+            .create_async()
+            .await;
+
+        let mut provider = CommonCrawlProvider::with_index(LATEST_INDEX_ALIAS.to_string());
+        provider.base_url = server.url();
+        provider.with_collinfo_cache(cache);
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/a".to_string()]);
+        collinfo.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_latest_alias_refreshes_stale_cache_and_falls_back_on_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cc-latest-index.json");
+        let stale = CachedLatestIndex {
+            id: "CC-MAIN-2098-50".to_string(),
+            fetched_at: unix_now() - COLLINFO_CACHE_TTL_SECS - 1,
+        };
+        std::fs::write(&cache, serde_json::to_string(&stale).unwrap()).unwrap();
+
+        // A stale entry triggers a refresh; collinfo being down means the
+        // stale id is still used rather than failing.
+        let collinfo = server
+            .mock("GET", "/collinfo.json")
+            .with_status(503)
+            .create_async()
+            .await;
+        let _index = server
+            .mock("GET", "/CC-MAIN-2098-50-index")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body("{\"url\": \"https://example.com/old\"}")
+            .create_async()
+            .await;
+
+        let mut provider = CommonCrawlProvider::with_index(LATEST_INDEX_ALIAS.to_string());
+        provider.base_url = server.url();
+        provider.with_retries(0);
+        provider.with_collinfo_cache(cache.clone());
+        assert_eq!(provider.effective_index().await.unwrap(), "CC-MAIN-2098-50");
+        collinfo.remove_async().await;
+
+        // When collinfo answers, the new id is persisted for later runs.
+        let _collinfo = server
+            .mock("GET", "/collinfo.json")
+            .with_status(200)
+            .with_body(r#"[{"id": "CC-MAIN-2099-01"}]"#)
+            .create_async()
+            .await;
+        let mut provider = CommonCrawlProvider::with_index(LATEST_INDEX_ALIAS.to_string());
+        provider.base_url = server.url();
+        provider.with_collinfo_cache(cache.clone());
+        assert_eq!(provider.effective_index().await.unwrap(), "CC-MAIN-2099-01");
+        assert_eq!(read_cached_latest(&cache).unwrap().id, "CC-MAIN-2099-01");
+    }
+
     #[tokio::test]
     async fn test_latest_alias_is_case_insensitive() {
         let mut server = mockito::Server::new_async().await;
@@ -0,0 +1,481 @@
+use anyhow::{Context, Result};
+use regex::Regex;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
+
+use super::{collect_pages, page_stream, Provider, RecordStream, UrlRecord};
+use crate::network::client::{get_with_retry, HttpClientConfig};
+use crate::network::{RateLimiter, RequestGate};
+use crate::progress::ProgressReporter;
+
+/// Pages walked for one domain when a paginated definition sets no
+/// `max_pages`.
+const DEFAULT_MAX_PAGES: u32 = 10;
+
+/// One `[[provider.custom]]` entry from config.toml: an HTTP source urx
+/// doesn't ship (an internal crawler, a company asset inventory, …),
+/// described well enough for [`GenericProvider`] to query it.
+///
+/// ```toml
+/// [[provider.custom]]
+/// id = "intel"
+/// url = "https://intel.example.com/api/urls?host={domain}&page={page}"
+/// json_path = "results.url"
+/// auth_header = "Authorization: Bearer ${INTEL_TOKEN}"
+/// ```
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomProviderDef {
+    /// Id used with `--providers`, `--rate-limit-by`, `[provider.<id>]`, …
+    pub id: String,
+    /// Name shown in progress and stats. Defaults to the id.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Request URL. `{domain}` becomes the target and `{page}` the page
+    /// number.
+    pub url: String,
+    /// Dotted path to the URLs in a JSON response (`data.items.url`). Arrays
+    /// met along the way are walked element by element.
+    #[serde(default)]
+    pub json_path: Option<String>,
+    /// Regex run over the raw response. Capture group 1 is the URL when the
+    /// pattern has one, the whole match otherwise.
+    #[serde(default)]
+    pub regex: Option<String>,
+    /// Query parameter carrying the page number, for URLs without `{page}`.
+    #[serde(default)]
+    pub page_param: Option<String>,
+    /// Number of the first page.
+    #[serde(default = "default_page_start")]
+    pub page_start: u32,
+    /// Most pages walked per domain (default 10 when the URL paginates).
+    #[serde(default)]
+    pub max_pages: Option<u32>,
+    /// Header sent with every request, as `Name: value`. `${VAR}` is read
+    /// from the environment, so the token can stay out of the config file.
+    #[serde(default)]
+    pub auth_header: Option<String>,
+}
+
+fn default_page_start() -> u32 {
+    1
+}
+
+impl CustomProviderDef {
+    /// Display name: `name`, or the id when unset.
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.id.clone())
+    }
+
+    fn paginates(&self) -> bool {
+        self.url.contains("{page}") || self.page_param.is_some()
+    }
+}
+
+/// Expand `${VAR}` references from the environment. An unset variable is an
+/// error rather than an empty string, so a missing token doesn't turn into
+/// an anonymous request.
+fn expand_env(raw: &str) -> Result<String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start + 2..].find('}') else {
+            break;
+        };
+        let var = &rest[start + 2..start + 2 + len];
+        let value =
+            std::env::var(var).with_context(|| format!("environment variable {var} is not set"))?;
+        out.push_str(&rest[..start]);
+        out.push_str(&value);
+        rest = &rest[start + 3 + len..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Collect the strings under `path` in `value`, walking into every element
+/// of the arrays it meets.
+fn collect_json_path(value: &serde_json::Value, path: &[&str], out: &mut Vec<String>) {
+    if let serde_json::Value::Array(items) = value {
+        for item in items {
+            collect_json_path(item, path, out);
+        }
+        return;
+    }
+    match path.split_first() {
+        None => {
+            if let Some(s) = value.as_str() {
+                out.push(s.to_string());
+            }
+        }
+        Some((key, rest)) => {
+            if let Some(next) = value.get(*key) {
+                collect_json_path(next, rest, out);
+            }
+        }
+    }
+}
+
+/// Provider built from a [`CustomProviderDef`].
+#[derive(Clone)]
+pub struct GenericProvider {
+    def: CustomProviderDef,
+    regex: Option<Regex>,
+    auth: Option<(HeaderName, HeaderValue)>,
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
+}
+
+impl GenericProvider {
+    /// Check `def` and build its provider. Errors name the offending id and
+    /// field.
+    pub fn new(def: CustomProviderDef) -> Result<Self> {
+        let id = def.id.clone();
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-'))
+        {
+            anyhow::bail!(
+                "Invalid custom provider id {id:?}: use lowercase letters, digits, '_' and '-'"
+            );
+        }
+        if !def.url.contains("{domain}") {
+            anyhow::bail!("Custom provider {id}: url must contain {{domain}}");
+        }
+        if def.json_path.is_some() && def.regex.is_some() {
+            anyhow::bail!("Custom provider {id}: set json_path or regex, not both");
+        }
+        let regex = def
+            .regex
+            .as_deref()
+            .map(Regex::new)
+            .transpose()
+            .with_context(|| format!("Custom provider {id}: invalid regex"))?;
+        let auth = match &def.auth_header {
+            None => None,
+            Some(raw) => {
+                let (name, value) = raw.split_once(':').with_context(|| {
+                    format!("Custom provider {id}: auth_header must look like `Name: value`")
+                })?;
+                let value = expand_env(value.trim())
+                    .with_context(|| format!("Custom provider {id}: auth_header"))?;
+                let name = HeaderName::from_bytes(name.trim().as_bytes())
+                    .with_context(|| format!("Custom provider {id}: invalid auth_header name"))?;
+                let mut value = HeaderValue::from_str(&value)
+                    .with_context(|| format!("Custom provider {id}: invalid auth_header value"))?;
+                value.set_sensitive(true);
+                Some((name, value))
+            }
+        };
+
+        Ok(GenericProvider {
+            def,
+            regex,
+            auth,
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            rate_limit: None,
+            cancel: CancellationToken::new(),
+        })
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn max_pages(&self) -> u32 {
+        if self.def.paginates() {
+            self.def.max_pages.unwrap_or(DEFAULT_MAX_PAGES).max(1)
+        } else {
+            1
+        }
+    }
+
+    fn page_url(&self, domain: &str, page: u32) -> String {
+        let mut url = self
+            .def
+            .url
+            .replace("{domain}", domain)
+            .replace("{page}", &page.to_string());
+        if let Some(param) = &self.def.page_param {
+            let sep = if url.contains('?') { '&' } else { '?' };
+            url = format!("{url}{sep}{param}={page}");
+        }
+        url
+    }
+
+    /// URLs in one response body: by `json_path`, by `regex`, or else every
+    /// line that looks like an absolute URL.
+    fn extract_urls(&self, body: &str) -> Result<Vec<String>> {
+        let mut urls = Vec::new();
+        if let Some(path) = &self.def.json_path {
+            let value: serde_json::Value = serde_json::from_str(body)
+                .with_context(|| format!("{} returned invalid JSON", self.def.id))?;
+            let path: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+            collect_json_path(&value, &path, &mut urls);
+        } else if let Some(regex) = &self.regex {
+            for captures in regex.captures_iter(body) {
+                if let Some(m) = captures.get(1).or_else(|| captures.get(0)) {
+                    urls.push(m.as_str().to_string());
+                }
+            }
+        } else {
+            urls.extend(
+                body.lines()
+                    .map(str::trim)
+                    .filter(|l| l.starts_with("http://") || l.starts_with("https://"))
+                    .map(str::to_string),
+            );
+        }
+        urls.retain(|u| !u.trim().is_empty());
+        Ok(urls)
+    }
+}
+
+impl Provider for GenericProvider {
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+
+    fn fetch_urls<'a>(
+        &'a self,
+        domain: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = collect_pages(self.fetch_records_stream(domain, None)).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> RecordStream<'a> {
+        page_stream(move |mut sink| async move {
+            let mut builder = self.client_config().client_builder()?;
+            if let Some((name, value)) = &self.auth {
+                let mut headers = HeaderMap::new();
+                headers.insert(name.clone(), value.clone());
+                builder = builder.default_headers(headers);
+            }
+            let client = builder.build()?;
+            let limiter = self.rate_limit.as_ref();
+
+            // Stop at max_pages, or as soon as a page adds no new URLs: an
+            // endpoint that ignores the page number re-serves the same rows.
+            let mut seen: HashSet<String> = HashSet::new();
+            for n in 0..self.max_pages() {
+                let page = self.def.page_start + n;
+                if let Some(r) = &reporter {
+                    if self.def.paginates() {
+                        r.detail(format!("page {}…", n + 1));
+                    }
+                }
+                let url = self.page_url(domain, page);
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    get_with_retry(&client, &url, self.retries, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                let urls = match fetched.and_then(|body| self.extract_urls(&body)) {
+                    Ok(urls) => urls,
+                    // Keep the pages already sent; only a first-page failure
+                    // fails the fetch.
+                    Err(e) if n == 0 => return Err(e),
+                    Err(_) => {
+                        if let Some(r) = &reporter {
+                            r.mark_partial();
+                        }
+                        break;
+                    }
+                };
+
+                let fresh: Vec<UrlRecord> = urls
+                    .into_iter()
+                    .filter(|u| seen.insert(u.clone()))
+                    .map(UrlRecord::new)
+                    .collect();
+                if fresh.is_empty() || !sink.send(fresh).await {
+                    break;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        let mut plan = vec![format!(
+            "GET {}",
+            self.page_url(domain, self.def.page_start)
+        )];
+        if let Some((name, _)) = &self.auth {
+            plan.push(format!("  with {name}: ***"));
+        }
+        if self.def.paginates() {
+            plan.push(format!(
+                "  then the next pages until one adds no new URLs (at most {} pages)",
+                self.max_pages()
+            ));
+        }
+        plan
+    }
+
+    /// The URL template decides what the source is asked for, so `--subs`
+    /// has nothing to change here; host validation still applies it.
+    fn with_subdomains(&mut self, _include: bool) {}
+
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
+        self.rate_limit = RateLimiter::from_rate(rate_limit);
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn def(toml_src: &str) -> CustomProviderDef {
+        toml::from_str(toml_src).unwrap()
+    }
+
+    #[test]
+    fn test_definition_checks_and_extraction() {
+        assert!(GenericProvider::new(def(r#"id = "x"
+url = "https://intel.example.com/""#))
+        .is_err());
+        assert!(GenericProvider::new(def(r#"id = "Bad Id"
+url = "https://intel.example.com/{domain}""#))
+        .is_err());
+        assert!(GenericProvider::new(def(r#"id = "x"
+url = "https://intel.example.com/{domain}"
+regex = "(""#))
+        .is_err());
+
+        let json = GenericProvider::new(def(r#"id = "x"
+url = "https://intel.example.com/{domain}"
+json_path = "data.hits.url""#))
+        .unwrap();
+        let body = r#"{"data":[{"hits":[{"url":"https://a.example.com/1"},{"url":""}]},{"hits":{"url":"https://a.example.com/2"}}]}"#;
+        assert_eq!(
+            json.extract_urls(body).unwrap(),
+            vec!["https://a.example.com/1", "https://a.example.com/2"]
+        );
+
+        let regex = GenericProvider::new(def(r#"id = "x"
+url = "https://intel.example.com/{domain}"
+regex = 'href="(https?://[^"]+)"'"#))
+        .unwrap();
+        assert_eq!(
+            regex
+                .extract_urls(r#"<a href="https://a.example.com/x">x</a>"#)
+                .unwrap(),
+            vec!["https://a.example.com/x"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_pages_with_auth_header() {
+        let mut server = mockito::Server::new_async().await;
+        let page1 = server
+            .mock("GET", "/api/urls")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("host".into(), "example.com".into()),
+                mockito::Matcher::UrlEncoded("p".into(), "1".into()),
+            ]))
+            .match_header("x-api-key", "secret")
+            .with_body(
+                r#"{"results":[{"url":"https://example.com/a"},{"url":"https://example.com/b"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        // The last page repeats a URL and adds nothing new, ending the walk.
+        let page2 = server
+            .mock("GET", "/api/urls")
+            .match_query(mockito::Matcher::UrlEncoded("p".into(), "2".into()))
+            .with_body(r#"{"results":[{"url":"https://example.com/b"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let provider = GenericProvider::new(CustomProviderDef {
+            id: "intel".to_string(),
+            name: None,
+            url: format!("{}/api/urls?host={{domain}}", server.url()),
+            json_path: Some("results.url".to_string()),
+            regex: None,
+            page_param: Some("p".to_string()),
+            page_start: 1,
+            max_pages: Some(5),
+            auth_header: Some("X-Api-Key: secret".to_string()),
+        })
+        .unwrap();
+        assert_eq!(
+            provider.plan_requests("example.com")[1],
+            "  with x-api-key: ***"
+        );
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+        page1.assert_async().await;
+        page2.assert_async().await;
+    }
+}
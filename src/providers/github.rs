@@ -3,12 +3,16 @@ use serde::Deserialize;
 use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
 use super::ApiKeyRotator;
 use super::Provider;
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{
+    read_json, server_retry_delay, wait_before_retry, QuotaMeter, RateLimiter, RequestGate,
+};
 use crate::progress::ProgressReporter;
+use crate::utils::split_host_port;
 
 /// Maximum search-result pages we fetch per domain. GitHub Code Search caps at
 /// 1000 results total (10 × 100), so 10 pages covers everything the API will
@@ -29,6 +33,8 @@ pub struct GitHubProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
+    quota: Option<QuotaMeter>,
     #[cfg(test)]
     base_url: String,
 }
@@ -73,6 +79,8 @@ impl GitHubProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
+            quota: None,
             #[cfg(test)]
             base_url: "https://api.github.com".to_string(),
         }
@@ -84,6 +92,20 @@ impl GitHubProvider {
         self
     }
 
+    /// Code search URL for `domain`, without the `page` param. Quoted phrase
+    /// search keeps the result set tight to literal mentions of the domain
+    /// rather than partial-token matches.
+    fn search_base(&self, domain: &str) -> String {
+        #[cfg(not(test))]
+        let base = "https://api.github.com";
+        #[cfg(test)]
+        let base = self.base_url.as_str();
+
+        let q = format!("\"{domain}\"");
+        let encoded_q = url::form_urlencoded::byte_serialize(q.as_bytes()).collect::<String>();
+        format!("{base}/search/code?q={encoded_q}&per_page={PER_PAGE}")
+    }
+
     fn client_config(&self) -> HttpClientConfig {
         HttpClientConfig {
             timeout: self.timeout,
@@ -91,6 +113,7 @@ impl GitHubProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 }
@@ -99,6 +122,7 @@ impl GitHubProvider {
 /// (or, when `include_subdomains` is true, any subdomain of it).
 /// The matched URL must end its host exactly at `domain` so we don't
 /// surface unrelated hosts like `notexample.com` for a search of `example.com`.
+/// A `host:port` target additionally requires the URL to be on that port.
 pub(crate) fn extract_matching_urls(
     fragment: &str,
     domain: &str,
@@ -106,6 +130,7 @@ pub(crate) fn extract_matching_urls(
     sink: &mut HashSet<String>,
 ) {
     let domain = domain.to_ascii_lowercase();
+    let (domain, port) = split_host_port(&domain);
     for token in fragment.split(|c: char| c.is_whitespace() || c == '"' || c == '\'' || c == ',') {
         let token = token.trim_end_matches(|c: char| {
             matches!(c, '.' | ')' | ']' | '}' | '>' | ';' | ':' | '!' | '?' | '`')
@@ -125,7 +150,7 @@ pub(crate) fn extract_matching_urls(
         } else {
             host == domain
         };
-        if host_matches {
+        if host_matches && (port.is_none() || parsed.port_or_known_default() == port) {
             sink.insert(token.to_string());
         }
     }
@@ -156,47 +181,50 @@ impl Provider for GitHubProvider {
             let client = self.client_config().build_client()?;
             let limiter = self.rate_limit.as_ref();
 
-            #[cfg(not(test))]
-            let base = "https://api.github.com";
-            #[cfg(test)]
-            let base = self.base_url.as_str();
-
-            // Quoted phrase search keeps the result set tight to literal
-            // mentions of the domain rather than partial-token matches.
-            let q = format!("\"{domain}\"");
-            let encoded_q = url::form_urlencoded::byte_serialize(q.as_bytes()).collect::<String>();
+            let search_base = self.search_base(domain);
 
             let mut urls: HashSet<String> = HashSet::new();
             let mut last_error: Option<anyhow::Error> = None;
             // Set when a page exhausts its retries, so results collected so far
             // are reported as a truncated/partial crawl rather than a clean run.
             let mut truncated = false;
+            // Set when the run is cancelled mid-walk (--max-time / Ctrl-C).
+            let mut cancelled = false;
 
             'pages: for page in 1..=MAX_PAGES {
-                let url =
-                    format!("{base}/search/code?q={encoded_q}&per_page={PER_PAGE}&page={page}");
+                let url = format!("{search_base}&page={page}");
 
                 let mut attempt: u32 = 0;
+
+                let mut server_delay = None;
                 loop {
                     if attempt > 0 {
-                        tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64))
-                            .await;
+                        wait_before_retry(attempt, server_delay.take()).await;
                     }
 
                     // Rotate the token per attempt so a rate-limited/secondary-
                     // limited token is retried with a different one when several
                     // are configured.
                     let api_key = self.api_key_rotator.next_key().unwrap_or_default();
-                    if let Some(rl) = &limiter {
-                        rl.acquire().await;
-                    }
-                    let resp = client
-                        .get(&url)
-                        .header("Authorization", format!("Bearer {api_key}"))
-                        .header("Accept", "application/vnd.github.v3.text-match+json")
-                        .header("X-GitHub-Api-Version", "2022-11-28")
-                        .send()
+                    let resp = self
+                        .cancel
+                        .run_until_cancelled(async {
+                            if let Some(rl) = &limiter {
+                                rl.acquire().await;
+                            }
+                            client
+                                .get(&url)
+                                .header("Authorization", format!("Bearer {api_key}"))
+                                .header("Accept", "application/vnd.github.v3.text-match+json")
+                                .header("X-GitHub-Api-Version", "2022-11-28")
+                                .send()
+                                .await
+                        })
                         .await;
+                    let Some(resp) = resp else {
+                        cancelled = true;
+                        break 'pages;
+                    };
 
                     match resp {
                         Ok(response) => {
@@ -208,15 +236,29 @@ impl Provider for GitHubProvider {
                                 if status.as_u16() == 422 {
                                     break 'pages;
                                 }
+                                let retry_after =
+                                    crate::network::retry_after_delay(response.headers());
+                                // GitHub signals primary (429) and secondary
+                                // (403) rate limits; 401 is a bad token. Bench
+                                // the token and retry at once with another
+                                // usable one, if any.
+                                let rotate = match status.as_u16() {
+                                    429 | 403 => self
+                                        .api_key_rotator
+                                        .mark_rate_limited(&api_key, retry_after),
+                                    401 => self.api_key_rotator.mark_rejected(&api_key),
+                                    _ => false,
+                                };
+                                if rotate {
+                                    last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
+                                    continue;
+                                }
                                 // Honor Retry-After on primary (429) and
                                 // secondary (403) rate limits before retrying.
-                                if matches!(status.as_u16(), 429 | 403) {
-                                    if let Some(d) = crate::network::client::retry_after_delay(
-                                        response.headers(),
-                                    ) {
-                                        tokio::time::sleep(d).await;
-                                    }
-                                }
+                                server_delay = match status.as_u16() {
+                                    403 => retry_after,
+                                    _ => server_retry_delay(status, response.headers()),
+                                };
                                 last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
                                 attempt += 1;
                                 if attempt > self.retries {
@@ -226,7 +268,7 @@ impl Provider for GitHubProvider {
                                 continue;
                             }
 
-                            match response.json::<SearchResponse>().await {
+                            match read_json::<SearchResponse>(response).await {
                                 Ok(parsed) => {
                                     let was_empty = parsed.items.is_empty();
                                     for item in parsed.items {
@@ -270,7 +312,11 @@ impl Provider for GitHubProvider {
                 }
             }
 
-            if urls.is_empty() {
+            if cancelled {
+                if let Some(r) = &reporter {
+                    r.mark_partial();
+                }
+            } else if urls.is_empty() {
                 if let Some(e) = last_error {
                     return Err(e);
                 }
@@ -289,6 +335,16 @@ impl Provider for GitHubProvider {
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!(
+                "GET {}&page=1 (Authorization: Bearer ***)",
+                self.search_base(domain)
+            ),
+            format!("  then page=2, 3, … while results continue (at most {MAX_PAGES} pages)"),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -313,6 +369,23 @@ impl Provider for GitHubProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_quota(&mut self, meter: QuotaMeter) {
+        self.rate_limit = Some(RateLimiter::metered(self.rate_limit.take(), meter.clone()));
+        self.quota = Some(meter);
+    }
+
+    fn quota(&self) -> Option<&QuotaMeter> {
+        self.quota.as_ref()
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -360,6 +433,19 @@ mod tests {
         assert!(!sink.iter().any(|u| u.contains("notexample.com")));
     }
 
+    #[test]
+    fn test_extract_urls_with_port() {
+        let mut sink = HashSet::new();
+        extract_matching_urls(
+            "http://example.com:8080/admin https://example.com/login http://example.com:9090/x",
+            "example.com:8080",
+            false,
+            &mut sink,
+        );
+        assert_eq!(sink.len(), 1);
+        assert!(sink.contains("http://example.com:8080/admin"));
+    }
+
     #[test]
     fn test_extract_urls_strips_trailing_punctuation() {
         let mut sink = HashSet::new();
@@ -1,12 +1,22 @@
 use anyhow::Result;
+use futures::channel::mpsc;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use futures::{FutureExt, SinkExt};
 use std::future::Future;
 use std::pin::Pin;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio_util::sync::CancellationToken;
+
+use crate::network::{QuotaMeter, RequestGate};
 
 mod api_key_rotation;
 mod arquivo;
+mod certstream;
 mod commoncrawl;
+mod generic;
 mod github;
 mod otx;
+mod plugin;
 mod robots;
 mod sitemap;
 mod urlscan;
@@ -15,16 +25,172 @@ pub mod wayback;
 mod zoomeye;
 pub use api_key_rotation::ApiKeyRotator;
 pub use arquivo::ArquivoProvider;
+pub use certstream::{CertstreamProvider, DEFAULT_CERTSTREAM_URL};
 pub use commoncrawl::CommonCrawlProvider;
+pub use generic::{CustomProviderDef, GenericProvider};
 pub use github::GitHubProvider;
 pub use otx::OTXProvider;
+pub use plugin::{PluginProvider, PluginProviderDef};
 pub use robots::RobotsProvider;
+pub(crate) use robots::{access_rules, is_allowed, AccessRule};
 pub use sitemap::SitemapProvider;
-pub use urlscan::UrlscanProvider;
+pub use urlscan::{
+    select_scan_targets, UrlscanProvider, DEFAULT_URLSCAN_MAX_RESULTS, DEFAULT_URLSCAN_SUBMIT_MAX,
+};
 pub use vt::VirusTotalProvider;
 pub use wayback::WaybackMachineProvider;
 pub use zoomeye::ZoomEyeProvider;
 
+/// A URL reported by a provider together with any per-URL metadata the
+/// upstream exposes (e.g. VirusTotal analysis stats). Metadata is surfaced in
+/// JSON output; providers without anything extra just wrap the URL.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UrlRecord {
+    pub url: String,
+    pub metadata: serde_json::Map<String, serde_json::Value>,
+}
+
+impl UrlRecord {
+    /// Wrap a bare URL with no metadata.
+    pub fn new(url: String) -> Self {
+        UrlRecord {
+            url,
+            metadata: serde_json::Map::new(),
+        }
+    }
+
+    /// Attach one metadata field, replacing any previous value for `key`.
+    pub fn with_meta(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.metadata.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Attach archive snapshot details under the shared [`SNAPSHOT_FIELDS`]
+    /// keys. CDX-style 14-digit timestamps are rewritten as RFC 3339; empty
+    /// values and the CDX placeholder `-` are skipped.
+    pub fn with_snapshot(
+        mut self,
+        timestamp: Option<&str>,
+        status: Option<&str>,
+        mime: Option<&str>,
+    ) -> Self {
+        fn present(v: Option<&str>) -> Option<&str> {
+            v.map(str::trim).filter(|v| !v.is_empty() && *v != "-")
+        }
+        if let Some(ts) = present(timestamp) {
+            self = self.with_meta("timestamp", cdx_timestamp_to_rfc3339(ts));
+        }
+        if let Some(status) = present(status) {
+            self = match status.parse::<u16>() {
+                Ok(code) => self.with_meta("archived_status", code),
+                Err(_) => self.with_meta("archived_status", status),
+            };
+        }
+        if let Some(mime) = present(mime) {
+            self = self.with_meta("mime", mime);
+        }
+        self
+    }
+}
+
+/// Metadata keys archive providers fill via [`UrlRecord::with_snapshot`]:
+/// when the capture was taken, the status code the archive recorded, and the
+/// captured MIME type. CSV output gets one column per key.
+pub const SNAPSHOT_FIELDS: [&str; 3] = ["timestamp", "archived_status", "mime"];
+
+/// `20200102030405` -> `2020-01-02T03:04:05Z`. Anything that isn't a 14-digit
+/// CDX timestamp (e.g. an ISO string from urlscan) is returned unchanged.
+fn cdx_timestamp_to_rfc3339(ts: &str) -> String {
+    if ts.len() != 14 || !ts.bytes().all(|b| b.is_ascii_digit()) {
+        return ts.to_string();
+    }
+    format!(
+        "{}-{}-{}T{}:{}:{}Z",
+        &ts[0..4],
+        &ts[4..6],
+        &ts[6..8],
+        &ts[8..10],
+        &ts[10..12],
+        &ts[12..14]
+    )
+}
+
+/// Pages of records from [`Provider::fetch_records_stream`], in the order the
+/// upstream returned them. An `Err` item ends the stream.
+pub type RecordStream<'a> = Pin<Box<dyn Stream<Item = Result<Vec<UrlRecord>>> + Send + 'a>>;
+
+/// Sending half handed to a paginating fetch by [`page_stream`].
+pub struct PageSink {
+    tx: mpsc::Sender<Result<Vec<UrlRecord>>>,
+    sent: usize,
+}
+
+impl PageSink {
+    /// Hand one page downstream, waiting while the consumer is still busy with
+    /// the previous one. Returns `false` once the consumer has dropped the
+    /// stream, so the fetch can stop paginating. Empty pages are not sent.
+    pub async fn send(&mut self, page: Vec<UrlRecord>) -> bool {
+        if page.is_empty() {
+            return !self.tx.is_closed();
+        }
+        self.sent += page.len();
+        self.tx.send(Ok(page)).await.is_ok()
+    }
+
+    /// Records sent so far.
+    pub fn sent(&self) -> usize {
+        self.sent
+    }
+}
+
+/// Turn a paginating fetch into a [`RecordStream`]. `produce` pushes each page
+/// into its [`PageSink`] as soon as it is parsed; an error it returns becomes
+/// the stream's last item. The fetch runs inside the stream's own poll, so it
+/// can borrow the provider, and the one-page channel stops it from running
+/// ahead of a slow consumer.
+pub fn page_stream<'a, F, Fut>(produce: F) -> RecordStream<'a>
+where
+    F: FnOnce(PageSink) -> Fut,
+    Fut: Future<Output = Result<()>> + Send + 'a,
+{
+    let (tx, rx) = mpsc::channel(0);
+    let mut error_tx = tx.clone();
+    let fetch = produce(PageSink { tx, sent: 0 });
+    let driver = async move {
+        if let Err(e) = fetch.await {
+            let _ = error_tx.send(Err(e)).await;
+        }
+    };
+    // The driver never yields items of its own; it only needs polling
+    // alongside the receiver until it has sent everything.
+    let driver = driver.into_stream().filter_map(|()| async { None });
+    Box::pin(stream::select(rx, driver))
+}
+
+/// Gather a [`RecordStream`] back into a single batch, for providers whose
+/// one-shot fetch is built on their stream.
+pub async fn collect_pages(pages: RecordStream<'_>) -> Result<Vec<UrlRecord>> {
+    pages.try_concat().await
+}
+
+/// Run one request of a provider's fetch unless the run is cancelled
+/// (`--max-time` / Ctrl-C). A cancelled fetch stops where it is and keeps
+/// what it already collected, so `None` also flags `reporter` partial rather
+/// than letting the cut-short result pass as complete.
+pub async fn unless_cancelled<F: Future>(
+    cancel: &CancellationToken,
+    reporter: Option<&crate::progress::ProgressReporter>,
+    fut: F,
+) -> Option<F::Output> {
+    let out = cancel.run_until_cancelled(fut).await;
+    if out.is_none() {
+        if let Some(r) = reporter {
+            r.mark_partial();
+        }
+    }
+    out
+}
+
 /// Provider trait for URL discovery services
 ///
 /// This trait defines common operations for classes that fetch URLs
@@ -53,6 +219,47 @@ pub trait Provider: Send + Sync {
         self.fetch_urls(domain)
     }
 
+    /// Fetch URLs along with per-URL metadata. This is what the runner calls.
+    ///
+    /// The default implementation wraps [`Provider::fetch_urls_with_progress`]
+    /// with empty metadata; providers whose upstream returns richer records
+    /// override it (and usually implement the URL-only methods on top of it).
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<crate::progress::ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
+        Box::pin(async move {
+            let urls = self.fetch_urls_with_progress(domain, reporter).await?;
+            Ok(urls.into_iter().map(UrlRecord::new).collect())
+        })
+    }
+
+    /// Stream records page by page as the upstream returns them, so a
+    /// consumer such as `--stream` can filter and print the first pages of a
+    /// huge domain while later ones are still downloading.
+    ///
+    /// The default implementation yields the whole
+    /// [`Provider::fetch_records_with_progress`] result as a single page.
+    /// Paginating providers override it with [`page_stream`]. Pages may
+    /// repeat URLs seen on earlier pages; deduplication is the consumer's job.
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<crate::progress::ProgressReporter>,
+    ) -> RecordStream<'a> {
+        Box::pin(stream::once(
+            self.fetch_records_with_progress(domain, reporter),
+        ))
+    }
+
+    /// Describe the requests a fetch for `domain` would make, one line per
+    /// request or pagination step, without sending anything. Used by
+    /// `--dry-run`; API keys are masked. The default has no plan to show.
+    fn plan_requests(&self, _domain: &str) -> Vec<String> {
+        Vec::new()
+    }
+
     // Configuration options
     /// Include or exclude subdomains in the search
     fn with_subdomains(&mut self, include: bool);
@@ -77,4 +284,107 @@ pub trait Provider: Send + Sync {
 
     /// Set rate limiting to avoid being blocked by providers
     fn with_rate_limit(&mut self, requests_per_second: Option<f32>);
+
+    /// Attach a token that aborts in-flight fetches once cancelled (--max-time,
+    /// Ctrl-C). Paginating providers stop between pages and return what they
+    /// have, flagged partial. Single-request providers can ignore it — the
+    /// runner aborts their task after a short grace period anyway.
+    fn with_cancellation(&mut self, _token: CancellationToken) {}
+
+    /// Route every outbound request through the run-wide
+    /// `--global-rate-limit` / `--max-requests` gate. Applied after
+    /// [`with_rate_limit`](Self::with_rate_limit), which would otherwise
+    /// replace the gated limiter.
+    fn with_request_gate(&mut self, _gate: RequestGate) {}
+
+    /// Count every outbound request on a daily quota meter
+    /// (`--provider-quota`). Applied last, like the gate. Only keyed
+    /// providers with a daily API quota implement it.
+    fn with_quota(&mut self, _meter: QuotaMeter) {}
+
+    /// The meter attached by [`with_quota`](Self::with_quota), so the runner
+    /// can skip remaining domains once the quota is spent.
+    fn quota(&self) -> Option<&QuotaMeter> {
+        None
+    }
+}
+
+/// Long-running provider that pushes URLs as they are discovered instead of
+/// returning a one-shot batch like [`Provider::fetch_urls`].
+///
+/// Used by watch modes (e.g. `--watch-ct`) that follow a live feed until the
+/// user interrupts. Implementations should return once `sink` is closed.
+pub trait StreamingProvider: Send + Sync {
+    /// Watch the feed for URLs belonging to `domains`, sending each new URL to
+    /// `sink`. Only returns on an unrecoverable error (the feed can't be
+    /// reached, or reconnecting keeps failing) or when the receiver is dropped.
+    fn watch<'a>(
+        &'a self,
+        domains: &'a [String],
+        sink: UnboundedSender<String>,
+    ) -> Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unless_cancelled_flags_partial() {
+        let reporter =
+            crate::progress::ProgressReporter::new(indicatif::ProgressBar::hidden(), "t · ");
+        let cancel = CancellationToken::new();
+        assert_eq!(
+            unless_cancelled(&cancel, Some(&reporter), async { 1 }).await,
+            Some(1)
+        );
+        assert!(!reporter.is_partial());
+
+        cancel.cancel();
+        let sent = unless_cancelled(&cancel, Some(&reporter), std::future::pending::<()>()).await;
+        assert!(sent.is_none());
+        assert!(reporter.is_partial());
+        assert!(unless_cancelled(&cancel, None, async { 1 }).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_page_stream_yields_pages_then_error() {
+        let pages: Vec<Result<Vec<UrlRecord>>> = page_stream(|mut sink| async move {
+            sink.send(vec![UrlRecord::new("https://a/1".into())]).await;
+            sink.send(Vec::new()).await;
+            sink.send(vec![UrlRecord::new("https://a/2".into())]).await;
+            assert_eq!(sink.sent(), 2);
+            Err(anyhow::anyhow!("page 3 failed"))
+        })
+        .collect()
+        .await;
+
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].as_ref().unwrap()[0].url, "https://a/1");
+        assert_eq!(pages[1].as_ref().unwrap()[0].url, "https://a/2");
+        assert!(pages[2].is_err());
+    }
+
+    #[tokio::test]
+    async fn test_page_stream_stops_when_dropped() {
+        let produced = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = std::sync::Arc::clone(&produced);
+        let mut pages = page_stream(move |mut sink| async move {
+            for i in 0.. {
+                counter.store(i + 1, std::sync::atomic::Ordering::Relaxed);
+                if !sink
+                    .send(vec![UrlRecord::new(format!("https://a/{i}"))])
+                    .await
+                {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        pages.next().await.unwrap().unwrap();
+        drop(pages);
+        // The fetch only runs while the stream is polled, one page ahead.
+        assert!(produced.load(std::sync::atomic::Ordering::Relaxed) <= 3);
+    }
 }
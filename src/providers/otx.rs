@@ -4,10 +4,14 @@ use serde::de::Deserializer;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
-use super::Provider;
+use super::{collect_pages, page_stream, Provider, RecordStream, UrlRecord};
+use crate::filters::StatusFilter;
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{read_text, server_retry_delay, wait_before_retry, RateLimiter, RequestGate};
+use crate::progress::ProgressReporter;
+use crate::utils::split_host_port;
 
 // Helper function to deserialize null as default value for i32
 fn deserialize_null_i32<'de, D>(deserializer: D) -> Result<i32, D::Error>
@@ -28,7 +32,9 @@ pub struct OTXProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
     base_url: String,
+    status_filter: StatusFilter,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -79,10 +85,19 @@ impl OTXProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
             base_url: "https://otx.alienvault.com".to_string(),
+            status_filter: StatusFilter::default(),
         }
     }
 
+    /// Drop entries whose recorded `httpcode` doesn't match the filter
+    /// (`--provider-status-filter`). Entries without one are kept.
+    pub fn with_status_filter(&mut self, filter: StatusFilter) -> &mut Self {
+        self.status_filter = filter;
+        self
+    }
+
     #[cfg(test)]
     fn with_base_url(&mut self, url: String) {
         self.base_url = url;
@@ -95,6 +110,7 @@ impl OTXProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -105,6 +121,8 @@ impl OTXProvider {
     fn format_url(&self, domain: &str, page: u32) -> String {
         // AlienVault OTX API pages start at 1, not 0
         let page_number = page + 1;
+        // OTX indexes hosts, not ports; host validation applies any port scope
+        let (domain, _) = split_host_port(domain);
 
         // We should always use domain endpoint for second-level domains like example.com
         // and hostname endpoint for subdomains like sub.example.com
@@ -163,7 +181,25 @@ impl Provider for OTXProvider {
         domain: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
         Box::pin(async move {
-            let mut all_urls = Vec::new();
+            let records = self.fetch_records_with_progress(domain, None).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
+        Box::pin(collect_pages(self.fetch_records_stream(domain, reporter)))
+    }
+
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> RecordStream<'a> {
+        page_stream(move |mut sink| async move {
             let mut page = 0;
             let client = self.client_config().build_client()?;
             let limiter = self.rate_limit.as_ref();
@@ -174,15 +210,27 @@ impl Provider for OTXProvider {
                 // Retry logic
                 let mut last_error = None;
                 let mut result = None;
+                let mut server_delay = None;
 
                 for attempt in 0..=self.retries {
-                    if let Some(rl) = &limiter {
-                        rl.acquire().await;
+                    if attempt > 0 {
+                        wait_before_retry(attempt, server_delay.take()).await;
                     }
-                    match client.get(&url).send().await {
+                    let Some(sent) =
+                        super::unless_cancelled(&self.cancel, reporter.as_ref(), async {
+                            if let Some(rl) = &limiter {
+                                rl.acquire().await;
+                            }
+                            client.get(&url).send().await
+                        })
+                        .await
+                    else {
+                        return Ok(());
+                    };
+                    match sent {
                         Ok(response) => {
                             if response.status().is_success() {
-                                match response.text().await {
+                                match read_text(response).await {
                                     Ok(text) => {
                                         // Try to parse as OTXResult first
                                         let parse_result = serde_json::from_str::<OTXResult>(&text);
@@ -256,6 +304,8 @@ impl Provider for OTXProvider {
                                     }
                                 }
                             } else {
+                                server_delay =
+                                    server_retry_delay(response.status(), response.headers());
                                 last_error =
                                     Some(anyhow::anyhow!("HTTP error: {}", response.status()));
                             }
@@ -268,10 +318,6 @@ impl Provider for OTXProvider {
                     if result.is_some() {
                         break;
                     }
-
-                    if attempt < self.retries {
-                        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
-                    }
                 }
 
                 if let Some(otx_result) = result {
@@ -281,13 +327,20 @@ impl Provider for OTXProvider {
                     // Keep only entries with a usable URL — OTX occasionally
                     // returns rows with an empty `url`, which would otherwise be
                     // emitted as blank lines.
-                    all_urls.extend(
-                        otx_result
-                            .url_list
-                            .into_iter()
-                            .map(|entry| entry.url)
-                            .filter(|url| !url.is_empty()),
-                    );
+                    let records = otx_result
+                        .url_list
+                        .into_iter()
+                        .filter(|entry| {
+                            !entry.url.is_empty()
+                                && self
+                                    .status_filter
+                                    .allows(u16::try_from(entry.httpcode).ok())
+                        })
+                        .map(|entry| UrlRecord::new(entry.url))
+                        .collect();
+                    if !sink.send(records).await {
+                        break;
+                    }
 
                     // Stop when this page returned nothing (there is no more
                     // data, even if the server still claims `has_next`), or when
@@ -312,10 +365,17 @@ impl Provider for OTXProvider {
                 }
             }
 
-            Ok(all_urls)
+            Ok(())
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!("GET {}", self.format_url(domain, 0)),
+            format!("  then page=2, 3, … while has_next (at most {OTX_MAX_PAGES} pages)"),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -347,6 +407,14 @@ impl Provider for OTXProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -493,6 +561,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_url_strips_port() {
+        let provider = OTXProvider::new();
+        let url = provider.format_url("example.com:8080", 0);
+        assert_eq!(
+            url,
+            format!(
+                "https://otx.alienvault.com/api/v1/indicators/domain/example.com/url_list?limit={OTX_RESULTS_LIMIT}&page=1"
+            )
+        );
+    }
+
     #[test]
     fn test_format_url_with_pagination() {
         let provider = OTXProvider::new();
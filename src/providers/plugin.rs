@@ -0,0 +1,365 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::future::Future;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio_util::sync::CancellationToken;
+
+use super::{collect_pages, page_stream, Provider, RecordStream, UrlRecord};
+use crate::progress::ProgressReporter;
+
+/// Records handed downstream per page while a plugin is still printing.
+const PLUGIN_PAGE_SIZE: usize = 500;
+
+/// Bytes of a failed plugin's stderr quoted in the error.
+const STDERR_TAIL: usize = 2048;
+
+/// One `[[provider.plugin]]` entry from config.toml: an external program
+/// run once per domain as a provider, so a source can ship as a script or
+/// binary of its own instead of a patch to urx.
+///
+/// The program gets the domain as the single line of its stdin (and as
+/// `{domain}` in `args`) and prints one URL per line on stdout. A line that
+/// is a JSON object is read as a record: its `url` key is the URL and the
+/// other keys become metadata.
+///
+/// ```toml
+/// [[provider.plugin]]
+/// id = "gau"
+/// command = "gau"
+/// args = ["--subs", "{domain}"]
+/// timeout = 600
+/// ```
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PluginProviderDef {
+    /// Id used with `--providers`, `--rate-limit-by`, `[provider.<id>]`, …
+    pub id: String,
+    /// Name shown in progress and stats. Defaults to the id.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Program to run, looked up on `PATH` when it has no slash.
+    pub command: String,
+    /// Arguments. `{domain}` in any of them becomes the target.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds one run may take before it is killed. Unlimited by default;
+    /// `--max-time` still stops it.
+    #[serde(default)]
+    pub timeout: Option<u64>,
+}
+
+impl PluginProviderDef {
+    /// Display name: `name`, or the id when unset.
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.id.clone())
+    }
+}
+
+/// Parse one line of plugin output. Blank lines, `#` comments and lines
+/// that aren't URLs or `{"url": …}` objects yield nothing.
+fn parse_line(line: &str) -> Option<UrlRecord> {
+    let line = line.trim();
+    if line.starts_with('{') {
+        let serde_json::Value::Object(mut fields) = serde_json::from_str(line).ok()? else {
+            return None;
+        };
+        let url = match fields.remove("url")? {
+            serde_json::Value::String(url) if !url.trim().is_empty() => url,
+            _ => return None,
+        };
+        return Some(UrlRecord {
+            url,
+            metadata: fields,
+        });
+    }
+    if line.starts_with("http://") || line.starts_with("https://") {
+        Some(UrlRecord::new(line.to_string()))
+    } else {
+        None
+    }
+}
+
+/// Provider backed by a [`PluginProviderDef`].
+#[derive(Clone)]
+pub struct PluginProvider {
+    def: PluginProviderDef,
+    include_subdomains: bool,
+    proxy: Option<String>,
+    cancel: CancellationToken,
+}
+
+impl PluginProvider {
+    /// Check `def` and build its provider. Errors name the offending id and
+    /// field.
+    pub fn new(def: PluginProviderDef) -> Result<Self> {
+        let id = def.id.clone();
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-'))
+        {
+            anyhow::bail!(
+                "Invalid plugin provider id {id:?}: use lowercase letters, digits, '_' and '-'"
+            );
+        }
+        if def.command.trim().is_empty() {
+            anyhow::bail!("Plugin provider {id}: command is empty");
+        }
+        if def.timeout == Some(0) {
+            anyhow::bail!("Plugin provider {id}: timeout must be at least 1 second");
+        }
+
+        Ok(PluginProvider {
+            def,
+            include_subdomains: false,
+            proxy: None,
+            cancel: CancellationToken::new(),
+        })
+    }
+
+    fn command(&self, domain: &str) -> Command {
+        let mut cmd = Command::new(&self.def.command);
+        cmd.args(self.def.args.iter().map(|a| a.replace("{domain}", domain)))
+            .env("URX_DOMAIN", domain)
+            .env("URX_SUBS", if self.include_subdomains { "1" } else { "0" })
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(proxy) = &self.proxy {
+            cmd.env("URX_PROXY", proxy);
+        }
+        cmd
+    }
+
+    /// Run the plugin for `domain`, sending its records downstream in pages
+    /// as it prints them.
+    async fn run(&self, domain: &str, sink: &mut super::PageSink) -> Result<RunEnd> {
+        let id = &self.def.id;
+        let mut child = self
+            .command(domain)
+            .spawn()
+            .with_context(|| format!("Plugin provider {id}: failed to run {}", self.def.command))?;
+
+        let mut stdin = child.stdin.take().context("plugin stdin")?;
+        // A plugin that never reads stdin closes it early; that's fine.
+        let _ = stdin.write_all(format!("{domain}\n").as_bytes()).await;
+        drop(stdin);
+
+        // Drain stderr alongside stdout so a chatty plugin can't block on a
+        // full pipe.
+        let mut stderr = child.stderr.take().context("plugin stderr")?;
+        let stderr_task = tokio::spawn(async move {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf).await;
+            buf
+        });
+
+        let mut lines = BufReader::new(child.stdout.take().context("plugin stdout")?).lines();
+        let mut page = Vec::new();
+        while let Some(line) = lines
+            .next_line()
+            .await
+            .with_context(|| format!("Plugin provider {id}: reading output"))?
+        {
+            page.extend(parse_line(&line));
+            if page.len() >= PLUGIN_PAGE_SIZE && !sink.send(std::mem::take(&mut page)).await {
+                return Ok(RunEnd::Dropped);
+            }
+        }
+        if !sink.send(page).await {
+            return Ok(RunEnd::Dropped);
+        }
+
+        let status = child.wait().await?;
+        if status.success() {
+            return Ok(RunEnd::Done);
+        }
+        let stderr = stderr_task.await.unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stderr = stderr.trim();
+        let mut start = stderr.len().saturating_sub(STDERR_TAIL);
+        while !stderr.is_char_boundary(start) {
+            start += 1;
+        }
+        let tail = &stderr[start..];
+        Ok(RunEnd::Failed(if tail.is_empty() {
+            format!("Plugin provider {id} exited with {status}")
+        } else {
+            format!("Plugin provider {id} exited with {status}: {tail}")
+        }))
+    }
+}
+
+/// How a plugin run ended short of an I/O error.
+enum RunEnd {
+    Done,
+    /// The consumer dropped the stream; the child is killed on drop.
+    Dropped,
+    /// Non-zero exit, with the message to report.
+    Failed(String),
+}
+
+impl Provider for PluginProvider {
+    fn clone_box(&self) -> Box<dyn Provider> {
+        Box::new(self.clone())
+    }
+
+    fn fetch_urls<'a>(
+        &'a self,
+        domain: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = collect_pages(self.fetch_records_stream(domain, None)).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> RecordStream<'a> {
+        page_stream(move |mut sink| async move {
+            let run = self.run(domain, &mut sink);
+            let limited = async {
+                match self.def.timeout {
+                    Some(secs) => tokio::time::timeout(Duration::from_secs(secs), run)
+                        .await
+                        .ok(),
+                    None => Some(run.await),
+                }
+            };
+            let ended = match self.cancel.run_until_cancelled(limited).await.flatten() {
+                Some(ended) => ended?,
+                // Cancelled or timed out: keep what was printed so far.
+                None if sink.sent() > 0 => {
+                    if let Some(r) = &reporter {
+                        r.mark_partial();
+                    }
+                    return Ok(());
+                }
+                None => anyhow::bail!("Plugin provider {} timed out", self.def.id),
+            };
+            match ended {
+                RunEnd::Done | RunEnd::Dropped => Ok(()),
+                // URLs already sent stand; a crash after them is a partial run.
+                RunEnd::Failed(_) if sink.sent() > 0 => {
+                    if let Some(r) = &reporter {
+                        r.mark_partial();
+                    }
+                    Ok(())
+                }
+                RunEnd::Failed(msg) => Err(anyhow::anyhow!(msg)),
+            }
+        })
+    }
+
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        let mut line = format!("RUN {}", self.def.command);
+        for arg in &self.def.args {
+            line.push(' ');
+            line.push_str(&arg.replace("{domain}", domain));
+        }
+        vec![line, format!("  with {domain} on stdin")]
+    }
+
+    fn with_subdomains(&mut self, include: bool) {
+        self.include_subdomains = include;
+    }
+
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// The plugin makes its own requests; only the proxy URL is passed on.
+    fn with_proxy_auth(&mut self, _auth: Option<String>) {}
+
+    fn with_timeout(&mut self, _seconds: u64) {}
+
+    fn with_retries(&mut self, _count: u32) {}
+
+    fn with_random_agent(&mut self, _enabled: bool) {}
+
+    fn with_insecure(&mut self, _enabled: bool) {}
+
+    fn with_rate_limit(&mut self, _rate_limit: Option<f32>) {}
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line_reads_urls_and_json_records() {
+        assert_eq!(
+            parse_line("  https://example.com/a \n").unwrap().url,
+            "https://example.com/a"
+        );
+        let record =
+            parse_line(r#"{"url":"https://example.com/b","source":"crawler","depth":2}"#).unwrap();
+        assert_eq!(record.url, "https://example.com/b");
+        assert_eq!(record.metadata["source"], "crawler");
+        assert_eq!(record.metadata["depth"], 2);
+        assert!(parse_line("# comment").is_none());
+        assert!(parse_line("").is_none());
+        assert!(parse_line("example.com/no-scheme").is_none());
+        assert!(parse_line(r#"{"href":"https://example.com/c"}"#).is_none());
+
+        assert!(PluginProvider::new(PluginProviderDef {
+            id: "Bad Id".to_string(),
+            name: None,
+            command: "true".to_string(),
+            args: vec![],
+            timeout: None,
+        })
+        .is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_fetch_runs_the_command_per_domain() {
+        let script = r#"read d; echo "https://$d/stdin"; echo "https://$1/arg?subs=$URX_SUBS"; echo noise; echo oops >&2"#;
+        let mut provider = PluginProvider::new(PluginProviderDef {
+            id: "sh".to_string(),
+            name: None,
+            command: "sh".to_string(),
+            args: vec![
+                "-c".into(),
+                script.into(),
+                "plugin".into(),
+                "{domain}".into(),
+            ],
+            timeout: Some(10),
+        })
+        .unwrap();
+        provider.with_subdomains(true);
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/stdin",
+                "https://example.com/arg?subs=1"
+            ]
+        );
+
+        let failing = PluginProvider::new(PluginProviderDef {
+            id: "fail".to_string(),
+            name: None,
+            command: "sh".to_string(),
+            args: vec!["-c".into(), "echo 'no token' >&2; exit 3".into()],
+            timeout: None,
+        })
+        .unwrap();
+        let err = failing.fetch_urls("example.com").await.unwrap_err();
+        assert!(err.to_string().contains("no token"), "{err}");
+    }
+}
@@ -6,9 +6,174 @@ use std::pin::Pin;
 use std::time::Duration;
 
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{read_text, RateLimiter, RequestGate};
 use crate::providers::Provider;
 
+/// What urx takes from a robots.txt file: the `Allow` / `Disallow` values
+/// (patterns included, in file order) and the `Sitemap:` URLs.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct RobotsRules {
+    pub paths: Vec<String>,
+    pub sitemaps: Vec<String>,
+}
+
+/// Parse robots.txt `text`. Empty rules and the bare `/` are dropped, since
+/// neither names anything beyond the site root.
+pub(crate) fn parse_robots(text: &str) -> RobotsRules {
+    let mut rules = RobotsRules::default();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        // RFC 9309: field names are case-insensitive and may carry
+        // surrounding whitespace (e.g. `Disallow :`). Split on the first
+        // colon so `Sitemap: https://…` keeps its `https://` value.
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        // Take the first whitespace-delimited token of the value: paths
+        // and URLs never contain spaces, so this drops any trailing
+        // inline `# comment` and stray whitespace in one step.
+        let value = value.split_whitespace().next().unwrap_or("");
+        if value.is_empty() {
+            continue;
+        }
+        match field.trim().to_ascii_lowercase().as_str() {
+            "allow" | "disallow" if value != "/" => rules.paths.push(value.to_string()),
+            "sitemap" => rules.sitemaps.push(value.to_string()),
+            _ => {}
+        }
+    }
+    rules
+}
+
+/// The concrete path an `Allow` / `Disallow` rule names. A `$` end-anchor is
+/// stripped. A rule with `*` wildcards yields nothing unless `expand` is set;
+/// then it yields its literal part: the prefix itself for a trailing `*`
+/// (`/private*` -> `/private`), or else the directory the first wildcard sits
+/// in (`/admin/*/edit` -> `/admin/`). Patterns that only reach the root yield
+/// nothing.
+pub(crate) fn rule_path(rule: &str, expand: bool) -> Option<String> {
+    let rule = rule.strip_suffix('$').unwrap_or(rule);
+    let path = match rule.find('*') {
+        None => rule,
+        Some(_) if !expand => return None,
+        Some(star) if rule[star..].chars().all(|c| c == '*') => {
+            let prefix = &rule[..star];
+            prefix.split_once('?').map_or(prefix, |(path, _)| path)
+        }
+        Some(star) => {
+            let prefix = &rule[..star];
+            let prefix = prefix.split_once('?').map_or(prefix, |(path, _)| path);
+            &prefix[..prefix.rfind('/').map_or(0, |i| i + 1)]
+        }
+    };
+    (path.starts_with('/') && path != "/").then(|| path.to_string())
+}
+
+/// One `Allow` / `Disallow` rule of a robots.txt group, for
+/// `--respect-robots`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct AccessRule {
+    pub allow: bool,
+    pub pattern: String,
+}
+
+/// The rules robots.txt `text` sets for the crawler named `agent`: those of
+/// every group naming it (case-insensitively), or else of the `*` groups
+/// (RFC 9309 §2.2.1). An empty `Disallow:` sets no rule.
+pub(crate) fn access_rules(text: &str, agent: &str) -> Vec<AccessRule> {
+    let mut groups: Vec<(Vec<String>, Vec<AccessRule>)> = Vec::new();
+    let mut in_rules = false;
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let Some((field, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.split_whitespace().next().unwrap_or("");
+        let allow = match field.trim().to_ascii_lowercase().as_str() {
+            "user-agent" => {
+                // Consecutive user-agent lines share one group
+                if in_rules || groups.is_empty() {
+                    groups.push((Vec::new(), Vec::new()));
+                    in_rules = false;
+                }
+                if let Some((agents, _)) = groups.last_mut() {
+                    agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            "allow" => true,
+            "disallow" => false,
+            _ => continue,
+        };
+        in_rules = true;
+        if let (Some((_, rules)), false) = (groups.last_mut(), value.is_empty()) {
+            rules.push(AccessRule {
+                allow,
+                pattern: value.to_string(),
+            });
+        }
+    }
+
+    let agent = agent.to_ascii_lowercase();
+    let rules_for = |name: &str| -> Option<Vec<AccessRule>> {
+        let matching: Vec<&(Vec<String>, Vec<AccessRule>)> = groups
+            .iter()
+            .filter(|(agents, _)| agents.iter().any(|a| a == name))
+            .collect();
+        (!matching.is_empty()).then(|| {
+            matching
+                .into_iter()
+                .flat_map(|(_, rules)| rules.iter().cloned())
+                .collect()
+        })
+    };
+    rules_for(&agent)
+        .or_else(|| rules_for("*"))
+        .unwrap_or_default()
+}
+
+/// Whether `path` (with its query) may be fetched under `rules`: the
+/// longest matching pattern decides, `Allow` wins a tie, and a path no rule
+/// matches is allowed (RFC 9309 §2.2.2).
+pub(crate) fn is_allowed(rules: &[AccessRule], path: &str) -> bool {
+    rules
+        .iter()
+        .filter(|rule| pattern_matches(&rule.pattern, path))
+        .max_by_key(|rule| (rule.pattern.len(), rule.allow))
+        .is_none_or(|rule| rule.allow)
+}
+
+/// Match a robots.txt path pattern, where `*` stands for any run of
+/// characters and a trailing `$` anchors the end, against `path`.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return !anchored || rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    if anchored {
+        rest.ends_with(last)
+    } else {
+        rest.contains(last)
+    }
+}
+
 #[derive(Clone)]
 pub struct RobotsProvider {
     timeout: Duration,
@@ -18,6 +183,7 @@ pub struct RobotsProvider {
     proxy_auth: Option<String>,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    expand_wildcards: bool,
     #[cfg(test)]
     base_url: String,
     #[cfg(test)]
@@ -34,6 +200,7 @@ impl RobotsProvider {
             proxy_auth: None,
             insecure: false,
             rate_limit: None,
+            expand_wildcards: false,
             #[cfg(test)]
             base_url: String::new(),
             #[cfg(test)]
@@ -41,6 +208,13 @@ impl RobotsProvider {
         }
     }
 
+    /// Also turn wildcard `Allow` / `Disallow` rules into candidate URLs from
+    /// their literal prefix (`--robots-wildcards`); see [`rule_path`].
+    pub fn with_wildcard_expansion(&mut self, enabled: bool) -> &mut Self {
+        self.expand_wildcards = enabled;
+        self
+    }
+
     #[cfg(test)]
     pub fn with_base_url(&mut self, url: String) -> &mut Self {
         self.base_url = url;
@@ -60,6 +234,7 @@ impl RobotsProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -103,7 +278,7 @@ impl Provider for RobotsProvider {
             let https_resp = client.get(&https_url).send().await;
             // Track which protocol was successful
             let (is_https, text) = match https_resp {
-                Ok(resp) if resp.status().is_success() => (true, resp.text().await?),
+                Ok(resp) if resp.status().is_success() => (true, read_text(resp).await?),
                 _ => {
                     // If HTTPS fails, try HTTP
                     #[cfg(not(test))]
@@ -131,52 +306,40 @@ impl Provider for RobotsProvider {
                     if !http_resp.status().is_success() {
                         return Ok(urls);
                     }
-                    (false, http_resp.text().await?)
+                    (false, read_text(http_resp).await?)
                 }
             };
 
             // Use the protocol that worked
             let protocol = if is_https { "https" } else { "http" };
 
-            for line in text.lines() {
-                let line = line.trim();
-                if line.is_empty() || line.starts_with('#') {
-                    continue;
-                }
-                // RFC 9309: field names are case-insensitive and may carry
-                // surrounding whitespace (e.g. `Disallow :`). Split on the first
-                // colon so `Sitemap: https://…` keeps its `https://` value.
-                let Some((field, value)) = line.split_once(':') else {
-                    continue;
-                };
-                // Take the first whitespace-delimited token of the value: paths
-                // and URLs never contain spaces, so this drops any trailing
-                // inline `# comment` and stray whitespace in one step.
-                let value = value.split_whitespace().next().unwrap_or("");
-                match field.trim().to_ascii_lowercase().as_str() {
-                    "disallow" if !value.is_empty() && value != "/" => {
-                        // Disallow entries can be match patterns, not literal
-                        // paths: skip glob (`*`) patterns and strip a trailing
-                        // `$` end-anchor so we don't emit unfetchable junk URLs.
-                        if value.contains('*') {
-                            continue;
-                        }
-                        let path = value.strip_suffix('$').unwrap_or(value);
-                        if !path.is_empty() && path != "/" {
-                            urls.push(format!("{protocol}://{domain}{path}"));
-                        }
-                    }
-                    "sitemap" if !value.is_empty() => {
-                        urls.push(value.to_string());
-                    }
-                    _ => {}
+            // Rules can be match patterns rather than literal paths; only
+            // their concrete part becomes a URL, so nothing unfetchable is
+            // emitted. Several patterns can share a prefix.
+            let rules = parse_robots(&text);
+            let mut seen = std::collections::HashSet::new();
+            for path in rules
+                .paths
+                .iter()
+                .filter_map(|rule| rule_path(rule, self.expand_wildcards))
+            {
+                if seen.insert(path.clone()) {
+                    urls.push(format!("{protocol}://{domain}{path}"));
                 }
             }
+            urls.extend(rules.sitemaps);
 
             Ok(urls)
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!("GET https://{domain}/robots.txt"),
+            format!("  on failure: GET http://{domain}/robots.txt"),
+        ]
+    }
+
     fn with_subdomains(&mut self, _include: bool) {}
     fn with_proxy(&mut self, proxy: Option<String>) {
         self.proxy = proxy;
@@ -199,6 +362,10 @@ impl Provider for RobotsProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
 }
 
 #[cfg(test)]
@@ -401,6 +568,79 @@ Sitemap: https://example.com/sitemap.xml
         assert!(!urls.iter().any(|u| u.contains('#')), "{urls:?}");
     }
 
+    #[test]
+    fn test_access_rules_pick_the_group_for_the_agent() {
+        let text = "\
+User-agent: Googlebot
+User-agent: urx
+Disallow: /private   # staff only
+Allow: /private/press
+
+User-agent: *
+Disallow: /
+
+User-agent: URX
+Disallow: /*.json$
+Disallow:
+";
+        let rules = access_rules(text, "urx");
+        assert_eq!(rules.len(), 3);
+        assert!(is_allowed(&rules, "/"));
+        assert!(!is_allowed(&rules, "/private/notes"));
+        assert!(is_allowed(&rules, "/private/press/2024"));
+        assert!(!is_allowed(&rules, "/api/users.json"));
+        assert!(is_allowed(&rules, "/api/users.json?page=2"));
+
+        // Anyone else falls under `*`
+        let rules = access_rules(text, "otherbot");
+        assert!(!is_allowed(&rules, "/anything"));
+        assert!(is_allowed(&access_rules("", "urx"), "/anything"));
+    }
+
+    #[test]
+    fn test_rule_path_expands_wildcards_only_when_asked() {
+        assert_eq!(rule_path("/admin/*/edit", false), None);
+        assert_eq!(rule_path("/admin/*/edit", true).as_deref(), Some("/admin/"));
+        assert_eq!(rule_path("/private*", true).as_deref(), Some("/private"));
+        assert_eq!(rule_path("/search?q=*", true).as_deref(), Some("/search"));
+        assert_eq!(
+            rule_path("/api/v1/*.json$", true).as_deref(),
+            Some("/api/v1/")
+        );
+        assert_eq!(rule_path("/*.php$", true), None);
+        assert_eq!(rule_path("*", true), None);
+        assert_eq!(rule_path("/secret$", false).as_deref(), Some("/secret"));
+    }
+
+    #[tokio::test]
+    async fn test_robots_wildcard_expansion_and_allow_rules() {
+        let mut server = mockito::Server::new_async().await;
+        let robots = "User-agent: *\n\
+                      Allow: /public/\n\
+                      Disallow: /admin/*/edit\n\
+                      Disallow: /admin/*.bak$\n\
+                      Disallow: /*.php\n";
+        let _m = server
+            .mock("GET", "/robots.txt")
+            .with_status(200)
+            .with_body(robots)
+            .create_async()
+            .await;
+
+        let mut provider = RobotsProvider::new();
+        provider.with_base_url(server.url());
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/public/"]);
+
+        provider.with_wildcard_expansion(true);
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        // Both /admin/ patterns collapse into one candidate.
+        assert_eq!(
+            urls,
+            vec!["https://example.com/public/", "https://example.com/admin/"]
+        );
+    }
+
     #[tokio::test]
     async fn test_url_construction() {
         let domain = "example.com";
@@ -9,8 +9,9 @@ use std::pin::Pin;
 use std::time::Duration;
 
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
-use crate::providers::Provider;
+use crate::network::{consume_bandwidth, read_text, RateLimiter, RequestGate};
+use crate::providers::robots::parse_robots;
+use crate::providers::{Provider, UrlRecord};
 
 /// Max nesting depth for sitemap-index → sitemap recursion. A hostile or
 /// misconfigured index can chain or cycle indefinitely; this bounds it.
@@ -27,10 +28,12 @@ const MAX_SITEMAP_BYTES: usize = 50 * 1024 * 1024;
 
 /// Read a response body but stop after `max` bytes, so an unbounded (or
 /// deliberately huge) document can't exhaust memory. Reads incrementally via
-/// `chunk()` rather than buffering the whole body up front.
+/// `chunk()` rather than buffering the whole body up front, charging each
+/// chunk against `--max-bandwidth`.
 async fn read_body_capped(mut resp: reqwest::Response, max: usize) -> Result<String> {
     let mut buf: Vec<u8> = Vec::new();
     while let Some(chunk) = resp.chunk().await? {
+        consume_bandwidth(chunk.len()).await;
         let remaining = max.saturating_sub(buf.len());
         if remaining == 0 {
             break;
@@ -75,6 +78,7 @@ impl SitemapProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -97,7 +101,7 @@ impl SitemapProvider {
         depth: usize,
         visited: &mut HashSet<String>,
         limiter: Option<&RateLimiter>,
-    ) -> Result<Vec<String>> {
+    ) -> Result<Vec<UrlRecord>> {
         if depth > MAX_SITEMAP_DEPTH {
             return Ok(Vec::new());
         }
@@ -170,7 +174,7 @@ impl SitemapProvider {
                             url_node.descendants().find(|n| n.has_tag_name("loc"))
                         {
                             if let Some(url) = loc_node.text() {
-                                urls.push(url.to_string());
+                                urls.push(Self::url_record(url_node, url));
                             }
                         }
                     }
@@ -187,7 +191,7 @@ impl SitemapProvider {
                         }
                         let line = line.trim();
                         if line.starts_with("http") {
-                            urls.push(line.to_string());
+                            urls.push(UrlRecord::new(line.to_string()));
                         }
                     }
                 }
@@ -196,6 +200,62 @@ impl SitemapProvider {
 
         Ok(urls)
     }
+
+    /// Sitemaps declared with `Sitemap:` in the domain's robots.txt (HTTPS,
+    /// falling back to HTTP). These often live at non-standard paths or on
+    /// another host that the fixed locations would never find. Best-effort:
+    /// an unreachable robots.txt just means none.
+    async fn robots_sitemaps(
+        client: &Client,
+        domain: &str,
+        limiter: Option<&RateLimiter>,
+    ) -> Vec<String> {
+        for scheme in ["https", "http"] {
+            if let Some(rl) = limiter {
+                rl.acquire().await;
+            }
+            let Ok(resp) = client
+                .get(format!("{scheme}://{domain}/robots.txt"))
+                .send()
+                .await
+            else {
+                continue;
+            };
+            if !resp.status().is_success() {
+                continue;
+            }
+            let Ok(text) = read_text(resp).await else {
+                continue;
+            };
+            return parse_robots(&text)
+                .sitemaps
+                .into_iter()
+                .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+                .collect();
+        }
+        Vec::new()
+    }
+
+    /// Build a record for a `<url>` entry, keeping `<lastmod>` (verbatim W3C
+    /// datetime) and `<priority>` (as a number) when the sitemap provides them.
+    fn url_record(url_node: roxmltree::Node, loc: &str) -> UrlRecord {
+        let child_text = |tag: &str| {
+            url_node
+                .children()
+                .find(|n| n.has_tag_name(tag))
+                .and_then(|n| n.text())
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+        };
+        let mut record = UrlRecord::new(loc.trim().to_string());
+        if let Some(lastmod) = child_text("lastmod") {
+            record = record.with_meta("lastmod", lastmod);
+        }
+        if let Some(priority) = child_text("priority").and_then(|p| p.parse::<f64>().ok()) {
+            record = record.with_meta("priority", priority);
+        }
+        record
+    }
 }
 
 #[async_trait]
@@ -208,6 +268,17 @@ impl Provider for SitemapProvider {
         &'a self,
         domain: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, None).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        _reporter: Option<crate::progress::ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
         Box::pin(async move {
             let client = self.build_client()?;
             let limiter = self.rate_limit.as_ref();
@@ -216,6 +287,15 @@ impl Provider for SitemapProvider {
             // more than one entry point is fetched at most once.
             let mut visited = HashSet::new();
 
+            for sitemap_url in Self::robots_sitemaps(&client, domain, limiter).await {
+                let found =
+                    Self::parse_sitemap(&client, &sitemap_url, 0, &mut visited, limiter).await;
+                // A broken declared sitemap shouldn't hide the standard ones.
+                if let Ok(found) = found {
+                    urls.extend(found);
+                }
+            }
+
             // Try common sitemap locations
             let sitemap_urls = vec![
                 format!("https://{}/sitemap.xml", domain),
@@ -227,6 +307,10 @@ impl Provider for SitemapProvider {
             ];
 
             for sitemap_url in sitemap_urls {
+                // Already walked via robots.txt.
+                if visited.contains(&sitemap_url) {
+                    continue;
+                }
                 // Pace the candidate-location probes too: this loop fires up to
                 // six back-to-back requests at the target.
                 if let Some(rl) = &limiter {
@@ -249,6 +333,20 @@ impl Provider for SitemapProvider {
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        let mut plan = vec![format!(
+            "GET https://{domain}/robots.txt (on failure over http) for Sitemap: entries"
+        )];
+        plan.extend(["https", "http"].iter().flat_map(|scheme| {
+            ["sitemap.xml", "sitemap_index.xml", "sitemap.txt"]
+                .map(|file| format!("GET {scheme}://{domain}/{file}"))
+        }));
+        plan.push(format!(
+            "  then every sitemap robots.txt declares and every nested sitemap they list (at most {MAX_SITEMAP_DEPTH} levels deep)"
+        ));
+        plan
+    }
+
     fn with_subdomains(&mut self, _include: bool) {}
     fn with_proxy(&mut self, proxy: Option<String>) {
         self.proxy = proxy;
@@ -271,6 +369,10 @@ impl Provider for SitemapProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
 }
 
 #[cfg(test)]
@@ -489,6 +591,79 @@ mod tests {
         assert!(urls.contains(&"https://example.com/page2".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_fetch_urls_follows_robots_sitemap_directive() {
+        let mut server = Server::new_async().await;
+        let host = server.host_with_port();
+        let _robots = server
+            .mock("GET", "/robots.txt")
+            .with_status(200)
+            .with_body(format!(
+                "User-agent: *\nDisallow: /tmp/\nSitemap: http://{host}/maps/products.xml\n"
+            ))
+            .create_async()
+            .await;
+        let products = server
+            .mock("GET", "/maps/products.xml")
+            .with_status(200)
+            .with_header("content-type", "application/xml")
+            .with_body(r#"<urlset><url><loc>https://example.com/p/1</loc></url></urlset>"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let urls = SitemapProvider::new().fetch_urls(&host).await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/p/1".to_string()]);
+        products.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_records_keeps_lastmod_and_priority() {
+        let mut server = Server::new_async().await;
+        let sitemap_xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+  <url>
+    <loc>https://example.com/fresh</loc>
+    <lastmod>2024-05-01T10:00:00+00:00</lastmod>
+    <priority>0.8</priority>
+  </url>
+  <url>
+    <loc>https://example.com/bare</loc>
+    <priority>not-a-number</priority>
+  </url>
+</urlset>"#;
+
+        let _m = server
+            .mock("GET", "/sitemap.xml")
+            .with_status(200)
+            .with_header("content-type", "application/xml")
+            .with_body(sitemap_xml)
+            .create_async()
+            .await;
+
+        let provider = SitemapProvider::new();
+        let records = provider
+            .fetch_records_with_progress(&server.host_with_port(), None)
+            .await
+            .unwrap();
+
+        let fresh = records
+            .iter()
+            .find(|r| r.url == "https://example.com/fresh")
+            .unwrap();
+        assert_eq!(
+            fresh.metadata["lastmod"],
+            serde_json::json!("2024-05-01T10:00:00+00:00")
+        );
+        assert_eq!(fresh.metadata["priority"], serde_json::json!(0.8));
+
+        let bare = records
+            .iter()
+            .find(|r| r.url == "https://example.com/bare")
+            .unwrap();
+        assert!(bare.metadata.is_empty());
+    }
+
     #[tokio::test]
     async fn test_fetch_urls_sitemap_index() {
         let mut server = Server::new_async().await;
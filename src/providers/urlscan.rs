@@ -2,11 +2,16 @@ use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
 use super::ApiKeyRotator;
-use super::Provider;
+use super::{Provider, UrlRecord};
+use crate::filters::StatusFilter;
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{
+    read_json, server_retry_delay, wait_before_retry, QuotaMeter, RateLimiter, RequestGate,
+};
+use crate::utils::split_host_port;
 
 #[derive(Clone)]
 pub struct UrlscanProvider {
@@ -19,6 +24,15 @@ pub struct UrlscanProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
+    quota: Option<QuotaMeter>,
+    max_results: usize,
+    status_filter: StatusFilter,
+    /// `--from` / `--to` as `YYYY-MM-DD`, for the search's `date:` range.
+    from: Option<String>,
+    to: Option<String>,
+    scan_initial_wait: std::time::Duration,
+    scan_poll_interval: std::time::Duration,
     #[cfg(test)]
     base_url: String,
 }
@@ -36,9 +50,18 @@ struct UrlscanResponse {
 struct SearchResult {
     page: ArchivedPage,
     #[serde(default)]
+    task: ScanTask,
+    #[serde(default)]
     sort: Vec<serde_json::Value>,
 }
 
+/// When the scan behind a search result ran.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ScanTask {
+    #[serde(default)]
+    time: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct ArchivedPage {
     domain: String,
@@ -50,9 +73,124 @@ struct ArchivedPage {
     status: String,
 }
 
-/// Hard ceiling on urlscan result pages walked for one domain (100 results
-/// each), so a huge or misbehaving result set can't spin indefinitely.
-const URLSCAN_MAX_PAGES: usize = 100;
+/// Results per search request; urlscan rejects larger `size` values for
+/// non-premium keys.
+const URLSCAN_PAGE_SIZE: usize = 100;
+
+/// Hard ceiling on urlscan result pages walked for one domain, so a huge or
+/// misbehaving result set can't spin indefinitely even with no results cap.
+const URLSCAN_MAX_PAGES: usize = 1_000;
+
+/// Default `--urlscan-max-results`. Deep pagination burns through the search
+/// quota quickly (one request per 100 results), so the walk stops here unless
+/// the user raises or disables the cap.
+pub const DEFAULT_URLSCAN_MAX_RESULTS: usize = 10_000;
+
+/// Body of a `POST /api/v1/scan/` submission. Scans are submitted as
+/// `unlisted` so they don't show up on urlscan's public feed.
+#[derive(Debug, Serialize)]
+struct ScanRequest<'a> {
+    url: &'a str,
+    visibility: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanSubmission {
+    uuid: String,
+}
+
+/// The subset of `GET /api/v1/result/{uuid}/` we harvest: the links found in
+/// the rendered DOM.
+#[derive(Debug, Deserialize, Default)]
+struct ScanResult {
+    #[serde(default)]
+    data: ScanData,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ScanData {
+    #[serde(default)]
+    links: Vec<ScanLink>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScanLink {
+    #[serde(default)]
+    href: String,
+}
+
+/// What a `--urlscan-submit` round produced for one domain.
+#[derive(Debug, Default)]
+pub struct ScanHarvest {
+    /// Links found in the DOM of every scan that finished in time.
+    pub links: Vec<String>,
+    /// Number of scans urlscan accepted.
+    pub submitted: usize,
+    /// Set when urlscan answered 429 to a submission — the key's scan quota is
+    /// used up, so the caller should stop submitting for the rest of the run.
+    pub quota_exhausted: bool,
+    /// Submissions and polls that failed. Each only costs its own scan; the
+    /// links from the others are still in `links`.
+    pub errors: Vec<anyhow::Error>,
+}
+
+/// urlscan asks clients to wait ~10s before the first result poll; scans
+/// usually finish within 10-30s.
+const SCAN_INITIAL_WAIT: std::time::Duration = std::time::Duration::from_secs(10);
+const SCAN_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(2);
+/// Give up on a scan after this many polls (~2 minutes with the interval above).
+const SCAN_MAX_POLLS: usize = 60;
+
+/// Default `--urlscan-submit-max`: scans submitted per domain, root included.
+pub const DEFAULT_URLSCAN_SUBMIT_MAX: usize = 5;
+
+/// Extensions never worth a live scan — the rendered DOM has no links.
+const STATIC_EXTENSIONS: &[&str] = &[
+    "js", "css", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "woff", "woff2", "ttf", "eot",
+    "pdf", "zip", "gz", "mp4", "mp3",
+];
+
+/// Pick what to submit for `domain`: the site root first, then discovered
+/// pages on the same host, shallowest paths first, one per distinct path and
+/// skipping static assets. Returns at most `max` URLs. A `host:port` domain
+/// keeps the port on the root and only selects pages served on it.
+pub fn select_scan_targets(domain: &str, discovered: &[String], max: usize) -> Vec<String> {
+    if max == 0 {
+        return Vec::new();
+    }
+    let root = format!("https://{domain}/");
+    let (host, port) = split_host_port(domain);
+    let mut seen_paths = std::collections::HashSet::from(["/".to_string()]);
+    let mut candidates: Vec<(usize, &String)> = Vec::new();
+    for url in discovered {
+        let Ok(parsed) = url::Url::parse(url) else {
+            continue;
+        };
+        if !matches!(parsed.scheme(), "http" | "https")
+            || parsed.host_str() != Some(host)
+            || (port.is_some() && parsed.port_or_known_default() != port)
+        {
+            continue;
+        }
+        let path = parsed.path();
+        let is_static = path
+            .rsplit('/')
+            .next()
+            .and_then(|last| last.rsplit_once('.'))
+            .is_some_and(|(_, ext)| STATIC_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()));
+        if is_static || !seen_paths.insert(path.to_string()) {
+            continue;
+        }
+        let depth = path.split('/').filter(|s| !s.is_empty()).count();
+        candidates.push((depth, url));
+    }
+    candidates.sort();
+
+    let mut targets = vec![root];
+    targets.extend(candidates.into_iter().map(|(_, u)| u.clone()));
+    targets.truncate(max);
+    targets
+}
 
 /// Turn a result's `sort` array into the `search_after` cursor urlscan expects:
 /// the array values rendered as a comma-separated string. Returns `None` when
@@ -95,17 +233,55 @@ impl UrlscanProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
+            quota: None,
+            max_results: DEFAULT_URLSCAN_MAX_RESULTS,
+            from: None,
+            to: None,
+            status_filter: StatusFilter::default(),
+            scan_initial_wait: SCAN_INITIAL_WAIT,
+            scan_poll_interval: SCAN_POLL_INTERVAL,
             #[cfg(test)]
             base_url: "https://urlscan.io".to_string(),
         }
     }
 
+    /// Stop paginating once this many results have been collected. `0`
+    /// removes the cap (the page ceiling still applies).
+    pub fn with_max_results(&mut self, max_results: usize) -> &mut Self {
+        self.max_results = max_results;
+        self
+    }
+
+    /// Only search scans made between `from` and `to` (14-digit CDX
+    /// timestamps, either end open), via a `date:[from TO to]` clause.
+    pub fn with_date_range(&mut self, from: Option<String>, to: Option<String>) -> &mut Self {
+        let iso_date = |ts: String| format!("{}-{}-{}", &ts[0..4], &ts[4..6], &ts[6..8]);
+        self.from = from.map(iso_date);
+        self.to = to.map(iso_date);
+        self
+    }
+
+    /// Drop results whose recorded page status doesn't match the filter
+    /// (`--provider-status-filter`). Results without one are kept.
+    pub fn with_status_filter(&mut self, filter: StatusFilter) -> &mut Self {
+        self.status_filter = filter;
+        self
+    }
+
     #[cfg(test)]
     pub fn with_base_url(&mut self, url: String) -> &mut Self {
         self.base_url = url;
         self
     }
 
+    #[cfg(test)]
+    fn with_scan_timing(&mut self, wait: std::time::Duration) -> &mut Self {
+        self.scan_initial_wait = wait;
+        self.scan_poll_interval = wait;
+        self
+    }
+
     fn client_config(&self) -> HttpClientConfig {
         HttpClientConfig {
             timeout: self.timeout,
@@ -113,6 +289,7 @@ impl UrlscanProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -126,10 +303,11 @@ impl UrlscanProvider {
     ) -> Result<UrlscanResponse> {
         let mut last_error = None;
         let mut attempt = 0;
+        let mut server_delay = None;
 
         while attempt <= self.retries {
             if attempt > 0 {
-                tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+                wait_before_retry(attempt, server_delay.take()).await;
             }
 
             // Rotate the key per attempt so a rate-limited key is retried with a
@@ -147,18 +325,23 @@ impl UrlscanProvider {
                 Ok(response) => {
                     let status = response.status();
                     if !status.is_success() {
-                        if status.as_u16() == 429 {
-                            if let Some(d) =
-                                crate::network::client::retry_after_delay(response.headers())
-                            {
-                                tokio::time::sleep(d).await;
-                            }
+                        let retry_after = crate::network::retry_after_delay(response.headers());
+                        // A throttled or rejected key sits out; retry at once
+                        // with another usable key, if there is one.
+                        if self.api_key_rotator.report_status(
+                            &api_key,
+                            status.as_u16(),
+                            retry_after,
+                        ) {
+                            last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
+                            continue;
                         }
+                        server_delay = server_retry_delay(status, response.headers());
                         attempt += 1;
                         last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
                         continue;
                     }
-                    match response.json::<UrlscanResponse>().await {
+                    match read_json::<UrlscanResponse>(response).await {
                         Ok(parsed) => return Ok(parsed),
                         Err(e) => {
                             attempt += 1;
@@ -184,6 +367,199 @@ impl UrlscanProvider {
     }
 }
 
+impl UrlscanProvider {
+    fn api_base(&self) -> &str {
+        #[cfg(test)]
+        {
+            self.base_url.as_str()
+        }
+        #[cfg(not(test))]
+        {
+            "https://urlscan.io"
+        }
+    }
+
+    /// Search query for `domain`, without the `size` / `search_after` params.
+    /// The `domain:` field is host-only; a `host:port` target searches the
+    /// host and leaves the port to host validation.
+    fn search_base(&self, domain: &str) -> String {
+        let (host, _) = split_host_port(domain);
+        let mut query = host.to_string();
+        if self.from.is_some() || self.to.is_some() {
+            query.push_str(&format!(
+                " AND date:[{} TO {}]",
+                self.from.as_deref().unwrap_or("*"),
+                self.to.as_deref().unwrap_or("*")
+            ));
+        }
+        let encoded_query =
+            url::form_urlencoded::byte_serialize(query.as_bytes()).collect::<String>();
+        format!(
+            "{}/api/v1/search/?q=domain:{encoded_query}",
+            self.api_base()
+        )
+    }
+
+    /// Submit `targets` for fresh scans, wait for them to finish, and return
+    /// the links found in each rendered page. Requires an API key. A 429 on
+    /// submission stops further submissions and is reported through
+    /// [`ScanHarvest::quota_exhausted`]; scans that never finish are skipped,
+    /// and a failed submission or poll is recorded in [`ScanHarvest::errors`]
+    /// without losing the other scans. Errors only when no API key is set.
+    pub async fn submit_and_harvest(&self, targets: &[String]) -> Result<ScanHarvest> {
+        if !self.api_key_rotator.has_keys() {
+            return Err(anyhow::anyhow!(
+                "urlscan scan submission requires an API key"
+            ));
+        }
+        let client = self.client_config().build_client()?;
+        let limiter = self.rate_limit.as_ref();
+        let mut harvest = ScanHarvest::default();
+
+        let mut pending = Vec::new();
+        'targets: for target in targets {
+            // Retries the same target only when a throttled key can be swapped
+            // for another usable one.
+            loop {
+                if self.cancel.is_cancelled() {
+                    break 'targets;
+                }
+                let api_key = self.api_key_rotator.next_key().unwrap_or_default();
+                let sent = self
+                    .cancel
+                    .run_until_cancelled(async {
+                        if let Some(rl) = limiter {
+                            rl.acquire().await;
+                        }
+                        client
+                            .post(format!("{}/api/v1/scan/", self.api_base()))
+                            .header("API-Key", &api_key)
+                            .json(&ScanRequest {
+                                url: target,
+                                visibility: "unlisted",
+                            })
+                            .send()
+                            .await
+                    })
+                    .await;
+                let Some(sent) = sent else {
+                    break 'targets;
+                };
+                let response = match sent {
+                    Ok(response) => response,
+                    Err(e) => {
+                        harvest
+                            .errors
+                            .push(anyhow::anyhow!("submitting {target}: {}", e.without_url()));
+                        break;
+                    }
+                };
+                match response.status().as_u16() {
+                    200 => match read_json::<ScanSubmission>(response).await {
+                        Ok(submission) => pending.push(submission.uuid),
+                        Err(e) => harvest
+                            .errors
+                            .push(anyhow::anyhow!("submitting {target}: {e}")),
+                    },
+                    429 => {
+                        let retry_after = crate::network::retry_after_delay(response.headers());
+                        if self
+                            .api_key_rotator
+                            .mark_rate_limited(&api_key, retry_after)
+                        {
+                            continue;
+                        }
+                        harvest.quota_exhausted = true;
+                        break 'targets;
+                    }
+                    // 400 covers blocked/unresolvable targets; skip and move on.
+                    _ => {}
+                }
+                break;
+            }
+        }
+        harvest.submitted = pending.len();
+        if pending.is_empty() {
+            return Ok(harvest);
+        }
+
+        let wait = self.scan_initial_wait;
+        if self
+            .cancel
+            .run_until_cancelled(tokio::time::sleep(wait))
+            .await
+            .is_none()
+        {
+            return Ok(harvest);
+        }
+        for uuid in pending {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+            match self.poll_scan(&client, &uuid).await {
+                Ok(Some(result)) => harvest.links.extend(
+                    result
+                        .data
+                        .links
+                        .into_iter()
+                        .map(|l| l.href)
+                        .filter(|h| h.starts_with("http")),
+                ),
+                Ok(None) => {}
+                Err(e) => harvest.errors.push(e),
+            }
+        }
+        harvest.links.sort();
+        harvest.links.dedup();
+        Ok(harvest)
+    }
+
+    /// Poll a submitted scan until its result is published (404 means still
+    /// running). `None` when it doesn't finish within the poll budget or the
+    /// run is cancelled.
+    async fn poll_scan(&self, client: &reqwest::Client, uuid: &str) -> Result<Option<ScanResult>> {
+        let url = format!("{}/api/v1/result/{uuid}/", self.api_base());
+        for _ in 0..SCAN_MAX_POLLS {
+            let sent = self
+                .cancel
+                .run_until_cancelled(async {
+                    if let Some(rl) = self.rate_limit.as_ref() {
+                        rl.acquire().await;
+                    }
+                    client.get(&url).send().await
+                })
+                .await;
+            let Some(sent) = sent else {
+                return Ok(None);
+            };
+            let response =
+                sent.map_err(|e| anyhow::anyhow!("polling scan {uuid}: {}", e.without_url()))?;
+            match response.status().as_u16() {
+                200 => {
+                    return read_json(response)
+                        .await
+                        .map(Some)
+                        .map_err(|e| anyhow::anyhow!("polling scan {uuid}: {e}"))
+                }
+                404 => {}
+                // 410: the scan was deleted or failed outright.
+                410 => return Ok(None),
+                status => return Err(anyhow::anyhow!("HTTP error polling scan {uuid}: {status}")),
+            }
+            let interval = self.scan_poll_interval;
+            if self
+                .cancel
+                .run_until_cancelled(tokio::time::sleep(interval))
+                .await
+                .is_none()
+            {
+                return Ok(None);
+            }
+        }
+        Ok(None)
+    }
+}
+
 impl Provider for UrlscanProvider {
     fn clone_box(&self) -> Box<dyn Provider> {
         Box::new(self.clone())
@@ -193,6 +569,17 @@ impl Provider for UrlscanProvider {
         &'a self,
         domain: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, None).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<crate::progress::ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
         Box::pin(async move {
             // urlscan.io's public search allows unauthenticated queries
             // (rate-limited to ~30 req/min per IP), so we always query. When no
@@ -200,20 +587,9 @@ impl Provider for UrlscanProvider {
             // (see `fetch_page`); a key only raises the limits and enables
             // rotation.
 
-            // Use the url crate for encoding the domain
-            let encoded_domain =
-                url::form_urlencoded::byte_serialize(domain.as_bytes()).collect::<String>();
-
-            // Construct the base query - use base_url in test mode
-            #[cfg(test)]
-            let base_query = format!(
-                "{}/api/v1/search/?q=domain:{}&size=100",
-                self.base_url, encoded_domain
-            );
-
-            #[cfg(not(test))]
-            let base_query =
-                format!("https://urlscan.io/api/v1/search/?q=domain:{encoded_domain}&size=100");
+            // The `domain:` field is host-only; a `host:port` target searches
+            // the host and leaves the port to host validation.
+            let base_query = self.search_base(domain);
 
             let client = self.client_config().build_client()?;
             let limiter = self.rate_limit.as_ref();
@@ -232,19 +608,47 @@ impl Provider for UrlscanProvider {
                     break;
                 }
 
-                let url = match &search_after {
-                    Some(cursor) => format!("{base_query}&search_after={cursor}"),
-                    None => base_query.clone(),
+                // Shrink the last request to what the cap still allows so we
+                // don't spend quota on results we'd throw away.
+                let size = if self.max_results == 0 {
+                    URLSCAN_PAGE_SIZE
+                } else {
+                    let remaining = self.max_results.saturating_sub(all_urls.len());
+                    if remaining == 0 {
+                        break;
+                    }
+                    remaining.min(URLSCAN_PAGE_SIZE)
                 };
 
-                let response = match self.fetch_page(&client, &url, limiter).await {
+                let mut url = format!("{base_query}&size={size}");
+                if let Some(cursor) = &search_after {
+                    let encoded_cursor: String =
+                        url::form_urlencoded::byte_serialize(cursor.as_bytes()).collect();
+                    url.push_str("&search_after=");
+                    url.push_str(&encoded_cursor);
+                }
+
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    self.fetch_page(&client, &url, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                let response = match fetched {
                     Ok(resp) => resp,
                     Err(e) => {
                         // A failure on the very first page is fatal; a later
-                        // failure keeps the pages already collected.
+                        // failure keeps the pages already collected, flagged
+                        // partial.
                         if all_urls.is_empty() {
                             return Err(e);
                         }
+                        if let Some(r) = &reporter {
+                            r.mark_partial();
+                        }
                         break;
                     }
                 };
@@ -261,7 +665,15 @@ impl Provider for UrlscanProvider {
 
                 let more = response.has_more;
                 for result in response.results {
-                    all_urls.push(result.page.url);
+                    let page = result.page;
+                    if !self.status_filter.allows(page.status.parse().ok()) {
+                        continue;
+                    }
+                    all_urls.push(UrlRecord::new(page.url).with_snapshot(
+                        Some(&result.task.time),
+                        Some(&page.status),
+                        Some(&page.mime_type),
+                    ));
                 }
 
                 if !more {
@@ -280,6 +692,27 @@ impl Provider for UrlscanProvider {
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        let cap = if self.max_results == 0 {
+            format!("at most {URLSCAN_MAX_PAGES} pages")
+        } else {
+            format!("up to {} results", self.max_results)
+        };
+        let size = match self.max_results {
+            0 => URLSCAN_PAGE_SIZE,
+            max => max.min(URLSCAN_PAGE_SIZE),
+        };
+        let key = if self.api_key_rotator.has_keys() {
+            " (API-Key: ***)"
+        } else {
+            " (anonymous)"
+        };
+        vec![
+            format!("GET {}&size={size}{key}", self.search_base(domain)),
+            format!("  then follow &search_after= while has_more ({cap})"),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -311,6 +744,23 @@ impl Provider for UrlscanProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_quota(&mut self, meter: QuotaMeter) {
+        self.rate_limit = Some(RateLimiter::metered(self.rate_limit.take(), meter.clone()));
+        self.quota = Some(meter);
+    }
+
+    fn quota(&self) -> Option<&QuotaMeter> {
+        self.quota.as_ref()
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -665,4 +1115,262 @@ mod tests {
         assert_eq!(urls[0], "https://example.com/page1");
         assert_eq!(urls[1], "https://example.com/page2");
     }
+
+    #[test]
+    fn test_date_range_adds_a_date_clause() {
+        let mut provider = UrlscanProvider::new(String::new());
+        provider.with_date_range(Some("20200101000000".to_string()), None);
+        assert_eq!(
+            provider.search_base("example.com"),
+            "https://urlscan.io/api/v1/search/?q=domain:example.com+AND+date%3A%5B2020-01-01+TO+*%5D"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_stops_at_max_results() {
+        let mut server = mockito::Server::new_async().await;
+
+        // Page one reports more results, but the cap is already reached, so no
+        // second request may be made.
+        let page1 = server
+            .mock("GET", "/api/v1/search/")
+            .match_query(mockito::Matcher::Regex("size=2$".into()))
+            .with_status(200)
+            .with_body(
+                r#"{"has_more":true,"results":[
+                    {"page":{"domain":"example.com","url":"https://example.com/a"},"sort":[1,"a"]},
+                    {"page":{"domain":"example.com","url":"https://example.com/b"},"sort":[2,"b"]}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let page2 = server
+            .mock("GET", "/api/v1/search/")
+            .match_query(mockito::Matcher::Regex("search_after".into()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut provider = UrlscanProvider::new("k".to_string());
+        provider.with_base_url(server.url()).with_max_results(2);
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls.len(), 2);
+        page1.assert();
+        page2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_applies_status_filter() {
+        let mut server = mockito::Server::new_async().await;
+        let _search = server
+            .mock("GET", "/api/v1/search/")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{"has_more":false,"results":[
+                    {"page":{"domain":"example.com","url":"https://example.com/ok","status":"200"}},
+                    {"page":{"domain":"example.com","url":"https://example.com/moved","status":"301"}},
+                    {"page":{"domain":"example.com","url":"https://example.com/gone","status":"404"}},
+                    {"page":{"domain":"example.com","url":"https://example.com/unknown"}}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut provider = UrlscanProvider::new("k".to_string());
+        provider
+            .with_base_url(server.url())
+            .with_status_filter(StatusFilter::new(&["200".to_string(), "3xx".to_string()]));
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/ok",
+                "https://example.com/moved",
+                "https://example.com/unknown"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_scan_targets_prefers_root_and_shallow_pages() {
+        let discovered: Vec<String> = [
+            "https://example.com/a/b/c",
+            "https://example.com/login?next=/",
+            "https://example.com/login?next=/home",
+            "https://example.com/static/app.js",
+            "https://other.com/page",
+            "https://example.com/docs/",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        assert_eq!(
+            select_scan_targets("example.com", &discovered, 3),
+            vec![
+                "https://example.com/",
+                "https://example.com/docs/",
+                "https://example.com/login?next=/",
+            ]
+        );
+        assert!(select_scan_targets("example.com", &discovered, 0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_harvest_polls_until_ready() {
+        let mut server = mockito::Server::new_async().await;
+        let submit = server
+            .mock("POST", "/api/v1/scan/")
+            .match_header("API-Key", "k")
+            .with_status(200)
+            .with_body(r#"{"uuid":"abc","api":"ignored"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        // First poll: still running. Second: done.
+        let pending = server
+            .mock("GET", "/api/v1/result/abc/")
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider = UrlscanProvider::new("k".to_string());
+        provider
+            .with_base_url(server.url())
+            .with_scan_timing(std::time::Duration::from_millis(10));
+
+        let poll = {
+            let provider = provider.clone();
+            tokio::spawn(async move {
+                provider
+                    .submit_and_harvest(&["https://example.com/".to_string()])
+                    .await
+            })
+        };
+        // Let the 404 be served before the finished result is registered.
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        pending.remove_async().await;
+        let _done = server
+            .mock("GET", "/api/v1/result/abc/")
+            .with_status(200)
+            .with_body(
+                r#"{"data":{"links":[
+                    {"href":"https://example.com/hidden","text":"x"},
+                    {"href":"javascript:void(0)","text":"y"}
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+
+        let harvest = poll.await.unwrap().unwrap();
+        assert_eq!(harvest.submitted, 1);
+        assert!(!harvest.quota_exhausted);
+        assert_eq!(harvest.links, vec!["https://example.com/hidden"]);
+        submit.assert();
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_harvest_stops_on_quota() {
+        let mut server = mockito::Server::new_async().await;
+        let submit = server
+            .mock("POST", "/api/v1/scan/")
+            .with_status(429)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider = UrlscanProvider::new("k".to_string());
+        provider.with_base_url(server.url());
+
+        let targets = vec![
+            "https://example.com/".to_string(),
+            "https://example.com/a".to_string(),
+        ];
+        let harvest = provider.submit_and_harvest(&targets).await.unwrap();
+        assert!(harvest.quota_exhausted);
+        assert_eq!(harvest.submitted, 0);
+        submit.assert();
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_harvest_keeps_links_when_a_scan_fails() {
+        let mut server = mockito::Server::new_async().await;
+        let _submit_ok = server
+            .mock("POST", "/api/v1/scan/")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"url":"https://example.com/"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"uuid":"good"}"#)
+            .create_async()
+            .await;
+        let _submit_bad = server
+            .mock("POST", "/api/v1/scan/")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"url":"https://example.com/a"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"uuid":"bad"}"#)
+            .create_async()
+            .await;
+        let _done = server
+            .mock("GET", "/api/v1/result/good/")
+            .with_status(200)
+            .with_body(r#"{"data":{"links":[{"href":"https://example.com/hidden"}]}}"#)
+            .create_async()
+            .await;
+        let _broken = server
+            .mock("GET", "/api/v1/result/bad/")
+            .with_status(500)
+            .create_async()
+            .await;
+        let _submit_garbled = server
+            .mock("POST", "/api/v1/scan/")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"url":"https://example.com/b"}"#.to_string(),
+            ))
+            .with_status(200)
+            .with_body(r#"{"uuid":"garbled"}"#)
+            .create_async()
+            .await;
+        let _garbled = server
+            .mock("GET", "/api/v1/result/garbled/")
+            .with_status(200)
+            .with_body("<html>not json</html>")
+            .create_async()
+            .await;
+
+        let mut provider = UrlscanProvider::new("k".to_string());
+        provider
+            .with_base_url(server.url())
+            .with_scan_timing(std::time::Duration::from_millis(10));
+
+        let targets = vec![
+            "https://example.com/".to_string(),
+            "https://example.com/a".to_string(),
+            "https://example.com/b".to_string(),
+        ];
+        let harvest = provider.submit_and_harvest(&targets).await.unwrap();
+        assert_eq!(harvest.submitted, 3);
+        assert_eq!(harvest.links, vec!["https://example.com/hidden"]);
+        let mut errors: Vec<String> = harvest.errors.iter().map(|e| e.to_string()).collect();
+        errors.sort();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("bad"));
+        assert!(errors[1].contains("polling scan garbled"));
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_harvest_requires_key() {
+        let provider = UrlscanProvider::new_with_keys(vec![]);
+        assert!(provider
+            .submit_and_harvest(&["https://example.com/".to_string()])
+            .await
+            .is_err());
+    }
 }
@@ -2,12 +2,30 @@ use anyhow::Result;
 use serde::Deserialize;
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
 use super::ApiKeyRotator;
-use super::Provider;
+use super::{Provider, UrlRecord};
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{
+    read_json, server_retry_delay, wait_before_retry, QuotaMeter, RateLimiter, RequestGate,
+};
 use crate::progress::ProgressReporter;
+use crate::utils::split_host_port;
+
+/// Final HTTP status of a failed v3 request, kept typed (under the
+/// human-readable context) so the caller can tell a rejected request, which is
+/// worth retrying on v2, from a transient failure, which is not.
+#[derive(Debug)]
+struct VtHttpStatus(reqwest::StatusCode);
+
+impl std::fmt::Display for VtHttpStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP error: {}", self.0)
+    }
+}
+
+impl std::error::Error for VtHttpStatus {}
 
 /// Page size for the v3 `urls` relationship. VirusTotal caps this relationship
 /// endpoint at 40 per page; larger values are silently clamped server-side.
@@ -28,6 +46,8 @@ pub struct VirusTotalProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
+    quota: Option<QuotaMeter>,
     #[cfg(test)]
     base_url: String,
 }
@@ -38,8 +58,7 @@ pub struct VirusTotalProvider {
 /// `links.next` absolute URL: `links.next` is server-controlled, and following
 /// it would send the `x-apikey` header to whatever host it names — a credential
 /// leak under a malicious/MITM'd response. Rebuilding the request from the
-/// trusted base URL + cursor removes that trust entirely. `Default` lets a 404
-/// ("no such domain") resolve to an empty page rather than an error.
+/// trusted base URL + cursor removes that trust entirely.
 #[derive(Debug, Deserialize, Default)]
 struct VtUrlsResponse {
     #[serde(default)]
@@ -62,6 +81,67 @@ struct VtUrlObject {
 #[derive(Debug, Deserialize)]
 struct VtUrlAttributes {
     url: String,
+    /// Verdict counts from the most recent scan (`harmless`, `malicious`, …).
+    #[serde(default)]
+    last_analysis_stats: Option<serde_json::Value>,
+    #[serde(default)]
+    last_http_response_code: Option<u16>,
+}
+
+impl VtUrlAttributes {
+    fn into_record(self) -> UrlRecord {
+        let mut record = UrlRecord::new(self.url);
+        if let Some(stats) = self.last_analysis_stats {
+            record = record.with_meta("last_analysis_stats", stats);
+        }
+        if let Some(code) = self.last_http_response_code {
+            record = record.with_meta("last_http_response_code", code);
+        }
+        record
+    }
+}
+
+/// The legacy v2 `domain/report` response. Only used as a fallback when the v3
+/// endpoint rejects the first request (e.g. a key that predates v3 access); it
+/// returns a single server-capped slice with no pagination.
+#[derive(Debug, Deserialize, Default)]
+struct VtV2Report {
+    #[serde(default)]
+    detected_urls: Vec<VtV2DetectedUrl>,
+    /// Rows of `[url, sha256, positives, total, scan_date]`.
+    #[serde(default)]
+    undetected_urls: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VtV2DetectedUrl {
+    url: String,
+    #[serde(default)]
+    positives: Option<u32>,
+    #[serde(default)]
+    total: Option<u32>,
+}
+
+impl VtV2Report {
+    fn into_records(self) -> Vec<UrlRecord> {
+        let mut records = Vec::new();
+        for detected in self.detected_urls {
+            let mut record = UrlRecord::new(detected.url);
+            if let Some(positives) = detected.positives {
+                record = record.with_meta("positives", positives);
+            }
+            if let Some(total) = detected.total {
+                record = record.with_meta("total", total);
+            }
+            records.push(record);
+        }
+        for row in self.undetected_urls {
+            if let Some(url) = row.first().and_then(|v| v.as_str()) {
+                records.push(UrlRecord::new(url.to_string()));
+            }
+        }
+        records
+    }
 }
 
 impl VirusTotalProvider {
@@ -88,6 +168,8 @@ impl VirusTotalProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
+            quota: None,
             #[cfg(test)]
             base_url: "https://www.virustotal.com".to_string(),
         }
@@ -106,6 +188,7 @@ impl VirusTotalProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -114,6 +197,9 @@ impl VirusTotalProvider {
     /// from our own base host (never a server-supplied URL), so the API key is
     /// only ever sent to VirusTotal.
     fn page_url(&self, domain: &str, cursor: Option<&str>) -> String {
+        // VirusTotal domain reports are host-only; host validation applies any
+        // port scope
+        let (domain, _) = split_host_port(domain);
         let encoded = url::form_urlencoded::byte_serialize(domain.as_bytes()).collect::<String>();
         let mut url = {
             #[cfg(test)]
@@ -141,10 +227,54 @@ impl VirusTotalProvider {
         url
     }
 
+    /// Build the legacy v2 `domain/report` URL. v2 carries the key as a query
+    /// parameter, so errors from this request must never surface the URL.
+    fn v2_report_url(&self, domain: &str, api_key: &str) -> String {
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("apikey", api_key)
+            .append_pair("domain", split_host_port(domain).0)
+            .finish();
+        #[cfg(test)]
+        {
+            format!("{}/vtapi/v2/domain/report?{query}", self.base_url)
+        }
+        #[cfg(not(test))]
+        {
+            format!("https://www.virustotal.com/vtapi/v2/domain/report?{query}")
+        }
+    }
+
+    /// Single-shot v2 fallback. No retries of its own: it only runs after the
+    /// v3 request already exhausted them, so the key is likely just unusable.
+    async fn fetch_v2_records(
+        &self,
+        client: &reqwest::Client,
+        domain: &str,
+        limiter: Option<&RateLimiter>,
+    ) -> Result<Vec<UrlRecord>> {
+        let api_key = self.api_key_rotator.next_key().unwrap_or_default();
+        if let Some(rl) = limiter {
+            rl.acquire().await;
+        }
+        let response = client
+            .get(self.v2_report_url(domain, &api_key))
+            .send()
+            .await
+            .map_err(|e| anyhow::anyhow!(e.without_url()))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(anyhow::anyhow!("HTTP error: {status}"));
+        }
+        let report: VtV2Report = read_json(response)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to parse VirusTotal v2 response: {e}"))?;
+        Ok(report.into_records())
+    }
+
     /// Fetch and parse a single page with retry/back-off and key rotation.
     ///
-    /// A 404 (the domain has no VT object) resolves to an empty page rather
-    /// than an error, matching the "no data" semantics of the other providers.
+    /// A 404 (the domain has no VT object) fails immediately without retrying;
+    /// HTTP failures carry a [`VtHttpStatus`] the caller can downcast to.
     async fn fetch_page(
         &self,
         client: &reqwest::Client,
@@ -152,11 +282,13 @@ impl VirusTotalProvider {
         limiter: Option<&RateLimiter>,
     ) -> Result<VtUrlsResponse> {
         let mut last_error = None;
+        let mut last_status = None;
         let mut attempt = 0;
+        let mut server_delay = None;
 
         while attempt <= self.retries {
             if attempt > 0 {
-                tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64)).await;
+                wait_before_retry(attempt, server_delay.take()).await;
             }
 
             // Rotate the key per attempt so a throttled/invalid key is retried
@@ -174,29 +306,38 @@ impl VirusTotalProvider {
             match req.send().await {
                 Ok(response) => {
                     let status = response.status();
-                    // 404 => no VT object for this domain; not an error.
+                    // 404 => no VT object for this domain; retrying won't help.
                     if status.as_u16() == 404 {
-                        return Ok(VtUrlsResponse::default());
+                        return Err(anyhow::Error::new(VtHttpStatus(status)));
                     }
                     if !status.is_success() {
-                        // On a throttle, wait as long as the server asked.
-                        if status.as_u16() == 429 {
-                            if let Some(d) =
-                                crate::network::client::retry_after_delay(response.headers())
-                            {
-                                tokio::time::sleep(d).await;
-                            }
+                        let retry_after = crate::network::retry_after_delay(response.headers());
+                        // A throttled (429) or rejected (401/403) key sits out;
+                        // when another key is usable, retry with it right away
+                        // without spending an attempt.
+                        if self.api_key_rotator.report_status(
+                            &api_key,
+                            status.as_u16(),
+                            retry_after,
+                        ) {
+                            last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
+                            last_status = Some(status);
+                            continue;
                         }
+                        // On a throttle, wait as long as the server asked.
+                        server_delay = server_retry_delay(status, response.headers());
                         attempt += 1;
                         last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
+                        last_status = Some(status);
                         continue;
                     }
-                    match response.json::<VtUrlsResponse>().await {
+                    match read_json::<VtUrlsResponse>(response).await {
                         Ok(parsed) => return Ok(parsed),
                         Err(e) => {
                             attempt += 1;
                             last_error =
                                 Some(anyhow::anyhow!("Failed to parse VirusTotal response: {e}"));
+                            last_status = None;
                             continue;
                         }
                     }
@@ -206,16 +347,21 @@ impl VirusTotalProvider {
                     // Defensive hygiene: keep the request URL out of surfaced
                     // transport errors (the key is a header, not in the URL).
                     last_error = Some(e.without_url().into());
+                    last_status = None;
                     continue;
                 }
             }
         }
 
-        Err(anyhow::anyhow!(
+        let message = format!(
             "Failed after {} attempts: {}",
             self.retries + 1,
             last_error.unwrap_or_else(|| anyhow::anyhow!("unknown error"))
-        ))
+        );
+        match last_status {
+            Some(status) => Err(anyhow::Error::new(VtHttpStatus(status)).context(message)),
+            None => Err(anyhow::anyhow!(message)),
+        }
     }
 }
 
@@ -236,6 +382,17 @@ impl Provider for VirusTotalProvider {
         domain: &'a str,
         reporter: Option<ProgressReporter>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, reporter).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
         Box::pin(async move {
             // Skip if no API keys are provided.
             if !self.api_key_rotator.has_keys() {
@@ -254,8 +411,8 @@ impl Provider for VirusTotalProvider {
             // each request from the trusted base + cursor (never the server's
             // `links.next` URL). The deprecated v2 `domain/report` returned a
             // single server-capped, non-paginated slice that silently truncated
-            // large domains.
-            let mut urls = Vec::new();
+            // large domains, so it is only used as a fallback below.
+            let mut records: Vec<UrlRecord> = Vec::new();
             let mut cursor: Option<String> = None;
             let mut pages = 0usize;
 
@@ -270,15 +427,45 @@ impl Provider for VirusTotalProvider {
                 let first_page = pages == 1;
                 let url = self.page_url(domain, cursor.as_deref());
 
-                let page = match self.fetch_page(&client, &url, limiter).await {
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    self.fetch_page(&client, &url, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                let page = match fetched {
                     Ok(page) => page,
                     Err(e) => {
-                        // A failure on the very first request is fatal; any
-                        // later failure keeps what we have and flags the result
-                        // partial rather than presenting a truncated crawl as a
-                        // clean success.
+                        let status = e.downcast_ref::<VtHttpStatus>().map(|s| s.0.as_u16());
+                        // Only a v3 request the server *rejected* (no v3
+                        // access for the key, or no v3 object) falls back to
+                        // v2; network errors, 5xx and 429 would fail there
+                        // too, so they surface as-is. If v2 fails as well,
+                        // report the original v3 error — except a 404 on
+                        // both, which just means VT has no data.
                         if first_page {
-                            return Err(e);
+                            if !matches!(status, Some(401 | 403 | 404)) {
+                                return Err(e);
+                            }
+                            if let Some(r) = &reporter {
+                                r.detail("v3 rejected, trying v2…");
+                            }
+                            match self.fetch_v2_records(&client, domain, limiter).await {
+                                Ok(v2) => records = v2,
+                                Err(_) if status == Some(404) => {}
+                                Err(_) => return Err(e),
+                            }
+                            break;
+                        }
+                        // Any later failure keeps what we have and flags the
+                        // result partial rather than presenting a truncated
+                        // crawl as a clean success; a 404 just means the cursor
+                        // ran past the end.
+                        if status == Some(404) {
+                            break;
                         }
                         if let Some(r) = &reporter {
                             r.mark_partial();
@@ -287,11 +474,13 @@ impl Provider for VirusTotalProvider {
                     }
                 };
 
-                for obj in page.data {
-                    urls.push(obj.attributes.url);
-                }
+                records.extend(
+                    page.data
+                        .into_iter()
+                        .map(|obj| obj.attributes.into_record()),
+                );
                 if let Some(r) = &reporter {
-                    r.detail(format!("{} URLs…", urls.len()));
+                    r.detail(format!("{} URLs…", records.len()));
                 }
 
                 match page.meta.cursor {
@@ -300,12 +489,25 @@ impl Provider for VirusTotalProvider {
                 }
             }
 
-            urls.sort();
-            urls.dedup();
-            Ok(urls)
+            records.sort_by(|a, b| a.url.cmp(&b.url));
+            records.dedup_by(|a, b| a.url == b.url);
+            Ok(records)
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!("GET {} (x-apikey: ***)", self.page_url(domain, None)),
+            format!(
+                "  then follow the response cursor via &cursor= (at most {VT_MAX_PAGES} pages)"
+            ),
+            format!(
+                "  on failure: GET {} (v2 fallback)",
+                self.v2_report_url(domain, "***")
+            ),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -337,6 +539,23 @@ impl Provider for VirusTotalProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_quota(&mut self, meter: QuotaMeter) {
+        self.rate_limit = Some(RateLimiter::metered(self.rate_limit.take(), meter.clone()));
+        self.quota = Some(meter);
+    }
+
+    fn quota(&self) -> Option<&QuotaMeter> {
+        self.quota.as_ref()
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -737,6 +956,45 @@ mod tests {
         assert!(reporter.is_partial());
     }
 
+    #[tokio::test]
+    async fn test_rate_limited_key_retries_with_next_key() {
+        let mut server = mockito::Server::new_async().await;
+
+        let throttled = server
+            .mock("GET", "/api/v3/domains/example.com/urls")
+            .match_query(mockito::Matcher::Any)
+            .match_header("x-apikey", "key1")
+            .with_status(429)
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/api/v3/domains/example.com/urls")
+            .match_query(mockito::Matcher::Any)
+            .match_header("x-apikey", "key2")
+            .with_status(200)
+            .with_body(r#"{"data": [{"attributes": {"url": "https://example.com/a"}}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider =
+            VirusTotalProvider::new_with_keys(vec!["key1".to_string(), "key2".to_string()]);
+        provider.with_base_url(server.url());
+        // No retry budget: the swap to key2 must not consume an attempt
+        provider.with_retries(0);
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["https://example.com/a".to_string()]);
+        // key1 now sits out its cooldown
+        assert_eq!(
+            provider.api_key_rotator.next_key(),
+            Some("key2".to_string())
+        );
+        throttled.assert_async().await;
+        ok.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_fetch_urls_404_returns_empty() {
         // A domain with no VT object answers 404; treat it as "no data", not an
@@ -757,4 +1015,118 @@ mod tests {
         let urls = provider.fetch_urls("example.com").await.unwrap();
         assert!(urls.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_fetch_records_carries_analysis_stats() {
+        let mut server = mockito::Server::new_async().await;
+        let _m = server
+            .mock("GET", "/api/v3/domains/example.com/urls")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "data": [
+                        {"attributes": {
+                            "url": "https://example.com/bad",
+                            "last_analysis_stats": {"harmless": 60, "malicious": 3},
+                            "last_http_response_code": 200
+                        }},
+                        {"attributes": {"url": "https://example.com/plain"}}
+                    ],
+                    "meta": {}
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let mut provider = VirusTotalProvider::new("test_api_key".to_string());
+        provider.with_base_url(server.url());
+
+        let records = provider
+            .fetch_records_with_progress("example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].url, "https://example.com/bad");
+        assert_eq!(
+            records[0].metadata["last_analysis_stats"]["malicious"],
+            serde_json::json!(3)
+        );
+        assert_eq!(
+            records[0].metadata["last_http_response_code"],
+            serde_json::json!(200)
+        );
+        assert!(records[1].metadata.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_falls_back_to_v2_when_v3_rejected() {
+        let mut server = mockito::Server::new_async().await;
+        let _v3 = server
+            .mock("GET", "/api/v3/domains/example.com/urls")
+            .match_query(mockito::Matcher::Any)
+            .with_status(403)
+            .create_async()
+            .await;
+        let v2 = server
+            .mock("GET", "/vtapi/v2/domain/report")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("apikey".into(), "test_api_key".into()),
+                mockito::Matcher::UrlEncoded("domain".into(), "example.com".into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{
+                    "detected_urls": [
+                        {"url": "https://example.com/x", "positives": 2, "total": 70}
+                    ],
+                    "undetected_urls": [
+                        ["https://example.com/y", "abc", 0, 70, "2024-01-01 00:00:00"]
+                    ]
+                }"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider = VirusTotalProvider::new("test_api_key".to_string());
+        provider.with_base_url(server.url());
+        provider.with_retries(0);
+
+        let records = provider
+            .fetch_records_with_progress("example.com", None)
+            .await
+            .unwrap();
+        let urls: Vec<&str> = records.iter().map(|r| r.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://example.com/x", "https://example.com/y"]);
+        assert_eq!(records[0].metadata["positives"], serde_json::json!(2));
+        v2.assert();
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_does_not_fall_back_on_server_error() {
+        // A 5xx is transient, not a v3 rejection: surface it instead of
+        // masking it with a v2 request that would fail the same way.
+        let mut server = mockito::Server::new_async().await;
+        let _v3 = server
+            .mock("GET", "/api/v3/domains/example.com/urls")
+            .match_query(mockito::Matcher::Any)
+            .with_status(500)
+            .create_async()
+            .await;
+        let v2 = server
+            .mock("GET", "/vtapi/v2/domain/report")
+            .match_query(mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut provider = VirusTotalProvider::new("test_api_key".to_string());
+        provider.with_base_url(server.url());
+        provider.with_retries(0);
+
+        let err = provider.fetch_urls("example.com").await.unwrap_err();
+        assert!(err.to_string().contains("500"), "unexpected error: {err}");
+        v2.assert();
+    }
 }
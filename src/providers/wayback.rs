@@ -1,10 +1,11 @@
 use anyhow::Result;
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
-use super::Provider;
+use super::{collect_pages, page_stream, Provider, RecordStream, UrlRecord};
 use crate::network::client::{get_with_retry, HttpClientConfig};
-use crate::network::RateLimiter;
+use crate::network::{RateLimiter, RequestGate};
 use crate::progress::ProgressReporter;
 
 /// How many rows to ask the CDX server for per request. A bounded `limit` is
@@ -61,6 +62,20 @@ fn split_page(text: &str) -> (Vec<String>, Option<String>) {
     (urls, resume_key)
 }
 
+/// Parse one CDX row (`fl=original,timestamp,statuscode,mimetype`) into a
+/// record. Fields are split from the right so the URL keeps any embedded
+/// spaces; a bare URL row (no extra fields) yields a record without metadata.
+fn parse_cdx_row(row: &str) -> UrlRecord {
+    let mut fields = row.rsplitn(4, ' ');
+    let (mime, status, timestamp) = (fields.next(), fields.next(), fields.next());
+    match (fields.next(), timestamp, status, mime) {
+        (Some(url), timestamp, status, mime) => {
+            UrlRecord::new(url.to_string()).with_snapshot(timestamp, status, mime)
+        }
+        _ => UrlRecord::new(row.to_string()),
+    }
+}
+
 /// Percent-encode a resume key so opaque cursor bytes (`+`, `/`, `=` in some
 /// base64 variants) survive being spliced back into the query string.
 fn encode_resume_key(key: &str) -> String {
@@ -136,6 +151,42 @@ pub fn normalize_cdx_timestamp(input: &str, end_of_range: bool) -> Option<String
     Some(format!("{year:04}{month}{day}{tail}"))
 }
 
+/// Result fields the CDX server can `filter=` or `collapse=` on.
+const CDX_FIELDS: &[&str] = &[
+    "urlkey",
+    "timestamp",
+    "original",
+    "mimetype",
+    "statuscode",
+    "digest",
+    "length",
+];
+
+/// Validate a `--wayback-filter` value: `[!]field:regex` where `field` is a
+/// CDX result field (e.g. `statuscode:200`, `!mimetype:image/.*`). Returns the
+/// trimmed filter, or `None` so the CLI can warn.
+pub fn parse_cdx_filter(input: &str) -> Option<String> {
+    let input = input.trim();
+    let (field, regex) = input.trim_start_matches('!').split_once(':')?;
+    (CDX_FIELDS.contains(&field) && !regex.is_empty()).then(|| input.to_string())
+}
+
+/// Validate a `--wayback-collapse` value: a CDX field, optionally with a
+/// prefix length (`timestamp:8` collapses to one capture per day). `none`
+/// disables collapsing and yields `Some(None)`; malformed input yields `None`.
+pub fn parse_cdx_collapse(input: &str) -> Option<Option<String>> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("none") {
+        return Some(None);
+    }
+    let (field, len) = match input.split_once(':') {
+        Some((field, len)) => (field, Some(len)),
+        None => (input, None),
+    };
+    let len_ok = len.is_none_or(|l| l.parse::<u32>().is_ok_and(|n| n > 0));
+    (CDX_FIELDS.contains(&field) && len_ok).then(|| Some(input.to_string()))
+}
+
 #[derive(Clone)]
 pub struct WaybackMachineProvider {
     include_subdomains: bool,
@@ -146,10 +197,17 @@ pub struct WaybackMachineProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
     /// CDX `from=` timestamp (already normalised to 14 digits).
     from: Option<String>,
     /// CDX `to=` timestamp (already normalised to 14 digits).
     to: Option<String>,
+    /// CDX `collapse=` value; `None` returns every capture.
+    collapse: Option<String>,
+    /// CDX `filter=` expressions, all of which must match.
+    filters: Vec<String>,
+    /// CDX `matchType=`; `None` keeps the wildcard `url=` form.
+    match_type: Option<String>,
     #[cfg(test)]
     base_url: String,
 }
@@ -166,8 +224,12 @@ impl WaybackMachineProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
             from: None,
             to: None,
+            collapse: Some("urlkey".to_string()),
+            filters: Vec::new(),
+            match_type: None,
             #[cfg(test)]
             base_url: "https://web.archive.org".to_string(),
         }
@@ -187,6 +249,28 @@ impl WaybackMachineProvider {
         self
     }
 
+    /// Set the CDX `collapse=` field (default `urlkey`, one row per distinct
+    /// URL). Pass `None` to return every capture.
+    pub fn with_collapse(&mut self, collapse: Option<String>) -> &mut Self {
+        self.collapse = collapse;
+        self
+    }
+
+    /// Server-side `filter=` expressions (see `parse_cdx_filter`), e.g.
+    /// `statuscode:200` or `!mimetype:image/.*`. Rows must match all of them.
+    pub fn with_filters(&mut self, filters: Vec<String>) -> &mut Self {
+        self.filters = filters;
+        self
+    }
+
+    /// Use an explicit CDX `matchType` (`exact`, `prefix`, `host` or `domain`) instead of
+    /// the wildcard URL form. `domain` also covers subdomains regardless of
+    /// `with_subdomains`; `exact` returns only the bare host URL.
+    pub fn with_match_type(&mut self, match_type: Option<String>) -> &mut Self {
+        self.match_type = match_type;
+        self
+    }
+
     #[cfg(test)]
     pub fn with_base_url(&mut self, url: String) -> &mut Self {
         self.base_url = url;
@@ -201,6 +285,7 @@ impl WaybackMachineProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
         }
     }
 
@@ -217,20 +302,31 @@ impl WaybackMachineProvider {
     }
 
     /// Build the CDX query *without* pagination params. Plain-text streaming
-    /// (`fl=original`) is far more reliable than `output=json` for large
-    /// domains, and `collapse=urlkey` trims server-side duplicates.
+    /// is far more reliable than `output=json` for large domains, and
+    /// `collapse=urlkey` (the default) trims server-side duplicates. Besides
+    /// the URL each row carries the capture timestamp, archived status, and
+    /// MIME type (see `parse_cdx_row`).
     fn query_base(&self, domain: &str) -> String {
-        let mut url = if self.include_subdomains {
-            format!(
-                "{}/cdx/search/cdx?url=*.{domain}/*&fl=original&collapse=urlkey",
-                self.base_url()
-            )
-        } else {
-            format!(
-                "{}/cdx/search/cdx?url={domain}/*&fl=original&collapse=urlkey",
-                self.base_url()
-            )
+        let target = match (&self.match_type, self.include_subdomains) {
+            (Some(match_type), _) => format!("url={domain}&matchType={match_type}"),
+            (None, true) => format!("url=*.{domain}/*"),
+            (None, false) => format!("url={domain}/*"),
         };
+        let mut url = format!(
+            "{}/cdx/search/cdx?{target}&fl=original,timestamp,statuscode,mimetype",
+            self.base_url()
+        );
+        // Filters are regexes, so `+`, `&` and friends must be escaped
+        let encode =
+            |v: &str| url::form_urlencoded::byte_serialize(v.as_bytes()).collect::<String>();
+        if let Some(collapse) = &self.collapse {
+            url.push_str("&collapse=");
+            url.push_str(&encode(collapse));
+        }
+        for filter in &self.filters {
+            url.push_str("&filter=");
+            url.push_str(&encode(filter));
+        }
         if let Some(ts) = &self.from {
             url.push_str("&from=");
             url.push_str(ts);
@@ -261,6 +357,34 @@ impl Provider for WaybackMachineProvider {
         reporter: Option<ProgressReporter>,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
         Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, reporter).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut urls = collect_pages(self.fetch_records_stream(domain, reporter)).await?;
+
+            // Stable sort keeps the earliest capture first when collapsing is
+            // off and a URL appears once per capture
+            urls.sort_by(|a, b| a.url.cmp(&b.url));
+            urls.dedup_by(|a, b| a.url == b.url);
+
+            Ok(urls)
+        })
+    }
+
+    fn fetch_records_stream<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<ProgressReporter>,
+    ) -> RecordStream<'a> {
+        page_stream(move |mut sink| async move {
             let client = self.client_config().build_client()?;
             let query_base = self.query_base(domain);
             let limiter = self.rate_limit.as_ref();
@@ -273,7 +397,6 @@ impl Provider for WaybackMachineProvider {
             // plus a resume key pointing at the next slice. Following the key
             // lets arbitrarily large domains complete as a series of bounded,
             // fast requests instead of one unbounded request that times out.
-            let mut urls: Vec<String> = Vec::new();
             let mut resume_key: Option<String> = None;
             let mut pages = 0usize;
 
@@ -289,16 +412,22 @@ impl Provider for WaybackMachineProvider {
                     url.push_str(&encode_resume_key(key));
                 }
 
-                if let Some(rl) = &limiter {
-                    rl.acquire().await;
-                }
-                let text = match get_with_retry(&client, &url, self.retries).await {
+                let Some(fetched) = super::unless_cancelled(
+                    &self.cancel,
+                    reporter.as_ref(),
+                    get_with_retry(&client, &url, self.retries, limiter),
+                )
+                .await
+                else {
+                    break;
+                };
+                let text = match fetched {
                     Ok(text) => text,
                     Err(e) => {
                         // Best effort: a mid-cursor failure shouldn't discard
                         // the pages we already pulled. Only a failure on the
                         // very first request (nothing collected) is fatal.
-                        if urls.is_empty() {
+                        if sink.sent() == 0 {
                             return Err(e);
                         }
                         // We're returning a truncated result. Flag it so the
@@ -313,10 +442,16 @@ impl Provider for WaybackMachineProvider {
 
                 let (page_urls, next_key) = split_page(&text);
                 let got = page_urls.len();
-                urls.extend(page_urls);
+                let mut page: Vec<UrlRecord> =
+                    page_urls.iter().map(|row| parse_cdx_row(row)).collect();
+                page.sort_by(|a, b| a.url.cmp(&b.url));
+                page.dedup_by(|a, b| a.url == b.url);
+                if !sink.send(page).await {
+                    break;
+                }
 
                 if let Some(r) = &reporter {
-                    r.detail(format!("{} URLs…", urls.len()));
+                    r.detail(format!("{} URLs…", sink.sent()));
                 }
 
                 // Continue only when the cursor actually advanced: a new resume
@@ -330,14 +465,21 @@ impl Provider for WaybackMachineProvider {
                 }
             }
 
-            urls.sort();
-            urls.dedup();
-
-            Ok(urls)
+            Ok(())
         })
     }
 
     // Implement new trait methods
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!(
+                "GET {}&limit={PAGE_LIMIT}&showResumeKey=true",
+                self.query_base(domain)
+            ),
+            format!("  then follow &resumeKey= until none is returned (at most {MAX_PAGES} pages)"),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -370,6 +512,14 @@ impl Provider for WaybackMachineProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -473,6 +623,19 @@ mod tests {
         assert_eq!(config.proxy_auth, Some("user:pass".to_string()));
     }
 
+    #[test]
+    fn test_plan_requests_uses_query_base() {
+        let mut provider = WaybackMachineProvider::new();
+        provider.with_base_url("https://web.archive.org".to_string());
+        provider.with_subdomains(true);
+        let plan = provider.plan_requests("example.com");
+        assert_eq!(
+            plan[0],
+            "GET https://web.archive.org/cdx/search/cdx?url=*.example.com/*&fl=original,timestamp,statuscode,mimetype&collapse=urlkey&limit=50000&showResumeKey=true"
+        );
+        assert!(plan[1].contains("resumeKey"));
+    }
+
     #[tokio::test]
     async fn test_fetch_urls_builds_correct_url_without_subdomains() {
         // 이 테스트는 실제 API 호출 없이 URL 구성을 확인합니다
@@ -539,7 +702,10 @@ mod tests {
             .mock("GET", "/cdx/search/cdx")
             .match_query(mockito::Matcher::AllOf(vec![
                 mockito::Matcher::UrlEncoded("url".into(), "example.com/*".into()),
-                mockito::Matcher::UrlEncoded("fl".into(), "original".into()),
+                mockito::Matcher::UrlEncoded(
+                    "fl".into(),
+                    "original,timestamp,statuscode,mimetype".into(),
+                ),
                 mockito::Matcher::UrlEncoded("collapse".into(), "urlkey".into()),
                 mockito::Matcher::UrlEncoded("showResumeKey".into(), "true".into()),
             ]))
@@ -615,6 +781,52 @@ mod tests {
         page2.assert();
     }
 
+    #[tokio::test]
+    async fn test_fetch_records_stream_yields_each_page() {
+        use futures::StreamExt;
+
+        let mut server = mockito::Server::new_async().await;
+        let _page1 = server
+            .mock("GET", "/cdx/search/cdx")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "showResumeKey".into(),
+                "true".into(),
+            ))
+            .with_status(200)
+            .with_body("http://example.com/a\nhttp://example.com/b\n\nKEY2\n")
+            .expect(1)
+            .create_async()
+            .await;
+        let _page2 = server
+            .mock("GET", "/cdx/search/cdx")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "resumeKey".into(),
+                "KEY2".into(),
+            ))
+            .with_status(200)
+            .with_body("http://example.com/b\nhttp://example.com/c\n")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider = WaybackMachineProvider::new();
+        provider.with_base_url(server.url());
+
+        // One item per CDX page, undeduplicated across pages.
+        let pages: Vec<Vec<String>> = provider
+            .fetch_records_stream("example.com", None)
+            .map(|page| page.unwrap().into_iter().map(|r| r.url).collect())
+            .collect()
+            .await;
+        assert_eq!(
+            pages,
+            vec![
+                vec!["http://example.com/a", "http://example.com/b"],
+                vec!["http://example.com/b", "http://example.com/c"],
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn test_rate_limit_paces_page_requests() {
         use std::time::{Duration, Instant};
@@ -760,6 +972,20 @@ mod tests {
         assert_eq!(key, None);
     }
 
+    #[test]
+    fn test_parse_cdx_row() {
+        let record = parse_cdx_row("http://example.com/a 20200102030405 200 text/html");
+        assert_eq!(record.url, "http://example.com/a");
+        assert_eq!(record.metadata["timestamp"], "2020-01-02T03:04:05Z");
+        assert_eq!(record.metadata["archived_status"], 200);
+        assert_eq!(record.metadata["mime"], "text/html");
+
+        // Revisit rows have no status; bare rows have no metadata at all
+        let revisit = parse_cdx_row("http://example.com/b 20200102030405 - warc/revisit");
+        assert!(!revisit.metadata.contains_key("archived_status"));
+        assert!(parse_cdx_row("http://example.com/c").metadata.is_empty());
+    }
+
     #[test]
     fn test_split_page_empty_body() {
         let (urls, key) = split_page("");
@@ -783,7 +1009,10 @@ mod tests {
             .mock("GET", "/cdx/search/cdx")
             .match_query(mockito::Matcher::AllOf(vec![
                 mockito::Matcher::UrlEncoded("url".into(), "*.example.com/*".into()),
-                mockito::Matcher::UrlEncoded("fl".into(), "original".into()),
+                mockito::Matcher::UrlEncoded(
+                    "fl".into(),
+                    "original,timestamp,statuscode,mimetype".into(),
+                ),
                 mockito::Matcher::UrlEncoded("collapse".into(), "urlkey".into()),
                 mockito::Matcher::UrlEncoded("showResumeKey".into(), "true".into()),
             ]))
@@ -920,7 +1149,10 @@ mod tests {
             .mock("GET", "/cdx/search/cdx")
             .match_query(mockito::Matcher::AllOf(vec![
                 mockito::Matcher::UrlEncoded("url".into(), "example.com/*".into()),
-                mockito::Matcher::UrlEncoded("fl".into(), "original".into()),
+                mockito::Matcher::UrlEncoded(
+                    "fl".into(),
+                    "original,timestamp,statuscode,mimetype".into(),
+                ),
                 mockito::Matcher::UrlEncoded("collapse".into(), "urlkey".into()),
                 mockito::Matcher::UrlEncoded("from".into(), "20200101000000".into()),
                 mockito::Matcher::UrlEncoded("to".into(), "20201231235959".into()),
@@ -942,4 +1174,71 @@ mod tests {
         assert_eq!(urls, vec!["http://example.com/page".to_string()]);
         mock.assert();
     }
+
+    #[test]
+    fn test_parse_cdx_filter_and_collapse() {
+        assert_eq!(
+            parse_cdx_filter(" statuscode:200 ").as_deref(),
+            Some("statuscode:200")
+        );
+        assert_eq!(
+            parse_cdx_filter("!mimetype:image/.*").as_deref(),
+            Some("!mimetype:image/.*")
+        );
+        assert!(parse_cdx_filter("status:200").is_none());
+        assert!(parse_cdx_filter("statuscode:").is_none());
+        assert!(parse_cdx_filter("statuscode").is_none());
+
+        assert_eq!(parse_cdx_collapse("none"), Some(None));
+        assert_eq!(
+            parse_cdx_collapse("timestamp:8"),
+            Some(Some("timestamp:8".to_string()))
+        );
+        assert_eq!(
+            parse_cdx_collapse("digest"),
+            Some(Some("digest".to_string()))
+        );
+        assert!(parse_cdx_collapse("timestamp:0").is_none());
+        assert!(parse_cdx_collapse("bogus").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_urls_passes_cdx_filters_and_match_type() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/cdx/search/cdx")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("url".into(), "example.com".into()),
+                mockito::Matcher::UrlEncoded("matchType".into(), "domain".into()),
+                mockito::Matcher::UrlEncoded("collapse".into(), "digest".into()),
+                // Repeated keys collapse in UrlEncoded, so check the raw query
+                mockito::Matcher::Regex(
+                    "filter=statuscode%3A200&filter=%21mimetype%3Aimage%2F.%2B".into(),
+                ),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "text/plain")
+            .with_body("http://example.com/page\n")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut provider = WaybackMachineProvider::new();
+        provider
+            .with_base_url(server.url())
+            .with_collapse(Some("digest".to_string()))
+            .with_filters(vec![
+                "statuscode:200".to_string(),
+                "!mimetype:image/.+".to_string(),
+            ])
+            .with_match_type(Some("domain".to_string()));
+
+        let urls = provider.fetch_urls("example.com").await.unwrap();
+        assert_eq!(urls, vec!["http://example.com/page".to_string()]);
+        mock.assert();
+
+        // Without collapse the parameter is left off entirely
+        provider.with_collapse(None);
+        assert!(!provider.query_base("example.com").contains("collapse="));
+    }
 }
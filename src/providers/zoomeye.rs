@@ -3,11 +3,15 @@ use base64::{engine::general_purpose::STANDARD, Engine as _};
 use serde::{Deserialize, Serialize};
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
 use super::ApiKeyRotator;
-use super::Provider;
+use super::{Provider, UrlRecord};
 use crate::network::client::HttpClientConfig;
-use crate::network::RateLimiter;
+use crate::network::{
+    read_json, server_retry_delay, wait_before_retry, QuotaMeter, RateLimiter, RequestGate,
+};
+use crate::utils::split_host_port;
 
 #[derive(Clone)]
 pub struct ZoomEyeProvider {
@@ -20,6 +24,8 @@ pub struct ZoomEyeProvider {
     random_agent: bool,
     insecure: bool,
     rate_limit: Option<RateLimiter>,
+    cancel: CancellationToken,
+    quota: Option<QuotaMeter>,
     #[cfg(test)]
     base_url: String,
 }
@@ -88,6 +94,8 @@ impl ZoomEyeProvider {
             random_agent: false,
             insecure: false,
             rate_limit: None,
+            cancel: CancellationToken::new(),
+            quota: None,
             #[cfg(test)]
             base_url: "https://api.zoomeye.ai".to_string(),
         }
@@ -106,14 +114,31 @@ impl ZoomEyeProvider {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            ..Default::default()
+        }
+    }
+
+    fn api_url(&self) -> String {
+        #[cfg(test)]
+        {
+            format!("{}/v2/search", self.base_url)
+        }
+        #[cfg(not(test))]
+        {
+            "https://api.zoomeye.ai/v2/search".to_string()
         }
     }
 
     fn build_dork(&self, domain: &str) -> String {
-        if self.include_subdomains {
-            format!("site:*.{domain}")
+        let (host, port) = split_host_port(domain);
+        let site = if self.include_subdomains {
+            format!("site:*.{host}")
         } else {
-            format!("site:{domain}")
+            format!("site:{host}")
+        };
+        match port {
+            Some(port) => format!("{site} port:{port}"),
+            None => site,
         }
     }
 }
@@ -127,6 +152,18 @@ impl Provider for ZoomEyeProvider {
         &'a self,
         domain: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            let records = self.fetch_records_with_progress(domain, None).await?;
+            Ok(records.into_iter().map(|r| r.url).collect())
+        })
+    }
+
+    /// Each record carries the `ip` ZoomEye resolved the site to, when known.
+    fn fetch_records_with_progress<'a>(
+        &'a self,
+        domain: &'a str,
+        reporter: Option<crate::progress::ProgressReporter>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<UrlRecord>>> + Send + 'a>> {
         Box::pin(async move {
             if !self.api_key_rotator.has_keys() {
                 return Ok(Vec::new());
@@ -135,16 +172,12 @@ impl Provider for ZoomEyeProvider {
             let dork = self.build_dork(domain);
             let qbase64 = STANDARD.encode(dork.as_bytes());
 
-            #[cfg(test)]
-            let api_url = format!("{}/v2/search", self.base_url);
-
-            #[cfg(not(test))]
-            let api_url = "https://api.zoomeye.ai/v2/search".to_string();
+            let api_url = self.api_url();
 
             let client = self.client_config().build_client()?;
             let limiter = self.rate_limit.as_ref();
 
-            let mut all_urls: Vec<String> = Vec::new();
+            let mut all_urls: Vec<UrlRecord> = Vec::new();
             let mut page: u32 = 1;
             let pagesize: u32 = 100;
 
@@ -158,13 +191,13 @@ impl Provider for ZoomEyeProvider {
 
                 let mut last_error = None;
                 let mut attempt = 0;
-                let mut page_urls: Vec<String> = Vec::new();
+                let mut server_delay = None;
+                let mut page_urls: Vec<UrlRecord> = Vec::new();
                 let mut total: u64 = 0;
 
                 while attempt <= self.retries {
                     if attempt > 0 {
-                        tokio::time::sleep(std::time::Duration::from_millis(500 * attempt as u64))
-                            .await;
+                        wait_before_retry(attempt, server_delay.take()).await;
                     }
 
                     // Rotate the key per attempt so a rate-limited/quota-hit key
@@ -175,26 +208,40 @@ impl Provider for ZoomEyeProvider {
                         .header("API-KEY", &api_key)
                         .json(&request_body);
 
-                    if let Some(rl) = &limiter {
-                        rl.acquire().await;
-                    }
-                    match req.send().await {
+                    let Some(sent) =
+                        super::unless_cancelled(&self.cancel, reporter.as_ref(), async {
+                            if let Some(rl) = &limiter {
+                                rl.acquire().await;
+                            }
+                            req.send().await
+                        })
+                        .await
+                    else {
+                        return Ok(all_urls);
+                    };
+                    match sent {
                         Ok(response) => {
                             let status = response.status();
                             if !status.is_success() {
-                                if status.as_u16() == 429 {
-                                    if let Some(d) = crate::network::client::retry_after_delay(
-                                        response.headers(),
-                                    ) {
-                                        tokio::time::sleep(d).await;
-                                    }
+                                let retry_after =
+                                    crate::network::retry_after_delay(response.headers());
+                                // A throttled or rejected key sits out; retry
+                                // at once with another usable key, if any.
+                                if self.api_key_rotator.report_status(
+                                    &api_key,
+                                    status.as_u16(),
+                                    retry_after,
+                                ) {
+                                    last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
+                                    continue;
                                 }
+                                server_delay = server_retry_delay(status, response.headers());
                                 attempt += 1;
                                 last_error = Some(anyhow::anyhow!("HTTP error: {status}"));
                                 continue;
                             }
 
-                            match response.json::<ZoomEyeResponse>().await {
+                            match read_json::<ZoomEyeResponse>(response).await {
                                 Ok(zoomeye_response) => {
                                     // A 200 with a non-success code is an API
                                     // error (rejected key, quota, bad query) —
@@ -208,9 +255,14 @@ impl Provider for ZoomEyeProvider {
                                     }
                                     total = zoomeye_response.total;
                                     for entry in zoomeye_response.data {
-                                        if !entry.url.is_empty() {
-                                            page_urls.push(entry.url);
+                                        if entry.url.is_empty() {
+                                            continue;
                                         }
+                                        let mut record = UrlRecord::new(entry.url);
+                                        if !entry.ip.is_empty() {
+                                            record = record.with_meta("ip", entry.ip);
+                                        }
+                                        page_urls.push(record);
                                     }
                                     last_error = None;
                                     break;
@@ -260,6 +312,19 @@ impl Provider for ZoomEyeProvider {
         })
     }
 
+    fn plan_requests(&self, domain: &str) -> Vec<String> {
+        vec![
+            format!(
+                "POST {} (API-KEY: ***) qbase64=base64({:?}) page=1 pagesize=100",
+                self.api_url(),
+                self.build_dork(domain)
+            ),
+            format!(
+                "  then page=2, 3, … until `total` is reached (at most {ZOOMEYE_MAX_PAGES} pages)"
+            ),
+        ]
+    }
+
     fn with_subdomains(&mut self, include: bool) {
         self.include_subdomains = include;
     }
@@ -291,6 +356,23 @@ impl Provider for ZoomEyeProvider {
     fn with_rate_limit(&mut self, rate_limit: Option<f32>) {
         self.rate_limit = RateLimiter::from_rate(rate_limit);
     }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.rate_limit = Some(RateLimiter::gated(self.rate_limit.take(), gate));
+    }
+
+    fn with_quota(&mut self, meter: QuotaMeter) {
+        self.rate_limit = Some(RateLimiter::metered(self.rate_limit.take(), meter.clone()));
+        self.quota = Some(meter);
+    }
+
+    fn quota(&self) -> Option<&QuotaMeter> {
+        self.quota.as_ref()
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
 }
 
 #[cfg(test)]
@@ -383,6 +465,15 @@ mod tests {
         assert_eq!(provider.build_dork("example.com"), "site:*.example.com");
     }
 
+    #[test]
+    fn test_build_dork_with_port() {
+        let provider = ZoomEyeProvider::new("key".to_string());
+        assert_eq!(
+            provider.build_dork("example.com:8080"),
+            "site:example.com port:8080"
+        );
+    }
+
     #[test]
     fn test_with_subdomains() {
         let provider = &mut ZoomEyeProvider::new("test_api_key".to_string());
@@ -576,6 +667,13 @@ mod tests {
         assert_eq!(urls.len(), 2);
         assert_eq!(urls[0], "https://example.com/page1");
         assert_eq!(urls[1], "https://example.com/page2");
+
+        // Records keep the IP ZoomEye resolved each site to.
+        let records = provider
+            .fetch_records_with_progress("example.com", None)
+            .await
+            .unwrap();
+        assert_eq!(records[0].metadata.get("ip").unwrap(), "1.2.3.4");
     }
 
     #[tokio::test]
@@ -2,6 +2,8 @@ use anyhow::Result;
 use std::io::{BufRead, Read};
 use std::path::Path;
 
+use crate::error::{UrxError, UrxResult};
+
 mod text_reader;
 mod urlteam_reader;
 mod warc_reader;
@@ -123,10 +125,10 @@ pub fn detect_file_format(file_path: &Path) -> Result<FileFormat> {
 }
 
 /// Read URLs from a file using auto-detected format
-pub fn read_urls_from_file(file_path: &Path) -> Result<Vec<String>> {
-    let format = detect_file_format(file_path)?;
+pub fn read_urls_from_file(file_path: &Path) -> UrxResult<Vec<String>> {
+    let format = detect_file_format(file_path).map_err(UrxError::io)?;
 
-    match format {
+    let urls = match format {
         FileFormat::Warc => {
             let reader = WarcFileReader::new();
             reader.read_urls(file_path)
@@ -139,7 +141,8 @@ pub fn read_urls_from_file(file_path: &Path) -> Result<Vec<String>> {
             let reader = TextFileReader::new();
             reader.read_urls(file_path)
         }
-    }
+    };
+    urls.map_err(UrxError::io)
 }
 
 #[cfg(test)]
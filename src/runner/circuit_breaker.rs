@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Per-provider circuit breaker for `--circuit-breaker`.
+///
+/// Counts failed domain fetches in completion order; a success resets the
+/// count. Once `threshold` failures happen in a row the circuit opens and
+/// stays open for the rest of the run, so a dead provider stops adding its
+/// full timeout to every remaining domain. A threshold of 0 never opens.
+#[derive(Debug)]
+pub(crate) struct CircuitBreaker {
+    threshold: usize,
+    consecutive: AtomicUsize,
+    open: AtomicBool,
+    skipped: AtomicUsize,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(threshold: usize) -> Self {
+        CircuitBreaker {
+            threshold,
+            consecutive: AtomicUsize::new(0),
+            open: AtomicBool::new(false),
+            skipped: AtomicUsize::new(0),
+        }
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record_success(&self) {
+        self.consecutive.store(0, Ordering::Relaxed);
+    }
+
+    /// Count a failed domain. Returns `true` only for the failure that opens
+    /// the circuit, so the caller can report it once.
+    pub(crate) fn record_failure(&self) -> bool {
+        let failures = self.consecutive.fetch_add(1, Ordering::Relaxed) + 1;
+        self.threshold > 0 && failures >= self.threshold && !self.open.swap(true, Ordering::Relaxed)
+    }
+
+    /// Note a domain skipped because the circuit was open.
+    pub(crate) fn record_skip(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn skipped(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3);
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        breaker.record_success();
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(!breaker.is_open());
+
+        // Third in a row opens it, and only that call reports the trip.
+        assert!(breaker.record_failure());
+        assert!(breaker.is_open());
+        assert!(!breaker.record_failure());
+        breaker.record_success();
+        assert!(breaker.is_open());
+    }
+
+    #[test]
+    fn test_zero_threshold_never_opens() {
+        let breaker = CircuitBreaker::new(0);
+        for _ in 0..100 {
+            assert!(!breaker.record_failure());
+        }
+        assert!(!breaker.is_open());
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio_util::sync::CancellationToken;
+
+/// Per-domain URL ceiling for `--max-urls-per-domain`, shared by every
+/// provider task.
+///
+/// Each domain counts the unique URLs first reported for it. Once the count
+/// reaches `max`, the domain's token is cancelled: paginating providers still
+/// working on it stop after their current page, and providers that haven't
+/// started on it skip it. Domain tokens are children of the run-wide token,
+/// so `--max-time` and Ctrl-C reach them too.
+#[derive(Debug)]
+pub(crate) struct DomainCap {
+    max: usize,
+    counts: Mutex<HashMap<String, usize>>,
+    tokens: HashMap<String, CancellationToken>,
+    parent: CancellationToken,
+}
+
+impl DomainCap {
+    pub(crate) fn new(max: usize, domains: &[String], parent: &CancellationToken) -> Self {
+        DomainCap {
+            max,
+            counts: Mutex::new(HashMap::new()),
+            tokens: domains
+                .iter()
+                .map(|d| (d.clone(), parent.child_token()))
+                .collect(),
+            parent: parent.clone(),
+        }
+    }
+
+    /// Token that is cancelled once `domain` reaches the cap (or the run ends).
+    pub(crate) fn token(&self, domain: &str) -> CancellationToken {
+        self.tokens
+            .get(domain)
+            .cloned()
+            .unwrap_or_else(|| self.parent.child_token())
+    }
+
+    pub(crate) fn is_reached(&self, domain: &str) -> bool {
+        self.tokens.get(domain).is_some_and(|t| t.is_cancelled()) && !self.parent.is_cancelled()
+    }
+
+    /// Add `new_urls` freshly collected URLs to `domain`'s count. Returns
+    /// `true` only for the call that takes the domain to the cap, so the
+    /// caller can report it once.
+    pub(crate) fn record(&self, domain: &str, new_urls: usize) -> bool {
+        let mut counts = self
+            .counts
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let count = counts.entry(domain.to_string()).or_insert(0);
+        let before = *count;
+        *count += new_urls;
+        if before < self.max && *count >= self.max {
+            if let Some(token) = self.tokens.get(domain) {
+                token.cancel();
+            }
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_cancels_only_the_full_domain() {
+        let parent = CancellationToken::new();
+        let domains = vec!["a.com".to_string(), "b.com".to_string()];
+        let cap = DomainCap::new(3, &domains, &parent);
+
+        assert!(!cap.record("a.com", 2));
+        assert!(!cap.is_reached("a.com"));
+        assert!(cap.record("a.com", 5));
+        assert!(!cap.record("a.com", 1));
+        assert!(cap.is_reached("a.com"));
+        assert!(cap.token("a.com").is_cancelled());
+        assert!(!cap.token("b.com").is_cancelled());
+
+        // The run-wide token reaches every domain, but isn't a cap hit.
+        parent.cancel();
+        assert!(cap.token("b.com").is_cancelled());
+        assert!(!cap.is_reached("b.com"));
+    }
+}
@@ -0,0 +1,126 @@
+use futures::future::join_all;
+use std::time::{Duration, Instant};
+
+use crate::providers::Provider;
+
+/// Outcome of probing one provider for `--check-providers`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProviderHealth {
+    /// Answered the probe; carries how many URLs came back.
+    Ok(usize),
+    /// Rejected the API key (HTTP 401/403).
+    Unauthorized(String),
+    /// Throttled or out of quota (HTTP 429).
+    QuotaExhausted(String),
+    /// Needs an API key and none was configured.
+    MissingKey,
+    /// Any other failure: DNS, timeout, 5xx, unparsable response, …
+    Failed(String),
+}
+
+impl ProviderHealth {
+    /// Sort a probe error into the failure it most likely is. Providers report
+    /// HTTP failures as `HTTP error: <status>`, so the status code is matched
+    /// in the rendered error chain.
+    pub fn from_error(e: &anyhow::Error) -> Self {
+        let msg = format!("{e:#}");
+        let one_line = msg.split('\n').next().unwrap_or(&msg).to_string();
+        if msg.contains("401") || msg.contains("403") {
+            ProviderHealth::Unauthorized(one_line)
+        } else if msg.contains("429") || msg.to_ascii_lowercase().contains("quota") {
+            ProviderHealth::QuotaExhausted(one_line)
+        } else {
+            ProviderHealth::Failed(one_line)
+        }
+    }
+
+    pub fn is_usable(&self) -> bool {
+        matches!(self, ProviderHealth::Ok(_))
+    }
+
+    /// Short status column for the report table.
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProviderHealth::Ok(_) => "ok",
+            ProviderHealth::Unauthorized(_) => "bad key",
+            ProviderHealth::QuotaExhausted(_) => "quota",
+            ProviderHealth::MissingKey => "no key",
+            ProviderHealth::Failed(_) => "error",
+        }
+    }
+
+    /// Free-form detail shown after the status column.
+    pub fn detail(&self) -> String {
+        match self {
+            ProviderHealth::Ok(urls) => format!("{urls} URLs"),
+            ProviderHealth::MissingKey => "API key required but not configured".to_string(),
+            ProviderHealth::Unauthorized(e)
+            | ProviderHealth::QuotaExhausted(e)
+            | ProviderHealth::Failed(e) => e.clone(),
+        }
+    }
+}
+
+/// One row of the `--check-providers` report.
+#[derive(Debug, Clone)]
+pub struct ProviderCheck {
+    pub name: String,
+    pub health: ProviderHealth,
+    /// Time the probe took; `None` when nothing was sent.
+    pub latency: Option<Duration>,
+}
+
+/// Probe every provider concurrently with a single fetch for `domain`,
+/// returning one [`ProviderCheck`] per provider in the order given.
+pub async fn check_providers(
+    providers: &[Box<dyn Provider>],
+    provider_names: &[String],
+    domain: &str,
+) -> Vec<ProviderCheck> {
+    let probes = providers
+        .iter()
+        .zip(provider_names)
+        .map(|(provider, name)| {
+            let domain = domain.to_string();
+            async move {
+                let started = Instant::now();
+                let result = provider.fetch_urls(&domain).await;
+                let latency = started.elapsed();
+                let health = match result {
+                    Ok(urls) => ProviderHealth::Ok(urls.len()),
+                    Err(e) => ProviderHealth::from_error(&e),
+                };
+                ProviderCheck {
+                    name: name.clone(),
+                    health,
+                    latency: Some(latency),
+                }
+            }
+        });
+    join_all(probes).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_error_classifies_http_status() {
+        let health = ProviderHealth::from_error(&anyhow::anyhow!(
+            "Failed after 3 attempts: HTTP error: 401 Unauthorized"
+        ));
+        assert_eq!(health.label(), "bad key");
+        assert_eq!(
+            ProviderHealth::from_error(&anyhow::anyhow!("HTTP error: 429 Too Many Requests"))
+                .label(),
+            "quota"
+        );
+        assert_eq!(
+            ProviderHealth::from_error(&anyhow::anyhow!("operation timed out")).label(),
+            "error"
+        );
+        assert!(!health.is_usable());
+        assert!(ProviderHealth::Ok(0).is_usable());
+        assert!(!ProviderHealth::MissingKey.is_usable());
+    }
+}
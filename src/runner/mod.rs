@@ -1,3 +1,8 @@
+mod circuit_breaker;
+mod domain_cap;
+pub mod health;
+mod summary;
+
 use futures::future::join_all;
 use futures::stream::{self, StreamExt};
 use indicatif::ProgressBar;
@@ -5,15 +10,19 @@ use std::collections::{HashMap, HashSet};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use tokio::task;
+use tokio_util::sync::CancellationToken;
 
 use crate::cli::Args;
-use crate::network::{NetworkScope, NetworkSettings};
+use crate::network::{NetworkScope, NetworkSettings, RequestGate};
 use crate::progress::{
     provider_error_style, provider_partial_style, provider_running_style, provider_success_style,
     ProgressManager, ProgressReporter,
 };
-use crate::providers::Provider;
+use crate::providers::{Provider, UrlRecord};
 use crate::utils::verbose_print;
+use circuit_breaker::CircuitBreaker;
+use domain_cap::DomainCap;
+pub use summary::{CacheSummary, RunSummary};
 
 /// Format an integer with thousands separators (e.g. `12345` → `12,345`) so
 /// large URL counts stay legible in the progress summary.
@@ -118,35 +127,49 @@ impl DomainCompletionCtx {
 
 /// Helper function to apply network settings to a provider
 pub fn apply_network_settings_to_provider(provider: &mut dyn Provider, settings: &NetworkSettings) {
-    // Skip applying settings if network scope doesn't include providers
-    if settings.scope == NetworkScope::Testers {
-        return;
-    }
-
-    provider.with_subdomains(settings.include_subdomains);
-    provider.with_timeout(settings.timeout);
-    provider.with_retries(settings.retries);
-    provider.with_random_agent(settings.random_agent);
-    provider.with_insecure(settings.insecure);
-
-    if let Some(proxy) = &settings.proxy {
-        provider.with_proxy(Some(proxy.clone()));
+    // Skip the scoped settings if network scope doesn't include providers
+    if settings.scope != NetworkScope::Testers {
+        provider.with_subdomains(settings.include_subdomains);
+        provider.with_timeout(settings.timeout);
+        provider.with_retries(settings.retries);
+        provider.with_random_agent(settings.random_agent);
+        provider.with_insecure(settings.insecure);
+
+        if let Some(proxy) = &settings.proxy {
+            provider.with_proxy(Some(proxy.clone()));
+
+            if let Some(auth) = &settings.proxy_auth {
+                provider.with_proxy_auth(Some(auth.clone()));
+            }
+        }
 
-        if let Some(auth) = &settings.proxy_auth {
-            provider.with_proxy_auth(Some(auth.clone()));
+        if let Some(rate) = settings.rate_limit {
+            provider.with_rate_limit(Some(rate));
         }
     }
 
-    if let Some(rate) = settings.rate_limit {
-        provider.with_rate_limit(Some(rate));
+    // The run-wide gate ignores --network-scope, and wraps whatever rate
+    // limiter was set above, so it has to come last.
+    if let Some(gate) = &settings.request_gate {
+        provider.with_request_gate(gate.clone());
     }
 }
 
+/// The providers built for a run. `names` (display names, used for stats and
+/// source attribution) and `ids` (the `--providers` ids that per-provider
+/// settings are keyed by) run parallel to `providers`; `--cc-index` instances
+/// share the `cc` id but keep their own names.
+#[derive(Default)]
+pub struct ProviderList {
+    pub providers: Vec<Box<dyn Provider>>,
+    pub names: Vec<String>,
+    pub ids: Vec<String>,
+}
+
 pub fn add_provider<T: Provider + 'static>(
     args: &Args,
     network_settings: &NetworkSettings,
-    providers: &mut Vec<Box<dyn Provider>>,
-    provider_names: &mut Vec<String>,
+    list: &mut ProviderList,
     provider_id: &str,
     provider_name: String,
     provider_builder: impl FnOnce() -> T,
@@ -154,15 +177,32 @@ pub fn add_provider<T: Provider + 'static>(
     // Apply a per-provider rate limit override when --rate-limit-by lists this
     // provider id. Cloning lets us thread the override into the existing
     // apply_network_settings_to_provider helper without changing its API.
-    let per_provider_rate = args.rate_limit_overrides().get(provider_id).copied();
+    let mut per_provider_rate = args.rate_limit_overrides().get(provider_id).copied();
     let mut effective_settings = network_settings.clone();
     if per_provider_rate.is_some() {
         effective_settings.rate_limit = per_provider_rate;
     }
+    // --provider-opt / [provider.<id>] overrides layer on top. main validates
+    // them up front, so a parse error can't reach this point.
+    let provider_override = args
+        .provider_overrides()
+        .ok()
+        .and_then(|mut overrides| overrides.remove(provider_id));
+    if let Some(o) = &provider_override {
+        o.apply_to(&mut effective_settings);
+        per_provider_rate = o.rate_limit.or(per_provider_rate);
+    }
 
     if args.verbose && !args.silent {
         let mut config_info = vec![
-            format!("Adding {provider_name} provider"),
+            format!(
+                "Adding {provider_name} provider{}",
+                if provider_override.is_some() {
+                    " (per-provider overrides)"
+                } else {
+                    ""
+                }
+            ),
             format!("  Timeout: {} seconds", effective_settings.timeout),
             format!("  Retries: {}", effective_settings.retries),
             format!("  Parallel requests: {}", effective_settings.parallel),
@@ -194,8 +234,13 @@ pub fn add_provider<T: Provider + 'static>(
 
     let mut provider = provider_builder();
     apply_network_settings_to_provider(&mut provider, &effective_settings);
-    providers.push(Box::new(provider));
-    provider_names.push(provider_name);
+    // Quota meters are keyed by provider id, which only this function knows.
+    if let Some(meter) = network_settings.quotas.get(provider_id) {
+        provider.with_quota(meter.clone());
+    }
+    list.providers.push(Box::new(provider));
+    list.names.push(provider_name);
+    list.ids.push(provider_id.to_string());
 }
 
 /// Per-provider tally for end-of-run summaries (`--stats`).
@@ -209,18 +254,91 @@ pub struct ProviderStats {
     pub error_count: usize,
     /// Number of domain fetches that returned incomplete (partial) results.
     pub partial_count: usize,
+    /// Domains skipped after the provider's circuit breaker opened.
+    pub skipped_count: usize,
+    /// Domains skipped because they already had `--max-urls-per-domain` URLs.
+    pub capped_count: usize,
+    /// Domains skipped because the provider's daily `--provider-quota` was spent.
+    pub quota_skipped_count: usize,
+    /// Whether the circuit breaker opened for this provider.
+    pub circuit_open: bool,
     /// Total wall-clock time spent in fetch_urls across domains.
     pub elapsed: std::time::Duration,
 }
 
+impl ProviderStats {
+    /// Add another run's tally for the same provider.
+    pub fn merge(&mut self, other: &ProviderStats) {
+        self.url_count += other.url_count;
+        self.error_count += other.error_count;
+        self.partial_count += other.partial_count;
+        self.skipped_count += other.skipped_count;
+        self.capped_count += other.capped_count;
+        self.quota_skipped_count += other.quota_skipped_count;
+        self.circuit_open |= other.circuit_open;
+        self.elapsed += other.elapsed;
+    }
+}
+
 /// Result of a provider run: URLs mapped to the providers that reported them,
 /// plus per-provider stats indexed in the same order as `provider_names`.
+/// `metadata` only has entries for URLs a provider attached extra fields to;
+/// when several providers report the same key the first one wins.
+/// `fetches` lists every (domain, provider) fetch that ran to completion, for
+/// the provider yield history behind `--smart-order`.
 #[derive(Debug, Default)]
 pub struct ProviderRunResult {
     pub urls: HashMap<String, HashSet<String>>,
     pub stats: Vec<ProviderStats>,
+    pub metadata: HashMap<String, UrlMetadata>,
+    pub fetches: Vec<FetchRecord>,
+}
+
+impl ProviderRunResult {
+    /// Fold in a run over other domains (or other providers). Stats for a
+    /// provider both runs used are summed.
+    pub fn merge(&mut self, other: ProviderRunResult) {
+        for (url, sources) in other.urls {
+            self.urls.entry(url).or_default().extend(sources);
+        }
+        for stats in other.stats {
+            match self.stats.iter_mut().find(|s| s.name == stats.name) {
+                Some(existing) => existing.merge(&stats),
+                None => self.stats.push(stats),
+            }
+        }
+        for (url, metadata) in other.metadata {
+            let existing = self.metadata.entry(url).or_default();
+            for (key, value) in metadata {
+                existing.entry(key).or_insert(value);
+            }
+        }
+        self.fetches.extend(other.fetches);
+    }
+}
+
+/// Outcome of one (domain, provider) fetch. A failed fetch is recorded with
+/// zero URLs.
+#[derive(Debug, Clone)]
+pub struct FetchRecord {
+    pub domain: String,
+    pub provider: String,
+    pub urls: usize,
+    pub elapsed: std::time::Duration,
 }
 
+/// How long in-flight provider tasks get to wind down after cancellation
+/// before they are aborted outright. Paginating providers use it to return
+/// the pages they already fetched instead of losing them with the task.
+const CANCEL_GRACE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Base pause before re-queuing failed (domain, provider) pairs; round `n`
+/// waits `n` times this so a struggling upstream gets progressively more room.
+const RETRY_FAILED_BACKOFF: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// Per-URL metadata collected from providers (see [`UrlRecord`]).
+pub type UrlMetadata = serde_json::Map<String, serde_json::Value>;
+
 /// Process domains using a provider-based concurrency pattern.
 ///
 /// Returns each discovered URL along with the set of providers that reported
@@ -231,10 +349,20 @@ pub async fn process_domains(
     progress_manager: &ProgressManager,
     providers: &[Box<dyn Provider>],
     provider_names: &[String],
+    request_gate: Option<&RequestGate>,
 ) -> ProviderRunResult {
     // Map URL -> set of provider names that reported it.
     let all_urls: Arc<Mutex<HashMap<String, HashSet<String>>>> =
         Arc::new(Mutex::new(HashMap::new()));
+    let all_metadata: Arc<Mutex<HashMap<String, UrlMetadata>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    // Under --deterministic, which provider (by index) set each metadata
+    // field, so the earliest-listed provider wins whatever order fetches
+    // finish in. Otherwise the first to report a field keeps it.
+    let metadata_origin: Arc<Mutex<HashMap<(String, String), usize>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    let deterministic = crate::utils::determinism::is_deterministic();
+    let fetch_log: Arc<Mutex<Vec<FetchRecord>>> = Arc::new(Mutex::new(Vec::new()));
     let total_domains = domains.len();
     let total_providers = providers.len();
 
@@ -272,11 +400,38 @@ pub async fn process_domains(
         format!("Using provider-based concurrency with {total_providers} providers"),
     );
 
+    // One token for the whole run: --max-time and Ctrl-C cancel it so every
+    // provider's pagination loop stops promptly with what it has.
+    let cancel = CancellationToken::new();
+
+    // Fired by whichever fetch takes the collection to --max-urls.
+    let url_limit = CancellationToken::new();
+    let max_urls = args.max_urls;
+
+    // --max-urls-per-domain: per-domain counts and tokens shared by every
+    // provider task, so one provider's haul can end another's pagination.
+    let domain_cap = args
+        .max_urls_per_domain
+        .map(|max| Arc::new(DomainCap::new(max, &domains, &cancel)));
+
+    // --smart-order with --max-urls runs providers one at a time in the given
+    // (ranked) order, so the limit can be met before the low-yield providers
+    // are queried at all. Each provider waits on its predecessor's token,
+    // which is cancelled when the predecessor's task ends.
+    let sequential = args.smart_order && max_urls.is_some();
+    let turns: Vec<CancellationToken> = (0..total_providers)
+        .map(|_| CancellationToken::new())
+        .collect();
+
     // Clone provider data for use in async tasks
     let provider_data: Vec<_> = providers
         .iter()
         .enumerate()
-        .map(|(idx, provider)| (provider.clone_box(), provider_names[idx].clone(), idx))
+        .map(|(idx, provider)| {
+            let mut provider = provider.clone_box();
+            provider.with_cancellation(cancel.clone());
+            (provider, provider_names[idx].clone(), idx)
+        })
         .collect();
 
     // Create a future for each provider
@@ -291,12 +446,25 @@ pub async fn process_domains(
     // The shared per-provider rate limiter (stored in the provider and cloned
     // per domain) keeps --rate-limit honest across these concurrent fetches.
     let parallel = args.parallel.unwrap_or(5).max(1) as usize;
+    let retry_failed = args.retry_failed_domains;
+    let circuit_threshold = args.circuit_breaker;
 
     for (provider_clone, provider_name, original_idx) in provider_data.into_iter() {
         let all_urls = Arc::clone(&all_urls);
+        let all_metadata = Arc::clone(&all_metadata);
+        let metadata_origin = Arc::clone(&metadata_origin);
         let stats = Arc::clone(&stats);
         let provider_bar = provider_bars[original_idx].clone();
         let domains = domains.clone();
+        let retry_cancel = cancel.clone();
+        let start_cancel = cancel.clone();
+        let start_limit = url_limit.clone();
+        let record_cancel = cancel.clone();
+        let url_limit = url_limit.clone();
+        let fetch_log = Arc::clone(&fetch_log);
+        let domain_cap = domain_cap.clone();
+        let wait_for = (sequential && original_idx > 0).then(|| turns[original_idx - 1].clone());
+        let my_turn = turns[original_idx].clone();
 
         // Shared so each concurrent domain future can mark domain completion
         // against the run-wide progress without contending on a &mut.
@@ -318,6 +486,22 @@ pub async fn process_domains(
 
         // Spawn a task for this provider
         let provider_future = task::spawn(async move {
+            // Hand over to the next provider however this task ends.
+            let _turn_done = my_turn.drop_guard();
+            if let Some(previous) = wait_for {
+                // A run that ends while we wait leaves this provider's bar
+                // to the end-of-run pass below. So does a predecessor that
+                // met --max-urls: the run is about to stop.
+                if start_cancel
+                    .run_until_cancelled(previous.cancelled())
+                    .await
+                    .is_none()
+                    || start_limit.is_cancelled()
+                {
+                    return;
+                }
+            }
+
             let provider = Arc::new(provider_clone);
             // Running totals are atomics so the concurrent domain futures below
             // can update them; read back for an honest end-of-run summary.
@@ -334,6 +518,17 @@ pub async fn process_domains(
             let summary_urls = Arc::clone(&url_total);
             let summary_errs = Arc::clone(&err_total);
             let summary_partials = Arc::clone(&partial_total);
+            let breaker = Arc::new(CircuitBreaker::new(circuit_threshold));
+            let summary_breaker = Arc::clone(&breaker);
+
+            // (domain, provider) pairs that failed outright on a non-final
+            // try, re-queued after this provider's pass (--retry-failed-domains).
+            let failed: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
+            let retry_queue = Arc::clone(&failed);
+            let retry_errs = Arc::clone(&err_total);
+            let retry_stats = Arc::clone(&stats);
+            let retry_done = Arc::clone(&done);
+            let retry_completion = Arc::clone(&completion_ctx);
 
             // Prime the line. In aggregate mode the elapsed timer measures the
             // whole provider run; rich mode resets it per domain below.
@@ -347,169 +542,336 @@ pub async fn process_domains(
                 provider_bar.tick();
             }
 
-            stream::iter(domains)
-                .map(move |domain| {
-                    let provider = Arc::clone(&provider);
-                    let provider_bar = provider_bar.clone();
-                    let provider_name = provider_name.clone();
-                    let all_urls = Arc::clone(&all_urls);
-                    let stats = Arc::clone(&stats);
-                    let completion_ctx = Arc::clone(&completion_ctx);
-                    let url_total = Arc::clone(&url_total);
-                    let err_total = Arc::clone(&err_total);
-                    let partial_total = Arc::clone(&partial_total);
-                    let done = Arc::clone(&done);
-
-                    async move {
-                        let prefix = format!("{domain} · ");
-
-                        // Rich mode: the reporter drives the visible line with
-                        // live page-by-page detail and re-arms the spinner.
-                        // Aggregate mode: it only carries the partial-result
-                        // flag (a hidden bar) so concurrent domains don't fight
-                        // over the single line; --silent suppresses it entirely.
-                        let reporter = if silent {
-                            None
-                        } else if rich {
-                            provider_bar.set_style(provider_running_style());
-                            provider_bar.set_prefix(format!("{provider_name:<16}"));
-                            provider_bar.reset_elapsed();
-                            provider_bar.set_message(format!("{prefix}fetching…"));
-                            if !no_progress {
-                                provider_bar.tick();
+            let fetch_domain = move |(domain, last_try): (String, bool)| {
+                let provider = Arc::clone(&provider);
+                let provider_bar = provider_bar.clone();
+                let provider_name = provider_name.clone();
+                let all_urls = Arc::clone(&all_urls);
+                let all_metadata = Arc::clone(&all_metadata);
+                let metadata_origin = Arc::clone(&metadata_origin);
+                let stats = Arc::clone(&stats);
+                let completion_ctx = Arc::clone(&completion_ctx);
+                let url_total = Arc::clone(&url_total);
+                let err_total = Arc::clone(&err_total);
+                let partial_total = Arc::clone(&partial_total);
+                let done = Arc::clone(&done);
+                let failed = Arc::clone(&failed);
+                let url_limit = url_limit.clone();
+                let fetch_log = Arc::clone(&fetch_log);
+                let record_cancel = record_cancel.clone();
+                let breaker = Arc::clone(&breaker);
+                let domain_cap = domain_cap.clone();
+
+                async move {
+                    // Circuit open, daily quota spent, or the domain already
+                    // has its --max-urls-per-domain: skip without touching
+                    // the network, but still count the domain as done so
+                    // progress completes.
+                    let capped = domain_cap.as_ref().is_some_and(|c| c.is_reached(&domain));
+                    let quota_spent = provider.quota().is_some_and(|q| q.is_spent());
+                    if capped || quota_spent || breaker.is_open() {
+                        if quota_spent {
+                            lock_ignore_poison(&stats)[original_idx].quota_skipped_count += 1;
+                        } else if capped {
+                            if verbose && !silent {
+                                println!(
+                                    "  - {provider_name}: Skipped {domain}, --max-urls-per-domain reached"
+                                );
                             }
-                            Some(ProgressReporter::new(provider_bar.clone(), prefix.clone()))
+                            lock_ignore_poison(&stats)[original_idx].capped_count += 1;
                         } else {
-                            Some(ProgressReporter::new(ProgressBar::hidden(), prefix.clone()))
-                        };
-
-                        // Fetch URLs for this domain using this provider.
-                        let fetch_start = std::time::Instant::now();
-                        let fetch_result = provider
-                            .fetch_urls_with_progress(&domain, reporter.clone())
-                            .await;
-                        let fetch_elapsed = fetch_start.elapsed();
-                        match fetch_result {
-                            Ok(urls) => {
-                                let url_count = urls.len();
-                                url_total.fetch_add(url_count, Ordering::Relaxed);
-
-                                // A *partial* result (e.g. a page failed
-                                // mid-pagination) is surfaced as a distinct,
-                                // warned state so a truncated crawl is never
-                                // mistaken for a clean success.
-                                let partial =
-                                    reporter.as_ref().is_some_and(|r| r.is_partial());
-                                if partial {
-                                    partial_total.fetch_add(1, Ordering::Relaxed);
-                                }
+                            breaker.record_skip();
+                            lock_ignore_poison(&stats)[original_idx].skipped_count += 1;
+                        }
+                        let done_n = done.fetch_add(1, Ordering::Relaxed) + 1;
+                        if !rich {
+                            tick_aggregate(
+                                &provider_bar,
+                                done_n,
+                                total,
+                                url_total.load(Ordering::Relaxed),
+                                no_progress,
+                                silent,
+                            );
+                        }
+                        completion_ctx.track(&domain);
+                        return;
+                    }
 
-                                // Add URLs to the shared map (URL -> providers).
-                                {
-                                    let mut url_map = lock_ignore_poison(&all_urls);
-                                    for url in urls {
-                                        url_map
-                                            .entry(url)
-                                            .or_default()
-                                            .insert(provider_name.clone());
-                                    }
+                    let prefix = format!("{domain} · ");
+
+                    // Rich mode: the reporter drives the visible line with
+                    // live page-by-page detail and re-arms the spinner.
+                    // Aggregate mode: it only carries the partial-result
+                    // flag (a hidden bar) so concurrent domains don't fight
+                    // over the single line; --silent suppresses it entirely.
+                    let reporter = if silent {
+                        None
+                    } else if rich {
+                        provider_bar.set_style(provider_running_style());
+                        provider_bar.set_prefix(format!("{provider_name:<16}"));
+                        provider_bar.reset_elapsed();
+                        provider_bar.set_message(format!("{prefix}fetching…"));
+                        if !no_progress {
+                            provider_bar.tick();
+                        }
+                        Some(ProgressReporter::new(provider_bar.clone(), prefix.clone()))
+                    } else {
+                        Some(ProgressReporter::new(ProgressBar::hidden(), prefix.clone()))
+                    };
+
+                    // Fetch URLs for this domain using this provider. Under
+                    // --max-urls-per-domain the fetch runs on a copy bound
+                    // to the domain's token so the cap can stop its paging.
+                    let domain_token = domain_cap.as_ref().map(|c| c.token(&domain));
+                    let fetch_start = std::time::Instant::now();
+                    let fetch_result = match &domain_token {
+                        Some(token) => {
+                            let mut scoped = provider.clone_box();
+                            scoped.with_cancellation(token.clone());
+                            scoped
+                                .fetch_records_with_progress(&domain, reporter.clone())
+                                .await
+                        }
+                        None => {
+                            provider
+                                .fetch_records_with_progress(&domain, reporter.clone())
+                                .await
+                        }
+                    };
+                    let fetch_elapsed = fetch_start.elapsed();
+                    // Fetches cut short by cancellation would understate the
+                    // provider's yield, so they stay out of the history.
+                    let cut_short = record_cancel.is_cancelled()
+                        || domain_token.as_ref().is_some_and(|t| t.is_cancelled());
+                    if !cut_short && (fetch_result.is_ok() || last_try) {
+                        lock_ignore_poison(&fetch_log).push(FetchRecord {
+                            domain: domain.clone(),
+                            provider: provider_name.clone(),
+                            urls: fetch_result.as_ref().map_or(0, |r| r.len()),
+                            elapsed: fetch_elapsed,
+                        });
+                    }
+                    match &fetch_result {
+                        Ok(_) => breaker.record_success(),
+                        Err(e) => {
+                            if breaker.record_failure() {
+                                lock_ignore_poison(&stats)[original_idx].circuit_open = true;
+                                if verbose && !silent {
+                                    eprintln!(
+                                        "Circuit breaker opened for {provider_name} after {circuit_threshold} consecutive failures (last: {e}); skipping its remaining domains"
+                                    );
                                 }
+                            }
+                        }
+                    }
+                    match fetch_result {
+                        Ok(records) => {
+                            let url_count = records.len();
+                            url_total.fetch_add(url_count, Ordering::Relaxed);
+
+                            // A *partial* result (e.g. a page failed
+                            // mid-pagination) is surfaced as a distinct,
+                            // warned state so a truncated crawl is never
+                            // mistaken for a clean success.
+                            let partial = reporter.as_ref().is_some_and(|r| r.is_partial());
+                            if partial {
+                                partial_total.fetch_add(1, Ordering::Relaxed);
+                            }
 
-                                // Update per-provider stats.
-                                {
-                                    let mut s = lock_ignore_poison(&stats);
-                                    s[original_idx].url_count += url_count;
-                                    if partial {
-                                        s[original_idx].partial_count += 1;
+                            // Add URLs to the shared map (URL -> providers).
+                            {
+                                let mut url_map = lock_ignore_poison(&all_urls);
+                                let mut meta_map = lock_ignore_poison(&all_metadata);
+                                let mut new_urls = 0;
+                                for UrlRecord { url, metadata } in records {
+                                    if !metadata.is_empty() {
+                                        let entry = meta_map.entry(url.clone()).or_default();
+                                        for (key, value) in metadata {
+                                            if deterministic {
+                                                let mut origin =
+                                                    lock_ignore_poison(&metadata_origin);
+                                                let owner = origin
+                                                    .entry((url.clone(), key.clone()))
+                                                    .or_insert(original_idx);
+                                                if *owner < original_idx {
+                                                    continue;
+                                                }
+                                                *owner = original_idx;
+                                                entry.insert(key, value);
+                                            } else {
+                                                entry.entry(key).or_insert(value);
+                                            }
+                                        }
                                     }
-                                    s[original_idx].elapsed += fetch_elapsed;
-                                }
-
-                                let done_n = done.fetch_add(1, Ordering::Relaxed) + 1;
-                                if rich {
-                                    if partial {
-                                        provider_bar.set_style(provider_partial_style());
-                                        provider_bar
-                                            .set_prefix(format!("◐ {provider_name:<16}"));
-                                        provider_bar.set_message(format!(
-                                            "{domain} · {} URLs (partial)",
-                                            fmt_count(url_count)
-                                        ));
-                                    } else {
-                                        provider_bar.set_style(provider_success_style());
-                                        provider_bar
-                                            .set_prefix(format!("✓ {provider_name:<16}"));
-                                        provider_bar.set_message(format!(
-                                            "{domain} · {} URLs",
-                                            fmt_count(url_count)
-                                        ));
+                                    let sources = url_map.entry(url).or_default();
+                                    if sources.is_empty() {
+                                        new_urls += 1;
                                     }
-                                    provider_bar.tick();
-                                    if partial && verbose && !silent {
-                                        eprintln!(
-                                            "Warning: partial results for {domain} from {provider_name}: a request failed mid-fetch; returning {url_count} URL(s) collected so far"
+                                    sources.insert(provider_name.clone());
+                                }
+                                if let Some(cap) = &domain_cap {
+                                    if cap.record(&domain, new_urls) && verbose && !silent {
+                                        println!(
+                                            "--max-urls-per-domain reached for {domain}; skipping its remaining provider requests"
                                         );
                                     }
-                                } else {
-                                    tick_aggregate(
-                                        &provider_bar,
-                                        done_n,
-                                        total,
-                                        url_total.load(Ordering::Relaxed),
-                                        no_progress,
-                                        silent,
-                                    );
                                 }
-
-                                completion_ctx.track(&domain);
-
-                                if verbose && !silent {
-                                    println!(
-                                        "  - {provider_name}: Found {url_count} URLs for {domain}"
-                                    );
+                                if max_urls.is_some_and(|max| url_map.len() >= max) {
+                                    url_limit.cancel();
                                 }
                             }
-                            Err(e) => {
-                                err_total.fetch_add(1, Ordering::Relaxed);
 
-                                {
-                                    let mut s = lock_ignore_poison(&stats);
-                                    s[original_idx].error_count += 1;
-                                    s[original_idx].elapsed += fetch_elapsed;
+                            // Update per-provider stats.
+                            {
+                                crate::metrics::record_urls(&provider_name, url_count);
+                                let mut s = lock_ignore_poison(&stats);
+                                s[original_idx].url_count += url_count;
+                                if partial {
+                                    s[original_idx].partial_count += 1;
                                 }
+                                s[original_idx].elapsed += fetch_elapsed;
+                            }
 
-                                let done_n = done.fetch_add(1, Ordering::Relaxed) + 1;
-                                if rich {
-                                    provider_bar.set_style(provider_error_style());
-                                    provider_bar.set_prefix(format!("✗ {provider_name:<16}"));
-                                    provider_bar
-                                        .set_message(format!("{domain} · {}", short_error(&e)));
-                                    provider_bar.tick();
+                            let done_n = done.fetch_add(1, Ordering::Relaxed) + 1;
+                            if rich {
+                                if partial {
+                                    provider_bar.set_style(provider_partial_style());
+                                    provider_bar.set_prefix(format!("◐ {provider_name:<16}"));
+                                    provider_bar.set_message(format!(
+                                        "{domain} · {} URLs (partial)",
+                                        fmt_count(url_count)
+                                    ));
                                 } else {
-                                    tick_aggregate(
-                                        &provider_bar,
-                                        done_n,
-                                        total,
-                                        url_total.load(Ordering::Relaxed),
-                                        no_progress,
-                                        silent,
-                                    );
+                                    provider_bar.set_style(provider_success_style());
+                                    provider_bar.set_prefix(format!("✓ {provider_name:<16}"));
+                                    provider_bar.set_message(format!(
+                                        "{domain} · {} URLs",
+                                        fmt_count(url_count)
+                                    ));
                                 }
+                                provider_bar.tick();
+                                if partial && verbose && !silent {
+                                    eprintln!(
+                                            "Warning: partial results for {domain} from {provider_name}: a request failed mid-fetch; returning {url_count} URL(s) collected so far"
+                                        );
+                                }
+                            } else {
+                                tick_aggregate(
+                                    &provider_bar,
+                                    done_n,
+                                    total,
+                                    url_total.load(Ordering::Relaxed),
+                                    no_progress,
+                                    silent,
+                                );
+                            }
 
-                                completion_ctx.track(&domain);
+                            completion_ctx.track(&domain);
 
-                                if verbose && !silent {
-                                    eprintln!(
-                                        "Error fetching URLs for {domain} from {provider_name}: {e}"
+                            if verbose && !silent {
+                                println!(
+                                    "  - {provider_name}: Found {url_count} URLs for {domain}"
+                                );
+                            }
+                        }
+                        Err(e) if !last_try => {
+                            // Not final yet: queue the pair for another go
+                            // at the end of the pass instead of recording
+                            // the failure.
+                            {
+                                let mut s = lock_ignore_poison(&stats);
+                                s[original_idx].elapsed += fetch_elapsed;
+                            }
+                            if rich {
+                                provider_bar
+                                    .set_message(format!("{domain} · failed, retry queued"));
+                                provider_bar.tick();
+                            }
+                            if verbose && !silent {
+                                eprintln!(
+                                        "Error fetching URLs for {domain} from {provider_name}: {e} (will retry)"
                                     );
-                                }
+                            }
+                            lock_ignore_poison(&failed).push(domain);
+                        }
+                        Err(e) => {
+                            err_total.fetch_add(1, Ordering::Relaxed);
+
+                            {
+                                crate::metrics::record_provider_error(&provider_name);
+                                let mut s = lock_ignore_poison(&stats);
+                                s[original_idx].error_count += 1;
+                                s[original_idx].elapsed += fetch_elapsed;
+                            }
+
+                            let done_n = done.fetch_add(1, Ordering::Relaxed) + 1;
+                            if rich {
+                                provider_bar.set_style(provider_error_style());
+                                provider_bar.set_prefix(format!("✗ {provider_name:<16}"));
+                                provider_bar.set_message(format!("{domain} · {}", short_error(&e)));
+                                provider_bar.tick();
+                            } else {
+                                tick_aggregate(
+                                    &provider_bar,
+                                    done_n,
+                                    total,
+                                    url_total.load(Ordering::Relaxed),
+                                    no_progress,
+                                    silent,
+                                );
+                            }
+
+                            completion_ctx.track(&domain);
+
+                            if verbose && !silent {
+                                eprintln!(
+                                    "Error fetching URLs for {domain} from {provider_name}: {e}"
+                                );
                             }
                         }
                     }
-                })
-                .buffer_unordered(effective_parallel)
-                .collect::<Vec<()>>()
-                .await;
+                }
+            };
+
+            let mut queue = domains;
+            let mut round = 0;
+            loop {
+                let last_try = round >= retry_failed;
+                stream::iter(queue.into_iter().map(|d| (d, last_try)))
+                    .map(&fetch_domain)
+                    .buffer_unordered(effective_parallel)
+                    .collect::<Vec<()>>()
+                    .await;
+
+                queue = std::mem::take(&mut *lock_ignore_poison(&retry_queue));
+                if queue.is_empty() {
+                    break;
+                }
+                round += 1;
+                if verbose && !silent {
+                    println!(
+                        "Retrying {} failed domain(s) for {summary_name} (round {round}/{retry_failed})",
+                        queue.len()
+                    );
+                }
+                // Fresh backoff before each round, cut short by cancellation;
+                // a cancelled run records what's left as failed instead.
+                let backoff = RETRY_FAILED_BACKOFF * round;
+                if retry_cancel
+                    .run_until_cancelled(tokio::time::sleep(backoff))
+                    .await
+                    .is_none()
+                {
+                    for domain in queue {
+                        retry_errs.fetch_add(1, Ordering::Relaxed);
+                        lock_ignore_poison(&retry_stats)[original_idx].error_count += 1;
+                        retry_done.fetch_add(1, Ordering::Relaxed);
+                        retry_completion.track(&domain);
+                    }
+                    break;
+                }
+            }
 
             // Freeze this provider's line on a one-line summary that reflects
             // what actually happened across all of its domains.
@@ -518,11 +880,17 @@ pub async fn process_domains(
             let provider_url_total = summary_urls.load(Ordering::Relaxed);
             let provider_err_total = summary_errs.load(Ordering::Relaxed);
             let provider_partial_total = summary_partials.load(Ordering::Relaxed);
+            let circuit_note = if summary_breaker.is_open() {
+                format!(" · circuit open, {} skipped", summary_breaker.skipped())
+            } else {
+                String::new()
+            };
             if provider_url_total == 0 && provider_err_total > 0 {
                 provider_bar.set_style(provider_error_style());
                 provider_bar.set_prefix(format!("✗ {provider_name:<16}"));
-                provider_bar
-                    .finish_with_message(format!("all {provider_err_total} fetch(es) failed"));
+                provider_bar.finish_with_message(format!(
+                    "all {provider_err_total} fetch(es) failed{circuit_note}"
+                ));
             } else {
                 // A partial anywhere keeps the line amber so the run doesn't
                 // read as a clean, complete success at a glance.
@@ -544,6 +912,7 @@ pub async fn process_domains(
                 if provider_err_total > 0 {
                     summary.push_str(&format!(" · {provider_err_total} error(s)"));
                 }
+                summary.push_str(&circuit_note);
                 provider_bar.finish_with_message(summary);
             }
 
@@ -555,23 +924,27 @@ pub async fn process_domains(
         provider_futures.push(provider_future);
     }
 
-    // Wait for all provider tasks to finish, honouring both --max-time and a
-    // Ctrl-C interrupt. Abort handles are grabbed up front so either trigger can
-    // cancel in-flight tasks while we keep whatever URLs they have already
-    // pushed into the shared map — an interrupted run still produces output and
-    // a summary instead of dying with nothing.
+    // Wait for all provider tasks to finish, honouring --max-time,
+    // --max-urls, a spent --max-requests budget, and a Ctrl-C interrupt. Any
+    // trigger first cancels the shared token so paginating providers can
+    // hand back what they have, then aborts whatever
+    // is still running after CANCEL_GRACE. URLs already pushed into the shared
+    // map are kept — an interrupted run still produces output and a summary
+    // instead of dying with nothing.
     let abort_handles: Vec<_> = provider_futures.iter().map(|h| h.abort_handle()).collect();
     let join_future = join_all(provider_futures);
+    tokio::pin!(join_future);
     let deadline = (args.max_time > 0).then(|| std::time::Duration::from_secs(args.max_time));
 
     enum RunEnd {
         Completed,
         TimedOut,
+        UrlLimitReached,
+        BudgetExhausted,
         Interrupted,
     }
 
     let run_end = {
-        tokio::pin!(join_future);
         // A deadline that simply never fires when --max-time isn't set.
         let timeout = async {
             match deadline {
@@ -580,26 +953,35 @@ pub async fn process_domains(
             }
         };
         tokio::pin!(timeout);
+        let exhausted = async {
+            match request_gate {
+                Some(gate) => gate.exhausted().await,
+                None => std::future::pending::<()>().await,
+            }
+        };
+        tokio::pin!(exhausted);
         tokio::select! {
             _ = &mut join_future => RunEnd::Completed,
             _ = &mut timeout => RunEnd::TimedOut,
-            // First Ctrl-C becomes a graceful stop. If signal registration
-            // fails we fall back to never firing, so the run isn't spuriously
-            // marked interrupted.
-            _ = async {
-                if tokio::signal::ctrl_c().await.is_err() {
-                    std::future::pending::<()>().await;
-                }
-            } => RunEnd::Interrupted,
+            _ = url_limit.cancelled() => RunEnd::UrlLimitReached,
+            _ = &mut exhausted => RunEnd::BudgetExhausted,
+            // First Ctrl-C becomes a graceful stop, even if it came before
+            // the providers started.
+            _ = crate::utils::interrupt::interrupted() => RunEnd::Interrupted,
         }
     };
 
+    // Under --smart-order the providers left waiting when --max-urls is met
+    // return at once, so the join can win the race; they still need the
+    // limit's bookkeeping below.
+    let run_end = match run_end {
+        RunEnd::Completed if url_limit.is_cancelled() => RunEnd::UrlLimitReached,
+        run_end => run_end,
+    };
+
     match &run_end {
         RunEnd::Completed => {}
         RunEnd::TimedOut => {
-            for h in &abort_handles {
-                h.abort();
-            }
             if !args.silent {
                 progress_manager.note(format!(
                     "[urx] --max-time {}s elapsed; aborting in-flight provider fetches and returning partial results",
@@ -607,32 +989,53 @@ pub async fn process_domains(
                 ));
             }
         }
-        RunEnd::Interrupted => {
-            for h in &abort_handles {
-                h.abort();
+        RunEnd::UrlLimitReached => {
+            if !args.silent {
+                progress_manager.note(format!(
+                    "[urx] --max-urls {} reached; stopping provider enumeration",
+                    max_urls.unwrap_or(0)
+                ));
             }
+        }
+        RunEnd::BudgetExhausted => {
+            if !args.silent {
+                progress_manager.note(format!(
+                    "[urx] --max-requests budget of {} requests spent; returning partial results",
+                    request_gate.and_then(|g| g.budget()).unwrap_or(0)
+                ));
+            }
+        }
+        RunEnd::Interrupted => {
             if !args.silent {
                 progress_manager.note(
                     "[urx] interrupted (Ctrl-C); returning URLs collected so far — press Ctrl-C again to force quit",
                 );
             }
-            // The rest of the pipeline (output, optional testing) can still take
-            // a while, so a second Ctrl-C force-quits.
-            tokio::spawn(async {
-                if tokio::signal::ctrl_c().await.is_ok() {
-                    std::process::exit(130);
-                }
-            });
+        }
+    }
+
+    // Announce first (above), then give providers a moment to return their
+    // partial pages before aborting the stragglers.
+    if !matches!(run_end, RunEnd::Completed) {
+        cancel.cancel();
+        if tokio::time::timeout(CANCEL_GRACE, &mut join_future)
+            .await
+            .is_err()
+        {
+            for h in &abort_handles {
+                h.abort();
+            }
         }
     }
 
     // A timeout/interrupt leaves the provider(s) that were mid-fetch on a
     // spinning "fetching…" line; freeze them so the final display is honest.
     if !matches!(run_end, RunEnd::Completed) {
-        let label = if matches!(run_end, RunEnd::TimedOut) {
-            "timed out"
-        } else {
-            "interrupted"
+        let label = match run_end {
+            RunEnd::TimedOut => "timed out",
+            RunEnd::UrlLimitReached => "stopped at --max-urls",
+            RunEnd::BudgetExhausted => "budget exhausted",
+            _ => "interrupted",
         };
         for (i, bar) in provider_bars.iter().enumerate() {
             if !bar.is_finished() {
@@ -648,6 +1051,10 @@ pub async fn process_domains(
     match run_end {
         RunEnd::Completed => overall_bar.finish_with_message("All domains processed"),
         RunEnd::TimedOut => overall_bar.finish_with_message("Stopped by --max-time deadline"),
+        RunEnd::UrlLimitReached => overall_bar.finish_with_message("Stopped at --max-urls limit"),
+        RunEnd::BudgetExhausted => {
+            overall_bar.finish_with_message("Stopped by --max-requests budget")
+        }
         RunEnd::Interrupted => overall_bar.finish_with_message("Interrupted by Ctrl-C"),
     }
 
@@ -666,5 +1073,40 @@ pub async fn process_domains(
             .unwrap_or_else(|poisoned| poisoned.into_inner()),
         Err(arc) => lock_ignore_poison(&arc).clone(),
     };
-    ProviderRunResult { urls, stats }
+    let metadata = match Arc::try_unwrap(all_metadata) {
+        Ok(m) => m
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        Err(arc) => lock_ignore_poison(&arc).clone(),
+    };
+    // Tripped breakers and spent quotas are worth a line even without
+    // --stats: those providers contributed nothing for the domains they
+    // skipped.
+    if !args.silent {
+        for s in stats.iter().filter(|s| s.circuit_open) {
+            progress_manager.note(format!(
+                "[urx] circuit breaker opened for {} after {} consecutive failures; skipped {} domain(s)",
+                s.name, circuit_threshold, s.skipped_count
+            ));
+        }
+        for s in stats.iter().filter(|s| s.quota_skipped_count > 0) {
+            progress_manager.note(format!(
+                "[urx] daily quota for {} is spent; skipped {} domain(s)",
+                s.name, s.quota_skipped_count
+            ));
+        }
+    }
+
+    let fetches = match Arc::try_unwrap(fetch_log) {
+        Ok(f) => f
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        Err(arc) => lock_ignore_poison(&arc).clone(),
+    };
+    ProviderRunResult {
+        urls,
+        stats,
+        metadata,
+        fetches,
+    }
 }
@@ -0,0 +1,300 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use super::ProviderStats;
+use crate::output::UrlData;
+
+/// Extensions listed in the text summary; the JSON one has them all.
+const TOP_EXTENSIONS: usize = 10;
+
+/// One provider's row of the summary.
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderSummary {
+    pub name: String,
+    pub urls: usize,
+    pub partial: usize,
+    pub errors: usize,
+    /// Domains skipped by the circuit breaker, `--max-urls-per-domain` or
+    /// `--provider-quota`.
+    pub skipped: usize,
+    pub elapsed_ms: u128,
+}
+
+/// Result-cache lookups of the run, one per (domain, provider).
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CacheSummary {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// End-of-run summary for `--stats` and `--stats-json`: what each provider
+/// returned, how many URLs the filters kept, and what the output holds by
+/// status class and extension.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub runtime_ms: u128,
+    pub providers: Vec<ProviderSummary>,
+    /// Unique URLs the providers (or input files) returned.
+    pub collected: usize,
+    /// URLs left after `--since`, the filters and the transformations.
+    pub after_filters: usize,
+    /// URLs written out, after testing (which may drop or discover some).
+    pub output: usize,
+    /// Output URLs by status class (`2xx`, …, `other`), when tested.
+    pub status_classes: BTreeMap<String, usize>,
+    /// Output URLs by lowercase file extension, `(none)` for none.
+    pub extensions: BTreeMap<String, usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CacheSummary>,
+}
+
+fn format_elapsed(ms: u128) -> String {
+    if ms >= 1000 {
+        format!("{:.2}s", ms as f64 / 1000.0)
+    } else {
+        format!("{ms}ms")
+    }
+}
+
+/// Extension of the last path segment of `url`, if it looks like one.
+fn url_extension(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let last = parsed.path_segments()?.next_back()?;
+    let (_, ext) = last.rsplit_once('.')?;
+    (!ext.is_empty() && ext.len() <= 10 && ext.chars().all(|c| c.is_ascii_alphanumeric()))
+        .then(|| ext.to_ascii_lowercase())
+}
+
+impl RunSummary {
+    pub fn new(
+        stats: &[ProviderStats],
+        collected: usize,
+        after_filters: usize,
+        urls: &[UrlData],
+        cache: Option<CacheSummary>,
+        runtime: Duration,
+    ) -> Self {
+        let providers = stats
+            .iter()
+            .map(|s| ProviderSummary {
+                name: s.name.clone(),
+                urls: s.url_count,
+                partial: s.partial_count,
+                errors: s.error_count,
+                skipped: s.skipped_count + s.capped_count + s.quota_skipped_count,
+                elapsed_ms: s.elapsed.as_millis(),
+            })
+            .collect();
+        let mut status_classes = BTreeMap::new();
+        let mut extensions = BTreeMap::new();
+        for entry in urls {
+            if let Some(status) = &entry.status {
+                let class = match status.chars().next() {
+                    Some(c @ '1'..='5') => format!("{c}xx"),
+                    _ => "other".to_string(),
+                };
+                *status_classes.entry(class).or_insert(0) += 1;
+            }
+            let ext = url_extension(&entry.url).unwrap_or_else(|| "(none)".to_string());
+            *extensions.entry(ext).or_insert(0) += 1;
+        }
+        RunSummary {
+            runtime_ms: runtime.as_millis(),
+            providers,
+            collected,
+            after_filters,
+            output: urls.len(),
+            status_classes,
+            extensions,
+            cache,
+        }
+    }
+
+    /// The summary as stderr tables.
+    pub fn render_text(&self) -> String {
+        let mut out = String::new();
+        if !self.providers.is_empty() {
+            let _ = writeln!(out, "\nProvider stats:");
+            let _ = writeln!(
+                out,
+                "  {:<18}  {:>8}  {:>8}  {:>7}  {:>7}  {:>10}",
+                "provider", "urls", "partial", "errors", "skipped", "elapsed"
+            );
+            let _ = writeln!(
+                out,
+                "  {:<18}  {:>8}  {:>8}  {:>7}  {:>7}  {:>10}",
+                "------------------", "--------", "--------", "-------", "-------", "----------"
+            );
+            for p in &self.providers {
+                let _ = writeln!(
+                    out,
+                    "  {:<18}  {:>8}  {:>8}  {:>7}  {:>7}  {:>10}",
+                    p.name,
+                    p.urls,
+                    p.partial,
+                    p.errors,
+                    p.skipped,
+                    format_elapsed(p.elapsed_ms)
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\nRun summary:");
+        let _ = writeln!(out, "  {:<14}  {}", "collected", self.collected);
+        let _ = writeln!(
+            out,
+            "  {:<14}  {} ({} filtered out)",
+            "after filters",
+            self.after_filters,
+            self.collected.saturating_sub(self.after_filters)
+        );
+        let _ = writeln!(out, "  {:<14}  {}", "output", self.output);
+        if let Some(cache) = self.cache {
+            let _ = writeln!(
+                out,
+                "  {:<14}  {} hit(s), {} miss(es)",
+                "cache", cache.hits, cache.misses
+            );
+        }
+        let _ = writeln!(
+            out,
+            "  {:<14}  {}",
+            "runtime",
+            format_elapsed(self.runtime_ms)
+        );
+
+        if !self.status_classes.is_empty() {
+            let classes: Vec<String> = self
+                .status_classes
+                .iter()
+                .map(|(class, n)| format!("{class} {n}"))
+                .collect();
+            let _ = writeln!(out, "  {:<14}  {}", "status", classes.join(", "));
+        }
+        if !self.extensions.is_empty() {
+            let mut by_count: Vec<(&String, &usize)> = self.extensions.iter().collect();
+            by_count.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            let mut top: Vec<String> = by_count
+                .iter()
+                .take(TOP_EXTENSIONS)
+                .map(|(ext, n)| format!("{ext} {n}"))
+                .collect();
+            if by_count.len() > TOP_EXTENSIONS {
+                top.push(format!("… {} more", by_count.len() - TOP_EXTENSIONS));
+            }
+            let _ = writeln!(out, "  {:<14}  {}", "extensions", top.join(", "));
+        }
+        out
+    }
+
+    /// The summary as a Markdown document for `--stats-markdown`, e.g. to
+    /// paste into a report or ticket.
+    pub fn render_markdown(&self) -> String {
+        let mut out = String::from("# urx run summary\n\n");
+        out.push_str("| | |\n|---|---:|\n");
+        let _ = writeln!(out, "| Collected | {} |", self.collected);
+        let _ = writeln!(out, "| After filters | {} |", self.after_filters);
+        let _ = writeln!(out, "| Output | {} |", self.output);
+        if let Some(cache) = self.cache {
+            let _ = writeln!(
+                out,
+                "| Cache hits / misses | {} / {} |",
+                cache.hits, cache.misses
+            );
+        }
+        let _ = writeln!(out, "| Runtime | {} |", format_elapsed(self.runtime_ms));
+
+        if !self.providers.is_empty() {
+            out.push_str("\n## Providers\n\n");
+            out.push_str("| Provider | URLs | Partial | Errors | Skipped | Elapsed |\n");
+            out.push_str("|---|---:|---:|---:|---:|---:|\n");
+            for p in &self.providers {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} | {} | {} | {} |",
+                    p.name,
+                    p.urls,
+                    p.partial,
+                    p.errors,
+                    p.skipped,
+                    format_elapsed(p.elapsed_ms)
+                );
+            }
+        }
+
+        if !self.status_classes.is_empty() {
+            out.push_str("\n## Status classes\n\n| Class | URLs |\n|---|---:|\n");
+            for (class, n) in &self.status_classes {
+                let _ = writeln!(out, "| {class} | {n} |");
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_summary_counts_output() {
+        let stats = [ProviderStats {
+            name: "Wayback Machine".into(),
+            url_count: 4,
+            error_count: 1,
+            elapsed: Duration::from_millis(1500),
+            ..Default::default()
+        }];
+        let urls = [
+            UrlData::with_status("https://example.com/app.JS".into(), "200 OK".into()),
+            UrlData::with_status(
+                "https://example.com/a.js?v=1".into(),
+                "404 Not Found".into(),
+            ),
+            UrlData::with_status("https://example.com/login".into(), "timeout".into()),
+            UrlData::new("https://example.com/v1.2/".into()),
+        ];
+        let summary = RunSummary::new(
+            &stats,
+            10,
+            4,
+            &urls,
+            Some(CacheSummary { hits: 2, misses: 1 }),
+            Duration::from_millis(2500),
+        );
+
+        assert_eq!(summary.output, 4);
+        assert_eq!(
+            summary.status_classes,
+            BTreeMap::from([
+                ("2xx".to_string(), 1),
+                ("4xx".to_string(), 1),
+                ("other".to_string(), 1)
+            ])
+        );
+        assert_eq!(
+            summary.extensions,
+            BTreeMap::from([("js".to_string(), 2), ("(none)".to_string(), 2)])
+        );
+
+        let text = summary.render_text();
+        assert!(text.contains("Wayback Machine"));
+        assert!(text.contains("1.50s"));
+        assert!(text.contains("4 (6 filtered out)"));
+        assert!(text.contains("2 hit(s), 1 miss(es)"));
+        assert!(text.contains("(none) 2, js 2"));
+
+        let markdown = summary.render_markdown();
+        assert!(markdown.contains("| After filters | 4 |"));
+        assert!(markdown.contains("| Cache hits / misses | 2 / 1 |"));
+        assert!(markdown.contains("| Wayback Machine | 4 | 0 | 1 | 0 | 1.50s |"));
+        assert!(markdown.contains("| 4xx | 1 |"));
+
+        let json = serde_json::to_value(&summary).unwrap();
+        assert_eq!(json["providers"][0]["errors"], 1);
+        assert_eq!(json["cache"]["hits"], 2);
+        assert_eq!(json["runtime_ms"], 2500);
+    }
+}
@@ -1,16 +1,37 @@
-use futures::stream::{self, StreamExt};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
 
 use crate::cli::Args;
+use crate::error::UrxError;
 use crate::network::{NetworkScope, NetworkSettings};
 use crate::output;
 use crate::progress::ProgressManager;
-use crate::testers::Tester;
+use crate::testers::{
+    annotate_js_endpoint, annotate_open_redirect, annotate_reflection, annotate_sourcemap,
+    annotate_tls, status_entry, Tester,
+};
 use crate::utils::verbose_print;
 
+mod robots;
+mod sampling;
+mod scope;
+
+pub use robots::RobotsPolicy;
+pub use scope::DiscoveryScope;
+
 /// Helper function to apply network settings to a tester
 pub fn apply_network_settings_to_tester(tester: &mut dyn Tester, settings: &NetworkSettings) {
+    // The run-wide gate ignores --network-scope
+    if let Some(gate) = &settings.request_gate {
+        tester.with_request_gate(gate.clone());
+    }
+    // So is the body cap: it guards memory, not the network
+    tester.with_max_body_bytes(settings.max_body_bytes);
+    tester.with_http_version(settings.http_version);
+
     // Skip applying settings if network scope doesn't include testers
     if settings.scope == NetworkScope::Providers {
         return;
@@ -30,14 +51,49 @@ pub fn apply_network_settings_to_tester(tester: &mut dyn Tester, settings: &Netw
     }
 }
 
+/// What a tester passed to [`process_urls_with_testers`] does, which decides
+/// where its results go in the URL's entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TesterRole {
+    Status,
+    Links,
+    Title,
+    Tls,
+    SourceMap,
+    JsEndpoints,
+    OpenRedirect,
+    Reflection,
+}
+
+/// Status reported for URLs `--respect-robots` kept from being requested.
+const ROBOTS_DISALLOWED: &str = "Disallowed by robots.txt";
+
+/// What every URL of a run is tested for, shared by the per-URL tasks.
+struct TestPlan {
+    /// Whether a URL whose status check failed is reported as such
+    check_status: bool,
+    report_errors: bool,
+    /// Which discovered URLs are kept; `None` keeps them all
+    scope: Option<DiscoveryScope>,
+    /// With `--respect-robots`, URLs robots.txt disallows are not requested
+    robots: Option<RobotsPolicy>,
+    /// A status or body filter decides which URLs are kept
+    filtered: bool,
+}
+
 /// Process URLs with tester components (status checker, link extractor, etc.)
+///
+/// Each tester comes paired with the [`TesterRole`] its results fill in.
 pub async fn process_urls_with_testers(
     transformed_urls: Vec<String>,
     args: &Args,
     progress_manager: &ProgressManager,
-    testers: Vec<Box<dyn Tester>>,
+    testers: Vec<(TesterRole, Box<dyn Tester>)>,
     should_check_status: bool,
+    network_settings: &NetworkSettings,
+    scope: Option<DiscoveryScope>,
 ) -> Vec<output::UrlData> {
+    let request_gate = network_settings.request_gate.as_ref();
     verbose_print(args, "Applying testing options...");
 
     // Create progress bar for testing
@@ -46,105 +102,142 @@ pub async fn process_urls_with_testers(
 
     // Process URLs with testers.
     //
-    // Concurrency is bounded by --parallel. The previous implementation spawned
-    // one task per 10-URL chunk and launched them all at once, so a run over
-    // tens of thousands of URLs could open thousands of simultaneous
-    // connections — exhausting file descriptors and hammering the target. We
-    // instead stream URL chunks through `buffer_unordered`, keeping at most
-    // `parallel` chunks in flight at a time, and advance the progress bar as
-    // each URL actually completes (not when its task is merely scheduled).
+    // Concurrency is bounded by --parallel: each URL runs as its own task
+    // once it holds a semaphore permit, so one slow URL never holds back
+    // others queued behind it, and the testers' parsing work spreads across
+    // the runtime's worker threads. The progress bar advances as each URL
+    // actually completes (not when its task is merely scheduled).
     let parallel = args.parallel.unwrap_or(5).max(1) as usize;
     let total = transformed_urls.len() as u64;
+
+    // Ctrl-C or a spent --max-requests budget cancels in-flight requests and
+    // skips the URLs not yet tested, so the run ends with the results
+    // gathered so far.
+    let cancel = CancellationToken::new();
+    let testers: Vec<(TesterRole, Box<dyn Tester>)> = testers
+        .into_iter()
+        .map(|(role, mut tester)| {
+            tester.with_cancellation(cancel.clone());
+            (role, tester)
+        })
+        .collect();
+    let interrupt_watch = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            crate::utils::interrupt::interrupted().await;
+            cancel.cancel();
+        })
+    };
+    let budget_watch = request_gate.map(|gate| {
+        let cancel = cancel.clone();
+        let gate = gate.clone();
+        tokio::spawn(async move {
+            gate.exhausted().await;
+            cancel.cancel();
+        })
+    });
     let completed = Arc::new(AtomicU64::new(0));
 
     let verbose = args.verbose;
-    let check_status = should_check_status;
-    let extract_links = args.extract_links;
+    let plan = Arc::new(TestPlan {
+        check_status: should_check_status,
+        report_errors: verbose && !args.silent,
+        scope,
+        robots: args
+            .respect_robots
+            .then(|| RobotsPolicy::new(network_settings)),
+        filtered: should_check_status
+            && (!args.include_status.is_empty()
+                || !args.exclude_status.is_empty()
+                || args.match_body.is_some()
+                || args.filter_body.is_some()),
+    });
     let silent = args.silent;
 
-    let url_chunks: Vec<Vec<String>> = transformed_urls
-        .chunks(10)
-        .map(|chunk| chunk.to_vec())
-        .collect();
-
-    let chunk_results: Vec<Vec<output::UrlData>> =
-        stream::iter(url_chunks.into_iter().map(|url_vec| {
-            let testers_clone: Vec<_> = testers.iter().map(|t| t.clone_box()).collect();
-            let test_bar = test_bar.clone();
-            let completed = Arc::clone(&completed);
+    // --per-category-limit: the status check still covers every URL, but the
+    // other testers only see a sample per host and kind of URL.
+    let sampled = args.per_category_limit.map(|limit| {
+        let sampled = sampling::sample_per_category(&transformed_urls, limit);
+        verbose_print(
+            args,
+            format!(
+                "Running expensive testers on {} of {} URLs (--per-category-limit {limit})",
+                sampled.len(),
+                transformed_urls.len()
+            ),
+        );
+        Arc::new(sampled)
+    });
+
+    let semaphore = Arc::new(Semaphore::new(parallel));
+    let mut tasks = JoinSet::new();
+    for url in transformed_urls {
+        // Waiting for a permit before spawning keeps at most `parallel` URLs
+        // in flight without queueing a task per URL up front
+        let Ok(permit) = Arc::clone(&semaphore).acquire_owned().await else {
+            break;
+        };
+        if cancel.is_cancelled() {
+            break;
+        }
+        let url_testers: Vec<_> = testers
+            .iter()
+            .map(|(role, tester)| (*role, tester.clone_box()))
+            .collect();
+        let test_bar = test_bar.clone();
+        let completed = Arc::clone(&completed);
+        let cancel = cancel.clone();
+        let plan = Arc::clone(&plan);
+        let in_sample = sampled.as_ref().is_none_or(|s| s.contains(&url));
+
+        tasks.spawn(async move {
+            let _permit = permit;
+            let entries = test_one_url(url, &url_testers, &plan, in_sample, &cancel).await;
+            if entries.is_some() {
+                let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
+                test_bar.set_position(done.min(total));
+            }
+            entries.unwrap_or_default()
+        });
+    }
 
-            async move {
-                let mut result_urls = Vec::new();
-
-                for url in url_vec {
-                    let mut status_result = None;
-                    let mut links_result = None;
-
-                    // Process URL with each tester
-                    for (i, tester) in testers_clone.iter().enumerate() {
-                        match tester.test_url(&url).await {
-                            Ok(results) => {
-                                if i == 0 && check_status {
-                                    // Status checker results (first tester if check_status is enabled)
-                                    status_result = Some(results);
-                                } else if extract_links {
-                                    // Link extractor results
-                                    links_result = Some(results);
-                                }
-                            }
-                            Err(e) => {
-                                if verbose && !silent {
-                                    eprintln!("Error testing URL {url}: {e}");
-                                }
-                            }
-                        }
-                    }
-
-                    // Create UrlData for this URL
-                    if let Some(status_urls) = status_result {
-                        for status_url in status_urls {
-                            // Parse the status URL (format: "{url} - {status}")
-                            result_urls.push(output::UrlData::from_string(status_url));
-                        }
-                    } else {
-                        // If no status but URL should be included anyway
-                        if check_status {
-                            let url_data = output::UrlData::with_status(
-                                url.clone(),
-                                "Status check failed".to_string(),
-                            );
-                            result_urls.push(url_data);
-                        } else {
-                            let url_data = output::UrlData::new(url.clone());
-                            result_urls.push(url_data);
-                        }
-                    }
-
-                    // If we have extracted links, add them to the result
-                    if let Some(link_urls) = links_result {
-                        for link_url in link_urls {
-                            result_urls.push(output::UrlData::new(link_url));
-                        }
-                    }
-
-                    let done = completed.fetch_add(1, Ordering::Relaxed) + 1;
-                    test_bar.set_position(done.min(total));
+    let mut new_urls = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(entries) => new_urls.extend(entries),
+            Err(e) => {
+                if verbose && !silent {
+                    eprintln!("Tester task failed: {e}");
                 }
-
-                result_urls
             }
-        }))
-        .buffer_unordered(parallel)
-        .collect()
-        .await;
+        }
+    }
 
-    let mut new_urls = Vec::new();
-    for urls in chunk_results {
-        new_urls.extend(urls);
+    interrupt_watch.abort();
+    if let Some(watch) = &budget_watch {
+        watch.abort();
+    }
+    let robots_skipped = plan.robots.as_ref().map_or(0, RobotsPolicy::skipped);
+    if robots_skipped > 0 && !silent {
+        progress_manager.note(format!(
+            "[urx] --respect-robots: left {robots_skipped} URL(s) disallowed by robots.txt untested"
+        ));
+    }
+    if cancel.is_cancelled() && !silent {
+        match request_gate.filter(|g| g.is_exhausted()) {
+            Some(gate) => progress_manager.note(format!(
+                "[urx] --max-requests budget of {} requests spent; returning URLs tested so far",
+                gate.budget().unwrap_or(0)
+            )),
+            None => {
+                progress_manager.note("[urx] interrupted (Ctrl-C); returning URLs tested so far — press Ctrl-C again to force quit")
+            }
+        }
     }
 
-    // Sort URLs by their URL field
-    new_urls.sort_by(|a, b| a.url.cmp(&b.url));
+    // Sort URLs by their URL field; the status settles ties, so the order
+    // doesn't depend on which chunk finished first
+    new_urls.sort_by(|a, b| a.url.cmp(&b.url).then_with(|| a.status.cmp(&b.status)));
 
     test_bar.finish_with_message(format!("Testing complete, found {} URLs", new_urls.len()));
 
@@ -155,10 +248,128 @@ pub async fn process_urls_with_testers(
     new_urls
 }
 
+/// Run every tester on one URL and build its entries: the URL itself
+/// (with status, title and tester annotations) followed by the URLs the
+/// testers discovered and the plan's scope allows. `None` when the run was
+/// cancelled mid-way, since a request cut short says nothing about the URL.
+async fn test_one_url(
+    url: String,
+    testers: &[(TesterRole, Box<dyn Tester>)],
+    plan: &TestPlan,
+    in_sample: bool,
+    cancel: &CancellationToken,
+) -> Option<Vec<output::UrlData>> {
+    if cancel.is_cancelled() {
+        return None;
+    }
+    if let Some(robots) = &plan.robots {
+        match cancel.run_until_cancelled(robots.allows(&url)).await {
+            None => return None,
+            // Never requested, so there is no status or body for a filter
+            // to pass; the end-of-run note still counts it
+            Some(false) if plan.filtered => return Some(Vec::new()),
+            // Still reported, just never requested
+            Some(false) => {
+                return Some(vec![if plan.check_status {
+                    output::UrlData::with_status(url, ROBOTS_DISALLOWED.to_string())
+                } else {
+                    output::UrlData::new(url)
+                }]);
+            }
+            Some(true) => {}
+        }
+    }
+    let mut status_result = None;
+    let mut links_result = None;
+    let mut reflected_params = Vec::new();
+    let mut redirect_params = Vec::new();
+    let mut sourcemap_urls = Vec::new();
+    let mut js_endpoints = Vec::new();
+    let mut title = None;
+    let mut tls_lines = Vec::new();
+
+    for (role, tester) in testers {
+        // The status check covers every URL, and TLS inspection costs one
+        // connection per host, so sampling leaves both alone
+        if !in_sample && !matches!(role, TesterRole::Status | TesterRole::Tls) {
+            continue;
+        }
+        match tester.test_url(&url).await {
+            Ok(results) => match role {
+                TesterRole::Status => status_result = Some(results),
+                TesterRole::Links => links_result = Some(results),
+                TesterRole::Title => title = results.into_iter().next(),
+                TesterRole::Tls => tls_lines = results,
+                TesterRole::SourceMap => sourcemap_urls = results,
+                TesterRole::JsEndpoints => js_endpoints = results,
+                TesterRole::OpenRedirect => redirect_params = results,
+                TesterRole::Reflection => reflected_params = results,
+            },
+            Err(e) => {
+                let e = UrxError::tester(e);
+                if plan.report_errors {
+                    eprintln!("Error testing URL {url}: {e}");
+                }
+            }
+        }
+    }
+
+    if cancel.is_cancelled() {
+        return None;
+    }
+
+    let mut result_urls = Vec::new();
+    if let Some(status_lines) = status_result {
+        // Status line plus content headers; nothing when the status was
+        // filtered out
+        result_urls.extend(status_entry(status_lines));
+    } else if plan.check_status {
+        result_urls.push(output::UrlData::with_status(
+            url.clone(),
+            "Status check failed".to_string(),
+        ));
+    } else {
+        result_urls.push(output::UrlData::new(url.clone()));
+    }
+
+    for entry in &mut result_urls {
+        annotate_reflection(&mut entry.metadata, &reflected_params);
+        annotate_open_redirect(&mut entry.metadata, &redirect_params);
+        entry.title.clone_from(&title);
+        annotate_tls(&mut entry.metadata, &tls_lines);
+    }
+
+    let in_scope = |found: &String| plan.scope.as_ref().is_none_or(|s| s.allows(&url, found));
+
+    if let Some(link_urls) = links_result {
+        result_urls.extend(
+            link_urls
+                .into_iter()
+                .filter(in_scope)
+                .map(output::UrlData::new),
+        );
+    }
+
+    for mined_url in sourcemap_urls.into_iter().filter(in_scope) {
+        let mut url_data = output::UrlData::new(mined_url);
+        annotate_sourcemap(&mut url_data.metadata, &url);
+        result_urls.push(url_data);
+    }
+
+    for endpoint in js_endpoints.into_iter().filter(in_scope) {
+        let mut url_data = output::UrlData::new(endpoint);
+        annotate_js_endpoint(&mut url_data.metadata, &url);
+        result_urls.push(url_data);
+    }
+
+    Some(result_urls)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use anyhow::Result;
+    use clap::Parser;
     use std::future::Future;
     use std::pin::Pin;
 
@@ -217,6 +428,112 @@ mod tests {
         }
     }
 
+    /// Records how many of its calls overlap.
+    #[derive(Clone, Default)]
+    struct ConcurrencyProbe {
+        in_flight: Arc<AtomicU64>,
+        peak: Arc<AtomicU64>,
+    }
+
+    impl Tester for ConcurrencyProbe {
+        fn clone_box(&self) -> Box<dyn Tester> {
+            Box::new(self.clone())
+        }
+
+        fn test_url<'a>(
+            &'a self,
+            _url: &'a str,
+        ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+            Box::pin(async move {
+                let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                self.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                self.in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(vec![])
+            })
+        }
+
+        fn with_timeout(&mut self, _seconds: u64) {}
+        fn with_retries(&mut self, _count: u32) {}
+        fn with_random_agent(&mut self, _enabled: bool) {}
+        fn with_insecure(&mut self, _enabled: bool) {}
+        fn with_proxy(&mut self, _proxy: Option<String>) {}
+        fn with_proxy_auth(&mut self, _auth: Option<String>) {}
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_process_urls_runs_parallel_urls_at_a_time() {
+        let args = Args::parse_from([
+            "urx",
+            "example.com",
+            "--extract-links",
+            "--parallel",
+            "3",
+            "--no-progress",
+        ]);
+        let probe = ConcurrencyProbe::default();
+        let urls: Vec<String> = (0..12)
+            .map(|i| format!("https://example.com/{i:02}"))
+            .collect();
+
+        let results = process_urls_with_testers(
+            urls.clone(),
+            &args,
+            &ProgressManager::new(true),
+            vec![(TesterRole::Links, Box::new(probe.clone()))],
+            false,
+            &NetworkSettings::default(),
+            None,
+        )
+        .await;
+
+        assert_eq!(probe.peak.load(Ordering::SeqCst), 3);
+        let tested: Vec<String> = results.into_iter().map(|u| u.url).collect();
+        assert_eq!(tested, urls);
+    }
+
+    #[tokio::test]
+    async fn test_robots_disallowed_urls_do_not_bypass_status_filters() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/robots.txt")
+            .with_body("User-agent: *\nDisallow: /admin\n")
+            .create_async()
+            .await;
+        let settings = NetworkSettings {
+            retries: 0,
+            ..NetworkSettings::default()
+        };
+        let admin = format!("{}/admin", server.url());
+
+        let run = |extra: &[&str]| {
+            let mut argv = vec!["urx", "example.com", "--respect-robots", "--no-progress"];
+            argv.extend_from_slice(extra);
+            let args = Args::parse_from(argv);
+            let urls = vec![admin.clone()];
+            let settings = &settings;
+            async move {
+                process_urls_with_testers(
+                    urls,
+                    &args,
+                    &ProgressManager::new(true),
+                    vec![(TesterRole::Links, Box::new(ConcurrencyProbe::default()))],
+                    true,
+                    settings,
+                    None,
+                )
+                .await
+            }
+        };
+
+        let unfiltered = run(&[]).await;
+        assert_eq!(unfiltered.len(), 1);
+        assert_eq!(unfiltered[0].status.as_deref(), Some(ROBOTS_DISALLOWED));
+
+        assert!(run(&["--include-status", "200"]).await.is_empty());
+        assert!(run(&["--match-body", "admin"]).await.is_empty());
+    }
+
     #[test]
     fn test_apply_network_settings_to_tester_basic() {
         let mut tester = MockTester::new();
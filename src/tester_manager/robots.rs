@@ -0,0 +1,176 @@
+use anyhow::Result;
+use reqwest::{Client, StatusCode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::network::client::HttpClientConfig;
+use crate::network::{
+    read_text_capped, wait_before_retry, NetworkScope, NetworkSettings, RequestGate,
+};
+use crate::providers::{access_rules, is_allowed, AccessRule};
+
+/// Product token urx looks for in `User-agent:` lines.
+const AGENT: &str = "urx";
+
+/// What an origin's robots.txt allows: its rules, or `None` when it could
+/// not be fetched and everything counts as disallowed.
+type OriginRules = Option<Vec<AccessRule>>;
+
+/// robots.txt compliance for `--respect-robots`
+///
+/// Fetches robots.txt once per origin, the first time one of its URLs is
+/// about to be tested, and answers whether urx may request a URL. As RFC
+/// 9309 asks, a missing robots.txt (4xx) allows everything, while a server
+/// error or an unreachable host disallows everything.
+pub struct RobotsPolicy {
+    config: HttpClientConfig,
+    retries: u32,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+    client: OnceCell<Client>,
+    origins: Mutex<HashMap<String, Arc<OnceCell<OriginRules>>>>,
+    skipped: AtomicU64,
+}
+
+impl RobotsPolicy {
+    /// A policy fetching robots.txt with the testers' network settings
+    pub fn new(settings: &NetworkSettings) -> Self {
+        let (config, retries) = if settings.scope == NetworkScope::Providers {
+            (HttpClientConfig::default(), 3)
+        } else {
+            (
+                HttpClientConfig {
+                    timeout: settings.timeout,
+                    insecure: settings.insecure,
+                    random_agent: settings.random_agent,
+                    proxy: settings.proxy.clone(),
+                    proxy_auth: settings.proxy_auth.clone(),
+                    ..Default::default()
+                },
+                settings.retries,
+            )
+        };
+        RobotsPolicy {
+            config,
+            retries,
+            gate: settings.request_gate.clone(),
+            max_body_bytes: settings.max_body_bytes,
+            client: OnceCell::new(),
+            origins: Mutex::new(HashMap::new()),
+            skipped: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether robots.txt lets urx request `url`. URLs that aren't HTTP(S)
+    /// are left alone. Every refusal is counted for [`skipped`](Self::skipped).
+    pub async fn allows(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return true;
+        };
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return true;
+        }
+        let origin = parsed.origin().ascii_serialization();
+        let cell = {
+            let mut origins = self.origins.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(origins.entry(origin.clone()).or_default())
+        };
+        let rules = cell.get_or_init(|| self.fetch_rules(&origin)).await;
+
+        let mut path = parsed.path().to_string();
+        if let Some(query) = parsed.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        let allowed = rules.as_deref().is_some_and(|r| is_allowed(r, &path));
+        if !allowed {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+        }
+        allowed
+    }
+
+    /// How many URLs [`allows`](Self::allows) refused.
+    pub fn skipped(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    async fn fetch_rules(&self, origin: &str) -> OriginRules {
+        self.fetch(&format!("{origin}/robots.txt")).await.ok()
+    }
+
+    /// Download and parse one robots.txt (with retries).
+    async fn fetch(&self, robots_url: &str) -> Result<Vec<AccessRule>> {
+        let client = self
+            .client
+            .get_or_try_init(|| async { self.config.build_client() })
+            .await?;
+
+        let mut last_error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(robots_url).send().await {
+                Ok(response) if response.status().is_success() => {
+                    let text = read_text_capped(response, self.max_body_bytes).await?;
+                    return Ok(access_rules(&text, AGENT));
+                }
+                Ok(response) if response.status().is_client_error() => {
+                    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                        return Ok(Vec::new());
+                    }
+                    last_error = Some(anyhow::anyhow!("HTTP error: {}", response.status()));
+                }
+                Ok(response) => {
+                    last_error = Some(anyhow::anyhow!("HTTP error: {}", response.status()));
+                }
+                Err(e) => last_error = Some(e.into()),
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to fetch {}: {:?}",
+            robots_url,
+            last_error
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_allows_follows_robots_per_origin() {
+        let mut server = mockito::Server::new_async().await;
+        let robots = server
+            .mock("GET", "/robots.txt")
+            .with_body("User-agent: *\nDisallow: /admin\nAllow: /admin/public\nDisallow: /*.pdf$\n")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let settings = NetworkSettings {
+            retries: 0,
+            ..NetworkSettings::default()
+        };
+        let policy = RobotsPolicy::new(&settings);
+        let base = server.url();
+        assert!(policy.allows(&format!("{base}/")).await);
+        assert!(!policy.allows(&format!("{base}/admin/users")).await);
+        assert!(policy.allows(&format!("{base}/admin/public/logo")).await);
+        assert!(!policy.allows(&format!("{base}/docs/guide.pdf")).await);
+        assert!(policy.allows(&format!("{base}/docs/guide.pdf?x=1")).await);
+        assert_eq!(policy.skipped(), 2);
+        robots.assert();
+
+        // Unreachable host: nothing is allowed
+        assert!(!policy.allows("http://127.0.0.1:1/").await);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+
+/// Extensions of files that carry no endpoints of their own.
+const STATIC_EXTENSIONS: &[&str] = &[
+    "css", "png", "jpg", "jpeg", "gif", "svg", "ico", "webp", "bmp", "woff", "woff2", "ttf", "eot",
+    "otf", "mp4", "webm", "mp3", "wav",
+];
+
+/// Extensions of downloadable documents.
+const DOCUMENT_EXTENSIONS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "csv", "txt", "zip",
+];
+
+/// Coarse kind of URL used by `--per-category-limit`: `js`, `api`, `static`,
+/// `document`, `param` (any other URL with a query string) or `page`.
+fn url_kind(url: &url::Url) -> &'static str {
+    let path = url.path().to_ascii_lowercase();
+    let file = path.rsplit('/').next().unwrap_or_default();
+    let ext = file
+        .rsplit_once('.')
+        .map(|(_, ext)| ext)
+        .unwrap_or_default();
+    if matches!(ext, "js" | "mjs" | "jsx" | "ts") {
+        "js"
+    } else if ext == "json"
+        || path.contains("/api/")
+        || path.contains("/graphql")
+        || path.split('/').any(|seg| {
+            seg.len() > 1 && seg.starts_with('v') && seg[1..].bytes().all(|b| b.is_ascii_digit())
+        })
+    {
+        "api"
+    } else if STATIC_EXTENSIONS.contains(&ext) {
+        "static"
+    } else if DOCUMENT_EXTENSIONS.contains(&ext) {
+        "document"
+    } else if url.query().is_some_and(|q| !q.is_empty()) {
+        "param"
+    } else {
+        "page"
+    }
+}
+
+/// The URLs expensive testers (link and source-map extraction, reflection
+/// checks) get to see under `--per-category-limit`: at most `limit` per host
+/// and [`url_kind`], taking the first ones in `urls` order. URLs that don't
+/// parse share one bucket.
+pub(crate) fn sample_per_category(urls: &[String], limit: usize) -> HashSet<String> {
+    let mut counts: HashMap<(String, &'static str), usize> = HashMap::new();
+    let mut sampled = HashSet::new();
+    for raw in urls {
+        let key = match url::Url::parse(raw) {
+            Ok(parsed) => (
+                parsed.host_str().unwrap_or_default().to_ascii_lowercase(),
+                url_kind(&parsed),
+            ),
+            Err(_) => (String::new(), "unparsed"),
+        };
+        let count = counts.entry(key).or_insert(0);
+        if *count < limit {
+            *count += 1;
+            sampled.insert(raw.clone());
+        }
+    }
+    sampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_per_category_caps_each_host_and_kind() {
+        let urls: Vec<String> = [
+            "https://example.com/app.js",
+            "https://example.com/vendor.js",
+            "https://example.com/api/users",
+            "https://example.com/v2/orders",
+            "https://example.com/search?q=1",
+            "https://example.com/search?q=2",
+            "https://example.com/about",
+            "https://cdn.example.com/lib.js",
+            "https://example.com/logo.png",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+        let sampled = sample_per_category(&urls, 1);
+        assert!(sampled.contains("https://example.com/app.js"));
+        assert!(!sampled.contains("https://example.com/vendor.js"));
+        assert!(sampled.contains("https://example.com/api/users"));
+        assert!(!sampled.contains("https://example.com/v2/orders"));
+        assert!(sampled.contains("https://example.com/search?q=1"));
+        assert!(!sampled.contains("https://example.com/search?q=2"));
+        assert!(sampled.contains("https://example.com/about"));
+        assert!(sampled.contains("https://cdn.example.com/lib.js"));
+        assert!(sampled.contains("https://example.com/logo.png"));
+        assert_eq!(sampled.len(), 6);
+
+        assert_eq!(sample_per_category(&urls, 2).len(), urls.len());
+    }
+}
@@ -0,0 +1,115 @@
+use url::Url;
+
+use crate::filters::{HostValidator, UrlFilter};
+
+/// Which URLs discovered by testers (extracted links, source map routes, JS
+/// endpoints) are added to the results. Provider URLs go through the URL
+/// filters and strict host validation before testing; without this, links
+/// found on a page would skip both and pull third-party hosts into the
+/// output. `--include-external` turns it off.
+pub struct DiscoveryScope {
+    filter: UrlFilter,
+    strict: bool,
+    /// Target hosts for strict mode. `None` (e.g. `--files` input, where
+    /// there are no targets) scopes each URL to the page it was found on.
+    targets: Option<HostValidator>,
+    include_subdomains: bool,
+    /// `--exclude-hosts`, applied even with `--include-external`
+    excluded: Option<HostValidator>,
+}
+
+impl DiscoveryScope {
+    /// A scope that applies `filter` and no host validation
+    pub fn new(filter: UrlFilter) -> Self {
+        DiscoveryScope {
+            filter,
+            strict: false,
+            targets: None,
+            include_subdomains: false,
+            excluded: None,
+        }
+    }
+
+    /// Also drop URLs on hosts matching `patterns` (`--exclude-hosts`)
+    pub fn with_excluded_hosts(&mut self, patterns: &[String]) -> &mut Self {
+        self.excluded = (!patterns.is_empty()).then(|| {
+            let mut validator = HostValidator::any_host();
+            validator.with_excluded_hosts(patterns);
+            validator
+        });
+        self
+    }
+
+    /// Also require the host of each discovered URL to match one of
+    /// `domains`, or the page it was found on when `domains` is empty
+    pub fn with_strict_hosts(
+        &mut self,
+        domains: &[String],
+        include_subdomains: bool,
+        ignore_port: bool,
+    ) -> &mut Self {
+        self.strict = true;
+        self.include_subdomains = include_subdomains;
+        self.targets = (!domains.is_empty()).then(|| {
+            let mut validator = HostValidator::new(domains, include_subdomains);
+            validator.with_ignore_port(ignore_port);
+            validator
+        });
+        self
+    }
+
+    /// Whether `url`, found while testing `page`, belongs in the results.
+    pub fn allows(&self, page: &str, url: &str) -> bool {
+        if self.filter.explain(url).is_some() {
+            return false;
+        }
+        if let Some(excluded) = &self.excluded {
+            if !excluded.is_valid_host(url) {
+                return false;
+            }
+        }
+        if !self.strict {
+            return true;
+        }
+        match &self.targets {
+            Some(validator) => validator.is_valid_host(url),
+            None => Url::parse(page)
+                .ok()
+                .and_then(|p| p.host_str().map(str::to_string))
+                .is_some_and(|host| {
+                    HostValidator::new(&[host], self.include_subdomains).is_valid_host(url)
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_targets_and_filters() {
+        let mut filter = UrlFilter::new();
+        filter.with_exclude_extensions(vec!["png".to_string()]);
+        let mut scope = DiscoveryScope::new(filter);
+        let page = "https://example.com/";
+        assert!(scope.allows(page, "https://cdn.other.net/app.js"));
+        assert!(!scope.allows(page, "https://example.com/logo.png"));
+
+        scope.with_strict_hosts(&["example.com".to_string()], true, false);
+        assert!(scope.allows(page, "https://api.example.com/v1"));
+        assert!(!scope.allows(page, "https://cdn.other.net/app.js"));
+        assert!(!scope.allows(page, "https://example.com/logo.png"));
+
+        // No targets: links stay on the page's own host
+        let mut scope = DiscoveryScope::new(UrlFilter::new());
+        scope.with_strict_hosts(&[], false, false);
+        assert!(scope.allows("https://shop.example.org/a", "https://shop.example.org/b"));
+        assert!(!scope.allows("https://shop.example.org/a", "https://example.org/"));
+
+        let mut scope = DiscoveryScope::new(UrlFilter::new());
+        scope.with_excluded_hosts(&["*.cdn.example.com".to_string()]);
+        assert!(scope.allows(page, "https://cdn.other.net/app.js"));
+        assert!(!scope.allows(page, "https://eu.cdn.example.com/app.js"));
+    }
+}
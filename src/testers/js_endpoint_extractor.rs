@@ -0,0 +1,318 @@
+use anyhow::Result;
+use regex::Regex;
+use reqwest::Client;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use super::{add_tag, Tester};
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{read_text_capped, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES};
+
+/// Metadata key naming the script an endpoint was found in.
+const JS_SOURCE_KEY: &str = "js_source";
+
+/// Tag attached to URLs found in scripts.
+const JS_ENDPOINT_TAG: &str = "js-endpoint";
+
+/// Quoted strings that look like endpoints, after LinkFinder: full and
+/// protocol-relative URLs, absolute and `./` / `../` paths, relative paths
+/// with a file extension, relative REST-style paths (`api/v1/users`), and
+/// bare file names with a server-side extension.
+static ENDPOINT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?x)
+        ["'`]
+        (
+            (?:[a-zA-Z]{1,10}://|//)[^"'`/\s]+\.[a-zA-Z]{2,}[^"'`\s]*
+          | (?:/|\.\./|\./)[^"'`><,;|\s*()%$^/\\\[\]][^"'`><,;|()\s]+
+          | [a-zA-Z0-9_\-/]+/[a-zA-Z0-9_\-/.]+\.(?:[a-zA-Z]{1,4}|action)(?:[?\#][^"'`\s]*)?
+          | [a-zA-Z0-9_\-/]+/[a-zA-Z0-9_\-/]{3,}(?:[?\#][^"'`\s]*)?
+          | [a-zA-Z0-9_\-]+\.(?:php|asp|aspx|jsp|json|action|html|js|txt|xml)(?:[?\#][^"'`\s]*)?
+        )
+        ["'`]
+        "#,
+    )
+    .unwrap()
+});
+
+/// Top-level MIME types, whose `type/subtype` strings match the REST-path
+/// pattern but are never endpoints.
+const MIME_TYPES: &[&str] = &[
+    "application/",
+    "audio/",
+    "font/",
+    "image/",
+    "multipart/",
+    "text/",
+    "video/",
+];
+
+/// Mark a URL as found in a script: the script under `js_source`, plus a
+/// `js-endpoint` entry in `tags`.
+pub fn annotate_js_endpoint(metadata: &mut Map<String, Value>, script: &str) {
+    metadata.insert(JS_SOURCE_KEY.to_string(), Value::from(script));
+    add_tag(metadata, JS_ENDPOINT_TAG);
+}
+
+/// JavaScript endpoint extractor for `--extract-js-endpoints`
+///
+/// Fetches every collected JavaScript URL and pulls endpoints out of its
+/// string literals the way LinkFinder does: full URLs, absolute paths, and
+/// relative paths such as `api/v1/users` or `./search.php`. Relative paths
+/// are resolved against the script's origin (`./` and `../` against the
+/// script itself). Non-script URLs are ignored without a request.
+#[derive(Clone)]
+pub struct JsEndpointExtractor {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+}
+
+impl JsEndpointExtractor {
+    /// Creates a new JsEndpointExtractor with default settings
+    pub fn new() -> Self {
+        JsEndpointExtractor {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async { self.client_config().build_client() })
+            .await
+    }
+
+    fn is_script(url: &Url) -> bool {
+        let path = url.path().to_ascii_lowercase();
+        [".js", ".mjs", ".cjs"]
+            .iter()
+            .any(|ext| path.ends_with(ext))
+    }
+
+    /// Endpoints quoted in `code`, as absolute HTTP(S) URLs, in order of
+    /// first appearance.
+    fn extract_endpoints(script: &Url, code: &str) -> Vec<String> {
+        let mut seen = HashSet::new();
+        let mut endpoints = Vec::new();
+        for captures in ENDPOINT.captures_iter(code) {
+            let endpoint = &captures[1];
+            if MIME_TYPES.iter().any(|m| endpoint.starts_with(m)) {
+                continue;
+            }
+            let resolved = if endpoint.contains("://")
+                || endpoint.starts_with('/')
+                || endpoint.starts_with("./")
+                || endpoint.starts_with("../")
+            {
+                script.join(endpoint)
+            } else {
+                script.join(&format!("/{endpoint}"))
+            };
+            let Ok(resolved) = resolved else {
+                continue;
+            };
+            if !matches!(resolved.scheme(), "http" | "https") || resolved.host_str().is_none() {
+                continue;
+            }
+            let resolved = resolved.to_string();
+            if resolved != script.as_str() && seen.insert(resolved.clone()) {
+                endpoints.push(resolved);
+            }
+        }
+        endpoints
+    }
+
+    /// Fetch a script (with retries) and return the endpoints it mentions.
+    async fn extract(&self, url: &str) -> Result<Vec<String>> {
+        let Some(script) = Url::parse(url).ok().filter(Self::is_script) else {
+            return Ok(vec![]);
+        };
+        let client = self.client().await?;
+
+        let mut last_error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(url).send().await {
+                Ok(response) => {
+                    let code = read_text_capped(response, self.max_body_bytes).await?;
+                    return Ok(Self::extract_endpoints(&script, &code));
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to extract endpoints from {}: {:?}",
+            url,
+            last_error
+        ))
+    }
+}
+
+impl Tester for JsEndpointExtractor {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Fetches a script and returns the endpoints found in it
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.extract(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!(
+                    "JS endpoint extraction cancelled for {url}"
+                )),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Enables or disables SSL certificate verification
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+
+    fn with_max_body_bytes(&mut self, bytes: u64) {
+        self.max_body_bytes = bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_endpoints_resolves_against_script() {
+        let script = Url::parse("https://example.com/static/js/app.js").unwrap();
+        let code = r#"
+            fetch("/api/v1/users?active=1");
+            axios.get('api/v2/orders');
+            const cdn = "https://cdn.example.net/lib.min.js";
+            const rel = `./chunk.3f2a.js`;
+            load("login.php");
+            headers["Content-Type"] = "application/json";
+            const greeting = "hello world";
+            fetch("/api/v1/users?active=1");
+        "#;
+        assert_eq!(
+            JsEndpointExtractor::extract_endpoints(&script, code),
+            [
+                "https://example.com/api/v1/users?active=1",
+                "https://example.com/api/v2/orders",
+                "https://cdn.example.net/lib.min.js",
+                "https://example.com/static/js/chunk.3f2a.js",
+                "https://example.com/login.php",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetches_scripts_only() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/app.js")
+            .with_body(r#"const u = "/graphql";"#)
+            .create_async()
+            .await;
+        let page = server
+            .mock("GET", "/index.html")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let extractor = JsEndpointExtractor::new();
+        let found = extractor
+            .test_url(&format!("{}/app.js", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(found, vec![format!("{}/graphql", server.url())]);
+        let skipped = extractor
+            .test_url(&format!("{}/index.html", server.url()))
+            .await
+            .unwrap();
+        assert!(skipped.is_empty());
+        page.assert();
+    }
+}
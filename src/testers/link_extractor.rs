@@ -5,10 +5,12 @@ use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
-use super::Tester;
-use crate::network::client::HttpClientConfig;
+use super::{SecretScanner, Tester};
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{read_text_capped, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES};
 
 /// HTML link extractor that finds URLs in web pages
 #[derive(Clone)]
@@ -19,6 +21,7 @@ pub struct LinkExtractor {
     retries: u32,
     random_agent: bool,
     insecure: bool,
+    http_version: HttpVersion,
     /// One HTTP client, built lazily on first use and reused for every tested
     /// URL. `reqwest::Client` pools connections internally, so building it once
     /// (rather than per URL) lets TLS handshakes and keep-alive connections be
@@ -28,6 +31,10 @@ pub struct LinkExtractor {
     /// `with_*` setters have applied network settings, so it always reflects
     /// the final configuration.
     client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+    secrets: Option<SecretScanner>,
 }
 
 impl LinkExtractor {
@@ -40,7 +47,12 @@ impl LinkExtractor {
             retries: 3,
             random_agent: false,
             insecure: false,
+            http_version: HttpVersion::Auto,
             client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            secrets: None,
         }
     }
 
@@ -51,6 +63,7 @@ impl LinkExtractor {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
         }
     }
 
@@ -84,6 +97,56 @@ impl LinkExtractor {
 
         links
     }
+
+    /// Download a page (with retries) and return the links it contains.
+    async fn extract(&self, url: &str) -> Result<Vec<String>> {
+        let client = self.client().await?;
+
+        // Perform the request with retries
+        let mut last_error = None;
+
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(url).send().await {
+                Ok(response) => {
+                    // Get the base URL for resolving relative URLs
+                    let base_url = match Url::parse(url) {
+                        Ok(parsed_url) => parsed_url,
+                        Err(_) => {
+                            return Err(anyhow::anyhow!("Failed to parse URL: {}", url));
+                        }
+                    };
+
+                    // Get the HTML content
+                    let html_content = read_text_capped(response, self.max_body_bytes).await?;
+                    if let Some(secrets) = &self.secrets {
+                        secrets.scan(url, &html_content);
+                    }
+
+                    // Extract links using the helper function
+                    let links = Self::extract_links(&base_url, &html_content);
+
+                    // Return the list of links
+                    return Ok(links);
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // If we get here, all retries failed
+        Err(anyhow::anyhow!(
+            "Failed to extract links from {}: {:?}",
+            url,
+            last_error
+        ))
+    }
 }
 
 impl Tester for LinkExtractor {
@@ -97,45 +160,10 @@ impl Tester for LinkExtractor {
         url: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
         Box::pin(async move {
-            let client = self.client().await?;
-
-            // Perform the request with retries
-            let mut last_error = None;
-
-            for _ in 0..=self.retries {
-                match client.get(url).send().await {
-                    Ok(response) => {
-                        // Get the base URL for resolving relative URLs
-                        let base_url = match Url::parse(url) {
-                            Ok(parsed_url) => parsed_url,
-                            Err(_) => {
-                                return Err(anyhow::anyhow!("Failed to parse URL: {}", url));
-                            }
-                        };
-
-                        // Get the HTML content
-                        let html_content = response.text().await?;
-
-                        // Extract links using the helper function
-                        let links = Self::extract_links(&base_url, &html_content);
-
-                        // Return the list of links
-                        return Ok(links);
-                    }
-                    Err(e) => {
-                        last_error = Some(e);
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        continue;
-                    }
-                }
+            match self.cancel.run_until_cancelled(self.extract(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Link extraction cancelled for {url}")),
             }
-
-            // If we get here, all retries failed
-            Err(anyhow::anyhow!(
-                "Failed to extract links from {}: {:?}",
-                url,
-                last_error
-            ))
         })
     }
 
@@ -168,6 +196,26 @@ impl Tester for LinkExtractor {
     fn with_proxy_auth(&mut self, auth: Option<String>) {
         self.proxy_auth = auth;
     }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+
+    fn with_max_body_bytes(&mut self, bytes: u64) {
+        self.max_body_bytes = bytes;
+    }
+
+    fn with_secret_scanner(&mut self, scanner: SecretScanner) {
+        self.secrets = Some(scanner);
+    }
 }
 
 #[cfg(test)]
@@ -1,12 +1,43 @@
 use anyhow::Result;
+use serde_json::{Map, Value};
 use std::future::Future;
 use std::pin::Pin;
+use tokio_util::sync::CancellationToken;
 
+use crate::network::client::HttpVersion;
+use crate::network::RequestGate;
+
+mod js_endpoint_extractor;
 mod link_extractor;
+mod open_redirect_tester;
+mod reflection_tester;
+mod secrets;
+mod sourcemap_extractor;
 mod status_checker;
+mod title_extractor;
+mod tls_inspector;
 
+pub use js_endpoint_extractor::{annotate_js_endpoint, JsEndpointExtractor};
 pub use link_extractor::LinkExtractor;
-pub use status_checker::StatusChecker;
+pub use open_redirect_tester::{annotate_open_redirect, OpenRedirectTester};
+pub use reflection_tester::{annotate_reflection, ReflectionTester};
+pub use secrets::{SecretFinding, SecretScanner};
+pub use sourcemap_extractor::{annotate_sourcemap, SourceMapExtractor};
+pub use status_checker::{status_entry, StatusChecker};
+pub use title_extractor::TitleExtractor;
+pub use tls_inspector::{annotate_tls, TlsInspector};
+
+/// Add `tag` to a URL's `tags` metadata list, once.
+fn add_tag(metadata: &mut Map<String, Value>, tag: &str) {
+    let tags = metadata
+        .entry("tags")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    if let Value::Array(tags) = tags {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(Value::from(tag));
+        }
+    }
+}
 
 /// Tester trait for URL testing operations
 ///
@@ -40,4 +71,23 @@ pub trait Tester: Send + Sync {
 
     /// Set the proxy authentication credentials (username:password)
     fn with_proxy_auth(&mut self, auth: Option<String>);
+
+    /// Attach a token that makes in-flight requests return early with an error
+    /// once cancelled (Ctrl-C during testing).
+    fn with_cancellation(&mut self, _token: CancellationToken) {}
+
+    /// Route every request through the run-wide `--global-rate-limit` /
+    /// `--max-requests` gate.
+    fn with_request_gate(&mut self, _gate: RequestGate) {}
+
+    /// Cap the response bodies this tester downloads (`--max-body-bytes`;
+    /// 0 = no cap). Testers that never read a body can ignore it.
+    fn with_max_body_bytes(&mut self, _bytes: u64) {}
+
+    /// Scan the response bodies this tester reads for secrets
+    /// (`--scan-secrets`). Testers that never read a body can ignore it.
+    fn with_secret_scanner(&mut self, _scanner: SecretScanner) {}
+
+    /// Speak this HTTP version (`--http-version`)
+    fn with_http_version(&mut self, _version: HttpVersion) {}
 }
@@ -0,0 +1,351 @@
+use anyhow::Result;
+use reqwest::Client;
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use super::{add_tag, Tester};
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{wait_before_retry, RequestGate};
+
+/// Host substituted into redirect parameters. `.example` is reserved (RFC
+/// 2606), so a redirect to it can only come from our probe.
+const CANARY_HOST: &str = "urx-canary.example";
+
+/// Parameter names that commonly carry a redirect target, compared
+/// case-insensitively. Names containing `redirect` also count.
+const REDIRECT_PARAMS: &[&str] = &[
+    "url",
+    "next",
+    "redirect",
+    "redir",
+    "return",
+    "returnto",
+    "return_to",
+    "returnurl",
+    "return_url",
+    "goto",
+    "go",
+    "dest",
+    "destination",
+    "continue",
+    "target",
+    "to",
+    "out",
+    "view",
+    "forward",
+    "callback",
+    "rurl",
+    "r",
+    "u",
+];
+
+/// Metadata key listing the parameters that redirected to the canary.
+const OPEN_REDIRECT_PARAMS_KEY: &str = "open_redirect_params";
+
+/// Metadata flag set on URLs with at least one such parameter.
+const OPEN_REDIRECT_KEY: &str = "open_redirect";
+
+/// Tag attached alongside the flag.
+const OPEN_REDIRECT_TAG: &str = "open-redirect";
+
+/// Record open redirect results on a URL's metadata: `open_redirect: true`,
+/// the parameter names under `open_redirect_params`, and an
+/// `open-redirect` entry in `tags`. Does nothing when no parameter
+/// redirected to the canary.
+pub fn annotate_open_redirect(metadata: &mut Map<String, Value>, params: &[String]) {
+    if params.is_empty() {
+        return;
+    }
+    metadata.insert(OPEN_REDIRECT_KEY.to_string(), Value::Bool(true));
+    metadata.insert(
+        OPEN_REDIRECT_PARAMS_KEY.to_string(),
+        Value::from(params.to_vec()),
+    );
+    add_tag(metadata, OPEN_REDIRECT_TAG);
+}
+
+/// Open redirect tester for `--check-open-redirect`
+///
+/// For every redirect-ish query parameter (`url=`, `next=`, `redirect=`, …)
+/// it requests the URL once with that parameter pointed at a canary host,
+/// without following redirects, and reports the parameters whose response
+/// is a 3xx whose `Location` leads to the canary. URLs without such
+/// parameters cost no requests.
+#[derive(Clone)]
+pub struct OpenRedirectTester {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+}
+
+impl OpenRedirectTester {
+    /// Creates a new OpenRedirectTester with default settings
+    pub fn new() -> Self {
+        OpenRedirectTester {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    /// The shared client, which reports redirects instead of following them.
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async {
+                Ok(self
+                    .client_config()
+                    .client_builder()?
+                    .redirect(reqwest::redirect::Policy::none())
+                    .build()?)
+            })
+            .await
+    }
+
+    fn is_redirect_param(name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        REDIRECT_PARAMS.contains(&name.as_str()) || name.contains("redirect")
+    }
+
+    /// One probe per redirect-ish parameter of `url`: the parameter's name
+    /// and the URL with its value replaced by the canary. Empty when `url`
+    /// has none.
+    fn probe_urls(url: &str) -> Vec<(String, Url)> {
+        let Ok(parsed) = Url::parse(url) else {
+            return vec![];
+        };
+        let pairs: Vec<(String, String)> = parsed.query_pairs().into_owned().collect();
+        let canary = format!("https://{CANARY_HOST}/");
+
+        let mut probes: Vec<(String, Url)> = Vec::new();
+        for (i, (name, _)) in pairs.iter().enumerate() {
+            if !Self::is_redirect_param(name) || probes.iter().any(|(n, _)| n == name) {
+                continue;
+            }
+            let mut probe = parsed.clone();
+            {
+                let mut query = probe.query_pairs_mut();
+                query.clear();
+                for (j, (n, v)) in pairs.iter().enumerate() {
+                    query.append_pair(n, if j == i { &canary } else { v });
+                }
+            }
+            probes.push((name.clone(), probe));
+        }
+        probes
+    }
+
+    /// Whether `response` is a 3xx pointing at the canary host. Relative
+    /// and protocol-relative `Location` values are resolved against `probe`.
+    fn redirects_to_canary(probe: &Url, response: &reqwest::Response) -> bool {
+        if !response.status().is_redirection() {
+            return false;
+        }
+        response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|location| probe.join(location.trim()).ok())
+            .and_then(|target| target.host_str().map(str::to_ascii_lowercase))
+            .is_some_and(|host| host == CANARY_HOST || host.ends_with(&format!(".{CANARY_HOST}")))
+    }
+
+    /// Send one probe (with retries) and report whether it redirected to the
+    /// canary.
+    async fn probe(&self, client: &Client, probe: &Url) -> Result<bool> {
+        let mut last_error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(probe.clone()).send().await {
+                Ok(response) => return Ok(Self::redirects_to_canary(probe, &response)),
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to check open redirect for {}: {:?}",
+            probe,
+            last_error
+        ))
+    }
+
+    /// Probe each redirect-ish parameter of `url` and report the ones that
+    /// redirected to the canary.
+    async fn check(&self, url: &str) -> Result<Vec<String>> {
+        let probes = Self::probe_urls(url);
+        if probes.is_empty() {
+            return Ok(vec![]);
+        }
+        let client = self.client().await?;
+
+        let mut redirected = Vec::new();
+        for (name, probe) in probes {
+            if self.probe(client, &probe).await? {
+                redirected.push(name);
+            }
+        }
+        Ok(redirected)
+    }
+}
+
+impl Tester for OpenRedirectTester {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Tests a URL for open redirects, returning the parameter names
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.check(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Open redirect check cancelled for {url}")),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Enables or disables SSL certificate verification
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_urls_swap_one_redirect_param_at_a_time() {
+        let probes = OpenRedirectTester::probe_urls(
+            "https://example.com/login?next=%2Fhome&lang=en&RedirectUri=x&next=again",
+        );
+        let names: Vec<&str> = probes.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["next", "RedirectUri"]);
+
+        let query: Vec<(String, String)> = probes[0].1.query_pairs().into_owned().collect();
+        assert_eq!(query[0].1, "https://urx-canary.example/");
+        assert_eq!(query[1], ("lang".to_string(), "en".to_string()));
+        assert_eq!(query[2].1, "x");
+
+        assert!(OpenRedirectTester::probe_urls("https://example.com/?q=1").is_empty());
+        assert!(OpenRedirectTester::probe_urls("not a url").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reports_params_that_redirect_to_the_canary() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/go")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "next".into(),
+                "https://urx-canary.example/".into(),
+            ))
+            .with_status(302)
+            .with_header("location", "//urx-canary.example/")
+            .create_async()
+            .await;
+        // `url` is validated: the server sends it home instead
+        server
+            .mock("GET", "/go")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "url".into(),
+                "https://urx-canary.example/".into(),
+            ))
+            .with_status(302)
+            .with_header("location", "/home")
+            .create_async()
+            .await;
+
+        let tester = OpenRedirectTester::new();
+        let found = tester
+            .test_url(&format!("{}/go?next=/a&url=/b", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(found, vec!["next".to_string()]);
+
+        let mut metadata = Map::new();
+        annotate_open_redirect(&mut metadata, &found);
+        assert_eq!(metadata["open_redirect"], true);
+        assert_eq!(
+            metadata["open_redirect_params"],
+            serde_json::json!(["next"])
+        );
+        assert_eq!(metadata["tags"], serde_json::json!(["open-redirect"]));
+    }
+}
@@ -0,0 +1,326 @@
+use anyhow::Result;
+use rand::RngExt;
+use reqwest::Client;
+use serde_json::{Map, Value};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use super::{add_tag, Tester};
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{read_text_capped, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES};
+use crate::utils::determinism;
+
+/// Metadata key listing the parameters a [`ReflectionTester`] saw echoed back.
+const REFLECTED_PARAMS_KEY: &str = "reflected_params";
+
+/// Tag attached to URLs with at least one reflected parameter.
+const XSS_CANDIDATE_TAG: &str = "xss-candidate";
+
+/// Record reflection results on a URL's metadata: the parameter names under
+/// `reflected_params`, plus an `xss-candidate` entry in `tags`. Does nothing
+/// when no parameter was reflected.
+pub fn annotate_reflection(metadata: &mut Map<String, Value>, params: &[String]) {
+    if params.is_empty() {
+        return;
+    }
+    metadata.insert(
+        REFLECTED_PARAMS_KEY.to_string(),
+        Value::from(params.to_vec()),
+    );
+    add_tag(metadata, XSS_CANDIDATE_TAG);
+}
+
+/// Parameter reflection tester for `--check-reflection`
+///
+/// Appends a unique alphanumeric marker to every query parameter value,
+/// fetches the URL once, and reports the names of the parameters whose marker
+/// shows up in the response body. A reflected parameter is only a candidate
+/// for XSS: the marker contains no special characters, so this says nothing
+/// about how the server encodes them.
+#[derive(Clone)]
+pub struct ReflectionTester {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Random per-run prefix, so markers can't collide with page content.
+    nonce: String,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+}
+
+impl ReflectionTester {
+    /// Creates a new ReflectionTester with default settings
+    pub fn new() -> Self {
+        ReflectionTester {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            nonce: format!(
+                "urx{:08x}",
+                determinism::rng(determinism::stream::REFLECTION_NONCE, 0)
+                    .map_or_else(rand::random::<u32>, |mut rng| rng.random::<u32>())
+            ),
+            client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async { self.client_config().build_client() })
+            .await
+    }
+
+    /// Marker for the `index`-th parameter. The trailing `z` keeps `…r1z`
+    /// from matching inside `…r10z`.
+    fn marker(&self, index: usize) -> String {
+        format!("{}r{index}z", self.nonce)
+    }
+
+    /// Rewrite `url` with a marker appended to each parameter value. Returns
+    /// the probe URL and `(name, marker)` pairs, or `None` when the URL has no
+    /// query parameters to test.
+    fn probe_url(&self, url: &str) -> Option<(Url, Vec<(String, String)>)> {
+        let mut probe = Url::parse(url).ok()?;
+        let pairs: Vec<(String, String)> = probe.query_pairs().into_owned().collect();
+        if pairs.is_empty() {
+            return None;
+        }
+
+        let mut markers = Vec::with_capacity(pairs.len());
+        {
+            let mut query = probe.query_pairs_mut();
+            query.clear();
+            for (i, (name, value)) in pairs.into_iter().enumerate() {
+                let marker = self.marker(i);
+                query.append_pair(&name, &format!("{value}{marker}"));
+                markers.push((name, marker));
+            }
+        }
+        Some((probe, markers))
+    }
+
+    /// Names of the parameters whose marker appears in `body`, in query order
+    /// and without duplicates.
+    fn reflected_params(markers: &[(String, String)], body: &str) -> Vec<String> {
+        let mut reflected: Vec<String> = Vec::new();
+        for (name, marker) in markers {
+            if body.contains(marker.as_str()) && !reflected.contains(name) {
+                reflected.push(name.clone());
+            }
+        }
+        reflected
+    }
+
+    /// Request the marked-up URL (with retries) and report reflected parameters.
+    async fn check(&self, url: &str) -> Result<Vec<String>> {
+        let Some((probe, markers)) = self.probe_url(url) else {
+            return Ok(vec![]);
+        };
+        let client = self.client().await?;
+
+        let mut last_error = None;
+
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(probe.clone()).send().await {
+                Ok(response) => {
+                    let body = read_text_capped(response, self.max_body_bytes).await?;
+                    return Ok(Self::reflected_params(&markers, &body));
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to check reflection for {}: {:?}",
+            url,
+            last_error
+        ))
+    }
+}
+
+impl Tester for ReflectionTester {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Tests a URL for reflected parameters, returning their names
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.check(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Reflection check cancelled for {url}")),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Enables or disables SSL certificate verification
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+
+    fn with_max_body_bytes(&mut self, bytes: u64) {
+        self.max_body_bytes = bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probe_url_marks_each_parameter() {
+        let tester = ReflectionTester::new();
+        let (probe, markers) = tester
+            .probe_url("https://example.com/search?q=shoes&page=2")
+            .unwrap();
+
+        assert_eq!(markers.len(), 2);
+        assert_eq!(markers[0].0, "q");
+        assert_eq!(markers[1].0, "page");
+        assert_ne!(markers[0].1, markers[1].1);
+        let query: Vec<(String, String)> = probe.query_pairs().into_owned().collect();
+        assert_eq!(
+            query[0],
+            ("q".to_string(), format!("shoes{}", markers[0].1))
+        );
+        assert_eq!(query[1], ("page".to_string(), format!("2{}", markers[1].1)));
+
+        assert!(tester.probe_url("https://example.com/about").is_none());
+        assert!(tester.probe_url("not a url").is_none());
+    }
+
+    #[test]
+    fn test_reflected_params() {
+        let tester = ReflectionTester::new();
+        let markers = vec![
+            ("a".to_string(), tester.marker(1)),
+            ("b".to_string(), tester.marker(10)),
+        ];
+        // Only b's marker is present; a's must not match inside it.
+        let body = format!("<p>{}</p>", tester.marker(10));
+        assert_eq!(
+            ReflectionTester::reflected_params(&markers, &body),
+            vec!["b".to_string()]
+        );
+        assert!(ReflectionTester::reflected_params(&markers, "nothing").is_empty());
+    }
+
+    #[test]
+    fn test_annotate_reflection() {
+        let mut metadata = Map::new();
+        annotate_reflection(&mut metadata, &[]);
+        assert!(metadata.is_empty());
+
+        let params = vec!["q".to_string(), "id".to_string()];
+        annotate_reflection(&mut metadata, &params);
+        annotate_reflection(&mut metadata, &params);
+        assert_eq!(metadata["reflected_params"], serde_json::json!(["q", "id"]));
+        assert_eq!(metadata["tags"], serde_json::json!(["xss-candidate"]));
+    }
+
+    #[tokio::test]
+    async fn test_detects_reflected_parameter() {
+        let mut server = mockito::Server::new_async().await;
+        let tester = ReflectionTester::new();
+        let q_marker = tester.marker(0);
+        let page = server
+            .mock("GET", "/search")
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_body(format!("<h1>Results for shoes{q_marker}</h1>"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let reflected = tester
+            .test_url(&format!("{}/search?q=shoes&page=2", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(reflected, vec!["q".to_string()]);
+
+        // No query parameters: nothing to test, no request made.
+        let none = tester
+            .test_url(&format!("{}/search", server.url()))
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+        page.assert();
+    }
+}
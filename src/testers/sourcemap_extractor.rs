@@ -0,0 +1,558 @@
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use reqwest::Client;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use super::{add_tag, Tester};
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{read_text_capped, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES};
+
+/// Metadata key naming the script whose source map produced a URL.
+const SOURCEMAP_SCRIPT_KEY: &str = "sourcemap_script";
+
+/// Tag attached to URLs mined from a source map.
+const SOURCEMAP_TAG: &str = "sourcemap";
+
+/// String literals longer than this are data blobs, not routes.
+const MAX_LITERAL_LEN: usize = 256;
+
+/// Mark a URL as sourcemap-derived: the script it came from under
+/// `sourcemap_script`, plus a `sourcemap` entry in `tags`.
+pub fn annotate_sourcemap(metadata: &mut Map<String, Value>, script: &str) {
+    metadata.insert(SOURCEMAP_SCRIPT_KEY.to_string(), Value::from(script));
+    add_tag(metadata, SOURCEMAP_TAG);
+}
+
+/// The parts of a source map (revision 3) worth mining.
+#[derive(Debug, Default, Deserialize)]
+struct SourceMap {
+    #[serde(default, rename = "sourceRoot")]
+    source_root: Option<String>,
+    #[serde(default)]
+    sources: Vec<Option<String>>,
+    #[serde(default, rename = "sourcesContent")]
+    sources_content: Vec<Option<String>>,
+}
+
+/// Source map miner for `--extract-sourcemaps`
+///
+/// For every collected JavaScript URL, fetches the script, follows its
+/// `sourceMappingURL` comment (or `SourceMap` header, or else tries the
+/// conventional `<script>.map` next to it) and returns the map
+/// itself plus what it reveals: original source files served next to the
+/// map, and route-like string literals (`"/api/v2/users"`) in the embedded
+/// sources. Third-party code under `node_modules` is skipped. Non-script
+/// URLs are ignored without a request.
+#[derive(Clone)]
+pub struct SourceMapExtractor {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+}
+
+impl SourceMapExtractor {
+    /// Creates a new SourceMapExtractor with default settings
+    pub fn new() -> Self {
+        SourceMapExtractor {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async { self.client_config().build_client() })
+            .await
+    }
+
+    /// Download `url` (with retries), returning the body and any `SourceMap`
+    /// / `X-SourceMap` response header.
+    async fn fetch(&self, url: &str) -> Result<(String, Option<String>)> {
+        let client = self.client().await?;
+        let mut last_error = None;
+
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(url).send().await {
+                Ok(response) => {
+                    let header = ["sourcemap", "x-sourcemap"].iter().find_map(|name| {
+                        response
+                            .headers()
+                            .get(*name)
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::to_string)
+                    });
+                    return Ok((
+                        read_text_capped(response, self.max_body_bytes).await?,
+                        header,
+                    ));
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!("Failed to fetch {}: {:?}", url, last_error))
+    }
+
+    /// Fetch a script and its source map, and return the URLs mined from it.
+    async fn extract(&self, url: &str) -> Result<Vec<String>> {
+        let Some(script) = Url::parse(url).ok().filter(is_script) else {
+            return Ok(vec![]);
+        };
+        let (body, header) = self.fetch(url).await?;
+        let reference = header.or_else(|| source_mapping_url(&body).map(str::to_string));
+
+        let mut found = Vec::new();
+        let map = match reference {
+            Some(reference) if reference.starts_with("data:") => {
+                match decode_data_url(&reference) {
+                    Some(json) => Some((script.clone(), json)),
+                    None => return Ok(vec![]),
+                }
+            }
+            Some(reference) => {
+                let map_url = script.join(&reference)?;
+                let (json, _) = self.fetch(map_url.as_str()).await?;
+                found.push(map_url.to_string());
+                Some((map_url, json))
+            }
+            None => None,
+        };
+
+        let (map_url, map) = match map {
+            Some((map_url, json)) => {
+                let map: SourceMap = serde_json::from_str(&json)
+                    .map_err(|e| anyhow::anyhow!("Invalid source map {map_url}: {e}"))?;
+                (map_url, map)
+            }
+            // No pragma or header, but many builds still publish the map
+            // next to the script. Anything that isn't a map there is the
+            // server's 404 page or SPA fallback, not an error.
+            None => {
+                let map_url = sibling_map_url(&script);
+                let (json, _) = self.fetch(map_url.as_str()).await?;
+                match serde_json::from_str::<SourceMap>(&json) {
+                    Ok(map) if !map.sources.is_empty() => {
+                        found.push(map_url.to_string());
+                        (map_url, map)
+                    }
+                    _ => return Ok(vec![]),
+                }
+            }
+        };
+        found.extend(mine_source_map(&map, &map_url, &script));
+
+        let mut seen = HashSet::new();
+        found.retain(|u| seen.insert(u.clone()));
+        Ok(found)
+    }
+}
+
+/// Only scripts carry a `sourceMappingURL` worth following.
+fn is_script(url: &Url) -> bool {
+    let path = url.path().to_ascii_lowercase();
+    [".js", ".mjs", ".cjs"]
+        .iter()
+        .any(|ext| path.ends_with(ext))
+}
+
+/// Where a script's map conventionally lives when nothing points at it:
+/// `app.js` → `app.js.map`, without the script's query or fragment.
+fn sibling_map_url(script: &Url) -> Url {
+    let mut map_url = script.clone();
+    map_url.set_query(None);
+    map_url.set_fragment(None);
+    map_url.set_path(&format!("{}.map", script.path()));
+    map_url
+}
+
+/// The last `//# sourceMappingURL=` (or legacy `//@`, or `/*# … */`) value in
+/// a script.
+fn source_mapping_url(body: &str) -> Option<&str> {
+    const MARKER: &str = "sourceMappingURL=";
+    let mut search = body;
+    while let Some(pos) = search.rfind(MARKER) {
+        let prefix = &search[..pos];
+        if ["//# ", "//@ ", "/*# ", "/*@ "]
+            .iter()
+            .any(|p| prefix.ends_with(p))
+        {
+            let value = search[pos + MARKER.len()..]
+                .split(|c: char| c.is_whitespace() || c == '*')
+                .next()
+                .unwrap_or("");
+            return (!value.is_empty()).then_some(value);
+        }
+        search = prefix;
+    }
+    None
+}
+
+/// Decode an inline `data:application/json;base64,…` source map.
+fn decode_data_url(data_url: &str) -> Option<String> {
+    let (header, payload) = data_url.split_once(',')?;
+    if header.ends_with(";base64") {
+        String::from_utf8(STANDARD.decode(payload.trim()).ok()?).ok()
+    } else {
+        Some(payload.to_string())
+    }
+}
+
+/// Collect URLs a source map gives away: original source files that resolve
+/// to HTTP(S) next to the map, and route literals in embedded sources,
+/// resolved against the script's origin.
+fn mine_source_map(map: &SourceMap, map_url: &Url, script: &Url) -> Vec<String> {
+    let root = map.source_root.as_deref().unwrap_or("");
+    let mut urls = Vec::new();
+
+    for (i, source) in map.sources.iter().enumerate() {
+        let source = source.as_deref().unwrap_or("");
+        if source.contains("node_modules") {
+            continue;
+        }
+        if !source.is_empty() {
+            if let Ok(resolved) = map_url.join(&format!("{root}{source}")) {
+                if matches!(resolved.scheme(), "http" | "https")
+                    && resolved.host_str() == script.host_str()
+                {
+                    urls.push(resolved.to_string());
+                }
+            }
+        }
+
+        let Some(Some(content)) = map.sources_content.get(i) else {
+            continue;
+        };
+        for literal in string_literals(content) {
+            if let Some(url) = route_url(literal, script) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+/// Contents of the `'…'`, `"…"` and `` `…` `` literals in `code`. A rough
+/// scan, not a parser: apostrophes in comments can pair up wrongly, which
+/// only yields junk that [`route_url`] rejects.
+fn string_literals(code: &str) -> Vec<&str> {
+    let mut literals = Vec::new();
+    let mut chars = code.char_indices();
+    while let Some((start, quote)) = chars.next() {
+        if !matches!(quote, '\'' | '"' | '`') {
+            continue;
+        }
+        let mut escaped = false;
+        for (i, c) in chars.by_ref() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == quote {
+                literals.push(&code[start + 1..i]);
+                break;
+            } else if c == '\n' && quote != '`' {
+                break;
+            }
+        }
+    }
+    literals
+}
+
+/// Turn a literal into an absolute URL on the script's host if it looks
+/// like a route: `/api/users`, `/users/{id}`, or a full same-host URL.
+/// Template literals are cut at their first `${`.
+fn route_url(literal: &str, script: &Url) -> Option<String> {
+    let literal = literal.split("${").next().unwrap_or(literal);
+    if literal.len() < 2 || literal.len() > MAX_LITERAL_LEN {
+        return None;
+    }
+    if literal.starts_with("http://") || literal.starts_with("https://") {
+        let url = Url::parse(literal).ok()?;
+        return (url.host_str() == script.host_str()).then(|| url.to_string());
+    }
+    let mut chars = literal.chars();
+    if chars.next() != Some('/') || !chars.next().is_some_and(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    if !literal
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "/-_.~:?=&%+@{}".contains(c))
+    {
+        return None;
+    }
+    script.join(literal).ok().map(|u| u.to_string())
+}
+
+impl Tester for SourceMapExtractor {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Mines the source map of a JavaScript URL, returning the URLs found
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.extract(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Source map extraction cancelled for {url}")),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Enables or disables SSL certificate verification
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+
+    fn with_max_body_bytes(&mut self, bytes: u64) {
+        self.max_body_bytes = bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_mapping_url() {
+        assert_eq!(
+            source_mapping_url("var a=1;\n//# sourceMappingURL=app.js.map\n"),
+            Some("app.js.map")
+        );
+        assert_eq!(
+            source_mapping_url("x\n/*# sourceMappingURL=main.map */"),
+            Some("main.map")
+        );
+        // A mention inside code isn't a pragma.
+        assert_eq!(source_mapping_url("s='sourceMappingURL=x.map'"), None);
+        assert_eq!(source_mapping_url("var a=1;"), None);
+    }
+
+    #[test]
+    fn test_route_url() {
+        let script = Url::parse("https://example.com/static/app.js").unwrap();
+        assert_eq!(
+            route_url("/api/v2/users", &script).as_deref(),
+            Some("https://example.com/api/v2/users")
+        );
+        assert_eq!(
+            route_url("/api/orders/${id}", &script).as_deref(),
+            Some("https://example.com/api/orders/")
+        );
+        assert_eq!(
+            route_url("https://example.com/internal", &script).as_deref(),
+            Some("https://example.com/internal")
+        );
+        assert_eq!(route_url("https://cdn.other.com/x", &script), None);
+        assert_eq!(route_url("/", &script), None);
+        assert_eq!(route_url("/^[a-z]+$/", &script), None);
+        assert_eq!(route_url("not a route", &script), None);
+        assert_eq!(route_url("/has space", &script), None);
+    }
+
+    #[test]
+    fn test_mine_source_map() {
+        let map: SourceMap = serde_json::from_str(
+            r#"{
+                "sources": ["../src/app.ts", "webpack:///./src/x.ts", "../node_modules/lib/index.js"],
+                "sourcesContent": [
+                    "fetch(\"/api/users\"); const a = 'admin'; get(`/api/orders/${id}`);",
+                    "router.get('/internal/debug')",
+                    "fetch('/vendor/only')"
+                ]
+            }"#,
+        )
+        .unwrap();
+        let script = Url::parse("https://example.com/static/js/app.js").unwrap();
+        let map_url = script.join("app.js.map").unwrap();
+        let urls = mine_source_map(&map, &map_url, &script);
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/static/src/app.ts",
+                "https://example.com/api/users",
+                "https://example.com/api/orders/",
+                "https://example.com/internal/debug",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotate_sourcemap() {
+        let mut metadata = Map::new();
+        annotate_sourcemap(&mut metadata, "https://example.com/app.js");
+        annotate_sourcemap(&mut metadata, "https://example.com/app.js");
+        assert_eq!(metadata["sourcemap_script"], "https://example.com/app.js");
+        assert_eq!(metadata["tags"], serde_json::json!(["sourcemap"]));
+    }
+
+    #[tokio::test]
+    async fn test_extracts_routes_from_source_map() {
+        let mut server = mockito::Server::new_async().await;
+        let script = server
+            .mock("GET", "/static/app.js")
+            .with_status(200)
+            .with_body("console.log(1);\n//# sourceMappingURL=app.js.map")
+            .expect(1)
+            .create_async()
+            .await;
+        let map = server
+            .mock("GET", "/static/app.js.map")
+            .with_status(200)
+            .with_body(
+                r#"{"version":3,"sources":["webpack:///src/api.js"],"sourcesContent":["axios.post('/api/v1/login')"]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let extractor = SourceMapExtractor::new();
+        let urls = extractor
+            .test_url(&format!("{}/static/app.js", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                format!("{}/static/app.js.map", server.url()),
+                format!("{}/api/v1/login", server.url()),
+            ]
+        );
+
+        // Not a script: skipped without a request.
+        let none = extractor
+            .test_url(&format!("{}/index.html", server.url()))
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+        script.assert();
+        map.assert();
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_sibling_map() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/js/main.js")
+            .with_body("console.log(1);")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/js/main.js.map")
+            .with_body(r#"{"version":3,"sources":["../src/main.ts"],"sourcesContent":[null]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/js/vendor.js")
+            .with_body("console.log(2);")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/js/vendor.js.map")
+            .with_body("<html>app shell</html>")
+            .create_async()
+            .await;
+
+        let extractor = SourceMapExtractor::new();
+        let urls = extractor
+            .test_url(&format!("{}/js/main.js?v=3", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(
+            urls,
+            vec![
+                format!("{}/js/main.js.map", server.url()),
+                format!("{}/src/main.ts", server.url()),
+            ]
+        );
+        let none = extractor
+            .test_url(&format!("{}/js/vendor.js", server.url()))
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    }
+}
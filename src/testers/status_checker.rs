@@ -1,12 +1,78 @@
 use anyhow::Result;
+use regex::Regex;
 use reqwest::Client;
+use sha2::{Digest, Sha256};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
 use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+
+use super::{SecretScanner, Tester};
+use crate::filters::status_matches_pattern;
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{
+    read_bytes_capped, server_retry_delay, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES,
+};
+use crate::output::UrlData;
+
+/// Prefixes of the result lines carrying response headers after the
+/// `{url} - {status}` line; see [`status_entry`].
+const CONTENT_TYPE_PREFIX: &str = "content-type: ";
+const CONTENT_LENGTH_PREFIX: &str = "content-length: ";
+const BODY_SHA256_PREFIX: &str = "body-sha256: ";
+const WORD_COUNT_PREFIX: &str = "words: ";
+const LINE_COUNT_PREFIX: &str = "lines: ";
+const HEADER_PREFIX: &str = "header: ";
+const PROTOCOL_PREFIX: &str = "protocol: ";
+
+/// Turn a [`StatusChecker`] result back into an entry: the first line is
+/// `{url} - {status}`, the optional lines after it carry the response's
+/// `Content-Type` and `Content-Length` and, with `--body-stats`, the body's
+/// SHA-256 and word and line counts, and with `--include-headers`, the
+/// requested headers as `header: {name}: {value}`, and with
+/// `--http-version`, the protocol the response came over. `None` when the
+/// status was filtered out.
+pub fn status_entry(results: Vec<String>) -> Option<UrlData> {
+    let mut lines = results.into_iter();
+    let mut entry = UrlData::from_string(lines.next()?);
+    for line in lines {
+        if let Some(content_type) = line.strip_prefix(CONTENT_TYPE_PREFIX) {
+            entry.content_type = Some(content_type.to_string());
+        } else if let Some(length) = line.strip_prefix(CONTENT_LENGTH_PREFIX) {
+            entry.content_length = length.parse().ok();
+        } else if let Some(protocol) = line.strip_prefix(PROTOCOL_PREFIX) {
+            entry.protocol = Some(protocol.to_string());
+        } else if let Some(hash) = line.strip_prefix(BODY_SHA256_PREFIX) {
+            entry.body_sha256 = Some(hash.to_string());
+        } else if let Some(words) = line.strip_prefix(WORD_COUNT_PREFIX) {
+            entry.word_count = words.parse().ok();
+        } else if let Some(count) = line.strip_prefix(LINE_COUNT_PREFIX) {
+            entry.line_count = count.parse().ok();
+        } else if let Some((name, value)) = line
+            .strip_prefix(HEADER_PREFIX)
+            .and_then(|header| header.split_once(": "))
+        {
+            entry.headers.insert(name.to_string(), value.to_string());
+        }
+    }
+    Some(entry)
+}
 
-use super::Tester;
-use crate::network::client::HttpClientConfig;
+/// The `--body-stats` lines for a response body: its SHA-256 (hex), and
+/// its whitespace-separated word and line counts, as httpx reports them.
+fn body_stat_lines(body: &[u8]) -> Vec<String> {
+    let hash: String = Sha256::digest(body)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    let text = String::from_utf8_lossy(body);
+    vec![
+        format!("{BODY_SHA256_PREFIX}{hash}"),
+        format!("{WORD_COUNT_PREFIX}{}", text.split_whitespace().count()),
+        format!("{LINE_COUNT_PREFIX}{}", text.lines().count()),
+    ]
+}
 
 /// HTTP status checker for URLs
 #[derive(Clone)]
@@ -17,6 +83,7 @@ pub struct StatusChecker {
     retries: u32,
     random_agent: bool,
     insecure: bool,
+    http_version: HttpVersion,
     include_status: Option<Vec<String>>,
     exclude_status: Option<Vec<String>>,
     /// One HTTP client, built lazily on first use and reused for every tested
@@ -28,6 +95,22 @@ pub struct StatusChecker {
     /// only after the `with_*` setters have applied network settings, so it
     /// always reflects the final configuration.
     client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+    /// With `--scan-secrets`, bodies are read and scanned; otherwise only
+    /// the status line and headers are.
+    secrets: Option<SecretScanner>,
+    /// With `--body-stats`, bodies are read and hashed and counted.
+    body_stats: bool,
+    /// Lowercase names of the headers `--include-headers` captures.
+    include_headers: Vec<String>,
+    /// `--match-body`: bodies must match to be kept.
+    match_body: Option<Regex>,
+    /// `--filter-body`: bodies that match are dropped.
+    filter_body: Option<Regex>,
+    /// Record each response's HTTP version.
+    record_protocol: bool,
 }
 
 impl StatusChecker {
@@ -40,9 +123,19 @@ impl StatusChecker {
             retries: 3,
             random_agent: false,
             insecure: false,
+            http_version: HttpVersion::Auto,
             include_status: None,
             exclude_status: None,
             client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            secrets: None,
+            body_stats: false,
+            include_headers: Vec::new(),
+            match_body: None,
+            filter_body: None,
+            record_protocol: false,
         }
     }
 
@@ -56,6 +149,40 @@ impl StatusChecker {
         self.exclude_status = status_codes;
     }
 
+    /// Also record each body's SHA-256 and word and line counts
+    pub fn with_body_stats(&mut self, enabled: bool) {
+        self.body_stats = enabled;
+    }
+
+    /// Sets the response headers to capture, by name (case-insensitive)
+    pub fn with_include_headers(&mut self, names: Vec<String>) {
+        self.include_headers = names
+            .into_iter()
+            .map(|name| name.trim().to_ascii_lowercase())
+            .filter(|name| !name.is_empty())
+            .collect();
+    }
+
+    /// Also record the HTTP version each response came over
+    pub fn with_record_protocol(&mut self, enabled: bool) {
+        self.record_protocol = enabled;
+    }
+
+    /// Keep only URLs whose body matches `keep`, and drop those whose body
+    /// matches `drop`, the way the status filters drop codes
+    pub fn with_body_match(&mut self, keep: Option<Regex>, drop: Option<Regex>) {
+        self.match_body = keep;
+        self.filter_body = drop;
+    }
+
+    /// Whether a body passes `--match-body` and `--filter-body`. A body that
+    /// could not be read matches nothing.
+    fn should_include_body(&self, body: Option<&str>) -> bool {
+        let matches = |regex: &Regex| body.is_some_and(|b| regex.is_match(b));
+        self.match_body.as_ref().is_none_or(matches)
+            && !self.filter_body.as_ref().is_some_and(matches)
+    }
+
     fn client_config(&self) -> HttpClientConfig {
         HttpClientConfig {
             timeout: self.timeout,
@@ -63,6 +190,7 @@ impl StatusChecker {
             random_agent: self.random_agent,
             proxy: self.proxy.clone(),
             proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
         }
     }
 
@@ -78,29 +206,7 @@ impl StatusChecker {
     /// Checks if a status code matches a pattern
     /// Patterns can be exact (e.g., "200") or wildcard (e.g., "20x", "3xx")
     fn status_matches_pattern(&self, status_code: u16, pattern: &str) -> bool {
-        if pattern.contains('x') || pattern.contains('X') {
-            let status_str = status_code.to_string();
-            let pattern = pattern.to_lowercase();
-
-            if status_str.len() != pattern.len() {
-                return false;
-            }
-
-            for (s, p) in status_str.chars().zip(pattern.chars()) {
-                if p != 'x' && p != s {
-                    return false;
-                }
-            }
-
-            true
-        } else {
-            // Exact match
-            if let Ok(pattern_code) = pattern.parse::<u16>() {
-                status_code == pattern_code
-            } else {
-                false
-            }
-        }
+        status_matches_pattern(status_code, pattern)
     }
 
     /// Checks if a status code matches any pattern in the given patterns vector
@@ -138,6 +244,122 @@ impl StatusChecker {
         // If neither filter is set, include all status codes
         true
     }
+
+    /// Request `url` (with retries) and format its status line. Connection
+    /// failures are retried, and so are 429 and 503 answers, after the
+    /// server's `Retry-After` when it sent one; a throttled URL whose
+    /// retries run out reports that last status.
+    async fn check(&self, url: &str) -> Result<Vec<String>> {
+        let client = self.client().await?;
+
+        // Perform the request with retries
+        let mut last_error = None;
+        let mut server_delay = None;
+
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, server_delay.take()).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(url).send().await {
+                Ok(response) => {
+                    let status = response.status();
+                    if attempt < self.retries
+                        && matches!(
+                            status,
+                            reqwest::StatusCode::TOO_MANY_REQUESTS
+                                | reqwest::StatusCode::SERVICE_UNAVAILABLE
+                        )
+                    {
+                        server_delay = server_retry_delay(status, response.headers());
+                        continue;
+                    }
+                    let status_code = status.as_u16();
+
+                    // Check if this status code should be included in results
+                    if !self.should_include_status(status_code) {
+                        return Ok(vec![]); // Return empty vec if filtered out
+                    }
+
+                    let status_text = format!(
+                        "{} {}",
+                        status_code,
+                        status.canonical_reason().unwrap_or("")
+                    );
+                    let mut lines = vec![format!("{} - {}", url, status_text)];
+                    if self.record_protocol {
+                        let protocol = match response.version() {
+                            reqwest::Version::HTTP_09 => "HTTP/0.9",
+                            reqwest::Version::HTTP_10 => "HTTP/1.0",
+                            reqwest::Version::HTTP_2 => "HTTP/2",
+                            reqwest::Version::HTTP_3 => "HTTP/3",
+                            _ => "HTTP/1.1",
+                        };
+                        lines.push(format!("{PROTOCOL_PREFIX}{protocol}"));
+                    }
+                    let header = |name| {
+                        response
+                            .headers()
+                            .get(name)
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::trim)
+                            .filter(|v| !v.is_empty())
+                    };
+                    if let Some(content_type) = header(reqwest::header::CONTENT_TYPE) {
+                        lines.push(format!("{CONTENT_TYPE_PREFIX}{content_type}"));
+                    }
+                    if let Some(length) =
+                        header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok())
+                    {
+                        lines.push(format!("{CONTENT_LENGTH_PREFIX}{length}"));
+                    }
+                    for name in &self.include_headers {
+                        // Repeated headers are joined the way HTTP folds them
+                        let values: Vec<&str> = response
+                            .headers()
+                            .get_all(name.as_str())
+                            .iter()
+                            .filter_map(|v| v.to_str().ok())
+                            .map(str::trim)
+                            .collect();
+                        if !values.is_empty() {
+                            lines.push(format!("{HEADER_PREFIX}{name}: {}", values.join(", ")));
+                        }
+                    }
+                    let by_body = self.match_body.is_some() || self.filter_body.is_some();
+                    if self.body_stats || by_body || self.secrets.is_some() {
+                        // An oversized or broken body only costs the stats
+                        // and the scan, and fails --match-body
+                        let body = read_bytes_capped(response, self.max_body_bytes).await.ok();
+                        let text = body.as_deref().map(String::from_utf8_lossy);
+                        // Secrets count even on pages the body filters drop
+                        if let (Some(secrets), Some(text)) = (&self.secrets, &text) {
+                            secrets.scan(url, text);
+                        }
+                        if !self.should_include_body(text.as_deref()) {
+                            return Ok(vec![]);
+                        }
+                        if let (true, Some(body)) = (self.body_stats, &body) {
+                            lines.extend(body_stat_lines(body));
+                        }
+                    }
+                    return Ok(lines);
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // If we get here, all retries failed
+        Err(anyhow::anyhow!(
+            "Failed to check status for {}: {:?}",
+            url,
+            last_error
+        ))
+    }
 }
 
 impl Tester for StatusChecker {
@@ -152,43 +374,10 @@ impl Tester for StatusChecker {
         url: &'a str,
     ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
         Box::pin(async move {
-            let client = self.client().await?;
-
-            // Perform the request with retries
-            let mut last_error = None;
-
-            for _ in 0..=self.retries {
-                match client.get(url).send().await {
-                    Ok(response) => {
-                        let status = response.status();
-                        let status_code = status.as_u16();
-
-                        // Check if this status code should be included in results
-                        if !self.should_include_status(status_code) {
-                            return Ok(vec![]); // Return empty vec if filtered out
-                        }
-
-                        let status_text = format!(
-                            "{} {}",
-                            status_code,
-                            status.canonical_reason().unwrap_or("")
-                        );
-                        return Ok(vec![format!("{} - {}", url, status_text)]);
-                    }
-                    Err(e) => {
-                        last_error = Some(e);
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        continue;
-                    }
-                }
+            match self.cancel.run_until_cancelled(self.check(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Status check cancelled for {url}")),
             }
-
-            // If we get here, all retries failed
-            Err(anyhow::anyhow!(
-                "Failed to check status for {}: {:?}",
-                url,
-                last_error
-            ))
         })
     }
 
@@ -221,6 +410,18 @@ impl Tester for StatusChecker {
     fn with_proxy_auth(&mut self, auth: Option<String>) {
         self.proxy_auth = auth;
     }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
 }
 
 #[cfg(test)]
@@ -371,6 +572,8 @@ mod tests {
         let missing = server
             .mock("GET", "/missing")
             .with_status(404)
+            .with_header("content-type", "text/html; charset=utf-8")
+            .with_body("not here")
             .expect(1)
             .create_async()
             .await;
@@ -387,9 +590,170 @@ mod tests {
 
         assert!(ok_result[0].contains("200"));
         assert!(missing_result[0].contains("404"));
+        let entry = status_entry(missing_result).unwrap();
+        assert_eq!(entry.status.as_deref(), Some("404 Not Found"));
+        assert_eq!(
+            entry.content_type.as_deref(),
+            Some("text/html; charset=utf-8")
+        );
+        assert_eq!(entry.content_length, Some(8));
         // A single client was built and shared across both requests.
         assert!(checker.client.get().is_some());
         ok.assert();
         missing.assert();
     }
+
+    #[tokio::test]
+    async fn test_retries_throttled_responses() {
+        let mut server = mockito::Server::new_async().await;
+        let throttled = server
+            .mock("GET", "/busy")
+            .with_status(429)
+            .with_header("retry-after", "0")
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut checker = StatusChecker::new();
+        checker.with_retries(1);
+        let result = checker
+            .test_url(&format!("{}/busy", server.url()))
+            .await
+            .unwrap();
+        // Out of retries: the throttle status is what gets reported
+        assert!(result[0].ends_with("429 Too Many Requests"));
+        throttled.assert();
+    }
+
+    #[tokio::test]
+    async fn test_body_stats_hash_and_count_the_body() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/page")
+            .with_body("<p>hello world</p>\nsecond line\n")
+            .create_async()
+            .await;
+
+        let mut checker = StatusChecker::new();
+        let plain = checker
+            .test_url(&format!("{}/page", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(status_entry(plain).unwrap().body_sha256, None);
+
+        checker.with_body_stats(true);
+        let result = checker
+            .test_url(&format!("{}/page", server.url()))
+            .await
+            .unwrap();
+        let entry = status_entry(result).unwrap();
+        assert_eq!(
+            entry.body_sha256.as_deref(),
+            Some("c848db211ccf9f655a7e33abdf4b368ac81c0cbe4b0f6dfe42b78c833010eba3")
+        );
+        assert_eq!(entry.word_count, Some(4));
+        assert_eq!(entry.line_count, Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_body_match_and_filter() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/admin")
+            .with_body("<h1>Admin Panel</h1>")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/missing")
+            .with_body("<h1>Admin Panel</h1><p>Page Not Found</p>")
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/home")
+            .with_body("<h1>Welcome</h1>")
+            .create_async()
+            .await;
+
+        let mut checker = StatusChecker::new();
+        checker.with_body_match(
+            Some(Regex::new("(?i)admin panel").unwrap()),
+            Some(Regex::new("Not Found").unwrap()),
+        );
+        let mut kept = Vec::new();
+        for path in ["/admin", "/missing", "/home"] {
+            let url = format!("{}{path}", server.url());
+            if !checker.test_url(&url).await.unwrap().is_empty() {
+                kept.push(path);
+            }
+        }
+        assert_eq!(kept, ["/admin"]);
+    }
+
+    #[tokio::test]
+    async fn test_records_protocol_per_http_version() {
+        let mut server = mockito::Server::new_async().await;
+        server.mock("GET", "/").create_async().await;
+        let url = format!("{}/", server.url());
+
+        let mut checker = StatusChecker::new();
+        let plain = checker.test_url(&url).await.unwrap();
+        assert_eq!(status_entry(plain).unwrap().protocol, None);
+
+        checker.with_record_protocol(true);
+        let entry = status_entry(checker.test_url(&url).await.unwrap()).unwrap();
+        assert_eq!(entry.protocol.as_deref(), Some("HTTP/1.1"));
+
+        // Prior knowledge speaks HTTP/2 even over plain HTTP
+        let mut checker = StatusChecker::new();
+        checker.with_record_protocol(true);
+        checker.with_http_version(HttpVersion::Http2);
+        let entry = status_entry(checker.test_url(&url).await.unwrap()).unwrap();
+        assert_eq!(entry.protocol.as_deref(), Some("HTTP/2"));
+    }
+
+    #[tokio::test]
+    async fn test_include_headers_captures_requested_headers() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/")
+            .with_header("server", "nginx")
+            .with_header("x-powered-by", "PHP/8.2")
+            .with_header("set-cookie", "a=1")
+            .with_header("set-cookie", "b=2")
+            .create_async()
+            .await;
+
+        let mut checker = StatusChecker::new();
+        checker.with_include_headers(vec![
+            "Server".to_string(),
+            "set-cookie".to_string(),
+            "location".to_string(),
+        ]);
+        let result = checker
+            .test_url(&format!("{}/", server.url()))
+            .await
+            .unwrap();
+        let entry = status_entry(result).unwrap();
+        assert_eq!(entry.headers.len(), 2);
+        assert_eq!(entry.headers["server"], "nginx");
+        assert_eq!(entry.headers["set-cookie"], "a=1, b=2");
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_token_aborts_check() {
+        // Port 1 would otherwise burn through retries with back-off sleeps; a
+        // cancelled token must short-circuit before any of that.
+        let token = CancellationToken::new();
+        token.cancel();
+        let mut checker = StatusChecker::new();
+        checker.with_cancellation(token);
+
+        let started = std::time::Instant::now();
+        let err = checker
+            .test_url("http://127.0.0.1:1/")
+            .await
+            .expect_err("cancelled check should fail");
+        assert!(err.to_string().contains("cancelled"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
 }
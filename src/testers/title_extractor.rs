@@ -0,0 +1,308 @@
+use anyhow::Result;
+use encoding_rs::{Encoding, UTF_8};
+use regex::bytes::Regex;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+
+use super::Tester;
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{read_bytes_capped, wait_before_retry, RequestGate, DEFAULT_MAX_BODY_BYTES};
+
+/// How far into the body a `<meta charset>` declaration is looked for, as
+/// in the HTML spec's encoding prescan.
+const META_PRESCAN_BYTES: usize = 1024;
+
+/// `<meta charset="…">` and `<meta http-equiv="Content-Type"
+/// content="text/html; charset=…">`.
+static META_CHARSET: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)<meta[^>]*?charset\s*=\s*["']?\s*([a-z0-9_:.\-]+)"#).unwrap()
+});
+
+/// Page title extractor for `--extract-title`
+///
+/// Fetches each URL and reports the text of its `<title>` with whitespace
+/// collapsed, like httpx's `-title`. Responses that declare a non-HTML
+/// content type are skipped without reading the body.
+#[derive(Clone)]
+pub struct TitleExtractor {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+    max_body_bytes: u64,
+}
+
+impl TitleExtractor {
+    /// Creates a new TitleExtractor with default settings
+    pub fn new() -> Self {
+        TitleExtractor {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            client: Arc::new(OnceCell::new()),
+            cancel: CancellationToken::new(),
+            gate: None,
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async { self.client_config().build_client() })
+            .await
+    }
+
+    /// Whether a `Content-Type` value can carry a title. A missing header
+    /// is given the benefit of the doubt.
+    fn is_html(content_type: Option<&str>) -> bool {
+        content_type.is_none_or(|ct| {
+            let mime = ct.split(';').next().unwrap_or_default().trim();
+            mime.eq_ignore_ascii_case("text/html")
+                || mime.eq_ignore_ascii_case("application/xhtml+xml")
+        })
+    }
+
+    /// Decode an HTML body. A byte order mark wins, then the `charset` of
+    /// the `Content-Type` header, then a `<meta>` declaration near the top;
+    /// anything else is read as UTF-8.
+    fn decode(body: &[u8], content_type: Option<&str>) -> String {
+        let from_header = content_type.and_then(|ct| {
+            ct.split(';').skip(1).find_map(|param| {
+                let (name, value) = param.split_once('=')?;
+                name.trim()
+                    .eq_ignore_ascii_case("charset")
+                    .then(|| value.trim().trim_matches(['"', '\'']))
+            })
+        });
+        let from_meta = || {
+            let head = &body[..body.len().min(META_PRESCAN_BYTES)];
+            META_CHARSET
+                .captures(head)
+                .and_then(|c| Encoding::for_label(&c[1]))
+        };
+        let encoding = from_header
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .or_else(from_meta)
+            .unwrap_or(UTF_8);
+        // `decode` sniffs the BOM itself and replaces malformed sequences.
+        encoding.decode(body).0.into_owned()
+    }
+
+    /// Text of the first `<title>`, whitespace collapsed. `None` when the
+    /// page has no title or it is blank.
+    fn extract_title(html: &str) -> Option<String> {
+        let document = Html::parse_document(html);
+        // Constant, valid selector
+        let selector = Selector::parse("title").unwrap();
+        let title = document.select(&selector).next()?;
+        let text = title.text().collect::<String>();
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// Fetch `url` (with retries) and report its title, if any.
+    async fn extract(&self, url: &str) -> Result<Vec<String>> {
+        let client = self.client().await?;
+
+        let mut last_error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.get(url).send().await {
+                Ok(response) => {
+                    let content_type = response
+                        .headers()
+                        .get(reqwest::header::CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(str::to_string);
+                    if !Self::is_html(content_type.as_deref()) {
+                        return Ok(vec![]);
+                    }
+                    let body = read_bytes_capped(response, self.max_body_bytes).await?;
+                    let html = Self::decode(&body, content_type.as_deref());
+                    return Ok(Self::extract_title(&html).into_iter().collect());
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to extract title from {}: {:?}",
+            url,
+            last_error
+        ))
+    }
+}
+
+impl Tester for TitleExtractor {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Fetches a URL and returns its page title (empty when it has none)
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.extract(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("Title extraction cancelled for {url}")),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Enables or disables SSL certificate verification
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+
+    fn with_max_body_bytes(&mut self, bytes: u64) {
+        self.max_body_bytes = bytes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_title_collapses_whitespace() {
+        assert_eq!(
+            TitleExtractor::extract_title(
+                "<html><head><title>\n  Admin &amp;\n  Login </title></head></html>"
+            ),
+            Some("Admin & Login".to_string())
+        );
+        assert_eq!(
+            TitleExtractor::extract_title("<title>First</title><title>Second</title>"),
+            Some("First".to_string())
+        );
+        assert_eq!(TitleExtractor::extract_title("<title>  </title>"), None);
+        assert_eq!(TitleExtractor::extract_title("<p>no title</p>"), None);
+    }
+
+    #[test]
+    fn test_decode_follows_header_meta_and_bom() {
+        // "Café" in windows-1252 and Shift_JIS "日本"
+        let latin1 = b"<title>Caf\xe9</title>";
+        assert_eq!(
+            TitleExtractor::decode(latin1, Some("text/html; charset=ISO-8859-1")),
+            "<title>Café</title>"
+        );
+        let sjis = b"<meta charset=\"shift_jis\"><title>\x93\xfa\x96\x7b</title>";
+        assert!(TitleExtractor::decode(sjis, Some("text/html")).contains("日本"));
+        let sjis = b"<meta http-equiv=\"Content-Type\" content=\"text/html; charset=Shift_JIS\"><title>\x93\xfa\x96\x7b</title>";
+        assert!(TitleExtractor::decode(sjis, None).contains("日本"));
+        // The BOM beats a contradicting header
+        let bom = b"\xef\xbb\xbf<title>Caf\xc3\xa9</title>";
+        assert_eq!(
+            TitleExtractor::decode(bom, Some("text/html; charset=windows-1252")),
+            "<title>Café</title>"
+        );
+        assert_eq!(
+            TitleExtractor::decode(b"<title>Caf\xc3\xa9</title>", None),
+            "<title>Café</title>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_extracts_title_and_skips_non_html() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/page")
+            .with_header("content-type", "text/html; charset=iso-8859-1")
+            .with_body(b"<html><title>R\xe9sum\xe9</title></html>".as_slice())
+            .create_async()
+            .await;
+        let json = server
+            .mock("GET", "/api")
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"title":"<title>nope</title>"}"#)
+            .create_async()
+            .await;
+
+        let extractor = TitleExtractor::new();
+        let page = extractor
+            .test_url(&format!("{}/page", server.url()))
+            .await
+            .unwrap();
+        assert_eq!(page, vec!["Résumé".to_string()]);
+        let api = extractor
+            .test_url(&format!("{}/api", server.url()))
+            .await
+            .unwrap();
+        assert!(api.is_empty());
+        json.assert();
+    }
+}
@@ -0,0 +1,351 @@
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use super::Tester;
+use crate::network::client::{HttpClientConfig, HttpVersion};
+use crate::network::{wait_before_retry, RequestGate};
+
+/// Metadata key holding the certificate summary.
+const TLS_KEY: &str = "tls";
+
+/// Outcome of inspecting one host, shared by every URL on it. Failures are
+/// kept as their message so a dead host is only tried once.
+type HostResult = Arc<OnceCell<std::result::Result<Vec<String>, String>>>;
+
+/// Record a [`TlsInspector`] result on a URL's metadata as a `tls` object
+/// with `subject`, `issuer`, `sans`, `not_before` and `not_after`. Does
+/// nothing for an empty result (plain HTTP, or the host failed).
+pub fn annotate_tls(metadata: &mut Map<String, Value>, lines: &[String]) {
+    if lines.is_empty() {
+        return;
+    }
+    let mut tls = Map::new();
+    let mut sans = Vec::new();
+    for line in lines {
+        let Some((field, value)) = line.split_once(": ") else {
+            continue;
+        };
+        if field == "san" {
+            sans.push(Value::from(value));
+        } else {
+            tls.insert(field.to_string(), Value::from(value));
+        }
+    }
+    tls.insert("sans".to_string(), Value::Array(sans));
+    metadata.insert(TLS_KEY.to_string(), Value::Object(tls));
+}
+
+/// Summarize a DER certificate as `field: value` lines: `subject`, `issuer`,
+/// one `san` per DNS name or IP address, and the validity bounds as RFC 3339
+/// `not_before` / `not_after`.
+fn certificate_lines(der: &[u8]) -> Result<Vec<String>> {
+    use x509_parser::extensions::GeneralName;
+
+    let (_, cert) =
+        x509_parser::parse_x509_certificate(der).context("Failed to parse certificate")?;
+    let rfc3339 = |time: &x509_parser::time::ASN1Time| {
+        chrono::DateTime::from_timestamp(time.timestamp(), 0)
+            .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+            .unwrap_or_default()
+    };
+
+    let mut lines = vec![
+        format!("subject: {}", cert.subject()),
+        format!("issuer: {}", cert.issuer()),
+    ];
+    if let Ok(Some(san)) = cert.subject_alternative_name() {
+        for name in &san.value.general_names {
+            match name {
+                GeneralName::DNSName(dns) => lines.push(format!("san: {dns}")),
+                GeneralName::IPAddress(ip) => {
+                    let ip = match *ip {
+                        [a, b, c, d] => std::net::IpAddr::from([*a, *b, *c, *d]),
+                        bytes => match <[u8; 16]>::try_from(bytes) {
+                            Ok(v6) => std::net::IpAddr::from(v6),
+                            Err(_) => continue,
+                        },
+                    };
+                    lines.push(format!("san: {ip}"));
+                }
+                _ => {}
+            }
+        }
+    }
+    lines.push(format!(
+        "not_before: {}",
+        rfc3339(&cert.validity().not_before)
+    ));
+    lines.push(format!(
+        "not_after: {}",
+        rfc3339(&cert.validity().not_after)
+    ));
+    Ok(lines)
+}
+
+/// TLS certificate inspector for `--tls-info`
+///
+/// Connects once to each unique HTTPS host (`host:port`) and reports the
+/// leaf certificate it presents. Every URL on the host gets the same
+/// summary; plain HTTP URLs get none. Certificates are read even when they
+/// would fail verification, since expired or mismatched ones are exactly
+/// what an inspection is for.
+#[derive(Clone)]
+pub struct TlsInspector {
+    proxy: Option<String>,
+    proxy_auth: Option<String>,
+    timeout: u64,
+    retries: u32,
+    random_agent: bool,
+    insecure: bool,
+    http_version: HttpVersion,
+    /// Shared, lazily built client; see `StatusChecker::client`.
+    client: Arc<OnceCell<Client>>,
+    /// Per-host results, shared across `clone_box` clones like the client.
+    hosts: Arc<Mutex<HashMap<String, HostResult>>>,
+    cancel: CancellationToken,
+    gate: Option<RequestGate>,
+}
+
+impl TlsInspector {
+    /// Creates a new TlsInspector with default settings
+    pub fn new() -> Self {
+        TlsInspector {
+            proxy: None,
+            proxy_auth: None,
+            timeout: 30,
+            retries: 3,
+            random_agent: false,
+            insecure: false,
+            http_version: HttpVersion::Auto,
+            client: Arc::new(OnceCell::new()),
+            hosts: Arc::new(Mutex::new(HashMap::new())),
+            cancel: CancellationToken::new(),
+            gate: None,
+        }
+    }
+
+    fn client_config(&self) -> HttpClientConfig {
+        HttpClientConfig {
+            timeout: self.timeout,
+            insecure: self.insecure,
+            random_agent: self.random_agent,
+            proxy: self.proxy.clone(),
+            proxy_auth: self.proxy_auth.clone(),
+            http_version: self.http_version,
+        }
+    }
+
+    /// The shared client: TLS details on every response, any certificate
+    /// accepted, and no redirects, so the certificate is the one the host
+    /// itself presents.
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async {
+                Ok(self
+                    .client_config()
+                    .client_builder()?
+                    .tls_info(true)
+                    .danger_accept_invalid_certs(true)
+                    .redirect(reqwest::redirect::Policy::none())
+                    .build()?)
+            })
+            .await
+    }
+
+    /// `https://host:port/` for an HTTPS URL, `None` for anything else.
+    fn host_origin(url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        if parsed.scheme() != "https" {
+            return None;
+        }
+        let host = parsed.host_str()?;
+        let port = parsed.port_or_known_default()?;
+        Some(format!("https://{host}:{port}/"))
+    }
+
+    /// Connect to `origin` (with retries) and summarize its certificate.
+    async fn inspect(&self, origin: &str) -> Result<Vec<String>> {
+        let client = self.client().await?;
+
+        let mut last_error = None;
+        for attempt in 0..=self.retries {
+            if attempt > 0 {
+                wait_before_retry(attempt, None).await;
+            }
+            if let Some(gate) = &self.gate {
+                gate.acquire().await;
+            }
+            match client.head(origin).send().await {
+                Ok(response) => {
+                    let der = response
+                        .extensions()
+                        .get::<reqwest::tls::TlsInfo>()
+                        .and_then(|info| info.peer_certificate())
+                        .with_context(|| format!("No certificate presented by {origin}"))?;
+                    return certificate_lines(der);
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(anyhow::anyhow!(
+            "Failed to inspect TLS certificate of {}: {:?}",
+            origin,
+            last_error
+        ))
+    }
+
+    /// The certificate summary for `url`'s host, inspecting it on first use.
+    async fn check(&self, url: &str) -> Result<Vec<String>> {
+        let Some(origin) = Self::host_origin(url) else {
+            return Ok(vec![]);
+        };
+        let cell = {
+            let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+            Arc::clone(hosts.entry(origin.clone()).or_default())
+        };
+        cell.get_or_init(|| async { self.inspect(&origin).await.map_err(|e| e.to_string()) })
+            .await
+            .clone()
+            .map_err(anyhow::Error::msg)
+    }
+}
+
+impl Tester for TlsInspector {
+    fn clone_box(&self) -> Box<dyn Tester> {
+        Box::new(self.clone())
+    }
+
+    /// Returns the certificate summary of the URL's host (empty for HTTP)
+    fn test_url<'a>(
+        &'a self,
+        url: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<String>>> + Send + 'a>> {
+        Box::pin(async move {
+            match self.cancel.run_until_cancelled(self.check(url)).await {
+                Some(result) => result,
+                None => Err(anyhow::anyhow!("TLS inspection cancelled for {url}")),
+            }
+        })
+    }
+
+    /// Sets the request timeout in seconds
+    fn with_timeout(&mut self, seconds: u64) {
+        self.timeout = seconds;
+    }
+
+    /// Sets the number of retry attempts for failed requests
+    fn with_retries(&mut self, count: u32) {
+        self.retries = count;
+    }
+
+    /// Enables or disables the use of random User-Agent headers
+    fn with_random_agent(&mut self, enabled: bool) {
+        self.random_agent = enabled;
+    }
+
+    /// Certificates are always accepted for inspection; kept for the trait.
+    fn with_insecure(&mut self, enabled: bool) {
+        self.insecure = enabled;
+    }
+
+    /// Sets the proxy server for HTTP requests
+    fn with_proxy(&mut self, proxy: Option<String>) {
+        self.proxy = proxy;
+    }
+
+    /// Sets the proxy authentication credentials (username:password)
+    fn with_proxy_auth(&mut self, auth: Option<String>) {
+        self.proxy_auth = auth;
+    }
+
+    fn with_http_version(&mut self, version: HttpVersion) {
+        self.http_version = version;
+    }
+
+    fn with_cancellation(&mut self, token: CancellationToken) {
+        self.cancel = token;
+    }
+
+    fn with_request_gate(&mut self, gate: RequestGate) {
+        self.gate = Some(gate);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    /// Self-signed P-256 certificate for CN=example.com, O=Example Org with
+    /// SANs example.com, *.example.com, staging.example.com and 10.0.0.1.
+    const TEST_CERT: &str = "\
+MIIB3TCCAYSgAwIBAgIBATAKBggqhkjOPQQDAjAsMRQwEgYDVQQDDAtleGFtcGxlLmNvbTEUMBIG\
+A1UECgwLRXhhbXBsZSBPcmcwHhcNMjYxMDE3MDY0NjUwWhcNMzYxMDE0MDY0NjUwWjAsMRQwEgYD\
+VQQDDAtleGFtcGxlLmNvbTEUMBIGA1UECgwLRXhhbXBsZSBPcmcwWTATBgcqhkjOPQIBBggqhkjO\
+PQMBBwNCAAREpqnx4XNokkcDYPcR1SBm34myGKZv1g8lkwHPP4/4fzYpPqMZl4VPS1YWLKHqzZxF\
+5I9yezCCWWoXkHYwDmnzo4GWMIGTMB0GA1UdDgQWBBTj4AvuJqkNm65c/FejWVBV1TA9ezAfBgNV\
+HSMEGDAWgBTj4AvuJqkNm65c/FejWVBV1TA9ezAPBgNVHRMBAf8EBTADAQH/MEAGA1UdEQQ5MDeC\
+C2V4YW1wbGUuY29tgg0qLmV4YW1wbGUuY29tghNzdGFnaW5nLmV4YW1wbGUuY29thwQKAAABMAoG\
+CCqGSM49BAMCA0cAMEQCIGMHUHOAzTv+6z+cQVPsOY+1L2k/UpsE0MF2Nxldo2RRAiAFoi1KOnAU\
+OkXmZCHv2u3Ez6tBMd7krRF6eYh1AHGYoA==";
+
+    #[test]
+    fn test_certificate_summary_lands_in_metadata() {
+        let der = STANDARD.decode(TEST_CERT).unwrap();
+        let lines = certificate_lines(&der).unwrap();
+
+        let mut metadata = Map::new();
+        annotate_tls(&mut metadata, &lines);
+        let tls = &metadata["tls"];
+        assert_eq!(tls["subject"], "CN=example.com, O=Example Org");
+        assert_eq!(tls["issuer"], "CN=example.com, O=Example Org");
+        assert_eq!(
+            tls["sans"],
+            serde_json::json!([
+                "example.com",
+                "*.example.com",
+                "staging.example.com",
+                "10.0.0.1"
+            ])
+        );
+        assert_eq!(tls["not_before"], "2026-10-17T06:46:50Z");
+        assert_eq!(tls["not_after"], "2036-10-14T06:46:50Z");
+
+        let mut untouched = Map::new();
+        annotate_tls(&mut untouched, &[]);
+        assert!(untouched.is_empty());
+        assert!(certificate_lines(b"not a certificate").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_only_https_hosts_are_inspected() {
+        assert_eq!(
+            TlsInspector::host_origin("https://Example.com/a?b=1"),
+            Some("https://example.com:443/".to_string())
+        );
+        assert_eq!(
+            TlsInspector::host_origin("https://example.com:8443/"),
+            Some("https://example.com:8443/".to_string())
+        );
+        assert_eq!(TlsInspector::host_origin("http://example.com/"), None);
+
+        // No connection is attempted for plain HTTP
+        let inspector = TlsInspector::new();
+        assert!(inspector
+            .test_url("http://127.0.0.1:1/")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
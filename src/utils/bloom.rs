@@ -0,0 +1,120 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// Fixed-size probabilistic set used by `--stream` to drop duplicate URLs
+/// without keeping every URL in memory.
+///
+/// Membership answers are "definitely new" or "probably seen": a false
+/// positive makes a genuinely new URL look like a duplicate, at roughly the
+/// configured rate once `capacity` items have been inserted. Memory is fixed
+/// up front and never grows.
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Size a filter for `capacity` items at a target false-positive rate,
+    /// using the standard optimal bit count and hash count.
+    pub fn with_rate(capacity: usize, fp_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let fp_rate = fp_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-(capacity * fp_rate.ln()) / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2)
+            .round()
+            .clamp(1.0, 32.0) as u32;
+
+        BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    /// Approximate memory held by the bit array, in bytes.
+    pub fn size_bytes(&self) -> usize {
+        self.bits.len() * std::mem::size_of::<u64>()
+    }
+
+    /// Whether `item` has probably been inserted before.
+    #[cfg(test)]
+    pub fn contains(&self, item: &str) -> bool {
+        self.bit_indexes(item)
+            .all(|i| self.bits[(i / 64) as usize] & (1 << (i % 64)) != 0)
+    }
+
+    /// Insert `item`, returning `true` if it was definitely not present before.
+    pub fn insert(&mut self, item: &str) -> bool {
+        let mut new = false;
+        for i in self.bit_indexes(item) {
+            let (word, mask) = ((i / 64) as usize, 1u64 << (i % 64));
+            if self.bits[word] & mask == 0 {
+                self.bits[word] |= mask;
+                new = true;
+            }
+        }
+        new
+    }
+
+    /// Bit positions for `item`, derived from two base hashes by double
+    /// hashing (Kirsch–Mitzenmacher) rather than `num_hashes` separate ones.
+    fn bit_indexes(&self, item: &str) -> impl Iterator<Item = u64> {
+        let h1 = seeded_hash(item, 0);
+        // Odd step so the probe sequence can't collapse onto one bit
+        let h2 = seeded_hash(item, 1) | 1;
+        let num_bits = self.num_bits;
+        (0..u64::from(self.num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+fn seeded_hash(item: &str, seed: u8) -> u64 {
+    // DefaultHasher::new() uses fixed keys, so positions are stable for the
+    // lifetime of the process (all a single run needs).
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_reports_new_items() {
+        let mut filter = BloomFilter::with_rate(100, 0.01);
+        assert!(filter.insert("https://example.com/a"));
+        assert!(!filter.insert("https://example.com/a"));
+        assert!(filter.contains("https://example.com/a"));
+        assert!(filter.insert("https://example.com/b"));
+    }
+
+    #[test]
+    fn test_false_positive_rate_near_target() {
+        let mut filter = BloomFilter::with_rate(10_000, 0.01);
+        for i in 0..10_000 {
+            filter.insert(&format!("https://example.com/seen/{i}"));
+        }
+        let false_positives = (0..10_000)
+            .filter(|i| filter.contains(&format!("https://example.com/unseen/{i}")))
+            .count();
+        // 1% target; allow generous slack so the test isn't flaky
+        assert!(
+            false_positives < 300,
+            "got {false_positives} false positives"
+        );
+    }
+
+    #[test]
+    fn test_sizing() {
+        // ~9.6 bits per item at 1%
+        let filter = BloomFilter::with_rate(1_000_000, 0.01);
+        assert!(filter.size_bytes() > 1_000_000 && filter.size_bytes() < 1_400_000);
+        // Degenerate inputs still produce a usable filter
+        let mut tiny = BloomFilter::with_rate(0, 0.0);
+        assert!(tiny.insert("x"));
+        assert!(tiny.contains("x"));
+    }
+}
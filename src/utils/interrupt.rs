@@ -0,0 +1,68 @@
+//! One Ctrl-C listener for the whole process. tokio stops the default
+//! SIGINT action as soon as anything listens for it, so a `ctrl_c()` awaited
+//! only while providers or testers run would swallow a Ctrl-C pressed between
+//! phases (filtering, sorting, writing the cache). Instead the first Ctrl-C
+//! cancels one token every phase watches — fetching and testing stop, and the
+//! URLs gathered so far still go through the filters and get written — and a
+//! second one exits at once.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use tokio_util::sync::CancellationToken;
+
+/// Exit status after a forced quit, as for a shell job killed by SIGINT.
+pub const FORCED_EXIT_CODE: i32 = 130;
+
+/// Ctrl-C presses and the token the first one cancels.
+#[derive(Debug, Default)]
+pub struct Interrupt {
+    token: CancellationToken,
+    presses: AtomicUsize,
+}
+
+impl Interrupt {
+    /// Record a Ctrl-C. Returns `true` from the second one on, when the
+    /// caller should stop waiting for a graceful end.
+    pub fn press(&self) -> bool {
+        self.token.cancel();
+        self.presses.fetch_add(1, Ordering::SeqCst) > 0
+    }
+}
+
+fn global() -> &'static Interrupt {
+    static INTERRUPT: OnceLock<Interrupt> = OnceLock::new();
+    INTERRUPT.get_or_init(Interrupt::default)
+}
+
+/// Start listening for Ctrl-C. Call once, early in the run. If the handler
+/// can't be registered, Ctrl-C keeps its default action of ending the process.
+pub fn install() {
+    tokio::spawn(async {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if global().press() {
+                std::process::exit(FORCED_EXIT_CODE);
+            }
+        }
+    });
+}
+
+/// Resolves on the first Ctrl-C, or right away if it was already pressed.
+pub async fn interrupted() {
+    global().token.cancelled().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_first_press_cancels_second_forces() {
+        let interrupt = Interrupt::default();
+        assert!(!interrupt.token.is_cancelled());
+        assert!(!interrupt.press());
+        assert!(interrupt.token.is_cancelled());
+        // Already cancelled: a phase starting after the press sees it at once
+        interrupt.token.cancelled().await;
+        assert!(interrupt.press());
+    }
+}
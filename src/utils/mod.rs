@@ -1,6 +1,11 @@
+pub mod bloom;
+pub mod determinism;
+pub mod interrupt;
 pub mod url;
+pub mod url_pattern;
 use crate::cli::Args;
-pub use url::UrlTransformer;
+pub use bloom::BloomFilter;
+pub use url::{encode_query_host, normalize_url, split_host_port, UrlTransformer, TRACKING_PARAMS};
 
 /// Prints messages only when verbose mode is enabled
 ///
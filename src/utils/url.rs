@@ -1,6 +1,39 @@
+use anyhow::{anyhow, bail, Result};
 use std::collections::{HashMap, HashSet};
 use url::Url;
 
+/// Placeholder written by `--fuzz-params`/`--fuzz-path-last`, the keyword
+/// ffuf and wfuzz substitute their wordlist into.
+pub const FUZZ_PLACEHOLDER: &str = "FUZZ";
+
+/// Tracking parameters removed by `--clean-params`. A trailing `*` matches any
+/// parameter name with that prefix.
+pub const TRACKING_PARAMS: &[&str] = &[
+    "utm_*",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "fbclid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "ttclid",
+    "igshid",
+    "li_fat_id",
+    "mc_cid",
+    "mc_eid",
+    "_ga",
+    "_gl",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+    "oly_anon_id",
+    "oly_enc_id",
+    "vero_id",
+];
+
 /// Utility for transforming and manipulating URL collections
 ///
 /// Provides methods for merging, filtering, and extracting parts of URLs.
@@ -9,7 +42,17 @@ pub struct UrlTransformer {
     show_only_host: bool,
     show_only_path: bool,
     show_only_param: bool,
+    show_only_param_keys: bool,
+    param_key_counts: bool,
     normalize_url: bool,
+    ignore_port: bool,
+    /// Lowercased query parameter names to drop; `prefix*` drops a prefix
+    strip_params: Vec<String>,
+    dedup_similar: bool,
+    sample_per_path: Option<usize>,
+    fuzz_params: bool,
+    fuzz_path_last: bool,
+    replace_values: Option<String>,
 }
 
 impl UrlTransformer {
@@ -20,10 +63,69 @@ impl UrlTransformer {
             show_only_host: false,
             show_only_path: false,
             show_only_param: false,
+            show_only_param_keys: false,
+            param_key_counts: false,
             normalize_url: false,
+            ignore_port: false,
+            strip_params: Vec::new(),
+            dedup_similar: false,
+            sample_per_path: None,
+            fuzz_params: false,
+            fuzz_path_last: false,
+            replace_values: None,
         }
     }
 
+    /// When enabled, keeps one URL per structural pattern: URLs that only
+    /// differ in numeric ids, UUIDs, hashes, dates or parameter values
+    /// collapse into the first of them (see [`url_template`](super::url_pattern::url_template))
+    pub fn with_dedup_similar(&mut self, dedup: bool) -> &mut Self {
+        self.dedup_similar = dedup;
+        self
+    }
+
+    /// Keeps at most this many URLs per host and path (see
+    /// [`sample_per_path`](super::url_pattern::sample_per_path))
+    pub fn with_sample_per_path(&mut self, limit: Option<usize>) -> &mut Self {
+        self.sample_per_path = limit;
+        self
+    }
+
+    /// When enabled, replaces every query parameter value with
+    /// [`FUZZ_PLACEHOLDER`]; URLs without parameters are dropped
+    pub fn with_fuzz_params(&mut self, fuzz: bool) -> &mut Self {
+        self.fuzz_params = fuzz;
+        self
+    }
+
+    /// Sets every query parameter value to `value` (qsreplace-style payload
+    /// injection); URLs without parameters are dropped. `&`, `=`, `+` and `#`
+    /// are percent-encoded so the payload stays one value; anything else is
+    /// inserted as given, so pre-encoded payloads keep their escapes
+    pub fn with_replace_values(&mut self, value: Option<String>) -> &mut Self {
+        self.replace_values = value;
+        self
+    }
+
+    /// When enabled, replaces the last path segment (everything after the
+    /// final `/`) with [`FUZZ_PLACEHOLDER`]
+    pub fn with_fuzz_path_last(&mut self, fuzz: bool) -> &mut Self {
+        self.fuzz_path_last = fuzz;
+        self
+    }
+
+    /// Removes these query parameters (case-insensitive; `utm_*` matches
+    /// every name starting with `utm_`), so URLs that only differ in
+    /// tracking parameters collapse into one
+    pub fn with_strip_params(&mut self, params: Vec<String>) -> &mut Self {
+        self.strip_params = params
+            .into_iter()
+            .map(|param| param.trim().to_lowercase())
+            .filter(|param| !param.is_empty())
+            .collect();
+        self
+    }
+
     /// Enables or disables merging of endpoints with the same path but different parameters
     pub fn with_merge_endpoint(&mut self, merge: bool) -> &mut Self {
         self.merge_endpoint = merge;
@@ -48,6 +150,21 @@ impl UrlTransformer {
         self
     }
 
+    /// When enabled, replaces the URLs with the sorted, deduplicated query
+    /// parameter names seen across all of them (a fuzzing wordlist)
+    pub fn with_show_only_param_keys(&mut self, show: bool) -> &mut Self {
+        self.show_only_param_keys = show;
+        self
+    }
+
+    /// With [`with_show_only_param_keys`](Self::with_show_only_param_keys),
+    /// prints `name<TAB>count` lines instead, counting the URLs each name
+    /// appears in, most common first
+    pub fn with_param_key_counts(&mut self, counts: bool) -> &mut Self {
+        self.param_key_counts = counts;
+        self
+    }
+
     /// When enabled, normalizes URLs for better deduplication
     /// Sorts query parameters alphabetically and normalizes paths
     pub fn with_normalize_url(&mut self, normalize: bool) -> &mut Self {
@@ -55,64 +172,163 @@ impl UrlTransformer {
         self
     }
 
+    /// When enabled, drops explicit ports so `host:8080/x` and `host/x`
+    /// collapse into one URL
+    pub fn with_ignore_port(&mut self, ignore: bool) -> &mut Self {
+        self.ignore_port = ignore;
+        self
+    }
+
     /// Transforms a list of URLs according to the configured settings
     pub fn transform(&self, urls: Vec<String>) -> Vec<String> {
         let mut transformed_urls = urls;
 
+        // Strip ports first so normalization and merging see the port-less form
+        if self.ignore_port {
+            transformed_urls = self.strip_ports(transformed_urls);
+        }
+
+        if !self.strip_params.is_empty() {
+            transformed_urls = self.strip_query_params(transformed_urls);
+        }
+
         // Normalize URLs if requested (should happen before merging)
         if self.normalize_url {
             transformed_urls = self.normalize_urls(transformed_urls);
         }
 
+        // Collapse look-alike URLs before merging, which would otherwise fold
+        // their differing values into one parameter list
+        if self.dedup_similar {
+            transformed_urls = super::url_pattern::dedup_similar(transformed_urls);
+        }
+
+        if let Some(limit) = self.sample_per_path {
+            transformed_urls = super::url_pattern::sample_per_path(transformed_urls, limit);
+        }
+
         // Merge endpoints if requested
         if self.merge_endpoint {
             transformed_urls = self.merge_endpoints(transformed_urls);
         }
 
+        if self.fuzz_params || self.fuzz_path_last || self.replace_values.is_some() {
+            transformed_urls = self.insert_placeholders(transformed_urls);
+        }
+
         // Extract URL parts if any show_only option is enabled
-        if self.show_only_host || self.show_only_path || self.show_only_param {
+        if self.show_only_param_keys {
+            transformed_urls = self.extract_param_keys(transformed_urls);
+        } else if self.show_only_host || self.show_only_path || self.show_only_param {
             transformed_urls = self.extract_url_parts(transformed_urls);
         }
 
         transformed_urls
     }
 
-    fn normalize_urls(&self, urls: Vec<String>) -> Vec<String> {
-        let mut normalized_urls = Vec::new();
+    fn strip_ports(&self, urls: Vec<String>) -> Vec<String> {
+        let mut seen = HashSet::new();
+        urls.into_iter()
+            .map(|url_str| match Url::parse(&url_str) {
+                Ok(mut url) if url.port().is_some() => {
+                    // Only fails for hosts that can't carry a port at all
+                    let _ = url.set_port(None);
+                    url.to_string()
+                }
+                _ => url_str,
+            })
+            .filter(|url| seen.insert(url.clone()))
+            .collect()
+    }
 
-        for url_str in urls {
-            if let Ok(mut url) = Url::parse(&url_str) {
-                // Normalize the path - remove trailing slash if it's not just "/"
-                let path = url.path().to_string();
-                if path.len() > 1 {
-                    if let Some(normalized_path) = path.strip_suffix('/') {
-                        url.set_path(normalized_path);
-                    }
+    fn strip_param(&self, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.strip_params
+            .iter()
+            .any(|param| match param.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == *param,
+            })
+    }
+
+    fn strip_query_params(&self, urls: Vec<String>) -> Vec<String> {
+        let mut seen = HashSet::new();
+        urls.into_iter()
+            .map(|url_str| {
+                let Ok(mut url) = Url::parse(&url_str) else {
+                    return url_str;
+                };
+                let Some(query) = url.query() else {
+                    return url_str;
+                };
+                // Work on the raw `key=value` tokens, like --normalize-url,
+                // so the parameters that stay are not re-encoded
+                let kept: Vec<&str> = query
+                    .split('&')
+                    .filter(|pair| !pair.is_empty())
+                    .filter(|pair| !self.strip_param(pair.split('=').next().unwrap_or(pair)))
+                    .collect();
+                let kept = kept.join("&");
+                if kept == query {
+                    return url_str;
                 }
+                url.set_query(None);
+                if !kept.is_empty() {
+                    url.set_query(Some(&kept));
+                }
+                url.to_string()
+            })
+            .filter(|url| seen.insert(url.clone()))
+            .collect()
+    }
 
-                // Normalize query parameters by sorting them. We sort the *raw*
-                // `key=value` tokens without decoding, so this stays a lossless
-                // reordering: a bare `?foo` is not rewritten to `?foo=`, and a
-                // literal '+' is not turned into '%20' (query_pairs() decodes
-                // both, which silently mutates the URL the archive recorded).
-                let sorted_query: Option<String> = url.query().map(|query| {
-                    let mut pairs: Vec<&str> = query.split('&').filter(|s| !s.is_empty()).collect();
-                    pairs.sort_unstable();
-                    pairs.join("&")
-                });
-                if let Some(query) = sorted_query {
-                    url.set_query(None);
-                    if !query.is_empty() {
-                        url.set_query(Some(&query));
+    fn insert_placeholders(&self, urls: Vec<String>) -> Vec<String> {
+        let value = match &self.replace_values {
+            Some(value) => value
+                .replace('&', "%26")
+                .replace('=', "%3D")
+                .replace('+', "%2B")
+                .replace('#', "%23"),
+            None if self.fuzz_params => FUZZ_PLACEHOLDER.to_string(),
+            None => String::new(),
+        };
+        let replace_values = self.fuzz_params || self.replace_values.is_some();
+
+        let mut seen = HashSet::new();
+        urls.into_iter()
+            .filter_map(|url_str| {
+                let mut url = Url::parse(&url_str).ok()?;
+                if replace_values {
+                    // Keep the raw names so they aren't re-encoded
+                    let fuzzed: Vec<String> = url
+                        .query()?
+                        .split('&')
+                        .filter(|pair| !pair.is_empty())
+                        .map(|pair| {
+                            let name = pair.split('=').next().unwrap_or(pair);
+                            format!("{name}={value}")
+                        })
+                        .collect();
+                    if fuzzed.is_empty() {
+                        return None;
                     }
+                    url.set_query(Some(&fuzzed.join("&")));
+                }
+                if self.fuzz_path_last {
+                    let path = url.path();
+                    let dir = &path[..path.rfind('/').map_or(0, |slash| slash + 1)];
+                    let fuzzed = format!("{dir}{FUZZ_PLACEHOLDER}");
+                    url.set_path(&fuzzed);
                 }
+                url.set_fragment(None);
+                Some(url.to_string())
+            })
+            .filter(|url| seen.insert(url.clone()))
+            .collect()
+    }
 
-                normalized_urls.push(url.to_string());
-            } else {
-                // If URL can't be parsed, keep it as is
-                normalized_urls.push(url_str);
-            }
-        }
+    fn normalize_urls(&self, urls: Vec<String>) -> Vec<String> {
+        let mut normalized_urls: Vec<String> = urls.iter().map(|url| normalize_url(url)).collect();
 
         // Remove duplicates that might have been created during normalization
         normalized_urls.sort();
@@ -226,12 +442,180 @@ impl UrlTransformer {
 
         extracted_parts
     }
+
+    fn extract_param_keys(&self, urls: Vec<String>) -> Vec<String> {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for url_str in urls {
+            let Ok(url) = Url::parse(&url_str) else {
+                continue;
+            };
+            // Count each name once per URL, however often it repeats there
+            let names: HashSet<String> = url
+                .query_pairs()
+                .map(|(name, _)| name.into_owned())
+                .filter(|name| !name.is_empty())
+                .collect();
+            for name in names {
+                *counts.entry(name).or_default() += 1;
+            }
+        }
+
+        let mut keys: Vec<(String, usize)> = counts.into_iter().collect();
+        if self.param_key_counts {
+            keys.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+            keys.into_iter()
+                // A decoded tab in the name would read as the separator
+                .map(|(name, count)| format!("{}\t{count}", name.replace('\t', "%09")))
+                .collect()
+        } else {
+            keys.sort();
+            keys.into_iter().map(|(name, _)| name).collect()
+        }
+    }
+}
+
+/// The `--normalize-url` form of `url`: no trailing slash on the path (unless
+/// it is just `/`) and query parameters sorted. Unparseable URLs come back
+/// unchanged.
+pub fn normalize_url(url_str: &str) -> String {
+    let Ok(mut url) = Url::parse(url_str) else {
+        return url_str.to_string();
+    };
+    // Normalize the path - remove trailing slash if it's not just "/"
+    let path = url.path().to_string();
+    if path.len() > 1 {
+        if let Some(normalized_path) = path.strip_suffix('/') {
+            url.set_path(normalized_path);
+        }
+    }
+
+    // Normalize query parameters by sorting them. We sort the *raw*
+    // `key=value` tokens without decoding, so this stays a lossless
+    // reordering: a bare `?foo` is not rewritten to `?foo=`, and a
+    // literal '+' is not turned into '%20' (query_pairs() decodes
+    // both, which silently mutates the URL the archive recorded).
+    let sorted_query: Option<String> = url.query().map(|query| {
+        let mut pairs: Vec<&str> = query.split('&').filter(|s| !s.is_empty()).collect();
+        pairs.sort_unstable();
+        pairs.join("&")
+    });
+    if let Some(query) = sorted_query {
+        url.set_query(None);
+        if !query.is_empty() {
+            url.set_query(Some(&query));
+        }
+    }
+    url.to_string()
+}
+
+/// Split a target such as `example.com:8080` or `[::1]:8443` into its host and
+/// explicit port. Targets without a (numeric) port come back unchanged.
+pub fn split_host_port(target: &str) -> (&str, Option<u16>) {
+    if target.starts_with('[') {
+        // Bracketed IPv6: the port, if any, follows the closing bracket
+        if let Some(end) = target.find(']') {
+            let (host, rest) = target.split_at(end + 1);
+            if let Some(port) = rest.strip_prefix(':').and_then(|p| p.parse().ok()) {
+                return (host, Some(port));
+            }
+        }
+        return (target, None);
+    }
+    match target.rsplit_once(':') {
+        // A bare IPv6 address has several colons and no port
+        Some((host, port)) if !host.contains(':') => match port.parse() {
+            Ok(port) => (host, Some(port)),
+            Err(_) => (target, None),
+        },
+        _ => (target, None),
+    }
+}
+
+/// Validate a normalized target (`host` or `host:port`) and return the form
+/// every provider can splice into its query as-is: internationalized names
+/// are converted to punycode and percent-escapes decoded, so only
+/// `[a-z0-9._-]` (or an IP literal) reaches CDX patterns and API paths.
+///
+/// Hosts that no provider could ever match (spaces, empty labels, labels
+/// over 63 bytes, leading/trailing hyphens, wildcards) are rejected with a
+/// message explaining what is wrong rather than producing an empty result.
+pub fn encode_query_host(target: &str) -> Result<String> {
+    let (host, port) = split_host_port(target);
+    if host.starts_with("*.") {
+        bail!("wildcards aren't supported; pass the parent domain and use --subs");
+    }
+    let parsed = url::Host::parse(host).map_err(|e| match e {
+        url::ParseError::IdnaError => {
+            anyhow!("contains characters that aren't valid in a hostname")
+        }
+        url::ParseError::InvalidIpv6Address => anyhow!("invalid IPv6 address"),
+        e => anyhow!("{e}"),
+    })?;
+    if let url::Host::Domain(domain) = &parsed {
+        if domain.len() > 253 {
+            bail!("hostname is longer than 253 characters");
+        }
+        for label in domain.split('.') {
+            if label.is_empty() {
+                bail!("hostname has an empty label (consecutive or leading dots)");
+            }
+            if label.len() > 63 {
+                bail!("label {label:?} is longer than 63 characters");
+            }
+            if label.starts_with('-') || label.ends_with('-') {
+                bail!("label {label:?} starts or ends with a hyphen");
+            }
+            // Underscores aren't valid in hostnames but show up in real DNS
+            // names (`_dmarc`, legacy hosts) and are safe in every query
+            if let Some(c) = label
+                .chars()
+                .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+            {
+                bail!("contains {c:?}, which isn't valid in a hostname");
+            }
+        }
+    }
+    Ok(match port {
+        Some(port) => format!("{parsed}:{port}"),
+        None => parsed.to_string(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_encode_query_host() {
+        assert_eq!(encode_query_host("example.com").unwrap(), "example.com");
+        assert_eq!(
+            encode_query_host("my_host.example.com").unwrap(),
+            "my_host.example.com"
+        );
+        assert_eq!(
+            encode_query_host("bücher.de:8080").unwrap(),
+            "xn--bcher-kva.de:8080"
+        );
+        assert_eq!(encode_query_host("[::1]:8443").unwrap(), "[::1]:8443");
+        assert_eq!(encode_query_host("10.0.0.1").unwrap(), "10.0.0.1");
+
+        for bad in [
+            "a b.com",
+            "a..b.com",
+            "-bad.com",
+            "*.example.com",
+            "exa!mple.com",
+            "",
+        ] {
+            assert!(
+                encode_query_host(bad).is_err(),
+                "{bad:?} should be rejected"
+            );
+        }
+        let long_label = format!("{}.com", "a".repeat(64));
+        assert!(encode_query_host(&long_label).is_err());
+    }
+
     #[test]
     fn test_url_transformer_merge_endpoints() {
         let mut transformer = UrlTransformer::new();
@@ -302,6 +686,108 @@ mod tests {
         assert!(transformed.contains(&"param2=value2".to_string()));
     }
 
+    #[test]
+    fn test_url_transformer_show_only_param_keys() {
+        let urls = vec![
+            "https://example.com/api?id=1&q=a&id=2".to_string(),
+            "https://example.com/search?q=b&page=2".to_string(),
+            "https://other.com/x?filter%5Bname%5D=c&q=".to_string(),
+            "https://example.com/plain".to_string(),
+            "not a url?skip=1".to_string(),
+        ];
+
+        let mut transformer = UrlTransformer::new();
+        transformer
+            .with_show_only_param_keys(true)
+            .with_param_key_counts(true);
+        assert_eq!(
+            transformer.transform(vec!["https://example.com/?a%09b=1".to_string()]),
+            vec!["a%09b\t1"]
+        );
+
+        let mut transformer = UrlTransformer::new();
+        transformer.with_show_only_param_keys(true);
+        assert_eq!(
+            transformer.transform(urls.clone()),
+            vec!["filter[name]", "id", "page", "q"]
+        );
+
+        transformer.with_param_key_counts(true);
+        assert_eq!(
+            transformer.transform(urls),
+            vec!["q\t3", "filter[name]\t1", "id\t1", "page\t1"]
+        );
+    }
+
+    #[test]
+    fn test_url_transformer_fuzz_placeholders() {
+        let urls = vec![
+            "https://example.com/search?q=shoes&page=2".to_string(),
+            "https://example.com/search?page=3&q=hats".to_string(),
+            "https://example.com/search?q=hats&page=9#top".to_string(),
+            "https://example.com/item?flag&id=".to_string(),
+            "https://example.com/about".to_string(),
+        ];
+
+        let mut transformer = UrlTransformer::new();
+        transformer.with_fuzz_params(true);
+        assert_eq!(
+            transformer.transform(urls.clone()),
+            vec![
+                "https://example.com/search?q=FUZZ&page=FUZZ",
+                "https://example.com/search?page=FUZZ&q=FUZZ",
+                "https://example.com/item?flag=FUZZ&id=FUZZ",
+            ]
+        );
+
+        let mut transformer = UrlTransformer::new();
+        transformer.with_fuzz_path_last(true);
+        assert_eq!(
+            transformer.transform(vec![
+                "https://example.com/api/v1/users?id=1".to_string(),
+                "https://example.com/api/v1/orders".to_string(),
+                "https://example.com/admin/".to_string(),
+                "https://example.com".to_string(),
+            ]),
+            vec![
+                "https://example.com/api/v1/FUZZ?id=1",
+                "https://example.com/api/v1/FUZZ",
+                "https://example.com/admin/FUZZ",
+                "https://example.com/FUZZ",
+            ]
+        );
+
+        transformer.with_fuzz_params(true);
+        assert_eq!(
+            transformer.transform(urls),
+            vec![
+                "https://example.com/FUZZ?q=FUZZ&page=FUZZ",
+                "https://example.com/FUZZ?page=FUZZ&q=FUZZ",
+                "https://example.com/FUZZ?flag=FUZZ&id=FUZZ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_transformer_replace_values() {
+        let mut transformer = UrlTransformer::new();
+        transformer.with_replace_values(Some("\"><x a=b&c#".to_string()));
+        assert_eq!(
+            transformer.transform(vec![
+                "https://example.com/search?q=shoes&page=2".to_string(),
+                "https://example.com/search?q=hats&page=3".to_string(),
+                "https://example.com/about".to_string(),
+            ]),
+            vec!["https://example.com/search?q=%22%3E%3Cx%20a%3Db%26c%23&page=%22%3E%3Cx%20a%3Db%26c%23"]
+        );
+
+        transformer.with_replace_values(Some("%27canary".to_string()));
+        assert_eq!(
+            transformer.transform(vec!["https://example.com/?id=1".to_string()]),
+            vec!["https://example.com/?id=%27canary"]
+        );
+    }
+
     #[test]
     fn test_url_transformer_normalize_query_params() {
         let mut transformer = UrlTransformer::new();
@@ -588,4 +1074,91 @@ mod tests {
         assert!(transformed.contains(&"://start-with-colon".to_string()));
         assert!(transformed.contains(&"".to_string()));
     }
+
+    #[test]
+    fn test_url_transformer_ignore_port() {
+        let mut transformer = UrlTransformer::new();
+        transformer.with_ignore_port(true);
+
+        let urls = vec![
+            "https://example.com:8443/admin".to_string(),
+            "https://example.com/admin".to_string(),
+            "http://example.com:8080/?q=1".to_string(),
+            "not a url".to_string(),
+        ];
+
+        let transformed = transformer.transform(urls);
+        assert_eq!(
+            transformed,
+            vec![
+                "https://example.com/admin".to_string(),
+                "http://example.com/?q=1".to_string(),
+                "not a url".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_transformer_strip_params() {
+        let mut transformer = UrlTransformer::new();
+        transformer.with_strip_params(TRACKING_PARAMS.iter().map(|p| p.to_string()).collect());
+
+        let urls = vec![
+            "https://example.com/a?id=1&utm_source=x&UTM_Medium=y".to_string(),
+            "https://example.com/a?fbclid=abc&id=1".to_string(),
+            "https://example.com/b?gclid=1".to_string(),
+            "https://example.com/c?q=a+b&flag&_ga=1".to_string(),
+            "https://example.com/d?utm=keep".to_string(),
+            "not a url?utm_source=x".to_string(),
+        ];
+
+        let transformed = transformer.transform(urls);
+        assert_eq!(
+            transformed,
+            vec![
+                "https://example.com/a?id=1".to_string(),
+                "https://example.com/b".to_string(),
+                "https://example.com/c?q=a+b&flag".to_string(),
+                "https://example.com/d?utm=keep".to_string(),
+                "not a url?utm_source=x".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_transformer_dedup_similar() {
+        let mut transformer = UrlTransformer::new();
+        transformer
+            .with_strip_params(vec!["utm_*".to_string()])
+            .with_dedup_similar(true);
+
+        let urls = vec![
+            "https://example.com/item/1?utm_source=a".to_string(),
+            "https://example.com/item/2".to_string(),
+            "https://example.com/item/2/edit".to_string(),
+        ];
+        assert_eq!(
+            transformer.transform(urls),
+            vec![
+                "https://example.com/item/1".to_string(),
+                "https://example.com/item/2/edit".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("example.com"), ("example.com", None));
+        assert_eq!(
+            split_host_port("example.com:8080"),
+            ("example.com", Some(8080))
+        );
+        assert_eq!(split_host_port("[::1]:8443"), ("[::1]", Some(8443)));
+        assert_eq!(split_host_port("[::1]"), ("[::1]", None));
+        assert_eq!(split_host_port("::1"), ("::1", None));
+        assert_eq!(
+            split_host_port("example.com:http"),
+            ("example.com:http", None)
+        );
+    }
 }
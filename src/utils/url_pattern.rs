@@ -0,0 +1,205 @@
+use std::collections::{HashMap, HashSet};
+use url::Url;
+
+/// Placeholder for a path segment (or its stem, before the extension) that
+/// looks like generated data rather than part of the site's structure.
+fn segment_placeholder(stem: &str) -> Option<&'static str> {
+    if stem.is_empty() {
+        return None;
+    }
+    if stem.bytes().all(|b| b.is_ascii_digit()) {
+        return Some("{int}");
+    }
+    if is_uuid(stem) {
+        return Some("{uuid}");
+    }
+    if stem.len() >= 16 && stem.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Some("{hash}");
+    }
+    if is_date(stem) {
+        return Some("{date}");
+    }
+    None
+}
+
+fn is_uuid(s: &str) -> bool {
+    s.len() == 36
+        && s.bytes().enumerate().all(|(i, b)| match i {
+            8 | 13 | 18 | 23 => b == b'-',
+            _ => b.is_ascii_hexdigit(),
+        })
+}
+
+/// `YYYY-MM-DD`
+fn is_date(s: &str) -> bool {
+    s.len() == 10
+        && s.bytes().enumerate().all(|(i, b)| match i {
+            4 | 7 => b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
+/// The structural pattern of `url`: numeric ids, UUIDs, hashes and dates in
+/// the path become placeholders and query values are dropped, leaving the
+/// sorted parameter names. `/product/123?ref=a` and `/product/456?ref=b`
+/// share `/product/{int}?ref`. Unparseable URLs are their own pattern.
+pub fn url_template(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let mut template = format!("{}://{}", parsed.scheme(), parsed.host_str().unwrap_or(""));
+    if let Some(port) = parsed.port() {
+        template.push_str(&format!(":{port}"));
+    }
+
+    match parsed.path_segments() {
+        Some(segments) => {
+            for segment in segments {
+                template.push('/');
+                let (stem, extension) = match segment.rsplit_once('.') {
+                    Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+                    _ => (segment, None),
+                };
+                match segment_placeholder(stem) {
+                    Some(placeholder) => {
+                        template.push_str(placeholder);
+                        if let Some(extension) = extension {
+                            template.push('.');
+                            template.push_str(extension);
+                        }
+                    }
+                    None => template.push_str(segment),
+                }
+            }
+        }
+        None => template.push_str(parsed.path()),
+    }
+
+    if let Some(query) = parsed.query() {
+        let mut names: Vec<&str> = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.split('=').next().unwrap_or(pair))
+            .collect();
+        names.sort_unstable();
+        names.dedup();
+        if !names.is_empty() {
+            template.push('?');
+            template.push_str(&names.join("&"));
+        }
+    }
+    template
+}
+
+/// Keep the first URL of each [`url_template`] pattern, in input order
+/// (`--dedup-similar`).
+pub fn dedup_similar(urls: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    urls.into_iter()
+        .filter(|url| seen.insert(url_template(url)))
+        .collect()
+}
+
+/// Keep at most `limit` URLs per host and path, in input order
+/// (`--sample-per-path`): a handful of parameter examples per endpoint
+/// instead of every archived variant. Ports count as part of the host;
+/// scheme, query and fragment don't. Unparseable URLs are their own endpoint.
+pub fn sample_per_path(urls: Vec<String>, limit: usize) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    urls.into_iter()
+        .filter(|url| {
+            let endpoint = match Url::parse(url) {
+                Ok(parsed) => match parsed.port() {
+                    Some(port) => format!(
+                        "{}:{port}{}",
+                        parsed.host_str().unwrap_or(""),
+                        parsed.path()
+                    ),
+                    None => format!("{}{}", parsed.host_str().unwrap_or(""), parsed.path()),
+                },
+                Err(_) => url.clone(),
+            };
+            let seen = counts.entry(endpoint).or_default();
+            *seen += 1;
+            *seen <= limit
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_template() {
+        assert_eq!(
+            url_template("https://example.com/product/123?ref=a&id=9&ref=b"),
+            "https://example.com/product/{int}?id&ref"
+        );
+        assert_eq!(
+            url_template(
+                "https://example.com:8443/u/3f2504e0-4f89-11d3-9a0c-0305e82c3301/avatar.png"
+            ),
+            "https://example.com:8443/u/{uuid}/avatar.png"
+        );
+        assert_eq!(
+            url_template("https://example.com/static/d41d8cd98f00b204e9800998ecf8427e.js"),
+            "https://example.com/static/{hash}.js"
+        );
+        assert_eq!(
+            url_template("https://example.com/news/2021-03-04/launch.html"),
+            "https://example.com/news/{date}/launch.html"
+        );
+        // Words, versions and short hex-looking names stay
+        assert_eq!(
+            url_template("https://example.com/api/v2/cafe/.env"),
+            "https://example.com/api/v2/cafe/.env"
+        );
+        assert_eq!(url_template("not a url"), "not a url");
+    }
+
+    #[test]
+    fn test_dedup_similar_keeps_first_per_pattern() {
+        let urls = vec![
+            "https://example.com/product/123".to_string(),
+            "https://example.com/product/456".to_string(),
+            "https://example.com/product/456/reviews".to_string(),
+            "https://example.com/search?q=shoes".to_string(),
+            "https://example.com/search?q=hats".to_string(),
+            "https://example.com/search?q=hats&page=2".to_string(),
+            "https://example.com/product/new".to_string(),
+        ];
+        assert_eq!(
+            dedup_similar(urls),
+            vec![
+                "https://example.com/product/123".to_string(),
+                "https://example.com/product/456/reviews".to_string(),
+                "https://example.com/search?q=shoes".to_string(),
+                "https://example.com/search?q=hats&page=2".to_string(),
+                "https://example.com/product/new".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sample_per_path() {
+        let urls = vec![
+            "https://example.com/search?q=a".to_string(),
+            "http://example.com/search?q=b".to_string(),
+            "https://example.com/search?q=c&page=2".to_string(),
+            "https://example.com:8443/search?q=d".to_string(),
+            "https://api.example.com/search?q=e".to_string(),
+            "https://example.com/search/?q=f".to_string(),
+        ];
+        assert_eq!(
+            sample_per_path(urls, 2),
+            vec![
+                "https://example.com/search?q=a".to_string(),
+                "http://example.com/search?q=b".to_string(),
+                "https://example.com:8443/search?q=d".to_string(),
+                "https://api.example.com/search?q=e".to_string(),
+                "https://example.com/search/?q=f".to_string(),
+            ]
+        );
+    }
+}